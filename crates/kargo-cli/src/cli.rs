@@ -9,17 +9,38 @@ use clap::{Parser, Subcommand};
 #[command(
     name = "kargo",
     version,
+    arg_required_else_help = true,
     about = "A Cargo-inspired build tool for Kotlin",
     long_about = "Kargo is a fast, modern build and dependency management tool for Kotlin \
                   with first-class support for Kotlin Multiplatform and Compose Multiplatform."
 )]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
 
-    /// Enable verbose output
-    #[arg(short, long, global = true)]
-    pub verbose: bool,
+    /// List built-in commands and installed plugins (`kargo-<cmd>` on PATH)
+    #[arg(long)]
+    pub list: bool,
+
+    /// Use verbose output (-vv for debug logging)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Print errors only: no status lines, warnings or progress
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// How errors and compiler diagnostics are printed: human, short (one
+    /// line each, for tools that parse stderr)
+    #[arg(long, global = true, default_value = "human", value_parser = ["human", "short"])]
+    pub error_format: String,
+}
+
+impl Cli {
+    /// Output policy selected by `-q`/`-v`/`-vv`.
+    pub fn verbosity(&self) -> kargo_util::progress::Verbosity {
+        kargo_util::progress::Verbosity::from_flags(self.quiet, self.verbose)
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -28,16 +49,25 @@ pub enum Command {
     New {
         /// Project name
         name: String,
-        /// Project template: jvm, kmp, cmp, android, lib
+        /// Project template: jvm, lib, kmp, kmp-lib, cmp, compose-desktop, android, ktor
         #[arg(short, long, default_value = "jvm")]
         template: String,
+        /// Initialize a new VCS repository: git, none
+        #[arg(long, default_value = "git")]
+        vcs: String,
+        /// Create an initial commit with the scaffolded files
+        #[arg(long)]
+        commit: bool,
     },
 
     /// Initialize Kargo in an existing directory
     Init {
-        /// Project template: jvm, kmp, cmp, android, lib
+        /// Project template: jvm, lib, kmp, kmp-lib, cmp, compose-desktop, android, ktor
         #[arg(short, long, default_value = "jvm")]
         template: String,
+        /// Initialize a new VCS repository: git, none
+        #[arg(long, default_value = "git")]
+        vcs: String,
     },
 
     /// Build the project
@@ -46,8 +76,12 @@ pub enum Command {
         #[arg(short, long)]
         target: Option<String>,
         /// Build profile
-        #[arg(short, long)]
+        #[arg(long)]
         profile: Option<String>,
+        /// Build only this workspace member (package name or directory);
+        /// may be repeated
+        #[arg(short, long = "package", value_name = "MEMBER")]
+        packages: Vec<String>,
         /// Build in release mode
         #[arg(long)]
         release: bool,
@@ -66,6 +100,25 @@ pub enum Command {
         /// Print build timings
         #[arg(long)]
         timings: bool,
+        /// Fail if the toolchain or JDK differs from the one in Kargo.lock
+        #[arg(long)]
+        locked: bool,
+        /// Fail instead of warning when a [budgets] limit is exceeded
+        #[arg(long)]
+        enforce_budgets: bool,
+        /// Split compilation by package and check it against a monolithic build
+        #[arg(long)]
+        verify_split: bool,
+        /// Output format: human, json-diagnostics (NDJSON events on stdout)
+        #[arg(long, default_value = "human")]
+        message_format: String,
+        /// Write a JSON description of the build outputs to FILE ('-' for stdout)
+        #[arg(long, value_name = "FILE")]
+        out_json: Option<std::path::PathBuf>,
+        /// Build only the workspace members changed since the git revision
+        /// REF, and the members depending on them
+        #[arg(long, value_name = "REF", conflicts_with_all = ["out_json", "packages"])]
+        affected_since: Option<String>,
     },
 
     /// Build and run the project
@@ -76,8 +129,21 @@ pub enum Command {
         /// Build variant
         #[arg(long)]
         variant: Option<String>,
-        /// Arguments to pass to the program
-        #[arg(last = true)]
+        /// Replace the kargo process with the program (Unix only)
+        #[arg(long)]
+        exec: bool,
+        /// Start the program's JVM suspended, waiting for a JDWP debugger
+        /// on PORT (5005 if omitted)
+        #[arg(
+            long,
+            value_name = "PORT",
+            num_args = 0..=1,
+            default_missing_value = "5005",
+            require_equals = true
+        )]
+        debug: Option<u16>,
+        /// Arguments to pass to the program, after `--`
+        #[arg(last = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
 
@@ -98,12 +164,46 @@ pub enum Command {
         /// Run tests in parallel
         #[arg(long)]
         parallel: bool,
+        /// Run the integration tests in src/integrationTest instead of the unit tests
+        #[arg(long)]
+        integration: bool,
+        /// Run the Android instrumentation tests on a device or emulator
+        /// (the only connected one unless a serial is given)
+        #[arg(
+            long,
+            value_name = "SERIAL",
+            num_args = 0..=1,
+            default_missing_value = "",
+            conflicts_with = "integration"
+        )]
+        device: Option<String>,
         /// Enable code coverage
         #[arg(long)]
         coverage: bool,
         /// Report formats (e.g. junit,html)
         #[arg(long)]
         report: Option<String>,
+        /// Environment variable for the test JVM (KEY=VALUE, repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// System property for the test JVM (-Dkey=value, repeatable)
+        #[arg(short = 'D', value_name = "KEY=VALUE")]
+        system_properties: Vec<String>,
+        /// Test only the workspace members changed since the git revision
+        /// REF, and the members depending on them
+        #[arg(long, value_name = "REF")]
+        affected_since: Option<String>,
+        /// Run only the test classes of shard INDEX of TOTAL (e.g. 2/5), for
+        /// splitting a suite across CI jobs
+        #[arg(long, value_name = "INDEX/TOTAL", conflicts_with = "device")]
+        shard: Option<String>,
+        /// Print the captured output of passed tests too
+        #[arg(long)]
+        show_output: bool,
+        /// Capture test output into the JUnit XML report (yes), or let tests
+        /// write to the console as they run (no)
+        #[arg(long, default_value = "yes", value_parser = ["yes", "no"])]
+        capture: String,
     },
 
     /// Type-check without compiling
@@ -111,6 +211,32 @@ pub enum Command {
         /// Build variant
         #[arg(long)]
         variant: Option<String>,
+        /// Output format: human, json-diagnostics (NDJSON events on stdout)
+        #[arg(long, default_value = "human")]
+        message_format: String,
+        /// Also validate the manifest, audit dependencies, apply the license
+        /// policy and check formatting; the exit code has one bit per failed
+        /// category
+        #[arg(long, conflicts_with = "message_format")]
+        all: bool,
+    },
+
+    /// Run the [generate] code generators whose inputs changed
+    Generate {
+        /// Run only this generator
+        name: Option<String>,
+        /// Build target whose generated directory receives the output
+        #[arg(short, long)]
+        target: Option<String>,
+        /// Build profile
+        #[arg(short, long)]
+        profile: Option<String>,
+        /// Use the release profile
+        #[arg(long)]
+        release: bool,
+        /// Run even when the inputs are unchanged
+        #[arg(long)]
+        force: bool,
     },
 
     /// Remove build artifacts
@@ -120,6 +246,22 @@ pub enum Command {
         variant: Option<String>,
     },
 
+    /// Report stale build outputs and unreferenced resources
+    Unused {
+        /// Build target to inspect
+        #[arg(short, long)]
+        target: Option<String>,
+        /// Build profile
+        #[arg(short, long)]
+        profile: Option<String>,
+        /// Use the release profile
+        #[arg(long)]
+        release: bool,
+        /// Delete stale packaged resources and generated outputs
+        #[arg(long)]
+        delete: bool,
+    },
+
     /// Add a dependency
     Add {
         /// Dependency coordinate (group:artifact:version)
@@ -135,6 +277,21 @@ pub enum Command {
         flavor: Option<String>,
     },
 
+    /// Search Maven Central for artifacts
+    Search {
+        /// Search terms, e.g. `ktor-client` or `g:io.ktor`
+        query: String,
+        /// Maximum number of results
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output format: human, json
+        #[arg(long, default_value = "human")]
+        format: String,
+        /// Add the chosen result to Kargo.toml
+        #[arg(long, conflicts_with = "format")]
+        add: bool,
+    },
+
     /// Remove a dependency
     #[command(alias = "rm")]
     Remove {
@@ -162,6 +319,12 @@ pub enum Command {
         /// Show what would be updated without changing files
         #[arg(long)]
         dry_run: bool,
+        /// Output format: human, json (proposed updates on stdout for bots)
+        #[arg(long, default_value = "human")]
+        format: String,
+        /// Pick the updates to apply from a checklist
+        #[arg(short, long, conflicts_with = "format")]
+        interactive: bool,
     },
 
     /// Download dependencies without building
@@ -169,10 +332,29 @@ pub enum Command {
         /// Re-verify checksums of cached artifacts against the lockfile
         #[arg(long)]
         verify: bool,
+        /// Also provision the Kotlin compiler, JDK, SDKs, and build tools for every declared target
+        #[arg(long)]
+        all_targets: bool,
+        /// Also provision tools used only by some profiles (e.g. R8)
+        #[arg(long)]
+        all_profiles: bool,
+        /// Print request counts, bytes, latency and failures per repository
+        #[arg(long)]
+        stats: bool,
     },
 
     /// Regenerate the lockfile
-    Lock,
+    Lock {
+        /// Print each resolution decision instead of writing Kargo.lock
+        #[arg(long)]
+        explain: bool,
+        /// Export Kargo.lock instead of re-resolving: gradle-verification, csv, purl
+        #[arg(long, value_name = "FORMAT", conflicts_with = "explain")]
+        export: Option<String>,
+        /// Write the export to this file instead of stdout
+        #[arg(short, long, requires = "export")]
+        output: Option<std::path::PathBuf>,
+    },
 
     /// Print the dependency tree
     Tree {
@@ -194,6 +376,25 @@ pub enum Command {
         /// Show dependency licenses
         #[arg(long)]
         licenses: bool,
+        /// Show optional dependencies that can be opted in to
+        #[arg(long)]
+        optional: bool,
+    },
+
+    /// Analyze the running application
+    Analyze {
+        #[command(subcommand)]
+        action: AnalyzeAction,
+    },
+
+    /// Show how much each dependency adds to the runtime classpath
+    Bloat {
+        /// Attribute sizes to the direct dependency that pulls each artifact in
+        #[arg(long)]
+        tree: bool,
+        /// Only count dependencies of this target
+        #[arg(short, long)]
+        target: Option<String>,
     },
 
     /// Show outdated dependencies
@@ -208,6 +409,18 @@ pub enum Command {
         /// Minimum severity to fail on: low, moderate, high, critical
         #[arg(long)]
         fail_on: Option<String>,
+        /// Submit Kargo.lock to the GitHub dependency graph instead of scanning
+        #[arg(long, conflicts_with_all = ["fail_on", "format"])]
+        submit_github: bool,
+        /// Output format: human, sarif (SARIF 2.1.0 on stdout for code scanning)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+
+    /// Manage pinned digests of downloaded tools (kargo.trust)
+    Trust {
+        #[command(subcommand)]
+        action: TrustAction,
     },
 
     /// Run the linter
@@ -234,6 +447,15 @@ pub enum Command {
         open: bool,
     },
 
+    /// Open the API documentation of a dependency
+    Docs {
+        /// Artifact name, group:artifact, or group:artifact:version
+        dep: String,
+        /// Serve the cached javadoc JAR locally instead of opening the website
+        #[arg(long)]
+        offline: bool,
+    },
+
     /// Run benchmarks
     Bench {
         /// Compare against a baseline
@@ -246,13 +468,34 @@ pub enum Command {
         /// Only build, don't run
         #[arg(long)]
         build_only: bool,
+        /// Interactive dashboard with diagnostics, test results and logs
+        #[arg(long)]
+        ui: bool,
     },
 
     /// Publish to a Maven repository
-    Publish,
+    Publish {
+        #[command(subcommand)]
+        action: Option<PublishAction>,
+        /// Publish to this [repositories] entry instead of Maven Central
+        #[arg(long)]
+        repository: Option<String>,
+        /// Build, sign and list the files without uploading them
+        #[arg(long)]
+        dry_run: bool,
+    },
 
     /// Create a distributable package
     Package {
+        /// Build target (e.g., jvm, android)
+        #[arg(short, long)]
+        target: Option<String>,
+        /// Also write <name>-<version>-sources.jar from the main source sets
+        #[arg(long)]
+        sources: bool,
+        /// Also write <name>-<version>-javadoc.jar from Dokka's HTML output
+        #[arg(long)]
+        javadoc: bool,
         /// Build a Docker image
         #[arg(long)]
         docker: bool,
@@ -261,9 +504,37 @@ pub enum Command {
         ios_universal: bool,
     },
 
+    /// Cut a release from the Unreleased section of CHANGELOG.md
+    Release {
+        /// major, minor, patch, or an explicit version
+        level: String,
+        /// Show the release plan and notes without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Publish to this [repositories] entry after building
+        #[arg(long, value_name = "REPOSITORY")]
+        publish: Option<String>,
+        /// Push the release commit and tag to origin
+        #[arg(long)]
+        push: bool,
+        /// Create a GitHub Release with the JAR attached (implies --push)
+        #[arg(long)]
+        github: bool,
+        /// Allow uncommitted changes in the working tree
+        #[arg(long)]
+        allow_dirty: bool,
+    },
+
     /// Launch Kotlin REPL
     Repl,
 
+    /// Run a task pipeline from [tasks], after the tasks it depends on
+    #[command(alias = "run-task")]
+    Task {
+        /// Task to run (lists the tasks when omitted)
+        name: Option<String>,
+    },
+
     /// Run a Kotlin script
     Script {
         /// Script file path
@@ -277,6 +548,13 @@ pub enum Command {
         format: String,
     },
 
+    /// Generate `kargow` wrapper scripts pinned to a Kargo version
+    Wrapper {
+        /// Kargo version to pin (defaults to the running version)
+        #[arg(long)]
+        kargo_version: Option<String>,
+    },
+
     /// Generate shell completions
     Completions {
         /// Shell type: bash, zsh, fish, powershell
@@ -307,6 +585,12 @@ pub enum Command {
         action: FlavorAction,
     },
 
+    /// Resolve and run JVM tools published to Maven
+    Tool {
+        #[command(subcommand)]
+        action: ToolAction,
+    },
+
     /// Manage Kotlin toolchains
     Toolchain {
         #[command(subcommand)]
@@ -320,6 +604,48 @@ pub enum Command {
         action: SelfAction,
     },
 
+    /// Dump or check the public ABI of a library (api/<name>.api)
+    Api {
+        #[command(subcommand)]
+        action: ApiAction,
+    },
+
+    /// Build Kotlin frameworks for an Xcode project
+    Xcode {
+        #[command(subcommand)]
+        action: XcodeAction,
+    },
+
+    /// Generate and publish a CocoaPods podspec for the XCFramework
+    Pod {
+        #[command(subcommand)]
+        action: PodAction,
+    },
+
+    /// Generate a Swift package for the XCFramework
+    Spm {
+        #[command(subcommand)]
+        action: SpmAction,
+    },
+
+    /// Android-specific tasks
+    Android {
+        #[command(subcommand)]
+        action: AndroidAction,
+    },
+
+    /// Manage Android emulators and iOS simulators for device tests
+    Device {
+        #[command(subcommand)]
+        action: DeviceAction,
+    },
+
+    /// Inspect dependencies across workspace members
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+
     /// Manage build cache
     Cache {
         #[command(subcommand)]
@@ -341,6 +667,185 @@ pub enum Command {
 
     /// Start Language Server Protocol server
     Lsp,
+
+    /// Generate IntelliJ IDEA project files (.iml and .idea/)
+    Idea {
+        /// Generate VS Code tasks, launch, and settings files instead
+        #[arg(long)]
+        vscode: bool,
+        /// Regenerate .idea/modules.xml, misc.xml and kotlinc.xml, replacing
+        /// your changes to them
+        #[arg(long, conflicts_with = "vscode")]
+        force: bool,
+    },
+
+    /// Serve resolve/build/test/metadata over JSON-RPC for editor tooling
+    Daemon {
+        /// Unix socket path (defaults to .kargo/daemon.sock)
+        #[arg(long)]
+        socket: Option<std::path::PathBuf>,
+    },
+
+    /// Run `kargo-<cmd>` from PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ApiAction {
+    /// Write the current public ABI to api/<name>.api
+    Dump,
+    /// Fail if the public ABI differs from api/<name>.api
+    Check,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum XcodeAction {
+    /// Write Kargo.xcconfig and the Run Script build phase for an Xcode project
+    Integrate {
+        /// Directory containing the .xcodeproj
+        #[arg(long, default_value = ".")]
+        xcode_dir: std::path::PathBuf,
+    },
+    /// Build the frameworks Xcode needs (run from the build phase script)
+    Build,
+    /// Build every Apple target and bundle the frameworks into an XCFramework
+    Xcframework {
+        /// Build profile
+        #[arg(long)]
+        profile: Option<String>,
+        /// Use the release profile
+        #[arg(long)]
+        release: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PodAction {
+    /// Write <Name>.podspec for the XCFramework
+    Spec {
+        /// URL of the released <Name>.xcframework.zip (default: vendor the local build)
+        #[arg(long)]
+        url: Option<String>,
+        /// Profile of the local XCFramework to vendor
+        #[arg(long, default_value = "release")]
+        profile: String,
+    },
+    /// Write the podspec for a released zip and push it to a spec repository
+    Publish {
+        /// Spec repository name, as added with `pod repo add`
+        #[arg(long)]
+        repo: String,
+        /// URL of the released <Name>.xcframework.zip
+        #[arg(long)]
+        url: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SpmAction {
+    /// Write Package.swift with a binary target for the XCFramework
+    Package {
+        /// URL of the released <Name>.xcframework.zip (default: the local build)
+        #[arg(long)]
+        url: Option<String>,
+        /// Upload the zip to --url with an HTTP PUT first
+        #[arg(long, requires = "url")]
+        upload: bool,
+        /// Profile of the XCFramework
+        #[arg(long, default_value = "release")]
+        profile: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AndroidAction {
+    /// Record a baseline profile by running the device tests and write it
+    /// to src/main/baseline-prof.txt
+    BaselineProfile {
+        /// Serial of the device to record on (default: the only connected one)
+        #[arg(long)]
+        device: Option<String>,
+        /// Only run the tests whose name matches this regex
+        #[arg(short, long)]
+        filter: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DeviceAction {
+    /// List AVDs, attached Android devices and iOS simulators
+    List,
+    /// Boot an AVD or simulator and wait until it is ready
+    Start {
+        /// AVD name, or simulator name or UDID
+        name: String,
+        /// System image to create the AVD from if it does not exist
+        /// (e.g. "system-images;android-34;google_apis;x86_64")
+        #[arg(long)]
+        image: Option<String>,
+        /// Show the emulator window instead of running headless
+        #[arg(long)]
+        window: bool,
+        /// Wipe the emulator's user data before booting
+        #[arg(long)]
+        wipe: bool,
+        /// Seconds to wait for the device to finish booting
+        #[arg(long, default_value_t = 300)]
+        timeout: u64,
+        /// Command to run once the device is ready; the device is stopped
+        /// when it exits
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Shut down an emulator or simulator
+    Stop {
+        /// AVD or simulator name, adb serial, or UDID
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+        /// Stop every device started with `kargo device start`
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceAction {
+    /// List the dependencies declared by workspace members and their versions
+    Deps {
+        /// Only show dependencies declared at diverging versions, and fail
+        /// if there are any
+        #[arg(long)]
+        inconsistent: bool,
+        /// Hoist diverging dependencies into [workspace.catalog] at their
+        /// highest version
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PublishAction {
+    /// Check that your Central Portal token can publish the package's group
+    VerifyNamespace {
+        /// Verification key from the portal, to check the DNS TXT record
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrustAction {
+    /// Create kargo.trust, pinning tools as they are downloaded
+    Init,
+    /// Download pinned tools again and replace pins that changed
+    Update {
+        /// Only update the pins of this tool (kotlin, jdk, ksp, ...)
+        tool: Option<String>,
+        /// Accept changed digests without asking
+        #[arg(long, short)]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -379,6 +884,50 @@ pub enum FlavorAction {
     Remove { dimension: String, name: String },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum ToolAction {
+    /// Run a tool's main class, resolving it and its dependencies on first use
+    Run {
+        /// Maven coordinate, group:artifact[:version] (latest release if omitted)
+        coordinate: String,
+        /// Main class to run instead of the Main-Class of the tool's JAR
+        #[arg(long = "main", value_name = "CLASS")]
+        main_class: Option<String>,
+        /// Resolve the tool again instead of using the recorded classpath
+        #[arg(long)]
+        refresh: bool,
+        /// Arguments to pass to the tool, after `--`
+        #[arg(last = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Pin a tool for the user and put a launcher for it on PATH
+    Install {
+        /// A known tool (detekt, ktlint) or a coordinate, group:artifact[:version]
+        tool: String,
+        /// Version to pin (latest release if omitted)
+        #[arg(long)]
+        version: Option<String>,
+        /// Name of the launcher (defaults to the tool's name)
+        #[arg(long)]
+        name: Option<String>,
+        /// Main class to run instead of the Main-Class of the tool's JAR
+        #[arg(long = "main", value_name = "CLASS")]
+        main_class: Option<String>,
+    },
+    /// Move installed tools to their latest release
+    Upgrade {
+        /// Installed tool to upgrade (all if omitted)
+        name: Option<String>,
+    },
+    /// List installed tools
+    List,
+    /// Remove an installed tool and its launcher
+    Uninstall {
+        /// Name of the installed tool
+        name: String,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum ToolchainAction {
     /// Download and install a Kotlin version (and optionally a JDK or Android SDK)
@@ -392,8 +941,16 @@ pub enum ToolchainAction {
         #[arg(long, num_args = 0..=1, default_missing_value = "35")]
         android: Option<String>,
     },
-    /// List installed toolchains
-    List,
+    /// List installed toolchains, or the Kotlin versions available to install
+    List {
+        /// List the published Kotlin versions instead, marking installed ones
+        /// and the project's pinned version
+        #[arg(long)]
+        remote: bool,
+        /// Print the remote list as JSON
+        #[arg(long, requires = "remote")]
+        json: bool,
+    },
     /// Remove a cached toolchain, JDK, or Android SDK
     Remove {
         /// Kotlin version to remove (e.g., 2.3.0)
@@ -423,6 +980,21 @@ pub enum SelfAction {
     Info,
     /// Clean global caches
     Clean,
+    /// Move ~/.kargo into the platform's data and cache directories
+    MigrateHome,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AnalyzeAction {
+    /// Run the app and report the classes it loads at startup per dependency
+    Startup {
+        /// Build target
+        #[arg(short, long)]
+        target: Option<String>,
+        /// Arguments to pass to the program, after `--`
+        #[arg(last = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -431,8 +1003,27 @@ pub enum CacheAction {
     Stats,
     /// Clear local build cache
     Clean,
+    /// Re-hash cached dependencies against Kargo.lock
+    Verify {
+        /// Re-download corrupt entries and remove orphaned ones
+        #[arg(long)]
+        repair: bool,
+    },
     /// Push build outputs to remote cache
     Push,
+    /// Write the build-cache entries and dependencies the last build used
+    /// to a .tar.gz, e.g. for a CI cache step
+    Export {
+        /// Archive to write
+        #[arg(default_value = "kargo-cache.tar.gz")]
+        file: std::path::PathBuf,
+    },
+    /// Unpack an archive from `kargo cache export` into the caches
+    Import {
+        /// Archive to read
+        #[arg(default_value = "kargo-cache.tar.gz")]
+        file: std::path::PathBuf,
+    },
     /// Stop the Kotlin compiler daemon
     StopDaemon,
 }
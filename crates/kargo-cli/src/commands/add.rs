@@ -26,13 +26,13 @@ pub async fn exec(dep: &str, dev: bool, target: Option<&str>, flavor: Option<&st
     )?;
 
     if dev {
-        eprintln!("Added {dep} to [dev-dependencies]");
+        kargo_util::progress::note(&format!("Added {dep} to [dev-dependencies]"));
     } else if let Some(t) = target {
-        eprintln!("Added {dep} to [target.{t}.dependencies]");
+        kargo_util::progress::note(&format!("Added {dep} to [target.{t}.dependencies]"));
     } else if let Some(f) = flavor {
-        eprintln!("Added {dep} to [flavor.{f}.dependencies]");
+        kargo_util::progress::note(&format!("Added {dep} to [flavor.{f}.dependencies]"));
     } else {
-        eprintln!("Added {dep} to [dependencies]");
+        kargo_util::progress::note(&format!("Added {dep} to [dependencies]"));
     }
 
     kargo_ops::ops_fetch::fetch(&project_root, false).await?;
@@ -0,0 +1,24 @@
+//! `--affected-since <ref>` for `kargo build` and `kargo test`.
+
+use std::path::{Path, PathBuf};
+
+use miette::Result;
+
+use kargo_ops::ops_affected;
+use kargo_util::progress::{status, status_info};
+
+/// Directories of the workspace members affected by changes since `since`,
+/// in build order, after listing them and why they are affected.
+pub fn members(cwd: &Path, since: &str) -> Result<Vec<PathBuf>> {
+    let (_, members) = ops_affected::affected_since(cwd, since)?;
+    if members.is_empty() {
+        status(
+            "Finished",
+            &format!("no workspace member changed since {since}"),
+        );
+    }
+    for member in &members {
+        status_info("Affected", &format!("{} ({})", member.name, member.reason));
+    }
+    Ok(members.into_iter().map(|m| m.dir).collect())
+}
@@ -0,0 +1,32 @@
+//! Handler for `kargo analyze`.
+
+use miette::Result;
+
+use kargo_ops::ops_analyze::{self, StartupOptions};
+
+use crate::cli::AnalyzeAction;
+
+pub async fn exec(action: AnalyzeAction, verbose: bool) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    match action {
+        AnalyzeAction::Startup { target, args } => {
+            ops_analyze::startup(
+                &project_root,
+                &StartupOptions {
+                    target,
+                    args,
+                    verbose,
+                },
+            )
+            .await
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Handler for `kargo android baseline-profile`.
+
+use miette::Result;
+
+use kargo_ops::ops_test::TestOptions;
+
+use crate::cli::AndroidAction;
+
+pub async fn exec(action: AndroidAction, verbose: bool) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        AndroidAction::BaselineProfile { device, filter } => {
+            let opts = TestOptions {
+                target: Some("android".into()),
+                filter,
+                env: Vec::new(),
+                system_properties: Vec::new(),
+                integration: false,
+                device: Some(device.unwrap_or_default()),
+                no_capture: false,
+                show_output: false,
+                shard: None,
+                verbose,
+            };
+            let path = kargo_ops::ops_baseline_profile::generate(&cwd, &opts).await?;
+            kargo_util::progress::status("Generated", &path.display().to_string());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Handler for `kargo api dump` and `kargo api check`.
+
+use miette::Result;
+
+use crate::cli::ApiAction;
+
+pub async fn exec(action: ApiAction, verbose: bool) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    match action {
+        ApiAction::Dump => kargo_ops::ops_api::dump(&project_root, verbose).await,
+        ApiAction::Check => kargo_ops::ops_api::check(&project_root, verbose).await,
+    }
+}
@@ -0,0 +1,18 @@
+//! Handler for `kargo bloat`.
+
+use miette::Result;
+
+use kargo_ops::ops_bloat::{self, BloatOptions};
+
+pub fn exec(tree: bool, target: Option<String>) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    ops_bloat::bloat(&project_root, &BloatOptions { tree, target })
+}
@@ -2,16 +2,29 @@
 
 use miette::Result;
 
-use kargo_ops::ops_build::{self, BuildOptions};
+use kargo_core::workspace;
+use kargo_ops::ops_build::{self, BuildOptions, BuildOutputs, MessageFormat};
+use kargo_ops::ops_workspace;
 
+use super::affected;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn exec(
     target: Option<&str>,
     profile: Option<&str>,
+    packages: &[String],
     release: bool,
     timings: bool,
     offline: bool,
+    locked: bool,
+    enforce_budgets: bool,
+    verify_split: bool,
+    message_format: &str,
+    out_json: Option<&std::path::Path>,
+    affected_since: Option<&str>,
     verbose: bool,
 ) -> Result<()> {
+    let message_format = MessageFormat::parse(message_format)?;
     let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
 
     let opts = BuildOptions {
@@ -21,13 +34,48 @@ pub async fn exec(
         verbose,
         timings,
         offline,
+        locked,
+        enforce_budgets,
+        verify_split,
+        message_format,
         ..Default::default()
     };
 
+    if let Some(since) = affected_since {
+        for member in affected::members(&cwd, since)? {
+            if !ops_build::build(&member, &opts).await?.success {
+                std::process::exit(kargo_util::errors::exit_code::COMPILE);
+            }
+        }
+        return Ok(());
+    }
+
+    // A virtual workspace root has no package of its own: build every member.
+    if !packages.is_empty() || workspace::is_virtual_root(&cwd) {
+        if out_json.is_some() && packages.len() != 1 {
+            return Err(kargo_util::errors::KargoError::Generic {
+                message: "--out-json describes a single build; select one member with -p".into(),
+            }
+            .into());
+        }
+        let results = ops_workspace::build(&cwd, packages, &opts).await?;
+        if let (Some(path), [result]) = (out_json, results.as_slice()) {
+            BuildOutputs::from_result(result).write(path)?;
+        }
+        if results.iter().any(|r| !r.success) {
+            std::process::exit(kargo_util::errors::exit_code::COMPILE);
+        }
+        return Ok(());
+    }
+
     let result = ops_build::build(&cwd, &opts).await?;
 
+    if let Some(path) = out_json {
+        BuildOutputs::from_result(&result).write(path)?;
+    }
+
     if !result.success {
-        std::process::exit(1);
+        std::process::exit(kargo_util::errors::exit_code::COMPILE);
     }
 
     Ok(())
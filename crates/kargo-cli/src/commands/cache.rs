@@ -8,6 +8,18 @@ pub async fn exec(action: CacheAction) -> Result<()> {
     match action {
         CacheAction::Stats => kargo_ops::ops_cache::stats(),
         CacheAction::Clean => kargo_ops::ops_cache::clean(),
+        CacheAction::Verify { repair } => {
+            let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+            kargo_ops::ops_cache::verify(&cwd, repair).await
+        }
+        CacheAction::Export { file } => {
+            let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+            kargo_ops::ops_cache::export(&cwd, &file).map(|_| ())
+        }
+        CacheAction::Import { file } => {
+            let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+            kargo_ops::ops_cache::import(&cwd, &file).map(|_| ())
+        }
         CacheAction::Push => {
             eprintln!("Remote cache push is not yet implemented.");
             Ok(())
@@ -2,7 +2,18 @@
 
 use miette::Result;
 
-pub async fn exec(verbose: bool) -> Result<()> {
+use kargo_ops::ops_build::MessageFormat;
+
+pub async fn exec(message_format: &str, all: bool, verbose: bool) -> Result<()> {
+    let message_format = MessageFormat::parse(message_format)?;
     let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
-    kargo_ops::ops_check::check(&cwd, verbose).await
+    if all {
+        let report = kargo_ops::ops_check::check_all(&cwd, verbose).await?;
+        let code = report.exit_code();
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+    kargo_ops::ops_check::check(&cwd, verbose, message_format).await
 }
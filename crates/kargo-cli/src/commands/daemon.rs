@@ -0,0 +1,10 @@
+//! Handler for `kargo daemon`.
+
+use std::path::Path;
+
+use miette::Result;
+
+pub async fn exec(socket: Option<&Path>) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+    kargo_ops::ops_daemon::serve(&cwd, socket).await
+}
@@ -0,0 +1,76 @@
+//! Handler for `kargo device list`, `kargo device start`, and
+//! `kargo device stop`.
+
+use std::time::Duration;
+
+use miette::Result;
+
+use kargo_ops::ops_device::{self, StartOptions};
+
+use crate::cli::DeviceAction;
+
+pub fn exec(action: DeviceAction) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        DeviceAction::List => {
+            let devices = ops_device::list(&cwd)?;
+            if devices.is_empty() {
+                println!("No devices found (neither the Android SDK nor Xcode has any).");
+                return Ok(());
+            }
+            let width = devices.iter().map(|d| d.name.len()).max().unwrap_or(0);
+            for device in devices {
+                let mut line = format!(
+                    "{:<10} {:<width$}  {:<9}",
+                    device.kind.label(),
+                    device.name,
+                    device.state
+                );
+                if let Some(runtime) = &device.runtime {
+                    line.push_str(&format!("  {runtime}"));
+                }
+                if let Some(id) = device.id.as_ref().filter(|id| **id != device.name) {
+                    line.push_str(&format!("  {id}"));
+                }
+                println!("{}", line.trim_end());
+            }
+            Ok(())
+        }
+        DeviceAction::Start {
+            name,
+            image,
+            window,
+            wipe,
+            timeout,
+            command,
+        } => {
+            let opts = StartOptions {
+                image,
+                window,
+                wipe,
+                timeout: Duration::from_secs(timeout),
+            };
+            if command.is_empty() {
+                let device = ops_device::start(&cwd, &name, &opts)?;
+                println!("{}", device.id);
+                Ok(())
+            } else {
+                ops_device::run_with_device(&cwd, &name, &opts, &command)
+            }
+        }
+        DeviceAction::Stop { name, all } => {
+            let stopped = ops_device::stop(&cwd, name.as_deref(), all)?;
+            for device in &stopped {
+                kargo_util::progress::status(
+                    "Stopped",
+                    &format!("{} {} ({})", device.kind.label(), device.name, device.id),
+                );
+            }
+            if stopped.is_empty() {
+                println!("No devices started by kargo are running.");
+            }
+            Ok(())
+        }
+    }
+}
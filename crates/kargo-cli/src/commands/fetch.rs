@@ -2,7 +2,9 @@
 
 use miette::Result;
 
-pub async fn exec(verbose: bool, verify: bool) -> Result<()> {
+use kargo_ops::ops_fetch::PrewarmOptions;
+
+pub async fn exec(verbose: bool, verify: bool, prewarm: PrewarmOptions, stats: bool) -> Result<()> {
     let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
 
     if !project_root.join("Kargo.toml").is_file() {
@@ -14,9 +16,17 @@ pub async fn exec(verbose: bool, verify: bool) -> Result<()> {
 
     kargo_ops::ops_fetch::fetch(&project_root, verbose).await?;
 
+    if !prewarm.is_empty() {
+        kargo_ops::ops_fetch::prewarm(&project_root, prewarm).await?;
+    }
+
     if verify {
         kargo_ops::ops_fetch::verify_checksums(&project_root)?;
     }
 
+    if stats {
+        kargo_ops::ops_fetch::print_repository_stats();
+    }
+
     Ok(())
 }
@@ -0,0 +1,43 @@
+//! Handler for `kargo generate`.
+
+use miette::Result;
+
+use kargo_ops::ops_generate::GenerateOptions;
+
+pub async fn exec(
+    name: Option<String>,
+    target: Option<&str>,
+    profile: Option<&str>,
+    release: bool,
+    force: bool,
+    verbose: bool,
+) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    let ctx = kargo_ops::BuildContext::load(&project_root, target, profile, release).await?;
+    if ctx.manifest.generate.is_empty() {
+        kargo_util::progress::status_info("Skipped", "no generators declared in [generate]");
+        return Ok(());
+    }
+    let ran = kargo_ops::ops_generate::run(
+        &ctx,
+        &GenerateOptions {
+            only: name,
+            force,
+            verbose,
+            quiet: false,
+        },
+    )
+    .await?;
+    if ran == 0 {
+        kargo_util::progress::status("Fresh", "all generators are up to date");
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! Handler for `kargo idea` (and `kargo idea --vscode`).
+
+use miette::Result;
+
+pub async fn exec(vscode: bool, force: bool) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !cwd.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    if vscode {
+        let written = kargo_ops::ops_vscode::export(&cwd).await?;
+        kargo_util::progress::status(
+            "Generated",
+            &format!("VS Code workspace files ({} files)", written.len()),
+        );
+    } else {
+        let written = kargo_ops::ops_idea::export(&cwd, force).await?;
+        kargo_util::progress::status(
+            "Generated",
+            &format!("IntelliJ IDEA project ({} files)", written.len()),
+        );
+    }
+    Ok(())
+}
@@ -2,9 +2,11 @@ use miette::Result;
 
 use kargo_core::template::{TemplateContext, TemplateRegistry};
 use kargo_core::DEFAULT_KOTLIN_VERSION;
+use kargo_ops::ops_new::Vcs;
 use kargo_util::errors::KargoError;
 
-pub async fn exec(template: &str) -> Result<()> {
+pub async fn exec(template: &str, vcs: &str) -> Result<()> {
+    let vcs = Vcs::parse(vcs)?;
     let cwd = std::env::current_dir().map_err(KargoError::Io)?;
     let manifest_path = cwd.join("Kargo.toml");
 
@@ -34,7 +36,28 @@ pub async fn exec(template: &str) -> Result<()> {
 
     println!("Initialized Kargo project in {}", cwd.display());
 
+    if let Some(import) = kargo_ops::ops_init::import_gradle_properties(&cwd)? {
+        let mut imported = Vec::new();
+        if !import.jvm_args.is_empty() {
+            imported.push("org.gradle.jvmargs → [test] jvm-args");
+        }
+        if import.code_style.is_some() {
+            imported.push("kotlin.code.style → [format] style");
+        }
+        for line in imported {
+            println!("  Imported {line} from gradle.properties");
+        }
+        if !import.unmapped.is_empty() {
+            println!(
+                "  Not imported from gradle.properties (no Kargo equivalent): {}",
+                import.unmapped.join(", ")
+            );
+        }
+    }
+
     kargo_ops::ops_setup::post_scaffold(&cwd).await;
 
+    kargo_ops::ops_new::init_vcs(&cwd, vcs, false);
+
     Ok(())
 }
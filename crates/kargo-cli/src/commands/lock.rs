@@ -1,8 +1,17 @@
 //! Handler for `kargo lock`.
 
+use std::path::Path;
+
 use miette::Result;
 
-pub async fn exec(verbose: bool) -> Result<()> {
+use kargo_ops::ops_lock::ExportFormat;
+
+pub async fn exec(
+    explain: bool,
+    export: Option<&str>,
+    output: Option<&Path>,
+    verbose: bool,
+) -> Result<()> {
     let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
 
     if !project_root.join("Kargo.toml").is_file() {
@@ -12,5 +21,11 @@ pub async fn exec(verbose: bool) -> Result<()> {
         .into());
     }
 
+    if let Some(format) = export {
+        return kargo_ops::ops_lock::export(&project_root, ExportFormat::parse(format)?, output);
+    }
+    if explain {
+        return kargo_ops::ops_lock::explain(&project_root).await;
+    }
     kargo_ops::ops_lock::lock(&project_root, verbose).await
 }
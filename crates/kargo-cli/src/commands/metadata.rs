@@ -0,0 +1,19 @@
+//! Handler for `kargo metadata`.
+
+use miette::Result;
+
+use kargo_util::errors::KargoError;
+
+pub fn exec(format: &str) -> Result<()> {
+    if format != "json" {
+        return Err(KargoError::Generic {
+            message: format!("Unknown metadata format '{format}'. Available: json"),
+        }
+        .into());
+    }
+    let cwd = std::env::current_dir().map_err(KargoError::Io)?;
+    let project_dir = kargo_util::fs::find_ancestor_with(&cwd, "Kargo.toml").unwrap_or(cwd);
+    let metadata = kargo_ops::ops_metadata::metadata(&project_dir)?;
+    println!("{metadata}");
+    Ok(())
+}
@@ -1,62 +1,178 @@
 //! Command dispatch and handler modules.
 
 mod add;
+mod affected;
+mod analyze;
+mod android;
+mod api;
 mod audit;
+mod bloat;
 mod build;
 mod cache;
 mod check;
 mod clean;
+mod daemon;
+mod device;
+mod docs;
 mod env;
 mod fetch;
+mod generate;
+mod idea;
 mod init;
 mod lock;
+mod metadata;
 mod new;
 mod outdated;
+mod package;
+mod plugin;
+mod pod;
+mod publish;
+mod release;
 mod remove;
 mod run;
+mod search;
 mod self_;
+mod spm;
+mod task;
 mod test_;
+mod tool;
 mod toolchain;
 mod tree;
+mod trust;
+mod unused;
 mod update;
 mod watch;
+mod watch_ui;
+mod workspace;
+mod wrapper;
+mod xcode;
 
+use clap::CommandFactory;
 use miette::Result;
 
-use crate::cli::{Cli, Command};
+use crate::cli::{Cli, Command, PluginAction};
 
 /// Route a parsed CLI invocation to the appropriate command handler.
 pub async fn dispatch(cli: Cli) -> Result<()> {
-    match cli.command {
-        Command::New { name, template } => new::exec(&name, &template).await,
-        Command::Init { template } => init::exec(&template).await,
+    let verbose = cli.verbose > 0;
+    let Some(command) = cli.command else {
+        if cli.list {
+            return plugin::list();
+        }
+        Cli::command()
+            .print_help()
+            .map_err(kargo_util::errors::KargoError::Io)?;
+        return Ok(());
+    };
+    match command {
+        Command::New {
+            name,
+            template,
+            vcs,
+            commit,
+        } => new::exec(&name, &template, &vcs, commit).await,
+        Command::Init { template, vcs } => init::exec(&template, &vcs).await,
         Command::Clean { variant } => clean::exec(variant.as_deref()),
+        Command::Unused {
+            target,
+            profile,
+            release,
+            delete,
+        } => unused::exec(target.as_deref(), profile.as_deref(), release, delete).await,
         Command::Env { reveal } => env::exec(reveal),
+        Command::Tool { action } => tool::exec(action, verbose).await,
         Command::Toolchain { action } => toolchain::exec(action).await,
         Command::SelfCmd { action } => self_::exec(action).await,
         Command::Build {
             target,
             profile,
+            packages,
             release,
             timings,
             offline,
+            locked,
+            enforce_budgets,
+            verify_split,
+            message_format,
+            out_json,
+            affected_since,
             ..
         } => {
             build::exec(
                 target.as_deref(),
                 profile.as_deref(),
+                &packages,
                 release,
                 timings,
                 offline,
-                cli.verbose,
+                locked,
+                enforce_budgets,
+                verify_split,
+                &message_format,
+                out_json.as_deref(),
+                affected_since.as_deref(),
+                verbose,
             )
             .await
         }
-        Command::Run { target, args, .. } => run::exec(target.as_deref(), &args, cli.verbose).await,
-        Command::Test { target, filter, .. } => {
-            test_::exec(target.as_deref(), filter.as_deref(), cli.verbose).await
+        Command::Run {
+            target,
+            args,
+            exec,
+            debug,
+            ..
+        } => run::exec(target.as_deref(), &args, exec, debug, verbose).await,
+        Command::Test {
+            target,
+            filter,
+            env,
+            system_properties,
+            integration,
+            device,
+            affected_since,
+            shard,
+            show_output,
+            capture,
+            ..
+        } => {
+            test_::exec(
+                target.as_deref(),
+                filter.as_deref(),
+                &env,
+                &system_properties,
+                integration,
+                device,
+                affected_since.as_deref(),
+                shard.as_deref(),
+                show_output,
+                capture == "no",
+                verbose,
+            )
+            .await
+        }
+        Command::Check {
+            message_format,
+            all,
+            ..
+        } => check::exec(&message_format, all, verbose).await,
+        Command::Generate {
+            name,
+            target,
+            profile,
+            release,
+            force,
+        } => {
+            generate::exec(
+                name,
+                target.as_deref(),
+                profile.as_deref(),
+                release,
+                force,
+                verbose,
+            )
+            .await
         }
-        Command::Check { .. } => check::exec(cli.verbose).await,
+        Command::Api { action } => api::exec(action, verbose).await,
         Command::Cache { action } => cache::exec(action).await,
         Command::Add {
             dep,
@@ -64,14 +180,36 @@ pub async fn dispatch(cli: Cli) -> Result<()> {
             target,
             flavor,
         } => add::exec(&dep, dev, target.as_deref(), flavor.as_deref()).await,
+        Command::Docs { dep, offline } => docs::exec(dep, offline).await,
+        Command::Search {
+            query,
+            limit,
+            format,
+            add,
+        } => search::exec(query, limit, &format, add).await,
         Command::Remove {
             dep,
             dev,
             target,
             flavor,
         } => remove::exec(&dep, dev, target.as_deref(), flavor.as_deref()).await,
-        Command::Fetch { verify } => fetch::exec(cli.verbose, verify).await,
-        Command::Lock => lock::exec(cli.verbose).await,
+        Command::Fetch {
+            verify,
+            all_targets,
+            all_profiles,
+            stats,
+        } => {
+            let prewarm = kargo_ops::ops_fetch::PrewarmOptions {
+                all_targets,
+                all_profiles,
+            };
+            fetch::exec(verbose, verify, prewarm, stats).await
+        }
+        Command::Lock {
+            explain,
+            export,
+            output,
+        } => lock::exec(explain, export.as_deref(), output.as_deref(), verbose).await,
         Command::Tree {
             depth,
             duplicates,
@@ -79,15 +217,84 @@ pub async fn dispatch(cli: Cli) -> Result<()> {
             why,
             conflicts,
             licenses,
-        } => tree::exec(depth, duplicates, inverted, why, conflicts, licenses).await,
+            optional,
+        } => {
+            tree::exec(
+                depth, duplicates, inverted, why, conflicts, licenses, optional,
+            )
+            .await
+        }
+        Command::Analyze { action } => analyze::exec(action, verbose).await,
+        Command::Bloat { tree, target } => bloat::exec(tree, target),
         Command::Outdated { major } => outdated::exec(major).await,
         Command::Update {
             major,
             dep,
             dry_run,
-        } => update::exec(major, dep, dry_run).await,
-        Command::Audit { fail_on } => audit::exec(fail_on).await,
-        Command::Watch { build_only } => watch::exec(build_only, cli.verbose).await,
+            format,
+            interactive,
+        } => update::exec(major, dep, dry_run, &format, interactive).await,
+        Command::Package {
+            target,
+            sources,
+            javadoc,
+            docker: false,
+            ios_universal: false,
+        } => {
+            package::exec(kargo_ops::ops_package::PackageOptions {
+                target,
+                sources,
+                javadoc,
+                verbose,
+            })
+            .await
+        }
+        Command::Release {
+            level,
+            dry_run,
+            publish,
+            push,
+            github,
+            allow_dirty,
+        } => {
+            release::exec(kargo_ops::ops_release::ReleaseOptions {
+                level,
+                dry_run,
+                publish,
+                push,
+                github,
+                allow_dirty,
+                verbose,
+            })
+            .await
+        }
+        Command::Audit {
+            fail_on,
+            submit_github,
+            format,
+        } => audit::exec(fail_on, submit_github, &format).await,
+        Command::Trust { action } => trust::exec(action).await,
+        Command::Publish {
+            action,
+            repository,
+            dry_run,
+        } => publish::exec(action, repository, dry_run, verbose).await,
+        Command::Watch { build_only, ui } => watch::exec(build_only, ui, verbose).await,
+        Command::Wrapper { kargo_version } => wrapper::exec(kargo_version.as_deref()),
+        Command::Idea { vscode, force } => idea::exec(vscode, force).await,
+        Command::Daemon { socket } => daemon::exec(socket.as_deref()).await,
+        Command::Xcode { action } => xcode::exec(action, verbose).await,
+        Command::Android { action } => android::exec(action, verbose).await,
+        Command::Device { action } => device::exec(action),
+        Command::Workspace { action } => workspace::exec(action),
+        Command::Pod { action } => pod::exec(action),
+        Command::Spm { action } => spm::exec(action).await,
+        Command::Metadata { format } => metadata::exec(&format),
+        Command::Task { name } => task::exec(name.as_deref()),
+        Command::Plugin {
+            action: PluginAction::List,
+        } => plugin::list_plugins(),
+        Command::External(args) => plugin::external(&args),
         _ => Err(kargo_util::errors::KargoError::Generic {
             message: "This command is not yet implemented".to_string(),
         }
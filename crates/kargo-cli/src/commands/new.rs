@@ -3,9 +3,11 @@ use std::path::Path;
 
 use kargo_core::template::{TemplateContext, TemplateRegistry};
 use kargo_core::DEFAULT_KOTLIN_VERSION;
+use kargo_ops::ops_new::Vcs;
 use kargo_util::errors::KargoError;
 
-pub async fn exec(name: &str, template: &str) -> Result<()> {
+pub async fn exec(name: &str, template: &str, vcs: &str, commit: bool) -> Result<()> {
+    let vcs = Vcs::parse(vcs)?;
     let project_dir = Path::new(name);
     if project_dir.exists() {
         return Err(KargoError::Generic {
@@ -35,5 +37,7 @@ pub async fn exec(name: &str, template: &str) -> Result<()> {
 
     kargo_ops::ops_setup::post_scaffold(project_dir).await;
 
+    kargo_ops::ops_new::init_vcs(project_dir, vcs, commit);
+
     Ok(())
 }
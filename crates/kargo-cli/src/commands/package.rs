@@ -0,0 +1,27 @@
+//! Handler for `kargo package`.
+
+use miette::Result;
+
+use kargo_ops::ops_package::PackageOptions;
+
+pub async fn exec(opts: PackageOptions) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    let output = kargo_ops::ops_package::package(&project_root, &opts).await?;
+    let jars = [
+        Some(&output.jar),
+        output.sources_jar.as_ref(),
+        output.javadoc_jar.as_ref(),
+    ];
+    for jar in jars.into_iter().flatten() {
+        println!("{}", jar.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Handlers for `kargo --list`, `kargo plugin list`, and external
+//! subcommands (`kargo <cmd>` running `kargo-<cmd>`).
+
+use clap::CommandFactory;
+use miette::Result;
+
+use kargo_ops::ops_plugin;
+use kargo_util::errors::KargoError;
+
+use crate::cli::Cli;
+
+/// `kargo --list`: built-in commands, then installed plugins.
+pub fn list() -> Result<()> {
+    let cli = Cli::command();
+    let mut commands: Vec<(String, String)> = cli
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set())
+        .map(|c| {
+            let about = c.get_about().map(|a| a.to_string()).unwrap_or_default();
+            (c.get_name().to_string(), about)
+        })
+        .collect();
+    for plugin in ops_plugin::list() {
+        // Built-in commands shadow plugins of the same name.
+        if !commands.iter().any(|(name, _)| *name == plugin.name) {
+            commands.push((plugin.name, plugin.path.display().to_string()));
+        }
+    }
+    commands.sort();
+    let width = commands
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    println!("Installed Commands:");
+    for (name, about) in commands {
+        println!("    {name:<width$}  {about}");
+    }
+    Ok(())
+}
+
+/// `kargo plugin list`: installed plugins only.
+pub fn list_plugins() -> Result<()> {
+    let plugins = ops_plugin::list();
+    if plugins.is_empty() {
+        println!("No plugins installed (executables named `kargo-<cmd>` on PATH).");
+    }
+    for plugin in plugins {
+        println!("{}  {}", plugin.name, plugin.path.display());
+    }
+    Ok(())
+}
+
+/// Run an external subcommand; `args` starts with its name.
+pub fn external(args: &[String]) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(KargoError::Io)?;
+    let Some((name, rest)) = args.split_first() else {
+        return list();
+    };
+    let code = ops_plugin::run(&cwd, name, rest)?;
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
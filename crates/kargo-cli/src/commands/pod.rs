@@ -0,0 +1,41 @@
+//! Handler for `kargo pod spec` and `kargo pod publish`.
+
+use miette::Result;
+
+use kargo_ops::ops_pod::{self, PodSource};
+
+use crate::cli::PodAction;
+
+pub fn exec(action: PodAction) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    match action {
+        PodAction::Spec { url, profile } => {
+            let source = match url {
+                Some(url) => PodSource::Http { url },
+                None => PodSource::Local { profile },
+            };
+            let path = ops_pod::spec(&project_root, &source)?;
+            kargo_util::progress::status("Generated", &path.display().to_string());
+            if matches!(source, PodSource::Local { .. }) {
+                kargo_util::progress::note(
+                    "The pod vendors the local XCFramework; run `kargo xcode xcframework --release` \
+                     before `pod install`.",
+                );
+            }
+            Ok(())
+        }
+        PodAction::Publish { repo, url } => {
+            let path = ops_pod::publish(&project_root, &repo, &url)?;
+            kargo_util::progress::status("Published", &format!("{} to {repo}", path.display()));
+            Ok(())
+        }
+    }
+}
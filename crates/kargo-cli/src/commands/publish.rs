@@ -0,0 +1,30 @@
+//! Handler for `kargo publish`.
+
+use miette::Result;
+
+use kargo_ops::ops_publish::{self, PublishOptions};
+
+use crate::cli::PublishAction;
+
+pub async fn exec(
+    action: Option<PublishAction>,
+    repository: Option<String>,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        Some(PublishAction::VerifyNamespace { key }) => {
+            ops_publish::verify_namespace(&cwd, key.as_deref()).await
+        }
+        None => {
+            let opts = PublishOptions {
+                repository,
+                dry_run,
+                verbose,
+            };
+            ops_publish::publish(&cwd, &opts).await
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Handler for `kargo release`.
+
+use miette::Result;
+
+use kargo_ops::ops_release::ReleaseOptions;
+
+pub async fn exec(opts: ReleaseOptions) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    kargo_ops::ops_release::release(&project_root, &opts).await
+}
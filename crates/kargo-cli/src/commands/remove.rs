@@ -25,7 +25,7 @@ pub async fn exec(dep: &str, dev: bool, target: Option<&str>, flavor: Option<&st
         },
     )?;
 
-    eprintln!("Removed {dep}");
+    kargo_util::progress::note(&format!("Removed {dep}"));
 
     kargo_ops::ops_fetch::fetch(&project_root, false).await?;
 
@@ -2,7 +2,28 @@
 
 use miette::Result;
 
-pub async fn exec(target: Option<&str>, args: &[String], verbose: bool) -> Result<()> {
+use kargo_ops::ops_run::{self, RunOptions};
+
+pub async fn exec(
+    target: Option<&str>,
+    args: &[String],
+    exec_mode: bool,
+    debug: Option<u16>,
+    verbose: bool,
+) -> Result<()> {
     let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
-    kargo_ops::ops_run::run(&cwd, target, args, verbose).await
+    let opts = RunOptions {
+        target: target.map(String::from),
+        args: args.to_vec(),
+        exec: exec_mode,
+        debug,
+        verbose,
+    };
+
+    let code = ops_run::run(&cwd, &opts).await?;
+    if code != 0 {
+        std::process::exit(code);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,28 @@
+//! Handler for `kargo search`.
+
+use miette::Result;
+
+use kargo_ops::ops_search::{self, SearchFormat, SearchOptions};
+
+pub async fn exec(query: String, limit: usize, format: &str, add: bool) -> Result<()> {
+    let add = if add {
+        let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+        if !project_root.join("Kargo.toml").is_file() {
+            return Err(kargo_util::errors::KargoError::Manifest {
+                message: "No Kargo.toml found in current directory".to_string(),
+            }
+            .into());
+        }
+        Some(project_root)
+    } else {
+        None
+    };
+
+    ops_search::search(&SearchOptions {
+        query,
+        limit,
+        format: SearchFormat::parse(format)?,
+        add,
+    })
+    .await
+}
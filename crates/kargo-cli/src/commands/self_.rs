@@ -6,6 +6,7 @@ pub async fn exec(action: SelfAction) -> Result<()> {
     match action {
         SelfAction::Info => kargo_ops::ops_self::cmd_info(env!("CARGO_PKG_VERSION")),
         SelfAction::Clean => kargo_ops::ops_self::cmd_clean(),
+        SelfAction::MigrateHome => kargo_ops::ops_self::cmd_migrate_home(),
         SelfAction::Update { check } => {
             kargo_ops::ops_self::cmd_update(env!("CARGO_PKG_VERSION"), check).await
         }
@@ -0,0 +1,29 @@
+//! Handler for `kargo spm package`.
+
+use miette::Result;
+
+use crate::cli::SpmAction;
+
+pub async fn exec(action: SpmAction) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    match action {
+        SpmAction::Package {
+            url,
+            upload,
+            profile,
+        } => {
+            let path = kargo_ops::ops_spm::package(&project_root, &profile, url.as_deref(), upload)
+                .await?;
+            kargo_util::progress::status("Generated", &path.display().to_string());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Handler for `kargo task` (alias `kargo run-task`).
+
+use clap::CommandFactory;
+use miette::Result;
+
+use kargo_ops::ops_task;
+use kargo_util::errors::KargoError;
+
+use crate::cli::Cli;
+
+pub fn exec(name: Option<&str>) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(KargoError::Io)?;
+    let project_root = kargo_util::fs::find_ancestor_with(&cwd, "Kargo.toml").ok_or_else(|| {
+        KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+    })?;
+
+    let Some(name) = name else {
+        let tasks = ops_task::list(&project_root)?;
+        if tasks.is_empty() {
+            println!("No tasks declared in [tasks].");
+        }
+        let width = tasks.keys().map(String::len).max().unwrap_or(0);
+        for (name, task) in tasks {
+            let line = format!(
+                "{name:<width$}  {}",
+                task.description.as_deref().unwrap_or_default()
+            );
+            println!("{}", line.trim_end());
+        }
+        return Ok(());
+    };
+
+    let builtins: Vec<String> = Cli::command()
+        .get_subcommands()
+        .flat_map(|c| {
+            std::iter::once(c.get_name().to_string()).chain(c.get_all_aliases().map(String::from))
+        })
+        .collect();
+    ops_task::run(&project_root, name, &builtins)
+}
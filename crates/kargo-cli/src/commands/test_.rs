@@ -2,7 +2,63 @@
 
 use miette::Result;
 
-pub async fn exec(target: Option<&str>, filter: Option<&str>, verbose: bool) -> Result<()> {
-    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
-    kargo_ops::ops_test::test(&cwd, target, filter, verbose).await
+use kargo_ops::ops_test::{self, Shard, TestOptions};
+use kargo_util::errors::KargoError;
+
+use super::affected;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn exec(
+    target: Option<&str>,
+    filter: Option<&str>,
+    env: &[String],
+    system_properties: &[String],
+    integration: bool,
+    device: Option<String>,
+    affected_since: Option<&str>,
+    shard: Option<&str>,
+    show_output: bool,
+    no_capture: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(KargoError::Io)?;
+    let opts = TestOptions {
+        target: target.map(String::from),
+        filter: filter.map(String::from),
+        env: env
+            .iter()
+            .map(|e| split_pair(e, "--env"))
+            .collect::<Result<_>>()?,
+        // `-Dflag` without a value sets an empty property, as `java -D` does.
+        system_properties: system_properties
+            .iter()
+            .map(|p| match p.split_once('=') {
+                Some((k, v)) => (k.to_string(), v.to_string()),
+                None => (p.clone(), String::new()),
+            })
+            .collect(),
+        integration,
+        device,
+        no_capture,
+        show_output,
+        shard: shard.map(Shard::parse).transpose()?,
+        verbose,
+    };
+    if let Some(since) = affected_since {
+        for member in affected::members(&cwd, since)? {
+            ops_test::test(&member, &opts).await?;
+        }
+        return Ok(());
+    }
+    ops_test::test(&cwd, &opts).await
+}
+
+fn split_pair(value: &str, flag: &str) -> Result<(String, String)> {
+    match value.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(KargoError::Generic {
+            message: format!("Invalid {flag} value '{value}': expected KEY=VALUE"),
+        }
+        .into()),
+    }
 }
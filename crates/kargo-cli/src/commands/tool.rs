@@ -0,0 +1,61 @@
+//! Handler for `kargo tool`.
+
+use miette::Result;
+
+use kargo_ops::ops_tool::{self, InstalledTools, ToolInstallOptions, ToolRunOptions};
+
+use crate::cli::ToolAction;
+
+pub async fn exec(action: ToolAction, verbose: bool) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        ToolAction::Run {
+            coordinate,
+            main_class,
+            refresh,
+            args,
+        } => {
+            let opts = ToolRunOptions {
+                coordinate,
+                main_class,
+                args,
+                refresh,
+                verbose,
+            };
+            let code = ops_tool::run(&cwd, &opts).await?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+            Ok(())
+        }
+        ToolAction::Install {
+            tool,
+            version,
+            name,
+            main_class,
+        } => {
+            let opts = ToolInstallOptions {
+                tool,
+                version,
+                name,
+                main_class,
+                verbose,
+            };
+            ops_tool::install(&opts).await
+        }
+        ToolAction::Upgrade { name } => ops_tool::upgrade(name.as_deref()).await,
+        ToolAction::List => {
+            let tools = InstalledTools::load(&InstalledTools::path())?.tools;
+            if tools.is_empty() {
+                println!("No tools installed (add one with `kargo tool install <tool>`).");
+            }
+            let width = tools.keys().map(|name| name.len()).max().unwrap_or(0);
+            for (name, tool) in tools {
+                println!("{name:<width$}  {:<10}  {}", tool.version, tool.coordinate);
+            }
+            Ok(())
+        }
+        ToolAction::Uninstall { name } => ops_tool::uninstall(&name),
+    }
+}
@@ -16,7 +16,10 @@ pub async fn exec(action: ToolchainAction) -> Result<()> {
             )
             .await
         }
-        ToolchainAction::List => kargo_ops::ops_toolchain::cmd_list(),
+        ToolchainAction::List { remote: false, .. } => kargo_ops::ops_toolchain::cmd_list(),
+        ToolchainAction::List { remote: true, json } => {
+            kargo_ops::ops_toolchain::cmd_list_remote(json).await
+        }
         ToolchainAction::Remove {
             version,
             jdk,
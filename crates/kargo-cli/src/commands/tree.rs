@@ -11,6 +11,7 @@ pub async fn exec(
     why: Option<String>,
     conflicts: bool,
     licenses: bool,
+    optional: bool,
 ) -> Result<()> {
     let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
 
@@ -28,6 +29,7 @@ pub async fn exec(
         conflicts,
         licenses,
         inverted,
+        optional,
     };
 
     ops_tree::tree(&project_root, &opts).await
@@ -0,0 +1,16 @@
+//! Handler for `kargo trust`.
+
+use miette::Result;
+
+use kargo_ops::ops_trust;
+
+use crate::cli::TrustAction;
+
+pub async fn exec(action: TrustAction) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        TrustAction::Init => ops_trust::init(&cwd).map(|_| ()),
+        TrustAction::Update { tool, yes } => ops_trust::update(&cwd, tool.as_deref(), yes).await,
+    }
+}
@@ -0,0 +1,21 @@
+//! Handler for `kargo unused`.
+
+use miette::Result;
+
+pub async fn exec(
+    target: Option<&str>,
+    profile: Option<&str>,
+    release: bool,
+    delete: bool,
+) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    kargo_ops::ops_unused::unused(&project_root, target, profile, release, delete).await
+}
@@ -2,9 +2,16 @@
 
 use miette::Result;
 
-use kargo_ops::ops_update::{self, UpdateOptions};
+use kargo_ops::ops_update::{self, UpdateFormat, UpdateOptions};
 
-pub async fn exec(major: bool, dep: Option<String>, dry_run: bool) -> Result<()> {
+pub async fn exec(
+    major: bool,
+    dep: Option<String>,
+    dry_run: bool,
+    format: &str,
+    interactive: bool,
+) -> Result<()> {
+    let format = UpdateFormat::parse(format)?;
     let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
 
     if !project_root.join("Kargo.toml").is_file() {
@@ -18,6 +25,8 @@ pub async fn exec(major: bool, dep: Option<String>, dry_run: bool) -> Result<()>
         major,
         dep,
         dry_run,
+        format,
+        interactive,
     };
 
     ops_update::update(&project_root, &opts).await
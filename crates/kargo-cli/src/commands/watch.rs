@@ -1,7 +1,8 @@
 //! Watch command: rebuild (and optionally run) on file changes.
 //!
 //! By default, `kargo watch` builds and runs the project on every change.
-//! Pass `--build-only` to only build without running.
+//! Pass `--build-only` to only build without running, and `--ui` for the
+//! terminal dashboard in [`super::watch_ui`].
 //!
 //! Uses `notify` to watch source directories, `Kargo.toml`, and resource
 //! directories. Events are debounced so rapid saves (e.g. from an IDE)
@@ -17,42 +18,22 @@ use kargo_core::manifest::Manifest;
 use kargo_ops::ops_build::{self, BuildOptions};
 use kargo_util::errors::KargoError;
 
-const DEBOUNCE_MS: u64 = 300;
+pub(super) const DEBOUNCE_MS: u64 = 300;
 
-pub async fn exec(build_only: bool, verbose: bool) -> Result<()> {
+pub async fn exec(build_only: bool, ui: bool, verbose: bool) -> Result<()> {
     let cwd = std::env::current_dir().map_err(KargoError::Io)?;
+    if ui {
+        return super::watch_ui::exec(cwd, build_only).await;
+    }
     let mode = if build_only { "build" } else { "build + run" };
 
     let watch_paths = collect_watch_paths(&cwd)?;
 
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
-    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
-        if let Ok(event) = res {
-            if is_relevant_event(&event) {
-                let _ = tx.send(());
-            }
-        }
-    })
-    .map_err(|e| KargoError::Generic {
-        message: format!("Failed to create file watcher: {e}"),
+    let _watcher = start_watcher(&watch_paths, move || {
+        let _ = tx.send(());
     })?;
 
-    for path in &watch_paths {
-        if path.is_dir() {
-            watcher
-                .watch(path, RecursiveMode::Recursive)
-                .map_err(|e| KargoError::Generic {
-                    message: format!("Failed to watch {}: {e}", path.display()),
-                })?;
-        } else if path.is_file() {
-            watcher
-                .watch(path, RecursiveMode::NonRecursive)
-                .map_err(|e| KargoError::Generic {
-                    message: format!("Failed to watch {}: {e}", path.display()),
-                })?;
-        }
-    }
-
     kargo_util::progress::status("Watching", &format!("for changes (mode: {mode})"));
     if verbose {
         for p in &watch_paths {
@@ -65,9 +46,15 @@ pub async fn exec(build_only: bool, verbose: bool) -> Result<()> {
 
     // Watch loop
     loop {
-        match rx.recv().await {
-            Some(()) => {}
-            None => break,
+        // `kargo run` installs a Ctrl-C handler while the program runs, so
+        // the signal has to be handled here as well.
+        tokio::select! {
+            event = rx.recv() => {
+                if event.is_none() {
+                    break;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
         }
 
         // Debounce: drain additional events within the window
@@ -94,8 +81,16 @@ async fn run_cycle(cwd: &Path, build_only: bool, verbose: bool) {
 
     match build_result {
         Ok(result) if result.success && !build_only => {
-            if let Err(e) = kargo_ops::ops_run::run(cwd, None, &[], verbose).await {
-                kargo_util::progress::status_warn("Error", &format!("{e}"));
+            let opts = kargo_ops::ops_run::RunOptions {
+                verbose,
+                ..Default::default()
+            };
+            match kargo_ops::ops_run::run(cwd, &opts).await {
+                Ok(0) => {}
+                Ok(code) => {
+                    kargo_util::progress::status_warn("Exited", &format!("with code {code}"));
+                }
+                Err(e) => kargo_util::progress::status_warn("Error", &format!("{e}")),
             }
             kargo_util::progress::status("Watching", "for changes...");
         }
@@ -109,8 +104,40 @@ async fn run_cycle(cwd: &Path, build_only: bool, verbose: bool) {
     }
 }
 
+/// Watch `paths`, calling `on_change` for every relevant file event. The
+/// returned watcher stops when dropped.
+pub(super) fn start_watcher(
+    paths: &[PathBuf],
+    on_change: impl Fn() + Send + 'static,
+) -> Result<notify::RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if is_relevant_event(&event) {
+                on_change();
+            }
+        }
+    })
+    .map_err(|e| KargoError::Generic {
+        message: format!("Failed to create file watcher: {e}"),
+    })?;
+
+    for path in paths {
+        let mode = if path.is_dir() {
+            RecursiveMode::Recursive
+        } else if path.is_file() {
+            RecursiveMode::NonRecursive
+        } else {
+            continue;
+        };
+        watcher.watch(path, mode).map_err(|e| KargoError::Generic {
+            message: format!("Failed to watch {}: {e}", path.display()),
+        })?;
+    }
+    Ok(watcher)
+}
+
 /// Collect all paths that should be watched for changes.
-fn collect_watch_paths(project_dir: &Path) -> Result<Vec<PathBuf>> {
+pub(super) fn collect_watch_paths(project_dir: &Path) -> Result<Vec<PathBuf>> {
     let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
     let discovered = kargo_compiler::source_set_discovery::discover(project_dir, &manifest);
 
@@ -119,7 +146,9 @@ fn collect_watch_paths(project_dir: &Path) -> Result<Vec<PathBuf>> {
     for ss in discovered
         .main_sources
         .iter()
+        .chain(discovered.fixtures_sources.iter())
         .chain(discovered.test_sources.iter())
+        .chain(discovered.integration_test_sources.iter())
     {
         for dir in &ss.kotlin_dirs {
             if dir.is_dir() {
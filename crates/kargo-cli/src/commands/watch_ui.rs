@@ -0,0 +1,674 @@
+//! `kargo watch --ui`: a terminal dashboard for watch mode.
+//!
+//! Builds, test runs and program runs happen in child `kargo` processes, so
+//! a hanging program or a crashing build never takes the dashboard down and
+//! a new change can interrupt whatever is running. Their output fills the
+//! log pane, and the build events they send to `KARGO_EVENT_SOCKET` (see
+//! [`kargo_util::events`]) drive the status line and the diagnostics and
+//! test panes.
+//!
+//! Keys: `b` build, `t` test, `r` run, `s` stop, `Tab` switch between the
+//! diagnostics and tests panes, `↑`/`↓` (or `k`/`j`) select, `Enter` (or
+//! `e`) open the selected diagnostic in `$VISUAL`/`$EDITOR`, `PgUp`/`PgDn`
+//! scroll the log, `q` quit.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use miette::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+
+use kargo_util::errors::KargoError;
+
+use super::watch::{collect_watch_paths, start_watcher, DEBOUNCE_MS};
+
+/// Log lines kept for the log pane.
+const LOG_CAPACITY: usize = 2000;
+
+pub async fn exec(cwd: PathBuf, build_only: bool) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(KargoError::Generic {
+            message: "`kargo watch --ui` needs an interactive terminal".into(),
+        }
+        .into());
+    }
+    if !cfg!(unix) {
+        return Err(KargoError::Generic {
+            message: "`kargo watch --ui` is not supported on this platform yet; \
+                      use `kargo watch`"
+                .into(),
+        }
+        .into());
+    }
+    tokio::task::spawn_blocking(move || run(&cwd, build_only))
+        .await
+        .map_err(|e| KargoError::Generic {
+            message: format!("Watch dashboard failed: {e}"),
+        })?
+}
+
+/// What the dashboard hears from the watcher and the child processes.
+enum Msg {
+    Changed,
+    Event(Value),
+    Log(String),
+}
+
+/// A command the dashboard runs as `kargo <job>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Job {
+    Build,
+    Test,
+    Run,
+}
+
+impl Job {
+    fn command(self) -> &'static str {
+        match self {
+            Job::Build => "build",
+            Job::Test => "test",
+            Job::Run => "run",
+        }
+    }
+}
+
+struct Running {
+    job: Job,
+    child: Child,
+    started: Instant,
+    /// Whether the build the job started succeeded, once it finished.
+    built: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Diagnostics,
+    Tests,
+}
+
+struct Diagnostic {
+    severity: String,
+    message: String,
+    file: Option<String>,
+    line: Option<u64>,
+    column: Option<u64>,
+}
+
+struct TestRow {
+    name: String,
+    outcome: String,
+    message: Option<String>,
+}
+
+struct App {
+    project_dir: PathBuf,
+    socket: PathBuf,
+    tx: mpsc::Sender<Msg>,
+    /// Job started on a file change.
+    on_change: Job,
+    running: Option<Running>,
+    phase: String,
+    last_build: Option<(bool, u64)>,
+    artifact: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+    diagnostics_state: ListState,
+    tests: Vec<TestRow>,
+    tests_state: ListState,
+    test_summary: Option<String>,
+    focus: Pane,
+    log: VecDeque<String>,
+    /// Lines scrolled up from the end of the log.
+    log_scroll: usize,
+    pending_change: Option<Instant>,
+}
+
+fn run(project_dir: &Path, build_only: bool) -> Result<()> {
+    let watch_paths = collect_watch_paths(project_dir)?;
+    let (tx, rx) = mpsc::channel();
+
+    let changes = tx.clone();
+    let _watcher = start_watcher(&watch_paths, move || {
+        let _ = changes.send(Msg::Changed);
+    })?;
+
+    let socket_dir = tempfile::Builder::new()
+        .prefix("kargo-watch-")
+        .tempdir()
+        .map_err(KargoError::Io)?;
+    let socket = socket_dir.path().join("events.sock");
+    listen(&socket, tx.clone())?;
+
+    let mut app = App {
+        project_dir: project_dir.to_path_buf(),
+        socket,
+        tx,
+        on_change: if build_only { Job::Build } else { Job::Run },
+        running: None,
+        phase: "Starting".into(),
+        last_build: None,
+        artifact: None,
+        diagnostics: Vec::new(),
+        diagnostics_state: ListState::default(),
+        tests: Vec::new(),
+        tests_state: ListState::default(),
+        test_summary: None,
+        focus: Pane::Diagnostics,
+        log: VecDeque::new(),
+        log_scroll: 0,
+        pending_change: None,
+    };
+    app.start(app.on_change);
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal, &rx);
+    ratatui::restore();
+    app.stop();
+    result
+}
+
+/// Accept event connections on `socket`, forwarding each line.
+#[cfg(unix)]
+fn listen(socket: &Path, tx: mpsc::Sender<Msg>) -> Result<()> {
+    let listener = std::os::unix::net::UnixListener::bind(socket).map_err(KargoError::Io)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stream).lines().map_while(|l| l.ok()) {
+                    if let Ok(event) = serde_json::from_str(&line) {
+                        if tx.send(Msg::Event(event)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen(_socket: &Path, _tx: mpsc::Sender<Msg>) -> Result<()> {
+    Ok(())
+}
+
+impl App {
+    fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        rx: &mpsc::Receiver<Msg>,
+    ) -> Result<()> {
+        let io = |e: std::io::Error| KargoError::Io(e);
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(io)?;
+
+            if event::poll(Duration::from_millis(100)).map_err(io)? {
+                if let Event::Key(key) = event::read().map_err(io)? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c');
+                    match key.code {
+                        _ if ctrl_c => return Ok(()),
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char('b') => self.start(Job::Build),
+                        KeyCode::Char('t') => self.start(Job::Test),
+                        KeyCode::Char('r') => self.start(Job::Run),
+                        KeyCode::Char('s') => self.stop(),
+                        KeyCode::Tab => {
+                            self.focus = match self.focus {
+                                Pane::Diagnostics => Pane::Tests,
+                                Pane::Tests => Pane::Diagnostics,
+                            }
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                        KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                        KeyCode::PageUp => {
+                            self.log_scroll = (self.log_scroll + 10).min(self.log.len());
+                        }
+                        KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(10),
+                        KeyCode::Enter | KeyCode::Char('e') => self.open_selected(terminal)?,
+                        _ => {}
+                    }
+                }
+            }
+
+            while let Ok(msg) = rx.try_recv() {
+                match msg {
+                    Msg::Changed => self.pending_change = Some(Instant::now()),
+                    Msg::Event(event) => self.on_event(&event),
+                    Msg::Log(line) => self.push_log(line),
+                }
+            }
+            if self
+                .pending_change
+                .is_some_and(|at| at.elapsed() >= Duration::from_millis(DEBOUNCE_MS))
+            {
+                self.pending_change = None;
+                self.push_log("[change detected]".into());
+                self.start(self.on_change);
+            }
+            self.reap();
+        }
+    }
+
+    /// Start `kargo <job>`, stopping whatever runs.
+    fn start(&mut self, job: Job) {
+        self.stop();
+        if job == Job::Test {
+            self.tests.clear();
+            self.tests_state.select(None);
+            self.test_summary = None;
+        }
+        self.push_log(format!("[kargo {}]", job.command()));
+        let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("kargo"));
+        let mut cmd = std::process::Command::new(exe);
+        cmd.arg(job.command())
+            .current_dir(&self.project_dir)
+            .env(kargo_util::events::SOCKET_ENV, &self.socket)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Own process group, so that stopping the job also stops the
+        // program or test JVM it started.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+        match cmd.spawn() {
+            Ok(mut child) => {
+                if let Some(out) = child.stdout.take() {
+                    forward_lines(out, self.tx.clone());
+                }
+                if let Some(err) = child.stderr.take() {
+                    forward_lines(err, self.tx.clone());
+                }
+                self.phase = match job {
+                    Job::Build => "Building".into(),
+                    Job::Test => "Testing".into(),
+                    Job::Run => "Building to run".into(),
+                };
+                self.running = Some(Running {
+                    job,
+                    child,
+                    started: Instant::now(),
+                    built: None,
+                });
+            }
+            Err(e) => self.push_log(format!("[failed to start kargo: {e}]")),
+        }
+    }
+
+    /// Stop the running job and everything it started.
+    fn stop(&mut self) {
+        let Some(mut running) = self.running.take() else {
+            return;
+        };
+        #[cfg(unix)]
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", &format!("-{}", running.child.id())])
+            .stderr(Stdio::null())
+            .status();
+        let _ = running.child.kill();
+        let _ = running.child.wait();
+        self.phase = format!("Stopped {}", running.job.command());
+    }
+
+    /// Notice a job that exited on its own.
+    fn reap(&mut self) {
+        let Some(running) = self.running.as_mut() else {
+            return;
+        };
+        let Ok(Some(status)) = running.child.try_wait() else {
+            return;
+        };
+        let job = running.job;
+        let elapsed = running.started.elapsed();
+        let built = running.built;
+        self.running = None;
+        let code = kargo_util::process::exit_code(status);
+        self.push_log(format!(
+            "[kargo {} exited with code {code} after {:.1}s]",
+            job.command(),
+            elapsed.as_secs_f64()
+        ));
+        self.phase = match (job, code) {
+            _ if built == Some(false) => "Build failed".into(),
+            (Job::Test, 0) => "Tests passed".into(),
+            (Job::Test, _) => "Tests failed".into(),
+            (Job::Run, 0) => "Program exited".into(),
+            (Job::Run, code) => format!("Program exited with code {code}"),
+            (Job::Build, 0) => "Build succeeded".into(),
+            (Job::Build, _) => "Build failed".into(),
+        };
+    }
+
+    fn on_event(&mut self, event: &Value) {
+        let str_field = |name: &str| event[name].as_str().unwrap_or_default().to_string();
+        match event["event"].as_str().unwrap_or_default() {
+            "resolve-start" => self.phase = "Resolving dependencies".into(),
+            "start" => {
+                self.diagnostics.clear();
+                self.diagnostics_state.select(None);
+                self.artifact = None;
+                self.phase = format!(
+                    "Compiling {} v{} ({} {})",
+                    str_field("package"),
+                    str_field("version"),
+                    str_field("target"),
+                    str_field("profile")
+                );
+            }
+            "unit" if event["fresh"].as_bool() == Some(false) => {
+                self.phase = format!(
+                    "Compiling {} ({} files)",
+                    str_field("unit"),
+                    event["sources"].as_u64().unwrap_or_default()
+                );
+            }
+            "diagnostic" => {
+                self.diagnostics.push(Diagnostic {
+                    severity: str_field("severity"),
+                    message: str_field("message"),
+                    file: event["file"].as_str().map(String::from),
+                    line: event["line"].as_u64(),
+                    column: event["column"].as_u64(),
+                });
+                if self.diagnostics_state.selected().is_none() {
+                    self.diagnostics_state.select(Some(0));
+                }
+            }
+            "artifact" => self.artifact = event["path"].as_str().map(String::from),
+            "finish" => {
+                let success = event["success"].as_bool().unwrap_or(false);
+                self.last_build = Some((success, event["durationMs"].as_u64().unwrap_or(0)));
+                let running = self.running.as_mut().map(|r| {
+                    r.built = Some(success);
+                    r.job
+                });
+                self.phase = match (success, running) {
+                    (true, Some(Job::Run)) => "Running".into(),
+                    (true, Some(Job::Test)) => "Running tests".into(),
+                    (true, _) => "Build succeeded".into(),
+                    (false, _) => "Build failed".into(),
+                };
+            }
+            "test" => {
+                self.tests.push(TestRow {
+                    name: format!("{}.{}", str_field("class"), str_field("name")),
+                    outcome: str_field("outcome"),
+                    message: event["message"].as_str().map(String::from),
+                });
+                if self.tests_state.selected().is_none() {
+                    self.tests_state.select(Some(0));
+                }
+            }
+            "test-finish" => {
+                self.test_summary = Some(format!(
+                    "{} passed, {} failed, {} skipped",
+                    event["passed"].as_u64().unwrap_or(0),
+                    event["failed"].as_u64().unwrap_or(0),
+                    event["skipped"].as_u64().unwrap_or(0)
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+        if self.log_scroll > 0 {
+            self.log_scroll = (self.log_scroll + 1).min(self.log.len());
+        }
+    }
+
+    fn select(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Diagnostics => (&mut self.diagnostics_state, self.diagnostics.len()),
+            Pane::Tests => (&mut self.tests_state, self.tests.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+    }
+
+    /// Open the selected diagnostic's file in the user's editor, suspending
+    /// the dashboard while it runs.
+    fn open_selected(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        if self.focus != Pane::Diagnostics {
+            return Ok(());
+        }
+        let Some(d) = self
+            .diagnostics_state
+            .selected()
+            .and_then(|i| self.diagnostics.get(i))
+        else {
+            return Ok(());
+        };
+        let Some(file) = d.file.clone() else {
+            return Ok(());
+        };
+        let path = self.project_dir.join(&file);
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".into());
+        let args = editor_args(&editor, &path, d.line, d.column);
+
+        ratatui::restore();
+        let mut parts = editor.split_whitespace();
+        let status = std::process::Command::new(parts.next().unwrap_or("vi"))
+            .args(parts)
+            .args(&args)
+            .current_dir(&self.project_dir)
+            .status();
+        *terminal = ratatui::init();
+        terminal.clear().map_err(KargoError::Io)?;
+        if let Err(e) = status {
+            self.push_log(format!("[failed to start {editor}: {e}]"));
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, panes, log, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Percentage(45),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [diagnostics, tests] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(panes);
+
+        self.draw_status(frame, header);
+        self.draw_diagnostics(frame, diagnostics);
+        self.draw_tests(frame, tests);
+        self.draw_log(frame, log);
+        frame.render_widget(
+            Paragraph::new(
+                " b build · t test · r run · s stop · tab switch pane · ↑↓ select · \
+                 enter open · pgup/pgdn scroll log · q quit",
+            )
+            .dark_gray(),
+            help,
+        );
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let mut spans = vec![Span::from(self.phase.as_str()).bold()];
+        if let Some(running) = &self.running {
+            spans.push(Span::from(format!(
+                "  ({} {:.0}s)",
+                running.job.command(),
+                running.started.elapsed().as_secs_f64()
+            )));
+        }
+        if let Some((success, ms)) = self.last_build {
+            spans.push(Span::from("  last build: "));
+            spans.push(if success {
+                Span::from("ok").green()
+            } else {
+                Span::from("failed").red()
+            });
+            spans.push(Span::from(format!(" in {:.2}s", ms as f64 / 1000.0)));
+        }
+        if let Some(artifact) = &self.artifact {
+            spans.push(Span::from(format!("  → {artifact}")).dark_gray());
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).block(Block::bordered().title(" kargo watch ")),
+            area,
+        );
+    }
+
+    fn draw_diagnostics(&mut self, frame: &mut Frame, area: Rect) {
+        let errors = self
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == "error")
+            .count();
+        let items: Vec<ListItem> = self
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let severity = match d.severity.as_str() {
+                    "error" => Span::from("error").red().bold(),
+                    "warning" => Span::from("warning").yellow(),
+                    other => Span::from(other.to_string()),
+                };
+                let location = match (&d.file, d.line) {
+                    (Some(f), Some(l)) => format!(" {f}:{l}"),
+                    (Some(f), None) => format!(" {f}"),
+                    _ => String::new(),
+                };
+                ListItem::new(Line::from(vec![
+                    severity,
+                    Span::from(location).cyan(),
+                    Span::from(format!(" {}", d.message)),
+                ]))
+            })
+            .collect();
+        let title = format!(
+            " Diagnostics ({errors} errors, {} warnings) ",
+            self.diagnostics.len() - errors
+        );
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(pane_block(title, self.focus == Pane::Diagnostics))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            area,
+            &mut self.diagnostics_state,
+        );
+    }
+
+    fn draw_tests(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .tests
+            .iter()
+            .map(|t| {
+                let mark = match t.outcome.as_str() {
+                    "passed" => Span::from("✓").green(),
+                    "failed" => Span::from("✗").red().bold(),
+                    _ => Span::from("-").dark_gray(),
+                };
+                let mut lines = vec![Line::from(vec![mark, Span::from(format!(" {}", t.name))])];
+                if let Some(message) = t.message.as_deref().filter(|_| t.outcome == "failed") {
+                    lines.push(Line::from(format!("    {message}")).red());
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let title = format!(
+            " Tests{} ",
+            self.test_summary
+                .as_deref()
+                .map(|s| format!(" ({s})"))
+                .unwrap_or_default()
+        );
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(pane_block(title, self.focus == Pane::Tests))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            area,
+            &mut self.tests_state,
+        );
+    }
+
+    fn draw_log(&self, frame: &mut Frame, area: Rect) {
+        let height = area.height.saturating_sub(2) as usize;
+        let end = self.log.len() - self.log_scroll.min(self.log.len());
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = self
+            .log
+            .range(start..end)
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        let title = if self.log_scroll > 0 {
+            format!(" Log (scrolled {} lines) ", self.log_scroll)
+        } else {
+            " Log ".to_string()
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
+
+/// Send each line of `reader` to the log pane.
+fn forward_lines(reader: impl std::io::Read + Send + 'static, tx: mpsc::Sender<Msg>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(|l| l.ok()) {
+            let line = line.replace('\r', "").replace('\t', "    ");
+            if tx.send(Msg::Log(line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Arguments that make `editor` open `path` at the given position. VS Code
+/// style editors take `-g file:line:column`; others (vi, nano, emacs, ...)
+/// `+line file`.
+fn editor_args(editor: &str, path: &Path, line: Option<u64>, column: Option<u64>) -> Vec<String> {
+    let program = editor
+        .split_whitespace()
+        .next()
+        .and_then(|p| Path::new(p).file_stem())
+        .and_then(|p| p.to_str())
+        .unwrap_or_default();
+    let path = path.to_string_lossy().into_owned();
+    match (program, line) {
+        ("code" | "code-insiders" | "codium" | "cursor", Some(line)) => vec![
+            "-g".into(),
+            format!("{path}:{line}:{}", column.unwrap_or(1)),
+        ],
+        ("subl" | "zed", Some(line)) => vec![format!("{path}:{line}:{}", column.unwrap_or(1))],
+        (_, Some(line)) => vec![format!("+{line}"), path],
+        (_, None) => vec![path],
+    }
+}
@@ -0,0 +1,17 @@
+//! Handler for `kargo workspace deps`.
+
+use miette::Result;
+
+use kargo_ops::ops_workspace::{self, DepsOptions};
+
+use crate::cli::WorkspaceAction;
+
+pub fn exec(action: WorkspaceAction) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    match action {
+        WorkspaceAction::Deps { inconsistent, fix } => {
+            ops_workspace::deps(&cwd, &DepsOptions { inconsistent, fix })
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Handler for `kargo wrapper`.
+
+use miette::Result;
+
+pub fn exec(kargo_version: Option<&str>) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    let version = kargo_version.unwrap_or(env!("CARGO_PKG_VERSION"));
+    kargo_ops::ops_wrapper::generate(&project_root, version)?;
+
+    kargo_util::progress::status(
+        "Generated",
+        &format!(
+            "kargow, kargow.bat, and {} (Kargo {})",
+            kargo_ops::ops_wrapper::VERSION_FILE,
+            version.trim_start_matches('v')
+        ),
+    );
+    Ok(())
+}
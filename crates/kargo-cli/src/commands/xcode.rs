@@ -0,0 +1,56 @@
+//! Handler for `kargo xcode integrate`, `kargo xcode build`, and
+//! `kargo xcode xcframework`.
+
+use miette::Result;
+
+use kargo_ops::ops_xcode;
+
+use crate::cli::XcodeAction;
+
+pub async fn exec(action: XcodeAction, verbose: bool) -> Result<()> {
+    let project_root = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+
+    if !project_root.join("Kargo.toml").is_file() {
+        return Err(kargo_util::errors::KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".to_string(),
+        }
+        .into());
+    }
+
+    match action {
+        XcodeAction::Integrate { xcode_dir } => {
+            let xcode_dir = project_root.join(xcode_dir);
+            ops_xcode::integrate(&project_root, &xcode_dir)?;
+            kargo_util::progress::status(
+                "Generated",
+                &format!(
+                    "{} and {} in {}",
+                    ops_xcode::XCCONFIG,
+                    ops_xcode::BUILD_PHASE_SCRIPT,
+                    xcode_dir.display()
+                ),
+            );
+            kargo_util::progress::note(&format!(
+                "Base the app target's configurations on {} and add a Run Script \
+                 build phase running \"$SRCROOT/{}\" before linking.",
+                ops_xcode::XCCONFIG,
+                ops_xcode::BUILD_PHASE_SCRIPT
+            ));
+            Ok(())
+        }
+        XcodeAction::Build => ops_xcode::build(&project_root, verbose).await,
+        XcodeAction::Xcframework { profile, release } => {
+            let xcframework =
+                ops_xcode::xcframework(&project_root, profile.as_deref(), release, verbose).await?;
+            kargo_util::progress::status(
+                "Packaged",
+                &format!(
+                    "{} and {}",
+                    xcframework.path.display(),
+                    xcframework.zip.display()
+                ),
+            );
+            Ok(())
+        }
+    }
+}
@@ -1,23 +1,54 @@
 //! Kargo CLI binary.
 //!
-//! This is the entry point for the `kargo` command-line tool. It initializes
-//! logging via `tracing`, parses arguments with `clap`, and dispatches to
-//! the appropriate command handler.
+//! This is the entry point for the `kargo` command-line tool. It parses
+//! arguments with `clap`, applies the `-q`/`-v` output policy, initializes
+//! logging via `tracing`, configures HTTP clients from `[network]`, and
+//! dispatches to the appropriate command handler. Errors are printed in the
+//! `--error-format` and exit with the code of their category
+//! ([`kargo_util::errors::exit_code`]).
 
 mod cli;
 mod commands;
 
 use miette::Result;
 
+use kargo_util::errors::{self, ErrorFormat};
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    let args = cli::parse();
+    let verbosity = args.verbosity();
+    kargo_util::progress::set_verbosity(verbosity);
+    let error_format = ErrorFormat::parse(&args.error_format).unwrap_or_default();
+    errors::set_error_format(error_format);
+    if error_format == ErrorFormat::Short {
+        std::panic::set_hook(Box::new(|info| {
+            eprintln!(
+                "error[internal]: {}",
+                errors::single_line(&info.to_string())
+            );
+        }));
+    }
+
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(verbosity.tracing_filter())),
         )
         .init();
 
-    let args = cli::parse();
+    if let Err(e) = run(args).await {
+        match error_format {
+            ErrorFormat::Human => eprintln!("Error: {e:?}"),
+            ErrorFormat::Short => eprintln!("{}", errors::short_line(&e)),
+        }
+        std::process::exit(errors::exit_code_of(&e));
+    }
+}
+
+async fn run(args: cli::Cli) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
+    kargo_ops::configure_http(&cwd)?;
+
     commands::dispatch(args).await
 }
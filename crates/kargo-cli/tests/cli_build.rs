@@ -0,0 +1,165 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+#[test]
+fn test_build_rejects_unknown_message_format() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["build", "--message-format", "xml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown message format 'xml'"));
+}
+
+#[test]
+fn test_build_selects_members_with_short_p() {
+    kargo_cmd()
+        .args(["build", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-p, --package <MEMBER>"))
+        .stdout(predicate::str::contains("    --profile <PROFILE>"));
+}
+
+#[test]
+fn test_invalid_manifest_exits_with_manifest_code_and_short_error() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(
+        tmp.path().join("Kargo.toml"),
+        "[package]\nname = \"bad name\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+    )
+    .unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["build", "--error-format", "short"])
+        .assert()
+        .code(7)
+        .stderr(predicate::str::starts_with(
+            "error[manifest]: Manifest error: package.name 'bad name'",
+        ))
+        .stderr(predicate::str::contains("help:").not());
+}
+
+#[test]
+fn test_build_json_diagnostics_reports_finish_on_failure() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["build", "--message-format", "json-diagnostics"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""event":"finish""#))
+        .stdout(predicate::str::contains(r#""success":false"#));
+}
+
+#[test]
+fn test_check_json_diagnostics_reports_finish_on_failure() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["check", "--message-format", "json-diagnostics"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""event":"finish""#));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_build_events_reach_socket_observer() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    let tmp = TempDir::new().unwrap();
+    let socket = tmp.path().join("events.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let observer = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        BufReader::new(stream)
+            .lines()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    });
+
+    // Human output stays on the terminal; the events go to the socket.
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .env("KARGO_EVENT_SOCKET", &socket)
+        .arg("build")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("event").not());
+
+    let lines = observer.join().unwrap();
+    let last = lines.last().expect("no events received");
+    assert!(last.contains(r#""event":"finish""#), "{last}");
+    assert!(last.contains(r#""success":false"#), "{last}");
+    assert!(last.contains(r#""pid":"#), "{last}");
+}
+
+#[test]
+fn test_build_affected_since_skips_unchanged_workspace() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"app\"]\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("app/src/main/kotlin")).unwrap();
+    std::fs::write(
+        root.join("app/Kargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("README.md"), "# app\n").unwrap();
+    for args in [
+        &["init", "-q"][..],
+        &["add", "."],
+        &[
+            "-c",
+            "user.name=Kargo Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-qm",
+            "init",
+        ],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    std::fs::write(root.join("README.md"), "# app, documented\n").unwrap();
+
+    kargo_cmd()
+        .current_dir(root)
+        .args(["build", "--affected-since", "HEAD"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "no workspace member changed since HEAD",
+        ));
+
+    kargo_cmd()
+        .current_dir(root.join("app"))
+        .args(["test", "--affected-since", "does-not-exist"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "git merge-base does-not-exist HEAD failed",
+        ));
+}
@@ -0,0 +1,86 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+#[test]
+fn test_daemon_requires_manifest() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["daemon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No Kargo.toml"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_serves_metadata_and_shuts_down() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::{Duration, Instant};
+
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join("Kargo.toml"),
+        "[package]\nname = \"warm\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+    )
+    .unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_kargo"))
+        .current_dir(tmp.path())
+        .arg("daemon")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let socket = tmp.path().join(".kargo/daemon.sock");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let stream = loop {
+        match UnixStream::connect(&socket) {
+            Ok(s) => break s,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => {
+                let _ = child.kill();
+                panic!("daemon did not start: {e}");
+            }
+        }
+    };
+
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut call = |request: &str| {
+        writer.write_all(request.as_bytes()).unwrap();
+        writer.write_all(b"\n").unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line
+    };
+
+    let pong = call(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#);
+    assert!(pong.contains(r#""result":"pong""#), "{pong}");
+
+    let meta = call(r#"{"jsonrpc":"2.0","id":2,"method":"metadata"}"#);
+    assert!(meta.contains(r#""name":"warm""#), "{meta}");
+    assert!(meta.contains(r#""id":2"#), "{meta}");
+
+    let unknown = call(r#"{"jsonrpc":"2.0","id":3,"method":"frobnicate"}"#);
+    assert!(unknown.contains("-32601"), "{unknown}");
+
+    let garbage = call("not json");
+    assert!(garbage.contains("-32700"), "{garbage}");
+
+    let bye = call(r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#);
+    assert!(bye.contains(r#""id":4"#), "{bye}");
+
+    let status = child.wait().unwrap();
+    assert!(status.success());
+    assert!(!socket.exists());
+}
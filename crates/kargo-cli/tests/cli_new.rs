@@ -133,6 +133,12 @@ fn test_new_android_project() {
     assert!(project_dir
         .join("src/main/kotlin/MainActivity.kt")
         .is_file());
+    assert!(project_dir
+        .join("src/main/res/values/strings.xml")
+        .is_file());
+    assert!(project_dir
+        .join("src/test/kotlin/GreetingTest.kt")
+        .is_file());
 
     let manifest = fs::read_to_string(project_dir.join("Kargo.toml")).unwrap();
     assert!(manifest.contains("[targets.android]"));
@@ -168,6 +174,59 @@ fn test_new_lib_project() {
     assert!(manifest.contains("[targets.jvm]"));
 }
 
+#[test]
+fn test_new_ktor_project() {
+    let tmp = TempDir::new().unwrap();
+    let project_name = "test-ktor";
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", project_name, "--template", "ktor"])
+        .assert()
+        .success();
+
+    let project_dir = tmp.path().join(project_name);
+    assert!(project_dir
+        .join("src/main/kotlin/com/example/Application.kt")
+        .is_file());
+    assert!(project_dir
+        .join("src/test/kotlin/com/example/ApplicationTest.kt")
+        .is_file());
+
+    let dockerfile = fs::read_to_string(project_dir.join("Dockerfile")).unwrap();
+    assert!(dockerfile.contains("EXPOSE 8080"));
+    assert!(dockerfile.contains(&format!("{project_name}.jar")));
+
+    let manifest = fs::read_to_string(project_dir.join("Kargo.toml")).unwrap();
+    assert!(manifest.contains("main-class = \"com.example.ApplicationKt\""));
+    assert!(manifest.contains("io.ktor:ktor-server-netty"));
+}
+
+#[test]
+fn test_new_compose_desktop_project() {
+    let tmp = TempDir::new().unwrap();
+    let project_name = "test-desktop";
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", project_name, "--template", "compose-desktop"])
+        .assert()
+        .success();
+
+    let project_dir = tmp.path().join(project_name);
+    let main_kt =
+        fs::read_to_string(project_dir.join("src/main/kotlin/com/example/Main.kt")).unwrap();
+    assert!(main_kt.contains("application {"));
+    assert!(project_dir
+        .join("src/test/kotlin/com/example/MainTest.kt")
+        .is_file());
+
+    let manifest = fs::read_to_string(project_dir.join("Kargo.toml")).unwrap();
+    assert!(manifest.contains("[compose]"));
+    assert!(manifest.contains("[targets.jvm]"));
+    assert!(!manifest.contains("android"));
+}
+
 #[test]
 fn test_new_existing_directory_fails() {
     let tmp = TempDir::new().unwrap();
@@ -205,8 +264,75 @@ fn test_new_gitignore_contains_required_entries() {
 
     let gitignore = fs::read_to_string(tmp.path().join(project_name).join(".gitignore")).unwrap();
     assert!(gitignore.contains("build/"));
+    assert!(gitignore.contains(".kargo/"));
     assert!(gitignore.contains(".kargo.env"));
-    assert!(!gitignore.contains("local.properties"));
+    assert!(gitignore.contains("local.properties"));
+}
+
+#[test]
+fn test_new_initializes_git_repository() {
+    let tmp = TempDir::new().unwrap();
+    let project_name = "git-default";
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", project_name])
+        .assert()
+        .success();
+
+    assert!(tmp.path().join(project_name).join(".git").is_dir());
+}
+
+#[test]
+fn test_new_vcs_none_skips_git() {
+    let tmp = TempDir::new().unwrap();
+    let project_name = "git-none";
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", project_name, "--vcs", "none"])
+        .assert()
+        .success();
+
+    let project_dir = tmp.path().join(project_name);
+    assert!(!project_dir.join(".git").exists());
+    assert!(project_dir.join(".gitignore").is_file());
+}
+
+#[test]
+fn test_new_unknown_vcs_fails() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", "bad-vcs", "--vcs", "svn"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown VCS"));
+}
+
+#[test]
+fn test_new_commit_creates_initial_commit() {
+    let tmp = TempDir::new().unwrap();
+    let project_name = "git-commit";
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .env("GIT_AUTHOR_NAME", "Kargo Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Kargo Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["new", project_name, "--commit"])
+        .assert()
+        .success();
+
+    let out = std::process::Command::new("git")
+        .args(["log", "--oneline"])
+        .current_dir(tmp.path().join(project_name))
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().count(), 1);
 }
 
 #[test]
@@ -285,7 +411,16 @@ fn test_init_does_not_overwrite_existing_files() {
 
 #[test]
 fn test_all_templates_produce_parseable_manifests() {
-    for template in &["jvm", "kmp", "cmp", "android", "lib"] {
+    for template in &[
+        "jvm",
+        "kmp",
+        "kmp-lib",
+        "cmp",
+        "compose-desktop",
+        "android",
+        "ktor",
+        "lib",
+    ] {
         let tmp = TempDir::new().unwrap();
         let project_name = format!("parse-{}", template);
 
@@ -0,0 +1,95 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+/// Install `kargo-hello` into `bin`, printing its arguments and the
+/// environment Kargo gives it.
+fn install_plugin(bin: &Path) {
+    fs::create_dir_all(bin).unwrap();
+    let script = bin.join("kargo-hello");
+    fs::write(
+        &script,
+        "#!/bin/sh\n\
+         echo \"args: $*\"\n\
+         echo \"plugin: $KARGO_PLUGIN\"\n\
+         test -x \"$KARGO\" && echo \"kargo: ok\"\n\
+         test -n \"$KARGO_METADATA\" && grep -o '\"name\":\"plugin-test\"' \"$KARGO_METADATA\"\n\
+         exit 3\n",
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn path_with(bin: &Path) -> String {
+    format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    )
+}
+
+#[test]
+fn test_external_subcommand_runs_with_project_metadata() {
+    let tmp = TempDir::new().unwrap();
+    let bin = tmp.path().join("bin");
+    install_plugin(&bin);
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", "plugin-test"])
+        .assert()
+        .success();
+
+    kargo_cmd()
+        .current_dir(tmp.path().join("plugin-test"))
+        .env("PATH", path_with(&bin))
+        .args(["hello", "--flag", "value"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("args: --flag value"))
+        .stdout(predicate::str::contains("plugin: hello"))
+        .stdout(predicate::str::contains("kargo: ok"))
+        .stdout(predicate::str::contains("\"name\":\"plugin-test\""));
+}
+
+#[test]
+fn test_list_shows_builtins_and_plugins() {
+    let tmp = TempDir::new().unwrap();
+    let bin = tmp.path().join("bin");
+    install_plugin(&bin);
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .env("PATH", path_with(&bin))
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed Commands:"))
+        .stdout(predicate::str::contains("build"))
+        .stdout(predicate::str::contains("hello"));
+}
+
+#[test]
+fn test_unknown_subcommand_fails() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .env("PATH", tmp.path())
+        .arg("no-such-command")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no such command: `no-such-command`",
+        ));
+}
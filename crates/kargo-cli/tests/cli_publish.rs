@@ -0,0 +1,52 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[allow(deprecated)]
+fn kargo() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+fn project(manifest: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Kargo.toml"), manifest).unwrap();
+    dir
+}
+
+#[test]
+fn publish_needs_a_group() {
+    let dir = project(
+        r#"[package]
+name = "lib"
+version = "1.0.0"
+kotlin = "2.3.0"
+"#,
+    );
+    kargo()
+        .args(["publish", "--dry-run"])
+        .current_dir(dir.path())
+        .assert()
+        .code(7)
+        .stderr(predicate::str::contains("[package] group"));
+}
+
+#[test]
+fn publish_refuses_unset_env_references() {
+    let dir = project(
+        r#"[package]
+name = "lib"
+group = "com.example"
+version = "1.0.0"
+kotlin = "2.3.0"
+
+[signing]
+gpg-key = "${env:KARGO_TEST_UNSET_GPG_KEY}"
+"#,
+    );
+    kargo()
+        .args(["publish", "--dry-run"])
+        .current_dir(dir.path())
+        .env_remove("KARGO_TEST_UNSET_GPG_KEY")
+        .assert()
+        .code(7)
+        .stderr(predicate::str::contains("KARGO_TEST_UNSET_GPG_KEY"));
+}
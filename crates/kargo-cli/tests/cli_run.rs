@@ -0,0 +1,97 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+/// A project whose `main` prints its arguments and exits with 3.
+fn echo_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src/main/kotlin")).unwrap();
+    fs::write(
+        dir.join("Kargo.toml"),
+        "[package]\nname = \"echo\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/main/kotlin/Main.kt"),
+        "import kotlin.system.exitProcess\n\n\
+         fun main(args: Array<String>) {\n    \
+             println(args.joinToString(\" \"))\n    \
+             exitProcess(3)\n\
+         }\n",
+    )
+    .unwrap();
+}
+
+#[test]
+#[ignore = "downloads the Kotlin compiler"]
+fn test_run_passes_args_and_exit_code_through() {
+    let tmp = TempDir::new().unwrap();
+    echo_project(tmp.path());
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["run", "--", "--port", "-v", "run"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("--port -v run\n"));
+}
+
+#[cfg(unix)]
+#[test]
+#[ignore = "downloads the Kotlin compiler"]
+fn test_run_exec_hands_the_process_to_the_program() {
+    let tmp = TempDir::new().unwrap();
+    echo_project(tmp.path());
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["run", "--exec", "--", "--port", "-v", "run"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("--port -v run\n"));
+}
+
+#[test]
+fn test_run_accepts_hyphenated_args_only_after_separator() {
+    let tmp = TempDir::new().unwrap();
+
+    // Without `--` the program's flags are Kargo's and rejected as such.
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["run", "--port"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("unexpected argument '--port'"));
+
+    // After `--` they parse, and the run gets as far as the missing project.
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["run", "--exec", "--", "--port", "-v", "run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument").not())
+        .stderr(predicate::str::contains("Kargo.toml"));
+}
+
+#[test]
+fn test_run_debug_takes_an_optional_port() {
+    kargo_cmd()
+        .args(["run", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--debug[=<PORT>]"));
+
+    kargo_cmd()
+        .args(["run", "--debug=next"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("invalid value 'next'"));
+}
@@ -0,0 +1,83 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+/// A new project with `tasks` appended to its manifest.
+fn project_with_tasks(tmp: &TempDir, tasks: &str) -> PathBuf {
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["new", "task-test"])
+        .assert()
+        .success();
+    let root = tmp.path().join("task-test");
+    let manifest = root.join("Kargo.toml");
+    let mut content = fs::read_to_string(&manifest).unwrap();
+    content.push_str(tasks);
+    fs::write(&manifest, content).unwrap();
+    root
+}
+
+#[test]
+fn test_task_runs_dependencies_and_kargo_steps() {
+    let tmp = TempDir::new().unwrap();
+    let root = project_with_tasks(
+        &tmp,
+        r#"
+[tasks.prepare]
+steps = ["echo \"$KARGO_TASK $GREETING $KARGO_PKG_NAME\" > prepared.txt"]
+env = { GREETING = "hello" }
+
+[tasks.ship]
+description = "Prepare, then dump metadata"
+depends-on = ["prepare"]
+steps = ["metadata > meta.json", "cat prepared.txt"]
+"#,
+    );
+
+    kargo_cmd()
+        .current_dir(&root)
+        .args(["task", "ship"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("prepare hello task-test"));
+    let meta = fs::read_to_string(root.join("meta.json")).unwrap();
+    assert!(meta.contains("\"name\":\"task-test\""));
+
+    kargo_cmd()
+        .current_dir(&root)
+        .arg("task")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "ship     Prepare, then dump metadata",
+        ));
+}
+
+#[test]
+fn test_run_task_stops_at_failing_step() {
+    let tmp = TempDir::new().unwrap();
+    let root = project_with_tasks(
+        &tmp,
+        r#"
+[tasks.broken]
+steps = ["exit 4", "touch never.txt"]
+"#,
+    );
+
+    kargo_cmd()
+        .current_dir(&root)
+        .args(["run-task", "broken"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`exit 4` exited with code 4"));
+    assert!(!root.join("never.txt").exists());
+}
@@ -0,0 +1,54 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+#[test]
+fn test_test_rejects_env_without_value() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["test", "--env", "API_URL"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid --env value 'API_URL': expected KEY=VALUE",
+        ));
+}
+
+#[test]
+fn test_test_accepts_system_properties() {
+    let tmp = TempDir::new().unwrap();
+
+    // Fails on the missing manifest, after the flags were accepted.
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args([
+            "test",
+            "-Ddb.url=jdbc:h2:mem:",
+            "-D",
+            "flag",
+            "--env",
+            "A=1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Kargo.toml"));
+}
+
+#[test]
+fn test_test_rejects_out_of_range_shard() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["test", "--shard", "6/5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --shard '6/5'"));
+}
@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[allow(deprecated)]
+fn kargo() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+#[test]
+fn tool_run_rejects_a_coordinate_without_group() {
+    let dir = tempfile::tempdir().unwrap();
+    kargo()
+        .args(["tool", "run", "ktlint", "--", "--format"])
+        .current_dir(dir.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "expected group:artifact[:version]",
+        ));
+}
+
+#[test]
+fn tool_list_and_uninstall_read_the_user_tools_file() {
+    let home = tempfile::tempdir().unwrap();
+    kargo()
+        .args(["tool", "list"])
+        .env("KARGO_HOME", home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No tools installed"));
+
+    std::fs::write(
+        home.path().join("tools.toml"),
+        "[tools.detekt]\ncoordinate = \"io.gitlab.arturbosch.detekt:detekt-cli\"\n\
+         version = \"1.23.7\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir(home.path().join("bin")).unwrap();
+    std::fs::write(
+        home.path().join("bin").join("detekt"),
+        "#!/bin/sh\n# Installed by `kargo tool install`.\n",
+    )
+    .unwrap();
+    kargo()
+        .args(["tool", "list"])
+        .env("KARGO_HOME", home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("detekt  1.23.7"));
+
+    kargo()
+        .args(["tool", "uninstall", "detekt"])
+        .env("KARGO_HOME", home.path())
+        .assert()
+        .success();
+    assert!(!home.path().join("bin").join("detekt").exists());
+    kargo()
+        .args(["tool", "uninstall", "detekt"])
+        .env("KARGO_HOME", home.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("no tool named 'detekt'"));
+}
@@ -14,13 +14,14 @@ fn toolchain_list_runs() {
 #[test]
 fn toolchain_path_without_project_or_default() {
     // In a temp dir with no Kargo.toml and possibly no default toolchain,
-    // this should either succeed (if a default is set) or fail gracefully.
+    // this should either succeed (if a default is set) or fail with the
+    // toolchain or, when the download fails, the network exit code.
     let dir = tempfile::tempdir().unwrap();
     kargo()
         .args(["toolchain", "path"])
         .current_dir(dir.path())
         .assert()
-        .code(predicate::in_iter([0, 1]));
+        .code(predicate::in_iter([0, 8, 9]));
 }
 
 #[test]
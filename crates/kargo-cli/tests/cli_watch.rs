@@ -0,0 +1,20 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+#[test]
+fn test_watch_ui_requires_a_terminal() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["watch", "--ui"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs an interactive terminal"));
+}
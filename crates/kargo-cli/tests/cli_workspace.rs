@@ -0,0 +1,144 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+fn member(root: &Path, name: &str, okio: &str) {
+    fs::create_dir_all(root.join(name)).unwrap();
+    fs::write(
+        root.join(name).join("Kargo.toml"),
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n\
+             [dependencies]\nokio = \"com.squareup.okio:okio:{okio}\"\n"
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_workspace_deps_fix_hoists_diverging_versions() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"app\", \"shared\"]\n",
+    )
+    .unwrap();
+    member(root, "app", "3.9.0");
+    member(root, "shared", "3.10.2");
+
+    kargo_cmd()
+        .current_dir(root.join("app"))
+        .args(["workspace", "deps", "--inconsistent"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("com.squareup.okio:okio"))
+        .stdout(predicate::str::contains(
+            "3.10.2          shared (dependencies)",
+        ))
+        .stderr(predicate::str::contains(
+            "1 dependencies are declared at diverging versions",
+        ));
+
+    kargo_cmd()
+        .current_dir(root)
+        .args(["workspace", "deps", "--fix"])
+        .assert()
+        .success();
+    let app = fs::read_to_string(root.join("app/Kargo.toml")).unwrap();
+    assert!(app.contains("okio = { catalog = \"okio\" }"));
+
+    kargo_cmd()
+        .current_dir(root)
+        .args(["workspace", "deps"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3.10.2          app (dependencies), shared (dependencies)",
+        ));
+}
+
+#[test]
+fn test_build_rejects_unknown_workspace_member() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"app\", \"shared\"]\n",
+    )
+    .unwrap();
+    member(root, "app", "3.9.0");
+    member(root, "shared", "3.9.0");
+
+    kargo_cmd()
+        .current_dir(root.join("app"))
+        .args(["build", "-p", "server"])
+        .assert()
+        .failure()
+        .code(7)
+        .stderr(predicate::str::contains(
+            "package 'server' is not a member of the workspace",
+        ))
+        .stderr(predicate::str::contains("(members: app, shared)"));
+
+    kargo_cmd()
+        .current_dir(root)
+        .args(["build", "--out-json", "-"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("select one member with -p"));
+}
+
+#[test]
+#[ignore = "downloads the Kotlin compiler"]
+fn test_member_calls_code_of_module_dependency() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"b\", \"a\"]\n",
+    )
+    .unwrap();
+    let package = |name: &str, body: &str| {
+        fs::create_dir_all(root.join(name).join("src/main/kotlin")).unwrap();
+        fs::write(
+            root.join(name).join("Kargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n{body}"
+            ),
+        )
+        .unwrap();
+    };
+    package("a", "");
+    fs::write(
+        root.join("a/src/main/kotlin/Greeting.kt"),
+        "package a\n\nfun greeting(name: String) = \"Hello, $name\"\n",
+    )
+    .unwrap();
+    package("b", "[dependencies]\na = { module = \"a\" }\n");
+    fs::write(
+        root.join("b/src/main/kotlin/Main.kt"),
+        "import a.greeting\n\nfun main() {\n    println(greeting(\"b\"))\n}\n",
+    )
+    .unwrap();
+
+    kargo_cmd()
+        .current_dir(root)
+        .arg("build")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("building a, b"));
+
+    kargo_cmd()
+        .current_dir(root.join("b"))
+        .arg("run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hello, b"));
+}
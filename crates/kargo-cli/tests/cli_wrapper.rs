@@ -0,0 +1,183 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[allow(deprecated)]
+fn kargo_cmd() -> Command {
+    Command::cargo_bin("kargo").unwrap()
+}
+
+fn write_manifest(dir: &std::path::Path) {
+    fs::write(
+        dir.join("Kargo.toml"),
+        "[package]\nname = \"wrapped\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_wrapper_generates_scripts_and_version_file() {
+    let tmp = TempDir::new().unwrap();
+    write_manifest(tmp.path());
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["wrapper"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Generated"));
+
+    let sh = fs::read_to_string(tmp.path().join("kargow")).unwrap();
+    assert!(sh.starts_with("#!/bin/sh"));
+    assert!(sh.contains(".kargo-version"));
+
+    let bat = fs::read_to_string(tmp.path().join("kargow.bat")).unwrap();
+    assert!(bat.contains("\r\n"));
+    assert!(bat.contains(".kargo-version"));
+
+    let pinned = fs::read_to_string(tmp.path().join(".kargo-version")).unwrap();
+    assert_eq!(pinned.trim(), env!("CARGO_PKG_VERSION"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(tmp.path().join("kargow"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111, "kargow must be executable");
+    }
+}
+
+#[test]
+fn test_wrapper_pins_explicit_version() {
+    let tmp = TempDir::new().unwrap();
+    write_manifest(tmp.path());
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["wrapper", "--kargo-version", "v0.1.5"])
+        .assert()
+        .success();
+
+    let pinned = fs::read_to_string(tmp.path().join(".kargo-version")).unwrap();
+    assert_eq!(pinned, "0.1.5\n");
+}
+
+#[test]
+fn test_wrapper_rejects_invalid_version() {
+    let tmp = TempDir::new().unwrap();
+    write_manifest(tmp.path());
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["wrapper", "--kargo-version", "latest"])
+        .assert()
+        .failure();
+
+    assert!(!tmp.path().join("kargow").exists());
+}
+
+#[test]
+fn test_wrapper_requires_manifest() {
+    let tmp = TempDir::new().unwrap();
+
+    kargo_cmd()
+        .current_dir(tmp.path())
+        .args(["wrapper"])
+        .assert()
+        .failure();
+}
+
+/// Publish a fake `kargo` release under `mirror` and return the asset path.
+#[cfg(unix)]
+fn publish_fake_release(mirror: &std::path::Path, version: &str) -> std::path::PathBuf {
+    let os = match std::env::consts::OS {
+        "macos" => "apple-darwin",
+        _ => "unknown-linux-gnu",
+    };
+    let arch = match std::env::consts::ARCH {
+        "aarch64" => "aarch64",
+        _ => "x86_64",
+    };
+    let staging = mirror.join("staging");
+    fs::create_dir_all(&staging).unwrap();
+    fs::write(staging.join("kargo"), "#!/bin/sh\necho \"fake kargo $*\"\n").unwrap();
+
+    let release = mirror.join(format!("v{version}"));
+    fs::create_dir_all(&release).unwrap();
+    let asset = release.join(format!("kargo-{arch}-{os}.tar.gz"));
+    let status = std::process::Command::new("tar")
+        .arg("czf")
+        .arg(&asset)
+        .arg("-C")
+        .arg(&staging)
+        .arg("kargo")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    asset
+}
+
+#[cfg(unix)]
+fn run_wrapper(
+    project: &std::path::Path,
+    mirror: &std::path::Path,
+    home: &std::path::Path,
+) -> std::process::Output {
+    std::process::Command::new(project.join("kargow"))
+        .arg("--version")
+        .env(
+            "KARGO_WRAPPER_BASE_URL",
+            format!("file://{}", mirror.display()),
+        )
+        .env("KARGO_HOME", home)
+        .output()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wrapper_verifies_download_checksum() {
+    if std::process::Command::new("curl")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+    let tmp = TempDir::new().unwrap();
+    let project = tmp.path().join("project");
+    let mirror = tmp.path().join("mirror");
+    let home = tmp.path().join("home");
+    fs::create_dir_all(&project).unwrap();
+    write_manifest(&project);
+
+    kargo_cmd()
+        .current_dir(&project)
+        .args(["wrapper", "--kargo-version", "0.1.5"])
+        .assert()
+        .success();
+    let asset = publish_fake_release(&mirror, "0.1.5");
+    let checksum = std::path::PathBuf::from(format!("{}.sha256", asset.display()));
+
+    fs::write(&checksum, format!("{}  asset\n", "0".repeat(64))).unwrap();
+    let out = run_wrapper(&project, &mirror, &home);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("checksum mismatch"));
+    assert!(!home.join("wrapper/0.1.5/kargo").exists());
+
+    let digest = kargo_util::hash::sha256_file(&asset).unwrap();
+    fs::write(&checksum, format!("{digest}  asset\n")).unwrap();
+    let out = run_wrapper(&project, &mirror, &home);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "fake kargo --version\n"
+    );
+}
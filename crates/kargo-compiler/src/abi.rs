@@ -0,0 +1,942 @@
+//! Public ABI dumps in the binary-compatibility-validator `.api` format.
+//!
+//! Reads compiled `.class` files directly and lists every class, field, and
+//! method reachable from outside the module, the way the Kotlin
+//! binary-compatibility-validator does:
+//!
+//! ```text
+//! public final class com/example/Greeter {
+//!     public static final field Companion Lcom/example/Greeter$Companion;
+//!     public fun <init> (Ljava/lang/String;)V
+//!     public final fun greet ()Ljava/lang/String;
+//! }
+//! ```
+//!
+//! (members are indented with a tab in the real files). Kotlin `internal`
+//! and `private` declarations that compile to public bytecode are hidden
+//! using the class's `@kotlin.Metadata` (class visibility and top-level
+//! functions and properties) and the name mangling Kotlin applies to
+//! `internal` members.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use kargo_util::errors::KargoError;
+
+const ACC_PUBLIC: u16 = 0x0001;
+const ACC_PRIVATE: u16 = 0x0002;
+const ACC_PROTECTED: u16 = 0x0004;
+const ACC_STATIC: u16 = 0x0008;
+const ACC_FINAL: u16 = 0x0010;
+const ACC_BRIDGE: u16 = 0x0040;
+const ACC_INTERFACE: u16 = 0x0200;
+const ACC_ABSTRACT: u16 = 0x0400;
+const ACC_SYNTHETIC: u16 = 0x1000;
+const ACC_ANNOTATION: u16 = 0x2000;
+
+/// Kotlin metadata kinds (`@Metadata(k = ...)`).
+const KIND_CLASS: i32 = 1;
+const KIND_FILE_FACADE: i32 = 2;
+const KIND_SYNTHETIC_CLASS: i32 = 3;
+const KIND_MULTI_FILE_CLASS_PART: i32 = 5;
+
+/// Kotlin `Visibility` values stored in metadata flags.
+const KOTLIN_PROTECTED: u64 = 2;
+const KOTLIN_PUBLIC: u64 = 3;
+
+/// A field or method of a class file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub access: u16,
+    pub name: String,
+    pub descriptor: String,
+}
+
+/// The parts of a class file that make up its ABI.
+#[derive(Debug, Clone, Default)]
+pub struct ClassFile {
+    pub access: u16,
+    /// Internal name, e.g. `com/example/Greeter$Companion`.
+    pub name: String,
+    pub super_name: Option<String>,
+    pub interfaces: Vec<String>,
+    pub fields: Vec<Member>,
+    pub methods: Vec<Member>,
+    /// Enclosing class and access flags from this class's own
+    /// `InnerClasses` entry, if it is nested. `None` as the outer class
+    /// marks a local or anonymous class.
+    pub nesting: Option<(Option<String>, u16)>,
+    pub kotlin: Option<KotlinMetadata>,
+}
+
+/// What Kargo reads from `@kotlin.Metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KotlinMetadata {
+    pub kind: i32,
+    /// Kotlin visibility of a class (`k = 1`), from its flags.
+    pub visibility: Option<u64>,
+    /// Top-level functions and properties that are not public.
+    pub hidden: BTreeSet<String>,
+}
+
+impl ClassFile {
+    /// Parse a `.class` file.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(bytes);
+        if r.u32()? != 0xCAFE_BABE {
+            return None;
+        }
+        r.skip(4)?;
+        let pool = ConstantPool::parse(&mut r)?;
+
+        let mut class = ClassFile {
+            access: r.u16()?,
+            ..Default::default()
+        };
+        class.name = pool.class_name(r.u16()?)?;
+        let super_index = r.u16()?;
+        class.super_name = (super_index != 0)
+            .then(|| pool.class_name(super_index))
+            .flatten();
+        for _ in 0..r.u16()? {
+            class.interfaces.push(pool.class_name(r.u16()?)?);
+        }
+        for methods in [false, true] {
+            for _ in 0..r.u16()? {
+                let member = Member {
+                    access: r.u16()?,
+                    name: pool.utf8(r.u16()?)?,
+                    descriptor: pool.utf8(r.u16()?)?,
+                };
+                for _ in 0..r.u16()? {
+                    r.skip(2)?;
+                    let len = r.u32()? as usize;
+                    r.skip(len)?;
+                }
+                if methods {
+                    class.methods.push(member);
+                } else {
+                    class.fields.push(member);
+                }
+            }
+        }
+        for _ in 0..r.u16()? {
+            let name = pool.utf8(r.u16()?)?;
+            let len = r.u32()? as usize;
+            let mut attr = Reader::new(r.bytes(len)?);
+            match name.as_str() {
+                "InnerClasses" => {
+                    for _ in 0..attr.u16()? {
+                        let inner = attr.u16()?;
+                        let outer = attr.u16()?;
+                        attr.skip(2)?;
+                        let flags = attr.u16()?;
+                        if inner != 0 && pool.class_name(inner)? == class.name {
+                            let outer = (outer != 0).then(|| pool.class_name(outer)).flatten();
+                            class.nesting = Some((outer, flags));
+                        }
+                    }
+                }
+                "RuntimeVisibleAnnotations" => {
+                    class.kotlin = read_kotlin_metadata(&mut attr, &pool);
+                }
+                _ => {}
+            }
+        }
+        Some(class)
+    }
+
+    fn is_file_facade(&self) -> bool {
+        self.kotlin
+            .as_ref()
+            .is_some_and(|k| matches!(k.kind, KIND_FILE_FACADE | KIND_MULTI_FILE_CLASS_PART))
+    }
+
+    /// Own visibility, ignoring the enclosing classes.
+    fn is_visible(&self) -> bool {
+        let access = match self.nesting {
+            Some((None, _)) => return false,
+            Some((Some(_), flags)) => flags,
+            None => self.access,
+        };
+        if access & (ACC_PUBLIC | ACC_PROTECTED) == 0 || access & ACC_SYNTHETIC != 0 {
+            return false;
+        }
+        match &self.kotlin {
+            Some(k) if k.kind == KIND_SYNTHETIC_CLASS => false,
+            Some(k) => k
+                .visibility
+                .map_or(true, |v| v == KOTLIN_PUBLIC || v == KOTLIN_PROTECTED),
+            None => true,
+        }
+    }
+
+    fn is_api_member(&self, member: &Member, is_method: bool) -> bool {
+        let extendable = self.access & ACC_FINAL == 0;
+        let visible =
+            member.access & ACC_PUBLIC != 0 || (member.access & ACC_PROTECTED != 0 && extendable);
+        if !visible || (is_method && member.access & ACC_BRIDGE != 0) {
+            return false;
+        }
+        // `$default` overloads are synthetic but callable from other
+        // modules; any other `$` marks a mangled `internal` member or a
+        // compiler-generated accessor.
+        let name = member.name.strip_suffix("$default").unwrap_or(&member.name);
+        if name.contains('$')
+            || (member.access & ACC_SYNTHETIC != 0 && name.len() == member.name.len())
+        {
+            return false;
+        }
+        match &self.kotlin {
+            Some(k) if self.is_file_facade() => !k.hidden.contains(name),
+            _ => true,
+        }
+    }
+}
+
+/// Render the `.api` dump for a set of classes.
+pub fn render(classes: &[ClassFile]) -> String {
+    let by_name: BTreeMap<&str, &ClassFile> =
+        classes.iter().map(|c| (c.name.as_str(), c)).collect();
+    let effectively_visible = |class: &ClassFile| {
+        let mut current = class;
+        let mut depth = 0;
+        loop {
+            if !current.is_visible() {
+                return false;
+            }
+            match &current.nesting {
+                Some((Some(outer), _)) if depth < 64 => match by_name.get(outer.as_str()) {
+                    Some(next) => {
+                        current = next;
+                        depth += 1;
+                    }
+                    None => return true,
+                },
+                _ => return true,
+            }
+        }
+    };
+
+    let mut out = String::new();
+    for class in by_name.values() {
+        if !effectively_visible(class) {
+            continue;
+        }
+        let mut fields: Vec<&Member> = class
+            .fields
+            .iter()
+            .filter(|f| class.is_api_member(f, false))
+            .collect();
+        let mut methods: Vec<&Member> = class
+            .methods
+            .iter()
+            .filter(|m| class.is_api_member(m, true))
+            .collect();
+        if class.is_file_facade() && fields.is_empty() && methods.is_empty() {
+            continue;
+        }
+        fields.sort_by(|a, b| (&a.name, &a.descriptor).cmp(&(&b.name, &b.descriptor)));
+        methods.sort_by(|a, b| (&a.name, &a.descriptor).cmp(&(&b.name, &b.descriptor)));
+
+        let access = class.nesting.as_ref().map_or(class.access, |(_, f)| *f);
+        let mut header = modifiers(access, false);
+        header.push(if access & ACC_ANNOTATION != 0 {
+            "interface annotation class"
+        } else if access & ACC_INTERFACE != 0 {
+            "interface"
+        } else {
+            "class"
+        });
+        out.push_str(&format!("{} {}", header.join(" "), class.name));
+        let supertypes: Vec<&str> = class
+            .super_name
+            .iter()
+            .filter(|s| *s != "java/lang/Object")
+            .chain(&class.interfaces)
+            .map(String::as_str)
+            .collect();
+        if !supertypes.is_empty() {
+            out.push_str(&format!(" : {}", supertypes.join(", ")));
+        }
+        out.push_str(" {\n");
+        for field in fields {
+            out.push_str(&format!(
+                "\t{} field {} {}\n",
+                modifiers(field.access, true).join(" "),
+                field.name,
+                field.descriptor
+            ));
+        }
+        for method in methods {
+            out.push_str(&format!(
+                "\t{} fun {} {}\n",
+                modifiers(method.access, true).join(" "),
+                method.name,
+                method.descriptor
+            ));
+        }
+        out.push_str("}\n\n");
+    }
+    out
+}
+
+/// Parse every `.class` file under `classes_dir` and render the dump.
+/// `module-info` and `META-INF/versions/` classes are skipped.
+pub fn dump_dir(classes_dir: &Path) -> miette::Result<String> {
+    let mut classes = Vec::new();
+    collect_classes(classes_dir, classes_dir, &mut classes)?;
+    Ok(render(&classes))
+}
+
+fn collect_classes(root: &Path, dir: &Path, classes: &mut Vec<ClassFile>) -> miette::Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if path != root.join("META-INF") {
+                collect_classes(root, &path, classes)?;
+            }
+            continue;
+        }
+        if !path.extension().is_some_and(|e| e == "class")
+            || path.file_stem().is_some_and(|s| s == "module-info")
+        {
+            continue;
+        }
+        let bytes = std::fs::read(&path).map_err(KargoError::Io)?;
+        let class = ClassFile::parse(&bytes).ok_or_else(|| KargoError::Generic {
+            message: format!("{} is not a valid class file", path.display()),
+        })?;
+        classes.push(class);
+    }
+    Ok(())
+}
+
+fn modifiers(access: u16, member: bool) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    if access & ACC_PUBLIC != 0 {
+        out.push("public");
+    } else if access & ACC_PROTECTED != 0 {
+        out.push("protected");
+    } else if access & ACC_PRIVATE != 0 {
+        out.push("private");
+    }
+    if member && access & ACC_STATIC != 0 {
+        out.push("static");
+    }
+    if access & ACC_FINAL != 0 {
+        out.push("final");
+    }
+    if access & ACC_ABSTRACT != 0 {
+        out.push("abstract");
+    }
+    if member && access & ACC_SYNTHETIC != 0 {
+        out.push("synthetic");
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Class file reading
+// ---------------------------------------------------------------------------
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(slice)
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.bytes(n).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let b = self.bytes(2)?;
+        Some(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let b = self.bytes(4)?;
+        Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+enum Constant {
+    Utf8(Vec<u16>),
+    Class(u16),
+    Integer(i32),
+    Other,
+}
+
+struct ConstantPool(Vec<Constant>);
+
+impl ConstantPool {
+    fn parse(r: &mut Reader<'_>) -> Option<Self> {
+        let count = r.u16()? as usize;
+        let mut entries = vec![Constant::Other];
+        while entries.len() < count {
+            let tag = r.u8()?;
+            let entry = match tag {
+                1 => {
+                    let len = r.u16()? as usize;
+                    Constant::Utf8(decode_modified_utf8(r.bytes(len)?))
+                }
+                7 => Constant::Class(r.u16()?),
+                3 => Constant::Integer(r.u32()? as i32),
+                4 | 9 | 10 | 11 | 12 | 17 | 18 => {
+                    r.skip(4)?;
+                    Constant::Other
+                }
+                5 | 6 => {
+                    // Longs and doubles take two slots.
+                    r.skip(8)?;
+                    entries.push(Constant::Other);
+                    Constant::Other
+                }
+                8 | 16 | 19 | 20 => {
+                    r.skip(2)?;
+                    Constant::Other
+                }
+                15 => {
+                    r.skip(3)?;
+                    Constant::Other
+                }
+                _ => return None,
+            };
+            entries.push(entry);
+        }
+        Some(Self(entries))
+    }
+
+    fn units(&self, index: u16) -> Option<&[u16]> {
+        match self.0.get(index as usize)? {
+            Constant::Utf8(units) => Some(units),
+            _ => None,
+        }
+    }
+
+    fn utf8(&self, index: u16) -> Option<String> {
+        self.units(index).map(String::from_utf16_lossy)
+    }
+
+    fn class_name(&self, index: u16) -> Option<String> {
+        match self.0.get(index as usize)? {
+            Constant::Class(name) => self.utf8(*name),
+            _ => None,
+        }
+    }
+
+    fn integer(&self, index: u16) -> Option<i32> {
+        match self.0.get(index as usize)? {
+            Constant::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the JVM's modified UTF-8 into UTF-16 code units.
+fn decode_modified_utf8(bytes: &[u8]) -> Vec<u16> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let cont = |k: usize| bytes.get(i + k).map_or(0, |c| *c as u16 & 0x3F);
+        if b & 0x80 == 0 {
+            out.push(b);
+            i += 1;
+        } else if b & 0xE0 == 0xC0 {
+            out.push(((b & 0x1F) << 6) | cont(1));
+            i += 2;
+        } else if b & 0xF0 == 0xE0 {
+            out.push(((b & 0x0F) << 12) | (cont(1) << 6) | cont(2));
+            i += 3;
+        } else {
+            out.push(0xFFFD);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Find `@kotlin.Metadata` in a `RuntimeVisibleAnnotations` attribute.
+fn read_kotlin_metadata(r: &mut Reader<'_>, pool: &ConstantPool) -> Option<KotlinMetadata> {
+    let mut found = None;
+    for _ in 0..r.u16()? {
+        let ty = pool.utf8(r.u16()?)?;
+        let mut kind = None;
+        let mut d1: Vec<u16> = Vec::new();
+        let mut d2: Vec<String> = Vec::new();
+        for _ in 0..r.u16()? {
+            let name = pool.utf8(r.u16()?)?;
+            let mut strings = Vec::new();
+            let int = read_element_value(r, pool, &mut strings)?;
+            match name.as_str() {
+                "k" => kind = int,
+                "d1" => d1 = strings.concat(),
+                "d2" => {
+                    d2 = strings
+                        .iter()
+                        .map(|s| String::from_utf16_lossy(s))
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        if ty == "Lkotlin/Metadata;" {
+            let kind = kind.unwrap_or(KIND_CLASS);
+            found = Some(decode_kotlin_metadata(kind, &d1, &d2));
+        }
+    }
+    found
+}
+
+/// Read one annotation element value, collecting string constants into
+/// `strings` and returning an `int` constant.
+fn read_element_value(
+    r: &mut Reader<'_>,
+    pool: &ConstantPool,
+    strings: &mut Vec<Vec<u16>>,
+) -> Option<Option<i32>> {
+    match r.u8()? {
+        b'I' => Some(pool.integer(r.u16()?)),
+        b's' => {
+            strings.push(pool.units(r.u16()?)?.to_vec());
+            Some(None)
+        }
+        b'B' | b'C' | b'D' | b'F' | b'J' | b'S' | b'Z' | b'c' => {
+            r.skip(2)?;
+            Some(None)
+        }
+        b'e' => {
+            r.skip(4)?;
+            Some(None)
+        }
+        b'@' => {
+            r.skip(2)?;
+            for _ in 0..r.u16()? {
+                r.skip(2)?;
+                read_element_value(r, pool, &mut Vec::new())?;
+            }
+            Some(None)
+        }
+        b'[' => {
+            for _ in 0..r.u16()? {
+                read_element_value(r, pool, strings)?;
+            }
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
+fn decode_kotlin_metadata(kind: i32, d1: &[u16], d2: &[String]) -> KotlinMetadata {
+    let mut metadata = KotlinMetadata {
+        kind,
+        ..Default::default()
+    };
+    // Only the UTF-8 mode of Kotlin's bit encoding (marked by a leading
+    // NUL, used by every Kotlin 1.4+ compiler) is understood.
+    if d1.first() != Some(&0) {
+        return metadata;
+    }
+    let bytes: Vec<u8> = d1[1..].iter().map(|u| *u as u8).collect();
+    let mut proto = Proto::new(&bytes);
+    // A length-delimited string table precedes the class or package message.
+    let Some(len) = proto.varint() else {
+        return metadata;
+    };
+    if proto.skip(len as usize).is_none() {
+        return metadata;
+    }
+
+    let visibility = |flags: u64| (flags >> 1) & 7;
+    match kind {
+        KIND_CLASS => {
+            let mut flags = 6;
+            while let Some((field, value)) = proto.field() {
+                if let (1, ProtoValue::Varint(v)) = (field, value) {
+                    flags = v;
+                }
+            }
+            metadata.visibility = Some(visibility(flags));
+        }
+        KIND_FILE_FACADE | KIND_MULTI_FILE_CLASS_PART => {
+            while let Some((field, value)) = proto.field() {
+                let ProtoValue::Bytes(message) = value else {
+                    continue;
+                };
+                // Package.function = 3 (flags = 9), Package.property = 4
+                // (flags = 11); both keep their name index in field 2.
+                let (flags_field, default_flags) = match field {
+                    3 => (9, 6),
+                    4 => (11, 518),
+                    _ => continue,
+                };
+                let mut flags = default_flags;
+                let mut name = None;
+                let mut declaration = Proto::new(message);
+                while let Some((f, v)) = declaration.field() {
+                    match (f, v) {
+                        (f, ProtoValue::Varint(v)) if f == flags_field => flags = v,
+                        (2, ProtoValue::Varint(v)) => name = d2.get(v as usize),
+                        _ => {}
+                    }
+                }
+                if let Some(name) = name.filter(|_| visibility(flags) != KOTLIN_PUBLIC) {
+                    metadata.hidden.insert(name.clone());
+                    if field == 4 {
+                        metadata.hidden.extend(accessor_names(name));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    metadata
+}
+
+/// JVM getter and setter names of a Kotlin property.
+fn accessor_names(property: &str) -> Vec<String> {
+    if let Some(rest) = property
+        .strip_prefix("is")
+        .filter(|r| r.starts_with(|c: char| !c.is_lowercase()))
+    {
+        return vec![format!("set{rest}")];
+    }
+    let mut chars = property.chars();
+    let capitalized = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => return Vec::new(),
+    };
+    vec![format!("get{capitalized}"), format!("set{capitalized}")]
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Minimal protobuf wire-format reader for Kotlin metadata.
+struct Proto<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Proto<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        let end = self.pos.checked_add(n)?;
+        if end > self.data.len() {
+            return None;
+        }
+        self.pos = end;
+        Some(())
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn field(&mut self) -> Option<(u64, ProtoValue<'a>)> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => ProtoValue::Varint(self.varint()?),
+            1 => {
+                self.skip(8)?;
+                ProtoValue::Fixed
+            }
+            2 => {
+                let len = self.varint()? as usize;
+                let start = self.pos;
+                self.skip(len)?;
+                ProtoValue::Bytes(&self.data[start..start + len])
+            }
+            5 => {
+                self.skip(4)?;
+                ProtoValue::Fixed
+            }
+            _ => return None,
+        };
+        Some((key >> 3, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(access: u16, name: &str, descriptor: &str) -> Member {
+        Member {
+            access,
+            name: name.into(),
+            descriptor: descriptor.into(),
+        }
+    }
+
+    fn class(access: u16, name: &str) -> ClassFile {
+        ClassFile {
+            access,
+            name: name.into(),
+            super_name: Some("java/lang/Object".into()),
+            ..Default::default()
+        }
+    }
+
+    /// Assemble a minimal class file: no fields, the given methods, and an
+    /// optional `@kotlin.Metadata(k, d1, d2)`.
+    fn class_bytes(
+        name: &str,
+        methods: &[(u16, &str, &str)],
+        kotlin: Option<(i32, &[u8], &[&str])>,
+    ) -> Vec<u8> {
+        let mut pool: Vec<Vec<u8>> = Vec::new();
+        let utf8 = |pool: &mut Vec<Vec<u8>>, s: &[u8]| {
+            let mut e = vec![1];
+            e.extend((s.len() as u16).to_be_bytes());
+            e.extend(s);
+            pool.push(e);
+            pool.len() as u16
+        };
+        let this_name = utf8(&mut pool, name.as_bytes());
+        pool.push([vec![7], this_name.to_be_bytes().to_vec()].concat());
+        let this_class = pool.len() as u16;
+        let object_name = utf8(&mut pool, b"java/lang/Object");
+        pool.push([vec![7], object_name.to_be_bytes().to_vec()].concat());
+        let object_class = pool.len() as u16;
+
+        let mut methods_out = Vec::new();
+        for (access, m, d) in methods {
+            let n = utf8(&mut pool, m.as_bytes());
+            let d = utf8(&mut pool, d.as_bytes());
+            methods_out.extend(access.to_be_bytes());
+            methods_out.extend(n.to_be_bytes());
+            methods_out.extend(d.to_be_bytes());
+            methods_out.extend(0u16.to_be_bytes());
+        }
+
+        let mut attributes = Vec::new();
+        if let Some((k, d1, d2)) = kotlin {
+            let attr_name = utf8(&mut pool, b"RuntimeVisibleAnnotations");
+            let ty = utf8(&mut pool, b"Lkotlin/Metadata;");
+            let k_name = utf8(&mut pool, b"k");
+            pool.push([vec![3], k.to_be_bytes().to_vec()].concat());
+            let k_value = pool.len() as u16;
+            let d1_name = utf8(&mut pool, b"d1");
+            // UTF-8 mode: a NUL marker, then each byte as a char. NUL is
+            // written as the two-byte form, bytes >= 0x80 as two bytes.
+            let mut encoded = vec![0xC0, 0x80];
+            for b in d1 {
+                match *b {
+                    0 => encoded.extend([0xC0, 0x80]),
+                    b if b < 0x80 => encoded.push(b),
+                    b => encoded.extend([0xC0 | (b >> 6), 0x80 | (b & 0x3F)]),
+                }
+            }
+            let d1_value = utf8(&mut pool, &encoded);
+            let d2_name = utf8(&mut pool, b"d2");
+            let d2_values: Vec<u16> = d2.iter().map(|s| utf8(&mut pool, s.as_bytes())).collect();
+
+            let mut ann = Vec::new();
+            ann.extend(1u16.to_be_bytes());
+            ann.extend(ty.to_be_bytes());
+            ann.extend(3u16.to_be_bytes());
+            ann.extend(k_name.to_be_bytes());
+            ann.push(b'I');
+            ann.extend(k_value.to_be_bytes());
+            ann.extend(d1_name.to_be_bytes());
+            ann.push(b'[');
+            ann.extend(1u16.to_be_bytes());
+            ann.push(b's');
+            ann.extend(d1_value.to_be_bytes());
+            ann.extend(d2_name.to_be_bytes());
+            ann.push(b'[');
+            ann.extend((d2_values.len() as u16).to_be_bytes());
+            for v in d2_values {
+                ann.push(b's');
+                ann.extend(v.to_be_bytes());
+            }
+            attributes.extend(attr_name.to_be_bytes());
+            attributes.extend((ann.len() as u32).to_be_bytes());
+            attributes.extend(ann);
+        }
+
+        let mut out = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 65];
+        out.extend(((pool.len() + 1) as u16).to_be_bytes());
+        for e in pool {
+            out.extend(e);
+        }
+        out.extend((ACC_PUBLIC | ACC_FINAL).to_be_bytes());
+        out.extend(this_class.to_be_bytes());
+        out.extend(object_class.to_be_bytes());
+        out.extend(0u16.to_be_bytes());
+        out.extend(0u16.to_be_bytes());
+        out.extend((methods.len() as u16).to_be_bytes());
+        out.extend(methods_out);
+        out.extend(u16::from(kotlin.is_some()).to_be_bytes());
+        out.extend(attributes);
+        out
+    }
+
+    /// Protobuf for an empty string table followed by `message`.
+    fn metadata_proto(message: &[u8]) -> Vec<u8> {
+        [vec![0], message.to_vec()].concat()
+    }
+
+    #[test]
+    fn renders_bcv_format() {
+        let mut greeter = class(ACC_PUBLIC | ACC_FINAL, "com/example/Greeter");
+        greeter.interfaces = vec!["java/io/Serializable".into()];
+        greeter.fields = vec![
+            member(
+                ACC_PUBLIC | ACC_STATIC | ACC_FINAL,
+                "Companion",
+                "Lcom/example/Greeter$Companion;",
+            ),
+            member(ACC_PRIVATE | ACC_FINAL, "name", "Ljava/lang/String;"),
+        ];
+        greeter.methods = vec![
+            member(ACC_PUBLIC | ACC_FINAL, "greet", "()Ljava/lang/String;"),
+            member(ACC_PUBLIC, "<init>", "(Ljava/lang/String;)V"),
+            member(ACC_PUBLIC | ACC_FINAL, "greet$app", "()V"),
+            member(
+                ACC_PUBLIC | ACC_STATIC | ACC_SYNTHETIC,
+                "greet$default",
+                "(Lcom/example/Greeter;ILjava/lang/Object;)Ljava/lang/String;",
+            ),
+            member(
+                ACC_PUBLIC | ACC_STATIC | ACC_SYNTHETIC,
+                "access$getName$p",
+                "(Lcom/example/Greeter;)Ljava/lang/String;",
+            ),
+            member(ACC_PROTECTED, "hook", "()V"),
+        ];
+        let hidden = class(ACC_FINAL, "com/example/PackagePrivate");
+
+        assert_eq!(
+            render(&[greeter, hidden]),
+            "public final class com/example/Greeter : java/io/Serializable {\n\
+             \tpublic static final field Companion Lcom/example/Greeter$Companion;\n\
+             \tpublic fun <init> (Ljava/lang/String;)V\n\
+             \tpublic final fun greet ()Ljava/lang/String;\n\
+             \tpublic static synthetic fun greet$default (Lcom/example/Greeter;ILjava/lang/Object;)Ljava/lang/String;\n\
+             }\n\n"
+        );
+    }
+
+    #[test]
+    fn nested_classes_follow_their_outer_class() {
+        let outer = class(ACC_PUBLIC, "a/Outer");
+        let mut protected_inner = class(ACC_PUBLIC | ACC_ABSTRACT | ACC_INTERFACE, "a/Outer$Api");
+        protected_inner.nesting = Some((
+            Some("a/Outer".into()),
+            ACC_PROTECTED | ACC_STATIC | ACC_ABSTRACT | ACC_INTERFACE,
+        ));
+        let mut private_inner = class(ACC_FINAL, "a/Outer$Impl");
+        private_inner.nesting = Some((Some("a/Outer".into()), ACC_PRIVATE | ACC_FINAL));
+        let mut inside_private = class(ACC_PUBLIC | ACC_FINAL, "a/Outer$Impl$Deep");
+        inside_private.nesting = Some((Some("a/Outer$Impl".into()), ACC_PUBLIC | ACC_FINAL));
+        let mut anonymous = class(ACC_PUBLIC | ACC_FINAL, "a/Outer$1");
+        anonymous.nesting = Some((None, ACC_FINAL));
+
+        let dump = render(&[
+            outer,
+            protected_inner,
+            private_inner,
+            inside_private,
+            anonymous,
+        ]);
+        assert_eq!(
+            dump,
+            "public class a/Outer {\n}\n\n\
+             protected abstract interface a/Outer$Api {\n}\n\n"
+        );
+    }
+
+    #[test]
+    fn kotlin_metadata_hides_internal_declarations() {
+        // Class flags 0b000 << 1: internal class.
+        let internal = class_bytes(
+            "a/Internal",
+            &[],
+            Some((KIND_CLASS, &metadata_proto(&[0x08, 0x00]), &[])),
+        );
+        // File facade with a public `api` function and an internal
+        // `helper` function and `counter` property.
+        let mut package = Vec::new();
+        for (field, flags_field, flags, name) in [(3u8, 9u8, 6u8, 0u8), (3, 9, 0, 1), (4, 11, 0, 2)]
+        {
+            let message = [flags_field << 3, flags, 2 << 3, name];
+            package.extend([(field << 3) | 2, message.len() as u8]);
+            package.extend(message);
+        }
+        let facade = class_bytes(
+            "a/UtilsKt",
+            &[
+                (ACC_PUBLIC | ACC_STATIC | ACC_FINAL, "api", "()V"),
+                (ACC_PUBLIC | ACC_STATIC | ACC_FINAL, "helper", "()V"),
+                (ACC_PUBLIC | ACC_STATIC | ACC_FINAL, "getCounter", "()I"),
+            ],
+            Some((
+                KIND_FILE_FACADE,
+                &metadata_proto(&package),
+                &["api", "helper", "counter"],
+            )),
+        );
+        let lambda = class_bytes(
+            "a/UtilsKt$run$1",
+            &[],
+            Some((KIND_SYNTHETIC_CLASS, &[], &[])),
+        );
+
+        let classes: Vec<ClassFile> = [internal, facade, lambda]
+            .iter()
+            .map(|b| ClassFile::parse(b).unwrap())
+            .collect();
+        assert_eq!(classes[0].kotlin.as_ref().unwrap().visibility, Some(0));
+        assert_eq!(
+            render(&classes),
+            "public final class a/UtilsKt {\n\
+             \tpublic static final fun api ()V\n\
+             }\n\n"
+        );
+    }
+
+    #[test]
+    fn accessor_names_follow_kotlin_conventions() {
+        assert_eq!(accessor_names("counter"), ["getCounter", "setCounter"]);
+        assert_eq!(accessor_names("isEnabled"), ["setEnabled"]);
+    }
+}
@@ -26,15 +26,26 @@ pub struct BuildCache {
 impl BuildCache {
     /// Create a build cache at the default or configured location.
     ///
-    /// `root` is typically `~/.kargo/build-cache/`.
+    /// `root` is typically [`default_path`](Self::default_path).
     pub fn new(root: PathBuf, max_size_str: Option<&str>) -> Self {
         let max_bytes = parse_size(max_size_str.unwrap_or("10GB"));
         Self { root, max_bytes }
     }
 
-    /// Default cache path: `~/.kargo/build-cache/`.
+    /// Default cache path: `build-cache/` in the Kargo cache directory.
     pub fn default_path() -> PathBuf {
-        kargo_util::dirs_path().join("build-cache")
+        kargo_util::cache_path().join("build-cache")
+    }
+
+    /// The directory holding the cache entries.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The entry stored under a fingerprint hash, if any, without marking
+    /// it as used.
+    pub fn entry(&self, hash: &str) -> Option<PathBuf> {
+        Some(self.root.join(hash)).filter(|dir| dir.is_dir())
     }
 
     /// Check if a cached build exists for the given fingerprint.
@@ -0,0 +1,182 @@
+//! kotlinc performance reports (`-Xdump-perf`).
+//!
+//! With `-Xdump-perf=<file>` the JVM compiler writes how long each of its
+//! internal phases took, one line per phase:
+//!
+//! ```text
+//! INIT: Compiler initialized in 466 ms
+//! ANALYZE: 120 files (8412 lines) in 6104 ms - 1378.1 loc/s
+//! IR LOWERING: 120 files (8412 lines) in 2010 ms - 4185.1 loc/s
+//! BACKEND: 120 files (8412 lines) in 3120 ms - 2696.2 loc/s
+//! ```
+//!
+//! (older compilers prefix each line with `PERF:`). `kargo build --timings`
+//! requests one report per compilation unit and merges the phases into its
+//! timing breakdown.
+
+use std::path::{Path, PathBuf};
+
+use crate::unit::CompilationUnit;
+
+/// Flag prefix that makes kotlinc write a performance report.
+pub const DUMP_PERF_FLAG: &str = "-Xdump-perf=";
+
+/// One compiler-internal phase of a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerfPhase {
+    /// Kargo's name for the phase: `init`, `frontend`, `ir translation`,
+    /// `ir lowering`, `backend`, or the compiler's own label lowercased.
+    pub name: String,
+    pub millis: u64,
+    pub files: Option<u32>,
+    pub lines: Option<u32>,
+}
+
+/// The phases kotlinc reported for one compilation unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerfReport {
+    pub unit: String,
+    pub phases: Vec<PerfPhase>,
+}
+
+/// Where kotlinc writes the report for `unit_name` inside `reports_dir`.
+pub fn report_path(reports_dir: &Path, unit_name: &str) -> PathBuf {
+    reports_dir.join(format!("{unit_name}.txt"))
+}
+
+/// A copy of `unit` that also writes a performance report into
+/// `reports_dir`. The flag is kept off the original unit so that asking for
+/// timings does not change its fingerprint.
+pub fn with_report(unit: &CompilationUnit, reports_dir: &Path) -> CompilationUnit {
+    let mut unit = unit.clone();
+    unit.compiler_args
+        .retain(|a| !a.starts_with(DUMP_PERF_FLAG));
+    unit.compiler_args.push(format!(
+        "{DUMP_PERF_FLAG}{}",
+        report_path(reports_dir, &unit.name).display()
+    ));
+    unit
+}
+
+/// Point an existing `-Xdump-perf` flag in `args` at the report file for
+/// `unit_name` in the same directory. Used when one unit is compiled as
+/// several, so the parts do not overwrite each other's reports.
+pub fn retarget(args: &mut [String], unit_name: &str) {
+    let Some(pos) = args.iter().position(|a| a.starts_with(DUMP_PERF_FLAG)) else {
+        return;
+    };
+    let old = PathBuf::from(&args[pos][DUMP_PERF_FLAG.len()..]);
+    let dir = old.parent().map(Path::to_path_buf).unwrap_or_default();
+    args[pos] = format!("{DUMP_PERF_FLAG}{}", report_path(&dir, unit_name).display());
+}
+
+/// Read every report in `reports_dir`, sorted by unit name.
+pub fn read_dir(reports_dir: &Path) -> Vec<PerfReport> {
+    let Ok(entries) = std::fs::read_dir(reports_dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<PerfReport> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "txt"))
+        .filter_map(|p| {
+            let text = std::fs::read_to_string(&p).ok()?;
+            let unit = p.file_stem()?.to_string_lossy().into_owned();
+            Some(PerfReport {
+                unit,
+                phases: parse(&text),
+            })
+        })
+        .filter(|r| !r.phases.is_empty())
+        .collect();
+    reports.sort_by(|a, b| a.unit.cmp(&b.unit));
+    reports
+}
+
+/// Parse the phase lines of a report; other lines are ignored.
+pub fn parse(text: &str) -> Vec<PerfPhase> {
+    text.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<PerfPhase> {
+    let line = line.trim();
+    let line = line.strip_prefix("PERF:").map(str::trim).unwrap_or(line);
+    let (label, rest) = line.split_once(':')?;
+    let (counts, time) = rest.rsplit_once(" in ")?;
+    let millis: u64 = time.split_whitespace().next()?.parse().ok()?;
+    if !time.split_whitespace().nth(1).is_some_and(|u| u == "ms") {
+        return None;
+    }
+
+    // "120 files (8412 lines)"
+    let mut words = counts.split_whitespace();
+    let (files, lines) = match (words.next(), words.next(), words.next()) {
+        (Some(f), Some("files"), Some(l)) => {
+            (f.parse().ok(), l.trim_start_matches('(').parse().ok())
+        }
+        _ => (None, None),
+    };
+
+    let name = match label.trim() {
+        "INIT" => "init".to_string(),
+        "ANALYZE" => "frontend".to_string(),
+        "TRANSLATION to IR" | "IR TRANSLATION" => "ir translation".to_string(),
+        "IR LOWERING" => "ir lowering".to_string(),
+        "BACKEND" | "GENERATE" | "IR GENERATION" => "backend".to_string(),
+        other => other.to_lowercase(),
+    };
+    Some(PerfPhase {
+        name,
+        millis,
+        files,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_k1_and_k2_reports() {
+        let report = parse(
+            "PERF: INIT: Compiler initialized in 466 ms\n\
+             PERF: ANALYZE: 120 files (8412 lines) in 6104 ms - 1378.1 loc/s\n\
+             TRANSLATION to IR: 120 files (8412 lines) in 180 ms - 46733.3 loc/s\n\
+             IR LOWERING: 120 files (8412 lines) in 2010 ms - 4185.1 loc/s\n\
+             BACKEND: 120 files (8412 lines) in 3120 ms - 2696.2 loc/s\n\
+             GC time for G1 Young Generation is 40 ms, 3 collections\n",
+        );
+        let names: Vec<&str> = report.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "init",
+                "frontend",
+                "ir translation",
+                "ir lowering",
+                "backend"
+            ]
+        );
+        assert_eq!(report[1].millis, 6104);
+        assert_eq!(report[1].files, Some(120));
+        assert_eq!(report[1].lines, Some(8412));
+        assert_eq!(report[0].files, None);
+    }
+
+    #[test]
+    fn retargets_the_report_per_unit() {
+        let mut args = vec![
+            "-Xopt-in=kotlin.RequiresOptIn".to_string(),
+            format!("{DUMP_PERF_FLAG}/b/reports/main.txt"),
+        ];
+        retarget(&mut args, "main-2");
+        assert_eq!(
+            args[1],
+            format!(
+                "{DUMP_PERF_FLAG}{}",
+                Path::new("/b/reports").join("main-2.txt").display()
+            )
+        );
+    }
+}
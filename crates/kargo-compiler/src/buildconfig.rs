@@ -1,11 +1,15 @@
 //! BuildConfig.kt code generation from manifest, flavor, and profile data.
 //!
 //! Generates a Kotlin `object BuildConfig` with compile-time constants
-//! derived from the project configuration.
+//! derived from the project configuration. The output depends only on its
+//! inputs: built-in fields come first in a fixed order, then custom fields
+//! sorted by name, then nested objects sorted by name.
 
 use std::collections::BTreeMap;
 use std::path::Path;
 
+use kargo_core::build_config::{self, BuildConfigField};
+use kargo_core::target::KotlinTarget;
 use kargo_util::errors::KargoError;
 
 /// Generate a `BuildConfig.kt` file in the given directory.
@@ -13,18 +17,21 @@ use kargo_util::errors::KargoError;
 /// `kotlin_package` is the Kotlin package for the generated file
 /// (e.g. `"com.example"`). If `None`, no package declaration is emitted.
 ///
-/// `custom_fields` contains user-defined key-value pairs from the
-/// `[build-config]` section in `Kargo.toml` and/or flavor build-config.
-/// Values support `${env:VAR}` interpolation (resolved before this
-/// function is called, during manifest loading).
+/// `custom_fields` contains the resolved entries of `[build-config]`
+/// merged with target and flavor build-config. Strings become `String`
+/// constants, numbers `Int` or `Long`, lists `List<String>`, and nested
+/// tables nested objects. `from-env` fields become properties that read the
+/// environment of the running program in the way `target` supports.
+#[allow(clippy::too_many_arguments)]
 pub fn generate(
     output_dir: &Path,
     kotlin_package: Option<&str>,
+    target: KotlinTarget,
     package_name: &str,
     version: &str,
     profile: &str,
     debug: bool,
-    custom_fields: &BTreeMap<String, String>,
+    custom_fields: &BTreeMap<String, BuildConfigField>,
 ) -> miette::Result<std::path::PathBuf> {
     std::fs::create_dir_all(output_dir).map_err(KargoError::Io)?;
 
@@ -32,6 +39,12 @@ pub fn generate(
     lines.push("// Auto-generated by Kargo. Do not edit.".to_string());
     lines.push(String::new());
 
+    let native_env = target.is_native() && build_config::uses_env(custom_fields);
+    if native_env {
+        lines.push("@file:OptIn(kotlinx.cinterop.ExperimentalForeignApi::class)".to_string());
+        lines.push(String::new());
+    }
+
     if let Some(pkg) = kotlin_package {
         if !pkg.is_empty() {
             lines.push(format!("package {pkg}"));
@@ -39,6 +52,11 @@ pub fn generate(
         }
     }
 
+    if native_env {
+        lines.push("import kotlinx.cinterop.toKString".to_string());
+        lines.push(String::new());
+    }
+
     lines.push("object BuildConfig {".to_string());
 
     lines.push(format!(
@@ -55,13 +73,7 @@ pub fn generate(
         escape_kotlin_string(package_name)
     ));
 
-    for (key, value) in custom_fields {
-        lines.push(format!(
-            "    const val {}: String = \"{}\"",
-            key,
-            escape_kotlin_string(value)
-        ));
-    }
+    render_fields(custom_fields, 1, target, &mut lines)?;
 
     lines.push("}".to_string());
     lines.push(String::new());
@@ -87,10 +99,78 @@ pub fn package_from_main_class(main_class: &str) -> Option<String> {
     }
 }
 
+/// Append `fields` at `depth` levels of indentation: plain fields first,
+/// then nested objects.
+fn render_fields(
+    fields: &BTreeMap<String, BuildConfigField>,
+    depth: usize,
+    target: KotlinTarget,
+    lines: &mut Vec<String>,
+) -> miette::Result<()> {
+    let indent = "    ".repeat(depth);
+    for (key, field) in fields {
+        let line = match field {
+            BuildConfigField::String(s) => {
+                format!("const val {key}: String = \"{}\"", escape_kotlin_string(s))
+            }
+            BuildConfigField::Boolean(b) => format!("const val {key}: Boolean = {b}"),
+            BuildConfigField::Int(n) => format!("const val {key}: Int = {n}"),
+            BuildConfigField::Long(n) => format!("const val {key}: Long = {n}L"),
+            BuildConfigField::StringList(items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|i| format!("\"{}\"", escape_kotlin_string(i)))
+                    .collect();
+                format!("val {key}: List<String> = listOf({})", items.join(", "))
+            }
+            BuildConfigField::FromEnv { var, default } => {
+                let lookup = env_lookup(target, var).ok_or_else(|| KargoError::Generic {
+                    message: format!(
+                        "build-config {key}: from-env values are not supported on {} targets",
+                        target.kebab_name()
+                    ),
+                })?;
+                match default {
+                    Some(d) => format!(
+                        "val {key}: String get() = {lookup} ?: \"{}\"",
+                        escape_kotlin_string(d)
+                    ),
+                    None => format!("val {key}: String? get() = {lookup}"),
+                }
+            }
+            BuildConfigField::Object(_) => continue,
+        };
+        lines.push(format!("{indent}{line}"));
+    }
+    for (key, field) in fields {
+        if let BuildConfigField::Object(nested) = field {
+            lines.push(String::new());
+            lines.push(format!("{indent}object {key} {{"));
+            render_fields(nested, depth + 1, target, lines)?;
+            lines.push(format!("{indent}}}"));
+        }
+    }
+    Ok(())
+}
+
+/// Kotlin expression reading environment variable `var` as a `String?` at
+/// run time, or `None` if `target` has no process environment.
+fn env_lookup(target: KotlinTarget, var: &str) -> Option<String> {
+    match target {
+        KotlinTarget::WasmJs | KotlinTarget::WasmWasi => None,
+        KotlinTarget::Js => Some(format!(
+            "(js(\"typeof process !== 'undefined' ? process.env : {{}}\")[\"{var}\"] as? String)"
+        )),
+        t if t.is_native() => Some(format!("platform.posix.getenv(\"{var}\")?.toKString()")),
+        _ => Some(format!("System.getenv(\"{var}\")")),
+    }
+}
+
 fn escape_kotlin_string(s: &str) -> String {
     s.replace('\\', "\\\\")
         .replace('"', "\\\"")
         .replace('\n', "\\n")
+        .replace('$', "\\$")
 }
 
 #[cfg(test)]
@@ -106,6 +186,7 @@ mod tests {
         let path = generate(
             tmp.path(),
             Some("com.example"),
+            KotlinTarget::Jvm,
             "my-app",
             "1.0.0",
             "dev",
@@ -130,6 +211,7 @@ mod tests {
         let path = generate(
             tmp.path(),
             None,
+            KotlinTarget::Jvm,
             "my-app",
             "1.0.0",
             "release",
@@ -155,4 +237,148 @@ mod tests {
         );
         assert_eq!(package_from_main_class("MainKt"), None);
     }
+
+    #[test]
+    fn generates_typed_and_nested_fields_in_stable_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = BTreeMap::from([
+            (
+                "TIMEOUT".to_string(),
+                BuildConfigField::Long(30_000_000_000),
+            ),
+            ("RETRIES".to_string(), BuildConfigField::Int(3)),
+            ("LOGGING".to_string(), BuildConfigField::Boolean(false)),
+            ("PRICE".to_string(), "$4.99".into()),
+            (
+                "LOCALES".to_string(),
+                BuildConfigField::StringList(vec!["en".into(), "de".into()]),
+            ),
+            (
+                "FEATURES".to_string(),
+                BuildConfigField::Object(BTreeMap::from([(
+                    "SEARCH".to_string(),
+                    BuildConfigField::Boolean(true),
+                )])),
+            ),
+        ]);
+
+        let path = generate(
+            tmp.path(),
+            None,
+            KotlinTarget::Jvm,
+            "app",
+            "1.0.0",
+            "dev",
+            true,
+            &config,
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let expected = "\
+// Auto-generated by Kargo. Do not edit.
+
+object BuildConfig {
+    const val BUILD_TYPE: String = \"dev\"
+    const val VERSION_NAME: String = \"1.0.0\"
+    const val DEBUG: Boolean = true
+    const val APPLICATION_NAME: String = \"app\"
+    val LOCALES: List<String> = listOf(\"en\", \"de\")
+    const val LOGGING: Boolean = false
+    const val PRICE: String = \"\\$4.99\"
+    const val RETRIES: Int = 3
+    const val TIMEOUT: Long = 30000000000L
+
+    object FEATURES {
+        const val SEARCH: Boolean = true
+    }
+}
+";
+        assert_eq!(content, expected);
+
+        let again = generate(
+            tmp.path(),
+            None,
+            KotlinTarget::Jvm,
+            "app",
+            "1.0.0",
+            "dev",
+            true,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(again).unwrap(), expected);
+    }
+
+    #[test]
+    fn from_env_fields_read_the_environment_at_run_time() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = BTreeMap::from([
+            (
+                "API_KEY".to_string(),
+                BuildConfigField::FromEnv {
+                    var: "API_KEY".into(),
+                    default: None,
+                },
+            ),
+            (
+                "REGION".to_string(),
+                BuildConfigField::FromEnv {
+                    var: "APP_REGION".into(),
+                    default: Some("eu".into()),
+                },
+            ),
+        ]);
+
+        let path = generate(
+            tmp.path(),
+            None,
+            KotlinTarget::Jvm,
+            "app",
+            "1.0.0",
+            "dev",
+            true,
+            &config,
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("    val API_KEY: String? get() = System.getenv(\"API_KEY\")\n"));
+        assert!(content
+            .contains("    val REGION: String get() = System.getenv(\"APP_REGION\") ?: \"eu\"\n"));
+
+        let path = generate(
+            tmp.path(),
+            Some("com.example"),
+            KotlinTarget::LinuxX64,
+            "app",
+            "1.0.0",
+            "dev",
+            true,
+            &config,
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.starts_with(
+            "// Auto-generated by Kargo. Do not edit.\n\n\
+             @file:OptIn(kotlinx.cinterop.ExperimentalForeignApi::class)\n\n\
+             package com.example\n\n\
+             import kotlinx.cinterop.toKString\n"
+        ));
+        assert!(content.contains("platform.posix.getenv(\"API_KEY\")?.toKString()"));
+
+        let err = generate(
+            tmp.path(),
+            None,
+            KotlinTarget::WasmJs,
+            "app",
+            "1.0.0",
+            "dev",
+            true,
+            &config,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("not supported on wasm-js targets"),
+            "{err}"
+        );
+    }
 }
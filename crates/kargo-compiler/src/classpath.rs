@@ -1,65 +1,204 @@
 //! Classpath assembly from the lockfile and local Maven cache.
 //!
-//! Separates compile-scoped JARs from test-scoped JARs so that main
-//! sources compile against only their declared dependencies and tests
-//! get the full classpath.
+//! Every classpath is computed strictly from the `scope` recorded for each
+//! package in `Kargo.lock`:
+//!
+//! | scope              | compile | runtime | test compile | test runtime | processor |
+//! |--------------------|---------|---------|--------------|--------------|-----------|
+//! | `compile` / none   | yes     | yes     | yes          | yes          |           |
+//! | `provided`         | yes     |         | yes          | yes          |           |
+//! | `runtime`          |         | yes     |              | yes          |           |
+//! | `test`             |         |         | yes          | yes          |           |
+//! | `ksp` / `kapt`     |         |         |              |              | yes       |
+//!
+//! `integration-test` packages extend the test classpaths for the
+//! integration-test unit. Project JARs from `[[file]]` entries are placed by
+//! the same table, relative to the project root. Processor classpaths also pull in the lockfile
+//! `dependencies` of every processor package, whatever their own scope, so a
+//! library shared by a processor and main code still reaches the processor.
 
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
-use kargo_core::lockfile::Lockfile;
+use kargo_core::lockfile::{LockedFile, LockedPackage, Lockfile};
 use kargo_maven::cache::LocalCache;
 
 /// Assembled classpath for a build.
 #[derive(Debug, Clone)]
 pub struct Classpath {
-    /// JARs required for compiling main sources.
+    /// JARs for compiling main sources (`compile` + `provided`).
     pub compile_jars: Vec<PathBuf>,
-    /// Additional JARs required for compiling test sources (includes compile_jars).
+    /// JARs for running main code (`compile` + `runtime`).
+    pub runtime_jars: Vec<PathBuf>,
+    /// JARs for compiling test sources (compile classpath + `test`).
     pub test_jars: Vec<PathBuf>,
+    /// JARs for running tests (every non-processor scope except
+    /// `integration-test`).
+    pub test_runtime_jars: Vec<PathBuf>,
+    /// JARs for compiling the integration-test unit (`test_jars` +
+    /// `integration-test`).
+    pub integration_test_jars: Vec<PathBuf>,
+    /// JARs for running integration tests (`test_runtime_jars` +
+    /// `integration-test`).
+    pub integration_test_runtime_jars: Vec<PathBuf>,
     /// JARs for annotation processors (KSP/KAPT) — only needed at build time,
     /// never included in runtime classpath or output JAR.
     pub processor_jars: Vec<PathBuf>,
+    /// Project JARs declared as `{ file = "..." }` dependencies, whatever
+    /// their scope.
+    pub local_jars: Vec<PathBuf>,
 }
 
 /// Build the classpath from the lockfile and local cache.
 ///
-/// Compile-scoped JARs are those with `scope == "compile"` (or no scope).
-/// Test-scoped JARs are those with `scope == "test"`.
-/// Processor-scoped JARs (`ksp`, `kapt`) are excluded from both — they are
-/// only needed during annotation processing which fetches them separately.
-/// The `test_jars` vector contains compile + test JARs.
+/// Packages with a scope Kargo does not know are left off every classpath.
+/// Artifacts published only as klibs (non-JVM variants of multiplatform
+/// libraries) contribute their `.klib` file in place of a JAR, and Android
+/// libraries the `classes.jar` extracted from their AAR.
 pub fn assemble(project_root: &Path, lockfile: &Lockfile) -> Classpath {
+    assemble_filtered(project_root, lockfile, None)
+}
+
+/// Like [`assemble`], but skips packages whose lockfile `targets` list
+/// excludes `target` (kebab-case), i.e. dependencies declared only under
+/// `[target.<selector>.dependencies]` sections that do not match it.
+pub fn assemble_for_target(project_root: &Path, lockfile: &Lockfile, target: &str) -> Classpath {
+    assemble_filtered(project_root, lockfile, Some(target))
+}
+
+fn assemble_filtered(project_root: &Path, lockfile: &Lockfile, target: Option<&str>) -> Classpath {
     let cache = LocalCache::new(project_root);
-    let mut compile_jars = Vec::new();
-    let mut test_only_jars = Vec::new();
-    let mut processor_jars = Vec::new();
-
-    for pkg in &lockfile.package {
-        let jar_path = match cache.get_jar(&pkg.group, &pkg.name, &pkg.version, None) {
-            Some(p) => p,
-            None => continue,
+    let mut classpath = assemble_with(lockfile, target, |pkg| {
+        cache
+            .get_library(&pkg.group, &pkg.name, &pkg.version)
+            .and_then(kargo_maven::aar::classpath_entry)
+    });
+    add_local_jars(&mut classpath, project_root, &lockfile.file);
+    classpath
+}
+
+/// Add the project JARs of `files` to the classpaths their scope puts them
+/// on, after the Maven artifacts.
+pub fn add_local_jars(classpath: &mut Classpath, project_root: &Path, files: &[LockedFile]) {
+    for file in files {
+        let jar = project_root.join(&file.path);
+        // compile, runtime, test, test runtime, integration-test, its runtime
+        let on = match file.scope.as_str() {
+            "compile" => [true, true, true, true, true, true],
+            "provided" => [true, false, true, true, true, true],
+            "runtime" => [false, true, false, true, false, true],
+            "test" => [false, false, true, true, true, true],
+            "integration-test" => [false, false, false, false, true, true],
+            _ => continue,
         };
+        let lists = [
+            &mut classpath.compile_jars,
+            &mut classpath.runtime_jars,
+            &mut classpath.test_jars,
+            &mut classpath.test_runtime_jars,
+            &mut classpath.integration_test_jars,
+            &mut classpath.integration_test_runtime_jars,
+        ];
+        for (list, on) in lists.into_iter().zip(on) {
+            if on && !list.contains(&jar) {
+                list.push(jar.clone());
+            }
+        }
+        classpath.local_jars.push(jar);
+    }
+}
 
-        let scope = pkg.scope.as_deref().unwrap_or("compile");
+/// Assemble the classpaths, locating each package's artifact with `locate`.
+/// Packages `locate` cannot find are skipped.
+pub fn assemble_with(
+    lockfile: &Lockfile,
+    target: Option<&str>,
+    locate: impl Fn(&LockedPackage) -> Option<PathBuf>,
+) -> Classpath {
+    let packages: Vec<&LockedPackage> = lockfile
+        .package
+        .iter()
+        .filter(|pkg| target.map_or(true, |t| pkg.applies_to(t)))
+        .collect();
 
-        match scope {
-            "test" => test_only_jars.push(jar_path),
-            "ksp" | "kapt" => processor_jars.push(jar_path),
-            _ => compile_jars.push(jar_path),
-        }
+    let mut compile = BTreeSet::new();
+    let mut runtime = BTreeSet::new();
+    let mut test = BTreeSet::new();
+    let mut integration_test = BTreeSet::new();
+    let mut provided = BTreeSet::new();
+    let mut processor = BTreeSet::new();
+
+    for pkg in &packages {
+        let Some(jar) = locate(pkg) else {
+            continue;
+        };
+        let bucket = match pkg.scope.as_deref().unwrap_or("compile") {
+            "compile" => &mut compile,
+            "runtime" => &mut runtime,
+            "provided" | "system" => &mut provided,
+            "test" => &mut test,
+            "integration-test" => &mut integration_test,
+            "ksp" | "kapt" => &mut processor,
+            _ => continue,
+        };
+        bucket.insert(jar);
     }
 
-    compile_jars.sort();
-    test_only_jars.sort();
-    processor_jars.sort();
+    // Follow lockfile edges out of the processor packages so their
+    // dependencies reach the processor path even when recorded under
+    // another scope.
+    let by_coordinate: HashMap<(&str, &str, &str), &LockedPackage> = packages
+        .iter()
+        .map(|p| ((p.group.as_str(), p.name.as_str(), p.version.as_str()), *p))
+        .collect();
+    let mut stack: Vec<&LockedPackage> = packages
+        .iter()
+        .copied()
+        .filter(|p| matches!(p.scope.as_deref(), Some("ksp" | "kapt")))
+        .collect();
+    let mut visited = BTreeSet::new();
+    while let Some(pkg) = stack.pop() {
+        if !visited.insert((&pkg.group, &pkg.name, &pkg.version)) {
+            continue;
+        }
+        if let Some(jar) = locate(pkg) {
+            processor.insert(jar);
+        }
+        for dep in &pkg.dependencies {
+            let key = (dep.group.as_str(), dep.name.as_str(), dep.version.as_str());
+            if let Some(next) = by_coordinate.get(&key) {
+                stack.push(next);
+            }
+        }
+    }
 
-    let mut test_jars = compile_jars.clone();
-    test_jars.extend(test_only_jars);
+    let union = |sets: &[&BTreeSet<PathBuf>]| -> Vec<PathBuf> {
+        let mut jars: Vec<PathBuf> = Vec::new();
+        for set in sets {
+            for jar in *set {
+                if !jars.contains(jar) {
+                    jars.push(jar.clone());
+                }
+            }
+        }
+        jars
+    };
 
     Classpath {
-        compile_jars,
-        test_jars,
-        processor_jars,
+        compile_jars: union(&[&compile, &provided]),
+        runtime_jars: union(&[&compile, &runtime]),
+        test_jars: union(&[&compile, &provided, &test]),
+        test_runtime_jars: union(&[&compile, &provided, &runtime, &test]),
+        integration_test_jars: union(&[&compile, &provided, &test, &integration_test]),
+        integration_test_runtime_jars: union(&[
+            &compile,
+            &provided,
+            &runtime,
+            &test,
+            &integration_test,
+        ]),
+        processor_jars: processor.into_iter().collect(),
+        local_jars: Vec::new(),
     }
 }
 
@@ -112,6 +251,211 @@ fn classpath_separator() -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kargo_core::lockfile::LockedDependencyRef;
+
+    fn pkg(name: &str, scope: Option<&str>, deps: &[&str]) -> LockedPackage {
+        LockedPackage {
+            name: name.into(),
+            group: "org.example".into(),
+            version: "1.0".into(),
+            checksum: None,
+            source: None,
+            scope: scope.map(str::to_string),
+            targets: vec![],
+            dependencies: deps
+                .iter()
+                .map(|d| LockedDependencyRef {
+                    name: d.to_string(),
+                    group: "org.example".into(),
+                    version: "1.0".into(),
+                })
+                .collect(),
+            relocated_from: None,
+            members: Vec::new(),
+        }
+    }
+
+    fn lockfile(package: Vec<LockedPackage>) -> Lockfile {
+        Lockfile {
+            toolchain: None,
+            environment: None,
+            package,
+            npm: vec![],
+            file: Vec::new(),
+            members: Vec::new(),
+        }
+    }
+
+    fn jar(pkg: &LockedPackage) -> Option<PathBuf> {
+        Some(PathBuf::from(format!("{}.jar", pkg.name)))
+    }
+
+    fn names(jars: &[PathBuf]) -> Vec<&str> {
+        jars.iter().map(|j| j.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn scopes_map_to_their_classpaths() {
+        let lf = lockfile(vec![
+            pkg("core", Some("compile"), &[]),
+            pkg("legacy", None, &[]),
+            pkg("driver", Some("runtime"), &[]),
+            pkg("servlet-api", Some("provided"), &[]),
+            pkg("junit", Some("test"), &[]),
+            pkg("testcontainers", Some("integration-test"), &[]),
+            pkg("room-compiler", Some("ksp"), &[]),
+            pkg("dagger-compiler", Some("kapt"), &[]),
+        ]);
+        let cp = assemble_with(&lf, None, jar);
+
+        assert_eq!(
+            names(&cp.compile_jars),
+            ["core.jar", "legacy.jar", "servlet-api.jar"]
+        );
+        assert_eq!(
+            names(&cp.runtime_jars),
+            ["core.jar", "legacy.jar", "driver.jar"]
+        );
+        assert_eq!(
+            names(&cp.test_jars),
+            ["core.jar", "legacy.jar", "servlet-api.jar", "junit.jar"]
+        );
+        assert_eq!(
+            names(&cp.test_runtime_jars),
+            [
+                "core.jar",
+                "legacy.jar",
+                "servlet-api.jar",
+                "driver.jar",
+                "junit.jar"
+            ]
+        );
+        assert_eq!(
+            names(&cp.integration_test_jars),
+            [
+                "core.jar",
+                "legacy.jar",
+                "servlet-api.jar",
+                "junit.jar",
+                "testcontainers.jar"
+            ]
+        );
+        assert_eq!(
+            names(&cp.integration_test_runtime_jars).last(),
+            Some(&"testcontainers.jar")
+        );
+        assert_eq!(
+            names(&cp.processor_jars),
+            ["dagger-compiler.jar", "room-compiler.jar"]
+        );
+    }
+
+    #[test]
+    fn project_jars_follow_the_scope_table() {
+        let file = |name: &str, scope: &str| LockedFile {
+            path: format!("libs/{name}.jar"),
+            checksum: String::new(),
+            scope: scope.into(),
+            members: vec![],
+        };
+        let mut cp = assemble_with(&lockfile(vec![pkg("core", None, &[])]), None, jar);
+        add_local_jars(
+            &mut cp,
+            Path::new("app"),
+            &[
+                file("vendor", "compile"),
+                file("api", "provided"),
+                file("driver", "runtime"),
+                file("fake", "test"),
+                file("stack", "integration-test"),
+            ],
+        );
+
+        let p = |path: &str| Path::new("app").join(path).to_str().unwrap().to_string();
+        let (vendor, api, driver, fake, stack) = (
+            p("libs/vendor.jar"),
+            p("libs/api.jar"),
+            p("libs/driver.jar"),
+            p("libs/fake.jar"),
+            p("libs/stack.jar"),
+        );
+        assert_eq!(names(&cp.compile_jars), ["core.jar", &vendor, &api]);
+        assert_eq!(names(&cp.runtime_jars), ["core.jar", &vendor, &driver]);
+        assert_eq!(names(&cp.test_jars), ["core.jar", &vendor, &api, &fake]);
+        assert_eq!(
+            names(&cp.test_runtime_jars),
+            ["core.jar", &vendor, &api, &driver, &fake]
+        );
+        assert_eq!(
+            names(&cp.integration_test_jars),
+            ["core.jar", &vendor, &api, &fake, &stack]
+        );
+        assert_eq!(cp.local_jars.len(), 5);
+    }
+
+    #[test]
+    fn processor_artifacts_never_reach_test_runtime() {
+        let lf = lockfile(vec![
+            pkg("app-lib", None, &[]),
+            pkg("ksp-api", Some("ksp"), &[]),
+            pkg("processor", Some("ksp"), &["ksp-api"]),
+            pkg("junit", Some("test"), &[]),
+        ]);
+        let cp = assemble_with(&lf, None, jar);
+
+        for classpath in [
+            &cp.compile_jars,
+            &cp.runtime_jars,
+            &cp.test_jars,
+            &cp.test_runtime_jars,
+            &cp.integration_test_runtime_jars,
+        ] {
+            assert!(!names(classpath)
+                .iter()
+                .any(|n| n.contains("ksp") || n.contains("processor")));
+        }
+        assert_eq!(names(&cp.processor_jars), ["ksp-api.jar", "processor.jar"]);
+    }
+
+    #[test]
+    fn processor_path_follows_lockfile_edges_into_other_scopes() {
+        // `guava` was first reached from main code, so it is locked as
+        // `compile`, but the processor still needs it.
+        let lf = lockfile(vec![
+            pkg("guava", Some("compile"), &["failureaccess"]),
+            pkg("failureaccess", Some("compile"), &[]),
+            pkg("processor", Some("kapt"), &["guava"]),
+            pkg("unrelated", Some("compile"), &[]),
+        ]);
+        let cp = assemble_with(&lf, None, jar);
+
+        assert_eq!(
+            names(&cp.processor_jars),
+            ["failureaccess.jar", "guava.jar", "processor.jar"]
+        );
+        assert_eq!(
+            names(&cp.runtime_jars),
+            ["failureaccess.jar", "guava.jar", "unrelated.jar"]
+        );
+    }
+
+    #[test]
+    fn target_filter_and_missing_artifacts_are_skipped() {
+        let mut android_only = pkg("android-lib", None, &[]);
+        android_only.targets = vec!["android".into()];
+        let lf = lockfile(vec![
+            pkg("core", None, &[]),
+            android_only,
+            pkg("not-downloaded", None, &[]),
+            pkg("odd", Some("import"), &[]),
+        ]);
+        let cp = assemble_with(&lf, Some("jvm"), |p| {
+            (p.name != "not-downloaded").then(|| PathBuf::from(format!("{}.jar", p.name)))
+        });
+
+        assert_eq!(names(&cp.compile_jars), ["core.jar"]);
+        assert_eq!(names(&cp.test_runtime_jars), ["core.jar"]);
+    }
 
     #[test]
     fn classpath_string_format() {
@@ -7,12 +7,14 @@
 //! orchestration layer.
 
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
+use kargo_core::config::{GlobalConfig, RemoteExecutionConfig};
 use kargo_core::target::KotlinTarget;
 use kargo_toolchain::discovery::ToolchainPaths;
 
 use crate::env::BuildEnv;
-use crate::unit::{CompilationOutput, CompilationUnit};
+use crate::unit::{CompilationOutput, CompilationUnit, Diagnostic};
 
 /// Trait implemented by each target-specific compiler backend.
 pub trait TargetCompiler {
@@ -26,6 +28,32 @@ pub trait TargetCompiler {
         env: &BuildEnv,
     ) -> miette::Result<CompilationOutput>;
 
+    /// Compile like [`compile`](Self::compile), passing each diagnostic to
+    /// `on_diagnostic` as soon as the compiler reports it. Backends that
+    /// cannot stream report all diagnostics once compilation finishes.
+    fn compile_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        let output = self.compile(unit, env)?;
+        output.diagnostics.iter().for_each(&mut *on_diagnostic);
+        Ok(output)
+    }
+
+    /// Streaming counterpart of [`check_only`](Self::check_only).
+    fn check_only_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        let output = self.check_only(unit, env)?;
+        output.diagnostics.iter().for_each(&mut *on_diagnostic);
+        Ok(output)
+    }
+
     /// The target this compiler handles.
     fn target(&self) -> KotlinTarget;
 
@@ -46,17 +74,45 @@ impl CompilerDispatch {
     ) -> Box<dyn TargetCompiler> {
         match target {
             KotlinTarget::Jvm | KotlinTarget::Android => {
-                let c = crate::kotlinc::JvmCompiler::new(target, toolchain, jdk_home, java_target);
-                Box::new(c)
+                let c = crate::kotlinc::JvmCompiler::new(
+                    target,
+                    toolchain,
+                    jdk_home,
+                    java_target.clone(),
+                );
+                match remote_execution() {
+                    Some(config) => Box::new(crate::kotlinc_remote::RemoteCompiler::new(
+                        Box::new(c),
+                        config.clone(),
+                        java_target,
+                    )),
+                    None => Box::new(c),
+                }
             }
             KotlinTarget::Js => Box::new(crate::kotlinc_js::JsCompiler::new(target)),
+            KotlinTarget::WasmJs => Box::new(crate::kotlinc_wasm::WasmCompiler::new(
+                target, toolchain, jdk_home,
+            )),
             _ if target.is_native() => Box::new(crate::kotlinc_native::NativeCompiler::new(target)),
             _ => Box::new(UnsupportedCompiler(target)),
         }
     }
 }
 
-/// Placeholder for targets with no backend yet (WASM without native flag, etc.).
+/// `[remote-execution]` from the global config, read once per process.
+fn remote_execution() -> Option<&'static RemoteExecutionConfig> {
+    static CONFIG: OnceLock<Option<RemoteExecutionConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            GlobalConfig::load()
+                .ok()
+                .and_then(|c| c.remote_execution)
+                .filter(|r| !r.endpoint.is_empty())
+        })
+        .as_ref()
+}
+
+/// Placeholder for targets with no backend yet (`wasm-wasi`, etc.).
 struct UnsupportedCompiler(KotlinTarget);
 
 impl TargetCompiler for UnsupportedCompiler {
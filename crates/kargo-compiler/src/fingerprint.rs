@@ -12,6 +12,7 @@
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use kargo_util::fs::normalize_path;
 use sha2::{Digest, Sha256};
 
 use crate::unit::CompilationUnit;
@@ -77,13 +78,20 @@ pub fn compute(unit: &CompilationUnit, kotlin_version: &str) -> Fingerprint {
         hasher.update(b"\n");
     }
 
-    // Source file contents
-    let mut all_sources = unit.all_sources();
+    // Source file contents, by normalized path so that another spelling of
+    // the project directory (`Src/Main` on a case-insensitive filesystem)
+    // neither changes the fingerprint nor counts a file twice.
+    let mut all_sources: Vec<(String, PathBuf)> = unit
+        .all_sources()
+        .into_iter()
+        .map(|src| (normalize_path(&src), src))
+        .collect();
     all_sources.sort();
-    for src in &all_sources {
+    all_sources.dedup_by(|a, b| a.0 == b.0);
+    for (key, src) in &all_sources {
         if let Ok(content) = std::fs::read(src) {
             hasher.update(b"src:");
-            hasher.update(src.to_string_lossy().as_bytes());
+            hasher.update(key.as_bytes());
             hasher.update(b":");
             let file_hash = Sha256::digest(&content);
             hasher.update(format!("{file_hash:x}").as_bytes());
@@ -104,6 +112,19 @@ pub fn compute(unit: &CompilationUnit, kotlin_version: &str) -> Fingerprint {
         hasher.update(b"\n");
     }
 
+    // Project JAR contents — replacing libs/foo.jar keeps its name
+    let mut local_jars: Vec<&PathBuf> = unit.local_jars.iter().collect();
+    local_jars.sort();
+    for jar in local_jars {
+        if let Ok(content) = std::fs::read(jar) {
+            hasher.update(b"local:");
+            hasher.update(normalize_path(jar).as_bytes());
+            hasher.update(b":");
+            hasher.update(format!("{:x}", Sha256::digest(&content)).as_bytes());
+            hasher.update(b"\n");
+        }
+    }
+
     let result = hasher.finalize();
     Fingerprint {
         hash: format!("{result:x}"),
@@ -114,11 +135,12 @@ pub fn compute(unit: &CompilationUnit, kotlin_version: &str) -> Fingerprint {
 // Mtime fast-path
 // ---------------------------------------------------------------------------
 
-/// Compute the maximum modification time across all source files in a unit.
-/// Returns epoch seconds, or 0 if no files have metadata.
+/// Compute the maximum modification time across all source files and
+/// project JARs in a unit. Returns epoch seconds, or 0 if no files have
+/// metadata.
 pub fn max_mtime(unit: &CompilationUnit) -> u64 {
     let mut max = 0u64;
-    for src in &unit.sources {
+    for src in unit.sources.iter().chain(&unit.local_jars) {
         if let Ok(meta) = std::fs::metadata(src) {
             if let Ok(modified) = meta.modified() {
                 let secs = modified
@@ -229,3 +251,56 @@ pub fn save(fp_dir: &Path, unit_name: &str, fp: &Fingerprint) -> miette::Result<
         .into()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_core::target::KotlinTarget;
+
+    fn unit(sources: Vec<PathBuf>) -> CompilationUnit {
+        CompilationUnit {
+            name: "main".into(),
+            target: KotlinTarget::Jvm,
+            sources,
+            resource_dirs: vec![],
+            classpath: vec![],
+            output_dir: PathBuf::from("build/classes"),
+            compiler_args: vec![],
+            is_test: false,
+            generated_sources: vec![],
+            processor_jars: vec![],
+            local_jars: vec![],
+        }
+    }
+
+    #[test]
+    fn spelling_of_source_paths_does_not_change_the_fingerprint() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("src/main/kotlin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Main.kt"), "fun main() {}").unwrap();
+
+        let plain = compute(&unit(vec![dir.join("Main.kt")]), "2.3.0");
+        let dotted = tmp.path().join("src/main/../main/./kotlin/Main.kt");
+        assert_eq!(compute(&unit(vec![dotted.clone()]), "2.3.0"), plain);
+        assert_eq!(
+            compute(&unit(vec![dir.join("Main.kt"), dotted]), "2.3.0"),
+            plain
+        );
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn case_only_differences_match_on_case_insensitive_hosts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("src/main/kotlin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Main.kt"), "fun main() {}").unwrap();
+
+        let upper = tmp.path().join("Src/Main/kotlin/Main.kt");
+        assert_eq!(
+            compute(&unit(vec![upper]), "2.3.0"),
+            compute(&unit(vec![dir.join("Main.kt")]), "2.3.0")
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Output JAR writer.
+//!
+//! Packages compiled classes and resources into a JAR without the JDK's
+//! `jar` tool. Entries are written in sorted order with a fixed timestamp,
+//! so repackaging unchanged classes produces a byte-identical JAR.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::PackageMetadata;
+use kargo_util::errors::KargoError;
+use kargo_util::fs::long_path;
+
+const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+const VERSIONS_PREFIX: &str = "META-INF/versions/";
+
+/// Longest manifest line in bytes, excluding the line break.
+const MAX_LINE: usize = 72;
+
+/// Main attributes of `META-INF/MANIFEST.MF`, in output order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JarManifest {
+    attributes: Vec<(String, String)>,
+}
+
+impl JarManifest {
+    /// Attributes for a package's output JAR.
+    ///
+    /// `Implementation-Title` and `Implementation-Version` come from the
+    /// package name and version, `Implementation-Vendor` from its authors
+    /// (or group). `[package.jar] manifest-attributes` override those and
+    /// are appended in name order.
+    pub fn for_package(package: &PackageMetadata, main_class: Option<&str>) -> Self {
+        let mut manifest = Self::default();
+        manifest.set("Manifest-Version", "1.0");
+        manifest.set("Created-By", "Kargo");
+        if let Some(main_class) = main_class {
+            manifest.set("Main-Class", main_class);
+        }
+        manifest.set("Implementation-Title", &package.name);
+        manifest.set("Implementation-Version", &package.version);
+        let vendor = if package.authors.is_empty() {
+            package.group.clone()
+        } else {
+            Some(package.authors.join(", "))
+        };
+        if let Some(vendor) = vendor {
+            manifest.set("Implementation-Vendor", &vendor);
+        }
+        if let Some(ref jar) = package.jar {
+            if jar.multi_release {
+                manifest.set("Multi-Release", "true");
+            }
+            for (name, value) in &jar.manifest_attributes {
+                manifest.set(name, value);
+            }
+        }
+        manifest
+    }
+
+    /// Parse the main section of a `META-INF/MANIFEST.MF`, joining
+    /// continuation lines.
+    pub fn parse(text: &str) -> Self {
+        let mut lines: Vec<String> = Vec::new();
+        for line in text.lines() {
+            if line.is_empty() {
+                break;
+            }
+            match (line.strip_prefix(' '), lines.last_mut()) {
+                (Some(rest), Some(last)) => last.push_str(rest),
+                _ => lines.push(line.to_string()),
+            }
+        }
+        let mut manifest = Self::default();
+        for line in &lines {
+            if let Some((name, value)) = line.split_once(':') {
+                manifest.set(name.trim(), value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        manifest
+    }
+
+    /// Set an attribute, replacing an existing one with the same
+    /// (case-insensitive) name in place.
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some(attr) => attr.1 = value.to_string(),
+            None => self.attributes.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Value of an attribute, looked up case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the JAR is marked `Multi-Release: true`.
+    pub fn is_multi_release(&self) -> bool {
+        self.get("Multi-Release")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    /// Render the manifest, wrapping lines at 72 bytes and using CRLF line
+    /// breaks as the JAR specification requires.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.attributes {
+            let line = format!("{name}: {value}");
+            let mut rest = line.as_str();
+            let mut limit = MAX_LINE;
+            while rest.len() > limit {
+                let mut split = limit;
+                while !rest.is_char_boundary(split) {
+                    split -= 1;
+                }
+                out.push_str(&rest[..split]);
+                out.push_str("\r\n ");
+                rest = &rest[split..];
+                // Continuation lines start with a space.
+                limit = MAX_LINE - 1;
+            }
+            out.push_str(rest);
+            out.push_str("\r\n");
+        }
+        out.push_str("\r\n");
+        out
+    }
+}
+
+/// What [`write_jar`] packaged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JarSummary {
+    /// Number of file entries, excluding the manifest.
+    pub files: usize,
+    /// Java versions with `META-INF/versions/<N>/` entries.
+    pub versions: BTreeSet<u32>,
+}
+
+/// Write a JAR at `jar_path` holding `manifest` and the contents of `roots`.
+///
+/// When several roots contain the same entry the first one wins, so pass
+/// the classes directory before the resources. A `META-INF/MANIFEST.MF`
+/// inside a root is ignored. For a multi-release manifest every directory
+/// under `META-INF/versions/` must be a Java version of 9 or later.
+pub fn write_jar(
+    jar_path: &Path,
+    roots: &[&Path],
+    manifest: &JarManifest,
+) -> miette::Result<JarSummary> {
+    let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for root in roots {
+        let root = long_path(root);
+        collect_files(&root, &root, &mut files);
+    }
+    files.remove(MANIFEST_PATH);
+
+    let mut summary = JarSummary {
+        files: files.len(),
+        versions: BTreeSet::new(),
+    };
+    for name in files.keys() {
+        let Some(rest) = name.strip_prefix(VERSIONS_PREFIX) else {
+            continue;
+        };
+        let dir = rest.split('/').next().unwrap_or_default();
+        match dir.parse::<u32>() {
+            Ok(v) if v >= 9 => {
+                summary.versions.insert(v);
+            }
+            _ if manifest.is_multi_release() => {
+                return Err(KargoError::Generic {
+                    message: format!(
+                        "multi-release JAR entry '{name}': '{VERSIONS_PREFIX}{dir}' is not \
+                         a Java version of 9 or later"
+                    ),
+                }
+                .into());
+            }
+            _ => {}
+        }
+    }
+
+    let mut dirs: BTreeSet<String> = BTreeSet::new();
+    for name in files.keys() {
+        let mut end = 0;
+        while let Some(i) = name[end..].find('/') {
+            end += i + 1;
+            dirs.insert(name[..end].to_string());
+        }
+    }
+    dirs.remove("META-INF/");
+
+    if let Some(parent) = jar_path.parent() {
+        std::fs::create_dir_all(long_path(parent)).map_err(KargoError::Io)?;
+    }
+    let tmp_path = long_path(&jar_path.with_extension("jar.tmp"));
+    let file = std::fs::File::create(&tmp_path).map_err(KargoError::Io)?;
+    let zip_err = |e: zip::result::ZipError| KargoError::Generic {
+        message: format!("Failed to write {}: {e}", jar_path.display()),
+    };
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default())
+        .unix_permissions(0o644);
+    let dir_options = options.unix_permissions(0o755);
+
+    let mut zip = zip::ZipWriter::new(file);
+    zip.add_directory("META-INF/", dir_options)
+        .map_err(zip_err)?;
+    zip.start_file(MANIFEST_PATH, options).map_err(zip_err)?;
+    zip.write_all(manifest.render().as_bytes())
+        .map_err(KargoError::Io)?;
+
+    let mut entries: BTreeMap<&str, Option<&PathBuf>> = BTreeMap::new();
+    for dir in &dirs {
+        entries.insert(dir, None);
+    }
+    for (name, path) in &files {
+        entries.insert(name, Some(path));
+    }
+    for (name, path) in entries {
+        match path {
+            None => zip.add_directory(name, dir_options).map_err(zip_err)?,
+            Some(path) => {
+                let content = std::fs::read(path).map_err(KargoError::Io)?;
+                zip.start_file(name, options).map_err(zip_err)?;
+                zip.write_all(&content).map_err(KargoError::Io)?;
+            }
+        }
+    }
+    zip.finish().map_err(zip_err)?;
+    std::fs::rename(&tmp_path, long_path(jar_path)).map_err(KargoError::Io)?;
+
+    Ok(summary)
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.entry(name).or_insert(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_core::manifest::{JarConfig, Manifest};
+    use std::io::Read;
+
+    fn package(extra: &str) -> PackageMetadata {
+        Manifest::parse_toml(&format!(
+            r#"
+[package]
+name = "app"
+version = "1.2.0"
+kotlin = "2.3.0"
+group = "com.example"
+{extra}
+"#
+        ))
+        .unwrap()
+        .package
+    }
+
+    fn write(root: &Path, name: &str, content: &str) {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn read_jar(path: &Path) -> Vec<(String, String)> {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut entry = archive.by_index(i).unwrap();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (entry.name().to_string(), content)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deep_class_directories_are_packaged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        let package: Vec<String> = (0..24).map(|i| format!("segment{i:02}")).collect();
+        let dir = classes.join(package.join("/"));
+        std::fs::create_dir_all(long_path(&dir)).unwrap();
+        std::fs::write(long_path(&dir.join("Deep.class")), "deep").unwrap();
+        assert!(dir.as_os_str().len() > 260);
+
+        let jar = dir.join("out.jar");
+        write_jar(&jar, &[classes.as_path()], &JarManifest::default()).unwrap();
+        let entry = format!("{}/Deep.class", package.join("/"));
+        assert!(read_jar(&long_path(&jar))
+            .iter()
+            .any(|(name, content)| *name == entry && content == "deep"));
+    }
+
+    #[test]
+    fn implementation_attributes_come_from_package_metadata() {
+        let manifest = JarManifest::for_package(&package(""), Some("com.example.MainKt"));
+        assert_eq!(
+            manifest.render(),
+            "Manifest-Version: 1.0\r\n\
+             Created-By: Kargo\r\n\
+             Main-Class: com.example.MainKt\r\n\
+             Implementation-Title: app\r\n\
+             Implementation-Version: 1.2.0\r\n\
+             Implementation-Vendor: com.example\r\n\
+             \r\n"
+        );
+
+        let authored = JarManifest::for_package(&package(r#"authors = ["Ana", "Ben"]"#), None);
+        assert_eq!(authored.get("implementation-vendor"), Some("Ana, Ben"));
+        assert_eq!(authored.get("Main-Class"), None);
+    }
+
+    #[test]
+    fn parsed_manifest_round_trips_wrapped_lines() {
+        let mut manifest = JarManifest::default();
+        manifest.set("Main-Class", "com.pinterest.ktlint.Main");
+        manifest.set("Class-Path", &"lib/dependency.jar ".repeat(10));
+        let parsed = JarManifest::parse(&format!(
+            "{}Name: com/example/\r\nSealed: true\r\n",
+            manifest.render()
+        ));
+        assert_eq!(parsed, manifest);
+        assert_eq!(parsed.get("main-class"), Some("com.pinterest.ktlint.Main"));
+    }
+
+    #[test]
+    fn configured_attributes_override_derived_ones() {
+        let pkg = package(
+            r#"
+[package.jar]
+multi-release = true
+manifest-attributes = { "Implementation-Version" = "1.2.0-rc1", "Add-Opens" = "java.base/java.lang" }
+"#,
+        );
+        assert!(matches!(
+            pkg.jar,
+            Some(JarConfig {
+                multi_release: true,
+                ..
+            })
+        ));
+        let manifest = JarManifest::for_package(&pkg, None);
+        assert_eq!(manifest.get("Implementation-Version"), Some("1.2.0-rc1"));
+        assert_eq!(manifest.get("Add-Opens"), Some("java.base/java.lang"));
+        assert!(manifest.is_multi_release());
+    }
+
+    #[test]
+    fn long_lines_are_wrapped_at_72_bytes() {
+        let mut manifest = JarManifest::default();
+        let value = "x".repeat(150);
+        manifest.set("Class-Path", &value);
+        let rendered = manifest.render();
+        let lines: Vec<&str> = rendered.split("\r\n").collect();
+        assert_eq!(lines[0].len(), 72);
+        assert!(lines[1].starts_with(' ') && lines[1].len() == 72);
+        assert!(lines.iter().all(|l| l.len() <= 72));
+        let unwrapped: String = lines[0].to_string() + &lines[1][1..] + &lines[2][1..];
+        assert_eq!(unwrapped, format!("Class-Path: {value}"));
+    }
+
+    #[test]
+    fn writes_sorted_entries_with_manifest_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        let resources = tmp.path().join("resources");
+        write(&classes, "com/example/MainKt.class", "main");
+        write(&classes, "app.properties", "from-classes");
+        write(&resources, "app.properties", "from-resources");
+        write(&resources, "META-INF/MANIFEST.MF", "ignored");
+        write(&resources, "META-INF/services/x.Y", "impl");
+
+        let jar = tmp.path().join("out").join("app.jar");
+        let summary = write_jar(
+            &jar,
+            &[&classes, &resources],
+            &JarManifest::for_package(&package(""), None),
+        )
+        .unwrap();
+        assert_eq!(summary.files, 3);
+
+        let entries = read_jar(&jar);
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "META-INF/",
+                "META-INF/MANIFEST.MF",
+                "META-INF/services/",
+                "META-INF/services/x.Y",
+                "app.properties",
+                "com/",
+                "com/example/",
+                "com/example/MainKt.class",
+            ]
+        );
+        assert!(entries[1].1.contains("Implementation-Title: app\r\n"));
+        assert_eq!(entries[4].1, "from-classes");
+
+        // Repackaging unchanged inputs is byte-identical.
+        let first = std::fs::read(&jar).unwrap();
+        write_jar(
+            &jar,
+            &[&classes, &resources],
+            &JarManifest::for_package(&package(""), None),
+        )
+        .unwrap();
+        assert_eq!(std::fs::read(&jar).unwrap(), first);
+    }
+
+    #[test]
+    fn multi_release_entries_are_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        write(&classes, "com/example/Api.class", "8");
+        write(&classes, "META-INF/versions/17/com/example/Api.class", "17");
+        let jar = tmp.path().join("app.jar");
+
+        let mut manifest = JarManifest::default();
+        manifest.set("Multi-Release", "true");
+        let summary = write_jar(&jar, &[&classes], &manifest).unwrap();
+        assert_eq!(summary.versions, BTreeSet::from([17]));
+
+        write(&classes, "META-INF/versions/latest/Api.class", "?");
+        let err = write_jar(&jar, &[&classes], &manifest).unwrap_err();
+        assert!(err.to_string().contains("META-INF/versions/latest"));
+    }
+}
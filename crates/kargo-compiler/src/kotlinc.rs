@@ -2,7 +2,7 @@
 //!
 //! Implements [`TargetCompiler`] for JVM and Android JVM targets.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use kargo_core::target::KotlinTarget;
 use kargo_toolchain::discovery::ToolchainPaths;
@@ -41,6 +41,7 @@ impl JvmCompiler {
         unit: &CompilationUnit,
         env: &BuildEnv,
         output_dir: &PathBuf,
+        on_diagnostic: Option<&mut dyn FnMut(&Diagnostic)>,
     ) -> miette::Result<CompilationOutput> {
         let all_sources = unit.all_sources();
         if all_sources.is_empty() {
@@ -80,43 +81,71 @@ impl JvmCompiler {
             kargo_util::process::CommandBuilder::new(kotlinc_bin.to_string_lossy().to_string());
         cmd = cmd
             .args(args)
+            .response_file(true)
             .env("JAVA_HOME", self.jdk_home.to_string_lossy().to_string());
 
         for (k, v) in &env.vars {
             cmd = cmd.env(k, v);
         }
 
-        let output = cmd.exec().map_err(|e| KargoError::Generic {
-            message: format!("Failed to execute kotlinc: {e}"),
-        })?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let mut diagnostics = parse_diagnostics(&stdout, &stderr);
-
-        if !output.status.success() && diagnostics.is_empty() {
-            let raw = format!("{stdout}\n{stderr}").trim().to_string();
-            if !raw.is_empty() {
-                diagnostics.push(Diagnostic {
-                    severity: DiagnosticSeverity::Error,
-                    message: raw,
-                    file: None,
-                    line: None,
-                });
+        run_compiler(cmd, "kotlinc", output_dir, on_diagnostic)
+    }
+}
+
+/// Execute a prepared compiler command and collect its diagnostics.
+///
+/// Shared by every backend that speaks kotlinc's `file:line:col: severity:`
+/// output format. A failing run with no parseable diagnostics reports the
+/// raw output as a single error.
+pub(crate) fn run_compiler(
+    cmd: kargo_util::process::CommandBuilder,
+    tool: &str,
+    output_dir: &Path,
+    mut on_diagnostic: Option<&mut dyn FnMut(&Diagnostic)>,
+) -> miette::Result<CompilationOutput> {
+    let output = match on_diagnostic.as_deref_mut() {
+        Some(sink) => cmd.exec_streaming(|_, line| {
+            if let Some(d) = parse_line(line) {
+                sink(&d);
             }
-        }
+        }),
+        None => cmd.exec(),
+    }
+    .map_err(|e| KargoError::Generic {
+        message: format!("Failed to execute {tool}: {e}"),
+    })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut diagnostics = parse_diagnostics(&stdout, &stderr);
 
-        Ok(CompilationOutput {
-            classes_dir: output_dir.clone(),
-            success: output.status.success(),
-            diagnostics,
-        })
+    if !output.status.success() && diagnostics.is_empty() {
+        let raw = format!("{stdout}\n{stderr}").trim().to_string();
+        if !raw.is_empty() {
+            let d = Diagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: raw,
+                file: None,
+                line: None,
+                column: None,
+            };
+            if let Some(sink) = on_diagnostic {
+                sink(&d);
+            }
+            diagnostics.push(d);
+        }
     }
+
+    Ok(CompilationOutput {
+        classes_dir: output_dir.to_path_buf(),
+        success: output.status.success(),
+        diagnostics,
+    })
 }
 
 impl TargetCompiler for JvmCompiler {
     fn compile(&self, unit: &CompilationUnit, env: &BuildEnv) -> miette::Result<CompilationOutput> {
-        self.invoke(unit, env, &unit.output_dir)
+        self.invoke(unit, env, &unit.output_dir, None)
     }
 
     fn check_only(
@@ -126,7 +155,27 @@ impl TargetCompiler for JvmCompiler {
     ) -> miette::Result<CompilationOutput> {
         let tmp = tempfile::tempdir().map_err(KargoError::Io)?;
         let tmp_out = tmp.path().to_path_buf();
-        self.invoke(unit, env, &tmp_out)
+        self.invoke(unit, env, &tmp_out, None)
+    }
+
+    fn compile_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        self.invoke(unit, env, &unit.output_dir, Some(on_diagnostic))
+    }
+
+    fn check_only_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        let tmp = tempfile::tempdir().map_err(KargoError::Io)?;
+        let tmp_out = tmp.path().to_path_buf();
+        self.invoke(unit, env, &tmp_out, Some(on_diagnostic))
     }
 
     fn target(&self) -> KotlinTarget {
@@ -138,72 +187,86 @@ impl TargetCompiler for JvmCompiler {
     }
 }
 
-fn parse_diagnostics(stdout: &str, stderr: &str) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
-    let combined = format!("{stdout}\n{stderr}");
-
-    for line in combined.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
+pub(crate) fn parse_diagnostics(stdout: &str, stderr: &str) -> Vec<Diagnostic> {
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .filter_map(parse_line)
+        .collect()
+}
 
-        if trimmed.contains(": error:") {
-            diagnostics.push(parse_diagnostic_line(trimmed, DiagnosticSeverity::Error));
-        } else if trimmed.contains(": warning:") {
-            diagnostics.push(parse_diagnostic_line(trimmed, DiagnosticSeverity::Warning));
-        } else if trimmed.contains(": info:") {
-            diagnostics.push(parse_diagnostic_line(trimmed, DiagnosticSeverity::Info));
-        }
+/// Parse one line of kotlinc output, if it is a diagnostic.
+fn parse_line(line: &str) -> Option<Diagnostic> {
+    let trimmed = line.trim();
+    if trimmed.contains(": error:") {
+        Some(parse_diagnostic_line(trimmed, DiagnosticSeverity::Error))
+    } else if trimmed.contains(": warning:") {
+        Some(parse_diagnostic_line(trimmed, DiagnosticSeverity::Warning))
+    } else if trimmed.contains(": info:") {
+        Some(parse_diagnostic_line(trimmed, DiagnosticSeverity::Info))
+    } else {
+        None
     }
-
-    diagnostics
 }
 
 fn parse_diagnostic_line(line: &str, severity: DiagnosticSeverity) -> Diagnostic {
     // kotlinc format: "file.kt:line:col: severity: message"
-    let parts: Vec<&str> = line
-        .splitn(
-            2,
-            match severity {
-                DiagnosticSeverity::Error => ": error:",
-                DiagnosticSeverity::Warning => ": warning:",
-                DiagnosticSeverity::Info => ": info:",
-            },
-        )
-        .collect();
-
-    let (file, line_num) = if let Some(location) = parts.first() {
-        let loc_parts: Vec<&str> = location.rsplitn(3, ':').collect();
-        if loc_parts.len() >= 2 {
-            let line_num = loc_parts.first().and_then(|s| s.parse::<u32>().ok());
-            let file = if loc_parts.len() >= 3 {
-                // Reconstruct file path (may contain colons on Windows)
-                Some(
-                    loc_parts[2..]
-                        .iter()
-                        .rev()
-                        .copied()
-                        .collect::<Vec<_>>()
-                        .join(":"),
-                )
-            } else {
-                Some(loc_parts.last().unwrap_or(&"").to_string())
-            };
-            (file, line_num)
-        } else {
-            (None, None)
-        }
-    } else {
-        (None, None)
+    let separator = format!(": {}:", severity.as_str());
+    let (location, message) = match line.split_once(&separator) {
+        Some((loc, msg)) => (loc, msg.trim()),
+        None => ("", line),
     };
 
-    let message = parts.get(1).unwrap_or(&line).trim().to_string();
+    // Peel up to two trailing numeric segments (line, then column) off the
+    // location. Whatever remains is the file path, which may itself contain
+    // colons on Windows.
+    let mut file = location;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        match file.rsplit_once(':') {
+            Some((rest, n)) if n.parse::<u32>().is_ok() => {
+                numbers.push(n.parse::<u32>().unwrap_or_default());
+                file = rest;
+            }
+            _ => break,
+        }
+    }
+    numbers.reverse();
 
     Diagnostic {
         severity,
-        message,
-        file,
-        line: line_num,
+        message: message.to_string(),
+        file: (!file.is_empty()).then(|| file.to_string()),
+        line: numbers.first().copied(),
+        column: numbers.get(1).copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_line_and_column() {
+        let d =
+            parse_line("src/main/kotlin/Main.kt:3:5: error: unresolved reference: foo").unwrap();
+        assert_eq!(d.severity, DiagnosticSeverity::Error);
+        assert_eq!(d.file.as_deref(), Some("src/main/kotlin/Main.kt"));
+        assert_eq!(d.line, Some(3));
+        assert_eq!(d.column, Some(5));
+        assert_eq!(d.message, "unresolved reference: foo");
+    }
+
+    #[test]
+    fn parses_windows_path_without_column() {
+        let d = parse_line(r"C:\src\Main.kt:12: warning: unused variable").unwrap();
+        assert_eq!(d.file.as_deref(), Some(r"C:\src\Main.kt"));
+        assert_eq!(d.line, Some(12));
+        assert_eq!(d.column, None);
+    }
+
+    #[test]
+    fn ignores_non_diagnostic_lines() {
+        assert!(parse_line("info: kotlinc-jvm 2.3.0").is_none());
     }
 }
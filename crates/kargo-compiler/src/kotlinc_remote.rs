@@ -0,0 +1,576 @@
+//! Experimental remote JVM compilation on a Remote Execution API cluster.
+//!
+//! With `[remote-execution]` in the global config, [`CompilerDispatch`]
+//! wraps the JVM backend in a [`RemoteCompiler`]. Each unit's sources,
+//! classpath entries and plugin jars become the input root of a REAPI
+//! action that runs `kotlinc` on a worker; the `out` directory it produces
+//! is downloaded into the unit's output directory, and diagnostics are
+//! mapped back to the local source paths.
+//!
+//! If the cluster cannot be reached or the action cannot be run, Kargo
+//! warns once and compiles locally for the rest of the process. A
+//! compilation that runs and fails is reported like a local one.
+//! Type-checking (`kargo check`) always runs locally.
+//!
+//! [`CompilerDispatch`]: crate::dispatch::CompilerDispatch
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use kargo_core::config::RemoteExecutionConfig;
+use kargo_core::target::KotlinTarget;
+use kargo_toolchain::discovery::ToolchainPaths;
+use kargo_util::errors::KargoError;
+use prost::Message;
+
+use crate::dispatch::TargetCompiler;
+use crate::env::BuildEnv;
+use crate::kotlinc::parse_diagnostics;
+use crate::reapi::{self, proto, Blob, InputTree};
+use crate::unit::{CompilationOutput, CompilationUnit, Diagnostic, DiagnosticSeverity};
+
+/// Set once remote execution has failed, so later units go straight to the
+/// local compiler.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Output directory of the remote action, relative to its input root.
+const OUT_DIR: &str = "out";
+
+/// Compiles JVM units remotely, falling back to `local`.
+pub struct RemoteCompiler {
+    local: Box<dyn TargetCompiler>,
+    config: RemoteExecutionConfig,
+    java_target: String,
+}
+
+/// An action ready to run, and how to map its results back.
+struct Prepared {
+    action: proto::Digest,
+    blobs: BTreeMap<proto::Digest, Blob>,
+    /// Remote source path -> local source path.
+    sources: BTreeMap<String, PathBuf>,
+}
+
+impl RemoteCompiler {
+    pub fn new(
+        local: Box<dyn TargetCompiler>,
+        config: RemoteExecutionConfig,
+        java_target: String,
+    ) -> Self {
+        Self {
+            local,
+            config,
+            java_target,
+        }
+    }
+
+    fn invoke(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: Option<&mut dyn FnMut(&Diagnostic)>,
+    ) -> miette::Result<CompilationOutput> {
+        let sources = unit.all_sources();
+        if sources.is_empty() || DISABLED.load(Ordering::Relaxed) {
+            return self.local(unit, env, on_diagnostic);
+        }
+        match self.remote(unit, env, &sources) {
+            Ok(output) => {
+                if let Some(sink) = on_diagnostic {
+                    output.diagnostics.iter().for_each(sink);
+                }
+                Ok(output)
+            }
+            Err(e) => {
+                if !DISABLED.swap(true, Ordering::Relaxed) {
+                    kargo_util::progress::status_warn(
+                        "Remote",
+                        &format!("{e}; compiling locally from now on"),
+                    );
+                }
+                self.local(unit, env, on_diagnostic)
+            }
+        }
+    }
+
+    fn local(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: Option<&mut dyn FnMut(&Diagnostic)>,
+    ) -> miette::Result<CompilationOutput> {
+        match on_diagnostic {
+            Some(sink) => self.local.compile_streaming(unit, env, sink),
+            None => self.local.compile(unit, env),
+        }
+    }
+
+    /// Compile `unit` on the cluster. An error means it could not be run
+    /// there; compile errors are diagnostics in the output.
+    fn remote(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        sources: &[PathBuf],
+    ) -> miette::Result<CompilationOutput> {
+        let prepared = self.prepare(unit, env, sources).map_err(KargoError::Io)?;
+        // The build may already be inside a runtime, so the client gets
+        // its own on a separate thread.
+        let config = &self.config;
+        let (result, stdout, stderr) = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(KargoError::Io)?
+                        .block_on(run(config, &prepared, &unit.output_dir))
+                })
+                .join()
+                .unwrap_or_else(|_| {
+                    Err(KargoError::Generic {
+                        message: "remote execution client panicked".into(),
+                    }
+                    .into())
+                })
+        })?;
+
+        let stdout = String::from_utf8_lossy(&stdout);
+        let stderr = String::from_utf8_lossy(&stderr);
+        let success = result.exit_code == 0;
+        let mut diagnostics = parse_diagnostics(&stdout, &stderr);
+        for d in &mut diagnostics {
+            if let Some(local) = d.file.as_ref().and_then(|f| prepared.sources.get(f)) {
+                d.file = Some(local.to_string_lossy().into_owned());
+            }
+        }
+        if !success && diagnostics.is_empty() {
+            let raw = format!("{stdout}\n{stderr}").trim().to_string();
+            // The command never ran: a worker problem, not a compile error.
+            if matches!(result.exit_code, 126 | 127) || raw.is_empty() {
+                return Err(KargoError::Generic {
+                    message: format!(
+                        "remote kotlinc exited with code {}: {raw}",
+                        result.exit_code
+                    ),
+                }
+                .into());
+            }
+            diagnostics.push(Diagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: raw,
+                file: None,
+                line: None,
+                column: None,
+            });
+        }
+        Ok(CompilationOutput {
+            classes_dir: unit.output_dir.clone(),
+            success,
+            diagnostics,
+        })
+    }
+
+    /// Build the input root, command and action for `unit`.
+    fn prepare(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        sources: &[PathBuf],
+    ) -> std::io::Result<Prepared> {
+        let mut tree = InputTree::default();
+        let mut mapped = BTreeMap::new();
+
+        let base = common_ancestor(sources);
+        let mut source_args = Vec::new();
+        for src in sources {
+            let relative = src.strip_prefix(&base).unwrap_or(src);
+            let remote = format!("src/{}", slash_path(relative));
+            tree.add_file(&remote, src)?;
+            source_args.push(remote.clone());
+            mapped.insert(remote, src.clone());
+        }
+
+        let mut classpath = Vec::new();
+        for (i, entry) in unit.classpath.iter().enumerate() {
+            let name = entry
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "entry".into());
+            let remote = format!("classpath/{i}/{name}");
+            if entry.is_dir() {
+                tree.add_dir(&remote, entry)?;
+            } else if entry.is_file() {
+                tree.add_file(&remote, entry)?;
+            } else {
+                continue;
+            }
+            classpath.push(remote);
+        }
+
+        let mut args = vec![
+            self.config
+                .kotlinc
+                .clone()
+                .unwrap_or_else(|| "kotlinc".into()),
+            "-d".into(),
+            OUT_DIR.into(),
+            "-jvm-target".into(),
+            self.java_target.clone(),
+        ];
+        if !classpath.is_empty() {
+            // Workers are unix machines.
+            args.push("-classpath".into());
+            args.push(classpath.join(":"));
+        }
+        for (i, arg) in unit.compiler_args.iter().enumerate() {
+            args.push(tool_arg(&mut tree, i, arg)?);
+        }
+        args.extend(source_args);
+
+        let mut environment: BTreeMap<String, String> = env
+            .vars
+            .iter()
+            .filter(|(k, _)| k.starts_with("KARGO_"))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        if let Some(java_home) = &self.config.java_home {
+            environment.insert("JAVA_HOME".into(), java_home.clone());
+        }
+        let platform = proto::Platform {
+            properties: self
+                .config
+                .platform
+                .iter()
+                .map(|(name, value)| proto::PlatformProperty {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+        };
+
+        let command = proto::Command {
+            arguments: args,
+            environment_variables: environment
+                .into_iter()
+                .map(|(name, value)| proto::EnvironmentVariable { name, value })
+                .collect(),
+            output_directories: vec![OUT_DIR.into()],
+            platform: Some(platform.clone()),
+            working_directory: String::new(),
+            output_paths: vec![OUT_DIR.into()],
+        };
+        let (input_root, mut blobs) = tree.finish();
+        let command = command.encode_to_vec();
+        let command_digest = reapi::digest(&command);
+        blobs.insert(command_digest.clone(), Blob::Bytes(command));
+
+        let timeout = self
+            .config
+            .timeout
+            .as_deref()
+            .and_then(kargo_core::manifest::parse_duration)
+            .map(|d| proto::Duration {
+                seconds: d.as_secs() as i64,
+                nanos: d.subsec_nanos() as i32,
+            });
+        let action = proto::Action {
+            command_digest: Some(command_digest),
+            input_root_digest: Some(input_root),
+            timeout,
+            do_not_cache: false,
+            platform: Some(platform),
+        };
+        let action = action.encode_to_vec();
+        let action_digest = reapi::digest(&action);
+        blobs.insert(action_digest.clone(), Blob::Bytes(action));
+
+        Ok(Prepared {
+            action: action_digest,
+            blobs,
+            sources: mapped,
+        })
+    }
+}
+
+/// Upload, execute and download; returns the result and its output.
+async fn run(
+    config: &RemoteExecutionConfig,
+    prepared: &Prepared,
+    output_dir: &Path,
+) -> miette::Result<(proto::ActionResult, Vec<u8>, Vec<u8>)> {
+    let mut client =
+        reapi::Client::connect(&config.endpoint, &config.instance_name, &config.headers).await?;
+    client.upload(&prepared.blobs).await?;
+    let result = client.execute(&prepared.action).await?;
+
+    let stdout = match &result.stdout_digest {
+        Some(digest) if result.stdout_raw.is_empty() => client.read(digest).await?,
+        _ => result.stdout_raw.clone(),
+    };
+    let stderr = match &result.stderr_digest {
+        Some(digest) if result.stderr_raw.is_empty() => client.read(digest).await?,
+        _ => result.stderr_raw.clone(),
+    };
+    if result.exit_code != 0 {
+        return Ok((result, stdout, stderr));
+    }
+
+    std::fs::create_dir_all(output_dir).map_err(KargoError::Io)?;
+    for dir in &result.output_directories {
+        let Some(digest) = &dir.tree_digest else {
+            continue;
+        };
+        let tree = proto::Tree::decode(client.read(digest).await?.as_slice()).map_err(|e| {
+            KargoError::Network {
+                message: format!("remote execution: decoding output tree: {e}"),
+            }
+        })?;
+        let children: BTreeMap<proto::Digest, &proto::Directory> = tree
+            .children
+            .iter()
+            .map(|child| (reapi::digest(&child.encode_to_vec()), child))
+            .collect();
+        if let Some(root) = &tree.root {
+            let target = output_dir.join(dir.path.strip_prefix(OUT_DIR).unwrap_or(&dir.path));
+            write_directory(&mut client, root, &children, &target).await?;
+        }
+    }
+    for file in &result.output_files {
+        let relative = file.path.strip_prefix(OUT_DIR).unwrap_or(&file.path);
+        let target = output_dir.join(relative.trim_start_matches('/'));
+        let data = match &file.digest {
+            Some(digest) if file.contents.is_empty() => client.read(digest).await?,
+            _ => file.contents.clone(),
+        };
+        write_file(&target, &data)?;
+    }
+    Ok((result, stdout, stderr))
+}
+
+/// Download the files of `dir` into `target`.
+async fn write_directory(
+    client: &mut reapi::Client,
+    dir: &proto::Directory,
+    children: &BTreeMap<proto::Digest, &proto::Directory>,
+    target: &Path,
+) -> miette::Result<()> {
+    std::fs::create_dir_all(target).map_err(KargoError::Io)?;
+    for file in &dir.files {
+        let Some(digest) = &file.digest else {
+            continue;
+        };
+        let data = client.read(digest).await?;
+        write_file(&target.join(&file.name), &data)?;
+    }
+    for child in &dir.directories {
+        let Some(child_dir) = child.digest.as_ref().and_then(|d| children.get(d)) else {
+            return Err(KargoError::Network {
+                message: format!(
+                    "remote execution: output tree is missing directory `{}`",
+                    child.name
+                ),
+            }
+            .into());
+        };
+        Box::pin(write_directory(
+            client,
+            child_dir,
+            children,
+            &target.join(&child.name),
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, data: &[u8]) -> miette::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    std::fs::write(path, data).map_err(KargoError::Io)?;
+    Ok(())
+}
+
+/// `arg` with a local absolute path, alone or after `=` (`-Xplugin=...`),
+/// replaced by the same file uploaded under `tools/<i>/`.
+fn tool_arg(tree: &mut InputTree, i: usize, arg: &str) -> std::io::Result<String> {
+    let (prefix, value) = match arg.split_once('=') {
+        Some((flag, value)) => (format!("{flag}="), value),
+        None => (String::new(), arg),
+    };
+    let path = Path::new(value);
+    if !path.is_absolute() || !path.exists() {
+        return Ok(arg.to_string());
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "tool".into());
+    let remote = format!("tools/{i}/{name}");
+    if path.is_dir() {
+        tree.add_dir(&remote, path)?;
+    } else {
+        tree.add_file(&remote, path)?;
+    }
+    Ok(format!("{prefix}{remote}"))
+}
+
+/// The deepest directory containing every file in `files`.
+fn common_ancestor(files: &[PathBuf]) -> PathBuf {
+    let mut base = files
+        .first()
+        .and_then(|f| f.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    for file in files {
+        while !file.starts_with(&base) {
+            if !base.pop() {
+                break;
+            }
+        }
+    }
+    base
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl TargetCompiler for RemoteCompiler {
+    fn compile(&self, unit: &CompilationUnit, env: &BuildEnv) -> miette::Result<CompilationOutput> {
+        self.invoke(unit, env, None)
+    }
+
+    fn check_only(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+    ) -> miette::Result<CompilationOutput> {
+        self.local.check_only(unit, env)
+    }
+
+    fn compile_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        self.invoke(unit, env, Some(on_diagnostic))
+    }
+
+    fn check_only_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        self.local.check_only_streaming(unit, env, on_diagnostic)
+    }
+
+    fn target(&self) -> KotlinTarget {
+        self.local.target()
+    }
+
+    fn compiler_binary(&self, toolchain: &ToolchainPaths) -> PathBuf {
+        self.local.compiler_binary(toolchain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Local;
+
+    impl TargetCompiler for Local {
+        fn compile(
+            &self,
+            unit: &CompilationUnit,
+            _: &BuildEnv,
+        ) -> miette::Result<CompilationOutput> {
+            Ok(CompilationOutput {
+                classes_dir: unit.output_dir.clone(),
+                success: true,
+                diagnostics: vec![],
+            })
+        }
+
+        fn check_only(
+            &self,
+            unit: &CompilationUnit,
+            env: &BuildEnv,
+        ) -> miette::Result<CompilationOutput> {
+            self.compile(unit, env)
+        }
+
+        fn target(&self) -> KotlinTarget {
+            KotlinTarget::Jvm
+        }
+
+        fn compiler_binary(&self, _: &ToolchainPaths) -> PathBuf {
+            PathBuf::from("kotlinc")
+        }
+    }
+
+    fn unit(dir: &Path) -> CompilationUnit {
+        let src = dir.join("src/main/kotlin/app");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("Main.kt"), "fun main() {}\n").unwrap();
+        std::fs::write(dir.join("src/main/kotlin/Util.kt"), "fun util() {}\n").unwrap();
+        CompilationUnit {
+            name: "main".into(),
+            target: KotlinTarget::Jvm,
+            sources: vec![src.join("Main.kt"), dir.join("src/main/kotlin/Util.kt")],
+            resource_dirs: vec![],
+            classpath: vec![],
+            output_dir: dir.join("out"),
+            compiler_args: vec![],
+            is_test: false,
+            generated_sources: vec![],
+            processor_jars: vec![],
+            local_jars: vec![],
+        }
+    }
+
+    #[test]
+    fn sources_are_placed_under_their_common_ancestor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let unit = unit(tmp.path());
+        let compiler = RemoteCompiler::new(
+            Box::new(Local),
+            RemoteExecutionConfig::default(),
+            "17".into(),
+        );
+        let env = BuildEnv {
+            vars: HashMap::new(),
+        };
+        let prepared = compiler.prepare(&unit, &env, &unit.all_sources()).unwrap();
+        assert_eq!(
+            prepared.sources.keys().collect::<Vec<_>>(),
+            ["src/Util.kt", "src/app/Main.kt"]
+        );
+    }
+
+    #[test]
+    fn unreachable_cluster_falls_back_to_local() {
+        let tmp = tempfile::tempdir().unwrap();
+        let unit = unit(tmp.path());
+        let config = RemoteExecutionConfig {
+            endpoint: "grpc://127.0.0.1:1".into(),
+            ..Default::default()
+        };
+        let compiler = RemoteCompiler::new(Box::new(Local), config, "17".into());
+        let env = BuildEnv {
+            vars: HashMap::new(),
+        };
+        let output = compiler.compile(&unit, &env).unwrap();
+        assert!(output.success);
+        assert!(DISABLED.load(Ordering::Relaxed));
+    }
+}
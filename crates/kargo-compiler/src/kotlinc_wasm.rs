@@ -0,0 +1,293 @@
+//! Kotlin/Wasm compiler invocation for the `wasm-js` target.
+//!
+//! Uses the K2 wasm backend of `kotlinc-js` in two phases, mirroring what the
+//! Gradle plugin does: sources are first compiled to a klib, which is then
+//! linked with its dependency klibs into `<name>.wasm` plus the `<name>.mjs`
+//! ES module loader. Type-checking only runs the first phase.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::target::KotlinTarget;
+use kargo_toolchain::discovery::ToolchainPaths;
+use kargo_util::errors::KargoError;
+
+use crate::dispatch::TargetCompiler;
+use crate::env::BuildEnv;
+use crate::kotlinc::run_compiler;
+use crate::unit::{CompilationOutput, CompilationUnit, Diagnostic};
+
+/// File name of the wasm-js standard library klib.
+pub const STDLIB_KLIB: &str = "kotlin-stdlib-wasm-js";
+
+/// Compiler backend for the `wasm-js` target.
+pub struct WasmCompiler {
+    target: KotlinTarget,
+    toolchain: ToolchainPaths,
+    jdk_home: PathBuf,
+}
+
+impl WasmCompiler {
+    pub fn new(target: KotlinTarget, toolchain: ToolchainPaths, jdk_home: PathBuf) -> Self {
+        Self {
+            target,
+            toolchain,
+            jdk_home,
+        }
+    }
+
+    fn invoke(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        output_dir: &Path,
+        link: bool,
+        mut on_diagnostic: Option<&mut dyn FnMut(&Diagnostic)>,
+    ) -> miette::Result<CompilationOutput> {
+        let all_sources = unit.all_sources();
+        if all_sources.is_empty() {
+            return Ok(CompilationOutput {
+                classes_dir: output_dir.to_path_buf(),
+                success: true,
+                diagnostics: vec![],
+            });
+        }
+
+        let libraries = self.libraries(unit)?;
+        let module_name = output_name(env);
+        let klib_dir = output_dir.join("klib");
+        std::fs::create_dir_all(&klib_dir).map_err(KargoError::Io)?;
+
+        // Phase 1: sources -> klib
+        let mut args = self.common_args(&libraries, &module_name);
+        args.extend([
+            "-Xir-produce-klib-dir".into(),
+            "-ir-output-dir".into(),
+            klib_dir.to_string_lossy().into(),
+        ]);
+        args.extend(unit.compiler_args.iter().cloned());
+        args.extend(all_sources.iter().map(|s| s.to_string_lossy().to_string()));
+
+        let output = run_compiler(
+            self.command(args, env),
+            "kotlinc-js",
+            output_dir,
+            on_diagnostic
+                .as_mut()
+                .map(|sink| &mut **sink as &mut dyn FnMut(&Diagnostic)),
+        )?;
+        if !output.success || !link {
+            return Ok(output);
+        }
+
+        // Phase 2: klib + dependencies -> .wasm and .mjs loader
+        let mut args = self.common_args(&libraries, &module_name);
+        args.extend([
+            "-Xir-produce-js".into(),
+            format!("-Xinclude={}", klib_dir.to_string_lossy()),
+            "-ir-output-dir".into(),
+            output_dir.to_string_lossy().into(),
+        ]);
+        args.extend(unit.compiler_args.iter().cloned());
+
+        let mut linked = run_compiler(
+            self.command(args, env),
+            "kotlinc-js",
+            output_dir,
+            on_diagnostic,
+        )?;
+        let mut diagnostics = output.diagnostics;
+        diagnostics.append(&mut linked.diagnostics);
+        linked.diagnostics = diagnostics;
+        Ok(linked)
+    }
+
+    fn common_args(&self, libraries: &[PathBuf], module_name: &str) -> Vec<String> {
+        vec![
+            "-Xwasm".into(),
+            "-Xwasm-target=wasm-js".into(),
+            "-libraries".into(),
+            std::env::join_paths(libraries)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            "-ir-output-name".into(),
+            module_name.into(),
+        ]
+    }
+
+    fn command(&self, args: Vec<String>, env: &BuildEnv) -> kargo_util::process::CommandBuilder {
+        let bin = self.compiler_binary(&self.toolchain);
+        let mut cmd = kargo_util::process::CommandBuilder::new(bin.to_string_lossy().to_string())
+            .args(args)
+            .response_file(true)
+            .env("JAVA_HOME", self.jdk_home.to_string_lossy().to_string());
+        for (k, v) in &env.vars {
+            cmd = cmd.env(k, v);
+        }
+        cmd
+    }
+
+    /// The stdlib klib followed by every klib on the unit's classpath.
+    /// JARs are ignored: they cannot be linked into a wasm module.
+    fn libraries(&self, unit: &CompilationUnit) -> miette::Result<Vec<PathBuf>> {
+        let klibs: Vec<PathBuf> = unit
+            .classpath
+            .iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "klib"))
+            .cloned()
+            .collect();
+
+        let bundled = self
+            .toolchain
+            .home
+            .join("lib")
+            .join(format!("{STDLIB_KLIB}.klib"));
+        let declared = klibs.iter().any(|p| {
+            p.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(STDLIB_KLIB))
+        });
+
+        if declared {
+            Ok(klibs)
+        } else if bundled.is_file() {
+            let mut libs = vec![bundled];
+            libs.extend(klibs);
+            Ok(libs)
+        } else {
+            Err(KargoError::Generic {
+                message: format!(
+                    "The Kotlin toolchain at {} does not bundle {STDLIB_KLIB}.klib.\n  \
+                     Add it as a dependency:\n\n  \
+                     [target.wasm-js.dependencies]\n  \
+                     {STDLIB_KLIB} = \"org.jetbrains.kotlin:{STDLIB_KLIB}:{}\"",
+                    self.toolchain.home.display(),
+                    self.toolchain.version
+                ),
+            }
+            .into())
+        }
+    }
+}
+
+/// Base name of the linked module: the package name, or `main` if unknown.
+pub fn output_name(env: &BuildEnv) -> String {
+    env.vars
+        .get("KARGO_PKG_NAME")
+        .cloned()
+        .unwrap_or_else(|| "main".to_string())
+}
+
+impl TargetCompiler for WasmCompiler {
+    fn compile(&self, unit: &CompilationUnit, env: &BuildEnv) -> miette::Result<CompilationOutput> {
+        self.invoke(unit, env, &unit.output_dir, !unit.is_test, None)
+    }
+
+    fn check_only(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+    ) -> miette::Result<CompilationOutput> {
+        let tmp = tempfile::tempdir().map_err(KargoError::Io)?;
+        self.invoke(unit, env, tmp.path(), false, None)
+    }
+
+    fn compile_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        self.invoke(
+            unit,
+            env,
+            &unit.output_dir,
+            !unit.is_test,
+            Some(on_diagnostic),
+        )
+    }
+
+    fn check_only_streaming(
+        &self,
+        unit: &CompilationUnit,
+        env: &BuildEnv,
+        on_diagnostic: &mut dyn FnMut(&Diagnostic),
+    ) -> miette::Result<CompilationOutput> {
+        let tmp = tempfile::tempdir().map_err(KargoError::Io)?;
+        self.invoke(unit, env, tmp.path(), false, Some(on_diagnostic))
+    }
+
+    fn target(&self) -> KotlinTarget {
+        self.target
+    }
+
+    fn compiler_binary(&self, toolchain: &ToolchainPaths) -> PathBuf {
+        toolchain.home.join("bin").join("kotlinc-js")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_toolchain::version::KotlinVersion;
+
+    fn compiler(home: &Path) -> WasmCompiler {
+        WasmCompiler::new(
+            KotlinTarget::WasmJs,
+            ToolchainPaths {
+                home: home.to_path_buf(),
+                version: KotlinVersion::new(2, 3, 0),
+                kotlinc: home.join("bin/kotlinc"),
+                kotlin_native: None,
+            },
+            PathBuf::from("/jdk"),
+        )
+    }
+
+    fn unit(classpath: Vec<PathBuf>) -> CompilationUnit {
+        CompilationUnit {
+            name: "main".into(),
+            target: KotlinTarget::WasmJs,
+            sources: vec![],
+            resource_dirs: vec![],
+            classpath,
+            output_dir: PathBuf::from("build/wasm-js/dev/classes"),
+            compiler_args: vec![],
+            is_test: false,
+            generated_sources: vec![],
+            processor_jars: vec![],
+            local_jars: vec![],
+        }
+    }
+
+    #[test]
+    fn libraries_prepend_bundled_stdlib_and_skip_jars() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lib = tmp.path().join("lib");
+        std::fs::create_dir_all(&lib).unwrap();
+        std::fs::write(lib.join("kotlin-stdlib-wasm-js.klib"), b"").unwrap();
+
+        let libs = compiler(tmp.path())
+            .libraries(&unit(vec![
+                PathBuf::from("/cache/coroutines-wasm-js-1.9.0.klib"),
+                PathBuf::from("/cache/guava-33.0.jar"),
+            ]))
+            .unwrap();
+        assert_eq!(
+            libs,
+            vec![
+                lib.join("kotlin-stdlib-wasm-js.klib"),
+                PathBuf::from("/cache/coroutines-wasm-js-1.9.0.klib"),
+            ]
+        );
+    }
+
+    #[test]
+    fn libraries_require_a_stdlib() {
+        let tmp = tempfile::tempdir().unwrap();
+        let c = compiler(tmp.path());
+        assert!(c.libraries(&unit(vec![])).is_err());
+
+        let declared = PathBuf::from("/cache/kotlin-stdlib-wasm-js-2.3.0.klib");
+        let libs = c.libraries(&unit(vec![declared.clone()])).unwrap();
+        assert_eq!(libs, vec![declared]);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod abi;
 pub mod build_cache;
+pub mod build_report;
 pub mod buildconfig;
 pub mod classpath;
 pub mod compose;
@@ -6,12 +8,17 @@ pub mod dispatch;
 pub mod env;
 pub mod fingerprint;
 pub mod incremental;
+pub mod jar;
 pub mod job_queue;
 pub mod kotlinc;
 pub mod kotlinc_js;
 pub mod kotlinc_native;
+pub mod kotlinc_remote;
+pub mod kotlinc_wasm;
 pub mod plugins;
+pub mod reapi;
 pub mod source_set_discovery;
 pub mod source_set_hierarchy;
 pub mod unit;
 pub mod unit_graph;
+pub mod unit_split;
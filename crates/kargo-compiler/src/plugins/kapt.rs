@@ -73,7 +73,7 @@ pub fn run_kapt_pass(
     }
 
     let kotlinc = ap.kotlin_home.join("bin").join("kotlinc");
-    let mut cmd = kargo_util::process::CommandBuilder::new(kotlinc.to_string_lossy().to_string());
+    let mut cmd = ap.command(&kotlinc)?;
 
     cmd = cmd.arg(format!("-Xplugin={}", kapt_plugin_jar.to_string_lossy()));
 
@@ -143,6 +143,7 @@ pub fn run_kapt_pass(
     let output = cmd.exec().map_err(|e| KargoError::Generic {
         message: format!("Failed to run KAPT pass: {e}"),
     })?;
+    ap.report_violations("KAPT", &output);
 
     let stdout_text = String::from_utf8_lossy(&output.stdout);
     let stderr_text = String::from_utf8_lossy(&output.stderr);
@@ -278,6 +278,7 @@ async fn ensure_ksp2_from_github(
     let zip_bytes = resp.bytes().await.map_err(|e| KargoError::Generic {
         message: format!("Failed to read KSP archive: {e}"),
     })?;
+    kargo_util::trust::verify_bytes("ksp", ksp_version, &url, &zip_bytes)?;
 
     let reader = std::io::Cursor::new(&zip_bytes);
     let mut archive = zip::ZipArchive::new(reader).map_err(|e| KargoError::Generic {
@@ -469,7 +470,8 @@ pub fn run_ksp2_standalone(
 
     let java_bin = ap.jdk_home.join("bin").join("java");
 
-    let mut cmd = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+    let mut cmd = ap
+        .command(&java_bin)?
         .arg("-cp")
         .arg(&tool_cp)
         .arg("com.google.devtools.ksp.cmdline.KSPJvmMain")
@@ -547,6 +549,7 @@ pub fn run_ksp2_standalone(
     let output = cmd.exec().map_err(|e| KargoError::Generic {
         message: format!("Failed to run KSP2: {e}"),
     })?;
+    ap.report_violations("KSP2", &output);
 
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stdout = String::from_utf8_lossy(&output.stdout);
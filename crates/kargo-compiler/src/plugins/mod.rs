@@ -15,6 +15,7 @@
 
 pub mod kapt;
 pub mod ksp;
+pub mod sandbox;
 
 pub use kapt::*;
 pub use ksp::*;
@@ -22,7 +23,7 @@ pub use ksp::*;
 use std::path::{Path, PathBuf};
 
 use kargo_core::dependency::Dependency;
-use kargo_core::manifest::Manifest;
+use kargo_core::manifest::{Manifest, SandboxConfig};
 use kargo_maven::cache::LocalCache;
 
 /// Shared context for annotation processing invocations (KSP2, KSP1, KAPT).
@@ -37,6 +38,8 @@ pub struct ApContext<'a> {
     pub jdk_home: &'a Path,
     pub project_dir: &'a Path,
     pub generated_dir: &'a Path,
+    /// `[sandbox]` of the package, restricting what processors may access.
+    pub sandbox: Option<&'a SandboxConfig>,
 }
 
 // ---------------------------------------------------------------------------
@@ -100,7 +103,7 @@ fn parse_processor(name: &str, dep: &Dependency, kind: ProcessorKind) -> Option<
             version: d.version.clone(),
             kind,
         }),
-        Dependency::Catalog(_) => None,
+        Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
     }
 }
 
@@ -114,17 +117,29 @@ pub async fn ensure_maven_jar(
     group: &str,
     artifact: &str,
     version: &str,
+) -> miette::Result<Option<PathBuf>> {
+    let repo = kargo_maven::repository::MavenRepository::maven_central();
+    ensure_maven_jar_from(&repo, cache, group, artifact, version).await
+}
+
+/// Like [`ensure_maven_jar`], but downloads from `repo` instead of Maven
+/// Central.
+pub async fn ensure_maven_jar_from(
+    repo: &kargo_maven::repository::MavenRepository,
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
 ) -> miette::Result<Option<PathBuf>> {
     if let Some(path) = cache.get_jar(group, artifact, version, None) {
         return Ok(Some(path));
     }
 
-    let repo = kargo_maven::repository::MavenRepository::maven_central();
     let client = kargo_maven::download::build_client()?;
     let url = repo.jar_url(group, artifact, version, None);
     let label = format!("{artifact}:{version}");
 
-    match kargo_maven::download::download_artifact(&client, &repo, &url, &label).await? {
+    match kargo_maven::download::download_artifact(&client, repo, &url, &label).await? {
         Some(data) => {
             let path = cache.put_jar(group, artifact, version, None, &data)?;
             Ok(Some(path))
@@ -0,0 +1,231 @@
+//! Sandboxed execution of annotation processors (`[sandbox] processors =
+//! true`).
+//!
+//! KSP and KAPT processors are arbitrary code from dependencies. In the
+//! sandbox they run under bubblewrap (`bwrap`) in fresh user, PID and
+//! network namespaces with a filesystem that holds only the system
+//! directories, the JDK and Kotlin toolchain, the dependency cache, the
+//! unit's sources and classpath, and `allow-read` paths, all read-only.
+//! Only the generated-sources directory is writable, and `HOME` is an empty
+//! tmpfs.
+//!
+//! Accesses the sandbox denies surface as exceptions in the processor's
+//! output; [`Sandbox::violations`] picks them out so they can be reported
+//! instead of leaving a puzzling processor failure. Only Linux is supported
+//! for now.
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use kargo_core::manifest::SandboxConfig;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+
+use super::ApContext;
+
+/// System directories mounted read-only when they exist.
+const SYSTEM_DIRS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc"];
+
+/// Output fragments showing a denied file or network access.
+const DENIED: &[&str] = &[
+    "AccessDeniedException",
+    "Permission denied",
+    "Read-only file system",
+    "UnknownHostException",
+    "Network is unreachable",
+    "ConnectException",
+];
+
+/// What a sandboxed process may access.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+    network: bool,
+    cwd: PathBuf,
+}
+
+impl Sandbox {
+    /// The sandbox for the processors of `ap`.
+    pub fn for_processors(ap: &ApContext<'_>, config: &SandboxConfig) -> Self {
+        let mut read = vec![ap.jdk_home.to_path_buf(), ap.kotlin_home.to_path_buf()];
+        read.extend(ap.cache.roots().map(Path::to_path_buf));
+        read.extend(ap.sources.iter().cloned());
+        read.extend(ap.library_jars.iter().cloned());
+        read.extend(ap.processor_scope_jars.iter().cloned());
+        read.extend(config.allow_read.iter().map(|p| ap.project_dir.join(p)));
+        Self {
+            read,
+            write: vec![ap.generated_dir.to_path_buf()],
+            network: config.allow_network,
+            cwd: ap.project_dir.to_path_buf(),
+        }
+    }
+
+    /// A command running `program` inside the sandbox.
+    pub fn command(&self, program: &Path) -> miette::Result<CommandBuilder> {
+        if !cfg!(target_os = "linux") {
+            return Err(KargoError::Generic {
+                message: "`[sandbox] processors = true` is only supported on Linux so far".into(),
+            }
+            .into());
+        }
+        let bwrap = find_bwrap().ok_or_else(|| KargoError::Generic {
+            message: "`[sandbox] processors = true` needs bubblewrap (`bwrap`) on the PATH".into(),
+        })?;
+        for dir in &self.write {
+            std::fs::create_dir_all(dir).map_err(KargoError::Io)?;
+        }
+        Ok(CommandBuilder::new(bwrap.to_string_lossy().to_string())
+            .args(self.bwrap_args(program))
+            .env("HOME", "/tmp"))
+    }
+
+    fn bwrap_args(&self, program: &Path) -> Vec<String> {
+        let mut args: Vec<String> = ["--unshare-all", "--die-with-parent", "--new-session"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        if self.network {
+            args.push("--share-net".into());
+        }
+        for dir in SYSTEM_DIRS {
+            args.extend(["--ro-bind-try".into(), dir.to_string(), dir.to_string()]);
+        }
+        for (mode, paths) in [("--ro-bind-try", &self.read), ("--bind", &self.write)] {
+            for path in paths {
+                let path = path.to_string_lossy().to_string();
+                args.extend([mode.to_string(), path.clone(), path]);
+            }
+        }
+        args.extend(
+            [
+                "--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp", "--chdir",
+            ]
+            .iter()
+            .map(|s| s.to_string()),
+        );
+        args.push(self.cwd.to_string_lossy().into());
+        args.push("--".into());
+        args.push(program.to_string_lossy().into());
+        args
+    }
+
+    /// Lines of `output` showing an access the sandbox denied: permission
+    /// and network errors, and missing files outside the readable paths.
+    pub fn violations(&self, output: &Output) -> Vec<String> {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut found: Vec<String> = Vec::new();
+        for line in stdout.lines().chain(stderr.lines()) {
+            let line = line.trim();
+            let denied = DENIED.iter().any(|d| line.contains(d))
+                || missing_path(line).is_some_and(|p| !self.readable(Path::new(p)));
+            if denied && !found.iter().any(|f| f == line) {
+                found.push(line.to_string());
+            }
+        }
+        found
+    }
+
+    fn readable(&self, path: &Path) -> bool {
+        SYSTEM_DIRS
+            .iter()
+            .map(Path::new)
+            .chain(self.read.iter().map(PathBuf::as_path))
+            .chain(self.write.iter().map(PathBuf::as_path))
+            .chain([Path::new("/tmp"), Path::new("/proc"), Path::new("/dev")])
+            .any(|root| path.starts_with(root))
+    }
+}
+
+/// The path of a `NoSuchFileException` or `FileNotFoundException` line.
+fn missing_path(line: &str) -> Option<&str> {
+    let (_, rest) = line
+        .split_once("NoSuchFileException: ")
+        .or_else(|| line.split_once("FileNotFoundException: "))?;
+    let path = rest.split(" (").next()?.trim();
+    path.starts_with('/').then_some(path)
+}
+
+fn find_bwrap() -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join("bwrap"))
+        .find(|candidate| candidate.is_file())
+}
+
+impl ApContext<'_> {
+    /// A command running `program`, inside the processor sandbox when
+    /// `[sandbox] processors` is on. `program` is kotlinc or a JDK 9+
+    /// `java`, so outside the sandbox the arguments go in a response file.
+    pub fn command(&self, program: &Path) -> miette::Result<CommandBuilder> {
+        match self.sandbox.filter(|s| s.processors) {
+            Some(config) => Sandbox::for_processors(self, config).command(program),
+            None => {
+                Ok(CommandBuilder::new(program.to_string_lossy().to_string()).response_file(true))
+            }
+        }
+    }
+
+    /// Warn about each access the sandbox denied `tool`.
+    pub fn report_violations(&self, tool: &str, output: &Output) {
+        let Some(config) = self.sandbox.filter(|s| s.processors) else {
+            return;
+        };
+        for line in Sandbox::for_processors(self, config).violations(output) {
+            kargo_util::progress::status_warn("Sandbox", &format!("{tool} was denied: {line}"));
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn sandbox() -> Sandbox {
+        Sandbox {
+            read: vec![PathBuf::from("/work/app/src/main/kotlin")],
+            write: vec![PathBuf::from("/work/app/build/generated")],
+            network: false,
+            cwd: PathBuf::from("/work/app"),
+        }
+    }
+
+    #[test]
+    fn only_declared_paths_are_mounted() {
+        let args = sandbox().bwrap_args(Path::new("/jdk/bin/java"));
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--unshare-all"));
+        assert!(!joined.contains("--share-net"));
+        assert!(
+            joined.contains("--ro-bind-try /work/app/src/main/kotlin /work/app/src/main/kotlin")
+        );
+        assert!(joined.contains("--bind /work/app/build/generated /work/app/build/generated"));
+        assert!(!joined.contains("--ro-bind-try /work/app /work/app"));
+        assert!(joined.ends_with("--chdir /work/app -- /jdk/bin/java"));
+    }
+
+    #[test]
+    fn denied_accesses_are_picked_out() {
+        let stderr = "\
+e: [ksp] java.nio.file.NoSuchFileException: /home/user/.ssh/id_rsa
+e: [ksp] java.nio.file.NoSuchFileException: /work/app/src/main/kotlin/Gone.kt
+e: [ksp] java.net.UnknownHostException: telemetry.example.com
+w: [ksp] unrelated warning
+";
+        let output = Output {
+            status: std::process::ExitStatus::from_raw(1 << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        assert_eq!(
+            sandbox().violations(&output),
+            [
+                "e: [ksp] java.nio.file.NoSuchFileException: /home/user/.ssh/id_rsa",
+                "e: [ksp] java.net.UnknownHostException: telemetry.example.com",
+            ]
+        );
+    }
+}
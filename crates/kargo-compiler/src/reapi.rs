@@ -0,0 +1,921 @@
+//! Minimal Remote Execution API (REAPI v2) client.
+//!
+//! Covers what remote compilation needs: uploading inputs to the
+//! content-addressable storage (`FindMissingBlobs`, `BatchUpdateBlobs`,
+//! ByteStream `Write`), running an action (`Execute`, `WaitExecution`), and
+//! downloading its outputs (`BatchReadBlobs`, ByteStream `Read`). The
+//! messages are hand-written prost types with the subset of fields Kargo
+//! uses; fields a server sends beyond those are skipped when decoding.
+//! Digests are SHA-256.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use prost::Message;
+use sha2::{Digest as _, Sha256};
+use tonic::client::Grpc;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::Request;
+use tonic_prost::ProstCodec;
+
+use kargo_util::errors::KargoError;
+
+/// Blobs up to this size go through the batch calls; larger ones are
+/// streamed through ByteStream, staying under gRPC's 4 MiB message limit.
+const BATCH_LIMIT: usize = 2 * 1024 * 1024;
+/// Size of each ByteStream `Write` chunk.
+const CHUNK_SIZE: usize = 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// REAPI message types (`build.bazel.remote.execution.v2` and the Google
+/// APIs it uses).
+pub mod proto {
+    #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Message)]
+    pub struct Digest {
+        #[prost(string, tag = "1")]
+        pub hash: String,
+        #[prost(int64, tag = "2")]
+        pub size_bytes: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Action {
+        #[prost(message, optional, tag = "1")]
+        pub command_digest: Option<Digest>,
+        #[prost(message, optional, tag = "2")]
+        pub input_root_digest: Option<Digest>,
+        #[prost(message, optional, tag = "6")]
+        pub timeout: Option<Duration>,
+        #[prost(bool, tag = "7")]
+        pub do_not_cache: bool,
+        #[prost(message, optional, tag = "10")]
+        pub platform: Option<Platform>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Command {
+        #[prost(string, repeated, tag = "1")]
+        pub arguments: Vec<String>,
+        #[prost(message, repeated, tag = "2")]
+        pub environment_variables: Vec<EnvironmentVariable>,
+        #[prost(string, repeated, tag = "4")]
+        pub output_directories: Vec<String>,
+        #[prost(message, optional, tag = "5")]
+        pub platform: Option<Platform>,
+        #[prost(string, tag = "6")]
+        pub working_directory: String,
+        #[prost(string, repeated, tag = "7")]
+        pub output_paths: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EnvironmentVariable {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub value: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Platform {
+        #[prost(message, repeated, tag = "1")]
+        pub properties: Vec<PlatformProperty>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PlatformProperty {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub value: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Directory {
+        #[prost(message, repeated, tag = "1")]
+        pub files: Vec<FileNode>,
+        #[prost(message, repeated, tag = "2")]
+        pub directories: Vec<DirectoryNode>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FileNode {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(message, optional, tag = "2")]
+        pub digest: Option<Digest>,
+        #[prost(bool, tag = "4")]
+        pub is_executable: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DirectoryNode {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(message, optional, tag = "2")]
+        pub digest: Option<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Tree {
+        #[prost(message, optional, tag = "1")]
+        pub root: Option<Directory>,
+        #[prost(message, repeated, tag = "2")]
+        pub children: Vec<Directory>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ActionResult {
+        #[prost(message, repeated, tag = "2")]
+        pub output_files: Vec<OutputFile>,
+        #[prost(message, repeated, tag = "3")]
+        pub output_directories: Vec<OutputDirectory>,
+        #[prost(int32, tag = "4")]
+        pub exit_code: i32,
+        #[prost(bytes = "vec", tag = "5")]
+        pub stdout_raw: Vec<u8>,
+        #[prost(message, optional, tag = "6")]
+        pub stdout_digest: Option<Digest>,
+        #[prost(bytes = "vec", tag = "7")]
+        pub stderr_raw: Vec<u8>,
+        #[prost(message, optional, tag = "8")]
+        pub stderr_digest: Option<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OutputFile {
+        #[prost(string, tag = "1")]
+        pub path: String,
+        #[prost(message, optional, tag = "2")]
+        pub digest: Option<Digest>,
+        #[prost(bool, tag = "4")]
+        pub is_executable: bool,
+        #[prost(bytes = "vec", tag = "5")]
+        pub contents: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OutputDirectory {
+        #[prost(string, tag = "1")]
+        pub path: String,
+        #[prost(message, optional, tag = "3")]
+        pub tree_digest: Option<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ExecuteRequest {
+        #[prost(string, tag = "1")]
+        pub instance_name: String,
+        #[prost(bool, tag = "3")]
+        pub skip_cache_lookup: bool,
+        #[prost(message, optional, tag = "6")]
+        pub action_digest: Option<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ExecuteResponse {
+        #[prost(message, optional, tag = "1")]
+        pub result: Option<ActionResult>,
+        #[prost(bool, tag = "2")]
+        pub cached_result: bool,
+        #[prost(message, optional, tag = "3")]
+        pub status: Option<Status>,
+        #[prost(string, tag = "5")]
+        pub message: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct WaitExecutionRequest {
+        #[prost(string, tag = "1")]
+        pub name: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FindMissingBlobsRequest {
+        #[prost(string, tag = "1")]
+        pub instance_name: String,
+        #[prost(message, repeated, tag = "2")]
+        pub blob_digests: Vec<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FindMissingBlobsResponse {
+        #[prost(message, repeated, tag = "2")]
+        pub missing_blob_digests: Vec<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BatchUpdateBlobsRequest {
+        #[prost(string, tag = "1")]
+        pub instance_name: String,
+        #[prost(message, repeated, tag = "2")]
+        pub requests: Vec<UpdateBlob>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateBlob {
+        #[prost(message, optional, tag = "1")]
+        pub digest: Option<Digest>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub data: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BatchUpdateBlobsResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub responses: Vec<UpdatedBlob>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdatedBlob {
+        #[prost(message, optional, tag = "1")]
+        pub digest: Option<Digest>,
+        #[prost(message, optional, tag = "2")]
+        pub status: Option<Status>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BatchReadBlobsRequest {
+        #[prost(string, tag = "1")]
+        pub instance_name: String,
+        #[prost(message, repeated, tag = "2")]
+        pub digests: Vec<Digest>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BatchReadBlobsResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub responses: Vec<ReadBlob>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReadBlob {
+        #[prost(message, optional, tag = "1")]
+        pub digest: Option<Digest>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub data: Vec<u8>,
+        #[prost(message, optional, tag = "3")]
+        pub status: Option<Status>,
+    }
+
+    /// `google.bytestream.ReadRequest`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReadRequest {
+        #[prost(string, tag = "1")]
+        pub resource_name: String,
+        #[prost(int64, tag = "2")]
+        pub read_offset: i64,
+        #[prost(int64, tag = "3")]
+        pub read_limit: i64,
+    }
+
+    /// `google.bytestream.ReadResponse`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReadResponse {
+        #[prost(bytes = "vec", tag = "10")]
+        pub data: Vec<u8>,
+    }
+
+    /// `google.bytestream.WriteRequest`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct WriteRequest {
+        #[prost(string, tag = "1")]
+        pub resource_name: String,
+        #[prost(int64, tag = "2")]
+        pub write_offset: i64,
+        #[prost(bool, tag = "3")]
+        pub finish_write: bool,
+        #[prost(bytes = "vec", tag = "10")]
+        pub data: Vec<u8>,
+    }
+
+    /// `google.bytestream.WriteResponse`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct WriteResponse {
+        #[prost(int64, tag = "1")]
+        pub committed_size: i64,
+    }
+
+    /// `google.longrunning.Operation`; `error` and `response` are its
+    /// `result` oneof.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Operation {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(bool, tag = "3")]
+        pub done: bool,
+        #[prost(message, optional, tag = "4")]
+        pub error: Option<Status>,
+        #[prost(message, optional, tag = "5")]
+        pub response: Option<Any>,
+    }
+
+    /// `google.rpc.Status`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Status {
+        #[prost(int32, tag = "1")]
+        pub code: i32,
+        #[prost(string, tag = "2")]
+        pub message: String,
+    }
+
+    /// `google.protobuf.Any`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Any {
+        #[prost(string, tag = "1")]
+        pub type_url: String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub value: Vec<u8>,
+    }
+
+    /// `google.protobuf.Duration`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Duration {
+        #[prost(int64, tag = "1")]
+        pub seconds: i64,
+        #[prost(int32, tag = "2")]
+        pub nanos: i32,
+    }
+}
+
+use proto::Digest;
+
+/// The digest of `data`.
+pub fn digest(data: &[u8]) -> Digest {
+    Digest {
+        hash: hex(&Sha256::digest(data)),
+        size_bytes: data.len() as i64,
+    }
+}
+
+/// The digest of the file at `path`, read in chunks.
+pub fn digest_file(path: &Path) -> std::io::Result<Digest> {
+    use std::io::Read;
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size = 0i64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as i64;
+    }
+    Ok(Digest {
+        hash: hex(&hasher.finalize()),
+        size_bytes: size,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Where the content of a blob to upload comes from.
+#[derive(Debug, Clone)]
+pub enum Blob {
+    Bytes(Vec<u8>),
+    File(std::path::PathBuf),
+}
+
+impl Blob {
+    fn read(&self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Blob::Bytes(bytes) => Ok(bytes.clone()),
+            Blob::File(path) => std::fs::read(path),
+        }
+    }
+}
+
+/// An input root under construction: a directory tree of files whose
+/// content lives in [`Blob`]s, turned into REAPI `Directory` messages by
+/// [`InputTree::finish`].
+#[derive(Debug, Default)]
+pub struct InputTree {
+    root: TreeDir,
+    blobs: BTreeMap<Digest, Blob>,
+}
+
+#[derive(Debug, Default)]
+struct TreeDir {
+    files: BTreeMap<String, (Digest, bool)>,
+    dirs: BTreeMap<String, TreeDir>,
+}
+
+impl InputTree {
+    /// Place the local file `local` at `path` (`/`-separated, relative to
+    /// the input root).
+    pub fn add_file(&mut self, path: &str, local: &Path) -> std::io::Result<()> {
+        let digest = digest_file(local)?;
+        let executable = is_executable(local);
+        self.insert(path, digest.clone(), executable);
+        self.blobs
+            .entry(digest)
+            .or_insert_with(|| Blob::File(local.to_path_buf()));
+        Ok(())
+    }
+
+    /// Place `data` at `path`.
+    pub fn add_bytes(&mut self, path: &str, data: Vec<u8>) {
+        let digest = digest(&data);
+        self.insert(path, digest.clone(), false);
+        self.blobs.entry(digest).or_insert(Blob::Bytes(data));
+    }
+
+    /// Place every file below the local directory `local` under `path`.
+    pub fn add_dir(&mut self, path: &str, local: &Path) -> std::io::Result<()> {
+        self.dir_mut(path);
+        for entry in std::fs::read_dir(local)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let child = format!("{path}/{name}");
+            if entry.file_type()?.is_dir() {
+                self.add_dir(&child, &entry.path())?;
+            } else {
+                self.add_file(&child, &entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, path: &str, digest: Digest, executable: bool) {
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        self.dir_mut(dir)
+            .files
+            .insert(name.to_string(), (digest, executable));
+    }
+
+    fn dir_mut(&mut self, path: &str) -> &mut TreeDir {
+        let mut dir = &mut self.root;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            dir = dir.dirs.entry(part.to_string()).or_default();
+        }
+        dir
+    }
+
+    /// The digest of the root `Directory` and every blob the tree needs,
+    /// the encoded `Directory` messages included.
+    pub fn finish(mut self) -> (Digest, BTreeMap<Digest, Blob>) {
+        fn encode(dir: &TreeDir, blobs: &mut BTreeMap<Digest, Blob>) -> Digest {
+            // Names are sorted (BTreeMap order), as REAPI requires.
+            let message = proto::Directory {
+                files: dir
+                    .files
+                    .iter()
+                    .map(|(name, (digest, executable))| proto::FileNode {
+                        name: name.clone(),
+                        digest: Some(digest.clone()),
+                        is_executable: *executable,
+                    })
+                    .collect(),
+                directories: dir
+                    .dirs
+                    .iter()
+                    .map(|(name, child)| proto::DirectoryNode {
+                        name: name.clone(),
+                        digest: Some(encode(child, blobs)),
+                    })
+                    .collect(),
+            };
+            let bytes = message.encode_to_vec();
+            let digest = digest(&bytes);
+            blobs.insert(digest.clone(), Blob::Bytes(bytes));
+            digest
+        }
+        let root = encode(&self.root, &mut self.blobs);
+        (root, self.blobs)
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+/// A connection to a REAPI server.
+pub struct Client {
+    grpc: Grpc<Channel>,
+    instance: String,
+    headers: Vec<(AsciiMetadataKey, AsciiMetadataValue)>,
+}
+
+fn network(context: &str, e: impl std::fmt::Display) -> miette::Report {
+    KargoError::Network {
+        message: format!("remote execution: {context}: {e}"),
+    }
+    .into()
+}
+
+impl Client {
+    /// Connect to `endpoint` (`grpc://` or `grpcs://`), sending `headers`
+    /// with every request.
+    pub async fn connect(
+        endpoint: &str,
+        instance: &str,
+        headers: &BTreeMap<String, String>,
+    ) -> miette::Result<Self> {
+        let (url, tls) = match endpoint.split_once("://") {
+            Some(("grpc" | "http", rest)) => (format!("http://{rest}"), false),
+            Some(("grpcs" | "https", rest)) => (format!("https://{rest}"), true),
+            _ => (format!("http://{endpoint}"), false),
+        };
+        let mut channel = Endpoint::from_shared(url)
+            .map_err(|e| network(endpoint, e))?
+            .connect_timeout(CONNECT_TIMEOUT);
+        if tls {
+            channel = channel
+                .tls_config(ClientTlsConfig::new().with_native_roots())
+                .map_err(|e| network(endpoint, e))?;
+        }
+        let channel = channel
+            .connect()
+            .await
+            .map_err(|e| network(&format!("connecting to {endpoint}"), e))?;
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let key = AsciiMetadataKey::from_bytes(name.to_ascii_lowercase().as_bytes())
+                    .map_err(|e| network(&format!("header `{name}`"), e))?;
+                let value = AsciiMetadataValue::try_from(value.as_str())
+                    .map_err(|e| network(&format!("header `{name}`"), e))?;
+                Ok((key, value))
+            })
+            .collect::<miette::Result<_>>()?;
+        Ok(Self {
+            grpc: Grpc::new(channel),
+            instance: instance.to_string(),
+            headers,
+        })
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        for (key, value) in &self.headers {
+            request.metadata_mut().insert(key.clone(), value.clone());
+        }
+        request
+    }
+
+    async fn unary<Req, Resp>(&mut self, method: &'static str, message: Req) -> miette::Result<Resp>
+    where
+        Req: Message + Send + Sync + 'static,
+        Resp: Message + Default + Send + Sync + 'static,
+    {
+        self.grpc.ready().await.map_err(|e| network(method, e))?;
+        let request = self.request(message);
+        let response = self
+            .grpc
+            .unary(
+                request,
+                PathAndQuery::from_static(method),
+                ProstCodec::<Req, Resp>::default(),
+            )
+            .await
+            .map_err(|e| network(method, e))?;
+        Ok(response.into_inner())
+    }
+
+    /// Upload the blobs the server does not have yet.
+    pub async fn upload(&mut self, blobs: &BTreeMap<Digest, Blob>) -> miette::Result<()> {
+        let response: proto::FindMissingBlobsResponse = self
+            .unary(
+                "/build.bazel.remote.execution.v2.ContentAddressableStorage/FindMissingBlobs",
+                proto::FindMissingBlobsRequest {
+                    instance_name: self.instance.clone(),
+                    blob_digests: blobs.keys().cloned().collect(),
+                },
+            )
+            .await?;
+
+        let mut batch = Vec::new();
+        let mut batch_size = 0;
+        for digest in response.missing_blob_digests {
+            let Some(blob) = blobs.get(&digest) else {
+                continue;
+            };
+            let data = blob
+                .read()
+                .map_err(|e| network(&format!("reading input {}", digest.hash), e))?;
+            if data.len() > BATCH_LIMIT {
+                self.write(&digest, data).await?;
+                continue;
+            }
+            if batch_size + data.len() > BATCH_LIMIT {
+                self.update_batch(std::mem::take(&mut batch)).await?;
+                batch_size = 0;
+            }
+            batch_size += data.len();
+            batch.push(proto::UpdateBlob {
+                digest: Some(digest),
+                data,
+            });
+        }
+        if !batch.is_empty() {
+            self.update_batch(batch).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_batch(&mut self, requests: Vec<proto::UpdateBlob>) -> miette::Result<()> {
+        let response: proto::BatchUpdateBlobsResponse = self
+            .unary(
+                "/build.bazel.remote.execution.v2.ContentAddressableStorage/BatchUpdateBlobs",
+                proto::BatchUpdateBlobsRequest {
+                    instance_name: self.instance.clone(),
+                    requests,
+                },
+            )
+            .await?;
+        for blob in response.responses {
+            if let Some(status) = blob.status.filter(|s| s.code != 0) {
+                return Err(network("uploading inputs", status.message));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stream a large blob to the server with ByteStream `Write`.
+    async fn write(&mut self, digest: &Digest, data: Vec<u8>) -> miette::Result<()> {
+        let resource = format!(
+            "{}uploads/{}/blobs/{}/{}",
+            self.prefix(),
+            upload_id(),
+            digest.hash,
+            digest.size_bytes
+        );
+        let total = data.len();
+        let requests: Vec<proto::WriteRequest> = data
+            .chunks(CHUNK_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| proto::WriteRequest {
+                resource_name: if i == 0 {
+                    resource.clone()
+                } else {
+                    String::new()
+                },
+                write_offset: (i * CHUNK_SIZE) as i64,
+                finish_write: i * CHUNK_SIZE + chunk.len() == total,
+                data: chunk.to_vec(),
+            })
+            .collect();
+        self.grpc
+            .ready()
+            .await
+            .map_err(|e| network("ByteStream.Write", e))?;
+        let request = self.request(tonic::codegen::tokio_stream::iter(requests));
+        let response = self
+            .grpc
+            .client_streaming(
+                request,
+                PathAndQuery::from_static("/google.bytestream.ByteStream/Write"),
+                ProstCodec::<proto::WriteRequest, proto::WriteResponse>::default(),
+            )
+            .await
+            .map_err(|e| network("ByteStream.Write", e))?;
+        let committed = response.into_inner().committed_size;
+        if committed != digest.size_bytes {
+            return Err(network(
+                "ByteStream.Write",
+                format!(
+                    "server committed {committed} of {} bytes",
+                    digest.size_bytes
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Download the blob with `digest`.
+    pub async fn read(&mut self, digest: &Digest) -> miette::Result<Vec<u8>> {
+        if digest.size_bytes == 0 {
+            return Ok(Vec::new());
+        }
+        if digest.size_bytes as usize <= BATCH_LIMIT {
+            let response: proto::BatchReadBlobsResponse = self
+                .unary(
+                    "/build.bazel.remote.execution.v2.ContentAddressableStorage/BatchReadBlobs",
+                    proto::BatchReadBlobsRequest {
+                        instance_name: self.instance.clone(),
+                        digests: vec![digest.clone()],
+                    },
+                )
+                .await?;
+            let blob = response
+                .responses
+                .into_iter()
+                .next()
+                .ok_or_else(|| network("BatchReadBlobs", "empty response"))?;
+            if let Some(status) = blob.status.filter(|s| s.code != 0) {
+                return Err(network(&format!("reading {}", digest.hash), status.message));
+            }
+            return Ok(blob.data);
+        }
+
+        let resource = format!(
+            "{}blobs/{}/{}",
+            self.prefix(),
+            digest.hash,
+            digest.size_bytes
+        );
+        self.grpc
+            .ready()
+            .await
+            .map_err(|e| network("ByteStream.Read", e))?;
+        let request = self.request(proto::ReadRequest {
+            resource_name: resource,
+            read_offset: 0,
+            read_limit: 0,
+        });
+        let mut stream = self
+            .grpc
+            .server_streaming(
+                request,
+                PathAndQuery::from_static("/google.bytestream.ByteStream/Read"),
+                ProstCodec::<proto::ReadRequest, proto::ReadResponse>::default(),
+            )
+            .await
+            .map_err(|e| network("ByteStream.Read", e))?
+            .into_inner();
+        let mut data = Vec::with_capacity(digest.size_bytes as usize);
+        while let Some(chunk) = stream
+            .message()
+            .await
+            .map_err(|e| network("ByteStream.Read", e))?
+        {
+            data.extend_from_slice(&chunk.data);
+        }
+        Ok(data)
+    }
+
+    /// Run the action with `action_digest` and wait for its result. An
+    /// error means the action could not be run, not that it failed.
+    pub async fn execute(&mut self, action_digest: &Digest) -> miette::Result<proto::ActionResult> {
+        let mut method = "/build.bazel.remote.execution.v2.Execution/Execute";
+        let mut execute = Some(proto::ExecuteRequest {
+            instance_name: self.instance.clone(),
+            skip_cache_lookup: false,
+            action_digest: Some(action_digest.clone()),
+        });
+        let mut name = String::new();
+        loop {
+            self.grpc.ready().await.map_err(|e| network(method, e))?;
+            let response = match execute.take() {
+                Some(request) => {
+                    let request = self.request(request);
+                    self.grpc
+                        .server_streaming(
+                            request,
+                            PathAndQuery::from_static(method),
+                            ProstCodec::<proto::ExecuteRequest, proto::Operation>::default(),
+                        )
+                        .await
+                }
+                None => {
+                    let request = self.request(proto::WaitExecutionRequest { name: name.clone() });
+                    self.grpc
+                        .server_streaming(
+                            request,
+                            PathAndQuery::from_static(method),
+                            ProstCodec::<proto::WaitExecutionRequest, proto::Operation>::default(),
+                        )
+                        .await
+                }
+            };
+            let mut stream = response.map_err(|e| network(method, e))?.into_inner();
+            let mut progressed = false;
+            while let Some(operation) = stream.message().await.map_err(|e| network(method, e))? {
+                progressed = true;
+                if operation.done {
+                    return finished(operation);
+                }
+                name = operation.name;
+            }
+            // The stream ended before the operation finished: reattach.
+            if !progressed || name.is_empty() {
+                return Err(network(method, "stream ended before the action finished"));
+            }
+            method = "/build.bazel.remote.execution.v2.Execution/WaitExecution";
+        }
+    }
+
+    /// `"<instance>/"`, the resource name prefix, or nothing for the
+    /// default instance.
+    fn prefix(&self) -> String {
+        if self.instance.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.instance)
+        }
+    }
+}
+
+/// The action result of a finished operation.
+fn finished(operation: proto::Operation) -> miette::Result<proto::ActionResult> {
+    if let Some(error) = operation.error {
+        return Err(network(
+            "Execute",
+            format!("{} (code {})", error.message, error.code),
+        ));
+    }
+    let any = operation
+        .response
+        .ok_or_else(|| network("Execute", "operation finished without a response"))?;
+    let response = proto::ExecuteResponse::decode(any.value.as_slice())
+        .map_err(|e| network("decoding ExecuteResponse", e))?;
+    if let Some(status) = response.status.filter(|s| s.code != 0) {
+        return Err(network(
+            "Execute",
+            format!("{} (code {})", status.message, status.code),
+        ));
+    }
+    response
+        .result
+        .ok_or_else(|| network("Execute", "response has no action result"))
+}
+
+/// A fresh UUID (version 4 layout) for a ByteStream upload.
+fn upload_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let seed = format!(
+        "{}-{nanos}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let mut bytes: [u8; 16] = Sha256::digest(seed.as_bytes())[..16]
+        .try_into()
+        .expect("SHA-256 is longer than 16 bytes");
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_directory_has_the_well_known_digest() {
+        let (root, blobs) = InputTree::default().finish();
+        assert_eq!(
+            root.hash,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(root.size_bytes, 0);
+        assert_eq!(blobs.len(), 1);
+    }
+
+    #[test]
+    fn input_tree_is_independent_of_insertion_order() {
+        let mut a = InputTree::default();
+        a.add_bytes("src/b/Two.kt", b"two".to_vec());
+        a.add_bytes("src/One.kt", b"one".to_vec());
+        let mut b = InputTree::default();
+        b.add_bytes("src/One.kt", b"one".to_vec());
+        b.add_bytes("src/b/Two.kt", b"two".to_vec());
+        let (root_a, blobs_a) = a.finish();
+        let (root_b, _) = b.finish();
+        assert_eq!(root_a, root_b);
+        // Two files, and the root, `src` and `src/b` directories.
+        assert_eq!(blobs_a.len(), 5);
+
+        let Blob::Bytes(bytes) = &blobs_a[&root_a] else {
+            panic!("directories are encoded in memory");
+        };
+        let root = proto::Directory::decode(bytes.as_slice()).unwrap();
+        assert_eq!(root.directories[0].name, "src");
+    }
+
+    #[test]
+    fn upload_ids_look_like_uuids() {
+        let id = upload_id();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert_ne!(id, upload_id());
+    }
+}
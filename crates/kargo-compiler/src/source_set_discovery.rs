@@ -3,18 +3,27 @@
 //! Supports two layout conventions:
 //! - **JVM-only**: `src/main/kotlin/`, `src/test/kotlin/`
 //! - **KMP (multiplatform)**: `src/commonMain/kotlin/`, `src/jvmMain/kotlin/`, etc.
+//!
+//! Both layouts may add test fixtures under `src/fixtures/kotlin/` and
+//! integration tests under `src/integrationTest/kotlin/`.
 
 use std::path::{Path, PathBuf};
 
 use kargo_core::manifest::Manifest;
 use kargo_core::source_set::SourceSet;
 use kargo_core::target::KotlinTarget;
+use kargo_util::fs::normalize_path;
 
-/// Collected source sets for a project, split into main and test groups.
-#[derive(Debug)]
+/// Collected source sets for a project, split into main, test fixtures,
+/// test, and integration-test groups.
+#[derive(Debug, Clone)]
 pub struct DiscoveredSources {
     pub main_sources: Vec<SourceSet>,
+    /// `src/fixtures`, compiled against main and shared with the tests.
+    pub fixtures_sources: Vec<SourceSet>,
     pub test_sources: Vec<SourceSet>,
+    /// `src/integrationTest`, compiled against main and test.
+    pub integration_test_sources: Vec<SourceSet>,
 }
 
 /// Discover source sets based on the project manifest and directory structure.
@@ -27,20 +36,46 @@ pub fn discover(project_root: &Path, manifest: &Manifest) -> DiscoveredSources {
     let src = project_root.join("src");
     let is_multiplatform = manifest.targets.len() > 1 || src.join("commonMain").is_dir();
 
-    if is_multiplatform {
+    let mut discovered = if is_multiplatform {
         discover_kmp(&src, manifest)
     } else {
         discover_single_target(&src)
+    };
+    let mut fixtures = SourceSet::new(FIXTURES_SOURCE_SET, src.clone());
+    for ss in &discovered.main_sources {
+        fixtures.depends_on.insert(ss.name.clone());
+    }
+    for ss in &mut discovered.test_sources {
+        ss.depends_on.insert(FIXTURES_SOURCE_SET.to_string());
     }
+    let mut integration = SourceSet::new(INTEGRATION_TEST_SOURCE_SET, src);
+    for ss in discovered
+        .main_sources
+        .iter()
+        .chain(&discovered.test_sources)
+    {
+        integration.depends_on.insert(ss.name.clone());
+    }
+    discovered.fixtures_sources = vec![fixtures];
+    discovered.integration_test_sources = vec![integration];
+    discovered
 }
 
+/// Name (and directory under `src/`) of the test fixtures source set.
+pub const FIXTURES_SOURCE_SET: &str = "fixtures";
+
+/// Name (and directory under `src/`) of the integration-test source set.
+pub const INTEGRATION_TEST_SOURCE_SET: &str = "integrationTest";
+
 fn discover_single_target(src: &Path) -> DiscoveredSources {
     let main = SourceSet::new("main", src.to_path_buf());
     let test = SourceSet::new("test", src.to_path_buf()).with_depends_on("main");
 
     DiscoveredSources {
         main_sources: vec![main],
+        fixtures_sources: vec![],
         test_sources: vec![test],
+        integration_test_sources: vec![],
     }
 }
 
@@ -137,11 +172,16 @@ fn discover_kmp(src: &Path, manifest: &Manifest) -> DiscoveredSources {
 
     DiscoveredSources {
         main_sources,
+        fixtures_sources: vec![],
         test_sources,
+        integration_test_sources: vec![],
     }
 }
 
 /// Recursively collect all `.kt` files from the given directories.
+///
+/// A file reached through two spellings of the same directory (`Src/Main`
+/// and `src/main` on a case-insensitive filesystem) is listed once.
 pub fn collect_kotlin_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
     let mut files = Vec::new();
     for dir in dirs {
@@ -149,7 +189,9 @@ pub fn collect_kotlin_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
             collect_files_recursive(dir, &mut files);
         }
     }
-    files.sort();
+    files.sort_by_cached_key(|f| normalize_path(f));
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| seen.insert(normalize_path(f)));
     files
 }
 
@@ -192,6 +234,8 @@ mod tests {
                     min_sdk: None,
                     target_sdk: None,
                     compile_sdk: None,
+                    manifest_placeholders: BTreeMap::new(),
+                    core_library_desugaring: None,
                 },
             );
         }
@@ -207,11 +251,13 @@ mod tests {
                 repository: None,
                 main_class: None,
                 ksp_version: None,
+                jar: None,
             },
             targets: target_map,
             compose: None,
             dependencies: BTreeMap::new(),
             dev_dependencies: BTreeMap::new(),
+            integration_test_dependencies: BTreeMap::new(),
             target: BTreeMap::new(),
             flavor: BTreeMap::new(),
             plugins: BTreeMap::new(),
@@ -227,6 +273,17 @@ mod tests {
             test: None,
             signing: None,
             docker: None,
+            npm_dependencies: BTreeMap::new(),
+            npm: None,
+            network: None,
+            generate: BTreeMap::new(),
+            protobuf: None,
+            sqldelight: None,
+            update_policy: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            budgets: None,
+            apple: None,
+            sandbox: None,
             ksp: BTreeMap::new(),
             ksp_options: BTreeMap::new(),
             kapt: BTreeMap::new(),
@@ -249,6 +306,15 @@ mod tests {
         assert_eq!(result.main_sources[0].name, "main");
         assert_eq!(result.test_sources.len(), 1);
         assert_eq!(result.test_sources[0].name, "test");
+        assert_eq!(result.integration_test_sources.len(), 1);
+        let it = &result.integration_test_sources[0];
+        assert_eq!(it.kotlin_dirs, vec![src.join("integrationTest/kotlin")]);
+        assert!(it.depends_on.contains("main") && it.depends_on.contains("test"));
+        assert_eq!(
+            result.fixtures_sources[0].kotlin_dirs,
+            vec![src.join("fixtures/kotlin")]
+        );
+        assert!(result.test_sources[0].depends_on.contains("fixtures"));
     }
 
     #[test]
@@ -285,4 +351,27 @@ mod tests {
         assert_eq!(files.len(), 2);
         assert!(files.iter().all(|f| f.extension().unwrap() == "kt"));
     }
+
+    #[test]
+    fn files_reached_twice_are_listed_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("src/main/kotlin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Main.kt"), "fun main() {}").unwrap();
+
+        let files = collect_kotlin_files(&[dir.clone(), tmp.path().join("src/main/./kotlin")]);
+        assert_eq!(files, vec![dir.join("Main.kt")]);
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn differently_cased_dirs_are_the_same_on_case_insensitive_hosts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("src/main/kotlin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Main.kt"), "fun main() {}").unwrap();
+
+        let files = collect_kotlin_files(&[dir, tmp.path().join("Src/Main/kotlin")]);
+        assert_eq!(files.len(), 1);
+    }
 }
@@ -28,6 +28,9 @@ pub struct CompilationUnit {
     /// Annotation processor JAR paths (KSP/KAPT) — included in fingerprint
     /// so that changing a processor version triggers recompilation.
     pub processor_jars: Vec<PathBuf>,
+    /// Project JARs from `{ file = "..." }` dependencies — hashed by
+    /// content, since editing one keeps its file name.
+    pub local_jars: Vec<PathBuf>,
 }
 
 impl CompilationUnit {
@@ -66,6 +69,7 @@ pub struct Diagnostic {
     pub message: String,
     pub file: Option<String>,
     pub line: Option<u32>,
+    pub column: Option<u32>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -74,3 +78,14 @@ pub enum DiagnosticSeverity {
     Warning,
     Info,
 }
+
+impl DiagnosticSeverity {
+    /// Lowercase label used in human and JSON output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Splitting one large compilation unit into per-package units.
+//!
+//! A module with thousands of files compiles on a single `kotlinc`
+//! invocation. When its packages do not depend on each other in a cycle,
+//! the sources can instead be partitioned by package into smaller units:
+//! packages are layered so that every unit only uses units from earlier
+//! waves, the units of a wave compile in parallel, and their outputs are
+//! merged into one classes directory.
+//!
+//! Package dependencies are found textually: every dotted name in a file
+//! (imports and fully qualified references) is matched against the packages
+//! declared in the module. Extra edges only cost parallelism, so the scan
+//! errs on the side of finding too many.
+//!
+//! Each unit gets its own `-module-name` so the `META-INF/*.kotlin_module`
+//! files of the parts coexist after merging, and `-Xfriend-paths` on the
+//! earlier units so `internal` declarations stay visible across parts.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use petgraph::graph::DiGraph;
+
+use kargo_util::errors::KargoError;
+
+use crate::dispatch::TargetCompiler;
+use crate::env::BuildEnv;
+use crate::unit::{CompilationOutput, CompilationUnit};
+
+/// A group of packages compiled by one `kotlinc` invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SplitUnit {
+    pub packages: Vec<String>,
+    pub sources: Vec<PathBuf>,
+}
+
+/// How the main sources should be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitPlan {
+    /// Units grouped into waves; a unit only depends on units of earlier
+    /// waves, so the units of one wave can compile in parallel.
+    Split(Vec<Vec<SplitUnit>>),
+    /// Compile everything in one unit, for the given reason.
+    Monolithic(String),
+}
+
+/// Plan a split of `sources` into at most `max_units` units per wave.
+pub fn plan(sources: &[PathBuf], max_units: usize) -> miette::Result<SplitPlan> {
+    let mut files = Vec::new();
+    for path in sources {
+        let text = std::fs::read_to_string(path).map_err(KargoError::Io)?;
+        files.push((path.clone(), text));
+    }
+    Ok(plan_sources(&files, max_units))
+}
+
+/// [`plan`] over already-read `(path, text)` pairs.
+pub fn plan_sources(files: &[(PathBuf, String)], max_units: usize) -> SplitPlan {
+    if let Some((path, _)) = files
+        .iter()
+        .find(|(p, _)| !p.extension().is_some_and(|e| e == "kt"))
+    {
+        return SplitPlan::Monolithic(format!("{} is not a Kotlin source", path.display()));
+    }
+
+    let mut by_package: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for (path, text) in files {
+        let package = package_of(text).unwrap_or_default();
+        by_package.entry(package).or_default().push(path.clone());
+    }
+    if by_package.len() < 2 {
+        return SplitPlan::Monolithic("the sources are in a single package".into());
+    }
+    if by_package.contains_key("") {
+        return SplitPlan::Monolithic("some sources are in the default package".into());
+    }
+
+    let packages: BTreeSet<String> = by_package.keys().cloned().collect();
+    let mut deps: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (_, text) in files {
+        let own = package_of(text).unwrap_or_default();
+        // The package line itself names parent packages; skip it.
+        let body: String = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("package "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let used: Vec<String> = references(&body, &packages)
+            .into_iter()
+            .filter(|p| *p != own)
+            .collect();
+        deps.entry(own).or_default().extend(used);
+    }
+
+    let mut graph = DiGraph::<&str, ()>::new();
+    let nodes: BTreeMap<&str, _> = packages
+        .iter()
+        .map(|p| (p.as_str(), graph.add_node(p.as_str())))
+        .collect();
+    for (package, used) in &deps {
+        for dep in used {
+            graph.add_edge(nodes[dep.as_str()], nodes[package.as_str()], ());
+        }
+    }
+    let order = match petgraph::algo::toposort(&graph, None) {
+        Ok(order) => order,
+        Err(cycle) => {
+            return SplitPlan::Monolithic(format!(
+                "package {} is part of a dependency cycle",
+                graph[cycle.node_id()]
+            ))
+        }
+    };
+
+    let mut level: BTreeMap<&str, usize> = BTreeMap::new();
+    for idx in order {
+        let package = graph[idx];
+        let depth = deps
+            .get(package)
+            .into_iter()
+            .flatten()
+            .map(|d| level[d.as_str()] + 1)
+            .max()
+            .unwrap_or(0);
+        level.insert(package, depth);
+    }
+
+    let wave_count = level.values().max().map_or(0, |m| m + 1);
+    let mut waves = Vec::with_capacity(wave_count);
+    for wave in 0..wave_count {
+        let mut members: Vec<&str> = level
+            .iter()
+            .filter(|(_, l)| **l == wave)
+            .map(|(p, _)| *p)
+            .collect();
+        members.sort_by_key(|p| std::cmp::Reverse(by_package[*p].len()));
+
+        let mut units = vec![SplitUnit::default(); members.len().min(max_units.max(1))];
+        for package in members {
+            let smallest = units
+                .iter_mut()
+                .min_by_key(|u| u.sources.len())
+                .expect("at least one unit per wave");
+            smallest.packages.push(package.to_string());
+            smallest.sources.extend(by_package[package].iter().cloned());
+        }
+        for unit in &mut units {
+            unit.packages.sort();
+            unit.sources.sort();
+        }
+        waves.push(units);
+    }
+
+    if waves.iter().map(Vec::len).sum::<usize>() < 2 {
+        return SplitPlan::Monolithic("the packages form a single unit".into());
+    }
+    SplitPlan::Split(waves)
+}
+
+/// The `package` a Kotlin file declares, if any.
+pub fn package_of(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find_map(|line| line.strip_prefix("package "))
+        .map(|rest| {
+            rest.split(|c: char| c.is_whitespace() || c == ';')
+                .next()
+                .unwrap_or_default()
+                .replace('`', "")
+        })
+        .filter(|p| !p.is_empty())
+}
+
+/// Packages from `packages` that `text` refers to by a dotted name.
+fn references(text: &str, packages: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut used = BTreeSet::new();
+    let chars: Vec<char> = text.chars().collect();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut i = 0;
+    while i < chars.len() {
+        let boundary = i == 0 || !(is_ident(chars[i - 1]) || chars[i - 1] == '.');
+        if !(boundary && (chars[i].is_alphabetic() || chars[i] == '_' || chars[i] == '`')) {
+            i += 1;
+            continue;
+        }
+
+        // Read `a.b.C` / `a.b.*`, dropping backticks.
+        let mut segments = vec![String::new()];
+        while i < chars.len() {
+            let c = chars[i];
+            if is_ident(c) {
+                segments.last_mut().unwrap().push(c);
+            } else if c == '`' {
+                // quoted segment; keep its content as-is
+            } else if c == '.'
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|n| is_ident(*n) || *n == '*' || *n == '`')
+            {
+                segments.push(String::new());
+            } else if c == '*' && segments.last().is_some_and(String::is_empty) {
+                segments.last_mut().unwrap().push('*');
+                i += 1;
+                break;
+            } else {
+                break;
+            }
+            i += 1;
+        }
+
+        for len in (1..segments.len()).rev() {
+            let candidate = segments[..len].join(".");
+            if packages.contains(&candidate) {
+                used.insert(candidate);
+                break;
+            }
+        }
+    }
+    used
+}
+
+/// Compile `waves` as separate units under `split_dir` and merge their
+/// classes into `base.output_dir` (and `split_dir/classes`, which
+/// [`verify`] compares). At most `jobs` units compile at once.
+///
+/// Each unit inherits the classpath, compiler arguments and processor jars
+/// of `base`. A failed unit stops the build after its wave finishes, with
+/// the diagnostics of every unit in the wave.
+pub fn compile(
+    waves: &[Vec<SplitUnit>],
+    base: &CompilationUnit,
+    split_dir: &Path,
+    env: &BuildEnv,
+    jobs: usize,
+    compiler: &(dyn Fn() -> Box<dyn TargetCompiler> + Sync),
+) -> miette::Result<CompilationOutput> {
+    let mut diagnostics = Vec::new();
+    let mut done: Vec<PathBuf> = Vec::new();
+    let mut index = 0;
+
+    for wave in waves {
+        let mut units = Vec::with_capacity(wave.len());
+        for part in wave {
+            let name = format!("{}-{index}", base.name);
+            let output_dir = split_dir.join(format!("unit-{index}"));
+            if output_dir.exists() {
+                std::fs::remove_dir_all(&output_dir).map_err(KargoError::Io)?;
+            }
+            let mut compiler_args = base.compiler_args.clone();
+            compiler_args.extend(["-module-name".to_string(), name.clone()]);
+            crate::build_report::retarget(&mut compiler_args, &name);
+            if !done.is_empty() {
+                let friends: Vec<String> = done
+                    .iter()
+                    .map(|d| d.to_string_lossy().into_owned())
+                    .collect();
+                compiler_args.push(format!("-Xfriend-paths={}", friends.join(",")));
+            }
+            let mut classpath = base.classpath.clone();
+            classpath.extend(done.iter().cloned());
+            units.push(CompilationUnit {
+                name,
+                target: base.target,
+                sources: part.sources.clone(),
+                resource_dirs: Vec::new(),
+                classpath,
+                output_dir,
+                compiler_args,
+                is_test: base.is_test,
+                generated_sources: Vec::new(),
+                processor_jars: base.processor_jars.clone(),
+                local_jars: base.local_jars.clone(),
+            });
+            index += 1;
+        }
+
+        let mut failed = false;
+        for chunk in units.chunks(jobs.max(1)) {
+            let results: Vec<miette::Result<CompilationOutput>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = chunk
+                    .iter()
+                    .map(|unit| scope.spawn(move || compiler().compile(unit, env)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|h| {
+                        h.join().unwrap_or_else(|_| {
+                            Err(KargoError::Generic {
+                                message: "A compiler thread panicked".into(),
+                            }
+                            .into())
+                        })
+                    })
+                    .collect()
+            });
+            for result in results {
+                let output = result?;
+                failed |= !output.success;
+                diagnostics.extend(output.diagnostics);
+            }
+        }
+        if failed {
+            return Ok(CompilationOutput {
+                classes_dir: base.output_dir.clone(),
+                success: false,
+                diagnostics,
+            });
+        }
+        done.extend(units.into_iter().map(|u| u.output_dir));
+    }
+
+    let merged = split_dir.join("classes");
+    if merged.exists() {
+        std::fs::remove_dir_all(&merged).map_err(KargoError::Io)?;
+    }
+    for dir in &done {
+        copy_tree(dir, &merged)?;
+        copy_tree(dir, &base.output_dir)?;
+    }
+
+    Ok(CompilationOutput {
+        classes_dir: base.output_dir.clone(),
+        success: true,
+        diagnostics,
+    })
+}
+
+/// Check that a split build produced the same classes and public ABI as a
+/// monolithic one. `split_dir` is the directory given to [`compile`].
+pub fn verify(split_dir: &Path, monolithic_classes: &Path) -> miette::Result<()> {
+    let split_classes = split_dir.join("classes");
+    let split = class_files(&split_classes);
+    let monolithic = class_files(monolithic_classes);
+    if split != monolithic {
+        let only = |a: &BTreeSet<PathBuf>, b: &BTreeSet<PathBuf>| -> String {
+            a.difference(b)
+                .take(5)
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        return Err(KargoError::Generic {
+            message: format!(
+                "Split compilation produced different classes than a monolithic build \
+                 (only split: [{}]; only monolithic: [{}])",
+                only(&split, &monolithic),
+                only(&monolithic, &split)
+            ),
+        }
+        .into());
+    }
+    if crate::abi::dump_dir(&split_classes)? != crate::abi::dump_dir(monolithic_classes)? {
+        return Err(KargoError::Generic {
+            message: "Split compilation produced a different public ABI than a monolithic build"
+                .into(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// `.class` files under `root`, relative to it, outside `META-INF`.
+fn class_files(root: &Path) -> BTreeSet<PathBuf> {
+    fn walk(root: &Path, dir: &Path, out: &mut BTreeSet<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if path != root.join("META-INF") {
+                    walk(root, &path, out);
+                }
+            } else if path.extension().is_some_and(|e| e == "class") {
+                if let Ok(rel) = path.strip_prefix(root) {
+                    out.insert(rel.to_path_buf());
+                }
+            }
+        }
+    }
+    let mut out = BTreeSet::new();
+    walk(root, root, &mut out);
+    out
+}
+
+fn copy_tree(src: &Path, dst: &Path) -> miette::Result<()> {
+    std::fs::create_dir_all(dst).map_err(KargoError::Io)?;
+    let Ok(entries) = std::fs::read_dir(src) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let dest = dst.join(entry.file_name());
+        if path.is_dir() {
+            copy_tree(&path, &dest)?;
+        } else {
+            std::fs::copy(&path, &dest).map_err(KargoError::Io)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, text: &str) -> (PathBuf, String) {
+        (PathBuf::from(name), text.to_string())
+    }
+
+    #[test]
+    fn layers_packages_by_dependency() {
+        let files = vec![
+            file(
+                "model/User.kt",
+                "package app.model\n\ndata class User(val id: Int)\n",
+            ),
+            file(
+                "util/Text.kt",
+                "package app.util\n\nfun String.slug() = lowercase()\n",
+            ),
+            file(
+                "api/Api.kt",
+                "package app.api\n\nimport app.model.User\nimport app.util.*\n\n\
+                 fun name(u: User) = u.id.toString().slug()\n",
+            ),
+            file(
+                "ui/Screen.kt",
+                "package app.ui\n\nfun show() = app.api.name(app.model.User(1))\n",
+            ),
+        ];
+        let SplitPlan::Split(waves) = plan_sources(&files, 4) else {
+            panic!("expected a split");
+        };
+        let packages: Vec<Vec<String>> = waves
+            .iter()
+            .map(|w| w.iter().flat_map(|u| u.packages.clone()).collect())
+            .collect();
+        assert_eq!(
+            packages,
+            vec![
+                vec!["app.model".to_string(), "app.util".to_string()],
+                vec!["app.api".to_string()],
+                vec!["app.ui".to_string()],
+            ]
+        );
+
+        let SplitPlan::Split(capped) = plan_sources(&files, 1) else {
+            panic!("expected a split");
+        };
+        assert_eq!(capped[0].len(), 1);
+        assert_eq!(capped[0][0].sources.len(), 2);
+    }
+
+    #[test]
+    fn falls_back_on_cycles_and_nested_package_prefixes() {
+        let cyclic = vec![
+            file(
+                "a/A.kt",
+                "package app.a\n\nimport app.b.B\nclass A(val b: B)\n",
+            ),
+            file(
+                "b/B.kt",
+                "package app.b\n\nimport app.a.A\nclass B(val a: A?)\n",
+            ),
+        ];
+        assert!(
+            matches!(plan_sources(&cyclic, 4), SplitPlan::Monolithic(reason) if reason.contains("cycle"))
+        );
+
+        // `app.core.io.Stream` belongs to `app.core.io`, not `app.core`.
+        let nested = vec![
+            file(
+                "core/C.kt",
+                "package app.core\n\nimport app.core.io.Stream\n",
+            ),
+            file("core/io/S.kt", "package app.core.io\n\nclass Stream\n"),
+        ];
+        let SplitPlan::Split(waves) = plan_sources(&nested, 4) else {
+            panic!("expected a split");
+        };
+        assert_eq!(waves[0][0].packages, vec!["app.core.io".to_string()]);
+
+        let java = vec![file("A.kt", "package a\n"), file("B.java", "package b;\n")];
+        assert!(matches!(plan_sources(&java, 4), SplitPlan::Monolithic(_)));
+    }
+}
@@ -0,0 +1,353 @@
+//! Typed `[build-config]` values.
+//!
+//! Entries in `[build-config]`, `[target.<t>.build-config]` and a flavor's
+//! `build-config` become fields of the generated `BuildConfig` object. A
+//! value's TOML type picks its Kotlin type:
+//!
+//! ```toml
+//! [build-config]
+//! API_URL = "https://api.example.com"        # String
+//! RETRIES = 3                                # Int (Long if it does not fit)
+//! LOGGING = true                             # Boolean
+//! LOCALES = ["en", "de"]                     # List<String>
+//! MAX_UPLOAD = { type = "long", value = 10 } # explicit type
+//! BUILT_AT = "${build:timestamp}"            # Long, epoch milliseconds
+//! API_KEY = { from-env = true }              # String?, read at run time
+//!
+//! [build-config.FEATURES]                    # nested `object FEATURES`
+//! SEARCH = true
+//! ```
+//!
+//! `from-env` entries are not compiled into the artifact: the generated
+//! property reads the environment variable when it is accessed, so secrets
+//! stay out of the JAR. [`likely_secrets`] flags values that look like
+//! credentials but are compiled in anyway.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Placeholder replaced by the build time in epoch milliseconds.
+pub const TIMESTAMP_PLACEHOLDER: &str = "${build:timestamp}";
+
+/// Explicit types accepted by `{ type = "...", value = ... }`.
+pub const BUILD_CONFIG_TYPES: &[&str] = &["string", "boolean", "int", "long", "string-list"];
+
+/// A `[build-config]` value as written in `Kargo.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BuildConfigValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    List(Vec<String>),
+    Typed(TypedValue),
+    FromEnv(FromEnv),
+    Object(BTreeMap<String, BuildConfigValue>),
+}
+
+/// `{ type = "long", value = 42 }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypedValue {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: toml::Value,
+}
+
+/// `{ from-env = true }` reads the variable named like the key;
+/// `{ from-env = "VAR" }` names it. `default` is used when it is unset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FromEnv {
+    #[serde(rename = "from-env")]
+    pub from_env: EnvRef,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// The `from-env` setting: `true`, or the variable name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvRef {
+    Enabled(bool),
+    Var(String),
+}
+
+/// A value resolved to the Kotlin type it is generated as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildConfigField {
+    String(String),
+    Boolean(bool),
+    Int(i32),
+    Long(i64),
+    StringList(Vec<String>),
+    /// Read from environment variable `var` at run time.
+    FromEnv {
+        var: String,
+        default: Option<String>,
+    },
+    Object(BTreeMap<String, BuildConfigField>),
+}
+
+impl From<&str> for BuildConfigValue {
+    fn from(s: &str) -> Self {
+        BuildConfigValue::String(s.to_string())
+    }
+}
+
+impl From<String> for BuildConfigValue {
+    fn from(s: String) -> Self {
+        BuildConfigValue::String(s)
+    }
+}
+
+impl From<&str> for BuildConfigField {
+    fn from(s: &str) -> Self {
+        BuildConfigField::String(s.to_string())
+    }
+}
+
+impl From<String> for BuildConfigField {
+    fn from(s: String) -> Self {
+        BuildConfigField::String(s)
+    }
+}
+
+impl BuildConfigValue {
+    /// Resolve to a Kotlin-typed field. `timestamp` replaces
+    /// [`TIMESTAMP_PLACEHOLDER`]. Errors name the offending key.
+    pub fn resolve(&self, key: &str, timestamp: i64) -> Result<BuildConfigField, String> {
+        Ok(match self {
+            BuildConfigValue::Bool(b) => BuildConfigField::Boolean(*b),
+            BuildConfigValue::Int(n) => match i32::try_from(*n) {
+                Ok(n) => BuildConfigField::Int(n),
+                Err(_) => BuildConfigField::Long(*n),
+            },
+            BuildConfigValue::String(s) if s == TIMESTAMP_PLACEHOLDER => {
+                BuildConfigField::Long(timestamp)
+            }
+            BuildConfigValue::String(s) => BuildConfigField::String(s.clone()),
+            BuildConfigValue::List(items) => BuildConfigField::StringList(items.clone()),
+            BuildConfigValue::Typed(typed) => typed.resolve(key)?,
+            BuildConfigValue::FromEnv(from_env) => from_env.resolve(key)?,
+            BuildConfigValue::Object(fields) => {
+                BuildConfigField::Object(resolve_all(fields, timestamp, Some(key))?)
+            }
+        })
+    }
+}
+
+impl TypedValue {
+    fn resolve(&self, key: &str) -> Result<BuildConfigField, String> {
+        let mismatch = || {
+            format!(
+                "build-config {key}: value does not match type '{}'",
+                self.kind
+            )
+        };
+        match (self.kind.as_str(), &self.value) {
+            ("string", toml::Value::String(s)) => Ok(BuildConfigField::String(s.clone())),
+            ("boolean", toml::Value::Boolean(b)) => Ok(BuildConfigField::Boolean(*b)),
+            ("int", toml::Value::Integer(n)) => i32::try_from(*n)
+                .map(BuildConfigField::Int)
+                .map_err(|_| format!("build-config {key}: {n} does not fit in an Int")),
+            ("long", toml::Value::Integer(n)) => Ok(BuildConfigField::Long(*n)),
+            ("string-list", toml::Value::Array(items)) => items
+                .iter()
+                .map(|v| v.as_str().map(str::to_string).ok_or_else(mismatch))
+                .collect::<Result<_, _>>()
+                .map(BuildConfigField::StringList),
+            (kind, _) if BUILD_CONFIG_TYPES.contains(&kind) => Err(mismatch()),
+            (kind, _) => Err(format!(
+                "build-config {key}: unknown type '{kind}'; expected one of: {}",
+                BUILD_CONFIG_TYPES.join(", ")
+            )),
+        }
+    }
+}
+
+impl FromEnv {
+    fn resolve(&self, key: &str) -> Result<BuildConfigField, String> {
+        let var = match &self.from_env {
+            EnvRef::Enabled(true) => key.replace('.', "_"),
+            EnvRef::Var(var) if is_identifier(var) => var.clone(),
+            EnvRef::Var(var) => {
+                return Err(format!(
+                    "build-config {key}: '{var}' is not a valid environment variable name"
+                ))
+            }
+            EnvRef::Enabled(false) => {
+                return Err(format!(
+                    "build-config {key}: from-env must be true or a variable name"
+                ))
+            }
+        };
+        Ok(BuildConfigField::FromEnv {
+            var,
+            default: self.default.clone(),
+        })
+    }
+}
+
+/// Resolve every entry of `fields`, checking that each key is a Kotlin
+/// identifier. `parent` prefixes keys in error messages.
+pub fn resolve_all(
+    fields: &BTreeMap<String, BuildConfigValue>,
+    timestamp: i64,
+    parent: Option<&str>,
+) -> Result<BTreeMap<String, BuildConfigField>, String> {
+    fields
+        .iter()
+        .map(|(key, value)| {
+            let path = match parent {
+                Some(p) => format!("{p}.{key}"),
+                None => key.clone(),
+            };
+            if !is_identifier(key) {
+                return Err(format!(
+                    "build-config key '{path}' is not a valid Kotlin identifier"
+                ));
+            }
+            Ok((key.clone(), value.resolve(&path, timestamp)?))
+        })
+        .collect()
+}
+
+/// Merge `overlay` into `base`. Nested objects merge key by key; any other
+/// value in `overlay` replaces the one in `base`.
+pub fn merge(
+    base: &mut BTreeMap<String, BuildConfigValue>,
+    overlay: &BTreeMap<String, BuildConfigValue>,
+) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(BuildConfigValue::Object(existing)), BuildConfigValue::Object(nested)) => {
+                merge(existing, nested);
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Whether any value (at any depth) is [`TIMESTAMP_PLACEHOLDER`].
+pub fn uses_timestamp(fields: &BTreeMap<String, BuildConfigValue>) -> bool {
+    fields.values().any(|v| match v {
+        BuildConfigValue::String(s) => s == TIMESTAMP_PLACEHOLDER,
+        BuildConfigValue::Object(nested) => uses_timestamp(nested),
+        _ => false,
+    })
+}
+
+/// Whether any field (at any depth) is read from the environment.
+pub fn uses_env(fields: &BTreeMap<String, BuildConfigField>) -> bool {
+    fields.values().any(|f| match f {
+        BuildConfigField::FromEnv { .. } => true,
+        BuildConfigField::Object(nested) => uses_env(nested),
+        _ => false,
+    })
+}
+
+/// Keys (dotted for nested objects) of compiled-in values that look like
+/// credentials: well-known token formats, long random-looking strings, and
+/// non-trivial values under names such as `API_KEY` or `SECRET`.
+pub fn likely_secrets(fields: &BTreeMap<String, BuildConfigField>) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_secrets(fields, None, &mut found);
+    found
+}
+
+fn collect_secrets(
+    fields: &BTreeMap<String, BuildConfigField>,
+    parent: Option<&str>,
+    found: &mut Vec<String>,
+) {
+    for (key, field) in fields {
+        let path = match parent {
+            Some(p) => format!("{p}.{key}"),
+            None => key.clone(),
+        };
+        let flagged = match field {
+            BuildConfigField::String(s) => looks_like_secret(key, s),
+            BuildConfigField::StringList(items) => items.iter().any(|s| looks_like_secret(key, s)),
+            BuildConfigField::Object(nested) => {
+                collect_secrets(nested, Some(&path), found);
+                false
+            }
+            _ => false,
+        };
+        if flagged {
+            found.push(path);
+        }
+    }
+}
+
+const SECRET_NAMES: &[&str] = &[
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "PASSWD",
+    "API_KEY",
+    "APIKEY",
+    "PRIVATE_KEY",
+    "CREDENTIAL",
+];
+
+const SECRET_PREFIXES: &[&str] = &[
+    "AIza",
+    "AKIA",
+    "ASIA",
+    "sk_live_",
+    "rk_live_",
+    "ghp_",
+    "gho_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "-----BEGIN",
+];
+
+fn looks_like_secret(key: &str, value: &str) -> bool {
+    let value = value.trim();
+    if value.is_empty() || value.contains(char::is_whitespace) || value.contains("://") {
+        return false;
+    }
+    if SECRET_PREFIXES
+        .iter()
+        .any(|p| value.starts_with(p) && value.len() >= p.len() + 16)
+    {
+        return true;
+    }
+    let upper = key.to_ascii_uppercase();
+    if SECRET_NAMES.iter().any(|n| upper.contains(n)) && value.len() >= 8 {
+        return true;
+    }
+    value.len() >= 24
+        && value.chars().any(|c| c.is_ascii_digit())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+        && entropy(value) >= 3.5
+}
+
+/// Shannon entropy in bits per character.
+fn entropy(s: &str) -> f64 {
+    let mut counts = BTreeMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = s.chars().count() as f64;
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
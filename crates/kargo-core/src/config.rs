@@ -25,30 +25,91 @@ pub struct GlobalConfig {
 
     #[serde(default)]
     pub format: Option<GlobalFormatConfig>,
+
+    /// TLS and IP settings for every project; a project's own `[network]`
+    /// overrides them key by key.
+    #[serde(default)]
+    pub network: Option<crate::manifest::NetworkConfig>,
+
+    /// Experimental remote compilation on a REAPI cluster.
+    #[serde(default, rename = "remote-execution")]
+    pub remote_execution: Option<RemoteExecutionConfig>,
+
+    /// Endpoint of `kargo search`.
+    #[serde(default)]
+    pub search: Option<SearchConfig>,
 }
 
 /// Build settings from `[build]` in global config.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawBuildConfig")]
 pub struct BuildConfig {
-    #[serde(default = "default_jobs")]
+    /// Parallel compiler and test JVMs. `jobs = "auto"` (the default) uses
+    /// one per core, as many as fit in available memory at `job-memory` each.
     pub jobs: u32,
-    #[serde(default, rename = "default-target")]
+    #[serde(rename = "default-target")]
     pub default_target: Option<String>,
+    /// Memory one compiler or test JVM needs, used to size `jobs = "auto"`
+    /// (default `"1GB"`).
+    #[serde(rename = "job-memory")]
+    pub job_memory: Option<String>,
 }
 
 impl Default for BuildConfig {
     fn default() -> Self {
         Self {
-            jobs: default_jobs(),
+            jobs: kargo_util::system::auto_jobs(kargo_util::system::DEFAULT_JOB_MEMORY),
             default_target: None,
+            job_memory: None,
         }
     }
 }
 
-fn default_jobs() -> u32 {
-    std::thread::available_parallelism()
-        .map(|n| n.get() as u32)
-        .unwrap_or(4)
+/// `[build]` as written, before `jobs = "auto"` is resolved.
+#[derive(Deserialize)]
+struct RawBuildConfig {
+    #[serde(default)]
+    jobs: Option<JobsSetting>,
+    #[serde(default, rename = "default-target")]
+    default_target: Option<String>,
+    #[serde(default, rename = "job-memory")]
+    job_memory: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JobsSetting {
+    Count(u32),
+    Keyword(String),
+}
+
+impl TryFrom<RawBuildConfig> for BuildConfig {
+    type Error = String;
+
+    fn try_from(raw: RawBuildConfig) -> Result<Self, Self::Error> {
+        let per_job = match raw.job_memory.as_deref() {
+            Some(v) => crate::manifest::parse_size(v)
+                .filter(|bytes| *bytes > 0)
+                .ok_or_else(|| format!("[build] job-memory '{v}' is not a size (e.g. \"1GB\")"))?,
+            None => kargo_util::system::DEFAULT_JOB_MEMORY,
+        };
+        let jobs = match raw.jobs {
+            Some(JobsSetting::Count(0)) => return Err("[build] jobs must be at least 1".into()),
+            Some(JobsSetting::Count(n)) => n,
+            Some(JobsSetting::Keyword(k)) if k == "auto" => kargo_util::system::auto_jobs(per_job),
+            Some(JobsSetting::Keyword(k)) => {
+                return Err(format!(
+                    "[build] jobs must be a number or \"auto\", not '{k}'"
+                ))
+            }
+            None => kargo_util::system::auto_jobs(per_job),
+        };
+        Ok(Self {
+            jobs,
+            default_target: raw.default_target,
+            job_memory: raw.job_memory,
+        })
+    }
 }
 
 /// Credential entry for a named repository.
@@ -75,6 +136,13 @@ pub struct CacheConfig {
     pub remote_auth: Option<String>,
     #[serde(default, rename = "remote-push")]
     pub remote_push: Option<bool>,
+    /// Where downloaded dependencies are stored.
+    #[serde(default)]
+    pub dependencies: DependencyCacheMode,
+    /// The per-user dependency cache (default: `dependencies/` in the Kargo
+    /// cache directory). `~/` is the home directory.
+    #[serde(default, rename = "dependencies-dir")]
+    pub dependencies_dir: Option<String>,
 }
 
 impl Default for CacheConfig {
@@ -85,6 +153,21 @@ impl Default for CacheConfig {
             remote: None,
             remote_auth: None,
             remote_push: None,
+            dependencies: DependencyCacheMode::default(),
+            dependencies_dir: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// The per-user dependency cache shared by every project.
+    pub fn shared_dependencies_dir(&self) -> PathBuf {
+        match self.dependencies_dir.as_deref() {
+            Some(dir) => match (dir.strip_prefix("~/"), kargo_util::dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(dir),
+            },
+            None => kargo_util::cache_path().join("dependencies"),
         }
     }
 }
@@ -93,6 +176,54 @@ fn default_cache_dir() -> String {
     "~/.kargo/dependencies".to_string()
 }
 
+/// Where downloaded dependencies are stored, from `[cache] dependencies`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyCacheMode {
+    /// In the project's `.kargo/dependencies/`.
+    #[default]
+    Project,
+    /// In the per-user cache, shared by every project.
+    User,
+    /// In the project's `.kargo/dependencies/`, which is looked in first;
+    /// dependencies it lacks are also looked for in the per-user cache.
+    Layered,
+}
+
+/// Remote execution settings from `[remote-execution]`: JVM compilation
+/// units run `kotlinc` on a Remote Execution API cluster (Buildbarn,
+/// BuildGrid, ...) instead of locally.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteExecutionConfig {
+    /// `grpc://host:port` (plaintext) or `grpcs://host:port` (TLS).
+    pub endpoint: String,
+    #[serde(default, rename = "instance-name")]
+    pub instance_name: String,
+    /// `kotlinc` on the workers (default: `kotlinc` from their `PATH`).
+    #[serde(default)]
+    pub kotlinc: Option<String>,
+    /// `JAVA_HOME` on the workers.
+    #[serde(default, rename = "java-home")]
+    pub java_home: Option<String>,
+    /// How long one compilation may run remotely, e.g. `"10m"`.
+    #[serde(default)]
+    pub timeout: Option<String>,
+    /// Platform properties selecting the workers, e.g. `container-image`.
+    #[serde(default)]
+    pub platform: BTreeMap<String, String>,
+    /// Metadata sent with every request, e.g. an API key header.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// `kargo search` settings from `[search]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// A search.maven.org-compatible Solr endpoint, e.g. a mirror's
+    /// `https://search.example.com/solrsearch/select`.
+    pub url: String,
+}
+
 /// Global toolchain settings from `[toolchain]`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolchainGlobalConfig {
@@ -159,7 +290,7 @@ impl GlobalConfig {
     }
 }
 
-/// Returns the path to the Kargo data directory (`~/.kargo/`).
+/// Returns the path to the Kargo data directory (see [`kargo_util::dirs`]).
 /// Re-exported from `kargo_util` for backward compatibility.
 pub fn dirs_path() -> PathBuf {
     kargo_util::dirs_path()
@@ -9,6 +9,8 @@ pub enum Dependency {
     Short(String),
     Detailed(DetailedDependency),
     Catalog(CatalogDependency),
+    Module(ModuleDependency),
+    File(FileDependency),
 }
 
 /// A dependency with explicit group, artifact, version, and optional metadata.
@@ -25,6 +27,10 @@ pub struct DetailedDependency {
     pub exclusions: Vec<Exclusion>,
     #[serde(default)]
     pub classifier: Option<String>,
+    /// Optional transitive dependencies (`group:artifact`) of this
+    /// dependency to resolve anyway.
+    #[serde(default, rename = "include-optional")]
+    pub include_optional: Vec<String>,
 }
 
 /// A reference to a version catalog entry.
@@ -35,6 +41,25 @@ pub struct CatalogDependency {
     pub bundle: bool,
 }
 
+/// A dependency on another member of the same workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleDependency {
+    /// Member package name, or its directory relative to the workspace root.
+    pub module: String,
+    /// Depend on the member's test fixtures (`src/fixtures`) as well.
+    #[serde(default)]
+    pub fixtures: bool,
+}
+
+/// A prebuilt JAR in the project, e.g. `{ file = "libs/foo.jar" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDependency {
+    /// Path of the JAR, relative to the directory of `Kargo.toml`.
+    pub file: String,
+    #[serde(default)]
+    pub scope: Option<DependencyScope>,
+}
+
 /// A transitive dependency to exclude.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Exclusion {
@@ -54,6 +79,18 @@ pub enum DependencyScope {
     Test,
 }
 
+impl DependencyScope {
+    /// The scope as written in `Kargo.toml` and `Kargo.lock`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compile => "compile",
+            Self::Runtime => "runtime",
+            Self::Provided => "provided",
+            Self::Test => "test",
+        }
+    }
+}
+
 /// Maven coordinates parsed from a shorthand string.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MavenCoordinate {
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::build_config::BuildConfigValue;
+
 /// Flavor configuration from `[flavors]`, defining flavor dimensions and their values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlavorConfig {
@@ -20,7 +22,7 @@ pub struct FlavorConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlavorDefinition {
     #[serde(default, rename = "build-config")]
-    pub build_config: BTreeMap<String, String>,
+    pub build_config: BTreeMap<String, BuildConfigValue>,
 
     #[serde(default, rename = "application-id-suffix")]
     pub application_id_suffix: Option<String>,
@@ -10,6 +10,7 @@
 /// Default Kotlin version used when scaffolding new projects.
 pub const DEFAULT_KOTLIN_VERSION: &str = "2.3.0";
 
+pub mod build_config;
 pub mod config;
 pub mod dependency;
 pub mod flavor;
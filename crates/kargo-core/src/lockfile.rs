@@ -4,10 +4,89 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 
 /// Deterministic lockfile recording exact resolved dependency versions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Lockfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<LockedToolchain>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<LockedEnvironment>,
     #[serde(default)]
     pub package: Vec<LockedPackage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub npm: Vec<LockedNpmPackage>,
+    /// Prebuilt JARs from `{ file = "..." }` dependencies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file: Vec<LockedFile>,
+    /// Toolchain pin and build environment of each workspace member; a
+    /// workspace lockfile leaves `toolchain` and `environment` unset.
+    #[serde(default, rename = "member", skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<LockedMember>,
+}
+
+/// The toolchain pin and build environment of one workspace member, which
+/// members do not share since each may alias a different Kotlin version or
+/// use KSP on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedMember {
+    /// Member key, as in the `members` lists of packages.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<LockedToolchain>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<LockedEnvironment>,
+}
+
+/// The concrete Kotlin version pinned for an aliased `package.kotlin`
+/// (e.g. `2.3` or `latest-stable`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedToolchain {
+    /// The requirement as written in `Kargo.toml`.
+    pub requested: String,
+    /// The resolved compiler version.
+    pub kotlin: String,
+}
+
+/// The build environment in use when the lockfile was generated, recorded by
+/// the first build after resolution and compared against on later builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedEnvironment {
+    /// Kotlin compiler version.
+    pub kotlin: String,
+    /// KSP version, if the project uses KSP processors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ksp: Option<String>,
+    /// JDK major version, e.g. `21`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jdk: Option<String>,
+}
+
+impl LockedEnvironment {
+    /// Describe every way `current` differs from this recorded environment,
+    /// e.g. `Kotlin 2.3.0 (locked with 2.2.20)`. Empty if they match.
+    pub fn drift(&self, current: &LockedEnvironment) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.kotlin != current.kotlin {
+            diffs.push(format!(
+                "Kotlin {} (locked with {})",
+                current.kotlin, self.kotlin
+            ));
+        }
+        let optional = [
+            ("KSP", &self.ksp, &current.ksp),
+            ("JDK", &self.jdk, &current.jdk),
+        ];
+        for (tool, locked, now) in optional {
+            match (locked, now) {
+                (Some(l), Some(n)) if l != n => {
+                    diffs.push(format!("{tool} {n} (locked with {l})"));
+                }
+                (Some(l), None) => diffs.push(format!("no {tool} (locked with {l})")),
+                (None, Some(n)) => diffs.push(format!("{tool} {n} (locked without {tool})")),
+                _ => {}
+            }
+        }
+        diffs
+    }
 }
 
 /// A single locked dependency with its resolved coordinates and checksum.
@@ -26,6 +105,56 @@ pub struct LockedPackage {
     pub targets: Vec<String>,
     #[serde(default)]
     pub dependencies: Vec<LockedDependencyRef>,
+    /// `group:artifact:version` that was declared and whose POM relocated
+    /// it to this package.
+    #[serde(
+        default,
+        rename = "relocated-from",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub relocated_from: Option<String>,
+    /// Workspace members using the package, by member key; empty outside a
+    /// workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Returns `true` if this package is needed when building for `target`
+    /// (kebab-case). Packages without a `targets` list apply to every target.
+    pub fn applies_to(&self, target: &str) -> bool {
+        self.targets.is_empty() || self.targets.iter().any(|t| t == target)
+    }
+}
+
+/// An installed `[npm-dependencies]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedNpmPackage {
+    pub name: String,
+    /// The version range as written in `Kargo.toml`.
+    pub requested: String,
+    /// The exact version the package manager installed.
+    pub version: String,
+    /// Workspace members using the package, by member key; empty outside a
+    /// workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+}
+
+/// A `{ file = "..." }` dependency and the content it was locked with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Path of the JAR relative to the project, with `/` separators.
+    pub path: String,
+    /// SHA-256 of the JAR.
+    pub checksum: String,
+    /// Classpath scope, as for packages: `compile`, `provided`, `runtime`,
+    /// `test` or `integration-test`.
+    pub scope: String,
+    /// Workspace members using the JAR, by member key; empty outside a
+    /// workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
 }
 
 /// A reference to a transitive dependency within the lockfile.
@@ -51,6 +180,149 @@ impl Lockfile {
         })
     }
 
+    /// Load the lockfile of the project in `project_dir`: its own
+    /// `Kargo.lock` or, for a workspace member, the member's part of the
+    /// workspace's.
+    pub fn for_project(project_dir: &Path) -> miette::Result<Self> {
+        let lockfile = Self::from_path(&crate::workspace::lockfile_path(project_dir))?;
+        Ok(match crate::workspace::membership(project_dir) {
+            Some((_, member)) => lockfile.member(&member),
+            None => lockfile,
+        })
+    }
+
+    /// Write the lockfile of the project in `project_dir`, replacing the
+    /// member's part of the workspace's `Kargo.lock` for a workspace member.
+    pub fn write_for_project(&self, project_dir: &Path) -> miette::Result<()> {
+        let path = crate::workspace::lockfile_path(project_dir);
+        let Some((_, member)) = crate::workspace::membership(project_dir) else {
+            return self.write_to(&path);
+        };
+        let mut shared = Self::from_path(&path).unwrap_or_default();
+        shared.set_member(&member, self.clone());
+        shared.write_to(&path)
+    }
+
+    /// The packages of a workspace lockfile that `member` uses, with the
+    /// member's own toolchain pin and build environment.
+    pub fn member(&self, member: &str) -> Self {
+        let uses = |members: &[String]| members.iter().any(|m| m == member);
+        let pins = self.members.iter().find(|m| m.name == member);
+        Self {
+            toolchain: pins.and_then(|m| m.toolchain.clone()),
+            environment: pins.and_then(|m| m.environment.clone()),
+            package: self
+                .package
+                .iter()
+                .filter(|p| uses(&p.members))
+                .map(|p| LockedPackage {
+                    members: Vec::new(),
+                    ..p.clone()
+                })
+                .collect(),
+            npm: self
+                .npm
+                .iter()
+                .filter(|p| uses(&p.members))
+                .map(|p| LockedNpmPackage {
+                    members: Vec::new(),
+                    ..p.clone()
+                })
+                .collect(),
+            file: self
+                .file
+                .iter()
+                .filter(|f| uses(&f.members))
+                .map(|f| LockedFile {
+                    members: Vec::new(),
+                    ..f.clone()
+                })
+                .collect(),
+            members: Vec::new(),
+        }
+    }
+
+    /// Replace the packages `member` uses, and its toolchain pin and build
+    /// environment, with those of `view`, its own lockfile. Packages no
+    /// member uses any more are dropped; packages locked the same way for
+    /// several members are listed once.
+    pub fn set_member(&mut self, member: &str, view: Lockfile) {
+        for pkg in &mut self.package {
+            pkg.members.retain(|m| m != member);
+        }
+        self.package.retain(|p| !p.members.is_empty());
+        for mut pkg in view.package {
+            let same = self.package.iter_mut().find(|p| {
+                (&p.group, &p.name, &p.version, &p.scope, &p.targets)
+                    == (
+                        &pkg.group,
+                        &pkg.name,
+                        &pkg.version,
+                        &pkg.scope,
+                        &pkg.targets,
+                    )
+            });
+            match same {
+                Some(existing) => {
+                    existing.members.push(member.to_string());
+                    existing.members.sort();
+                }
+                None => {
+                    pkg.members = vec![member.to_string()];
+                    self.package.push(pkg);
+                }
+            }
+        }
+        self.package
+            .sort_by(|a, b| (&a.group, &a.name, &a.version).cmp(&(&b.group, &b.name, &b.version)));
+
+        for pkg in &mut self.npm {
+            pkg.members.retain(|m| m != member);
+        }
+        self.npm.retain(|p| !p.members.is_empty());
+        for mut pkg in view.npm {
+            let same = self.npm.iter_mut().find(|p| {
+                (&p.name, &p.requested, &p.version) == (&pkg.name, &pkg.requested, &pkg.version)
+            });
+            match same {
+                Some(existing) => {
+                    existing.members.push(member.to_string());
+                    existing.members.sort();
+                }
+                None => {
+                    pkg.members = vec![member.to_string()];
+                    self.npm.push(pkg);
+                }
+            }
+        }
+        self.npm.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Paths are relative to each member, so files are never shared.
+        for file in &mut self.file {
+            file.members.retain(|m| m != member);
+        }
+        self.file.retain(|f| !f.members.is_empty());
+        self.file.extend(view.file.into_iter().map(|f| LockedFile {
+            members: vec![member.to_string()],
+            ..f
+        }));
+        self.file
+            .sort_by(|a, b| (&a.members, &a.path).cmp(&(&b.members, &b.path)));
+
+        self.members.retain(|m| m.name != member);
+        if view.toolchain.is_some() || view.environment.is_some() {
+            self.members.push(LockedMember {
+                name: member.to_string(),
+                toolchain: view.toolchain,
+                environment: view.environment,
+            });
+            self.members.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        // Pins shared by all members, written before they were kept per member.
+        self.toolchain = None;
+        self.environment = None;
+    }
+
     /// Serialize the lockfile to a pretty-printed TOML string.
     pub fn to_string_pretty(&self) -> miette::Result<String> {
         toml::to_string_pretty(self).map_err(|e| {
@@ -101,12 +373,21 @@ impl Lockfile {
                         version: v,
                     })
                     .collect(),
+                relocated_from: p.relocated_from,
+                members: Vec::new(),
             })
             .collect();
 
         pkgs.sort_by(|a, b| (&a.group, &a.name, &a.version).cmp(&(&b.group, &b.name, &b.version)));
 
-        Self { package: pkgs }
+        Self {
+            toolchain: None,
+            environment: None,
+            package: pkgs,
+            npm: Vec::new(),
+            file: Vec::new(),
+            members: Vec::new(),
+        }
     }
 
     /// Check whether the lockfile is up-to-date with respect to the declared dependencies.
@@ -125,6 +406,35 @@ impl Lockfile {
         true
     }
 
+    /// Check whether the locked npm packages match `[npm-dependencies]`
+    /// exactly (same names, same requested ranges).
+    pub fn npm_up_to_date(&self, declared: &std::collections::BTreeMap<String, String>) -> bool {
+        self.npm.len() == declared.len()
+            && declared
+                .iter()
+                .all(|(name, req)| self.locked_npm_version(name, req).is_some())
+    }
+
+    /// Look up the locked version of an npm package, if it was installed for
+    /// the same `requested` range.
+    pub fn locked_npm_version(&self, name: &str, requested: &str) -> Option<&str> {
+        self.npm
+            .iter()
+            .find(|p| p.name == name && p.requested == requested)
+            .map(|p| p.version.as_str())
+    }
+
+    /// Return the pinned Kotlin version if it was resolved for `requested`.
+    ///
+    /// A pin recorded for a different requirement (the manifest changed since
+    /// the last `kargo update`) is ignored.
+    pub fn pinned_kotlin(&self, requested: &str) -> Option<&str> {
+        self.toolchain
+            .as_ref()
+            .filter(|t| t.requested == requested)
+            .map(|t| t.kotlin.as_str())
+    }
+
     /// Look up a locked version for a given `group:artifact`.
     pub fn locked_version(&self, group: &str, artifact: &str) -> Option<&str> {
         self.package
@@ -154,6 +464,8 @@ pub struct ResolvedPackageInfo {
     pub targets: Vec<String>,
     /// Dependencies as `(group, artifact, version)` tuples.
     pub dependencies: Vec<(String, String, String)>,
+    /// `group:artifact:version` relocated to this package.
+    pub relocated_from: Option<String>,
 }
 
 #[cfg(test)]
@@ -172,6 +484,7 @@ mod tests {
                 checksum: None,
                 targets: vec![],
                 dependencies: vec![],
+                relocated_from: None,
             },
             ResolvedPackageInfo {
                 group: "org.a".to_string(),
@@ -182,6 +495,7 @@ mod tests {
                 checksum: None,
                 targets: vec![],
                 dependencies: vec![("org.z".into(), "z-lib".into(), "1.0".into())],
+                relocated_from: None,
             },
         ];
 
@@ -195,6 +509,9 @@ mod tests {
     #[test]
     fn is_up_to_date_check() {
         let lf = Lockfile {
+            toolchain: None,
+            environment: None,
+            npm: vec![],
             package: vec![LockedPackage {
                 name: "lib".to_string(),
                 group: "org.example".to_string(),
@@ -204,7 +521,11 @@ mod tests {
                 scope: None,
                 targets: vec![],
                 dependencies: vec![],
+                relocated_from: None,
+                members: Vec::new(),
             }],
+            file: Vec::new(),
+            members: Vec::new(),
         };
 
         assert!(lf.is_up_to_date(&[("org.example".into(), "lib".into(), "1.0".into())]));
@@ -214,6 +535,9 @@ mod tests {
     #[test]
     fn locked_version_lookup() {
         let lf = Lockfile {
+            toolchain: None,
+            environment: None,
+            npm: vec![],
             package: vec![LockedPackage {
                 name: "lib".to_string(),
                 group: "org.example".to_string(),
@@ -223,7 +547,11 @@ mod tests {
                 scope: None,
                 targets: vec![],
                 dependencies: vec![],
+                relocated_from: None,
+                members: Vec::new(),
             }],
+            file: Vec::new(),
+            members: Vec::new(),
         };
 
         assert_eq!(lf.locked_version("org.example", "lib"), Some("1.0"));
@@ -241,6 +569,7 @@ mod tests {
             checksum: Some("abc123".to_string()),
             targets: vec!["jvm".to_string()],
             dependencies: vec![],
+            relocated_from: None,
         }]);
 
         let toml_str = lf.to_string_pretty().unwrap();
@@ -248,4 +577,53 @@ mod tests {
         assert_eq!(parsed.package.len(), 1);
         assert_eq!(parsed.package[0].targets, vec!["jvm"]);
     }
+
+    #[test]
+    fn toolchain_pin_round_trip() {
+        let lf = Lockfile {
+            toolchain: Some(LockedToolchain {
+                requested: "latest-stable".to_string(),
+                kotlin: "2.3.0".to_string(),
+            }),
+            environment: None,
+            package: vec![],
+            npm: vec![],
+            file: Vec::new(),
+            members: Vec::new(),
+        };
+
+        let parsed: Lockfile = toml::from_str(&lf.to_string_pretty().unwrap()).unwrap();
+        assert_eq!(parsed.pinned_kotlin("latest-stable"), Some("2.3.0"));
+        assert_eq!(parsed.pinned_kotlin("2.2"), None);
+    }
+
+    #[test]
+    fn npm_packages_round_trip() {
+        let lf = Lockfile {
+            toolchain: None,
+            environment: None,
+            package: vec![],
+            npm: vec![LockedNpmPackage {
+                name: "left-pad".to_string(),
+                requested: "^1.3.0".to_string(),
+                version: "1.3.0".to_string(),
+                members: Vec::new(),
+            }],
+            file: Vec::new(),
+            members: Vec::new(),
+        };
+
+        let parsed: Lockfile = toml::from_str(&lf.to_string_pretty().unwrap()).unwrap();
+        assert_eq!(
+            parsed.locked_npm_version("left-pad", "^1.3.0"),
+            Some("1.3.0")
+        );
+        assert_eq!(parsed.locked_npm_version("left-pad", "^2.0.0"), None);
+
+        let mut declared = std::collections::BTreeMap::new();
+        declared.insert("left-pad".to_string(), "^1.3.0".to_string());
+        assert!(parsed.npm_up_to_date(&declared));
+        declared.insert("lodash".to_string(), "^4.0.0".to_string());
+        assert!(!parsed.npm_up_to_date(&declared));
+    }
 }
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use kargo_util::http::{HttpSettings, IpVersion, TlsBackend, TlsVersion};
+
+use crate::build_config::BuildConfigValue;
 use crate::dependency::Dependency;
 use crate::flavor::FlavorConfig;
 use crate::profile::Profile;
-use crate::target::TargetConfig;
+use crate::target::{KotlinTarget, TargetConfig, TargetSelector, TARGET_GROUPS};
 
 /// The parsed representation of a `Kargo.toml` file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +27,9 @@ pub struct Manifest {
     #[serde(default, rename = "dev-dependencies")]
     pub dev_dependencies: BTreeMap<String, Dependency>,
 
+    #[serde(default, rename = "integration-test-dependencies")]
+    pub integration_test_dependencies: BTreeMap<String, Dependency>,
+
     #[serde(default)]
     pub target: BTreeMap<String, TargetDependencies>,
 
@@ -69,6 +75,48 @@ pub struct Manifest {
     #[serde(default, rename = "package.docker")]
     pub docker: Option<DockerConfig>,
 
+    /// npm packages for the `js` and `wasm-js` targets from
+    /// `[npm-dependencies]` (package name → version range).
+    #[serde(default, rename = "npm-dependencies")]
+    pub npm_dependencies: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub npm: Option<NpmConfig>,
+
+    #[serde(default)]
+    pub network: Option<NetworkConfig>,
+
+    /// Code generators from `[generate.<name>]`, run before compilation.
+    #[serde(default)]
+    pub generate: BTreeMap<String, GeneratorConfig>,
+
+    /// Task pipelines from `[tasks.<name>]`, run with `kargo task <name>`.
+    #[serde(default)]
+    pub tasks: BTreeMap<String, TaskConfig>,
+
+    #[serde(default)]
+    pub protobuf: Option<ProtobufConfig>,
+
+    #[serde(default)]
+    pub sqldelight: Option<SqlDelightConfig>,
+
+    /// How far `kargo update` may move dependencies matching a
+    /// `group:artifact` pattern (`*` matches any run of characters).
+    #[serde(default, rename = "update-policy")]
+    pub update_policy: BTreeMap<String, UpdatePolicy>,
+
+    /// Build-time and artifact-size limits checked after each build.
+    #[serde(default)]
+    pub budgets: Option<BudgetConfig>,
+
+    /// Framework name and deployment targets for Apple consumers.
+    #[serde(default)]
+    pub apple: Option<AppleConfig>,
+
+    /// Restrictions on build-time code run with the user's permissions.
+    #[serde(default)]
+    pub sandbox: Option<SandboxConfig>,
+
     #[serde(default)]
     pub ksp: BTreeMap<String, Dependency>,
 
@@ -83,11 +131,12 @@ pub struct Manifest {
 
     /// Custom compile-time constants from `[build-config]`.
     ///
-    /// These are merged with flavor build-config and emitted as
-    /// `const val` fields in the generated `BuildConfig` object.
-    /// Values support `${env:VAR}` interpolation (resolved at load time).
+    /// These are merged with target and flavor build-config and emitted as
+    /// fields of the generated `BuildConfig` object, typed by their TOML
+    /// value (see [`crate::build_config`]). Values support `${env:VAR}`
+    /// interpolation (resolved at load time).
     #[serde(default, rename = "build-config")]
-    pub build_config: BTreeMap<String, String>,
+    pub build_config: BTreeMap<String, BuildConfigValue>,
 }
 
 /// Package identity and metadata from the `[package]` section.
@@ -110,8 +159,32 @@ pub struct PackageMetadata {
     pub main_class: Option<String>,
     #[serde(default, rename = "ksp-version")]
     pub ksp_version: Option<String>,
+    #[serde(default)]
+    pub jar: Option<JarConfig>,
 }
 
+/// Output JAR settings from `[package.jar]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JarConfig {
+    /// Extra `META-INF/MANIFEST.MF` main attributes. These override the
+    /// `Implementation-*` attributes Kargo derives from `[package]`.
+    #[serde(default, rename = "manifest-attributes")]
+    pub manifest_attributes: BTreeMap<String, String>,
+    /// Mark the JAR `Multi-Release: true` so `META-INF/versions/<N>/`
+    /// entries are picked up by JDK 9+.
+    #[serde(default, rename = "multi-release")]
+    pub multi_release: bool,
+}
+
+/// Main attributes Kargo writes itself; set them through their dedicated
+/// settings instead of `[package.jar] manifest-attributes`.
+pub const RESERVED_JAR_ATTRIBUTES: &[(&str, &str)] = &[
+    ("Manifest-Version", "it is always 1.0"),
+    ("Created-By", "it is always Kargo"),
+    ("Main-Class", "use package.main-class"),
+    ("Multi-Release", "use [package.jar] multi-release"),
+];
+
 /// Compose Multiplatform configuration from `[compose]`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComposeConfig {
@@ -124,6 +197,10 @@ pub struct ComposeConfig {
 pub struct TargetDependencies {
     #[serde(default)]
     pub dependencies: BTreeMap<String, Dependency>,
+    /// `[target.<t>.build-config]`, merged over `[build-config]` when
+    /// building a matching target.
+    #[serde(default, rename = "build-config")]
+    pub build_config: BTreeMap<String, BuildConfigValue>,
 }
 
 /// Per-flavor dependency overrides from `[flavor.<name>.dependencies]`.
@@ -174,9 +251,36 @@ pub enum RepositoryEntry {
         username: Option<String>,
         #[serde(default)]
         password: Option<String>,
+        /// Only query this repository for groups matching one of these
+        /// patterns (see [`group_matches`]); empty means every group.
+        #[serde(default, rename = "include-groups")]
+        include_groups: Vec<String>,
+        /// Never query this repository for groups matching these patterns.
+        #[serde(default, rename = "exclude-groups")]
+        exclude_groups: Vec<String>,
     },
 }
 
+/// Whether Maven group `group` matches `pattern`. `*` matches any run of
+/// characters, and a trailing `.*` also matches the group itself:
+/// `com.mycorp.*` matches `com.mycorp` and `com.mycorp.billing`.
+pub fn group_matches(pattern: &str, group: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix(".*") {
+        if group == prefix {
+            return true;
+        }
+    }
+    glob_match(pattern.as_bytes(), group.as_bytes())
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
 /// Workspace configuration from the `[workspace]` section.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
@@ -184,6 +288,28 @@ pub struct WorkspaceConfig {
     pub members: Vec<String>,
     #[serde(default)]
     pub exclude: Vec<String>,
+    /// Version catalog shared by all members (`[workspace.catalog]`).
+    #[serde(default)]
+    pub catalog: Option<CatalogConfig>,
+    /// Package metadata members take with `key.workspace = true`.
+    #[serde(default)]
+    pub package: Option<WorkspacePackage>,
+    /// Profiles for members that do not define them (`[workspace.profile.*]`).
+    #[serde(default)]
+    pub profile: BTreeMap<String, Profile>,
+}
+
+/// Shared package metadata from `[workspace.package]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspacePackage {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub authors: Option<Vec<String>>,
+    #[serde(default)]
+    pub license: Option<String>,
 }
 
 /// Per-project toolchain overrides from `[toolchain]`.
@@ -198,7 +324,7 @@ pub struct ToolchainConfig {
 }
 
 /// Version catalog configuration from `[catalog]`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CatalogConfig {
     #[serde(default)]
     pub versions: BTreeMap<String, String>,
@@ -234,6 +360,72 @@ pub struct CatalogPlugin {
 pub struct TestConfig {
     #[serde(default)]
     pub coverage: Option<CoverageConfig>,
+    /// Environment variables set for the test JVM.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Extra arguments for the test JVM, e.g. `-Xmx1g`.
+    #[serde(default, rename = "jvm-args")]
+    pub jvm_args: Vec<String>,
+    /// System properties passed to the test JVM as `-Dkey=value`.
+    #[serde(default, rename = "system-properties")]
+    pub system_properties: BTreeMap<String, String>,
+    /// Most output captured per test and stream, e.g. `"64KB"`; output
+    /// past it is dropped from the report. Defaults to 1MB.
+    #[serde(default, rename = "max-output")]
+    pub max_output: Option<String>,
+    /// Device test settings (`[test.android]`).
+    #[serde(default)]
+    pub android: Option<AndroidTestConfig>,
+}
+
+impl TestConfig {
+    pub fn max_output(&self) -> Option<u64> {
+        self.max_output.as_deref().and_then(parse_size)
+    }
+}
+
+/// Instrumentation settings for `kargo test --device`, from `[test.android]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AndroidTestConfig {
+    /// Package of the test APK (default: `<group>.<name>.test`).
+    #[serde(default, rename = "application-id")]
+    pub application_id: Option<String>,
+    /// Instrumentation runner class.
+    #[serde(default = "default_android_test_runner")]
+    pub runner: String,
+    /// Run each test in its own instrumentation with Android Test
+    /// Orchestrator, so a crash only fails that test.
+    #[serde(default = "default_orchestrator")]
+    pub orchestrator: bool,
+    /// Have the orchestrator clear the app's data between tests.
+    #[serde(default, rename = "clear-package-data")]
+    pub clear_package_data: bool,
+    /// Extra `-e key value` arguments for `am instrument`.
+    #[serde(default, rename = "instrumentation-args")]
+    pub instrumentation_args: BTreeMap<String, String>,
+}
+
+impl Default for AndroidTestConfig {
+    fn default() -> Self {
+        Self {
+            application_id: None,
+            runner: default_android_test_runner(),
+            orchestrator: true,
+            clear_package_data: false,
+            instrumentation_args: BTreeMap::new(),
+        }
+    }
+}
+
+/// Runner used when `[test.android] runner` is not set.
+pub const DEFAULT_ANDROID_TEST_RUNNER: &str = "androidx.test.runner.AndroidJUnitRunner";
+
+fn default_android_test_runner() -> String {
+    DEFAULT_ANDROID_TEST_RUNNER.to_string()
+}
+
+fn default_orchestrator() -> bool {
+    true
 }
 
 /// Code coverage configuration from `[test.coverage]`.
@@ -249,6 +441,364 @@ pub struct CoverageConfig {
     pub exclude: Vec<String>,
 }
 
+/// npm settings from `[npm]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpmConfig {
+    /// `npm` (default), `yarn`, or `pnpm`.
+    #[serde(default, rename = "package-manager")]
+    pub package_manager: Option<String>,
+}
+
+/// Package managers accepted by `[npm] package-manager`.
+pub const NPM_PACKAGE_MANAGERS: &[&str] = &["npm", "yarn", "pnpm"];
+
+/// HTTP limits and TLS settings for repository traffic from `[network]`.
+///
+/// Corporate repository managers often throttle clients that open many
+/// connections at once; the limits keep Kargo under them. The TLS settings
+/// cover TLS-intercepting proxies (`ca-bundle`), mutual TLS and IP family
+/// restrictions, and may also be set in `~/.kargo/config.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Most requests in flight across all repositories (default 8).
+    #[serde(default, rename = "max-concurrency")]
+    pub max_concurrency: Option<usize>,
+    /// Most requests in flight to one host (default: `max-concurrency`).
+    #[serde(default, rename = "max-per-host")]
+    pub max_per_host: Option<usize>,
+    /// Minimum gap between two requests to the same host, in milliseconds.
+    #[serde(default, rename = "request-delay-ms")]
+    pub request_delay_ms: Option<u64>,
+    /// Lower per-host concurrency when a host slows down or answers 429/503,
+    /// and raise it again while it keeps up (default true).
+    #[serde(default, rename = "auto-tune")]
+    pub auto_tune: Option<bool>,
+    /// Overrides for individual hosts, keyed by host name.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, HostNetworkConfig>,
+    /// `rustls` (default) or `native`, the platform TLS library.
+    #[serde(default, rename = "tls-backend")]
+    pub tls_backend: Option<TlsBackend>,
+    /// Lowest accepted TLS version, `"1.2"` or `"1.3"`.
+    #[serde(default, rename = "min-tls-version")]
+    pub min_tls_version: Option<TlsVersion>,
+    /// PEM file of additional root certificates to trust.
+    #[serde(default, rename = "ca-bundle")]
+    pub ca_bundle: Option<String>,
+    /// Trust the TLS backend's built-in roots as well (default true).
+    #[serde(default, rename = "tls-built-in-roots")]
+    pub tls_built_in_roots: Option<bool>,
+    /// PEM client certificate for mutual TLS.
+    #[serde(default, rename = "client-cert")]
+    pub client_cert: Option<String>,
+    /// PEM private key for `client-cert`, if not in the same file.
+    #[serde(default, rename = "client-key")]
+    pub client_key: Option<String>,
+    /// `any` (default), `ipv4` or `ipv6`.
+    #[serde(default, rename = "ip-version")]
+    pub ip_version: Option<IpVersion>,
+}
+
+impl NetworkConfig {
+    /// `base` with the TLS and IP settings of this section applied on top.
+    /// Relative paths are resolved against `dir`, the directory of the file
+    /// the section was read from; `~/` is the home directory.
+    pub fn http_settings(&self, dir: &Path, base: HttpSettings) -> HttpSettings {
+        let path = |p: &String| {
+            let p = match p.strip_prefix("~/") {
+                Some(rest) => kargo_util::dirs::home_dir()
+                    .map(|home| home.join(rest))
+                    .unwrap_or_else(|| PathBuf::from(p)),
+                None => PathBuf::from(p),
+            };
+            dir.join(p)
+        };
+        HttpSettings {
+            tls_backend: self.tls_backend.unwrap_or(base.tls_backend),
+            min_tls_version: self.min_tls_version.or(base.min_tls_version),
+            ca_bundle: self.ca_bundle.as_ref().map(path).or(base.ca_bundle),
+            tls_built_in_roots: self.tls_built_in_roots.unwrap_or(base.tls_built_in_roots),
+            client_cert: self.client_cert.as_ref().map(path).or(base.client_cert),
+            client_key: self.client_key.as_ref().map(path).or(base.client_key),
+            ip_version: self.ip_version.unwrap_or(base.ip_version),
+        }
+    }
+
+    /// Check settings that only make sense together.
+    pub fn validate_tls(&self) -> Result<(), String> {
+        if self.client_key.is_some() && self.client_cert.is_none() {
+            return Err("[network] client-key requires client-cert".to_string());
+        }
+        if self.tls_built_in_roots == Some(false) && self.ca_bundle.is_none() {
+            return Err(
+                "[network] tls-built-in-roots = false requires a ca-bundle to trust".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Per-host overrides from `[network.hosts."<host>"]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostNetworkConfig {
+    #[serde(default, rename = "max-concurrency")]
+    pub max_concurrency: Option<usize>,
+    #[serde(default, rename = "request-delay-ms")]
+    pub request_delay_ms: Option<u64>,
+}
+
+/// Upper bound for `[network]` concurrency settings.
+pub const MAX_NETWORK_CONCURRENCY: usize = 64;
+
+/// A code generator from `[generate.<name>]`.
+///
+/// Either runs `command` or a built-in `generator`. Generation is skipped
+/// while the `inputs` and the generator's own settings are unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeneratorConfig {
+    /// Program and arguments, run from the project root. `{out}` in an
+    /// argument is replaced by the generator's output directory.
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Built-in generator to run instead of a command (see [`BUILTIN_GENERATORS`]).
+    #[serde(default)]
+    pub generator: Option<String>,
+    /// Files, directories, or glob patterns the output is derived from.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Files the generator must produce, relative to its output directory.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Input specification for built-in generators (e.g. the OpenAPI document).
+    #[serde(default)]
+    pub spec: Option<String>,
+    /// Package of the generated code, for built-in generators.
+    #[serde(default)]
+    pub package: Option<String>,
+    /// Version of the built-in generator's tool.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A task from `[tasks.<name>]`.
+///
+/// Its `depends-on` tasks run first, each at most once, then its `steps` in
+/// order. A step whose first word is a Kargo command (`build --release`)
+/// runs that command; any other step is a shell command run from the project
+/// root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskConfig {
+    /// One-line summary shown by `kargo task`.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Tasks to run before this one.
+    #[serde(default, rename = "depends-on")]
+    pub depends_on: Vec<String>,
+    /// Commands to run, in order; the task stops at the first that fails.
+    #[serde(default)]
+    pub steps: Vec<String>,
+    /// Environment variables set for the steps.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Generators accepted by `[generate.<name>] generator`, with the settings
+/// each one requires.
+pub const BUILTIN_GENERATORS: &[(&str, &[&str])] = &[("openapi", &["spec"])];
+
+/// Protocol Buffers code generation from `[protobuf]`.
+///
+/// `.proto` files under `src/main/proto` are compiled even without this
+/// section; it only overrides versions and enables gRPC stubs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProtobufConfig {
+    /// protoc release, as published on Maven Central (e.g. `4.28.2`).
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Generate Kotlin DSL builders next to the Java messages (default true).
+    #[serde(default)]
+    pub kotlin: Option<bool>,
+    /// Generate gRPC service stubs (default false).
+    #[serde(default)]
+    pub grpc: bool,
+    #[serde(default, rename = "grpc-version")]
+    pub grpc_version: Option<String>,
+    #[serde(default, rename = "grpc-kotlin-version")]
+    pub grpc_kotlin_version: Option<String>,
+    /// Directories holding `.proto` files (default `["src/main/proto"]`).
+    #[serde(default, rename = "source-dirs")]
+    pub source_dirs: Vec<String>,
+}
+
+/// SQLDelight code generation from `[sqldelight]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlDelightConfig {
+    /// `group:artifact:version` of the SQLDelight compiler CLI.
+    #[serde(default)]
+    pub compiler: Option<String>,
+    /// Entry point of the compiler CLI.
+    #[serde(default, rename = "main-class")]
+    pub main_class: Option<String>,
+    /// Databases to generate, keyed by the generated interface name.
+    #[serde(default)]
+    pub databases: BTreeMap<String, SqlDelightDatabase>,
+}
+
+/// One database from `[sqldelight.databases.<Name>]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlDelightDatabase {
+    /// Package of the generated code.
+    pub package: String,
+    /// `group:artifact:version` of the SQL dialect module.
+    #[serde(default)]
+    pub dialect: Option<String>,
+    /// Directories holding `.sq`/`.sqm` files (default `["src/main/sqldelight"]`).
+    #[serde(default, rename = "source-dirs")]
+    pub source_dirs: Vec<String>,
+    /// Check migrations against the schema in `kargo check`.
+    #[serde(default, rename = "verify-migrations")]
+    pub verify_migrations: bool,
+}
+
+/// The largest version change `kargo update` may make to a dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdatePolicy {
+    /// Never update.
+    None,
+    /// Only change the third version segment.
+    Patch,
+    /// Stay within the current major version.
+    Minor,
+    /// Any newer version.
+    Major,
+}
+
+impl UpdatePolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdatePolicy::None => "none",
+            UpdatePolicy::Patch => "patch",
+            UpdatePolicy::Minor => "minor",
+            UpdatePolicy::Major => "major",
+        }
+    }
+}
+
+/// Limits from `[budgets]`. Exceeding one prints a warning after the
+/// build, or fails it under `kargo build --enforce-budgets`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Wall-clock time of the whole build, e.g. `"60s"` or `"2m"`.
+    #[serde(default, rename = "build-time")]
+    pub build_time: Option<String>,
+    /// Size of the packaged JAR, e.g. `"25MB"`.
+    #[serde(default, rename = "jar-size")]
+    pub jar_size: Option<String>,
+    /// Time limits for individual build phases, keyed by phase name
+    /// (see [`BUDGET_PHASES`]).
+    #[serde(default)]
+    pub phases: BTreeMap<String, String>,
+}
+
+/// Sandboxing from `[sandbox]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Run KSP and KAPT processors in a sandbox that can only read their
+    /// inputs, write their output directories and not reach the network.
+    #[serde(default)]
+    pub processors: bool,
+    /// Extra files or directories, relative to the package, that sandboxed
+    /// processors may read.
+    #[serde(default, rename = "allow-read")]
+    pub allow_read: Vec<String>,
+    /// Let sandboxed processors use the network.
+    #[serde(default, rename = "allow-network")]
+    pub allow_network: bool,
+}
+
+/// Apple framework settings from `[apple]`, used when Xcode, CocoaPods or
+/// Swift Package Manager consume the Kotlin/Native frameworks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppleConfig {
+    /// Name of the framework and the Swift module (default: the package
+    /// name in UpperCamelCase).
+    #[serde(default, rename = "framework-name")]
+    pub framework_name: Option<String>,
+    /// Minimum OS version per platform (`ios`, `macos`, `tvos`, `watchos`).
+    #[serde(default, rename = "deployment-target")]
+    pub deployment_target: BTreeMap<String, String>,
+}
+
+/// Apple platforms with the deployment target used when `[apple]` does
+/// not set one.
+pub const APPLE_PLATFORMS: &[(&str, &str)] = &[
+    ("ios", "13.0"),
+    ("macos", "11.0"),
+    ("tvos", "13.0"),
+    ("watchos", "6.0"),
+];
+
+/// Build phases that `[budgets.phases]` may limit.
+pub const BUDGET_PHASES: &[&str] = &[
+    "generate",
+    "annotation-processing",
+    "compile",
+    "package",
+    "shrink",
+];
+
+impl BudgetConfig {
+    pub fn build_time(&self) -> Option<std::time::Duration> {
+        self.build_time.as_deref().and_then(parse_duration)
+    }
+
+    pub fn jar_size(&self) -> Option<u64> {
+        self.jar_size.as_deref().and_then(parse_size)
+    }
+
+    /// The time budget for `phase`, if one is set.
+    pub fn phase_time(&self, phase: &str) -> Option<std::time::Duration> {
+        self.phases.get(phase).and_then(|v| parse_duration(v))
+    }
+}
+
+/// Parse a duration such as `"500ms"`, `"90s"`, `"2m"` or `"1.5m"`.
+pub fn parse_duration(s: &str) -> Option<std::time::Duration> {
+    let s = s.trim();
+    let (num, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60.0)
+    } else if let Some(n) = s.strip_suffix('h') {
+        (n, 3600.0)
+    } else {
+        return None;
+    };
+    let value: f64 = num.trim().parse().ok()?;
+    (value.is_finite() && value >= 0.0).then(|| std::time::Duration::from_secs_f64(value * scale))
+}
+
+/// Parse a size such as `"512KB"`, `"25MB"`, `"1.5GB"` or a plain byte count.
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (num, unit) = if let Some(n) = s.strip_suffix("GB") {
+        (n, 1024.0 * 1024.0 * 1024.0)
+    } else if let Some(n) = s.strip_suffix("MB") {
+        (n, 1024.0 * 1024.0)
+    } else if let Some(n) = s.strip_suffix("KB") {
+        (n, 1024.0)
+    } else if let Some(n) = s.strip_suffix('B') {
+        (n, 1.0)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = num.trim().parse().ok()?;
+    (value.is_finite() && value >= 0.0).then_some((value * unit) as u64)
+}
+
 /// Artifact signing configuration from `[signing]`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SigningConfig {
@@ -276,7 +826,11 @@ impl Manifest {
     ///
     /// Before parsing, `${env:VAR}` references in the manifest content are
     /// resolved using `.kargo.env` (if present alongside `Kargo.toml`) and
-    /// process environment variables.
+    /// process environment variables. `[package]` keys set to
+    /// `{ workspace = true }` take their value from `[workspace.package]` of
+    /// the enclosing workspace, and catalog entries and profiles the
+    /// manifest does not define itself come from its `[workspace.catalog]`
+    /// and `[workspace.profile.*]`.
     pub fn from_path(path: &Path) -> miette::Result<Self> {
         let content = std::fs::read_to_string(path).map_err(|e| {
             kargo_util::errors::KargoError::Manifest {
@@ -289,7 +843,14 @@ impl Manifest {
             crate::properties::load_env_file(&dir.join(".kargo.env")).unwrap_or_default();
         let resolved = crate::properties::interpolate(&content, &env_vars);
 
-        let manifest = Self::parse_toml(&resolved)?;
+        let abs_dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let workspace = crate::workspace::find_root(&abs_dir).map(|(_, config)| config);
+        let resolved = crate::workspace::inherit_package(&resolved, workspace.as_ref())?;
+
+        let mut manifest = Self::parse_toml(&resolved)?;
+        if let Some(workspace) = workspace {
+            crate::workspace::inherit(&mut manifest, workspace);
+        }
         manifest.validate()?;
         Ok(manifest)
     }
@@ -333,10 +894,11 @@ impl Manifest {
             )));
         }
 
-        // kotlin: major.minor or major.minor.patch
-        if !is_valid_kotlin_version(&self.package.kotlin) {
+        // kotlin: major.minor.patch, a major.minor alias, or a release keyword
+        if KotlinVersionReq::parse(&self.package.kotlin).is_none() {
             return Err(err(format!(
-                "package.kotlin '{}' is not a valid version (expected e.g. 2.3.0 or 2.3)",
+                "package.kotlin '{}' is not a valid version \
+                 (expected e.g. 2.3.0, 2.3, latest-stable, or latest)",
                 self.package.kotlin
             )));
         }
@@ -368,11 +930,116 @@ impl Manifest {
             }
         }
 
+        // jar manifest attributes: valid header names, not Kargo-owned
+        if let Some(ref jar) = self.package.jar {
+            for name in jar.manifest_attributes.keys() {
+                if name.is_empty()
+                    || name.len() > 70
+                    || !name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(err(format!(
+                        "[package.jar] manifest attribute '{name}' is not a valid JAR manifest \
+                         header name (alphanumeric, '-', '_', at most 70 characters)"
+                    )));
+                }
+                if let Some((_, hint)) = RESERVED_JAR_ATTRIBUTES
+                    .iter()
+                    .find(|(r, _)| r.eq_ignore_ascii_case(name))
+                {
+                    return Err(err(format!(
+                        "[package.jar] manifest attribute '{name}' is set by Kargo ({hint})"
+                    )));
+                }
+            }
+        }
+
+        // Prebuilt JARs go on the classpaths of the project itself
+        let mut file_sections: Vec<(String, &BTreeMap<String, Dependency>)> = vec![
+            ("dependencies".into(), &self.dependencies),
+            ("dev-dependencies".into(), &self.dev_dependencies),
+            (
+                "integration-test-dependencies".into(),
+                &self.integration_test_dependencies,
+            ),
+            ("ksp".into(), &self.ksp),
+            ("kapt".into(), &self.kapt),
+        ];
+        for (selector, deps) in &self.target {
+            file_sections.push((
+                format!("target.{selector}.dependencies"),
+                &deps.dependencies,
+            ));
+        }
+        for (section, deps) in file_sections {
+            for (key, dep) in deps {
+                let Dependency::File(f) = dep else {
+                    continue;
+                };
+                let project_section = matches!(
+                    section.as_str(),
+                    "dependencies" | "dev-dependencies" | "integration-test-dependencies"
+                );
+                if !project_section {
+                    return Err(err(format!(
+                        "file dependency '{key}' in [{section}]: JAR files are only supported \
+                         in [dependencies], [dev-dependencies] and [integration-test-dependencies]"
+                    )));
+                }
+                if !f.file.ends_with(".jar") {
+                    return Err(err(format!(
+                        "file dependency '{key}': '{}' is not a .jar file",
+                        f.file
+                    )));
+                }
+            }
+        }
+
+        // Workspace module dependencies are built before the consuming
+        // member, so processor and per-target sections cannot take them
+        let mut processor_sections: Vec<(String, &BTreeMap<String, Dependency>)> =
+            vec![("ksp".into(), &self.ksp), ("kapt".into(), &self.kapt)];
+        for (selector, deps) in &self.target {
+            processor_sections.push((
+                format!("target.{selector}.dependencies"),
+                &deps.dependencies,
+            ));
+        }
+        for (section, deps) in processor_sections {
+            if let Some(key) = deps
+                .iter()
+                .find(|(_, d)| matches!(d, Dependency::Module(_)))
+                .map(|(k, _)| k)
+            {
+                return Err(err(format!(
+                    "module dependency '{key}' in [{section}]: workspace module dependencies \
+                     are only supported in [dependencies], [dev-dependencies] and \
+                     [integration-test-dependencies]"
+                )));
+            }
+        }
+        if let Some(key) = self
+            .dependencies
+            .iter()
+            .find(|(_, d)| matches!(d, Dependency::Module(m) if m.fixtures))
+            .map(|(k, _)| k)
+        {
+            return Err(err(format!(
+                "module dependency '{key}' in [dependencies]: fixtures are only available \
+                 to [dev-dependencies] and [integration-test-dependencies]"
+            )));
+        }
+
         // Duplicate dependency detection across sections
         let mut seen = std::collections::HashSet::new();
         let sections: &[(&str, &BTreeMap<String, Dependency>)] = &[
             ("dependencies", &self.dependencies),
             ("dev-dependencies", &self.dev_dependencies),
+            (
+                "integration-test-dependencies",
+                &self.integration_test_dependencies,
+            ),
             ("ksp", &self.ksp),
             ("kapt", &self.kapt),
         ];
@@ -387,7 +1054,323 @@ impl Manifest {
             }
         }
 
+        let target_sections = self.target.values().map(|t| &t.dependencies);
+        for deps in sections.iter().map(|(_, d)| *d).chain(target_sections) {
+            for (key, dep) in deps {
+                let Dependency::Detailed(d) = dep else {
+                    continue;
+                };
+                if let Some(bad) = d
+                    .include_optional
+                    .iter()
+                    .find(|c| c.split(':').count() != 2 || c.split(':').any(str::is_empty))
+                {
+                    return Err(err(format!(
+                        "dependency '{key}': include-optional entry '{bad}' must be \
+                         \"group:artifact\""
+                    )));
+                }
+            }
+        }
+
+        if let Some(pm) = self.npm.as_ref().and_then(|n| n.package_manager.as_deref()) {
+            if !NPM_PACKAGE_MANAGERS.contains(&pm) {
+                return Err(err(format!(
+                    "unknown npm.package-manager '{pm}' (expected one of: {})",
+                    NPM_PACKAGE_MANAGERS.join(", ")
+                )));
+            }
+        }
+        if !self.npm_dependencies.is_empty()
+            && !self
+                .declared_targets()
+                .iter()
+                .any(|t| matches!(t, KotlinTarget::Js | KotlinTarget::WasmJs))
+        {
+            return Err(err(
+                "[npm-dependencies] requires a `js` or `wasm-js` entry in [targets]".into(),
+            ));
+        }
+
+        if let Some(ref network) = self.network {
+            network.validate_tls().map_err(err)?;
+            let limits = [
+                ("max-concurrency".to_string(), network.max_concurrency),
+                ("max-per-host".to_string(), network.max_per_host),
+            ]
+            .into_iter()
+            .chain(network.hosts.iter().map(|(host, h)| {
+                (
+                    format!("hosts.\"{host}\".max-concurrency"),
+                    h.max_concurrency,
+                )
+            }));
+            for (key, value) in limits {
+                if value.is_some_and(|n| n == 0 || n > MAX_NETWORK_CONCURRENCY) {
+                    return Err(err(format!(
+                        "[network] {key} must be between 1 and {MAX_NETWORK_CONCURRENCY}"
+                    )));
+                }
+            }
+        }
+
+        for (name, gen) in &self.generate {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(err(format!(
+                    "[generate.{name}]: generator names may only contain alphanumeric \
+                     characters, '-' and '_'"
+                )));
+            }
+            match (&gen.generator, gen.command.is_empty()) {
+                (None, true) => {
+                    return Err(err(format!(
+                        "[generate.{name}] needs either `command` or `generator`"
+                    )));
+                }
+                (Some(_), false) => {
+                    return Err(err(format!(
+                        "[generate.{name}] sets both `command` and `generator`; use one"
+                    )));
+                }
+                (Some(builtin), true) => {
+                    let Some((_, required)) = BUILTIN_GENERATORS.iter().find(|(g, _)| g == builtin)
+                    else {
+                        return Err(err(format!(
+                            "[generate.{name}]: unknown generator '{builtin}' (expected one of: {})",
+                            BUILTIN_GENERATORS
+                                .iter()
+                                .map(|(g, _)| *g)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )));
+                    };
+                    if required.contains(&"spec") && gen.spec.is_none() {
+                        return Err(err(format!(
+                            "[generate.{name}]: generator '{builtin}' requires `spec`"
+                        )));
+                    }
+                }
+                (None, false) => {}
+            }
+            if let Some(bad) = gen
+                .outputs
+                .iter()
+                .find(|o| Path::new(o).is_absolute() || o.split(['/', '\\']).any(|s| s == ".."))
+            {
+                return Err(err(format!(
+                    "[generate.{name}]: output '{bad}' must be relative to the output directory"
+                )));
+            }
+        }
+
+        for (name, task) in &self.tasks {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(err(format!(
+                    "[tasks.{name}]: task names may only contain alphanumeric \
+                     characters, '-' and '_'"
+                )));
+            }
+            if task.steps.is_empty() && task.depends_on.is_empty() {
+                return Err(err(format!("[tasks.{name}] needs `steps` or `depends-on`")));
+            }
+            if let Some(missing) = task
+                .depends_on
+                .iter()
+                .find(|dep| !self.tasks.contains_key(*dep))
+            {
+                return Err(err(format!(
+                    "[tasks.{name}] depends on unknown task '{missing}'"
+                )));
+            }
+        }
+
+        if let Some(ref sqldelight) = self.sqldelight {
+            let is_coordinate =
+                |c: &str| c.split(':').count() == 3 && !c.split(':').any(str::is_empty);
+            if !sqldelight.databases.is_empty()
+                && (sqldelight.compiler.is_none() || sqldelight.main_class.is_none())
+            {
+                return Err(err(
+                    "[sqldelight] databases require `compiler` and `main-class`".into(),
+                ));
+            }
+            if let Some(compiler) = sqldelight.compiler.as_deref().filter(|c| !is_coordinate(c)) {
+                return Err(err(format!(
+                    "[sqldelight] compiler '{compiler}' must be \"group:artifact:version\""
+                )));
+            }
+            for (name, db) in &sqldelight.databases {
+                if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+                    || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(err(format!(
+                        "[sqldelight.databases.{name}]: the database name must be a Kotlin identifier"
+                    )));
+                }
+                if db.package.is_empty() {
+                    return Err(err(format!(
+                        "[sqldelight.databases.{name}]: `package` must not be empty"
+                    )));
+                }
+                if let Some(dialect) = db.dialect.as_deref().filter(|d| !is_coordinate(d)) {
+                    return Err(err(format!(
+                        "[sqldelight.databases.{name}]: dialect '{dialect}' must be \
+                         \"group:artifact:version\""
+                    )));
+                }
+            }
+        }
+
+        let mut build_configs = vec![&self.build_config];
+        build_configs.extend(self.target.values().map(|t| &t.build_config));
+        if let Some(flavors) = &self.flavors {
+            build_configs.extend(
+                flavors
+                    .dimension_flavors
+                    .values()
+                    .flat_map(|dim| dim.values().map(|f| &f.build_config)),
+            );
+        }
+        for fields in build_configs {
+            crate::build_config::resolve_all(fields, 0, None).map_err(err)?;
+        }
+
+        if let Some(v) = self
+            .test
+            .as_ref()
+            .and_then(|t| t.max_output.as_deref())
+            .filter(|v| parse_size(v).is_none())
+        {
+            return Err(err(format!(
+                "[test] max-output '{v}' is not a size (e.g. \"64KB\")"
+            )));
+        }
+
+        if let Some(budgets) = &self.budgets {
+            if let Some(v) = budgets
+                .build_time
+                .as_deref()
+                .filter(|v| parse_duration(v).is_none())
+            {
+                return Err(err(format!(
+                    "[budgets] build-time '{v}' is not a duration (e.g. \"90s\" or \"2m\")"
+                )));
+            }
+            if let Some(v) = budgets
+                .jar_size
+                .as_deref()
+                .filter(|v| parse_size(v).is_none())
+            {
+                return Err(err(format!(
+                    "[budgets] jar-size '{v}' is not a size (e.g. \"25MB\")"
+                )));
+            }
+            for (phase, v) in &budgets.phases {
+                if !BUDGET_PHASES.contains(&phase.as_str()) {
+                    return Err(err(format!(
+                        "[budgets.phases] unknown phase '{phase}'; expected one of: {}",
+                        BUDGET_PHASES.join(", ")
+                    )));
+                }
+                if parse_duration(v).is_none() {
+                    return Err(err(format!(
+                        "[budgets.phases] {phase} '{v}' is not a duration (e.g. \"30s\")"
+                    )));
+                }
+            }
+        }
+
+        if let Some(apple) = &self.apple {
+            if let Some(name) = &apple.framework_name {
+                let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(err(format!(
+                        "[apple] framework-name '{name}' must be a valid Swift module name"
+                    )));
+                }
+            }
+            for (platform, version) in &apple.deployment_target {
+                if !APPLE_PLATFORMS.iter().any(|(p, _)| p == platform) {
+                    return Err(err(format!(
+                        "[apple] unknown deployment-target platform '{platform}'; \
+                         expected one of: ios, macos, tvos, watchos"
+                    )));
+                }
+                let numeric = !version.is_empty()
+                    && version
+                        .split('.')
+                        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+                if !numeric {
+                    return Err(err(format!(
+                        "[apple] deployment-target {platform} '{version}' is not a version \
+                         (e.g. \"15.0\")"
+                    )));
+                }
+            }
+        }
+
+        if let Some(android) = self.test.as_ref().and_then(|t| t.android.as_ref()) {
+            if let Some(id) = &android.application_id {
+                if !is_application_id(id) {
+                    return Err(err(format!(
+                        "[test.android] application-id '{id}' must be a package name with at \
+                         least two segments (e.g. \"com.example.app.test\")"
+                    )));
+                }
+            }
+        }
+
+        for pattern in self.update_policy.keys() {
+            let valid = pattern
+                .split_once(':')
+                .is_some_and(|(g, a)| !g.is_empty() && !a.is_empty() && !a.contains(':'));
+            if !valid {
+                return Err(err(format!(
+                    "[update-policy] pattern '{pattern}' must be \"group:artifact\" \
+                     (`*` is a wildcard)"
+                )));
+            }
+        }
+
+        for key in self.target.keys() {
+            if TargetSelector::parse(key).is_none() {
+                return Err(err(format!(
+                    "invalid target selector '{key}' in [target.{key}.dependencies]; \
+                     expected a target name, a group ({}), or a cfg(...) expression",
+                    TARGET_GROUPS.join(", ")
+                )));
+            }
+        }
+
         for (name, entry) in &self.repositories {
+            if let RepositoryEntry::Detailed {
+                include_groups,
+                exclude_groups,
+                ..
+            } = entry
+            {
+                for pattern in include_groups.iter().chain(exclude_groups) {
+                    if pattern.is_empty()
+                        || !pattern
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "._-*".contains(c))
+                    {
+                        return Err(err(format!(
+                            "invalid group pattern '{pattern}' in [repositories.{name}]; \
+                             expected a group such as \"com.mycorp\" or \"com.mycorp.*\""
+                        )));
+                    }
+                }
+            }
             if let RepositoryEntry::Detailed {
                 password: Some(pw), ..
             } = entry
@@ -403,12 +1386,225 @@ impl Manifest {
 
         Ok(())
     }
+
+    /// Targets declared under `[targets]`, or `jvm` alone when none are.
+    /// Unknown target names are skipped.
+    pub fn declared_targets(&self) -> Vec<KotlinTarget> {
+        if self.targets.is_empty() {
+            return vec![KotlinTarget::Jvm];
+        }
+        self.targets
+            .keys()
+            .filter_map(|k| KotlinTarget::parse(k))
+            .collect()
+    }
+
+    /// The framework name Apple consumers import: `[apple] framework-name`,
+    /// or the package name in UpperCamelCase (`my-lib` -> `MyLib`).
+    pub fn framework_name(&self) -> String {
+        if let Some(name) = self.apple.as_ref().and_then(|a| a.framework_name.clone()) {
+            return name;
+        }
+        self.package
+            .name
+            .split(['-', '_'])
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Package of the APK `kargo test --device` builds: `[test.android]
+    /// application-id`, or `<group>.<name>.test` with the name's dashes
+    /// turned into underscores.
+    pub fn android_test_application_id(&self) -> String {
+        if let Some(id) = self
+            .test
+            .as_ref()
+            .and_then(|t| t.android.as_ref())
+            .and_then(|a| a.application_id.clone())
+        {
+            return id;
+        }
+        let name: String = self
+            .package
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        match &self.package.group {
+            Some(group) => format!("{group}.{name}.test"),
+            None => format!("{name}.test"),
+        }
+    }
+
+    /// Minimum OS version for an Apple `platform` (`ios`, `macos`, ...).
+    pub fn deployment_target(&self, platform: &str) -> Option<String> {
+        self.apple
+            .as_ref()
+            .and_then(|a| a.deployment_target.get(platform).cloned())
+            .or_else(|| {
+                APPLE_PLATFORMS
+                    .iter()
+                    .find(|(p, _)| *p == platform)
+                    .map(|(_, v)| v.to_string())
+            })
+    }
+
+    /// The `[update-policy]` rule for `group:artifact`. When several
+    /// patterns match, the one with the most literal characters wins.
+    pub fn update_policy_for(&self, group: &str, artifact: &str) -> Option<UpdatePolicy> {
+        let coordinate = format!("{group}:{artifact}");
+        self.update_policy
+            .iter()
+            .filter(|(pattern, _)| wildcard_match(pattern, &coordinate))
+            .max_by_key(|(pattern, _)| pattern.chars().filter(|&c| c != '*').count())
+            .map(|(_, policy)| *policy)
+    }
+
+    /// Declared targets that the `[target.<key>]` selector applies to.
+    pub fn targets_matching(&self, key: &str) -> Vec<KotlinTarget> {
+        let Some(selector) = TargetSelector::parse(key) else {
+            return Vec::new();
+        };
+        self.declared_targets()
+            .into_iter()
+            .filter(|t| selector.matches(*t))
+            .collect()
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Whether `id` is an Android package name: two or more dot-separated
+/// Java identifiers.
+fn is_application_id(id: &str) -> bool {
+    let segments: Vec<&str> = id.split('.').collect();
+    segments.len() >= 2
+        && segments.iter().all(|s| {
+            s.starts_with(|c: char| c.is_ascii_alphabetic())
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+impl PackageMetadata {
+    /// Interpret the `kotlin` field as a version requirement.
+    ///
+    /// Returns `None` if the field is not a recognized version or alias;
+    /// [`Manifest::validate`] rejects such manifests.
+    pub fn kotlin_req(&self) -> Option<KotlinVersionReq> {
+        KotlinVersionReq::parse(&self.kotlin)
+    }
+}
+
+/// How `package.kotlin` selects a compiler version.
+///
+/// Anything other than [`KotlinVersionReq::Exact`] is resolved against
+/// release metadata by `kargo update` and pinned in `Kargo.lock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KotlinVersionReq {
+    /// A fully specified version such as `2.3.0` or `2.2.20-RC`.
+    Exact(String),
+    /// A `major.minor` alias such as `2.3`: the newest stable patch release.
+    Minor { major: u64, minor: u64 },
+    /// `latest-stable` (or `stable`): the newest stable release.
+    LatestStable,
+    /// `latest`: the newest release, including betas and release candidates.
+    Latest,
 }
 
-fn is_valid_kotlin_version(s: &str) -> bool {
-    let parts: Vec<&str> = s.split('.').collect();
-    matches!(parts.len(), 2 | 3)
-        && parts
+impl KotlinVersionReq {
+    /// Parse a `package.kotlin` value.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "latest-stable" | "stable" => return Some(Self::LatestStable),
+            "latest" => return Some(Self::Latest),
+            _ => {}
+        }
+
+        let (core, pre) = match s.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (s, None),
+        };
+        let parts: Vec<&str> = core.split('.').collect();
+        let numeric = parts
             .iter()
-            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+        if !numeric || pre.is_some_and(|p| p.is_empty()) {
+            return None;
+        }
+
+        match (parts.len(), pre) {
+            (2, None) => Some(Self::Minor {
+                major: parts[0].parse().ok()?,
+                minor: parts[1].parse().ok()?,
+            }),
+            (3, _) => Some(Self::Exact(s.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this names one concrete version.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+
+    /// Returns `true` if the concrete `version` satisfies this requirement.
+    pub fn matches(&self, version: &str) -> bool {
+        let Ok(v) = semver::Version::parse(version) else {
+            return false;
+        };
+        match self {
+            Self::Exact(e) => e == version,
+            Self::Minor { major, minor } => {
+                v.pre.is_empty() && v.major == *major && v.minor == *minor
+            }
+            Self::LatestStable => v.pre.is_empty(),
+            Self::Latest => true,
+        }
+    }
+
+    /// Pick the newest version from `available` that satisfies this requirement.
+    pub fn select<'a>(&self, available: impl IntoIterator<Item = &'a str>) -> Option<String> {
+        available
+            .into_iter()
+            .filter(|v| self.matches(v))
+            .filter_map(|v| semver::Version::parse(v).ok().map(|sv| (sv, v)))
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|(_, v)| v.to_string())
+    }
+}
+
+impl std::fmt::Display for KotlinVersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact(v) => write!(f, "{v}"),
+            Self::Minor { major, minor } => write!(f, "{major}.{minor}"),
+            Self::LatestStable => write!(f, "latest-stable"),
+            Self::Latest => write!(f, "latest"),
+        }
+    }
 }
@@ -11,6 +11,38 @@ pub struct Profile {
 
     #[serde(default, rename = "compiler-args")]
     pub compiler_args: Vec<String>,
+
+    #[serde(default)]
+    pub shrink: Option<ShrinkConfig>,
+
+    /// Compile the main sources as parallel per-package units when the
+    /// packages have no dependency cycles.
+    #[serde(default, rename = "split-units")]
+    pub split_units: bool,
+
+    /// Keep generated output independent of when it was built:
+    /// `${build:timestamp}` in `[build-config]` becomes `SOURCE_DATE_EPOCH`
+    /// or 0 instead of the current time.
+    #[serde(default)]
+    pub reproducible: bool,
+}
+
+/// R8 shrinking of the output JAR from `[profile.<name>.shrink]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShrinkConfig {
+    /// Run R8 after packaging; defaults to `true` once the table is present.
+    #[serde(default = "default_shrink_enabled")]
+    pub enabled: bool,
+    /// ProGuard-format rules files, relative to the project root.
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// R8 version fetched from Google Maven.
+    #[serde(default, rename = "r8-version")]
+    pub r8_version: Option<String>,
+}
+
+fn default_shrink_enabled() -> bool {
+    true
 }
 
 impl Profile {
@@ -20,6 +52,9 @@ impl Profile {
             debug: Some(true),
             optimization: Some(false),
             compiler_args: Vec::new(),
+            shrink: None,
+            split_units: false,
+            reproducible: false,
         }
     }
 
@@ -29,6 +64,14 @@ impl Profile {
             debug: Some(false),
             optimization: Some(true),
             compiler_args: Vec::new(),
+            shrink: None,
+            split_units: false,
+            reproducible: false,
         }
     }
+
+    /// The shrink settings, if shrinking is enabled for this profile.
+    pub fn shrink(&self) -> Option<&ShrinkConfig> {
+        self.shrink.as_ref().filter(|s| s.enabled)
+    }
 }
@@ -1,5 +1,12 @@
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Machine-local settings written by Android Studio (`sdk.dir`); never
+/// committed.
+pub const LOCAL_PROPERTIES: &str = "local.properties";
+
+/// Gradle build settings, read by `kargo init` when migrating a project.
+pub const GRADLE_PROPERTIES: &str = "gradle.properties";
 
 /// Loads a `.kargo.env` file (shell-style `KEY=value` format).
 ///
@@ -45,3 +52,133 @@ pub fn interpolate(input: &str, env_overrides: &BTreeMap<String, String>) -> Str
     }
     result
 }
+
+/// Loads a Java `.properties` file such as `local.properties` or
+/// `gradle.properties`.
+///
+/// Supports `=`, `:` and whitespace separators, `#`/`!` comments, `\`
+/// line continuations and the standard escapes (`\t`, `\n`, `\uXXXX`, ...).
+/// A missing file yields an empty map.
+pub fn load_properties(path: &Path) -> miette::Result<BTreeMap<String, String>> {
+    if !path.is_file() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(path).map_err(kargo_util::errors::KargoError::Io)?;
+    Ok(parse_properties(&content))
+}
+
+/// Parse `.properties` content; see [`load_properties`].
+pub fn parse_properties(content: &str) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+            continue;
+        }
+        let mut logical = trimmed.to_string();
+        while ends_with_continuation(&logical) {
+            logical.pop();
+            match lines.next() {
+                Some(next) => logical.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+
+        let (key, rest) = split_key(&logical);
+        let rest = rest.trim_start();
+        let rest = rest
+            .strip_prefix(['=', ':'])
+            .map(str::trim_start)
+            .unwrap_or(rest);
+        map.insert(unescape(key), unescape(rest));
+    }
+    map
+}
+
+/// `sdk.dir` from `local.properties` in `project_dir`.
+pub fn local_sdk_dir(project_dir: &Path) -> Option<PathBuf> {
+    load_properties(&project_dir.join(LOCAL_PROPERTIES))
+        .ok()?
+        .remove("sdk.dir")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `gradle.properties` settings that have a `Kargo.toml` equivalent.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GradleImport {
+    /// `org.gradle.jvmargs`, for `[test] jvm-args`.
+    pub jvm_args: Vec<String>,
+    /// `kotlin.code.style`, for `[format] style`.
+    pub code_style: Option<String>,
+    /// Keys with no Kargo equivalent, sorted.
+    pub unmapped: Vec<String>,
+}
+
+impl GradleImport {
+    /// Map the keys of a loaded `gradle.properties`.
+    pub fn from_properties(props: &BTreeMap<String, String>) -> Self {
+        let mut import = Self::default();
+        for (key, value) in props {
+            match key.as_str() {
+                "org.gradle.jvmargs" => {
+                    import.jvm_args = value.split_whitespace().map(str::to_string).collect();
+                }
+                "kotlin.code.style" => import.code_style = Some(value.clone()),
+                _ => import.unmapped.push(key.clone()),
+            }
+        }
+        import
+    }
+
+    /// Whether anything maps to `Kargo.toml`.
+    pub fn is_empty(&self) -> bool {
+        self.jvm_args.is_empty() && self.code_style.is_none()
+    }
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+/// Split at the first unescaped `=`, `:` or whitespace.
+fn split_key(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            return (&line[..i], &line[i..]);
+        }
+    }
+    (line, "")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                if let Some(ch) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    out.push(ch);
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
@@ -22,6 +22,15 @@ pub struct TargetConfig {
 
     #[serde(default, rename = "compile-sdk")]
     pub compile_sdk: Option<u32>,
+
+    /// Values for `${name}` placeholders in merged Android manifests.
+    #[serde(default, rename = "manifest-placeholders")]
+    pub manifest_placeholders: std::collections::BTreeMap<String, String>,
+
+    /// Core library desugaring of Java APIs newer than `min-sdk`; detected
+    /// from the dexed classes when unset.
+    #[serde(default, rename = "core-library-desugaring")]
+    pub core_library_desugaring: Option<bool>,
 }
 
 /// C/Objective-C interop configuration for Kotlin/Native targets.
@@ -65,6 +74,29 @@ pub enum KotlinTarget {
 }
 
 impl KotlinTarget {
+    /// Every supported target, in declaration order.
+    pub const ALL: &'static [KotlinTarget] = &[
+        Self::Jvm,
+        Self::Android,
+        Self::Js,
+        Self::WasmJs,
+        Self::WasmWasi,
+        Self::IosArm64,
+        Self::IosSimulatorArm64,
+        Self::IosX64,
+        Self::MacosArm64,
+        Self::MacosX64,
+        Self::LinuxX64,
+        Self::LinuxArm64,
+        Self::MingwX64,
+        Self::TvosArm64,
+        Self::TvosSimulatorArm64,
+        Self::WatchosArm64,
+        Self::WatchosSimulatorArm64,
+        Self::AndroidNativeArm64,
+        Self::AndroidNativeX64,
+    ];
+
     /// Parse a target name (kebab-case or camelCase) into a `KotlinTarget`.
     pub fn parse(s: &str) -> Option<Self> {
         match s {
@@ -122,6 +154,26 @@ impl KotlinTarget {
         )
     }
 
+    /// The Apple platform (`ios`, `macos`, `tvos`, `watchos`) of an Apple
+    /// target.
+    pub fn apple_platform(&self) -> Option<&'static str> {
+        if !self.is_apple() {
+            return None;
+        }
+        self.groups().last().copied()
+    }
+
+    /// Returns `true` for the iOS, tvOS and watchOS simulator targets.
+    pub fn is_simulator(&self) -> bool {
+        matches!(
+            self,
+            Self::IosSimulatorArm64
+                | Self::IosX64
+                | Self::TvosSimulatorArm64
+                | Self::WatchosSimulatorArm64
+        )
+    }
+
     /// Returns the Kotlin compiler binary name for this target.
     pub fn compiler_name(&self) -> &'static str {
         match self {
@@ -181,6 +233,166 @@ impl KotlinTarget {
             Self::AndroidNativeX64 => "androidNativeX64",
         }
     }
+
+    /// Platform groups this target belongs to, following the default KMP
+    /// hierarchy (`common` is implied and not listed). For example,
+    /// `ios-arm64` belongs to `native`, `apple`, and `ios`.
+    pub fn groups(&self) -> &'static [&'static str] {
+        match self {
+            Self::Jvm | Self::Android | Self::Js => &[],
+            Self::WasmJs | Self::WasmWasi => &["wasm"],
+            Self::IosArm64 | Self::IosSimulatorArm64 | Self::IosX64 => &["native", "apple", "ios"],
+            Self::MacosArm64 | Self::MacosX64 => &["native", "apple", "macos"],
+            Self::TvosArm64 | Self::TvosSimulatorArm64 => &["native", "apple", "tvos"],
+            Self::WatchosArm64 | Self::WatchosSimulatorArm64 => &["native", "apple", "watchos"],
+            Self::LinuxX64 | Self::LinuxArm64 => &["native", "linux"],
+            Self::MingwX64 => &["native", "mingw"],
+            Self::AndroidNativeArm64 | Self::AndroidNativeX64 => &["native", "android-native"],
+        }
+    }
+}
+
+/// Group names accepted by [`TargetSelector`], in addition to target names.
+pub const TARGET_GROUPS: &[&str] = &[
+    "common",
+    "native",
+    "apple",
+    "ios",
+    "macos",
+    "tvos",
+    "watchos",
+    "linux",
+    "mingw",
+    "android-native",
+    "wasm",
+];
+
+/// Which targets a `[target.<selector>.dependencies]` section applies to.
+///
+/// A selector is a single target (`jvm`, `ios-arm64`), a target group
+/// (`apple`, `native`, `ios`, ...; see [`TARGET_GROUPS`]), or a `cfg(...)`
+/// expression combining them with `any`, `all`, and `not`:
+///
+/// ```text
+/// [target.apple.dependencies]
+/// [target.'cfg(any(jvm, android))'.dependencies]
+/// [target.'cfg(all(native, not(mingw)))'.dependencies]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetSelector {
+    Target(KotlinTarget),
+    Group(&'static str),
+    Any(Vec<TargetSelector>),
+    All(Vec<TargetSelector>),
+    Not(Box<TargetSelector>),
+}
+
+impl TargetSelector {
+    /// Parse a `[target.<key>]` table key. Returns `None` for unknown names
+    /// or malformed `cfg(...)` expressions.
+    pub fn parse(key: &str) -> Option<Self> {
+        let key = key.trim();
+        match key.strip_prefix("cfg(").and_then(|r| r.strip_suffix(')')) {
+            Some(inner) => {
+                let mut parser = CfgParser {
+                    input: inner,
+                    pos: 0,
+                };
+                let expr = parser.expr()?;
+                parser.skip_ws();
+                (parser.pos == parser.input.len()).then_some(expr)
+            }
+            None => Self::name(key),
+        }
+    }
+
+    fn name(name: &str) -> Option<Self> {
+        if let Some(t) = KotlinTarget::parse(name) {
+            return Some(Self::Target(t));
+        }
+        let kebab = if name == "androidNative" {
+            "android-native"
+        } else {
+            name
+        };
+        TARGET_GROUPS
+            .iter()
+            .find(|g| **g == kebab)
+            .map(|g| Self::Group(g))
+    }
+
+    /// Returns `true` if `target` is selected.
+    pub fn matches(&self, target: KotlinTarget) -> bool {
+        match self {
+            Self::Target(t) => *t == target,
+            Self::Group("common") => true,
+            Self::Group(g) => target.groups().contains(g),
+            Self::Any(items) => items.iter().any(|s| s.matches(target)),
+            Self::All(items) => items.iter().all(|s| s.matches(target)),
+            Self::Not(inner) => !inner.matches(target),
+        }
+    }
+}
+
+/// Recursive-descent parser for the inside of `cfg(...)`.
+struct CfgParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl CfgParser<'_> {
+    fn skip_ws(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Option<&str> {
+        self.skip_ws();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    fn expr(&mut self) -> Option<TargetSelector> {
+        let ident = self.ident()?.to_string();
+        match ident.as_str() {
+            "any" | "all" | "not" => {
+                if !self.eat('(') {
+                    return None;
+                }
+                let mut items = vec![self.expr()?];
+                while self.eat(',') {
+                    items.push(self.expr()?);
+                }
+                if !self.eat(')') {
+                    return None;
+                }
+                match ident.as_str() {
+                    "any" => Some(TargetSelector::Any(items)),
+                    "all" => Some(TargetSelector::All(items)),
+                    _ if items.len() == 1 => Some(TargetSelector::Not(Box::new(items.remove(0)))),
+                    _ => None,
+                }
+            }
+            name => TargetSelector::name(name),
+        }
+    }
 }
 
 impl fmt::Display for KotlinTarget {
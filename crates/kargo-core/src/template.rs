@@ -48,6 +48,20 @@ pub struct ProjectTemplate {
     pub files: Vec<FileEntry>,
 }
 
+/// Default `.gitignore` written into every scaffolded project.
+///
+/// Covers build output, the per-project Kargo state directory, secrets, and
+/// machine-local SDK paths and IDE files that must never be committed.
+pub const GITIGNORE: &str = "\
+build/
+.kargo/
+.kargo.env
+local.properties
+.idea/
+*.iml
+.DS_Store
+";
+
 /// Variables available for `{{variable}}` interpolation in template content.
 pub struct TemplateContext {
     vars: BTreeMap<String, String>,
@@ -150,7 +164,7 @@ impl ProjectTemplate {
             "# This file is auto-generated by Kargo. Commit it for applications.\n",
         )?;
 
-        write(root.join(".gitignore"), "build/\n.kargo/\n.kargo.env\n")?;
+        write(root.join(".gitignore"), GITIGNORE)?;
 
         write(
             root.join(".kargo.env"),
@@ -201,6 +215,12 @@ impl TemplateRegistry {
             ("kmp", include_str!("../templates/kmp.toml")),
             ("cmp", include_str!("../templates/cmp.toml")),
             ("android", include_str!("../templates/android.toml")),
+            (
+                "compose-desktop",
+                include_str!("../templates/compose-desktop.toml"),
+            ),
+            ("ktor", include_str!("../templates/ktor.toml")),
+            ("kmp-lib", include_str!("../templates/kmp-lib.toml")),
         ];
 
         let mut templates = BTreeMap::new();
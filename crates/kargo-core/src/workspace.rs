@@ -1,5 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use kargo_util::errors::KargoError;
+use toml_edit::{Array, DocumentMut, Item};
+
+use crate::manifest::{CatalogConfig, Manifest, WorkspaceConfig};
 use crate::package::Package;
 
 /// A Kargo workspace: a root manifest with member packages.
@@ -20,3 +24,193 @@ impl Workspace {
                 .unwrap_or(true)
     }
 }
+
+/// Nearest directory at or above `start` whose `Kargo.toml` declares a
+/// `[workspace]`, with that workspace configuration.
+pub fn find_root(start: &Path) -> Option<(PathBuf, WorkspaceConfig)> {
+    start.ancestors().find_map(|dir| {
+        // The root may be a virtual manifest without `[package]`, so only
+        // the `[workspace]` table is parsed.
+        #[derive(serde::Deserialize)]
+        struct Root {
+            workspace: Option<WorkspaceConfig>,
+        }
+        let content = std::fs::read_to_string(dir.join("Kargo.toml")).ok()?;
+        let root: Root = toml::from_str(&content).ok()?;
+        Some((dir.to_path_buf(), root.workspace?))
+    })
+}
+
+/// Whether the `Kargo.toml` in `dir` is a virtual workspace manifest: a
+/// `[workspace]` without a `[package]` of its own.
+pub fn is_virtual_root(dir: &Path) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Root {
+        package: Option<toml::Value>,
+        workspace: Option<toml::Value>,
+    }
+    std::fs::read_to_string(dir.join("Kargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<Root>(&content).ok())
+        .is_some_and(|root| root.workspace.is_some() && root.package.is_none())
+}
+
+/// The workspace root and member key of the project in `project_dir`, if it
+/// is a workspace member: its directory relative to the root with `/`
+/// separators, or `.` for the package of the root manifest.
+pub fn membership(project_dir: &Path) -> Option<(PathBuf, String)> {
+    let (root, config) = find_root(project_dir)?;
+    if project_dir == root {
+        return (!is_virtual_root(&root)).then(|| (root, ".".to_string()));
+    }
+    if !member_dirs(&root, &config).iter().any(|d| d == project_dir) {
+        return None;
+    }
+    let key = project_dir
+        .strip_prefix(&root)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((root, key))
+}
+
+/// The `Kargo.lock` of the project in `project_dir`. Workspace members share
+/// the one at the workspace root.
+pub fn lockfile_path(project_dir: &Path) -> PathBuf {
+    match membership(project_dir) {
+        Some((root, _)) => root.join("Kargo.lock"),
+        None => project_dir.join("Kargo.lock"),
+    }
+}
+
+/// Member directories listed in `[workspace] members`. An entry ending in
+/// `/*` stands for every subdirectory that contains a `Kargo.toml`.
+pub fn member_dirs(root: &Path, config: &WorkspaceConfig) -> Vec<PathBuf> {
+    let excluded = |dir: &Path| {
+        config
+            .exclude
+            .iter()
+            .any(|e| dir == root.join(e.trim_end_matches('/')))
+    };
+    let mut dirs = Vec::new();
+    for member in &config.members {
+        if let Some(parent) = member.strip_suffix("/*") {
+            let Ok(entries) = std::fs::read_dir(root.join(parent)) else {
+                continue;
+            };
+            let mut found: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.join("Kargo.toml").is_file())
+                .collect();
+            found.sort();
+            dirs.extend(found);
+        } else {
+            dirs.push(root.join(member));
+        }
+    }
+    dirs.retain(|d| !excluded(d));
+    dirs
+}
+
+/// Directory of the member named `module`: matched against each member's
+/// package name, then its path relative to the workspace root.
+pub fn find_member(root: &Path, config: &WorkspaceConfig, module: &str) -> Option<PathBuf> {
+    let members = member_dirs(root, config);
+    members
+        .iter()
+        .find(|dir| {
+            Manifest::from_path(&dir.join("Kargo.toml"))
+                .map(|m| m.package.name == module)
+                .unwrap_or(false)
+        })
+        .or_else(|| {
+            members.iter().find(|dir| {
+                dir.strip_prefix(root)
+                    .is_ok_and(|rel| rel == Path::new(module))
+            })
+        })
+        .cloned()
+}
+
+/// `[package]` keys a member can take from `[workspace.package]`.
+pub const INHERITED_PACKAGE_KEYS: &[&str] = &["version", "group", "authors", "license"];
+
+/// `content` with every `[package]` key set to `{ workspace = true }`
+/// replaced by the value from `[workspace.package]` of `workspace`.
+pub fn inherit_package(
+    content: &str,
+    workspace: Option<&WorkspaceConfig>,
+) -> miette::Result<String> {
+    // Syntax errors are left for the manifest parser to report.
+    let Ok(mut doc) = content.parse::<DocumentMut>() else {
+        return Ok(content.to_string());
+    };
+    let Some(package) = doc.get_mut("package").and_then(Item::as_table_like_mut) else {
+        return Ok(content.to_string());
+    };
+    let shared = workspace
+        .and_then(|w| w.package.clone())
+        .unwrap_or_default();
+    let mut changed = false;
+    for &key in INHERITED_PACKAGE_KEYS {
+        let inherits = package
+            .get(key)
+            .and_then(|item| item.get("workspace"))
+            .and_then(Item::as_bool)
+            == Some(true);
+        if !inherits {
+            continue;
+        }
+        let value = match key {
+            "version" => shared.version.clone().map(toml_edit::value),
+            "group" => shared.group.clone().map(toml_edit::value),
+            "license" => shared.license.clone().map(toml_edit::value),
+            _ => shared
+                .authors
+                .clone()
+                .map(|authors| toml_edit::value(Array::from_iter(authors))),
+        };
+        let value = value.ok_or_else(|| KargoError::Manifest {
+            message: if workspace.is_some() {
+                format!("`package.{key}.workspace = true`, but [workspace.package] does not set `{key}`")
+            } else {
+                format!("`package.{key}.workspace = true`, but no [workspace] was found")
+            },
+        })?;
+        package.insert(key, value);
+        changed = true;
+    }
+    Ok(if changed {
+        doc.to_string()
+    } else {
+        content.to_string()
+    })
+}
+
+/// Add the `[workspace.catalog]` entries and `[workspace.profile.*]` of
+/// `workspace` to `manifest`. Entries and profiles the member defines itself
+/// win.
+pub fn inherit(manifest: &mut Manifest, workspace: WorkspaceConfig) {
+    for (name, profile) in workspace.profile {
+        manifest.profile.entry(name).or_insert(profile);
+    }
+    let Some(shared) = workspace.catalog else {
+        return;
+    };
+    let catalog = manifest.catalog.get_or_insert_with(CatalogConfig::default);
+    for (name, version) in shared.versions {
+        catalog.versions.entry(name).or_insert(version);
+    }
+    for (name, library) in shared.libraries {
+        catalog.libraries.entry(name).or_insert(library);
+    }
+    for (name, bundle) in shared.bundles {
+        catalog.bundles.entry(name).or_insert(bundle);
+    }
+    for (name, plugin) in shared.plugins {
+        catalog.plugins.entry(name).or_insert(plugin);
+    }
+}
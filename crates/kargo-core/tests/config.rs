@@ -1,4 +1,4 @@
-use kargo_core::config::{dirs_path, GlobalConfig};
+use kargo_core::config::{dirs_path, DependencyCacheMode, GlobalConfig};
 
 #[test]
 fn test_global_config_default_jobs_nonzero() {
@@ -32,9 +32,10 @@ fn test_global_config_default_empty_repos() {
 }
 
 #[test]
-fn test_dirs_path_contains_kargo() {
+fn test_dirs_path_is_the_kargo_data_dir() {
     let path = dirs_path();
-    assert!(path.ends_with(".kargo"));
+    assert_eq!(path, kargo_util::dirs::KargoDirs::from_env().data);
+    assert_ne!(path, std::path::Path::new(".kargo"));
 }
 
 #[test]
@@ -63,3 +64,40 @@ jdk = "/usr/lib/jvm/java-21"
         Some("/usr/lib/jvm/java-21")
     );
 }
+
+#[test]
+fn test_global_config_dependency_cache_mode() {
+    let config = GlobalConfig::default();
+    assert_eq!(config.cache.dependencies, DependencyCacheMode::Project);
+
+    let config: GlobalConfig = toml::from_str(
+        "[cache]\ndependencies = \"layered\"\ndependencies-dir = \"/srv/kargo-deps\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.cache.dependencies, DependencyCacheMode::Layered);
+    assert_eq!(
+        config.cache.shared_dependencies_dir(),
+        std::path::Path::new("/srv/kargo-deps")
+    );
+    assert!(toml::from_str::<GlobalConfig>("[cache]\ndependencies = \"global\"\n").is_err());
+}
+
+#[test]
+fn test_global_config_jobs_auto_and_job_memory() {
+    let config: GlobalConfig =
+        toml::from_str("[build]\njobs = \"auto\"\njob-memory = \"512MB\"\n").unwrap();
+    assert!(config.build.jobs > 0);
+    assert_eq!(config.build.job_memory.as_deref(), Some("512MB"));
+
+    let config: GlobalConfig = toml::from_str("[build]\ndefault-target = \"jvm\"\n").unwrap();
+    assert!(config.build.jobs > 0);
+
+    for (toml, expected) in [
+        ("[build]\njobs = \"many\"\n", "number or \"auto\""),
+        ("[build]\njobs = 0\n", "at least 1"),
+        ("[build]\njob-memory = \"lots\"\n", "is not a size"),
+    ] {
+        let err = toml::from_str::<GlobalConfig>(toml).unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
@@ -1,8 +1,13 @@
-use kargo_core::lockfile::{LockedDependencyRef, LockedPackage, Lockfile};
+use kargo_core::lockfile::{
+    LockedDependencyRef, LockedEnvironment, LockedFile, LockedPackage, LockedToolchain, Lockfile,
+};
 
 #[test]
 fn round_trip_serialize_deserialize() {
     let lockfile = Lockfile {
+        toolchain: None,
+        environment: None,
+        npm: vec![],
         package: vec![LockedPackage {
             name: "kotlin-stdlib".to_string(),
             group: "org.jetbrains.kotlin".to_string(),
@@ -16,7 +21,11 @@ fn round_trip_serialize_deserialize() {
                 group: "org.jetbrains".to_string(),
                 version: "24.0.0".to_string(),
             }],
+            relocated_from: None,
+            members: Vec::new(),
         }],
+        file: Vec::new(),
+        members: Vec::new(),
     };
 
     let serialized = lockfile.to_string_pretty().unwrap();
@@ -37,10 +46,122 @@ fn round_trip_serialize_deserialize() {
     );
 }
 
+#[test]
+fn file_dependencies_round_trip_and_merge_per_member() {
+    let vendor = |scope: &str| LockedFile {
+        path: "libs/vendor.jar".to_string(),
+        checksum: "abc123".to_string(),
+        scope: scope.to_string(),
+        members: Vec::new(),
+    };
+    let mut shared = Lockfile::default();
+    shared.set_member(
+        "app",
+        Lockfile {
+            file: vec![vendor("compile")],
+            ..Lockfile::default()
+        },
+    );
+    shared.set_member(
+        "cli",
+        Lockfile {
+            file: vec![vendor("test")],
+            ..Lockfile::default()
+        },
+    );
+
+    let serialized = shared.to_string_pretty().unwrap();
+    assert!(serialized.contains("[[file]]"));
+    let deserialized: Lockfile = toml::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.file, shared.file);
+    assert_eq!(deserialized.file.len(), 2);
+    assert_eq!(deserialized.member("cli").file, [vendor("test")]);
+
+    shared.set_member("cli", Lockfile::default());
+    assert_eq!(shared.member("app").file, [vendor("compile")]);
+    assert!(shared.member("cli").file.is_empty());
+}
+
+#[test]
+fn environment_and_toolchain_kept_per_member() {
+    let env = |kotlin: &str, ksp: Option<&str>| LockedEnvironment {
+        kotlin: kotlin.to_string(),
+        ksp: ksp.map(str::to_string),
+        jdk: Some("21".to_string()),
+    };
+    let app = Lockfile {
+        toolchain: Some(LockedToolchain {
+            requested: "2.3".to_string(),
+            kotlin: "2.3.0".to_string(),
+        }),
+        environment: Some(env("2.3.0", Some("2.3.0-2.0.4"))),
+        ..Lockfile::default()
+    };
+    let cli = Lockfile {
+        environment: Some(env("2.2.20", None)),
+        ..Lockfile::default()
+    };
+
+    let mut shared = Lockfile::default();
+    shared.set_member("app", app.clone());
+    shared.set_member("cli", cli.clone());
+    assert!(shared.toolchain.is_none() && shared.environment.is_none());
+
+    let deserialized: Lockfile = toml::from_str(&shared.to_string_pretty().unwrap()).unwrap();
+    for (member, view) in [("app", &app), ("cli", &cli)] {
+        let own = deserialized.member(member);
+        assert_eq!(own.toolchain, view.toolchain);
+        assert_eq!(own.environment, view.environment);
+        assert!(view
+            .environment
+            .as_ref()
+            .unwrap()
+            .drift(own.environment.as_ref().unwrap())
+            .is_empty());
+    }
+
+    // Rewriting one member keeps the other's pins.
+    shared.set_member("cli", Lockfile::default());
+    assert_eq!(shared.member("app").environment, app.environment);
+    assert!(shared.member("cli").environment.is_none());
+    assert_eq!(shared.members.len(), 1);
+}
+
 #[test]
 fn lockfile_empty_packages_serializes_deserializes() {
-    let lockfile = Lockfile { package: vec![] };
+    let lockfile = Lockfile::default();
     let serialized = lockfile.to_string_pretty().unwrap();
     let deserialized: Lockfile = toml::from_str(&serialized).unwrap();
     assert!(deserialized.package.is_empty());
 }
+
+#[test]
+fn environment_round_trip_and_drift() {
+    let recorded = LockedEnvironment {
+        kotlin: "2.2.20".to_string(),
+        ksp: Some("2.2.20-2.0.3".to_string()),
+        jdk: Some("17".to_string()),
+    };
+    let lockfile = Lockfile {
+        environment: Some(recorded.clone()),
+        ..Default::default()
+    };
+    let parsed: Lockfile = toml::from_str(&lockfile.to_string_pretty().unwrap()).unwrap();
+    assert_eq!(parsed.environment.as_ref(), Some(&recorded));
+
+    assert!(recorded.drift(&recorded).is_empty());
+
+    let current = LockedEnvironment {
+        kotlin: "2.3.0".to_string(),
+        ksp: None,
+        jdk: Some("21".to_string()),
+    };
+    assert_eq!(
+        recorded.drift(&current),
+        vec![
+            "Kotlin 2.3.0 (locked with 2.2.20)",
+            "no KSP (locked with 2.2.20-2.0.3)",
+            "JDK 21 (locked with 17)",
+        ]
+    );
+}
@@ -206,3 +206,896 @@ compile-sdk = 35
     assert_eq!(android.target_sdk, Some(35));
     assert_eq!(android.compile_sdk, Some(35));
 }
+
+#[test]
+fn test_kotlin_req_parses_aliases() {
+    use kargo_core::manifest::KotlinVersionReq;
+
+    assert_eq!(
+        KotlinVersionReq::parse("2.3.0"),
+        Some(KotlinVersionReq::Exact("2.3.0".to_string()))
+    );
+    assert_eq!(
+        KotlinVersionReq::parse("2.2.20-RC"),
+        Some(KotlinVersionReq::Exact("2.2.20-RC".to_string()))
+    );
+    assert_eq!(
+        KotlinVersionReq::parse("2.3"),
+        Some(KotlinVersionReq::Minor { major: 2, minor: 3 })
+    );
+    assert_eq!(
+        KotlinVersionReq::parse("latest-stable"),
+        Some(KotlinVersionReq::LatestStable)
+    );
+    assert_eq!(
+        KotlinVersionReq::parse("stable"),
+        Some(KotlinVersionReq::LatestStable)
+    );
+    assert_eq!(
+        KotlinVersionReq::parse("latest"),
+        Some(KotlinVersionReq::Latest)
+    );
+    assert_eq!(KotlinVersionReq::parse("2"), None);
+    assert_eq!(KotlinVersionReq::parse("2.x"), None);
+    assert_eq!(KotlinVersionReq::parse("newest"), None);
+}
+
+#[test]
+fn test_kotlin_req_select() {
+    use kargo_core::manifest::KotlinVersionReq;
+
+    let available = ["2.2.0", "2.2.21", "2.3.0", "2.3.10", "2.4.0-Beta1"];
+
+    let minor = KotlinVersionReq::parse("2.2").unwrap();
+    assert_eq!(minor.select(available), Some("2.2.21".to_string()));
+
+    let stable = KotlinVersionReq::parse("latest-stable").unwrap();
+    assert_eq!(stable.select(available), Some("2.3.10".to_string()));
+
+    let latest = KotlinVersionReq::parse("latest").unwrap();
+    assert_eq!(latest.select(available), Some("2.4.0-Beta1".to_string()));
+
+    let missing = KotlinVersionReq::parse("1.9").unwrap();
+    assert_eq!(missing.select(available), None);
+}
+
+#[test]
+fn test_validate_accepts_kotlin_aliases() {
+    for kotlin in ["2.3", "latest-stable", "latest"] {
+        let toml =
+            format!("[package]\nname = \"test\"\nversion = \"0.1.0\"\nkotlin = \"{kotlin}\"\n");
+        let manifest = Manifest::parse_toml(&toml).unwrap();
+        assert!(
+            manifest.validate().is_ok(),
+            "kotlin = {kotlin:?} should be valid"
+        );
+    }
+
+    let bad = Manifest::parse_toml(
+        "[package]\nname = \"test\"\nversion = \"0.1.0\"\nkotlin = \"newest\"\n",
+    )
+    .unwrap();
+    assert!(bad.validate().is_err());
+}
+
+#[test]
+fn test_target_selectors_match_declared_targets() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "test"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[targets.jvm]
+[targets.ios-arm64]
+[targets.macos-arm64]
+[targets.linux-x64]
+
+[target.apple.dependencies]
+ktor-darwin = "io.ktor:ktor-client-darwin:3.0.0"
+
+[target.'cfg(not(native))'.dependencies]
+ktor-okhttp = "io.ktor:ktor-client-okhttp:3.0.0"
+"#,
+    )
+    .unwrap();
+    assert!(manifest.validate().is_ok());
+
+    let names = |key: &str| -> Vec<&'static str> {
+        manifest
+            .targets_matching(key)
+            .iter()
+            .map(|t| t.kebab_name())
+            .collect()
+    };
+    assert_eq!(names("apple"), vec!["ios-arm64", "macos-arm64"]);
+    assert_eq!(names("cfg(not(native))"), vec!["jvm"]);
+    assert!(names("mingw").is_empty());
+}
+
+#[test]
+fn test_validate_rejects_unknown_target_selector() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "test"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[target.desktop.dependencies]
+foo = "com.example:foo:1.0"
+"#,
+    )
+    .unwrap();
+    let err = manifest.validate().unwrap_err();
+    assert!(format!("{err:?}").contains("desktop"));
+}
+
+#[test]
+fn test_parse_integration_test_dependencies() {
+    let toml = r#"
+[package]
+name = "test"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dev-dependencies]
+kotlin-test = "org.jetbrains.kotlin:kotlin-test:2.3.0"
+
+[integration-test-dependencies]
+testcontainers = "org.testcontainers:testcontainers:1.20.4"
+"#;
+    let manifest = Manifest::parse_toml(toml).unwrap();
+    assert_eq!(manifest.integration_test_dependencies.len(), 1);
+    assert!(manifest.validate().is_ok());
+
+    let duplicate = toml.replace("testcontainers = ", "kotlin-test = ");
+    let err = Manifest::parse_toml(&duplicate)
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(err.to_string().contains("integration-test-dependencies"));
+}
+
+#[test]
+fn test_validate_module_dependency_sections() {
+    let parse = |section: &str, dep: &str| {
+        Manifest::parse_toml(&format!(
+            "[package]\nname = \"test\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n\
+             [{section}]\nlib = {dep}\n"
+        ))
+        .unwrap()
+        .validate()
+    };
+
+    assert!(parse("dependencies", "{ module = \"lib\" }").is_ok());
+    let err = parse("dependencies", "{ module = \"lib\", fixtures = true }").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("module dependency 'lib' in [dependencies]: fixtures"));
+    let err = parse("target.jvm.dependencies", "{ module = \"lib\" }").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("module dependency 'lib' in [target.jvm.dependencies]"));
+}
+
+#[test]
+fn test_validate_include_optional_entries() {
+    let toml = r#"
+[package]
+name = "test"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+reports = { group = "com.example", artifact = "reports", version = "2.1.0", include-optional = ["org.apache.poi:poi-ooxml"] }
+"#;
+    let manifest = Manifest::parse_toml(toml).unwrap();
+    assert!(manifest.validate().is_ok());
+
+    let invalid = toml.replace("org.apache.poi:poi-ooxml", "org.apache.poi:poi-ooxml:5.2.5");
+    let err = Manifest::parse_toml(&invalid)
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(err.to_string().contains("include-optional entry"));
+}
+
+#[test]
+fn validate_jar_manifest_attributes() {
+    let toml = r#"
+[package]
+name = "app"
+version = "1.0.0"
+kotlin = "2.3.0"
+
+[package.jar]
+manifest-attributes = { "Add-Opens" = "java.base/java.lang" }
+"#;
+    let manifest = Manifest::parse_toml(toml).unwrap();
+    assert!(manifest.validate().is_ok());
+
+    let reserved = toml.replace("Add-Opens", "main-class");
+    let err = Manifest::parse_toml(&reserved)
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(err.to_string().contains("use package.main-class"));
+
+    let invalid = toml.replace("Add-Opens", "Add Opens");
+    let err = Manifest::parse_toml(&invalid)
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("not a valid JAR manifest header name"));
+}
+
+#[test]
+fn network_limits_are_parsed_and_validated() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[network]
+max-concurrency = 16
+request-delay-ms = 50
+
+[network.hosts."nexus.corp"]
+max-concurrency = 2
+"#,
+    )
+    .unwrap();
+    let network = manifest.network.unwrap();
+    assert_eq!(network.max_concurrency, Some(16));
+    assert_eq!(network.request_delay_ms, Some(50));
+    assert_eq!(network.hosts["nexus.corp"].max_concurrency, Some(2));
+    assert_eq!(network.auto_tune, None);
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[network.hosts."nexus.corp"]
+max-concurrency = 0
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("max-concurrency must be between 1 and 64"));
+}
+
+#[test]
+fn generators_are_parsed_and_validated() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[generate.schema]
+command = ["./gen-schema.sh", "{out}"]
+inputs = ["schema/**/*.json"]
+outputs = ["Schema.kt"]
+
+[generate.petstore]
+generator = "openapi"
+spec = "api/petstore.yaml"
+package = "com.example.petstore"
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    assert_eq!(manifest.generate["schema"].command[1], "{out}");
+    assert_eq!(manifest.generate["schema"].outputs, ["Schema.kt"]);
+    assert_eq!(
+        manifest.generate["petstore"].generator.as_deref(),
+        Some("openapi")
+    );
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[generate.petstore]
+generator = "openapi"
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err.to_string().contains("requires `spec`"));
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[generate.empty]
+inputs = ["a.txt"]
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("needs either `command` or `generator`"));
+}
+
+#[test]
+fn tasks_are_parsed_and_validated() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[tasks.image]
+steps = ["build --release", "docker build -t app ."]
+env = { DOCKER_BUILDKIT = "1" }
+
+[tasks.deploy]
+description = "Build and ship the image"
+depends-on = ["image"]
+steps = ["./deploy.sh"]
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    assert_eq!(manifest.tasks["image"].steps.len(), 2);
+    assert_eq!(manifest.tasks["image"].env["DOCKER_BUILDKIT"], "1");
+    assert_eq!(manifest.tasks["deploy"].depends_on, ["image"]);
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[tasks.deploy]
+depends-on = ["image"]
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err.to_string().contains("depends on unknown task 'image'"));
+}
+
+#[test]
+fn protobuf_settings_are_parsed() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[protobuf]
+version = "4.28.2"
+grpc = true
+source-dirs = ["proto"]
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    let protobuf = manifest.protobuf.unwrap();
+    assert_eq!(protobuf.version.as_deref(), Some("4.28.2"));
+    assert!(protobuf.grpc);
+    assert_eq!(protobuf.kotlin, None);
+    assert_eq!(protobuf.source_dirs, ["proto"]);
+}
+
+#[test]
+fn sqldelight_databases_need_a_compiler() {
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[sqldelight]
+compiler = "com.example:sqldelight-cli:1.0.0"
+main-class = "com.example.sqldelight.MainKt"
+
+[sqldelight.databases.AppDatabase]
+package = "com.example.db"
+dialect = "app.cash.sqldelight:sqlite-3-38-dialect:2.0.2"
+verify-migrations = true
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    let db = &manifest.sqldelight.unwrap().databases["AppDatabase"];
+    assert_eq!(db.package, "com.example.db");
+    assert!(db.verify_migrations);
+    assert!(db.source_dirs.is_empty());
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[sqldelight.databases.AppDatabase]
+package = "com.example.db"
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("require `compiler` and `main-class`"));
+}
+
+#[test]
+fn update_policy_picks_the_most_specific_pattern() {
+    use kargo_core::manifest::UpdatePolicy;
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[update-policy]
+"org.jetbrains.kotlinx:*" = "minor"
+"org.jetbrains.kotlinx:kotlinx-coroutines-*" = "patch"
+"junit:*" = "none"
+"*:*-bom" = "major"
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    let policy = |g: &str, a: &str| manifest.update_policy_for(g, a);
+    assert_eq!(
+        policy("org.jetbrains.kotlinx", "kotlinx-serialization-json"),
+        Some(UpdatePolicy::Minor)
+    );
+    assert_eq!(
+        policy("org.jetbrains.kotlinx", "kotlinx-coroutines-core"),
+        Some(UpdatePolicy::Patch)
+    );
+    assert_eq!(policy("junit", "junit"), Some(UpdatePolicy::None));
+    assert_eq!(policy("io.ktor", "ktor-bom"), Some(UpdatePolicy::Major));
+    assert_eq!(policy("io.ktor", "ktor-client-core"), None);
+
+    let err = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[update-policy]
+"junit" = "none"
+"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(err.to_string().contains("must be \"group:artifact\""));
+}
+
+#[test]
+fn budgets_parse_durations_and_sizes() {
+    use std::time::Duration;
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[budgets]
+build-time = "1.5m"
+jar-size = "25MB"
+
+[budgets.phases]
+compile = "500ms"
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    let budgets = manifest.budgets.unwrap();
+    assert_eq!(budgets.build_time(), Some(Duration::from_secs(90)));
+    assert_eq!(budgets.jar_size(), Some(25 * 1024 * 1024));
+    assert_eq!(
+        budgets.phase_time("compile"),
+        Some(Duration::from_millis(500))
+    );
+    assert_eq!(budgets.phase_time("package"), None);
+
+    for (section, expected) in [
+        ("[budgets]\nbuild-time = \"soon\"", "is not a duration"),
+        ("[budgets]\njar-size = \"big\"", "is not a size"),
+        ("[budgets.phases]\nlink = \"5s\"", "unknown phase 'link'"),
+    ] {
+        let toml = format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n{section}\n"
+        );
+        let err = Manifest::parse_toml(&toml).unwrap().validate().unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn build_config_resolves_typed_and_nested_values() {
+    use kargo_core::build_config::{self, BuildConfigField};
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[build-config]
+API_URL = "https://api.example.com"
+RETRIES = 3
+BIG = 5000000000
+LOGGING = true
+LOCALES = ["en", "de"]
+MAX_UPLOAD = { type = "long", value = 10 }
+BUILT_AT = "${build:timestamp}"
+
+[build-config.FEATURES]
+SEARCH = true
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    assert!(build_config::uses_timestamp(&manifest.build_config));
+
+    let fields = build_config::resolve_all(&manifest.build_config, 42, None).unwrap();
+    assert_eq!(fields["API_URL"], "https://api.example.com".into());
+    assert_eq!(fields["RETRIES"], BuildConfigField::Int(3));
+    assert_eq!(fields["BIG"], BuildConfigField::Long(5_000_000_000));
+    assert_eq!(fields["LOGGING"], BuildConfigField::Boolean(true));
+    assert_eq!(
+        fields["LOCALES"],
+        BuildConfigField::StringList(vec!["en".into(), "de".into()])
+    );
+    assert_eq!(fields["MAX_UPLOAD"], BuildConfigField::Long(10));
+    assert_eq!(fields["BUILT_AT"], BuildConfigField::Long(42));
+    let BuildConfigField::Object(features) = &fields["FEATURES"] else {
+        panic!("FEATURES should be a nested object");
+    };
+    assert_eq!(features["SEARCH"], BuildConfigField::Boolean(true));
+
+    for (section, expected) in [
+        (
+            "\"api-url\" = \"x\"",
+            "'api-url' is not a valid Kotlin identifier",
+        ),
+        (
+            "N = { type = \"int\", value = \"3\" }",
+            "does not match type 'int'",
+        ),
+        (
+            "N = { type = \"double\", value = 1 }",
+            "unknown type 'double'",
+        ),
+        ("[build-config.A]\n\"b c\" = 1", "'A.b c' is not a valid"),
+    ] {
+        let section = if section.starts_with('[') {
+            section.to_string()
+        } else {
+            format!("[build-config]\n{section}")
+        };
+        let toml = format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n{section}\n"
+        );
+        let err = Manifest::parse_toml(&toml).unwrap().validate().unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn build_config_merges_nested_objects() {
+    use kargo_core::build_config::{merge, BuildConfigValue};
+    use std::collections::BTreeMap;
+
+    let mut base = BTreeMap::from([
+        ("URL".to_string(), BuildConfigValue::from("prod")),
+        (
+            "FEATURES".to_string(),
+            BuildConfigValue::Object(BTreeMap::from([
+                ("A".to_string(), BuildConfigValue::Bool(true)),
+                ("B".to_string(), BuildConfigValue::Bool(true)),
+            ])),
+        ),
+    ]);
+    let overlay = BTreeMap::from([
+        ("URL".to_string(), BuildConfigValue::from("staging")),
+        (
+            "FEATURES".to_string(),
+            BuildConfigValue::Object(BTreeMap::from([(
+                "B".to_string(),
+                BuildConfigValue::Bool(false),
+            )])),
+        ),
+    ]);
+    merge(&mut base, &overlay);
+    assert_eq!(base["URL"], "staging".into());
+    let BuildConfigValue::Object(features) = &base["FEATURES"] else {
+        panic!("FEATURES should stay an object");
+    };
+    assert_eq!(features["A"], BuildConfigValue::Bool(true));
+    assert_eq!(features["B"], BuildConfigValue::Bool(false));
+}
+
+#[test]
+fn build_config_from_env_and_secret_lint() {
+    use kargo_core::build_config::{self, BuildConfigField};
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[build-config]
+API_URL = "https://api.example.com"
+API_KEY = { from-env = true }
+REGION = { from-env = "APP_REGION", default = "eu" }
+MAPS_KEY = "AIzaSyD3x4mpl3K3yV4lu3F0rT35t1ng00000"
+CLIENT_SECRET = "s3cr3t-value"
+GREETING = "hello"
+BUILD_ID = "7f3a9c2e81d64b0fa5e2c91d7b48e6f3"
+
+[build-config.SENTRY]
+DSN_TOKEN = "abcdef123456"
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+
+    let fields = build_config::resolve_all(&manifest.build_config, 0, None).unwrap();
+    assert!(build_config::uses_env(&fields));
+    assert_eq!(
+        fields["API_KEY"],
+        BuildConfigField::FromEnv {
+            var: "API_KEY".into(),
+            default: None
+        }
+    );
+    assert_eq!(
+        fields["REGION"],
+        BuildConfigField::FromEnv {
+            var: "APP_REGION".into(),
+            default: Some("eu".into())
+        }
+    );
+    assert_eq!(
+        build_config::likely_secrets(&fields),
+        ["BUILD_ID", "CLIENT_SECRET", "MAPS_KEY", "SENTRY.DSN_TOKEN"]
+    );
+
+    for (entry, expected) in [
+        (
+            "K = { from-env = false }",
+            "from-env must be true or a variable name",
+        ),
+        (
+            "K = { from-env = \"MY-VAR\" }",
+            "not a valid environment variable name",
+        ),
+    ] {
+        let toml = format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n[build-config]\n{entry}\n"
+        );
+        let err = Manifest::parse_toml(&toml).unwrap().validate().unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn repository_group_filters() {
+    use kargo_core::manifest::{group_matches, RepositoryEntry};
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[repositories.internal]
+url = "https://nexus.company.com/maven"
+include-groups = ["com.mycorp.*"]
+exclude-groups = ["com.mycorp.oss"]
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+    let RepositoryEntry::Detailed {
+        include_groups,
+        exclude_groups,
+        ..
+    } = &manifest.repositories["internal"]
+    else {
+        panic!("expected a detailed repository entry");
+    };
+    assert_eq!(include_groups, &["com.mycorp.*"]);
+    assert_eq!(exclude_groups, &["com.mycorp.oss"]);
+
+    assert!(group_matches("com.mycorp.*", "com.mycorp"));
+    assert!(group_matches("com.mycorp.*", "com.mycorp.billing.api"));
+    assert!(!group_matches("com.mycorp.*", "com.mycorpx"));
+    assert!(group_matches("com.*.internal", "com.mycorp.internal"));
+    assert!(group_matches("com.mycorp", "com.mycorp"));
+    assert!(!group_matches("com.mycorp", "com.mycorp.billing"));
+
+    let err = Manifest::parse_toml(
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n\
+         [repositories.internal]\nurl = \"https://nexus\"\ninclude-groups = [\"com/mycorp\"]\n",
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid group pattern 'com/mycorp'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn network_tls_settings_layer_over_global_defaults() {
+    use kargo_util::http::{HttpSettings, IpVersion, TlsBackend, TlsVersion};
+    use std::path::{Path, PathBuf};
+
+    let manifest = Manifest::parse_toml(
+        r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[network]
+ca-bundle = "certs/corp-ca.pem"
+min-tls-version = "1.3"
+ip-version = "ipv4"
+"#,
+    )
+    .unwrap();
+    manifest.validate().unwrap();
+
+    let global = HttpSettings {
+        tls_backend: TlsBackend::Native,
+        client_cert: Some(PathBuf::from("/etc/kargo/client.pem")),
+        ..HttpSettings::new()
+    };
+    let settings = manifest
+        .network
+        .unwrap()
+        .http_settings(Path::new("/work/app"), global);
+    assert_eq!(settings.tls_backend, TlsBackend::Native);
+    assert_eq!(settings.min_tls_version, Some(TlsVersion::Tls13));
+    assert_eq!(settings.ip_version, IpVersion::Ipv4);
+    assert_eq!(
+        settings.ca_bundle,
+        Some(Path::new("/work/app").join("certs/corp-ca.pem"))
+    );
+    assert_eq!(
+        settings.client_cert,
+        Some(PathBuf::from("/etc/kargo/client.pem"))
+    );
+    assert!(settings.tls_built_in_roots);
+
+    for (section, expected) in [
+        (
+            "client-key = \"key.pem\"",
+            "client-key requires client-cert",
+        ),
+        ("tls-built-in-roots = false", "requires a ca-bundle"),
+        ("tls-backend = \"openssl\"", "unknown variant `openssl`"),
+    ] {
+        let toml = format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n[network]\n{section}\n"
+        );
+        let err = Manifest::parse_toml(&toml)
+            .and_then(|m| m.validate())
+            .unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn apple_framework_name_and_deployment_targets() {
+    let manifest = Manifest::parse_toml(MINIMAL_TOML).unwrap();
+    assert_eq!(manifest.framework_name(), "TestApp");
+    assert_eq!(manifest.deployment_target("ios").as_deref(), Some("13.0"));
+
+    let manifest = Manifest::parse_toml(&format!(
+        "{MINIMAL_TOML}\n[apple]\nframework-name = \"Shared\"\n\
+         deployment-target = {{ ios = \"15.0\" }}\n"
+    ))
+    .unwrap();
+    manifest.validate().unwrap();
+    assert_eq!(manifest.framework_name(), "Shared");
+    assert_eq!(manifest.deployment_target("ios").as_deref(), Some("15.0"));
+    assert_eq!(manifest.deployment_target("macos").as_deref(), Some("11.0"));
+    assert_eq!(manifest.deployment_target("android"), None);
+
+    for bad in [
+        "framework-name = \"my-lib\"",
+        "deployment-target = { visionos = \"1.0\" }",
+        "deployment-target = { ios = \"latest\" }",
+    ] {
+        let manifest = Manifest::parse_toml(&format!("{MINIMAL_TOML}\n[apple]\n{bad}\n")).unwrap();
+        assert!(manifest.validate().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn android_test_settings() {
+    let manifest = Manifest::parse_toml(MINIMAL_TOML).unwrap();
+    assert_eq!(manifest.android_test_application_id(), "test_app.test");
+
+    let manifest = Manifest::parse_toml(&format!(
+        "{MINIMAL_TOML}\n[test.android]\napplication-id = \"com.example.app.test\"\n\
+         instrumentation-args = {{ size = \"small\" }}\n"
+    ))
+    .unwrap();
+    manifest.validate().unwrap();
+    assert_eq!(
+        manifest.android_test_application_id(),
+        "com.example.app.test"
+    );
+    let android = manifest.test.unwrap().android.unwrap();
+    assert_eq!(android.runner, "androidx.test.runner.AndroidJUnitRunner");
+    assert!(android.orchestrator && !android.clear_package_data);
+    assert_eq!(android.instrumentation_args["size"], "small");
+
+    let manifest = Manifest::parse_toml(&format!(
+        "{MINIMAL_TOML}\n[test.android]\napplication-id = \"tests\"\n"
+    ))
+    .unwrap();
+    assert!(manifest.validate().is_err());
+}
+
+#[test]
+fn file_dependencies_are_jars_in_project_sections() {
+    let manifest = Manifest::parse_toml(&format!(
+        "{MINIMAL_TOML}\n[dependencies]\nvendor = {{ file = \"libs/vendor.jar\", scope = \"runtime\" }}\n"
+    ))
+    .unwrap();
+    manifest.validate().unwrap();
+
+    for bad in [
+        "[dependencies]\nvendor = { file = \"libs/vendor.aar\" }",
+        "[ksp]\nvendor = { file = \"libs/vendor.jar\" }",
+        "[target.jvm.dependencies]\nvendor = { file = \"libs/vendor.jar\" }",
+    ] {
+        let manifest = Manifest::parse_toml(&format!("{MINIMAL_TOML}\n{bad}\n")).unwrap();
+        assert!(manifest.validate().is_err(), "{bad}");
+    }
+}
@@ -15,3 +15,21 @@ fn profile_release() {
     assert_eq!(p.optimization, Some(true));
     assert!(p.compiler_args.is_empty());
 }
+
+#[test]
+fn profile_shrink_enabled_by_default() {
+    let p: Profile = toml::from_str(
+        r#"
+[shrink]
+rules = ["proguard-rules.pro"]
+"#,
+    )
+    .unwrap();
+    let shrink = p.shrink().unwrap();
+    assert_eq!(shrink.rules, ["proguard-rules.pro"]);
+    assert_eq!(shrink.r8_version, None);
+
+    let disabled: Profile = toml::from_str("[shrink]\nenabled = false\n").unwrap();
+    assert!(disabled.shrink().is_none());
+    assert!(Profile::release().shrink().is_none());
+}
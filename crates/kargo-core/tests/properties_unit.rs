@@ -1,4 +1,6 @@
-use kargo_core::properties::{interpolate, load_env_file};
+use kargo_core::properties::{
+    interpolate, load_env_file, local_sdk_dir, parse_properties, GradleImport,
+};
 use std::collections::BTreeMap;
 use std::io::Write;
 use tempfile::NamedTempFile;
@@ -67,3 +69,55 @@ fn interpolate_multiple_refs() {
     let result = interpolate("u=${env:USER} p=${env:PASS}", &env);
     assert_eq!(result, "u=deploy p=s3cret");
 }
+
+#[test]
+fn parse_properties_handles_java_syntax() {
+    let props = parse_properties(
+        "# comment\n\
+         ! also a comment\n\
+         sdk.dir=C\\:\\\\Users\\\\me\\\\Android\n\
+         key.colon: value\n\
+         key.space   spaced value\n\
+         multi = one, \\\n    two\n\
+         unicode=caf\\u00e9\n\
+         empty=\n",
+    );
+    assert_eq!(props["sdk.dir"], "C:\\Users\\me\\Android");
+    assert_eq!(props["key.colon"], "value");
+    assert_eq!(props["key.space"], "spaced value");
+    assert_eq!(props["multi"], "one, two");
+    assert_eq!(props["unicode"], "café");
+    assert_eq!(props["empty"], "");
+    assert_eq!(props.len(), 6);
+}
+
+#[test]
+fn local_sdk_dir_reads_local_properties() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(local_sdk_dir(dir.path()), None);
+
+    std::fs::write(
+        dir.path().join("local.properties"),
+        "sdk.dir=/opt/android-sdk\n",
+    )
+    .unwrap();
+    assert_eq!(
+        local_sdk_dir(dir.path()),
+        Some(std::path::PathBuf::from("/opt/android-sdk"))
+    );
+}
+
+#[test]
+fn gradle_import_maps_known_keys() {
+    let props = parse_properties(
+        "org.gradle.jvmargs=-Xmx4g -XX:+UseParallelGC\n\
+         kotlin.code.style=official\n\
+         org.gradle.parallel=true\n",
+    );
+    let import = GradleImport::from_properties(&props);
+    assert_eq!(import.jvm_args, ["-Xmx4g", "-XX:+UseParallelGC"]);
+    assert_eq!(import.code_style.as_deref(), Some("official"));
+    assert_eq!(import.unmapped, ["org.gradle.parallel"]);
+    assert!(!import.is_empty());
+    assert!(GradleImport::from_properties(&BTreeMap::new()).is_empty());
+}
@@ -1,4 +1,4 @@
-use kargo_core::target::KotlinTarget;
+use kargo_core::target::{KotlinTarget, TargetSelector};
 
 #[test]
 fn from_str_jvm() {
@@ -94,3 +94,55 @@ fn compiler_name_native() {
     assert_eq!(KotlinTarget::IosArm64.compiler_name(), "kotlinc-native");
     assert_eq!(KotlinTarget::LinuxX64.compiler_name(), "kotlinc-native");
 }
+
+#[test]
+fn selector_target_and_group() {
+    let ios = TargetSelector::parse("ios-arm64").unwrap();
+    assert!(ios.matches(KotlinTarget::IosArm64));
+    assert!(!ios.matches(KotlinTarget::IosX64));
+
+    let apple = TargetSelector::parse("apple").unwrap();
+    assert!(apple.matches(KotlinTarget::IosSimulatorArm64));
+    assert!(apple.matches(KotlinTarget::MacosX64));
+    assert!(apple.matches(KotlinTarget::WatchosArm64));
+    assert!(!apple.matches(KotlinTarget::LinuxX64));
+    assert!(!apple.matches(KotlinTarget::Jvm));
+
+    let common = TargetSelector::parse("common").unwrap();
+    assert!(KotlinTarget::ALL.iter().all(|t| common.matches(*t)));
+
+    let android_native = TargetSelector::parse("androidNative").unwrap();
+    assert!(android_native.matches(KotlinTarget::AndroidNativeX64));
+    assert!(!android_native.matches(KotlinTarget::Android));
+}
+
+#[test]
+fn selector_cfg_expressions() {
+    let jvmish = TargetSelector::parse("cfg(any(jvm, android))").unwrap();
+    assert!(jvmish.matches(KotlinTarget::Jvm));
+    assert!(jvmish.matches(KotlinTarget::Android));
+    assert!(!jvmish.matches(KotlinTarget::Js));
+
+    let posix = TargetSelector::parse("cfg(all(native, not(mingw)))").unwrap();
+    assert!(posix.matches(KotlinTarget::LinuxArm64));
+    assert!(posix.matches(KotlinTarget::IosArm64));
+    assert!(!posix.matches(KotlinTarget::MingwX64));
+    assert!(!posix.matches(KotlinTarget::Jvm));
+
+    let nbsp = TargetSelector::parse("cfg(any(jvm,\u{a0}js))").unwrap();
+    assert!(nbsp.matches(KotlinTarget::Js));
+
+    let single = TargetSelector::parse("cfg(wasm)").unwrap();
+    assert!(single.matches(KotlinTarget::WasmWasi));
+}
+
+#[test]
+fn selector_rejects_invalid() {
+    assert_eq!(TargetSelector::parse("desktop"), None);
+    assert_eq!(TargetSelector::parse("cfg(any(jvm, desktop))"), None);
+    assert_eq!(TargetSelector::parse("cfg(not(jvm, js))"), None);
+    assert_eq!(TargetSelector::parse("cfg(any(jvm)"), None);
+    assert_eq!(TargetSelector::parse("cfg(jvm) js"), None);
+    assert_eq!(TargetSelector::parse("cfg(any(jvm\u{a0}js))"), None);
+    assert_eq!(TargetSelector::parse("cfg(\u{a0}\u{3000})"), None);
+}
@@ -174,7 +174,10 @@ fn test_registry_loads_all_builtin_templates() {
     assert!(names.contains(&"kmp"));
     assert!(names.contains(&"cmp"));
     assert!(names.contains(&"android"));
-    assert_eq!(names.len(), 5);
+    assert!(names.contains(&"compose-desktop"));
+    assert!(names.contains(&"ktor"));
+    assert!(names.contains(&"kmp-lib"));
+    assert_eq!(names.len(), 8);
 }
 
 #[test]
@@ -197,7 +200,7 @@ fn test_registry_get_unknown_returns_none() {
 fn test_registry_list_returns_descriptions() {
     let registry = TemplateRegistry::new().unwrap();
     let list = registry.list();
-    assert_eq!(list.len(), 5);
+    assert_eq!(list.len(), 8);
     for (name, desc) in &list {
         assert!(!name.is_empty());
         assert!(!desc.is_empty());
@@ -236,3 +239,53 @@ fn test_cmp_template_differs_from_kmp() {
     assert!(!kmp_dirs.contains(&"src/androidMain/kotlin"));
     assert!(!kmp_dirs.contains(&"src/desktopMain/kotlin"));
 }
+
+#[test]
+fn test_every_builtin_template_ships_a_test() {
+    let registry = TemplateRegistry::new().unwrap();
+    for name in ["jvm", "android", "compose-desktop", "ktor", "kmp-lib"] {
+        let tmpl = registry.get(name).unwrap();
+        assert!(
+            tmpl.files
+                .iter()
+                .any(|f| f.path.contains("Test/") || f.path.starts_with("src/test/")),
+            "template '{name}' should include a sample test"
+        );
+    }
+}
+
+#[test]
+fn test_android_template_compiles_without_generated_r() {
+    let registry = TemplateRegistry::new().unwrap();
+    let tmpl = registry.get("android").unwrap();
+    let activity = tmpl
+        .files
+        .iter()
+        .find(|f| f.path == "src/main/kotlin/MainActivity.kt")
+        .unwrap();
+    assert!(activity.content.contains("package com.example\n"));
+    assert!(!activity.content.contains("R."));
+
+    let manifest = tmpl
+        .files
+        .iter()
+        .find(|f| f.path == "src/main/AndroidManifest.xml")
+        .unwrap();
+    assert!(manifest
+        .content
+        .contains("android:name=\"com.example.MainActivity\""));
+}
+
+#[test]
+fn test_kmp_lib_template_declares_jvm_and_js() {
+    let registry = TemplateRegistry::new().unwrap();
+    let tmpl = registry.get("kmp-lib").unwrap();
+    assert!(tmpl.manifest.content.contains("jvm = "));
+    assert!(tmpl.manifest.content.contains("js = {}"));
+    assert!(!tmpl.manifest.content.contains("main-class"));
+
+    let paths: Vec<&str> = tmpl.files.iter().map(|f| f.path.as_str()).collect();
+    assert!(paths.contains(&"src/commonMain/kotlin/Greeting.kt"));
+    assert!(paths.contains(&"src/jvmMain/kotlin/Platform.jvm.kt"));
+    assert!(paths.contains(&"src/jsMain/kotlin/Platform.js.kt"));
+}
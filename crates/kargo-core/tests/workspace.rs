@@ -1,6 +1,7 @@
+use kargo_core::lockfile::{Lockfile, ResolvedPackageInfo};
 use kargo_core::manifest::Manifest;
 use kargo_core::package::Package;
-use kargo_core::workspace::Workspace;
+use kargo_core::workspace::{self, Workspace};
 use std::path::PathBuf;
 
 fn dummy_package(root: PathBuf) -> Package {
@@ -60,3 +61,230 @@ fn test_workspace_no_members_is_virtual() {
     };
     assert!(ws.is_virtual());
 }
+
+#[test]
+fn test_find_workspace_member_by_name_or_path() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"app\", \"libs/*\"]\nexclude = [\"libs/old\"]\n",
+    )
+    .unwrap();
+    for (dir, name) in [
+        ("app", "app"),
+        ("libs/testing", "test-support"),
+        ("libs/old", "old"),
+    ] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(
+            root.join(dir).join("Kargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n"),
+        )
+        .unwrap();
+    }
+
+    let (found_root, config) = workspace::find_root(&root.join("app")).unwrap();
+    assert_eq!(found_root, root);
+    assert_eq!(
+        workspace::member_dirs(root, &config),
+        vec![root.join("app"), root.join("libs/testing")]
+    );
+    assert_eq!(
+        workspace::find_member(root, &config, "test-support"),
+        Some(root.join("libs/testing"))
+    );
+    assert_eq!(
+        workspace::find_member(root, &config, "libs/testing"),
+        Some(root.join("libs/testing"))
+    );
+    assert_eq!(workspace::find_member(root, &config, "old"), None);
+}
+
+#[test]
+fn test_members_share_the_lockfile_at_the_workspace_root() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"libs/*\"]\nexclude = [\"libs/old\"]\n",
+    )
+    .unwrap();
+    for dir in ["libs/core", "libs/net", "libs/old"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(
+            root.join(dir).join("Kargo.toml"),
+            "[package]\nname = \"m\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+        )
+        .unwrap();
+    }
+
+    assert!(workspace::is_virtual_root(root));
+    assert_eq!(workspace::membership(root), None);
+    assert_eq!(
+        workspace::membership(&root.join("libs/core")),
+        Some((root.to_path_buf(), "libs/core".to_string()))
+    );
+    assert_eq!(
+        workspace::lockfile_path(&root.join("libs/net")),
+        root.join("Kargo.lock")
+    );
+    // Excluded directories keep a lockfile of their own.
+    assert_eq!(
+        workspace::lockfile_path(&root.join("libs/old")),
+        root.join("libs/old/Kargo.lock")
+    );
+
+    let locked = |artifact: &str| {
+        Lockfile::generate(vec![ResolvedPackageInfo {
+            group: "org.example".into(),
+            artifact: artifact.into(),
+            version: "1.0".into(),
+            scope: Some("compile".into()),
+            source: None,
+            checksum: None,
+            targets: vec![],
+            dependencies: vec![],
+            relocated_from: None,
+        }])
+    };
+    let mut core = locked("shared");
+    core.package.extend(locked("core-only").package);
+    core.write_for_project(&root.join("libs/core")).unwrap();
+    locked("shared")
+        .write_for_project(&root.join("libs/net"))
+        .unwrap();
+
+    let shared = Lockfile::from_path(&root.join("Kargo.lock")).unwrap();
+    let members: Vec<(&str, Vec<String>)> = shared
+        .package
+        .iter()
+        .map(|p| (p.name.as_str(), p.members.clone()))
+        .collect();
+    assert_eq!(
+        members,
+        [
+            ("core-only", vec!["libs/core".to_string()]),
+            (
+                "shared",
+                vec!["libs/core".to_string(), "libs/net".to_string()]
+            ),
+        ]
+    );
+    let net = Lockfile::for_project(&root.join("libs/net")).unwrap();
+    assert_eq!(net.package.len(), 1);
+    assert!(net.package[0].members.is_empty());
+
+    // Re-locking a member drops the packages only it used.
+    locked("shared")
+        .write_for_project(&root.join("libs/core"))
+        .unwrap();
+    let shared = Lockfile::from_path(&root.join("Kargo.lock")).unwrap();
+    assert_eq!(shared.package.len(), 1);
+    assert!(!root.join("libs/core/Kargo.lock").exists());
+}
+
+#[test]
+fn test_members_inherit_workspace_catalog() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("Kargo.toml"),
+        r#"[workspace]
+members = ["app"]
+
+[workspace.catalog.libraries]
+okio = { group = "com.squareup.okio", artifact = "okio", version = "3.9.0" }
+coroutines = { group = "org.jetbrains.kotlinx", artifact = "kotlinx-coroutines-core", version = "1.10.1" }
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("app")).unwrap();
+    std::fs::write(
+        root.join("app/Kargo.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+okio = { catalog = "okio" }
+
+[catalog.libraries]
+coroutines = { group = "org.jetbrains.kotlinx", artifact = "kotlinx-coroutines-core", version = "1.8.0" }
+"#,
+    )
+    .unwrap();
+
+    let manifest = Manifest::from_path(&root.join("app/Kargo.toml")).unwrap();
+    let catalog = manifest.catalog.unwrap();
+    assert_eq!(catalog.libraries["okio"].version.as_deref(), Some("3.9.0"));
+    // The member's own entry wins over the workspace's.
+    assert_eq!(
+        catalog.libraries["coroutines"].version.as_deref(),
+        Some("1.8.0")
+    );
+}
+
+#[test]
+fn test_members_inherit_workspace_package_and_profiles() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("Kargo.toml"),
+        r#"[workspace]
+members = ["app"]
+
+[workspace.package]
+version = "2.4.0"
+group = "com.example"
+authors = ["Ada <ada@example.com>"]
+license = "Apache-2.0"
+
+[workspace.profile.release]
+compiler-args = ["-Xno-param-assertions"]
+
+[workspace.profile.bench]
+optimization = true
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("app")).unwrap();
+    std::fs::write(
+        root.join("app/Kargo.toml"),
+        r#"[package]
+name = "app"
+version.workspace = true
+group = { workspace = true }
+authors.workspace = true
+license = "MIT"
+kotlin = "2.3.0"
+
+[profile.bench]
+optimization = false
+"#,
+    )
+    .unwrap();
+
+    let manifest = Manifest::from_path(&root.join("app/Kargo.toml")).unwrap();
+    assert_eq!(manifest.package.version, "2.4.0");
+    assert_eq!(manifest.package.group.as_deref(), Some("com.example"));
+    assert_eq!(manifest.package.authors, ["Ada <ada@example.com>"]);
+    assert_eq!(manifest.package.license.as_deref(), Some("MIT"));
+    assert_eq!(
+        manifest.profile["release"].compiler_args,
+        ["-Xno-param-assertions"]
+    );
+    // The member's own profile wins over the workspace's.
+    assert_eq!(manifest.profile["bench"].optimization, Some(false));
+
+    std::fs::write(
+        root.join("Kargo.toml"),
+        "[workspace]\nmembers = [\"app\"]\n",
+    )
+    .unwrap();
+    let err = Manifest::from_path(&root.join("app/Kargo.toml")).unwrap_err();
+    assert!(err.to_string().contains(
+        "`package.version.workspace = true`, but [workspace.package] does not set `version`"
+    ));
+}
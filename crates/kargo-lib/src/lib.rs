@@ -0,0 +1,135 @@
+//! Embeddable Kargo: drive builds, tests and dependency resolution of a
+//! Kotlin project from Rust, in-process and without the CLI.
+//!
+//! [`Project`] wraps the operations `kargo build`, `kargo test` and
+//! `kargo metadata` run, for integration tests, IDE plugins and custom
+//! orchestration tools:
+//!
+//! ```no_run
+//! # async fn example() -> miette::Result<()> {
+//! use kargo_lib::{BuildOptions, Project};
+//!
+//! let project = Project::discover(".")?;
+//! let build = project.build(&BuildOptions::default()).await?;
+//! if build.success {
+//!     println!("{}", project.outputs(&build).classes_dir.display());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Operations report progress on stderr as the CLI does; set
+//! [`BuildOptions::quiet`] to keep builds silent.
+
+use std::path::{Path, PathBuf};
+
+use kargo_util::errors::KargoError;
+
+pub use kargo_core::lockfile::Lockfile;
+pub use kargo_core::manifest::Manifest;
+pub use kargo_ops::ops_build::{BuildOptions, BuildOutputs, BuildResult, MessageFormat};
+pub use kargo_ops::ops_test::TestOptions;
+pub use kargo_resolver::ResolutionResult;
+
+/// A Kargo project: a directory with a `Kargo.toml`.
+#[derive(Debug, Clone)]
+pub struct Project {
+    root: PathBuf,
+    manifest: Manifest,
+}
+
+impl Project {
+    /// Load the project whose `Kargo.toml` is `path`, or is in the
+    /// directory `path`.
+    pub fn load(path: impl AsRef<Path>) -> miette::Result<Self> {
+        let path = path.as_ref();
+        let (root, manifest_path) = if path.is_dir() {
+            (path.to_path_buf(), path.join("Kargo.toml"))
+        } else {
+            let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            (root, path.to_path_buf())
+        };
+        if !manifest_path.is_file() {
+            return Err(KargoError::Manifest {
+                message: format!("No Kargo.toml found at {}", manifest_path.display()),
+            }
+            .into());
+        }
+        let manifest = Manifest::from_path(&manifest_path)?;
+        Ok(Self { root, manifest })
+    }
+
+    /// Load the nearest project containing `start`, looking in `start` and
+    /// then in each of its parents.
+    pub fn discover(start: impl AsRef<Path>) -> miette::Result<Self> {
+        let start = start.as_ref();
+        let absolute = std::path::absolute(start).map_err(KargoError::Io)?;
+        match absolute
+            .ancestors()
+            .find(|dir| dir.join("Kargo.toml").is_file())
+        {
+            Some(root) => Self::load(root),
+            None => Err(KargoError::Manifest {
+                message: format!(
+                    "No Kargo.toml found in {} or any parent directory",
+                    start.display()
+                ),
+            }
+            .into()),
+        }
+    }
+
+    /// The directory holding `Kargo.toml`.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The parsed `Kargo.toml`, as of [`load`](Self::load) or the last
+    /// [`reload`](Self::reload).
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Re-read `Kargo.toml`, e.g. after editing it.
+    pub fn reload(&mut self) -> miette::Result<()> {
+        self.manifest = Manifest::from_path(&self.root.join("Kargo.toml"))?;
+        Ok(())
+    }
+
+    /// The project's `Kargo.lock`, if it has one.
+    pub fn lockfile(&self) -> miette::Result<Option<Lockfile>> {
+        if !kargo_core::workspace::lockfile_path(&self.root).is_file() {
+            return Ok(None);
+        }
+        Lockfile::for_project(&self.root).map(Some)
+    }
+
+    /// The `kargo metadata` document: the project root, manifest and
+    /// lockfile as JSON.
+    pub fn metadata(&self) -> miette::Result<serde_json::Value> {
+        let lockfile = self.lockfile()?.unwrap_or_default();
+        kargo_ops::ops_metadata::render(&self.root, &self.manifest, &lockfile)
+    }
+
+    /// Resolve the dependency graph, pinned by `Kargo.lock` if present.
+    pub async fn resolve(&self) -> miette::Result<ResolutionResult> {
+        kargo_resolver::resolve_manifest_path(&self.root.join("Kargo.toml")).await
+    }
+
+    /// Build the project, like `kargo build`. A build with compile errors
+    /// returns a result whose `success` is false.
+    pub async fn build(&self, opts: &BuildOptions) -> miette::Result<BuildResult> {
+        kargo_ops::ops_build::build(&self.root, opts).await
+    }
+
+    /// What `build` produced and where, as `kargo build --out-json` writes it.
+    pub fn outputs(&self, build: &BuildResult) -> BuildOutputs {
+        BuildOutputs::from_result(build)
+    }
+
+    /// Build and run the tests, like `kargo test`. Failing tests are an
+    /// error; JUnit reports are in `build/<target>/<profile>/test-results/`.
+    pub async fn test(&self, opts: &TestOptions) -> miette::Result<()> {
+        kargo_ops::ops_test::test(&self.root, opts).await
+    }
+}
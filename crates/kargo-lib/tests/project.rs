@@ -0,0 +1,60 @@
+use kargo_lib::Project;
+
+const MANIFEST: &str = r#"
+[package]
+name = "embedded"
+version = "0.3.0"
+kotlin = "2.3.0"
+"#;
+
+#[test]
+fn load_accepts_the_directory_or_the_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("Kargo.toml"), MANIFEST).unwrap();
+
+    let project = Project::load(tmp.path()).unwrap();
+    assert_eq!(project.root(), tmp.path());
+    assert_eq!(project.manifest().package.name, "embedded");
+    let project = Project::load(tmp.path().join("Kargo.toml")).unwrap();
+    assert_eq!(project.root(), tmp.path());
+    assert!(project.lockfile().unwrap().is_none());
+}
+
+#[test]
+fn discover_walks_up_to_the_project() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("Kargo.toml"), MANIFEST).unwrap();
+    let nested = tmp.path().join("src").join("main").join("kotlin");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    let project = Project::discover(&nested).unwrap();
+    assert_eq!(project.manifest().package.version, "0.3.0");
+    assert!(Project::load(&nested).is_err());
+}
+
+#[test]
+fn metadata_and_reload_follow_the_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("Kargo.toml"), MANIFEST).unwrap();
+    let mut project = Project::load(tmp.path()).unwrap();
+    assert_eq!(
+        project.metadata().unwrap()["manifest"]["package"]["name"],
+        "embedded"
+    );
+
+    std::fs::write(
+        tmp.path().join("Kargo.toml"),
+        MANIFEST.replace("0.3.0", "0.4.0"),
+    )
+    .unwrap();
+    project.reload().unwrap();
+    assert_eq!(project.manifest().package.version, "0.4.0");
+}
+
+#[tokio::test]
+async fn projects_without_dependencies_resolve_offline() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("Kargo.toml"), MANIFEST).unwrap();
+    let result = Project::load(tmp.path()).unwrap().resolve().await.unwrap();
+    assert!(result.artifacts.is_empty());
+}
@@ -1 +1,80 @@
 //! Kotlin code formatter following official coding conventions.
+//!
+//! [`check`] reports the layout problems a formatter run would fix:
+//! tab indentation, trailing whitespace, lines over `[format]
+//! max-line-length` and a missing final newline.
+
+use kargo_core::manifest::FormatConfig;
+
+/// A line a formatter run would change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatIssue {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The layout problems of the Kotlin `source`, in line order.
+pub fn check(source: &str, config: Option<&FormatConfig>) -> Vec<FormatIssue> {
+    let max_line_length = config.and_then(|c| c.max_line_length).map(|n| n as usize);
+    let mut issues = Vec::new();
+    let mut issue = |line: usize, message: String| issues.push(FormatIssue { line, message });
+
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i + 1;
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if indent.contains('\t') {
+            issue(line_no, "indented with tabs".into());
+        }
+        if line.trim_end() != line {
+            issue(line_no, "trailing whitespace".into());
+        }
+        let length = line.chars().count();
+        if let Some(max) = max_line_length.filter(|max| length > *max) {
+            issue(
+                line_no,
+                format!("{length} characters, over the limit of {max}"),
+            );
+        }
+    }
+    if !source.is_empty() && !source.ends_with('\n') {
+        issue(source.lines().count(), "no newline at end of file".into());
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_layout_problems_by_line() {
+        let config = FormatConfig {
+            style: None,
+            indent: None,
+            max_line_length: Some(20),
+        };
+        let source = "fun main() {\n\tprintln(\"hi\")  \n    val s = \"a long string\"\n}";
+        let issues = check(source, Some(&config));
+        let found: Vec<(usize, &str)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (2, "indented with tabs"),
+                (2, "trailing whitespace"),
+                (3, "27 characters, over the limit of 20"),
+                (4, "no newline at end of file"),
+            ]
+        );
+    }
+
+    #[test]
+    fn formatted_source_has_no_issues() {
+        let source = "fun main() {\n    println(\"hi\")\n}\n";
+        assert!(check(source, None).is_empty());
+        assert!(check("", None).is_empty());
+    }
+}
@@ -2,3 +2,4 @@ pub mod config;
 pub mod formatter;
 pub mod linter;
 pub mod rules;
+pub mod sarif;
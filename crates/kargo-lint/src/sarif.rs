@@ -0,0 +1,272 @@
+//! SARIF 2.1.0 reports for code scanning.
+//!
+//! Findings from Kargo's analyses (lint rules, dependency audits) are
+//! collected into a [`Report`] and serialized as a single-run SARIF log that
+//! GitHub code scanning and other SARIF consumers ingest. Every result
+//! carries a `partialFingerprints` entry so alerts are deduplicated across
+//! runs even when line numbers shift.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// Key of the fingerprint Kargo attaches to every result.
+pub const FINGERPRINT_KEY: &str = "kargoFindingHash/v1";
+
+/// SARIF result level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note => "note",
+        }
+    }
+}
+
+/// Metadata of a rule that produced results.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub id: String,
+    pub name: Option<String>,
+    pub short_description: String,
+    pub full_description: Option<String>,
+    pub help_uri: Option<String>,
+    pub default_level: Level,
+    pub tags: Vec<String>,
+    /// GitHub's `security-severity` score (0.0–10.0) for security rules.
+    pub security_severity: Option<f32>,
+}
+
+/// Where a result was found, relative to the repository root.
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub uri: String,
+    /// 1-based line, if known.
+    pub line: Option<u32>,
+    /// 1-based column, if known.
+    pub column: Option<u32>,
+}
+
+/// One finding.
+#[derive(Debug, Clone)]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub level: Level,
+    pub message: String,
+    pub location: Location,
+    /// Stable identity of the finding, independent of its position. Hashed
+    /// into the result's fingerprint.
+    pub identity: String,
+}
+
+/// A single-run SARIF log under construction.
+#[derive(Debug, Clone)]
+pub struct Report {
+    tool: String,
+    version: String,
+    information_uri: Option<String>,
+    rules: BTreeMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl Report {
+    /// A report for `tool` at `version`.
+    pub fn new(tool: &str, version: &str) -> Self {
+        Self {
+            tool: tool.to_string(),
+            version: version.to_string(),
+            information_uri: None,
+            rules: BTreeMap::new(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn information_uri(mut self, uri: &str) -> Self {
+        self.information_uri = Some(uri.to_string());
+        self
+    }
+
+    /// Register `rule`; the first registration of an id wins.
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.entry(rule.id.clone()).or_insert(rule);
+    }
+
+    pub fn add_result(&mut self, result: SarifResult) {
+        self.results.push(result);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// The SARIF log as JSON.
+    pub fn to_json(&self) -> Value {
+        let index: BTreeMap<&str, usize> = self
+            .rules
+            .keys()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect();
+        let rules: Vec<Value> = self.rules.values().map(rule_json).collect();
+        let results: Vec<Value> = self
+            .results
+            .iter()
+            .map(|r| {
+                let mut region = serde_json::Map::new();
+                if let Some(line) = r.location.line {
+                    region.insert("startLine".into(), json!(line));
+                }
+                if let Some(column) = r.location.column {
+                    region.insert("startColumn".into(), json!(column));
+                }
+                let mut physical = json!({
+                    "artifactLocation": { "uri": r.location.uri, "uriBaseId": "%SRCROOT%" },
+                });
+                if !region.is_empty() {
+                    physical["region"] = Value::Object(region);
+                }
+                let mut result = json!({
+                    "ruleId": r.rule_id,
+                    "level": r.level.as_str(),
+                    "message": { "text": r.message },
+                    "locations": [{ "physicalLocation": physical }],
+                    "partialFingerprints": { FINGERPRINT_KEY: fingerprint(&r.rule_id, &r.identity) },
+                });
+                if let Some(i) = index.get(r.rule_id.as_str()) {
+                    result["ruleIndex"] = json!(i);
+                }
+                result
+            })
+            .collect();
+
+        let mut driver = json!({
+            "name": self.tool,
+            "version": self.version,
+            "semanticVersion": self.version,
+            "rules": rules,
+        });
+        if let Some(uri) = &self.information_uri {
+            driver["informationUri"] = json!(uri);
+        }
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [{
+                "tool": { "driver": driver },
+                "results": results,
+            }],
+        })
+    }
+}
+
+fn rule_json(rule: &Rule) -> Value {
+    let mut value = json!({
+        "id": rule.id,
+        "shortDescription": { "text": rule.short_description },
+        "defaultConfiguration": { "level": rule.default_level.as_str() },
+    });
+    if let Some(name) = &rule.name {
+        value["name"] = json!(name);
+    }
+    if let Some(full) = &rule.full_description {
+        value["fullDescription"] = json!({ "text": full });
+    }
+    if let Some(uri) = &rule.help_uri {
+        value["helpUri"] = json!(uri);
+        value["help"] = json!({ "text": uri });
+    }
+    let mut properties = serde_json::Map::new();
+    if !rule.tags.is_empty() {
+        properties.insert("tags".into(), json!(rule.tags));
+    }
+    if let Some(score) = rule.security_severity {
+        properties.insert("security-severity".into(), json!(format!("{score:.1}")));
+    }
+    if !properties.is_empty() {
+        value["properties"] = Value::Object(properties);
+    }
+    value
+}
+
+/// Fingerprint of a finding: a hash of its rule and position-independent
+/// identity, so an alert survives unrelated edits to the file.
+pub fn fingerprint(rule_id: &str, identity: &str) -> String {
+    kargo_util::hash::sha256_bytes(format!("{rule_id}\n{identity}").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let mut report = Report::new("kargo", "0.1.0").information_uri("https://example.com");
+        report.add_rule(Rule {
+            id: "GHSA-1".into(),
+            name: None,
+            short_description: "Remote code execution".into(),
+            full_description: None,
+            help_uri: Some("https://osv.dev/GHSA-1".into()),
+            default_level: Level::Error,
+            tags: vec!["security".into()],
+            security_severity: Some(9.5),
+        });
+        report.add_result(SarifResult {
+            rule_id: "GHSA-1".into(),
+            level: Level::Error,
+            message: "lib 1.0 is vulnerable".into(),
+            location: Location {
+                uri: "Kargo.lock".into(),
+                line: Some(12),
+                column: None,
+            },
+            identity: "org.example:lib".into(),
+        });
+        report
+    }
+
+    #[test]
+    fn report_has_rules_results_and_fingerprints() {
+        let log = report().to_json();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "kargo");
+        let rule = &run["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["id"], "GHSA-1");
+        assert_eq!(rule["properties"]["security-severity"], "9.5");
+        assert_eq!(rule["helpUri"], "https://osv.dev/GHSA-1");
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "Kargo.lock");
+        assert_eq!(location["region"]["startLine"], 12);
+        assert!(location["region"].get("startColumn").is_none());
+        assert_eq!(
+            result["partialFingerprints"][FINGERPRINT_KEY],
+            fingerprint("GHSA-1", "org.example:lib")
+        );
+    }
+
+    #[test]
+    fn fingerprint_ignores_position() {
+        let mut moved = report();
+        moved.results[0].location.line = Some(40);
+        assert_eq!(
+            report().to_json()["runs"][0]["results"][0]["partialFingerprints"],
+            moved.to_json()["runs"][0]["results"][0]["partialFingerprints"]
+        );
+        assert_ne!(fingerprint("GHSA-1", "a:b"), fingerprint("GHSA-2", "a:b"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Android archives (AARs): Android libraries published to Maven.
+//!
+//! An AAR is a zip holding the library's `classes.jar`, its
+//! `AndroidManifest.xml`, an `res/` tree, the ProGuard rules it asks
+//! consumers to apply (`proguard.txt`) and its baseline profile
+//! (`baseline-prof.txt`). The Kotlin compiler only reads the
+//! classes, so a cached AAR is extracted once into a sibling
+//! `<artifact>-<version>-aar/` directory and the pieces are used from there.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use kargo_util::errors::KargoError;
+use kargo_util::fs::long_path;
+
+/// An AAR extracted into the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplodedAar {
+    pub dir: PathBuf,
+}
+
+impl ExplodedAar {
+    /// The library's classes, absent for resource-only AARs.
+    pub fn classes_jar(&self) -> Option<PathBuf> {
+        self.file("classes.jar")
+    }
+
+    /// The library's `AndroidManifest.xml`.
+    pub fn manifest(&self) -> Option<PathBuf> {
+        self.file("AndroidManifest.xml")
+    }
+
+    /// The library's `res/` directory.
+    pub fn res_dir(&self) -> Option<PathBuf> {
+        let dir = self.dir.join("res");
+        long_path(&dir).is_dir().then_some(dir)
+    }
+
+    /// ProGuard/R8 rules the library ships for its consumers.
+    pub fn consumer_rules(&self) -> Option<PathBuf> {
+        self.file("proguard.txt")
+    }
+
+    /// The library's baseline profile rules (`baseline-prof.txt`).
+    pub fn baseline_profile(&self) -> Option<PathBuf> {
+        self.file("baseline-prof.txt")
+    }
+
+    fn file(&self, name: &str) -> Option<PathBuf> {
+        let path = self.dir.join(name);
+        long_path(&path).is_file().then_some(path)
+    }
+}
+
+/// Whether `path` names an AAR.
+pub fn is_aar(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "aar")
+}
+
+/// Directory `aar` is extracted into.
+pub fn exploded_dir(aar: &Path) -> PathBuf {
+    let stem = aar.file_stem().unwrap_or_default().to_string_lossy();
+    aar.with_file_name(format!("{stem}-aar"))
+}
+
+/// Extract `aar` next to itself unless that already happened.
+pub fn explode(aar: &Path) -> miette::Result<ExplodedAar> {
+    let dir = exploded_dir(aar);
+    if long_path(&dir).is_dir() {
+        return Ok(ExplodedAar { dir });
+    }
+    let zip_err = |e: zip::result::ZipError| KargoError::Generic {
+        message: format!("Failed to extract {}: {e}", aar.display()),
+    };
+    let parent = aar.parent().unwrap_or(Path::new("."));
+    // Extract into a temporary directory first so that concurrent builds
+    // never see a half-written tree.
+    let staging = tempfile::tempdir_in(long_path(parent)).map_err(KargoError::Io)?;
+    let file = fs::File::open(long_path(aar)).map_err(KargoError::Io)?;
+    zip::ZipArchive::new(file)
+        .map_err(zip_err)?
+        .extract(staging.path())
+        .map_err(zip_err)?;
+    let staged = staging.keep();
+    if fs::rename(&staged, long_path(&dir)).is_err() {
+        // Another process won the race.
+        let _ = fs::remove_dir_all(&staged);
+        if !long_path(&dir).is_dir() {
+            return Err(KargoError::Generic {
+                message: format!("Failed to extract {}", aar.display()),
+            }
+            .into());
+        }
+    }
+    Ok(ExplodedAar { dir })
+}
+
+/// The JAR to put on a classpath for the cached library file `path`: the
+/// file itself, or the classes of an AAR. `None` for AARs without classes
+/// or that cannot be extracted.
+pub fn classpath_entry(path: PathBuf) -> Option<PathBuf> {
+    if !is_aar(&path) {
+        return Some(path);
+    }
+    match explode(&path) {
+        Ok(exploded) => exploded.classes_jar(),
+        Err(e) => {
+            tracing::warn!("{e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_aar(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn explodes_next_to_the_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let aar = tmp.path().join("core-1.13.1.aar");
+        write_aar(
+            &aar,
+            &[
+                ("classes.jar", "jar"),
+                ("AndroidManifest.xml", "<manifest/>"),
+                ("res/values/values.xml", "<resources/>"),
+                ("proguard.txt", "-keep class a.B"),
+                ("baseline-prof.txt", "HSPLa/B;->c()V"),
+            ],
+        );
+
+        let exploded = explode(&aar).unwrap();
+        assert_eq!(exploded.dir, tmp.path().join("core-1.13.1-aar"));
+        assert!(exploded.manifest().is_some());
+        assert!(exploded
+            .res_dir()
+            .unwrap()
+            .join("values/values.xml")
+            .is_file());
+        assert!(exploded.consumer_rules().is_some());
+        assert!(exploded.baseline_profile().is_some());
+        assert_eq!(
+            classpath_entry(aar.clone()),
+            Some(exploded.dir.join("classes.jar"))
+        );
+        // A second call reuses the extracted tree.
+        assert_eq!(explode(&aar).unwrap(), exploded);
+
+        let jar = tmp.path().join("lib-1.0.jar");
+        assert_eq!(classpath_entry(jar.clone()), Some(jar));
+    }
+
+    #[test]
+    fn deep_cache_paths_are_extracted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let segments: Vec<String> = (0..24).map(|i| format!("org-example-{i:02}")).collect();
+        let dir = tmp.path().join(segments.join("/"));
+        fs::create_dir_all(long_path(&dir)).unwrap();
+        let aar = dir.join("core-1.0.aar");
+        assert!(aar.as_os_str().len() > 260);
+        let mut zip = zip::ZipWriter::new(fs::File::create(long_path(&aar)).unwrap());
+        zip.start_file("classes.jar", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"jar").unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(
+            classpath_entry(aar),
+            Some(dir.join("core-1.0-aar/classes.jar"))
+        );
+    }
+
+    #[test]
+    fn resource_only_aars_have_no_classes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let aar = tmp.path().join("icons-1.0.aar");
+        write_aar(&aar, &[("AndroidManifest.xml", "<manifest/>")]);
+        assert_eq!(classpath_entry(aar), None);
+    }
+}
@@ -1,48 +1,97 @@
-//! Project-local Maven cache management mirroring repository layout.
+//! Maven artifact cache management mirroring repository layout.
 
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use kargo_util::fs::dir_size;
+use kargo_core::config::{CacheConfig, DependencyCacheMode, GlobalConfig};
+use kargo_util::fs::{dir_size, long_path};
 
 use crate::pom::{self, Pom};
 use crate::repository::MavenRepository;
 
-/// Project-local Maven artifact cache at `<project>/.kargo/dependencies/`.
+/// Maven artifact cache of a project: `<project>/.kargo/dependencies/`, the
+/// per-user cache, or the former layered over the latter, as
+/// `[cache] dependencies` in the global config selects.
 #[derive(Debug, Clone)]
 pub struct LocalCache {
+    /// Where downloads are written; looked in first.
     root: PathBuf,
+    /// The per-user cache looked in after `root` in layered mode.
+    shared: Option<PathBuf>,
+    /// Whether `root` is the per-user cache, which other projects use too.
+    root_is_shared: bool,
 }
 
 impl LocalCache {
-    /// Create a cache rooted at `project_root/.kargo/dependencies/`.
+    /// The dependency cache of the project in `project_root`, laid out as
+    /// the global config's `[cache]` section says.
     pub fn new(project_root: &Path) -> Self {
-        Self {
-            root: project_root.join(".kargo").join("dependencies"),
+        let config = GlobalConfig::load().unwrap_or_else(|e| {
+            tracing::warn!("Failed to load global config, using defaults: {e}");
+            GlobalConfig::default()
+        });
+        Self::for_project(project_root, &config.cache)
+    }
+
+    /// The dependency cache of the project in `project_root` under `config`.
+    pub fn for_project(project_root: &Path, config: &CacheConfig) -> Self {
+        let project = project_root.join(".kargo").join("dependencies");
+        match config.dependencies {
+            DependencyCacheMode::Project => Self::from_root(project),
+            DependencyCacheMode::User => Self {
+                root: config.shared_dependencies_dir(),
+                shared: None,
+                root_is_shared: true,
+            },
+            DependencyCacheMode::Layered => Self {
+                root: project,
+                shared: Some(config.shared_dependencies_dir()),
+                root_is_shared: false,
+            },
         }
     }
 
     /// Create a cache from an already-resolved root path.
     pub fn from_root(root: PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            shared: None,
+            root_is_shared: false,
+        }
     }
 
-    /// The root directory of this cache.
+    /// The root directory of this cache, where downloads are written.
     pub fn root(&self) -> &Path {
         &self.root
     }
 
-    /// Path within the cache for a given Maven coordinate.
+    /// Every directory looked in, in lookup order.
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.root.as_path()).chain(self.shared.as_deref())
+    }
+
+    /// Whether pruning removes nothing because the cache is shared with
+    /// other projects.
+    pub fn is_shared(&self) -> bool {
+        self.root_is_shared
+    }
+
+    /// Path within the cache for a given Maven coordinate: the first
+    /// directory that has it, or where its downloads go.
     pub fn artifact_dir(&self, group: &str, artifact: &str, version: &str) -> PathBuf {
-        self.root
-            .join(group.replace('.', "/"))
-            .join(artifact)
-            .join(version)
+        self.roots()
+            .map(|root| coordinate_dir(root, group, artifact, version))
+            .find(|dir| long_path(dir).is_dir())
+            .unwrap_or_else(|| coordinate_dir(&self.root, group, artifact, version))
     }
 
-    /// Path to a specific file in the cache.
+    /// Path to a specific file in the cache: the first directory that has
+    /// it, or where it is downloaded to.
     fn artifact_path(&self, group: &str, artifact: &str, version: &str, filename: &str) -> PathBuf {
-        self.artifact_dir(group, artifact, version).join(filename)
+        self.roots()
+            .map(|root| coordinate_dir(root, group, artifact, version).join(filename))
+            .find(|path| long_path(path).is_file())
+            .unwrap_or_else(|| coordinate_dir(&self.root, group, artifact, version).join(filename))
     }
 
     /// Check if a JAR is cached and return its path.
@@ -58,21 +107,45 @@ impl LocalCache {
             None => format!("{artifact}-{version}.jar"),
         };
         let path = self.artifact_path(group, artifact, version, &filename);
-        path.is_file().then_some(path)
+        long_path(&path).is_file().then_some(path)
+    }
+
+    /// Check if a klib is cached and return its path.
+    pub fn get_klib(&self, group: &str, artifact: &str, version: &str) -> Option<PathBuf> {
+        let filename = format!("{artifact}-{version}.klib");
+        let path = self.artifact_path(group, artifact, version, &filename);
+        long_path(&path).is_file().then_some(path)
+    }
+
+    /// Check if an AAR (Android library) is cached and return its path.
+    pub fn get_aar(&self, group: &str, artifact: &str, version: &str) -> Option<PathBuf> {
+        let filename = format!("{artifact}-{version}.aar");
+        let path = self.artifact_path(group, artifact, version, &filename);
+        long_path(&path).is_file().then_some(path)
+    }
+
+    /// The cached library file for a coordinate: its JAR, its klib if the
+    /// artifact has no JAR, or its AAR if it has neither.
+    pub fn get_library(&self, group: &str, artifact: &str, version: &str) -> Option<PathBuf> {
+        self.get_jar(group, artifact, version, None)
+            .or_else(|| self.get_klib(group, artifact, version))
+            .or_else(|| self.get_aar(group, artifact, version))
     }
 
     /// Check if a POM is cached and parse it.
     pub fn get_pom(&self, group: &str, artifact: &str, version: &str) -> Option<Pom> {
         let filename = format!("{artifact}-{version}.pom");
         let path = self.artifact_path(group, artifact, version, &filename);
-        if !path.is_file() {
+        if !long_path(&path).is_file() {
             return None;
         }
-        let content = fs::read_to_string(&path).ok()?;
+        let content = fs::read_to_string(long_path(&path)).ok()?;
         pom::parse_pom(&content).ok()
     }
 
     /// Store artifact data in the cache, creating directories as needed.
+    /// The file appears complete or not at all, even if the process is
+    /// killed while writing it.
     pub fn put(
         &self,
         group: &str,
@@ -81,10 +154,13 @@ impl LocalCache {
         filename: &str,
         data: &[u8],
     ) -> miette::Result<PathBuf> {
-        let dir = self.artifact_dir(group, artifact, version);
-        fs::create_dir_all(&dir).map_err(kargo_util::errors::KargoError::Io)?;
+        let dir = coordinate_dir(&self.root, group, artifact, version);
+        fs::create_dir_all(long_path(&dir)).map_err(kargo_util::errors::KargoError::Io)?;
         let path = dir.join(filename);
-        fs::write(&path, data).map_err(kargo_util::errors::KargoError::Io)?;
+        let partial = dir.join(format!(".{filename}.part"));
+        fs::write(long_path(&partial), data).map_err(kargo_util::errors::KargoError::Io)?;
+        fs::rename(long_path(&partial), long_path(&path))
+            .map_err(kargo_util::errors::KargoError::Io)?;
         Ok(path)
     }
 
@@ -116,9 +192,21 @@ impl LocalCache {
         self.put(group, artifact, version, &filename, data)
     }
 
-    /// Check whether the JAR for this coordinate exists in cache.
+    /// Store a klib file in the cache.
+    pub fn put_klib(
+        &self,
+        group: &str,
+        artifact: &str,
+        version: &str,
+        data: &[u8],
+    ) -> miette::Result<PathBuf> {
+        let filename = format!("{artifact}-{version}.klib");
+        self.put(group, artifact, version, &filename, data)
+    }
+
+    /// Check whether the library file (JAR, klib or AAR) for this coordinate exists in cache.
     pub fn has_artifact(&self, group: &str, artifact: &str, version: &str) -> bool {
-        self.get_jar(group, artifact, version, None).is_some()
+        self.get_library(group, artifact, version).is_some()
     }
 
     /// Fetch or download a POM, using cache when available.
@@ -151,19 +239,95 @@ impl LocalCache {
     /// `keep` contains `(group, artifact, version)` tuples of artifacts
     /// that should be retained. Everything else gets deleted.
     /// Returns the number of version directories removed.
+    ///
+    /// Only the project's own directory is pruned: the per-user cache holds
+    /// other projects' dependencies too.
     pub fn prune(&self, keep: &std::collections::HashSet<(String, String, String)>) -> u32 {
         let mut removed = 0u32;
-        if !self.root.is_dir() {
+        if self.root_is_shared || !self.root.is_dir() {
             return removed;
         }
         collect_version_dirs(&self.root, &self.root, keep, &mut removed);
         removed
     }
 
-    /// Total size of the cache directory in bytes.
+    /// Total size of the root directory in bytes.
     pub fn size(&self) -> u64 {
         dir_size(&self.root)
     }
+
+    /// Every cached library file (JAR or klib) with its
+    /// `(group, artifact, version)`, sorted by coordinate. POM-only version
+    /// directories are not included.
+    pub fn libraries(&self) -> Vec<((String, String, String), PathBuf)> {
+        let mut found = Vec::new();
+        for root in self.roots() {
+            collect_libraries(self, root, root, &mut found);
+        }
+        found.sort();
+        found.dedup_by(|a, b| a.0 == b.0);
+        found
+    }
+
+    /// Every cached version directory with its `(group, artifact, version)`,
+    /// sorted by coordinate; the first directory looked in wins when both
+    /// layers have a coordinate.
+    pub fn version_dirs(&self) -> Vec<((String, String, String), PathBuf)> {
+        let mut found = Vec::new();
+        for root in self.roots() {
+            collect_all_version_dirs(root, root, &mut found);
+        }
+        // Stable sort keeps the upper layer's directory first.
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found.dedup_by(|a, b| a.0 == b.0);
+        found
+    }
+}
+
+fn collect_all_version_dirs(
+    root: &Path,
+    current: &Path,
+    found: &mut Vec<((String, String, String), PathBuf)>,
+) {
+    let Ok(entries) = fs::read_dir(current) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let has_files = fs::read_dir(&path)
+            .map(|rd| rd.flatten().any(|e| e.path().is_file()))
+            .unwrap_or(false);
+        match reconstruct_coordinate(root, &path).filter(|_| has_files) {
+            Some(coord) => found.push((coord, path)),
+            None => collect_all_version_dirs(root, &path, found),
+        }
+    }
+}
+
+fn coordinate_dir(root: &Path, group: &str, artifact: &str, version: &str) -> PathBuf {
+    root.join(group.replace('.', "/"))
+        .join(artifact)
+        .join(version)
+}
+
+fn collect_libraries(
+    cache: &LocalCache,
+    root: &Path,
+    current: &Path,
+    found: &mut Vec<((String, String, String), PathBuf)>,
+) {
+    let Ok(entries) = fs::read_dir(current) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let library = reconstruct_coordinate(root, &path).and_then(|(g, a, v)| {
+            let file = cache.get_library(&g, &a, &v)?;
+            Some(((g, a, v), file))
+        });
+        match library {
+            Some(entry) => found.push(entry),
+            None => collect_libraries(cache, root, &path, found),
+        }
+    }
 }
 
 /// Walk the cache tree to find version directories (leaf dirs containing files)
@@ -195,13 +359,16 @@ fn collect_version_dirs(
             // This is a version dir: reconstruct group:artifact:version from path
             if let Some(coord) = reconstruct_coordinate(root, &path) {
                 if !keep.contains(&coord) {
-                    // Only prune if the directory has a JAR (not just a POM).
-                    // The resolver caches POM files for versions that don't
-                    // win "nearest wins"; those POM-only dirs are harmless.
+                    // Only prune if the directory has a JAR or AAR (not just
+                    // a POM). The resolver caches POM files for versions that
+                    // don't win "nearest wins"; those POM-only dirs are harmless.
                     let has_jar = fs::read_dir(&path)
                         .map(|rd| {
-                            rd.flatten()
-                                .any(|e| e.path().extension().is_some_and(|ext| ext == "jar"))
+                            rd.flatten().any(|e| {
+                                e.path()
+                                    .extension()
+                                    .is_some_and(|ext| ext == "jar" || ext == "aar")
+                            })
                         })
                         .unwrap_or(false);
                     if has_jar {
@@ -289,6 +456,53 @@ mod tests {
         assert_eq!(pom.unwrap().artifact_id.as_deref(), Some("lib"));
     }
 
+    #[test]
+    fn klib_is_used_when_no_jar() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let path = cache
+            .put_klib("org.example", "lib-wasm-js", "1.0", b"klib")
+            .unwrap();
+        assert_eq!(
+            cache.get_library("org.example", "lib-wasm-js", "1.0"),
+            Some(path)
+        );
+        assert!(cache.has_artifact("org.example", "lib-wasm-js", "1.0"));
+
+        let jar = cache
+            .put_jar("org.example", "lib-wasm-js", "1.0", None, b"jar")
+            .unwrap();
+        assert_eq!(
+            cache.get_library("org.example", "lib-wasm-js", "1.0"),
+            Some(jar)
+        );
+    }
+
+    #[test]
+    fn aar_is_the_last_resort() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let aar = cache
+            .put("androidx.core", "core", "1.13.1", "core-1.13.1.aar", b"aar")
+            .unwrap();
+        assert_eq!(
+            cache.get_aar("androidx.core", "core", "1.13.1"),
+            Some(aar.clone())
+        );
+        assert_eq!(
+            cache.get_library("androidx.core", "core", "1.13.1"),
+            Some(aar)
+        );
+
+        let jar = cache
+            .put_jar("androidx.core", "core", "1.13.1", None, b"jar")
+            .unwrap();
+        assert_eq!(
+            cache.get_library("androidx.core", "core", "1.13.1"),
+            Some(jar)
+        );
+    }
+
     #[test]
     fn cache_miss() {
         let tmp = tempfile::tempdir().unwrap();
@@ -348,6 +562,34 @@ mod tests {
         assert!(cache.has_artifact("org.other", "util", "3.0"));
     }
 
+    #[test]
+    fn libraries_lists_jars_and_klibs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        cache
+            .put_jar("org.example", "lib", "1.0", None, b"jar")
+            .unwrap();
+        cache
+            .put_klib("org.example.wasm", "lib-wasm-js", "1.0", b"klib")
+            .unwrap();
+        cache
+            .put_pom("org.example", "parent", "1.0", "<project/>")
+            .unwrap();
+
+        let coords: Vec<String> = cache
+            .libraries()
+            .into_iter()
+            .map(|((g, a, v), path)| {
+                assert!(path.is_file());
+                format!("{g}:{a}:{v}")
+            })
+            .collect();
+        assert_eq!(
+            coords,
+            ["org.example:lib:1.0", "org.example.wasm:lib-wasm-js:1.0"]
+        );
+    }
+
     #[test]
     fn prune_cleans_empty_parent_dirs() {
         let tmp = tempfile::tempdir().unwrap();
@@ -364,4 +606,63 @@ mod tests {
         // The entire org/removed/gone directory tree should be gone
         assert!(!cache.artifact_dir("org.removed", "gone", "1.0").exists());
     }
+
+    fn config(mode: DependencyCacheMode, shared: &Path) -> CacheConfig {
+        CacheConfig {
+            dependencies: mode,
+            dependencies_dir: Some(shared.to_string_lossy().into_owned()),
+            ..CacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn layered_cache_reads_the_project_first_and_writes_there() {
+        let tmp = tempfile::tempdir().unwrap();
+        let shared_dir = tmp.path().join("shared");
+        let shared = LocalCache::from_root(shared_dir.clone());
+        shared
+            .put_jar("org.example", "both", "1.0", None, b"shared")
+            .unwrap();
+        shared
+            .put_jar("org.example", "only-shared", "1.0", None, b"shared")
+            .unwrap();
+
+        let project = tmp.path().join("app");
+        let cache =
+            LocalCache::for_project(&project, &config(DependencyCacheMode::Layered, &shared_dir));
+        cache
+            .put_jar("org.example", "both", "1.0", None, b"project")
+            .unwrap();
+
+        let both = cache.get_jar("org.example", "both", "1.0", None).unwrap();
+        assert_eq!(std::fs::read(both).unwrap(), b"project");
+        let fallback = cache
+            .get_jar("org.example", "only-shared", "1.0", None)
+            .unwrap();
+        assert!(fallback.starts_with(&shared_dir));
+        assert!(cache.root().starts_with(&project));
+        assert_eq!(cache.libraries().len(), 2);
+
+        // Pruning leaves the per-user cache alone.
+        assert_eq!(cache.prune(&std::collections::HashSet::new()), 1);
+        assert!(cache.has_artifact("org.example", "only-shared", "1.0"));
+        assert!(shared.has_artifact("org.example", "both", "1.0"));
+    }
+
+    #[test]
+    fn user_cache_is_shared_and_never_pruned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let shared_dir = tmp.path().join("shared");
+        let config = config(DependencyCacheMode::User, &shared_dir);
+        let a = LocalCache::for_project(&tmp.path().join("a"), &config);
+        a.put_jar("org.example", "lib", "1.0", None, b"data")
+            .unwrap();
+
+        let b = LocalCache::for_project(&tmp.path().join("b"), &config);
+        assert!(b.is_shared());
+        assert_eq!(b.root(), shared_dir);
+        assert!(b.has_artifact("org.example", "lib", "1.0"));
+        assert_eq!(b.prune(&std::collections::HashSet::new()), 0);
+        assert!(a.has_artifact("org.example", "lib", "1.0"));
+    }
 }
@@ -65,19 +65,19 @@ fn extract_hash(content: &str) -> String {
     content.split_whitespace().next().unwrap_or("").to_string()
 }
 
-fn hex_sha256(data: &[u8]) -> String {
+pub(crate) fn hex_sha256(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
 
-fn hex_sha1(data: &[u8]) -> String {
+pub(crate) fn hex_sha1(data: &[u8]) -> String {
     let mut hasher = Sha1::new();
     hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
 
-fn hex_md5(data: &[u8]) -> String {
+pub(crate) fn hex_md5(data: &[u8]) -> String {
     let mut hasher = Md5::new();
     hasher.update(data);
     format!("{:x}", hasher.finalize())
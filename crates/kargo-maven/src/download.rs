@@ -1,12 +1,16 @@
 //! Artifact downloading from Maven repositories.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use indicatif::{ProgressBar, ProgressStyle};
+use kargo_util::http_cache::{CachedResponse, HttpCache};
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 
 use crate::auth;
+use crate::metrics::{self, RequestResult};
 use crate::repository::MavenRepository;
+use crate::throttle::{self, Outcome};
 
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY: Duration = Duration::from_secs(2);
@@ -14,7 +18,7 @@ const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// Build a shared reqwest client for Maven downloads.
 pub fn build_client() -> miette::Result<Client> {
-    Client::builder()
+    kargo_util::http::client_builder()?
         .timeout(REQUEST_TIMEOUT)
         .user_agent("kargo/0.1")
         .build()
@@ -35,6 +39,52 @@ pub async fn download_bytes(
     repo: &MavenRepository,
     url: &str,
 ) -> miette::Result<Option<Vec<u8>>> {
+    match send(client, repo, url, None).await? {
+        Response::Fresh(bytes, _) => Ok(Some(bytes)),
+        Response::NotModified | Response::NotFound => Ok(None),
+    }
+}
+
+/// Download a text file that changes in place, such as
+/// `maven-metadata.xml`, with a conditional request against the copy in
+/// `cache`. An unchanged file costs one round trip without a body.
+pub async fn download_text_cached(
+    client: &Client,
+    repo: &MavenRepository,
+    url: &str,
+    cache: &HttpCache,
+) -> miette::Result<Option<String>> {
+    let cached = cache.lookup(url);
+    let bytes = match send(client, repo, url, cached.as_ref()).await? {
+        Response::Fresh(bytes, headers) => {
+            cache.store(url, &headers, &bytes);
+            bytes
+        }
+        Response::NotModified => cached.map(|c| c.body).unwrap_or_default(),
+        Response::NotFound => {
+            cache.remove(url);
+            return Ok(None);
+        }
+    };
+    Ok(Some(String::from_utf8_lossy(&bytes).to_string()))
+}
+
+/// How a request ended.
+enum Response {
+    Fresh(Vec<u8>, HeaderMap),
+    /// 304 to a request made conditional on a cached copy.
+    NotModified,
+    NotFound,
+}
+
+/// GET `url`, with authentication and retries, conditional on `cached`
+/// having changed if given.
+async fn send(
+    client: &Client,
+    repo: &MavenRepository,
+    url: &str,
+    cached: Option<&CachedResponse>,
+) -> miette::Result<Response> {
     let mut last_err = String::new();
 
     for attempt in 0..MAX_RETRIES {
@@ -44,37 +94,62 @@ pub async fn download_bytes(
 
         let mut req = client.get(url);
         req = auth::apply_auth(req, repo);
+        if let Some(cached) = cached {
+            req = cached.conditional(req);
+        }
 
+        let mut permit = throttle::acquire(url).await;
+        let started = Instant::now();
+        let done = |result| metrics::record(repo, result, started.elapsed());
         match req.send().await {
             Ok(resp) => {
                 let status = resp.status();
+                if throttle::is_throttled(status) {
+                    permit.record(Outcome::Throttled(throttle::retry_after(resp.headers())));
+                    done(RequestResult::Failed);
+                    last_err = format!("HTTP {status} from {url}");
+                    continue;
+                }
+                permit.record(Outcome::Ok);
                 if status == reqwest::StatusCode::NOT_FOUND {
-                    return Ok(None);
+                    done(RequestResult::NotFound);
+                    return Ok(Response::NotFound);
+                }
+                if status == reqwest::StatusCode::NOT_MODIFIED && cached.is_some() {
+                    done(RequestResult::Ok(0));
+                    return Ok(Response::NotModified);
                 }
                 if status.is_server_error() {
+                    done(RequestResult::Failed);
                     last_err = format!("HTTP {status} from {url}");
                     continue;
                 }
                 if !status.is_success() {
+                    done(RequestResult::Failed);
                     return Err(kargo_util::errors::KargoError::Network {
                         message: format!("HTTP {status} fetching {url}"),
                     }
                     .into());
                 }
 
-                let bytes =
-                    resp.bytes()
-                        .await
-                        .map_err(|e| kargo_util::errors::KargoError::Network {
-                            message: format!("Failed to read response from {url}: {e}"),
-                        })?;
-                return Ok(Some(bytes.to_vec()));
+                let headers = resp.headers().clone();
+                let bytes = resp.bytes().await.map_err(|e| {
+                    done(RequestResult::Failed);
+                    kargo_util::errors::KargoError::Network {
+                        message: format!("Failed to read response from {url}: {e}"),
+                    }
+                })?;
+                done(RequestResult::Ok(bytes.len() as u64));
+                return Ok(Response::Fresh(bytes.to_vec(), headers));
             }
             Err(e) if e.is_timeout() || e.is_connect() => {
+                permit.record(Outcome::Failed);
+                done(RequestResult::Failed);
                 last_err = format!("{e}");
                 continue;
             }
             Err(e) => {
+                done(RequestResult::Failed);
                 return Err(kargo_util::errors::KargoError::Network {
                     message: format!("Request to {url} failed: {e}"),
                 }
@@ -99,17 +174,28 @@ pub async fn download_artifact(
     let mut req = client.get(url);
     req = auth::apply_auth(req, repo);
 
-    let resp = req
-        .send()
-        .await
-        .map_err(|e| kargo_util::errors::KargoError::Network {
+    let mut permit = throttle::acquire(url).await;
+    let started = Instant::now();
+    let done = |result| metrics::record(repo, result, started.elapsed());
+    let resp = req.send().await.map_err(|e| {
+        permit.record(Outcome::Failed);
+        done(RequestResult::Failed);
+        kargo_util::errors::KargoError::Network {
             message: format!("Request to {url} failed: {e}"),
-        })?;
+        }
+    })?;
+    permit.record(if throttle::is_throttled(resp.status()) {
+        Outcome::Throttled(throttle::retry_after(resp.headers()))
+    } else {
+        Outcome::Ok
+    });
 
     if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        done(RequestResult::NotFound);
         return Ok(None);
     }
     if !resp.status().is_success() {
+        done(RequestResult::Failed);
         return Err(kargo_util::errors::KargoError::Network {
             message: format!("HTTP {} fetching {url}", resp.status()),
         }
@@ -130,12 +216,13 @@ pub async fn download_artifact(
         None
     };
 
-    let bytes = resp
-        .bytes()
-        .await
-        .map_err(|e| kargo_util::errors::KargoError::Network {
+    let bytes = resp.bytes().await.map_err(|e| {
+        done(RequestResult::Failed);
+        kargo_util::errors::KargoError::Network {
             message: format!("Failed to read {url}: {e}"),
-        })?;
+        }
+    })?;
+    done(RequestResult::Ok(bytes.len() as u64));
 
     if let Some(pb) = pb {
         pb.set_position(bytes.len() as u64);
@@ -156,3 +243,72 @@ pub async fn download_text(
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    /// Serve `maven-metadata.xml` with an ETag: 200 with the body, or 304
+    /// to a request carrying the ETag. Returns the base URL and the
+    /// requests it received.
+    async fn metadata_server() -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    let body = "<metadata><versioning><versions>\
+                                <version>1.0</version></versions></versioning></metadata>";
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\
+                         Connection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (base, handle)
+    }
+
+    #[tokio::test]
+    async fn unchanged_metadata_is_served_from_the_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(tmp.path().join("http"));
+        let (base, server) = metadata_server().await;
+        let repo = MavenRepository {
+            name: "local".into(),
+            url: base.clone(),
+            ..MavenRepository::maven_central()
+        };
+        let client = Client::new();
+        let url = format!("{base}/org/example/lib/maven-metadata.xml");
+
+        let first = download_text_cached(&client, &repo, &url, &cache)
+            .await
+            .unwrap()
+            .unwrap();
+        let second = download_text_cached(&client, &repo, &url, &cache)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, second);
+        assert!(first.contains("<version>1.0</version>"));
+
+        let requests = server.await.unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+}
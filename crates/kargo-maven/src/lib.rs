@@ -1,11 +1,17 @@
 //! Maven repository protocol: POM parsing, artifact download, checksum
-//! verification, local cache, and authentication.
+//! verification, local cache, Android archives, license resolution,
+//! authentication, per-host rate limiting, and per-repository request
+//! metrics.
 
+pub mod aar;
 pub mod auth;
 pub mod cache;
 pub mod checksum;
 pub mod download;
+pub mod license;
 pub mod metadata;
+pub mod metrics;
 pub mod pom;
 pub mod publish;
 pub mod repository;
+pub mod throttle;
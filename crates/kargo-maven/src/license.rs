@@ -0,0 +1,503 @@
+//! Artifact licenses: resolution through the parent POM chain and
+//! normalization to SPDX identifiers.
+//!
+//! Many artifacts declare no `<licenses>` of their own and inherit them from
+//! a parent POM (an organization's or a multi-module build's). The licenses
+//! of an artifact are those of the nearest POM in its parent chain that
+//! declares any. License names are free text, so they are matched against a
+//! bundled table of common spellings and URLs to get SPDX identifiers.
+//!
+//! A resolved license is cached per coordinate next to the artifact's POM as
+//! `<artifact>-<version>.license.json`, holding the licenses as declared so
+//! that a newer alias table applies to cached entries too.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::LocalCache;
+use crate::pom::Pom;
+use crate::repository::{self, MavenRepository};
+
+/// Longest parent chain followed, as in dependency resolution.
+const MAX_PARENT_DEPTH: usize = 16;
+
+/// License names, normalized by [`normalize_name`], and their SPDX
+/// identifiers.
+const NAME_ALIASES: &[(&str, &str)] = &[
+    ("apache 2", "Apache-2.0"),
+    ("asl 2", "Apache-2.0"),
+    ("al 2", "Apache-2.0"),
+    ("apache 1.1", "Apache-1.1"),
+    ("mit", "MIT"),
+    ("mit x11", "MIT"),
+    ("bouncy castle", "MIT"),
+    ("bsd 2 clause", "BSD-2-Clause"),
+    ("simplified bsd", "BSD-2-Clause"),
+    ("freebsd", "BSD-2-Clause"),
+    ("bsd 3 clause", "BSD-3-Clause"),
+    ("new bsd", "BSD-3-Clause"),
+    ("bsd new", "BSD-3-Clause"),
+    ("revised bsd", "BSD-3-Clause"),
+    ("modified bsd", "BSD-3-Clause"),
+    ("eclipse distribution 1", "BSD-3-Clause"),
+    ("edl 1", "BSD-3-Clause"),
+    ("eclipse public 1", "EPL-1.0"),
+    ("epl 1", "EPL-1.0"),
+    ("eclipse public 2", "EPL-2.0"),
+    ("epl 2", "EPL-2.0"),
+    ("gnu general public 2", "GPL-2.0-only"),
+    ("gpl 2", "GPL-2.0-only"),
+    ("gplv2", "GPL-2.0-only"),
+    (
+        "gnu general public 2 with classpath exception",
+        "GPL-2.0-only WITH Classpath-exception-2.0",
+    ),
+    (
+        "gpl 2 with classpath exception",
+        "GPL-2.0-only WITH Classpath-exception-2.0",
+    ),
+    ("gpl2 w cpe", "GPL-2.0-only WITH Classpath-exception-2.0"),
+    ("gnu general public 3", "GPL-3.0-only"),
+    ("gpl 3", "GPL-3.0-only"),
+    ("gplv3", "GPL-3.0-only"),
+    ("gnu lesser general public 2.1", "LGPL-2.1-only"),
+    ("lgpl 2.1", "LGPL-2.1-only"),
+    ("gnu lesser general public 3", "LGPL-3.0-only"),
+    ("lgpl 3", "LGPL-3.0-only"),
+    ("mozilla public 1.1", "MPL-1.1"),
+    ("mpl 1.1", "MPL-1.1"),
+    ("mozilla public 2", "MPL-2.0"),
+    ("mpl 2", "MPL-2.0"),
+    ("common development and distribution 1", "CDDL-1.0"),
+    ("cddl 1", "CDDL-1.0"),
+    ("common development and distribution 1.1", "CDDL-1.1"),
+    ("cddl 1.1", "CDDL-1.1"),
+    ("cc0", "CC0-1.0"),
+    ("cc0 1", "CC0-1.0"),
+    ("creative commons zero 1", "CC0-1.0"),
+    ("unlicense", "Unlicense"),
+    ("isc", "ISC"),
+];
+
+/// License URLs, normalized by [`normalize_url`], and their SPDX
+/// identifiers.
+const URL_ALIASES: &[(&str, &str)] = &[
+    ("apache.org/licenses/license-2.0", "Apache-2.0"),
+    ("opensource.org/licenses/apache-2.0", "Apache-2.0"),
+    ("opensource.org/licenses/mit", "MIT"),
+    ("opensource.org/licenses/mit-license", "MIT"),
+    ("opensource.org/licenses/bsd-2-clause", "BSD-2-Clause"),
+    ("opensource.org/licenses/bsd-3-clause", "BSD-3-Clause"),
+    ("opensource.org/licenses/bsd-license", "BSD-3-Clause"),
+    ("eclipse.org/org/documents/edl-v10", "BSD-3-Clause"),
+    ("eclipse.org/legal/epl-v10", "EPL-1.0"),
+    ("eclipse.org/legal/epl-2.0", "EPL-2.0"),
+    ("eclipse.org/legal/epl-v20", "EPL-2.0"),
+    ("gnu.org/licenses/old-licenses/gpl-2.0", "GPL-2.0-only"),
+    ("gnu.org/licenses/gpl-3.0", "GPL-3.0-only"),
+    ("gnu.org/licenses/old-licenses/lgpl-2.1", "LGPL-2.1-only"),
+    ("gnu.org/licenses/lgpl-3.0", "LGPL-3.0-only"),
+    ("mozilla.org/mpl/2.0", "MPL-2.0"),
+    ("creativecommons.org/publicdomain/zero/1.0", "CC0-1.0"),
+    ("unlicense.org", "Unlicense"),
+];
+
+/// Words that do not tell licenses apart.
+const STOP_WORDS: &[&str] = &[
+    "the", "license", "licence", "licensed", "version", "v", "software",
+];
+
+/// A `<license>` as declared in a POM.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclaredLicense {
+    pub name: Option<String>,
+    pub url: Option<String>,
+}
+
+impl DeclaredLicense {
+    /// The SPDX identifier of this license, by name or else by URL.
+    pub fn spdx_id(&self) -> Option<&'static str> {
+        self.name
+            .as_deref()
+            .and_then(spdx_id_for_name)
+            .or_else(|| self.url.as_deref().and_then(spdx_id_for_url))
+    }
+
+    /// The SPDX identifier, or the name (or URL) as declared.
+    pub fn display(&self) -> Option<String> {
+        self.spdx_id()
+            .map(String::from)
+            .or_else(|| self.name.clone())
+            .or_else(|| self.url.clone())
+    }
+}
+
+/// The licenses of one artifact.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedLicense {
+    /// Licenses declared by the artifact's POM or its nearest parent that
+    /// declares any. Empty when none does.
+    pub licenses: Vec<DeclaredLicense>,
+    /// The parent POM the licenses come from, as `group:artifact:version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherited_from: Option<String>,
+}
+
+impl ResolvedLicense {
+    /// The licenses as an SPDX expression, such as `Apache-2.0` or
+    /// `EPL-2.0 OR LGPL-2.1-only`: several declared licenses are
+    /// alternatives. Unrecognized licenses appear as declared.
+    pub fn expression(&self) -> Option<String> {
+        let mut parts: Vec<String> = Vec::new();
+        for part in self.licenses.iter().filter_map(DeclaredLicense::display) {
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+        (!parts.is_empty()).then(|| parts.join(" OR "))
+    }
+}
+
+/// The SPDX identifier for a license name such as "The Apache Software
+/// License, Version 2.0".
+pub fn spdx_id_for_name(name: &str) -> Option<&'static str> {
+    let trimmed = name.trim();
+    if let Some(id) = known_ids().find(|id| id.eq_ignore_ascii_case(trimmed)) {
+        return Some(id);
+    }
+    let key = normalize_name(name);
+    NAME_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, id)| *id)
+}
+
+/// The SPDX identifier for a license URL such as
+/// `https://www.apache.org/licenses/LICENSE-2.0.txt`.
+pub fn spdx_id_for_url(url: &str) -> Option<&'static str> {
+    let key = normalize_url(url);
+    URL_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, id)| *id)
+}
+
+fn known_ids() -> impl Iterator<Item = &'static str> {
+    NAME_ALIASES
+        .iter()
+        .chain(URL_ALIASES)
+        .map(|(_, id)| *id)
+        .filter(|id| !id.contains(' '))
+}
+
+/// Lowercase words of a license name without punctuation, filler words,
+/// repeats or trailing `.0`s: "The MIT License (MIT)" becomes `mit`,
+/// "Apache License, Version 2.0" becomes `apache 2`.
+fn normalize_name(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    let mut words: Vec<String> = Vec::new();
+    for raw in lower.split(|c: char| !(c.is_ascii_alphanumeric() || c == '.')) {
+        let mut word = raw.trim_matches('.');
+        // "v2" and "v2.0" are versions.
+        if word.len() > 1
+            && word.starts_with('v')
+            && word[1..].chars().all(|c| c.is_ascii_digit() || c == '.')
+        {
+            word = &word[1..];
+        }
+        while word.len() > 2 && word.ends_with(".0") {
+            word = &word[..word.len() - 2];
+        }
+        if word.is_empty() || STOP_WORDS.contains(&word) || words.iter().any(|w| w == word) {
+            continue;
+        }
+        words.push(word.to_string());
+    }
+    words.join(" ")
+}
+
+/// A license URL without scheme, `www.`, trailing slash or file extension.
+fn normalize_url(url: &str) -> String {
+    let lower = url.trim().to_ascii_lowercase();
+    let rest = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+        .unwrap_or(&lower);
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    let rest = rest.trim_end_matches('/');
+    [".txt", ".html", ".htm", ".php"]
+        .iter()
+        .find_map(|ext| rest.strip_suffix(ext))
+        .unwrap_or(rest)
+        .to_string()
+}
+
+/// Where the resolved license of a coordinate is cached.
+fn cache_path(cache: &LocalCache, group: &str, artifact: &str, version: &str) -> PathBuf {
+    cache
+        .artifact_dir(group, artifact, version)
+        .join(format!("{artifact}-{version}.license.json"))
+}
+
+/// The licenses of `group:artifact:version`, following its parent chain
+/// through the cache and `repos`. `None` when its POM cannot be found.
+///
+/// The result is cached unless the chain could not be followed to the end,
+/// so that a missing parent is retried next time.
+pub async fn resolve(
+    client: &reqwest::Client,
+    repos: &[MavenRepository],
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> miette::Result<Option<ResolvedLicense>> {
+    let cached = cache_path(cache, group, artifact, version);
+    if let Some(resolved) = fs::read_to_string(&cached)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+    {
+        return Ok(Some(resolved));
+    }
+
+    let Some(mut pom) = fetch_pom(client, repos, cache, group, artifact, version).await? else {
+        return Ok(None);
+    };
+    let child_key = format!("{group}:{artifact}:{version}");
+    let mut seen = HashSet::from([child_key.clone()]);
+    let mut inherited_from = None;
+    let mut complete = true;
+    loop {
+        if !pom.licenses.is_empty() {
+            break;
+        }
+        let Some(parent) = pom.parent.clone() else {
+            break;
+        };
+        let key = format!(
+            "{}:{}:{}",
+            parent.group_id, parent.artifact_id, parent.version
+        );
+        if !seen.insert(key.clone()) || seen.len() > MAX_PARENT_DEPTH + 1 {
+            tracing::warn!("Stopped following the parent POMs of {child_key} at {key}");
+            complete = false;
+            break;
+        }
+        match fetch_pom(
+            client,
+            repos,
+            cache,
+            &parent.group_id,
+            &parent.artifact_id,
+            &parent.version,
+        )
+        .await?
+        {
+            Some(parent_pom) => {
+                pom = parent_pom;
+                inherited_from = Some(key);
+            }
+            None => {
+                tracing::warn!("Parent POM {key} of {child_key} not found");
+                complete = false;
+                break;
+            }
+        }
+    }
+
+    let resolved = ResolvedLicense {
+        licenses: pom
+            .licenses
+            .iter()
+            .map(|l| DeclaredLicense {
+                name: l.name.as_deref().map(|n| pom.interpolate(n)),
+                url: l.url.as_deref().map(|u| pom.interpolate(u)),
+            })
+            .collect(),
+        inherited_from: inherited_from.filter(|_| !pom.licenses.is_empty()),
+    };
+    if complete {
+        if let Ok(json) = serde_json::to_string_pretty(&resolved) {
+            if let Err(e) = fs::write(&cached, json) {
+                tracing::debug!("Failed to cache license of {child_key}: {e}");
+            }
+        }
+    }
+    Ok(Some(resolved))
+}
+
+/// A POM from the cache, or else from the first repository serving `group`
+/// that has it.
+async fn fetch_pom(
+    client: &reqwest::Client,
+    repos: &[MavenRepository],
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> miette::Result<Option<Pom>> {
+    if let Some(pom) = cache.get_pom(group, artifact, version) {
+        return Ok(Some(pom));
+    }
+    for repo in repository::for_group(repos, group) {
+        if let Some(pom) = cache
+            .fetch_pom(client, repo, group, artifact, version)
+            .await?
+        {
+            return Ok(Some(pom));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> Option<&'static str> {
+        spdx_id_for_name(name)
+    }
+
+    #[test]
+    fn normalizes_common_names_and_urls() {
+        assert_eq!(
+            named("The Apache Software License, Version 2.0"),
+            Some("Apache-2.0")
+        );
+        assert_eq!(named("Apache License 2.0"), Some("Apache-2.0"));
+        assert_eq!(named("apache-2.0"), Some("Apache-2.0"));
+        assert_eq!(named("The MIT License (MIT)"), Some("MIT"));
+        assert_eq!(named("Eclipse Public License - v 2.0"), Some("EPL-2.0"));
+        assert_eq!(named("Eclipse Public License v1.0"), Some("EPL-1.0"));
+        assert_eq!(
+            named("GNU Lesser General Public License, version 2.1"),
+            Some("LGPL-2.1-only")
+        );
+        assert_eq!(
+            named("GPL2 w/ CPE"),
+            Some("GPL-2.0-only WITH Classpath-exception-2.0")
+        );
+        assert_eq!(named("BSD License"), None);
+        assert_eq!(
+            spdx_id_for_url("https://www.apache.org/licenses/LICENSE-2.0.txt"),
+            Some("Apache-2.0")
+        );
+        assert_eq!(
+            spdx_id_for_url("http://opensource.org/licenses/BSD-3-Clause/"),
+            Some("BSD-3-Clause")
+        );
+
+        let resolved = ResolvedLicense {
+            licenses: vec![
+                DeclaredLicense {
+                    name: Some("BSD License".into()),
+                    url: Some("https://opensource.org/licenses/bsd-license.php".into()),
+                },
+                DeclaredLicense {
+                    name: Some("Some Custom License".into()),
+                    url: None,
+                },
+            ],
+            inherited_from: None,
+        };
+        assert_eq!(
+            resolved.expression().as_deref(),
+            Some("BSD-3-Clause OR Some Custom License")
+        );
+        assert_eq!(ResolvedLicense::default().expression(), None);
+    }
+
+    fn pom(coordinate: &str, parent: Option<&str>, licenses: &str) -> String {
+        let [group, artifact, version]: [&str; 3] = coordinate
+            .split(':')
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        let parent = parent
+            .map(|p| {
+                let [g, a, v]: [&str; 3] = p.split(':').collect::<Vec<_>>().try_into().unwrap();
+                format!(
+                    "<parent><groupId>{g}</groupId><artifactId>{a}</artifactId>\
+                     <version>{v}</version></parent>"
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            "<project>{parent}<groupId>{group}</groupId><artifactId>{artifact}</artifactId>\
+             <version>{version}</version>{licenses}</project>"
+        )
+    }
+
+    #[tokio::test]
+    async fn follows_the_parent_chain_and_caches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let client = reqwest::Client::new();
+        let put = |coordinate: &str, xml: &str| {
+            let [g, a, v]: [&str; 3] = coordinate
+                .split(':')
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            cache.put_pom(g, a, v, xml).unwrap();
+        };
+        put(
+            "org.example:lib:1.0",
+            &pom("org.example:lib:1.0", Some("org.example:parent:3"), ""),
+        );
+        put(
+            "org.example:parent:3",
+            &pom("org.example:parent:3", Some("org.example:root:1"), ""),
+        );
+        put(
+            "org.example:root:1",
+            &pom(
+                "org.example:root:1",
+                None,
+                "<properties><license.name>The Apache License, Version 2.0</license.name>\
+                 </properties><licenses><license><name>${license.name}</name></license>\
+                 </licenses>",
+            ),
+        );
+
+        let resolved = resolve(&client, &[], &cache, "org.example", "lib", "1.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.expression().as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            resolved.inherited_from.as_deref(),
+            Some("org.example:root:1")
+        );
+
+        // The result is now cached per coordinate.
+        fs::remove_dir_all(cache.artifact_dir("org.example", "root", "1")).unwrap();
+        let cached = resolve(&client, &[], &cache, "org.example", "lib", "1.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached, resolved);
+
+        // A broken chain is not cached.
+        put(
+            "org.example:other:2.0",
+            &pom("org.example:other:2.0", Some("org.example:root:1"), ""),
+        );
+        let broken = resolve(&client, &[], &cache, "org.example", "other", "2.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(broken.expression(), None);
+        assert!(!cache_path(&cache, "org.example", "other", "2.0").exists());
+
+        assert_eq!(
+            resolve(&client, &[], &cache, "org.example", "missing", "1.0")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
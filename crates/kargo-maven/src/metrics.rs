@@ -0,0 +1,145 @@
+//! Per-repository request metrics.
+//!
+//! Every request made through [`crate::download`] is counted against the
+//! repository it was sent to: how it ended, how many bytes came back, and
+//! how long it took. `kargo fetch --stats` prints the totals, which shows
+//! slow mirrors and repositories that mostly answer 404.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::repository::MavenRepository;
+
+/// How one request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestResult {
+    /// A successful response with this many body bytes.
+    Ok(u64),
+    /// 404: the repository does not have the file.
+    NotFound,
+    /// A network error, throttling, or an error status.
+    Failed,
+}
+
+/// Totals for one repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoStats {
+    pub requests: u64,
+    pub ok: u64,
+    pub not_found: u64,
+    pub failed: u64,
+    pub bytes: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+}
+
+impl RepoStats {
+    /// Add one request.
+    pub fn record(&mut self, result: RequestResult, latency: Duration) {
+        self.requests += 1;
+        match result {
+            RequestResult::Ok(bytes) => {
+                self.ok += 1;
+                self.bytes += bytes;
+            }
+            RequestResult::NotFound => self.not_found += 1,
+            RequestResult::Failed => self.failed += 1,
+        }
+        self.total_latency += latency;
+        self.max_latency = self.max_latency.max(latency);
+    }
+
+    /// Average time per request.
+    pub fn mean_latency(&self) -> Duration {
+        match u32::try_from(self.requests) {
+            Ok(n) if n > 0 => self.total_latency / n,
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Share of requests that failed, from 0.0 to 1.0.
+    pub fn failure_rate(&self) -> f64 {
+        ratio(self.failed, self.requests)
+    }
+
+    /// Share of requests answered with 404, from 0.0 to 1.0.
+    pub fn not_found_rate(&self) -> f64 {
+        ratio(self.not_found, self.requests)
+    }
+
+    /// Bytes per second over the time spent on this repository's requests.
+    /// Requests overlap, so this is per-connection throughput rather than
+    /// total bandwidth.
+    pub fn throughput(&self) -> Option<f64> {
+        let secs = self.total_latency.as_secs_f64();
+        (self.bytes > 0 && secs > 0.0).then(|| self.bytes as f64 / secs)
+    }
+}
+
+/// Totals for one repository, as reported by [`snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoReport {
+    pub name: String,
+    pub url: String,
+    pub stats: RepoStats,
+}
+
+static STATS: Mutex<BTreeMap<String, RepoReport>> = Mutex::new(BTreeMap::new());
+
+/// Count one request to `repo`.
+pub fn record(repo: &MavenRepository, result: RequestResult, latency: Duration) {
+    let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    stats
+        .entry(repo.name.clone())
+        .or_insert_with(|| RepoReport {
+            name: repo.name.clone(),
+            url: repo.url.clone(),
+            stats: RepoStats::default(),
+        })
+        .stats
+        .record(result, latency);
+}
+
+/// Totals so far for every repository that was sent a request, by name.
+pub fn snapshot() -> Vec<RepoReport> {
+    let stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    stats.values().cloned().collect()
+}
+
+/// Forget all totals.
+pub fn reset() {
+    STATS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_accumulate() {
+        let mut stats = RepoStats::default();
+        stats.record(RequestResult::Ok(2_000), Duration::from_millis(100));
+        stats.record(RequestResult::NotFound, Duration::from_millis(20));
+        stats.record(RequestResult::Failed, Duration::from_millis(180));
+        stats.record(RequestResult::Ok(1_000), Duration::from_millis(100));
+
+        assert_eq!(stats.requests, 4);
+        assert_eq!((stats.ok, stats.not_found, stats.failed), (2, 1, 1));
+        assert_eq!(stats.bytes, 3_000);
+        assert_eq!(stats.mean_latency(), Duration::from_millis(100));
+        assert_eq!(stats.max_latency, Duration::from_millis(180));
+        assert_eq!(stats.failure_rate(), 0.25);
+        assert_eq!(stats.not_found_rate(), 0.25);
+        assert_eq!(stats.throughput(), Some(7_500.0));
+        assert_eq!(RepoStats::default().throughput(), None);
+    }
+}
@@ -1,4 +1,5 @@
-//! POM file parsing: dependency declarations, parent inheritance, property interpolation, BOM imports.
+//! POM file parsing: dependency declarations, parent inheritance, property interpolation, BOM imports,
+//! and profile activation.
 
 use std::collections::BTreeMap;
 
@@ -14,6 +15,10 @@ pub struct Pom {
     pub packaging: Option<String>,
     pub name: Option<String>,
     pub description: Option<String>,
+    /// Project home page (`<url>`).
+    pub url: Option<String>,
+    /// Source repository (`<scm><url>`).
+    pub scm_url: Option<String>,
 
     pub parent: Option<ParentRef>,
     pub properties: BTreeMap<String, String>,
@@ -21,6 +26,20 @@ pub struct Pom {
     pub dependency_management: Vec<PomDependency>,
     pub modules: Vec<String>,
     pub licenses: Vec<PomLicense>,
+    pub profiles: Vec<PomProfile>,
+    /// `<distributionManagement><relocation>`: the artifact moved to other
+    /// coordinates.
+    pub relocation: Option<Relocation>,
+}
+
+/// New coordinates of a relocated artifact. Fields left out keep the
+/// artifact's own value.
+#[derive(Debug, Clone, Default)]
+pub struct Relocation {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+    pub message: Option<String>,
 }
 
 /// Reference to a parent POM.
@@ -52,6 +71,161 @@ pub struct PomExclusion {
     pub artifact_id: Option<String>,
 }
 
+/// A `<profile>` declared in a POM file.
+#[derive(Debug, Clone, Default)]
+pub struct PomProfile {
+    pub id: Option<String>,
+    pub activation: ProfileActivation,
+    pub properties: BTreeMap<String, String>,
+    pub dependencies: Vec<PomDependency>,
+    pub dependency_management: Vec<PomDependency>,
+}
+
+/// The `<activation>` conditions of a profile. All conditions that are set
+/// must match for the profile to activate.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileActivation {
+    pub active_by_default: bool,
+    /// JDK version prefix (`11`, `1.8`, `!1.8`) or range (`[11,)`).
+    pub jdk: Option<String>,
+    pub os: Option<OsActivation>,
+    /// Set when activated by `<property>` or `<file>`, which Kargo does not
+    /// evaluate: such profiles never activate.
+    pub unsupported: bool,
+}
+
+/// The `<os>` condition of a profile activation. Each field may be negated
+/// with a leading `!`.
+#[derive(Debug, Clone, Default)]
+pub struct OsActivation {
+    pub family: Option<String>,
+    pub name: Option<String>,
+    pub arch: Option<String>,
+    pub version: Option<String>,
+}
+
+/// The environment profile activation is evaluated against, in the form
+/// Maven reads it from the JVM's system properties.
+#[derive(Debug, Clone)]
+pub struct ActivationContext {
+    /// `java.version`-style JDK version, e.g. `21` or `1.8`.
+    pub jdk: String,
+    /// Lowercase `os.name`, e.g. `linux`, `mac os x`, `windows`.
+    pub os_name: String,
+    /// Lowercase `os.arch`, e.g. `amd64`, `aarch64`.
+    pub os_arch: String,
+    /// Maven OS families that match, e.g. `["unix"]`, `["mac", "unix"]`.
+    pub os_families: Vec<String>,
+}
+
+impl ActivationContext {
+    /// The host OS and architecture with the given JDK major version.
+    /// Java 8 and older report themselves as `1.<major>`.
+    pub fn host(jdk_major: u32) -> Self {
+        let jdk = if jdk_major <= 8 {
+            format!("1.{jdk_major}")
+        } else {
+            jdk_major.to_string()
+        };
+        let (os_name, families): (&str, &[&str]) = match std::env::consts::OS {
+            "macos" => ("mac os x", &["mac", "unix"]),
+            "windows" => ("windows", &["windows"]),
+            other => (other, &["unix"]),
+        };
+        let os_arch = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "x86" => "x86",
+            other => other,
+        };
+        Self {
+            jdk,
+            os_name: os_name.to_string(),
+            os_arch: os_arch.to_string(),
+            os_families: families.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl ProfileActivation {
+    /// Whether any condition other than `activeByDefault` is declared.
+    fn has_conditions(&self) -> bool {
+        self.jdk.is_some() || self.os.is_some() || self.unsupported
+    }
+
+    /// Whether the declared conditions all hold in `ctx`.
+    /// `false` if there are no conditions.
+    fn conditions_match(&self, ctx: &ActivationContext) -> bool {
+        if !self.has_conditions() || self.unsupported {
+            return false;
+        }
+        let jdk = self
+            .jdk
+            .as_deref()
+            .map_or(true, |j| jdk_matches(j, &ctx.jdk));
+        let os = self.os.as_ref().map_or(true, |os| {
+            negatable(os.family.as_deref(), |f| ctx.os_families.iter().any(|c| c == f))
+                && negatable(os.name.as_deref(), |n| ctx.os_name.starts_with(n))
+                && negatable(os.arch.as_deref(), |a| ctx.os_arch == a)
+                // The OS version is not known without a JVM; never matches.
+                && os.version.is_none()
+        });
+        jdk && os
+    }
+}
+
+/// Evaluate an optional, `!`-negatable, case-insensitive condition.
+fn negatable(value: Option<&str>, matches: impl Fn(&str) -> bool) -> bool {
+    let Some(value) = value else {
+        return true;
+    };
+    let value = value.trim().to_lowercase();
+    match value.strip_prefix('!') {
+        Some(v) => !matches(v),
+        None => matches(&value),
+    }
+}
+
+/// Maven's `<jdk>` activation: a version prefix (`1.8` matches `1.8.0_292`),
+/// optionally negated, or a version range such as `[11,)` or `(,1.8]`.
+fn jdk_matches(spec: &str, jdk: &str) -> bool {
+    let spec = spec.trim();
+    if !spec.starts_with(['[', '(']) {
+        return negatable(Some(spec), |prefix| {
+            jdk == prefix || jdk.starts_with(&format!("{prefix}."))
+        });
+    }
+    let version = jdk_version_parts(jdk);
+    spec.split_inclusive([']', ')'])
+        .map(|r| r.trim_start_matches(',').trim())
+        .filter(|r| !r.is_empty())
+        .any(|range| {
+            let inclusive_low = range.starts_with('[');
+            let inclusive_high = range.ends_with(']');
+            let inner = &range[1..range.len() - 1];
+            let (low, high) = inner.split_once(',').unwrap_or((inner, inner));
+            let low_ok = low.trim().is_empty() || {
+                let low = jdk_version_parts(low.trim());
+                version > low || (inclusive_low && version == low)
+            };
+            let high_ok = high.trim().is_empty() || {
+                let high = jdk_version_parts(high.trim());
+                version < high || (inclusive_high && version == high)
+            };
+            low_ok && high_ok
+        })
+}
+
+/// Numeric components of a JDK version, with the legacy `1.x` prefix folded
+/// so that `1.8` compares as `8`.
+fn jdk_version_parts(version: &str) -> Vec<u32> {
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .take_while(|p| !p.is_empty())
+        .filter_map(|p| p.parse().ok())
+        .collect()
+}
+
 /// A license declared in a POM file.
 #[derive(Debug, Clone)]
 pub struct PomLicense {
@@ -74,6 +248,27 @@ impl Pom {
             .or(self.parent.as_ref().map(|p| p.version.as_str()))
     }
 
+    /// The `(group, artifact, version)` that `group:artifact:version`, the
+    /// coordinates of this POM, was relocated to, if it moved.
+    pub fn relocated_to(
+        &self,
+        group: &str,
+        artifact: &str,
+        version: &str,
+    ) -> Option<(String, String, String)> {
+        let relocation = self.relocation.as_ref()?;
+        let field = |value: &Option<String>, own: &str| match value {
+            Some(v) if !v.trim().is_empty() => self.interpolate(v.trim()),
+            _ => own.to_string(),
+        };
+        let target = (
+            field(&relocation.group_id, group),
+            field(&relocation.artifact_id, artifact),
+            field(&relocation.version, version),
+        );
+        (target != (group.to_string(), artifact.to_string(), version.to_string())).then_some(target)
+    }
+
     /// Resolve `${property}` references in a string using POM properties
     /// and built-in project variables.
     pub fn interpolate(&self, input: &str) -> String {
@@ -138,7 +333,11 @@ impl Pom {
         }
     }
 
-    /// Merge a parent POM's properties and dependency management into this POM.
+    /// Merge a parent POM's properties, dependencies, and dependency
+    /// management into this POM. Entries declared here take precedence.
+    ///
+    /// Call before [`Pom::resolve_properties`] so inherited entries are
+    /// interpolated with this POM's properties, as Maven does.
     pub fn apply_parent(&mut self, parent: &Pom) {
         for (k, v) in &parent.properties {
             self.properties
@@ -160,6 +359,70 @@ impl Pom {
                 self.dependency_management.push(dm.clone());
             }
         }
+        for dep in &parent.dependencies {
+            let overridden = self
+                .dependencies
+                .iter()
+                .any(|d| d.group_id == dep.group_id && d.artifact_id == dep.artifact_id);
+            if !overridden {
+                self.dependencies.push(dep.clone());
+            }
+        }
+    }
+
+    /// Where to look for the parent POM on disk, relative to the directory
+    /// holding this POM: `<relativePath>` (default `../pom.xml`), with
+    /// `pom.xml` appended when it names a directory. `None` if the lookup is
+    /// disabled with an empty `<relativePath/>` or there is no parent.
+    pub fn parent_relative_path(&self) -> Option<&str> {
+        let parent = self.parent.as_ref()?;
+        match parent.relative_path.as_deref() {
+            None => Some("../pom.xml"),
+            Some("") => None,
+            Some(p) => Some(p),
+        }
+    }
+
+    /// Merge the profiles that activate in `ctx` into this POM and return
+    /// their ids. As in Maven, `activeByDefault` profiles apply only when no
+    /// other profile of this POM is activated by its conditions. Profile
+    /// properties and entries override the POM's own.
+    ///
+    /// Call before [`Pom::apply_parent`]: each POM in a parent chain
+    /// activates its own profiles.
+    pub fn apply_profiles(&mut self, ctx: &ActivationContext) -> Vec<String> {
+        let mut active: Vec<usize> = self
+            .profiles
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.activation.conditions_match(ctx))
+            .map(|(i, _)| i)
+            .collect();
+        if active.is_empty() {
+            active = self
+                .profiles
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.activation.active_by_default)
+                .map(|(i, _)| i)
+                .collect();
+        }
+
+        let profiles = std::mem::take(&mut self.profiles);
+        let mut ids = Vec::new();
+        for profile in active.iter().map(|&i| &profiles[i]) {
+            for (k, v) in &profile.properties {
+                self.properties.insert(k.clone(), v.clone());
+            }
+            merge_dependencies(&mut self.dependencies, &profile.dependencies);
+            merge_dependencies(
+                &mut self.dependency_management,
+                &profile.dependency_management,
+            );
+            ids.push(profile.id.clone().unwrap_or_else(|| "<unnamed>".into()));
+        }
+        self.profiles = profiles;
+        ids
     }
 
     /// Look up a version from dependency management for a given group:artifact.
@@ -170,6 +433,24 @@ impl Pom {
             .and_then(|d| d.version.as_deref())
     }
 
+    /// Merge the `<dependencyManagement>` of an imported BOM into this POM.
+    /// Entries managed here, or by a BOM imported earlier, take precedence.
+    /// The BOM's own imports are left out; they are imported separately.
+    /// Call with the BOM's properties resolved, as its entries are
+    /// interpolated with its own properties.
+    pub fn import_bom(&mut self, bom: &Pom) {
+        for dm in &bom.dependency_management {
+            let is_import = dm.scope.as_deref() == Some("import");
+            let dominated = self
+                .dependency_management
+                .iter()
+                .any(|d| d.group_id == dm.group_id && d.artifact_id == dm.artifact_id);
+            if !is_import && !dominated {
+                self.dependency_management.push(dm.clone());
+            }
+        }
+    }
+
     /// Return BOM imports from dependency management
     /// (entries with `scope = "import"` and `type = "pom"`).
     pub fn bom_imports(&self) -> Vec<&PomDependency> {
@@ -182,6 +463,15 @@ impl Pom {
     }
 }
 
+/// Add `overrides` to `deps`, replacing entries with the same group and
+/// artifact.
+fn merge_dependencies(deps: &mut Vec<PomDependency>, overrides: &[PomDependency]) {
+    for dep in overrides {
+        deps.retain(|d| d.group_id != dep.group_id || d.artifact_id != dep.artifact_id);
+        deps.push(dep.clone());
+    }
+}
+
 /// Parse a POM XML string into a `Pom` struct.
 pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
     let mut reader = Reader::from_str(xml);
@@ -196,6 +486,7 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
     let mut current_exclusion: Option<PomExclusion> = None;
     let mut current_parent: Option<ParentRef> = None;
     let mut current_license: Option<PomLicense> = None;
+    let mut current_profile: Option<PomProfile> = None;
     let mut in_dep_mgmt = false;
 
     loop {
@@ -210,7 +501,9 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
 
                 match ctx.as_str() {
                     "project>dependencyManagement>dependencies>dependency"
-                    | "project>dependencies>dependency" => {
+                    | "project>dependencies>dependency"
+                    | "project>profiles>profile>dependencyManagement>dependencies>dependency"
+                    | "project>profiles>profile>dependencies>dependency" => {
                         if ctx.contains("dependencyManagement") {
                             in_dep_mgmt = true;
                         }
@@ -245,6 +538,23 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
                             url: None,
                         });
                     }
+                    "project>profiles>profile" => {
+                        current_profile = Some(PomProfile::default());
+                    }
+                    "project>distributionManagement>relocation" => {
+                        pom.relocation = Some(Relocation::default());
+                    }
+                    "project>profiles>profile>activation>os" => {
+                        if let Some(ref mut profile) = current_profile {
+                            profile.activation.os = Some(OsActivation::default());
+                        }
+                    }
+                    "project>profiles>profile>activation>property"
+                    | "project>profiles>profile>activation>file" => {
+                        if let Some(ref mut profile) = current_profile {
+                            profile.activation.unsupported = true;
+                        }
+                    }
                     _ => {
                         // properties are children of <project><properties>
                         if depth == 3 && path.get(1).map(|s| s.as_str()) == Some("properties") {
@@ -256,6 +566,15 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
             Ok(Event::Text(ref e)) => {
                 text_buf = e.unescape().unwrap_or_default().to_string();
             }
+            // `<relativePath/>` disables the filesystem lookup of the parent.
+            Ok(Event::Empty(ref e))
+                if e.name().as_ref() == b"relativePath"
+                    && path_context(&path) == "project>parent" =>
+            {
+                if let Some(ref mut parent) = current_parent {
+                    parent.relative_path = Some(String::new());
+                }
+            }
             Ok(Event::End(ref e)) => {
                 let _tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
                 let ctx = path_context(&path);
@@ -307,14 +626,29 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
                         }
                     }
 
-                    if ctx == "project>dependencies>dependency"
-                        || ctx == "project>dependencyManagement>dependencies>dependency"
+                    if let Some(section) = ctx
+                        .strip_prefix("project>")
+                        .and_then(|c| c.strip_suffix("dependencies>dependency"))
+                        .filter(|s| {
+                            matches!(
+                                *s,
+                                "" | "dependencyManagement>"
+                                    | "profiles>profile>"
+                                    | "profiles>profile>dependencyManagement>"
+                            )
+                        })
                     {
                         if let Some(dep) = current_dep.take() {
+                            let (deps, managed) = match current_profile.as_mut() {
+                                Some(p) if section.starts_with("profiles>") => {
+                                    (&mut p.dependencies, &mut p.dependency_management)
+                                }
+                                _ => (&mut pom.dependencies, &mut pom.dependency_management),
+                            };
                             if in_dep_mgmt {
-                                pom.dependency_management.push(dep);
+                                managed.push(dep);
                             } else {
-                                pom.dependencies.push(dep);
+                                deps.push(dep);
                             }
                         }
                         in_dep_mgmt = false;
@@ -361,6 +695,41 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
                     }
                 }
 
+                // Profile fields
+                if let Some(ref mut profile) = current_profile {
+                    let field = path.last().map(|s| s.as_str()).unwrap_or_default();
+                    match ctx.strip_prefix("project>profiles>profile>") {
+                        Some("id") => profile.id = Some(text_buf.clone()),
+                        Some("activation>activeByDefault") => {
+                            profile.activation.active_by_default = text_buf.trim() == "true";
+                        }
+                        Some("activation>jdk") => profile.activation.jdk = Some(text_buf.clone()),
+                        Some(c) if c.starts_with("activation>os>") => {
+                            if let Some(ref mut os) = profile.activation.os {
+                                let value = Some(text_buf.clone());
+                                match field {
+                                    "family" => os.family = value,
+                                    "name" => os.name = value,
+                                    "arch" => os.arch = value,
+                                    "version" => os.version = value,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Some(c) if depth == 5 && c.starts_with("properties>") => {
+                            profile
+                                .properties
+                                .insert(field.to_string(), text_buf.clone());
+                        }
+                        _ => {}
+                    }
+                    if ctx == "project>profiles>profile" {
+                        if let Some(profile) = current_profile.take() {
+                            pom.profiles.push(profile);
+                        }
+                    }
+                }
+
                 // Top-level project fields
                 if depth == 2 {
                     match path.last().map(|s| s.as_str()) {
@@ -370,6 +739,23 @@ pub fn parse_pom(xml: &str) -> miette::Result<Pom> {
                         Some("packaging") => pom.packaging = Some(text_buf.clone()),
                         Some("name") => pom.name = Some(text_buf.clone()),
                         Some("description") => pom.description = Some(text_buf.clone()),
+                        Some("url") => pom.url = Some(text_buf.clone()),
+                        _ => {}
+                    }
+                }
+                if ctx == "project>scm>url" {
+                    pom.scm_url = Some(text_buf.clone());
+                }
+                if let (Some(field), Some(relocation)) = (
+                    ctx.strip_prefix("project>distributionManagement>relocation>"),
+                    pom.relocation.as_mut(),
+                ) {
+                    let value = Some(text_buf.clone());
+                    match field {
+                        "groupId" => relocation.group_id = value,
+                        "artifactId" => relocation.artifact_id = value,
+                        "version" => relocation.version = value,
+                        "message" => relocation.message = value,
                         _ => {}
                     }
                 }
@@ -441,6 +827,56 @@ mod tests {
         assert_eq!(pom.packaging.as_deref(), Some("jar"));
         assert_eq!(pom.dependencies.len(), 2);
         assert_eq!(pom.properties.get("kotlin.version").unwrap(), "2.3.0");
+        assert!(pom.url.is_none() && pom.scm_url.is_none());
+    }
+
+    #[test]
+    fn parse_project_urls() {
+        let xml = r#"<project>
+    <groupId>org.example</groupId>
+    <artifactId>lib</artifactId>
+    <version>1.0</version>
+    <url>https://example.org/lib</url>
+    <licenses><license><url>https://example.org/license</url></license></licenses>
+    <scm>
+        <connection>scm:git:git://github.com/example/lib.git</connection>
+        <url>https://github.com/example/lib</url>
+    </scm>
+</project>"#;
+        let pom = parse_pom(xml).unwrap();
+        assert_eq!(pom.url.as_deref(), Some("https://example.org/lib"));
+        assert_eq!(
+            pom.scm_url.as_deref(),
+            Some("https://github.com/example/lib")
+        );
+    }
+
+    #[test]
+    fn parse_relocation() {
+        let xml = r#"<project>
+    <groupId>old.group</groupId>
+    <artifactId>lib</artifactId>
+    <version>1.2</version>
+    <distributionManagement>
+        <relocation>
+            <groupId>new.group</groupId>
+            <message>lib moved to new.group</message>
+        </relocation>
+    </distributionManagement>
+</project>"#;
+        let pom = parse_pom(xml).unwrap();
+        assert_eq!(
+            pom.relocation.as_ref().unwrap().message.as_deref(),
+            Some("lib moved to new.group")
+        );
+        assert_eq!(
+            pom.relocated_to("old.group", "lib", "1.2"),
+            Some(("new.group".into(), "lib".into(), "1.2".into()))
+        );
+        assert_eq!(
+            parse_pom(SIMPLE_POM).unwrap().relocated_to("a", "b", "1"),
+            None
+        );
     }
 
     #[test]
@@ -512,6 +948,170 @@ mod tests {
         let p = pom.parent.as_ref().unwrap();
         assert_eq!(p.group_id, "org.example");
         assert_eq!(p.version, "2.0.0");
+        assert_eq!(pom.parent_relative_path(), Some("../pom.xml"));
+    }
+
+    #[test]
+    fn parent_inheritance_is_interpolated_with_child_properties() {
+        let parent = parse_pom(
+            r#"<project>
+    <groupId>org.example</groupId>
+    <artifactId>parent-pom</artifactId>
+    <version>2.0.0</version>
+    <properties>
+        <jackson.version>2.15.0</jackson.version>
+        <slf4j.version>2.0.9</slf4j.version>
+    </properties>
+    <dependencyManagement>
+        <dependencies>
+            <dependency>
+                <groupId>com.fasterxml.jackson.core</groupId>
+                <artifactId>jackson-core</artifactId>
+                <version>${jackson.version}</version>
+            </dependency>
+        </dependencies>
+    </dependencyManagement>
+    <dependencies>
+        <dependency>
+            <groupId>org.slf4j</groupId>
+            <artifactId>slf4j-api</artifactId>
+            <version>${slf4j.version}</version>
+        </dependency>
+    </dependencies>
+</project>"#,
+        )
+        .unwrap();
+        let mut child = parse_pom(
+            r#"<project>
+    <parent>
+        <groupId>org.example</groupId>
+        <artifactId>parent-pom</artifactId>
+        <version>2.0.0</version>
+        <relativePath/>
+    </parent>
+    <artifactId>child</artifactId>
+    <properties>
+        <jackson.version>2.17.1</jackson.version>
+    </properties>
+</project>"#,
+        )
+        .unwrap();
+        assert_eq!(child.parent_relative_path(), None);
+
+        child.apply_parent(&parent);
+        child.resolve_properties();
+        assert_eq!(
+            child.managed_version("com.fasterxml.jackson.core", "jackson-core"),
+            Some("2.17.1")
+        );
+        assert_eq!(child.dependencies.len(), 1);
+        assert_eq!(child.dependencies[0].version.as_deref(), Some("2.0.9"));
+        assert_eq!(parent.parent_relative_path(), None);
+    }
+
+    const PROFILES_POM: &str = r#"<project>
+    <groupId>org.example</groupId>
+    <artifactId>lib</artifactId>
+    <version>1.0</version>
+    <properties>
+        <impl.version>1.0</impl.version>
+    </properties>
+    <profiles>
+        <profile>
+            <id>default</id>
+            <activation><activeByDefault>true</activeByDefault></activation>
+            <dependencies>
+                <dependency>
+                    <groupId>org.example</groupId>
+                    <artifactId>fallback</artifactId>
+                    <version>1.0</version>
+                </dependency>
+            </dependencies>
+        </profile>
+        <profile>
+            <id>jdk11</id>
+            <activation><jdk>[11,)</jdk></activation>
+            <properties>
+                <impl.version>2.0</impl.version>
+            </properties>
+            <dependencies>
+                <dependency>
+                    <groupId>org.example</groupId>
+                    <artifactId>impl</artifactId>
+                    <version>${impl.version}</version>
+                </dependency>
+            </dependencies>
+        </profile>
+        <profile>
+            <id>windows</id>
+            <activation><os><family>windows</family></os></activation>
+            <dependencyManagement>
+                <dependencies>
+                    <dependency>
+                        <groupId>org.example</groupId>
+                        <artifactId>native</artifactId>
+                        <version>3.0</version>
+                    </dependency>
+                </dependencies>
+            </dependencyManagement>
+        </profile>
+        <profile>
+            <id>release</id>
+            <activation><property><name>release</name></property></activation>
+        </profile>
+    </profiles>
+</project>"#;
+
+    fn context(jdk: u32, family: &str) -> ActivationContext {
+        ActivationContext {
+            os_families: vec![family.to_string()],
+            ..ActivationContext::host(jdk)
+        }
+    }
+
+    #[test]
+    fn profile_parsing() {
+        let pom = parse_pom(PROFILES_POM).unwrap();
+        assert!(pom.dependencies.is_empty());
+        assert_eq!(pom.profiles.len(), 4);
+        assert!(pom.profiles[0].activation.active_by_default);
+        assert_eq!(pom.profiles[1].activation.jdk.as_deref(), Some("[11,)"));
+        assert_eq!(pom.profiles[1].properties["impl.version"], "2.0");
+        assert_eq!(pom.profiles[1].dependencies[0].artifact_id, "impl");
+        let os = pom.profiles[2].activation.os.as_ref().unwrap();
+        assert_eq!(os.family.as_deref(), Some("windows"));
+        assert_eq!(pom.profiles[2].dependency_management.len(), 1);
+        assert!(pom.profiles[3].activation.unsupported);
+    }
+
+    #[test]
+    fn profile_activation() {
+        let mut pom = parse_pom(PROFILES_POM).unwrap();
+        assert_eq!(
+            pom.apply_profiles(&context(21, "windows")),
+            ["jdk11", "windows"]
+        );
+        pom.resolve_properties();
+        assert_eq!(pom.dependencies.len(), 1);
+        assert_eq!(pom.dependencies[0].version.as_deref(), Some("2.0"));
+        assert_eq!(pom.managed_version("org.example", "native"), Some("3.0"));
+
+        // Nothing else activates on JDK 8: the default profile applies.
+        let mut pom = parse_pom(PROFILES_POM).unwrap();
+        assert_eq!(pom.apply_profiles(&context(8, "unix")), ["default"]);
+        assert_eq!(pom.dependencies[0].artifact_id, "fallback");
+    }
+
+    #[test]
+    fn jdk_activation_specs() {
+        assert!(jdk_matches("1.8", "1.8"));
+        assert!(jdk_matches("1", "1.8"));
+        assert!(!jdk_matches("1.8", "11"));
+        assert!(jdk_matches("!1.8", "11"));
+        assert!(jdk_matches("[1.8,11)", "1.8"));
+        assert!(!jdk_matches("[1.8,11)", "11"));
+        assert!(jdk_matches("(,1.8],[17,)", "21"));
+        assert!(!jdk_matches("(,1.8],[17,)", "11"));
     }
 
     #[test]
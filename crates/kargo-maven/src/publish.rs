@@ -1,22 +1,452 @@
-//! Publishing artifacts to Maven repositories with GPG signing.
+//! Publishing artifacts to Maven repositories.
 //!
-//! This module will be implemented in Phase 8 (Packaging & Distribution).
+//! [`pom_xml`] writes the POM of a publication. [`publish_artifact`] uploads
+//! the files of a publication with their checksums in the standard layout,
+//! as `mvn deploy` does, and adds the version to the artifact's
+//! `maven-metadata.xml`. [`bundle`] packs the same files into the zip the
+//! Sonatype Central Portal accepts. Signing is left to the caller: detached
+//! `.asc` signatures are published like any other file, without checksums.
 
+use std::io::Write;
+use std::path::PathBuf;
+
+use kargo_util::xml;
+use reqwest::Client;
+
+use crate::checksum;
+use crate::metadata::{self, MavenMetadata};
+use crate::pom::PomDependency;
 use crate::repository::MavenRepository;
+use crate::{auth, download};
+
+/// A file of a publication: the main JAR, or one with a classifier such as
+/// `sources` or `javadoc`, the POM, or the signature of one of those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishFile {
+    pub classifier: Option<String>,
+    pub path: PathBuf,
+}
+
+impl PublishFile {
+    /// The extension the file is published with: `jar`, `pom`, or `jar.asc`
+    /// for a signature.
+    pub fn extension(&self) -> String {
+        let name = self
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let ext = |name: &str| name.rsplit_once('.').map(|(_, e)| e.to_string());
+        match name.strip_suffix(".asc") {
+            Some(signed) => format!("{}.asc", ext(signed).unwrap_or_default()),
+            None => ext(&name).unwrap_or_default(),
+        }
+    }
+
+    /// The file's name in the repository, e.g. `lib-1.0-sources.jar`.
+    pub fn remote_name(&self, artifact: &str, version: &str) -> String {
+        match &self.classifier {
+            Some(c) => format!("{artifact}-{version}-{c}.{}", self.extension()),
+            None => format!("{artifact}-{version}.{}", self.extension()),
+        }
+    }
+
+    /// Whether this is a detached signature, which gets no checksums.
+    pub fn is_signature(&self) -> bool {
+        self.extension().ends_with(".asc")
+    }
+}
+
+/// What the POM of a publication describes.
+#[derive(Debug, Clone, Default)]
+pub struct PomProject {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// Project and source repository URL.
+    pub url: Option<String>,
+    /// License name, e.g. an SPDX identifier.
+    pub license: Option<String>,
+    /// Developers, as `Name` or `Name <email>`.
+    pub authors: Vec<String>,
+    pub dependencies: Vec<PomDependency>,
+}
+
+/// Render the POM of `project`.
+pub fn pom_xml(project: &PomProject) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <project xmlns=\"http://maven.apache.org/POM/4.0.0\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://maven.apache.org/POM/4.0.0 \
+         https://maven.apache.org/xsd/maven-4.0.0.xsd\">\n  \
+         <modelVersion>4.0.0</modelVersion>\n",
+    );
+    let element = |xml: &mut String, indent: &str, name: &str, value: &str| {
+        xml.push_str(&format!(
+            "{indent}<{name}>{}</{name}>\n",
+            xml::escape(value)
+        ));
+    };
+    element(&mut xml, "  ", "groupId", &project.group);
+    element(&mut xml, "  ", "artifactId", &project.artifact);
+    element(&mut xml, "  ", "version", &project.version);
+    element(&mut xml, "  ", "packaging", "jar");
+    element(&mut xml, "  ", "name", &project.artifact);
+    if let Some(description) = &project.description {
+        element(&mut xml, "  ", "description", description);
+    }
+    if let Some(url) = &project.url {
+        element(&mut xml, "  ", "url", url);
+    }
+    if let Some(license) = &project.license {
+        xml.push_str("  <licenses>\n    <license>\n");
+        element(&mut xml, "      ", "name", license);
+        xml.push_str("    </license>\n  </licenses>\n");
+    }
+    if !project.authors.is_empty() {
+        xml.push_str("  <developers>\n");
+        for author in &project.authors {
+            let (name, email) = match author.split_once('<') {
+                Some((name, rest)) => (name.trim(), rest.strip_suffix('>').map(str::trim)),
+                None => (author.trim(), None),
+            };
+            xml.push_str("    <developer>\n");
+            element(&mut xml, "      ", "name", name);
+            if let Some(email) = email {
+                element(&mut xml, "      ", "email", email);
+            }
+            xml.push_str("    </developer>\n");
+        }
+        xml.push_str("  </developers>\n");
+    }
+    if let Some(url) = &project.url {
+        xml.push_str("  <scm>\n");
+        element(&mut xml, "    ", "url", url);
+        element(&mut xml, "    ", "connection", &format!("scm:git:{url}"));
+        xml.push_str("  </scm>\n");
+    }
+    if !project.dependencies.is_empty() {
+        xml.push_str("  <dependencies>\n");
+        for dep in &project.dependencies {
+            xml.push_str("    <dependency>\n");
+            element(&mut xml, "      ", "groupId", &dep.group_id);
+            element(&mut xml, "      ", "artifactId", &dep.artifact_id);
+            if let Some(version) = &dep.version {
+                element(&mut xml, "      ", "version", version);
+            }
+            if let Some(classifier) = &dep.classifier {
+                element(&mut xml, "      ", "classifier", classifier);
+            }
+            if let Some(scope) = &dep.scope {
+                element(&mut xml, "      ", "scope", scope);
+            }
+            if dep.optional {
+                element(&mut xml, "      ", "optional", "true");
+            }
+            if !dep.exclusions.is_empty() {
+                xml.push_str("      <exclusions>\n");
+                for exclusion in &dep.exclusions {
+                    xml.push_str("        <exclusion>\n");
+                    element(&mut xml, "          ", "groupId", &exclusion.group_id);
+                    let artifact = exclusion.artifact_id.as_deref().unwrap_or("*");
+                    element(&mut xml, "          ", "artifactId", artifact);
+                    xml.push_str("        </exclusion>\n");
+                }
+                xml.push_str("      </exclusions>\n");
+            }
+            xml.push_str("    </dependency>\n");
+        }
+        xml.push_str("  </dependencies>\n");
+    }
+    xml.push_str("</project>\n");
+    xml
+}
 
-/// Publish a JAR + POM + sources to a Maven repository.
-///
-/// # Not yet implemented
-///
-/// This is a Phase 8 feature. Calling this will return an error.
+/// Upload `files` of `group:artifact:version` to `repo` with their MD5,
+/// SHA-1 and SHA-256 checksums, then list the version in the artifact's
+/// `maven-metadata.xml`.
 pub async fn publish_artifact(
-    _repo: &MavenRepository,
-    _group: &str,
-    _artifact: &str,
-    _version: &str,
+    repo: &MavenRepository,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    files: &[PublishFile],
 ) -> miette::Result<()> {
-    Err(kargo_util::errors::KargoError::Generic {
-        message: "Publishing is not yet implemented (planned for Phase 8)".to_string(),
+    let client = download::build_client()?;
+    for file in files {
+        let data = std::fs::read(&file.path).map_err(kargo_util::errors::KargoError::Io)?;
+        let url = repo.file_url(
+            group,
+            artifact,
+            version,
+            &file.remote_name(artifact, version),
+        );
+        upload_with_checksums(&client, repo, &url, data, !file.is_signature()).await?;
+    }
+
+    let metadata_url = repo.metadata_url(group, artifact);
+    let existing = match download::download_text(&client, repo, &metadata_url).await? {
+        Some(xml) => metadata::parse_metadata(&xml)?,
+        None => MavenMetadata::default(),
+    };
+    let xml = metadata_xml(group, artifact, version, existing);
+    upload_with_checksums(&client, repo, &metadata_url, xml.into_bytes(), true).await
+}
+
+/// The Central Portal upload bundle for `files`: each file at its path in
+/// the Maven layout, with `.md5` and `.sha1` checksums for all but the
+/// signatures.
+pub fn bundle(
+    group: &str,
+    artifact: &str,
+    version: &str,
+    files: &[PublishFile],
+) -> miette::Result<Vec<u8>> {
+    let zip_err = |e: zip::result::ZipError| kargo_util::errors::KargoError::Generic {
+        message: format!("Failed to write the publication bundle: {e}"),
+    };
+    let dir = MavenRepository::coordinate_path(group, artifact, version);
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for file in files {
+        let data = std::fs::read(&file.path).map_err(kargo_util::errors::KargoError::Io)?;
+        let name = format!("{dir}/{}", file.remote_name(artifact, version));
+        let mut entries = vec![(name.clone(), data.clone())];
+        if !file.is_signature() {
+            entries.push((format!("{name}.md5"), checksum::hex_md5(&data).into_bytes()));
+            entries.push((
+                format!("{name}.sha1"),
+                checksum::hex_sha1(&data).into_bytes(),
+            ));
+        }
+        for (name, content) in entries {
+            zip.start_file(name, options).map_err(zip_err)?;
+            zip.write_all(&content)
+                .map_err(kargo_util::errors::KargoError::Io)?;
+        }
+    }
+    Ok(zip.finish().map_err(zip_err)?.into_inner())
+}
+
+/// `existing` artifact metadata with `version` added and made the latest
+/// release (unless it is a snapshot).
+fn metadata_xml(group: &str, artifact: &str, version: &str, existing: MavenMetadata) -> String {
+    let mut versions = existing.versions;
+    if !versions.iter().any(|v| v == version) {
+        versions.push(version.to_string());
+    }
+    let release = if version.ends_with("-SNAPSHOT") {
+        existing.release
+    } else {
+        Some(version.to_string())
+    };
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<metadata>\n  \
+         <groupId>{}</groupId>\n  <artifactId>{}</artifactId>\n  <versioning>\n    \
+         <latest>{}</latest>\n",
+        xml::escape(group),
+        xml::escape(artifact),
+        xml::escape(version)
+    );
+    if let Some(release) = release {
+        xml.push_str(&format!(
+            "    <release>{}</release>\n",
+            xml::escape(&release)
+        ));
+    }
+    xml.push_str("    <versions>\n");
+    for v in &versions {
+        xml.push_str(&format!("      <version>{}</version>\n", xml::escape(v)));
+    }
+    xml.push_str("    </versions>\n  </versioning>\n</metadata>\n");
+    xml
+}
+
+/// PUT `data` at `url`, followed by its checksum files if `checksums`.
+async fn upload_with_checksums(
+    client: &Client,
+    repo: &MavenRepository,
+    url: &str,
+    data: Vec<u8>,
+    checksums: bool,
+) -> miette::Result<()> {
+    let sums = [
+        ("md5", checksum::hex_md5(&data)),
+        ("sha1", checksum::hex_sha1(&data)),
+        ("sha256", checksum::hex_sha256(&data)),
+    ];
+    put(client, repo, url, data).await?;
+    if checksums {
+        for (ext, sum) in sums {
+            put(client, repo, &format!("{url}.{ext}"), sum.into_bytes()).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn put(
+    client: &Client,
+    repo: &MavenRepository,
+    url: &str,
+    body: Vec<u8>,
+) -> miette::Result<()> {
+    let resp = auth::apply_auth(client.put(url), repo)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| kargo_util::errors::KargoError::Network {
+            message: format!("Upload to {url} failed: {e}"),
+        })?;
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let hint = match status.as_u16() {
+        401 | 403 => format!(
+            "; check the username and password of [repositories] {}",
+            repo.name
+        ),
+        400 | 409 => "; the repository may not accept a version that is already published".into(),
+        _ => String::new(),
+    };
+    Err(kargo_util::errors::KargoError::Network {
+        message: format!("HTTP {status} uploading {url}{hint}"),
     }
     .into())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::pom::{parse_pom, PomExclusion};
+
+    fn file(classifier: Option<&str>, path: &str) -> PublishFile {
+        PublishFile {
+            classifier: classifier.map(String::from),
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn files_are_named_by_classifier_and_extension() {
+        assert_eq!(
+            file(None, "out/app-1.0.jar").remote_name("lib", "1.0"),
+            "lib-1.0.jar"
+        );
+        assert_eq!(
+            file(Some("sources"), "out/app-1.0-sources.jar.asc").remote_name("lib", "1.0"),
+            "lib-1.0-sources.jar.asc"
+        );
+        assert!(file(None, "lib.pom.asc").is_signature());
+        assert!(!file(None, "lib.pom").is_signature());
+    }
+
+    #[test]
+    fn pom_describes_the_project_and_its_dependencies() {
+        let project = PomProject {
+            group: "com.example".into(),
+            artifact: "lib".into(),
+            version: "1.0.0".into(),
+            description: Some("Parsing & printing".into()),
+            url: Some("https://github.com/example/lib".into()),
+            license: Some("MIT".into()),
+            authors: vec!["Ana <ana@example.com>".into(), "Ben".into()],
+            dependencies: vec![PomDependency {
+                group_id: "com.squareup.okio".into(),
+                artifact_id: "okio".into(),
+                version: Some("3.9.0".into()),
+                scope: Some("runtime".into()),
+                optional: false,
+                classifier: None,
+                type_: None,
+                exclusions: vec![PomExclusion {
+                    group_id: "org.jetbrains".into(),
+                    artifact_id: None,
+                }],
+            }],
+        };
+        let xml = pom_xml(&project);
+        assert!(xml.contains("<description>Parsing &amp; printing</description>"));
+        assert!(xml.contains("<email>ana@example.com</email>"));
+        assert!(xml.contains("<connection>scm:git:https://github.com/example/lib</connection>"));
+        assert!(xml.contains("<artifactId>*</artifactId>"));
+
+        let pom = parse_pom(&xml).unwrap();
+        assert_eq!(pom.artifact_id.as_deref(), Some("lib"));
+        assert_eq!(pom.dependencies.len(), 1);
+        assert_eq!(pom.dependencies[0].scope.as_deref(), Some("runtime"));
+    }
+
+    #[test]
+    fn metadata_lists_the_new_release() {
+        let existing = MavenMetadata {
+            versions: vec!["1.0.0".into()],
+            release: Some("1.0.0".into()),
+            ..Default::default()
+        };
+        let meta = metadata::parse_metadata(&metadata_xml(
+            "com.example",
+            "lib",
+            "1.1.0",
+            existing.clone(),
+        ))
+        .unwrap();
+        assert_eq!(meta.versions, ["1.0.0", "1.1.0"]);
+        assert_eq!(meta.release.as_deref(), Some("1.1.0"));
+
+        let meta = metadata::parse_metadata(&metadata_xml(
+            "com.example",
+            "lib",
+            "1.1.0-SNAPSHOT",
+            existing,
+        ))
+        .unwrap();
+        assert_eq!(meta.release.as_deref(), Some("1.0.0"));
+        assert_eq!(meta.latest.as_deref(), Some("1.1.0-SNAPSHOT"));
+    }
+
+    #[test]
+    fn bundle_has_checksums_for_all_but_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("app.jar");
+        let sig = tmp.path().join("app.jar.asc");
+        std::fs::write(&jar, b"jar").unwrap();
+        std::fs::write(&sig, b"sig").unwrap();
+        let files = [
+            PublishFile {
+                classifier: None,
+                path: jar,
+            },
+            PublishFile {
+                classifier: None,
+                path: sig,
+            },
+        ];
+        let bytes = bundle("com.example", "lib", "1.0", &files).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        let dir = "com/example/lib/1.0";
+        let mut expected = vec![
+            format!("{dir}/lib-1.0.jar"),
+            format!("{dir}/lib-1.0.jar.md5"),
+            format!("{dir}/lib-1.0.jar.sha1"),
+            format!("{dir}/lib-1.0.jar.asc"),
+        ];
+        let mut names: Vec<String> = names.into_iter().map(String::from).collect();
+        names.sort();
+        expected.sort();
+        assert_eq!(names, expected);
+
+        let mut sha1 = String::new();
+        archive
+            .by_name(&format!("{dir}/lib-1.0.jar.sha1"))
+            .unwrap()
+            .read_to_string(&mut sha1)
+            .unwrap();
+        assert_eq!(sha1, checksum::hex_sha1(b"jar"));
+    }
+}
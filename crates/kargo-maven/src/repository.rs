@@ -1,6 +1,6 @@
 //! Maven repository abstraction: URL layout, source types, configuration.
 
-use kargo_core::manifest::RepositoryEntry;
+use kargo_core::manifest::{group_matches, RepositoryEntry};
 
 /// Maven Central base URL.
 pub const MAVEN_CENTRAL_URL: &str = "https://repo.maven.apache.org/maven2";
@@ -15,6 +15,10 @@ pub struct MavenRepository {
     pub url: String,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Group patterns this repository is limited to; empty means all.
+    pub include_groups: Vec<String>,
+    /// Group patterns this repository is never queried for.
+    pub exclude_groups: Vec<String>,
 }
 
 impl MavenRepository {
@@ -26,17 +30,23 @@ impl MavenRepository {
                 url: url.trim_end_matches('/').to_string(),
                 username: None,
                 password: None,
+                include_groups: Vec::new(),
+                exclude_groups: Vec::new(),
             },
             RepositoryEntry::Detailed {
                 url,
                 username,
                 password,
+                include_groups,
+                exclude_groups,
                 ..
             } => Self {
                 name: name.to_string(),
                 url: url.trim_end_matches('/').to_string(),
                 username: username.clone(),
                 password: password.clone(),
+                include_groups: include_groups.clone(),
+                exclude_groups: exclude_groups.clone(),
             },
         }
     }
@@ -48,6 +58,8 @@ impl MavenRepository {
             url: MAVEN_CENTRAL_URL.to_string(),
             username: None,
             password: None,
+            include_groups: Vec::new(),
+            exclude_groups: Vec::new(),
         }
     }
 
@@ -58,6 +70,8 @@ impl MavenRepository {
             url: GOOGLE_MAVEN_URL.to_string(),
             username: None,
             password: None,
+            include_groups: Vec::new(),
+            exclude_groups: Vec::new(),
         }
     }
 
@@ -100,6 +114,21 @@ impl MavenRepository {
         self.file_url(group, artifact, version, &filename)
     }
 
+    /// URL to the Kotlin library (`.klib`) file for a given coordinate.
+    ///
+    /// Non-JVM variants of multiplatform libraries (e.g. `*-wasm-js`) publish
+    /// a klib instead of a JAR.
+    pub fn klib_url(&self, group: &str, artifact: &str, version: &str) -> String {
+        let filename = format!("{artifact}-{version}.klib");
+        self.file_url(group, artifact, version, &filename)
+    }
+
+    /// URL to an AAR (Android library) artifact.
+    pub fn aar_url(&self, group: &str, artifact: &str, version: &str) -> String {
+        let filename = format!("{artifact}-{version}.aar");
+        self.file_url(group, artifact, version, &filename)
+    }
+
     /// URL to the `maven-metadata.xml` at the artifact level (version listing).
     pub fn metadata_url(&self, group: &str, artifact: &str) -> String {
         format!(
@@ -125,12 +154,28 @@ impl MavenRepository {
         self.file_url(group, artifact, version, &filename)
     }
 
+    /// Whether coordinates in `group` may be requested from this repository,
+    /// per its `include-groups` and `exclude-groups`.
+    pub fn serves(&self, group: &str) -> bool {
+        (self.include_groups.is_empty()
+            || self.include_groups.iter().any(|p| group_matches(p, group)))
+            && !self.exclude_groups.iter().any(|p| group_matches(p, group))
+    }
+
     /// Whether this repository has authentication configured.
     pub fn has_auth(&self) -> bool {
         self.username.is_some() || self.password.is_some()
     }
 }
 
+/// The repositories in `repos` that may be queried for `group`, in order.
+pub fn for_group<'a>(
+    repos: &'a [MavenRepository],
+    group: &'a str,
+) -> impl Iterator<Item = &'a MavenRepository> {
+    repos.iter().filter(move |r| r.serves(group))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,9 +239,41 @@ mod tests {
             auth: None,
             username: Some("user".to_string()),
             password: Some("pass".to_string()),
+            include_groups: vec![],
+            exclude_groups: vec![],
         };
         let repo = MavenRepository::from_entry("nexus", &entry);
         assert!(repo.has_auth());
         assert_eq!(repo.username.as_deref(), Some("user"));
     }
+
+    #[test]
+    fn serves_honours_group_filters() {
+        let entry = RepositoryEntry::Detailed {
+            url: "https://nexus.co/maven".to_string(),
+            auth: None,
+            username: None,
+            password: None,
+            include_groups: vec!["com.mycorp.*".to_string()],
+            exclude_groups: vec!["com.mycorp.legacy".to_string()],
+        };
+        let nexus = MavenRepository::from_entry("nexus", &entry);
+        assert!(nexus.serves("com.mycorp"));
+        assert!(nexus.serves("com.mycorp.billing"));
+        assert!(!nexus.serves("com.mycorp.legacy"));
+        assert!(!nexus.serves("com.mycorpx"));
+        assert!(!nexus.serves("org.jetbrains.kotlin"));
+        assert!(MavenRepository::maven_central().serves("com.mycorp"));
+    }
+
+    #[test]
+    fn for_group_skips_filtered_repositories() {
+        let mut central = MavenRepository::maven_central();
+        central.exclude_groups = vec!["com.mycorp.*".to_string()];
+        let repos = [central, MavenRepository::google()];
+        let names: Vec<&str> = for_group(&repos, "com.mycorp.app")
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, ["google"]);
+    }
 }
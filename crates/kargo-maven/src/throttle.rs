@@ -0,0 +1,437 @@
+//! Per-host limits for repository requests.
+//!
+//! Every request made through [`crate::download`] first takes a
+//! [`HostPermit`]. A host admits at most its concurrency limit of requests
+//! at once and spaces them by its politeness delay; all hosts together are
+//! capped by `[network] max-concurrency`. With auto-tuning the per-host
+//! limit is halved when the host answers 429/503 or its latency climbs well
+//! above the best seen, and grows back by one after a run of healthy
+//! responses.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use kargo_core::manifest::NetworkConfig;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+/// Requests in flight when `[network] max-concurrency` is not set and
+/// memory is plentiful.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Memory assumed per request in flight (response bodies are buffered).
+const REQUEST_MEMORY: u64 = 32 * 1024 * 1024;
+
+/// Latency this many times the best observed one counts as a slowdown.
+const SLOW_FACTOR: u32 = 3;
+
+/// Pause after a 429/503 without a usable `Retry-After`.
+const THROTTLE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest `Retry-After` honoured.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Resolved `[network]` settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkLimits {
+    pub max_concurrency: usize,
+    pub max_per_host: usize,
+    pub request_delay: Duration,
+    pub auto_tune: bool,
+    pub hosts: BTreeMap<String, HostLimits>,
+}
+
+/// Limits applied to a single host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostLimits {
+    pub max_concurrency: usize,
+    pub request_delay: Duration,
+}
+
+impl Default for NetworkLimits {
+    fn default() -> Self {
+        Self::from_config(None)
+    }
+}
+
+impl NetworkLimits {
+    pub fn from_config(config: Option<&NetworkConfig>) -> Self {
+        let config = config.cloned().unwrap_or_default();
+        let max_concurrency = config
+            .max_concurrency
+            .unwrap_or_else(default_max_concurrency);
+        let request_delay = Duration::from_millis(config.request_delay_ms.unwrap_or(0));
+        let hosts = config
+            .hosts
+            .iter()
+            .map(|(host, h)| {
+                (
+                    host.to_ascii_lowercase(),
+                    HostLimits {
+                        max_concurrency: h.max_concurrency.unwrap_or(max_concurrency),
+                        request_delay: h
+                            .request_delay_ms
+                            .map(Duration::from_millis)
+                            .unwrap_or(request_delay),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            max_concurrency,
+            max_per_host: config.max_per_host.unwrap_or(max_concurrency),
+            request_delay,
+            auto_tune: config.auto_tune.unwrap_or(true),
+            hosts,
+        }
+    }
+
+    /// Limits for `host`: its `[network.hosts]` entry, else the defaults.
+    pub fn for_host(&self, host: &str) -> HostLimits {
+        self.hosts
+            .get(&host.to_ascii_lowercase())
+            .copied()
+            .unwrap_or(HostLimits {
+                max_concurrency: self.max_per_host,
+                request_delay: self.request_delay,
+            })
+    }
+}
+
+/// [`DEFAULT_MAX_CONCURRENCY`], lowered when little memory is available.
+/// Sampled once so that repeated configuration compares equal.
+fn default_max_concurrency() -> usize {
+    static DEFAULT: OnceLock<usize> = OnceLock::new();
+    *DEFAULT.get_or_init(|| {
+        kargo_util::system::fit_to_memory(
+            DEFAULT_MAX_CONCURRENCY as u32,
+            REQUEST_MEMORY,
+            kargo_util::system::available_memory(),
+        ) as usize
+    })
+}
+
+/// How a request went, as reported to its [`HostPermit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The host answered (any status other than 429/503).
+    Ok,
+    /// The host answered 429 or 503, optionally with a `Retry-After`.
+    Throttled(Option<Duration>),
+    /// No answer (timeout or connection error).
+    Failed,
+}
+
+/// Adaptive state of one host.
+#[derive(Debug)]
+struct HostState {
+    limits: HostLimits,
+    /// Current concurrency limit, at most `limits.max_concurrency`.
+    limit: usize,
+    in_flight: usize,
+    /// Earliest time the next request may start.
+    next_slot: Instant,
+    /// Smoothed time to response headers.
+    latency: Option<Duration>,
+    best_latency: Option<Duration>,
+    /// Healthy responses since the limit last changed.
+    healthy: usize,
+}
+
+impl HostState {
+    fn new(limits: HostLimits) -> Self {
+        Self {
+            limits,
+            limit: limits.max_concurrency,
+            in_flight: 0,
+            next_slot: Instant::now(),
+            latency: None,
+            best_latency: None,
+            healthy: 0,
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome, elapsed: Duration, auto_tune: bool, now: Instant) {
+        match outcome {
+            Outcome::Throttled(retry_after) => {
+                let pause = retry_after.unwrap_or(THROTTLE_BACKOFF).min(MAX_RETRY_AFTER);
+                self.next_slot = self.next_slot.max(now + pause);
+                if auto_tune {
+                    self.limit = (self.limit / 2).max(1);
+                    self.healthy = 0;
+                }
+            }
+            Outcome::Ok => {
+                let latency = match self.latency {
+                    Some(prev) => (prev * 4 + elapsed) / 5,
+                    None => elapsed,
+                };
+                self.latency = Some(latency);
+                let best = self.best_latency.map_or(latency, |b| b.min(latency));
+                self.best_latency = Some(best);
+                if !auto_tune {
+                    return;
+                }
+                if latency > best * SLOW_FACTOR {
+                    self.limit = self.limit.saturating_sub(1).max(1);
+                    self.healthy = 0;
+                } else {
+                    self.healthy += 1;
+                    if self.healthy >= self.limit && self.limit < self.limits.max_concurrency {
+                        self.limit += 1;
+                        self.healthy = 0;
+                    }
+                }
+            }
+            Outcome::Failed => {}
+        }
+    }
+}
+
+struct Host {
+    state: Mutex<HostState>,
+    released: Notify,
+}
+
+struct Limiter {
+    limits: NetworkLimits,
+    global: Arc<Semaphore>,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+impl Limiter {
+    fn host(&self, name: &str) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                Arc::new(Host {
+                    state: Mutex::new(HostState::new(self.limits.for_host(name))),
+                    released: Notify::new(),
+                })
+            })
+            .clone()
+    }
+}
+
+static LIMITER: Mutex<Option<Arc<Limiter>>> = Mutex::new(None);
+
+fn limiter() -> Arc<Limiter> {
+    let mut current = LIMITER.lock().unwrap();
+    current
+        .get_or_insert_with(|| new_limiter(NetworkLimits::default()))
+        .clone()
+}
+
+fn new_limiter(limits: NetworkLimits) -> Arc<Limiter> {
+    Arc::new(Limiter {
+        global: Arc::new(Semaphore::new(limits.max_concurrency)),
+        limits,
+        hosts: Mutex::new(HashMap::new()),
+    })
+}
+
+/// Apply `limits` to subsequent requests. Reconfiguring with the same
+/// limits keeps what has been learned about each host.
+pub fn configure(limits: NetworkLimits) {
+    let mut current = LIMITER.lock().unwrap();
+    if current.as_ref().is_some_and(|l| l.limits == limits) {
+        return;
+    }
+    *current = Some(new_limiter(limits));
+}
+
+/// The configured cap on requests in flight across all hosts.
+pub fn max_concurrency() -> usize {
+    limiter().limits.max_concurrency
+}
+
+/// Host name of `url`, lowercased, without port or credentials.
+fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        .unwrap_or_default()
+}
+
+/// A `Retry-After` header given in seconds.
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Whether `status` means the server wants the client to slow down.
+pub fn is_throttled(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Permission to send one request to a host; released on drop.
+pub struct HostPermit {
+    host: Arc<Host>,
+    started: Instant,
+    auto_tune: bool,
+    recorded: bool,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+impl HostPermit {
+    /// Report how the request went. Only the first report counts.
+    pub fn record(&mut self, outcome: Outcome) {
+        if std::mem::replace(&mut self.recorded, true) {
+            return;
+        }
+        let mut state = self.host.state.lock().unwrap();
+        state.record(
+            outcome,
+            self.started.elapsed(),
+            self.auto_tune,
+            Instant::now(),
+        );
+    }
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        self.host.state.lock().unwrap().in_flight -= 1;
+        self.host.released.notify_one();
+    }
+}
+
+/// Wait until a request to `url` may start.
+pub async fn acquire(url: &str) -> HostPermit {
+    let limiter = limiter();
+    let host = limiter.host(&host_of(url));
+    loop {
+        let wait = {
+            let mut state = host.state.lock().unwrap();
+            let now = Instant::now();
+            if state.in_flight >= state.limit {
+                None
+            } else if now >= state.next_slot {
+                state.in_flight += 1;
+                state.next_slot = now + state.limits.request_delay;
+                break;
+            } else {
+                Some(state.next_slot - now)
+            }
+        };
+        match wait {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => host.released.notified().await,
+        }
+    }
+    // Hold the host slot while waiting for the global one, so a busy host
+    // cannot starve the others of global permits.
+    let global = limiter.global.clone().acquire_owned().await.ok();
+    HostPermit {
+        host,
+        started: Instant::now(),
+        auto_tune: limiter.limits.auto_tune,
+        recorded: false,
+        _global: global,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_core::manifest::HostNetworkConfig;
+
+    fn state(max: usize) -> HostState {
+        HostState::new(HostLimits {
+            max_concurrency: max,
+            request_delay: Duration::ZERO,
+        })
+    }
+
+    #[test]
+    fn host_overrides_fall_back_to_defaults() {
+        let config = NetworkConfig {
+            max_concurrency: Some(16),
+            max_per_host: Some(4),
+            request_delay_ms: Some(50),
+            hosts: BTreeMap::from([(
+                "Nexus.Corp".to_string(),
+                HostNetworkConfig {
+                    max_concurrency: Some(2),
+                    request_delay_ms: None,
+                },
+            )]),
+            ..Default::default()
+        };
+        let limits = NetworkLimits::from_config(Some(&config));
+        assert_eq!(limits.max_concurrency, 16);
+        assert!(limits.auto_tune);
+        assert_eq!(
+            limits.for_host("nexus.corp"),
+            HostLimits {
+                max_concurrency: 2,
+                request_delay: Duration::from_millis(50)
+            }
+        );
+        assert_eq!(limits.for_host("repo.maven.apache.org").max_concurrency, 4);
+        assert_eq!(
+            NetworkLimits::default().for_host("x").max_concurrency,
+            DEFAULT_MAX_CONCURRENCY
+        );
+    }
+
+    #[test]
+    fn throttling_halves_the_limit_and_delays_the_host() {
+        let mut host = state(8);
+        let now = Instant::now();
+        host.record(
+            Outcome::Throttled(Some(Duration::from_secs(5))),
+            Duration::from_millis(10),
+            true,
+            now,
+        );
+        assert_eq!(host.limit, 4);
+        assert_eq!(host.next_slot, now + Duration::from_secs(5));
+        host.record(Outcome::Throttled(None), Duration::ZERO, true, now);
+        host.record(Outcome::Throttled(None), Duration::ZERO, true, now);
+        host.record(Outcome::Throttled(None), Duration::ZERO, true, now);
+        assert_eq!(host.limit, 1);
+    }
+
+    #[test]
+    fn healthy_responses_grow_the_limit_and_slow_ones_shrink_it() {
+        let mut host = state(4);
+        host.limit = 1;
+        let now = Instant::now();
+        let fast = Duration::from_millis(20);
+        host.record(Outcome::Ok, fast, true, now);
+        assert_eq!(host.limit, 2);
+        for _ in 0..20 {
+            host.record(Outcome::Ok, fast, true, now);
+        }
+        assert_eq!(host.limit, 4, "never above max-concurrency");
+
+        for _ in 0..10 {
+            host.record(Outcome::Ok, fast * 20, true, now);
+        }
+        assert!(host.limit < 4);
+
+        let mut fixed = state(4);
+        fixed.record(Outcome::Throttled(None), fast, false, now);
+        assert_eq!(fixed.limit, 4, "auto-tune off keeps the limit");
+    }
+
+    #[test]
+    fn retry_after_and_hosts_are_parsed() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "30".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+        assert_eq!(
+            host_of("https://user:pw@Nexus.Corp:8443/repository/maven/"),
+            "nexus.corp"
+        );
+    }
+}
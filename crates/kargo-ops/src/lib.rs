@@ -1,23 +1,59 @@
 pub mod ops_add;
+pub mod ops_affected;
+pub mod ops_analyze;
+pub mod ops_android_merge;
+pub mod ops_api;
 pub mod ops_audit;
+pub mod ops_baseline_profile;
+pub mod ops_bloat;
 pub mod ops_build;
 pub mod ops_cache;
 pub mod ops_check;
 pub mod ops_clean;
+pub mod ops_daemon;
+pub mod ops_desugar;
+pub mod ops_device;
+pub mod ops_device_test;
+pub mod ops_docs;
 pub mod ops_fetch;
+pub mod ops_fixtures;
+pub mod ops_generate;
+pub mod ops_idea;
 pub mod ops_init;
+pub mod ops_kotlin_migration;
 pub mod ops_lock;
+pub mod ops_metadata;
 pub mod ops_new;
+pub mod ops_npm;
 pub mod ops_outdated;
+pub mod ops_package;
+pub mod ops_plugin;
+pub mod ops_pod;
+pub mod ops_protobuf;
+pub mod ops_publish;
+pub mod ops_release;
 pub mod ops_remove;
 pub mod ops_run;
+pub mod ops_search;
 pub mod ops_self;
 pub mod ops_self_update;
 pub mod ops_setup;
+pub mod ops_shrink;
+pub mod ops_spm;
+pub mod ops_sqldelight;
+pub mod ops_task;
 pub mod ops_test;
+pub mod ops_tool;
 pub mod ops_toolchain;
 pub mod ops_tree;
+pub mod ops_trust;
+pub mod ops_unused;
 pub mod ops_update;
+pub mod ops_vscode;
+pub mod ops_wasm;
+pub mod ops_workspace;
+pub mod ops_wrapper;
+pub mod ops_xcode;
 
 use std::path::{Path, PathBuf};
 
@@ -33,6 +69,7 @@ use kargo_util::errors::KargoError;
 use crate::ops_setup::PreflightResult;
 
 /// Shared build context assembled once and reused by build, test, run, check.
+#[derive(Clone)]
 pub struct BuildContext {
     pub project_dir: PathBuf,
     pub manifest: Manifest,
@@ -62,9 +99,11 @@ impl BuildContext {
         let preflight = crate::ops_setup::preflight(project_dir).await?;
         crate::ops_setup::ensure_lockfile(project_dir).await?;
 
-        let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
-        let lockfile = Lockfile::from_path(&project_dir.join("Kargo.lock"))
-            .unwrap_or(Lockfile { package: vec![] });
+        let mut manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+        // Downstream consumers (KSP, BuildConfig, env) expect a concrete
+        // version rather than an alias such as `latest-stable`.
+        manifest.package.kotlin = preflight.toolchain.version.to_string();
+        let lockfile = Lockfile::for_project(project_dir).unwrap_or_default();
 
         let target_name = target
             .or_else(|| manifest.targets.keys().next().map(|s| s.as_str()))
@@ -126,7 +165,7 @@ impl BuildContext {
         };
 
         let kotlin_ver = preflight.toolchain.version.to_string();
-        let env = BuildEnv::new(
+        let mut env = BuildEnv::new(
             &manifest,
             project_dir,
             &build_dir,
@@ -137,7 +176,15 @@ impl BuildContext {
             config.build.jobs,
         );
 
-        let cp = classpath::assemble(project_dir, &lockfile);
+        // Expose installed [npm-dependencies] to the JS/Wasm toolchain and hooks.
+        let node_modules = ops_npm::node_modules_dir(project_dir);
+        if matches!(kotlin_target, KotlinTarget::Js | KotlinTarget::WasmJs) && node_modules.is_dir()
+        {
+            env.vars
+                .insert("NODE_PATH".into(), node_modules.to_string_lossy().into());
+        }
+
+        let cp = classpath::assemble_for_target(project_dir, &lockfile, kotlin_target.kebab_name());
         let discovered = source_set_discovery::discover(project_dir, &manifest);
 
         Ok(BuildContext {
@@ -160,6 +207,140 @@ impl BuildContext {
     }
 }
 
+/// Configure the HTTP clients of this process from the `[network]` TLS and
+/// IP settings in `~/.kargo/config.toml`, overridden by those in the
+/// `Kargo.toml` of the project containing `cwd`.
+///
+/// Only the `[network]` table is read, so a manifest with errors elsewhere
+/// still gets its network settings; the command reports those errors itself.
+pub fn configure_http(cwd: &Path) -> miette::Result<()> {
+    #[derive(serde::Deserialize)]
+    struct NetworkOnly {
+        #[serde(default)]
+        network: Option<kargo_core::manifest::NetworkConfig>,
+    }
+
+    let mut settings = kargo_util::http::HttpSettings::new();
+    if let Some(network) = GlobalConfig::load()?.network {
+        network
+            .validate_tls()
+            .map_err(|message| KargoError::Generic { message })?;
+        settings = network.http_settings(&kargo_util::dirs_path(), settings);
+    }
+    if let Some(root) = kargo_util::fs::find_ancestor_with(cwd, "Kargo.toml") {
+        let manifest_path = root.join("Kargo.toml");
+        let content = std::fs::read_to_string(&manifest_path).map_err(KargoError::Io)?;
+        let env = kargo_core::properties::load_env_file(&root.join(".kargo.env"))?;
+        let content = kargo_core::properties::interpolate(&content, &env);
+        if let Ok(NetworkOnly {
+            network: Some(network),
+        }) = toml::from_str(&content)
+        {
+            network
+                .validate_tls()
+                .map_err(|message| KargoError::Manifest { message })?;
+            settings = network.http_settings(&root, settings);
+        }
+    }
+    kargo_util::http::configure(settings);
+    Ok(())
+}
+
+/// Resolve the JVM tool JARs `coords` (`group:artifact:version`) with their
+/// transitive dependencies from the repositories of `manifest`, into the
+/// project cache of `project_dir`, and return the JARs to run them with.
+pub async fn resolve_tool_classpath<'a>(
+    project_dir: &Path,
+    manifest: &Manifest,
+    tool: &str,
+    coords: impl IntoIterator<Item = &'a str>,
+) -> miette::Result<Vec<PathBuf>> {
+    let repos = kargo_resolver::resolver::build_repos(manifest);
+    let cache = kargo_maven::cache::LocalCache::new(project_dir);
+    resolve_tool_jars(&repos, &cache, &manifest.package.kotlin, tool, coords).await
+}
+
+/// Like [`resolve_tool_classpath`], but each JAR is also checked against
+/// the project's `kargo.trust` under `tool`, keyed by its coordinate, as
+/// the compiler and JDK archives are.
+pub async fn resolve_pinned_tool_classpath<'a>(
+    project_dir: &Path,
+    manifest: &Manifest,
+    tool: &str,
+    coords: impl IntoIterator<Item = &'a str>,
+) -> miette::Result<Vec<PathBuf>> {
+    let repos = kargo_resolver::resolver::build_repos(manifest);
+    let cache = kargo_maven::cache::LocalCache::new(project_dir);
+    let artifacts =
+        resolve_tool_artifacts(&repos, &cache, &manifest.package.kotlin, tool, coords).await?;
+    let mut jars = Vec::with_capacity(artifacts.len());
+    for (coordinate, url, jar) in artifacts {
+        kargo_util::trust::verify_file(tool, &coordinate, &url, &jar)?;
+        jars.push(jar);
+    }
+    Ok(jars)
+}
+
+/// Like [`resolve_tool_classpath`], resolving from `repos` into `cache`.
+pub(crate) async fn resolve_tool_jars<'a>(
+    repos: &[kargo_maven::repository::MavenRepository],
+    cache: &kargo_maven::cache::LocalCache,
+    kotlin: &str,
+    tool: &str,
+    coords: impl IntoIterator<Item = &'a str>,
+) -> miette::Result<Vec<PathBuf>> {
+    let artifacts = resolve_tool_artifacts(repos, cache, kotlin, tool, coords).await?;
+    Ok(artifacts.into_iter().map(|(_, _, jar)| jar).collect())
+}
+
+/// The resolved tool JARs as `(coordinate, url, path)`, where `url` is the
+/// repository location the JAR is served from.
+async fn resolve_tool_artifacts<'a>(
+    repos: &[kargo_maven::repository::MavenRepository],
+    cache: &kargo_maven::cache::LocalCache,
+    kotlin: &str,
+    tool: &str,
+    coords: impl IntoIterator<Item = &'a str>,
+) -> miette::Result<Vec<(String, String, PathBuf)>> {
+    let mut toml = format!(
+        "[package]\nname = \"kargo-{tool}\"\nversion = \"0.0.0\"\nkotlin = \"{kotlin}\"\n\n[dependencies]\n"
+    );
+    for (i, coord) in coords.into_iter().enumerate() {
+        toml.push_str(&format!("tool-{i} = \"{coord}\"\n"));
+    }
+    let tool_manifest = Manifest::parse_toml(&toml)?;
+
+    let client = kargo_maven::download::build_client()?;
+    let resolved =
+        kargo_resolver::resolver::resolve(&tool_manifest, repos, cache, None, &client).await?;
+
+    let mut jars = Vec::new();
+    for artifact in &resolved.artifacts {
+        for repo in kargo_maven::repository::for_group(repos, &artifact.group) {
+            let jar = kargo_compiler::plugins::ensure_maven_jar_from(
+                repo,
+                cache,
+                &artifact.group,
+                &artifact.artifact,
+                &artifact.version,
+            )
+            .await?;
+            if let Some(jar) = jar {
+                jars.push((
+                    format!(
+                        "{}:{}:{}",
+                        artifact.group, artifact.artifact, artifact.version
+                    ),
+                    repo.jar_url(&artifact.group, &artifact.artifact, &artifact.version, None),
+                    jar,
+                ));
+                break;
+            }
+        }
+    }
+    Ok(jars)
+}
+
 /// Re-export `classpath_string_with_stdlib` from the compiler crate for convenience.
 pub fn classpath_string_with_stdlib(jars: &[PathBuf], kotlin_home: &Path) -> String {
     kargo_compiler::classpath::classpath_string_with_stdlib(jars, kotlin_home)
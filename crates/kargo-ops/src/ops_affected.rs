@@ -0,0 +1,212 @@
+//! Operation: workspace members affected by changes since a git revision
+//! (`--affected-since <ref>`).
+//!
+//! Changed files are those that differ between the working tree and the
+//! merge base of `<ref>` and `HEAD`, plus untracked files. A member is
+//! affected when a changed file lies in one of its inputs: `src/` (the root
+//! of every source set), `[protobuf]` source directories, `[generate]`
+//! inputs and specs, or its `Kargo.toml` and `.kargo.env`. A change to the
+//! workspace root's `Kargo.toml` or to the `Kargo.lock` the members share
+//! affects every member. Members depending on an affected member through
+//! `{ module = "..." }` are affected in turn.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::{Manifest, WorkspaceConfig};
+use kargo_core::workspace;
+use kargo_util::errors::KargoError;
+
+use crate::ops_release::git;
+use crate::ops_workspace::{canonical, MemberGraph};
+
+/// A member of the workspace that needs rebuilding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AffectedMember {
+    /// Package name.
+    pub name: String,
+    pub dir: PathBuf,
+    /// Why it is affected: the first changed input, or the member it
+    /// depends on.
+    pub reason: String,
+}
+
+/// The members of the workspace enclosing `cwd` affected by changes since
+/// `since`, dependencies before their dependents, and the workspace root.
+pub fn affected_since(cwd: &Path, since: &str) -> miette::Result<(PathBuf, Vec<AffectedMember>)> {
+    let (root, config) = workspace::find_root(cwd).ok_or_else(|| KargoError::Manifest {
+        message: format!(
+            "--affected-since needs a workspace, but no [workspace] was found at or above {}",
+            cwd.display()
+        ),
+    })?;
+    let changed = changed_files(&root, since)?;
+    let members = affected(&root, &config, &changed)?;
+    Ok((root, members))
+}
+
+/// Files changed since the merge base of `since` and `HEAD` in the git
+/// repository containing `dir`, including uncommitted and untracked files.
+pub fn changed_files(dir: &Path, since: &str) -> miette::Result<Vec<PathBuf>> {
+    let toplevel = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+    let base = git(dir, &["merge-base", since, "HEAD"])?;
+    let diff = git(dir, &["diff", "--name-only", "--no-renames", &base])?;
+    let untracked = git(
+        dir,
+        &["ls-files", "--others", "--exclude-standard", "--full-name"],
+    )?;
+    Ok(diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| toplevel.join(line))
+        .collect())
+}
+
+/// The members of the workspace at `root` that `changed` affects,
+/// dependencies before their dependents.
+pub fn affected(
+    root: &Path,
+    config: &WorkspaceConfig,
+    changed: &[PathBuf],
+) -> miette::Result<Vec<AffectedMember>> {
+    let root = canonical(root);
+    let changed: Vec<PathBuf> = changed.iter().map(|p| canonical(p)).collect();
+    let root_changed = ["Kargo.toml", "Kargo.lock"]
+        .into_iter()
+        .find(|file| changed.contains(&root.join(file)));
+
+    let graph = MemberGraph::load(&root, config)?;
+    let members = &graph.members;
+
+    let mut reasons: BTreeMap<usize, String> = BTreeMap::new();
+    for (i, (dir, manifest)) in members.iter().enumerate() {
+        let inputs = inputs(dir, manifest);
+        let hit = changed
+            .iter()
+            .find(|file| inputs.iter().any(|input| file.starts_with(input)));
+        if let Some(file) = hit {
+            let shown = file.strip_prefix(&root).unwrap_or(file);
+            reasons.insert(i, format!("{} changed", shown.display()));
+        } else if let Some(file) = root_changed {
+            reasons.insert(i, format!("workspace {file} changed"));
+        }
+    }
+    // Dependents of affected members, until nothing new is found.
+    loop {
+        let mut found = false;
+        for (i, deps) in graph.depends_on.iter().enumerate() {
+            if reasons.contains_key(&i) {
+                continue;
+            }
+            if let Some(dep) = deps.iter().find(|dep| reasons.contains_key(dep)) {
+                reasons.insert(i, format!("depends on {}", members[*dep].1.package.name));
+                found = true;
+            }
+        }
+        if !found {
+            break;
+        }
+    }
+
+    let affected: BTreeSet<usize> = reasons.keys().copied().collect();
+    Ok(graph
+        .order(&affected)
+        .into_iter()
+        .filter_map(|i| {
+            let reason = reasons.remove(&i)?;
+            let (dir, manifest) = &members[i];
+            Some(AffectedMember {
+                name: manifest.package.name.clone(),
+                dir: dir.clone(),
+                reason,
+            })
+        })
+        .collect())
+}
+
+/// Files and directories of the member at `dir` whose changes affect it.
+fn inputs(dir: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    let mut inputs: Vec<PathBuf> = ["src", "Kargo.toml", ".kargo.env"]
+        .iter()
+        .map(|p| dir.join(p))
+        .collect();
+    if let Some(protobuf) = &manifest.protobuf {
+        inputs.extend(protobuf.source_dirs.iter().map(|d| dir.join(d)));
+    }
+    for generator in manifest.generate.values() {
+        for input in generator.inputs.iter().chain(&generator.spec) {
+            // A glob stands for the directory it starts in.
+            let literal: PathBuf = Path::new(input)
+                .components()
+                .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+                .collect();
+            inputs.push(dir.join(literal));
+        }
+    }
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(root: &Path, name: &str, extra: &str) {
+        std::fs::create_dir_all(root.join(name).join("src/main/kotlin")).unwrap();
+        std::fs::write(
+            root.join(name).join("Kargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n{extra}"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn names(members: &[AffectedMember]) -> Vec<&str> {
+        members.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn changes_reach_members_and_their_dependents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"tools\"]\n",
+        )
+        .unwrap();
+        member(
+            root,
+            "app",
+            "\n[dev-dependencies]\ncore = { module = \"core\" }\n",
+        );
+        member(root, "core", "");
+        member(
+            root,
+            "tools",
+            "\n[generate.schema]\ncommand = [\"gen\"]\ninputs = [\"schema/**/*.json\"]\n",
+        );
+        let (_, config) = workspace::find_root(root).unwrap();
+
+        let changed = [root.join("core/src/main/kotlin/Core.kt")];
+        let found = affected(root, &config, &changed).unwrap();
+        assert_eq!(names(&found), ["core", "app"]);
+        assert_eq!(found[0].reason, "core/src/main/kotlin/Core.kt changed");
+        assert_eq!(found[1].reason, "depends on core");
+
+        let changed = [
+            root.join("tools/schema/v1/user.json"),
+            root.join("core/README.md"),
+        ];
+        assert_eq!(
+            names(&affected(root, &config, &changed).unwrap()),
+            ["tools"]
+        );
+
+        let changed = [root.join("Kargo.toml")];
+        assert_eq!(
+            names(&affected(root, &config, &changed).unwrap()),
+            ["core", "app", "tools"]
+        );
+    }
+}
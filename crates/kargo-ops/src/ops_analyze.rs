@@ -0,0 +1,368 @@
+//! Operation: `kargo analyze startup` — which classes an application loads
+//! before it finishes, and where they come from.
+//!
+//! The project is built and run once with `-Xlog:class+load` writing to a
+//! file under the build directory. Every loaded class is attributed to the
+//! classpath entry it came from (project classes, a dependency JAR, or the
+//! JDK), and compared with the number of classes each JAR ships. The run
+//! measures the whole process, so the application should exit on its own
+//! (a CLI invocation or a single serverless request).
+//!
+//! Suggestions are heuristics: a dependency that is never loaded may still
+//! be needed on another code path, and one that loads many classes may be
+//! needed immediately.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use kargo_core::target::KotlinTarget;
+use kargo_maven::cache::LocalCache;
+use kargo_util::errors::KargoError;
+
+use crate::ops_build::{self, BuildOptions};
+
+/// Origin of project classes and resources.
+const PROJECT: &str = "(project)";
+/// Origin of classes from the JDK, including the CDS archive.
+const JDK: &str = "(JDK)";
+/// Origin of classes defined at runtime (lambdas, proxies, hidden classes).
+const GENERATED: &str = "(generated at runtime)";
+
+/// Dependencies loading at least this many classes during startup are
+/// suggested for lazy loading.
+const LAZY_LOAD_THRESHOLD: usize = 100;
+
+/// Options for `kargo analyze startup`.
+#[derive(Debug, Clone, Default)]
+pub struct StartupOptions {
+    pub target: Option<String>,
+    /// Arguments passed to the application unchanged.
+    pub args: Vec<String>,
+    pub verbose: bool,
+}
+
+/// Classes loaded from one origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginUsage {
+    /// `group:artifact`, or one of the bracketed pseudo-origins.
+    pub origin: String,
+    pub loaded: usize,
+    /// Classes the JAR contains, when the origin is a JAR.
+    pub available: Option<usize>,
+    /// Whether the origin is a declared runtime dependency (not the
+    /// project, the Kotlin stdlib or the JDK).
+    pub dependency: bool,
+}
+
+/// Parse a `-Xlog:class+load` log (no decorations) into `(class, source)`.
+pub fn parse_class_load_log(log: &str) -> Vec<(String, String)> {
+    log.lines()
+        .filter_map(|line| {
+            let (class, source) = line.trim().split_once(" source: ")?;
+            Some((class.to_string(), source.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes in a `file:` URL path.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Attribute a class-load `source` to an origin, given the classpath
+/// entries and their origins.
+pub fn classify(source: &str, entries: &[(PathBuf, String)]) -> String {
+    if source == "shared objects file" || source.starts_with("jrt:/") {
+        return JDK.to_string();
+    }
+    let Some(url) = source.strip_prefix("file:") else {
+        return GENERATED.to_string();
+    };
+    let path = PathBuf::from(percent_decode(url.trim_end_matches('/')));
+    entries
+        .iter()
+        .find(|(entry, _)| path == *entry)
+        .map(|(_, origin)| origin.clone())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Number of classes in a JAR, ignoring `module-info`.
+fn class_count(jar: &Path) -> Option<usize> {
+    let archive = zip::ZipArchive::new(std::fs::File::open(jar).ok()?).ok()?;
+    Some(
+        archive
+            .file_names()
+            .filter(|n| n.ends_with(".class") && !n.ends_with("module-info.class"))
+            .count(),
+    )
+}
+
+/// Suggestions for the startup profile in `usage`.
+pub fn suggestions(usage: &[OriginUsage]) -> Vec<String> {
+    let mut out = Vec::new();
+    for u in usage.iter().filter(|u| u.dependency) {
+        if u.loaded == 0 {
+            out.push(format!(
+                "{} is not loaded at startup. If the application only compiles against it \
+                 (annotations, APIs the runtime provides), declare it with scope = \"provided\".",
+                u.origin
+            ));
+        } else if u.loaded >= LAZY_LOAD_THRESHOLD {
+            out.push(format!(
+                "{} loads {} classes at startup. Move its first use off the startup path \
+                 (e.g. `by lazy`) if the application does not need it immediately.",
+                u.origin, u.loaded
+            ));
+        }
+    }
+    out
+}
+
+/// Build and run the project, then report its class loading at startup.
+pub async fn startup(project_dir: &Path, opts: &StartupOptions) -> miette::Result<()> {
+    let build_result = ops_build::build(
+        project_dir,
+        &BuildOptions {
+            target: opts.target.clone(),
+            verbose: opts.verbose,
+            quiet: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+    if !build_result.success {
+        return Err(KargoError::Compilation {
+            message: "cannot analyze startup".into(),
+        }
+        .into());
+    }
+    if !matches!(build_result.target, KotlinTarget::Jvm) {
+        return Err(KargoError::Generic {
+            message: "`kargo analyze startup` needs a JVM target".into(),
+        }
+        .into());
+    }
+
+    let main_class = crate::ops_run::main_class(&build_result.manifest, project_dir)?;
+    let launch = crate::ops_run::launch_classpath(&build_result);
+
+    // Label every classpath entry with its origin.
+    let cache = LocalCache::new(project_dir);
+    let mut jar_origins: HashMap<PathBuf, String> = HashMap::new();
+    for pkg in &build_result.lockfile.package {
+        if let Some(jar) = cache
+            .get_library(&pkg.group, &pkg.name, &pkg.version)
+            .and_then(kargo_maven::aar::classpath_entry)
+        {
+            jar_origins.insert(jar, format!("{}:{}", pkg.group, pkg.name));
+        }
+    }
+    let runtime: Vec<&PathBuf> = build_result.classpath.runtime_jars.iter().collect();
+    let mut entries: Vec<(PathBuf, String)> = Vec::new();
+    let mut dependencies: Vec<String> = Vec::new();
+    for entry in &launch {
+        let origin = if entry.starts_with(&build_result.build_dir) {
+            PROJECT.to_string()
+        } else if let Some(origin) = jar_origins.get(entry) {
+            if runtime.contains(&entry) {
+                dependencies.push(origin.clone());
+            }
+            origin.clone()
+        } else {
+            let stem = entry.file_stem().unwrap_or_default().to_string_lossy();
+            format!("org.jetbrains.kotlin:{stem}")
+        };
+        entries.push((entry.clone(), origin));
+    }
+
+    let log_path = build_result.build_dir.join("startup-class-load.log");
+    let _ = std::fs::remove_file(&log_path);
+    let java_bin = build_result.preflight.jdk.home.join("bin").join("java");
+    kargo_util::progress::status("Running", &format!("{main_class} (logging class loading)"));
+    let cmd = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(build_result.preflight.jdk.java_accepts_argfiles())
+        .arg(format!(
+            "-Xlog:class+load=info:file={}:none",
+            log_path.display()
+        ))
+        .arg("-cp")
+        .arg(kargo_compiler::classpath::to_classpath_string(&launch))
+        .arg(&main_class)
+        .args(opts.args.iter().cloned())
+        .env(
+            "JAVA_HOME",
+            build_result
+                .preflight
+                .jdk
+                .home
+                .to_string_lossy()
+                .to_string(),
+        );
+    let start = Instant::now();
+    let status = cmd.status()?;
+    let elapsed = start.elapsed();
+    if !status.success() {
+        kargo_util::progress::status_warn(
+            "Warning",
+            &format!(
+                "application exited with code {}",
+                kargo_util::process::exit_code(status)
+            ),
+        );
+    }
+
+    let log = std::fs::read_to_string(&log_path).map_err(|e| KargoError::Generic {
+        message: format!("No class-load log at {}: {e}", log_path.display()),
+    })?;
+    let loaded = parse_class_load_log(&log);
+
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, source) in &loaded {
+        *counts.entry(classify(source, &entries)).or_default() += 1;
+    }
+    let mut usage: Vec<OriginUsage> = Vec::new();
+    for (entry, origin) in &entries {
+        if usage.iter().any(|u| &u.origin == origin) {
+            continue;
+        }
+        let available = if entry.is_file() {
+            class_count(entry)
+        } else {
+            None
+        };
+        usage.push(OriginUsage {
+            origin: origin.clone(),
+            loaded: counts.remove(origin).unwrap_or(0),
+            available,
+            dependency: dependencies.contains(origin),
+        });
+    }
+    for (origin, loaded) in counts {
+        usage.push(OriginUsage {
+            origin,
+            loaded,
+            available: None,
+            dependency: false,
+        });
+    }
+    usage.sort_by(|a, b| b.loaded.cmp(&a.loaded).then(a.origin.cmp(&b.origin)));
+
+    println!();
+    println!(
+        "Startup: {} ms, {} classes loaded",
+        elapsed.as_millis(),
+        loaded.len()
+    );
+    println!();
+    println!(
+        "{:<56} {:>8} {:>8} {:>6}",
+        "Origin", "Loaded", "In JAR", "Used"
+    );
+    for u in &usage {
+        let (available, used) = match u.available {
+            Some(n) if n > 0 => (n.to_string(), format!("{}%", u.loaded * 100 / n)),
+            Some(n) => (n.to_string(), String::new()),
+            None => (String::new(), String::new()),
+        };
+        println!(
+            "{:<56} {:>8} {:>8} {:>6}",
+            u.origin, u.loaded, available, used
+        );
+    }
+
+    let hints = suggestions(&usage);
+    if !hints.is_empty() {
+        println!();
+        println!("Suggestions:");
+        for hint in hints {
+            println!("  - {hint}");
+        }
+    }
+    if opts.verbose {
+        eprintln!("  class-load log: {}", log_path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_loads_are_attributed_to_classpath_entries() {
+        let log = "\
+java.lang.Object source: shared objects file
+java.sql.Driver source: jrt:/java.sql
+com.example.MainKt source: file:/work/app/build/jvm/dev/classes/
+kotlin.jvm.internal.Intrinsics source: file:/home/u/.kargo/kotlin/lib/kotlin-stdlib.jar
+okhttp3.OkHttpClient source: file:/cache/My%20Libs/okhttp-4.12.0.jar
+com.example.MainKt$$Lambda/0x0000 source: com.example.MainKt
+not a class-load line
+";
+        let loaded = parse_class_load_log(log);
+        assert_eq!(loaded.len(), 6);
+        assert_eq!(loaded[2].0, "com.example.MainKt");
+
+        let entries = vec![
+            (
+                PathBuf::from("/work/app/build/jvm/dev/classes"),
+                PROJECT.to_string(),
+            ),
+            (
+                PathBuf::from("/home/u/.kargo/kotlin/lib/kotlin-stdlib.jar"),
+                "org.jetbrains.kotlin:kotlin-stdlib".to_string(),
+            ),
+            (
+                PathBuf::from("/cache/My Libs/okhttp-4.12.0.jar"),
+                "com.squareup.okhttp3:okhttp".to_string(),
+            ),
+        ];
+        let origins: Vec<String> = loaded.iter().map(|(_, s)| classify(s, &entries)).collect();
+        assert_eq!(
+            origins,
+            [
+                JDK,
+                JDK,
+                PROJECT,
+                "org.jetbrains.kotlin:kotlin-stdlib",
+                "com.squareup.okhttp3:okhttp",
+                GENERATED
+            ]
+        );
+    }
+
+    #[test]
+    fn unused_and_heavy_dependencies_are_suggested() {
+        let usage = |origin: &str, loaded: usize, dependency: bool| OriginUsage {
+            origin: origin.to_string(),
+            loaded,
+            available: Some(1000),
+            dependency,
+        };
+        let hints = suggestions(&[
+            usage("org.jetbrains.kotlin:kotlin-stdlib", 400, false),
+            usage("com.example:annotations", 0, true),
+            usage("com.example:heavy", 250, true),
+            usage("com.example:small", 12, true),
+        ]);
+        assert_eq!(hints.len(), 2);
+        assert!(hints[0].starts_with("com.example:annotations") && hints[0].contains("provided"));
+        assert!(hints[1].starts_with("com.example:heavy loads 250"));
+    }
+}
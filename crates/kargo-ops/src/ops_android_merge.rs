@@ -0,0 +1,1287 @@
+//! Operation: merge Android manifests and resources.
+//!
+//! `aapt2 link` takes a single manifest and cannot combine the resource
+//! trees of several libraries, so Android packaging first merges the
+//! project's `AndroidManifest.xml` and `res/` (under `src/main/`, or
+//! `src/androidMain/` in the multiplatform layout) with those of the AARs
+//! on the classpath. The results land in `build/android/<profile>/merged/`:
+//!
+//! - `AndroidManifest.xml`: lower-priority manifests are merged into the
+//!   first one, libraries in lockfile order. Elements match by tag and
+//!   `android:name`; attributes that disagree fail the merge unless the
+//!   higher-priority element lists them in `tools:replace`, and
+//!   `tools:node="remove"`, `"removeAll"` and `"replace"` behave as with the
+//!   Android Gradle plugin. `${applicationId}` and `[targets.android]
+//!   manifest-placeholders` are substituted, relative class names are
+//!   qualified with their manifest's package, and `<uses-sdk>` is filled in
+//!   from `min-sdk`/`target-sdk`.
+//! - `res/`: one `values.xml` per qualifier plus every file resource. The
+//!   project overrides libraries. A resource defined twice by the same
+//!   source fails the merge; libraries that disagree on a resource are
+//!   reported, and the first in lockfile order wins.
+//! - `proguard-rules.pro`: keep rules for the classes Android instantiates
+//!   by name from the merged manifest and layouts, followed by the consumer
+//!   rules of every AAR, so that R8 can shrink the app safely.
+//! - `baseline-prof.txt`: the baseline profile rules of the project
+//!   (`src/main/baseline-prof.txt`) and of every AAR, without duplicates,
+//!   ready to be compiled into the APK (see [`crate::ops_baseline_profile`]).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::Manifest;
+use kargo_maven::cache::LocalCache;
+use kargo_util::errors::KargoError;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Directory under the build directory holding the merged outputs.
+pub const MERGED_DIR: &str = "merged";
+
+/// Keep rules written next to the merged manifest.
+pub const PROGUARD_RULES: &str = "proguard-rules.pro";
+
+/// Baseline profile rules written next to the merged manifest, and the name
+/// of the project's and libraries' own rules files.
+pub const BASELINE_PROFILE: &str = "baseline-prof.txt";
+
+/// Lockfile scopes whose AARs are packaged into the app.
+pub const APP_SCOPES: &[&str] = &["compile", "runtime"];
+
+/// Source sets holding the app's own manifest and resources.
+pub const MAIN_SOURCE_SETS: &[&str] = &["androidMain", "main"];
+
+/// Manifest elements matched by tag alone.
+const SINGLETONS: &[&str] = &[
+    "application",
+    "uses-sdk",
+    "queries",
+    "supports-screens",
+    "compatible-screens",
+    "uses-configuration",
+];
+
+/// Manifest elements naming a class Android instantiates.
+const COMPONENTS: &[&str] = &[
+    "application",
+    "activity",
+    "activity-alias",
+    "service",
+    "receiver",
+    "provider",
+    "instrumentation",
+];
+
+/// Component attributes holding class names.
+const CLASS_ATTRIBUTES: &[&str] = &[
+    "android:name",
+    "android:backupAgent",
+    "android:appComponentFactory",
+    "android:targetActivity",
+];
+
+/// A manifest, resource directory or rules file taking part in a merge.
+#[derive(Debug, Clone)]
+pub struct MergeSource {
+    /// Where it comes from, for diagnostics: a project-relative path or a
+    /// library coordinate.
+    pub origin: String,
+    pub path: PathBuf,
+    /// Whether it belongs to a library rather than the project.
+    pub library: bool,
+}
+
+/// Everything a merge reads. Sources are listed highest priority first.
+#[derive(Debug, Clone, Default)]
+pub struct MergeInputs {
+    pub manifests: Vec<MergeSource>,
+    pub res_dirs: Vec<MergeSource>,
+    pub consumer_rules: Vec<MergeSource>,
+    pub baseline_profiles: Vec<MergeSource>,
+    pub placeholders: BTreeMap<String, String>,
+    pub min_sdk: Option<u32>,
+    pub target_sdk: Option<u32>,
+}
+
+/// What [`merge`] wrote.
+#[derive(Debug, Clone)]
+pub struct MergeOutput {
+    pub manifest: PathBuf,
+    /// The merged `res/`, absent when no source has resources.
+    pub res_dir: Option<PathBuf>,
+    pub proguard_rules: PathBuf,
+    /// The merged baseline profile, absent when no source has one.
+    pub baseline_profile: Option<PathBuf>,
+    /// Packages of the manifests merged into the first one, which need
+    /// their own `R` class.
+    pub packages: Vec<String>,
+    /// Conflicts that were resolved by priority.
+    pub warnings: Vec<String>,
+}
+
+/// The project's own `AndroidManifest.xml`, if it has one.
+pub fn project_manifest(project_dir: &Path) -> Option<PathBuf> {
+    MAIN_SOURCE_SETS
+        .iter()
+        .map(|name| {
+            project_dir
+                .join("src")
+                .join(name)
+                .join("AndroidManifest.xml")
+        })
+        .find(|path| path.is_file())
+}
+
+/// Merge inputs for the `android` target: the project's manifest and
+/// resources, then those of the AARs recorded in `lockfile` under `scopes`.
+pub fn inputs(
+    project_dir: &Path,
+    manifest: &Manifest,
+    lockfile: &Lockfile,
+    scopes: &[&str],
+) -> miette::Result<MergeInputs> {
+    let android = manifest.targets.get("android");
+    let mut inputs = MergeInputs {
+        placeholders: android
+            .map(|t| t.manifest_placeholders.clone())
+            .unwrap_or_default(),
+        min_sdk: android.and_then(|t| t.min_sdk),
+        target_sdk: android.and_then(|t| t.target_sdk),
+        ..Default::default()
+    };
+    for name in MAIN_SOURCE_SETS {
+        let dir = project_dir.join("src").join(name);
+        let manifest_path = dir.join("AndroidManifest.xml");
+        if manifest_path.is_file() {
+            inputs.manifests.push(MergeSource {
+                origin: format!("src/{name}/AndroidManifest.xml"),
+                path: manifest_path,
+                library: false,
+            });
+        }
+        let res = dir.join("res");
+        if res.is_dir() {
+            inputs.res_dirs.push(MergeSource {
+                origin: format!("src/{name}/res"),
+                path: res,
+                library: false,
+            });
+        }
+        let profile = dir.join(BASELINE_PROFILE);
+        if profile.is_file() {
+            inputs.baseline_profiles.push(MergeSource {
+                origin: format!("src/{name}/{BASELINE_PROFILE}"),
+                path: profile,
+                library: false,
+            });
+        }
+    }
+
+    let cache = LocalCache::new(project_dir);
+    for pkg in lockfile.package.iter().filter(|p| {
+        p.applies_to("android") && scopes.contains(&p.scope.as_deref().unwrap_or("compile"))
+    }) {
+        let Some(aar) = cache.get_aar(&pkg.group, &pkg.name, &pkg.version) else {
+            continue;
+        };
+        let exploded = kargo_maven::aar::explode(&aar)?;
+        let origin = format!("{}:{}:{}", pkg.group, pkg.name, pkg.version);
+        let parts = [
+            (&mut inputs.manifests, exploded.manifest()),
+            (&mut inputs.res_dirs, exploded.res_dir()),
+            (&mut inputs.consumer_rules, exploded.consumer_rules()),
+            (&mut inputs.baseline_profiles, exploded.baseline_profile()),
+        ];
+        for (list, path) in parts {
+            if let Some(path) = path {
+                list.push(MergeSource {
+                    origin: origin.clone(),
+                    path,
+                    library: true,
+                });
+            }
+        }
+    }
+    Ok(inputs)
+}
+
+/// Merge `inputs` into `out_dir`, replacing what an earlier merge left there.
+pub fn merge(inputs: &MergeInputs, out_dir: &Path) -> miette::Result<MergeOutput> {
+    if inputs.manifests.is_empty() {
+        return Err(KargoError::Generic {
+            message: "No AndroidManifest.xml to merge".into(),
+        }
+        .into());
+    }
+    if out_dir.exists() {
+        fs::remove_dir_all(out_dir).map_err(KargoError::Io)?;
+    }
+    fs::create_dir_all(out_dir).map_err(KargoError::Io)?;
+
+    let mut manifests = Vec::new();
+    for source in &inputs.manifests {
+        let xml = fs::read_to_string(&source.path).map_err(KargoError::Io)?;
+        manifests.push((source.origin.clone(), xml));
+    }
+    let merged = merge_manifests(&manifests, inputs).map_err(|errors| KargoError::Generic {
+        message: format!("Manifest merger failed:\n  {}", errors.join("\n  ")),
+    })?;
+    let manifest = out_dir.join("AndroidManifest.xml");
+    fs::write(&manifest, render_document(&merged.root)).map_err(KargoError::Io)?;
+
+    let (res_dir, warnings, layouts) = if inputs.res_dirs.is_empty() {
+        (None, Vec::new(), Vec::new())
+    } else {
+        let res_dir = out_dir.join("res");
+        let merged_res = merge_resources(&inputs.res_dirs, &res_dir)?;
+        (Some(res_dir), merged_res.warnings, merged_res.layouts)
+    };
+
+    let mut rules = keep_rules(&merged.root, &layouts);
+    for source in &inputs.consumer_rules {
+        let text = fs::read_to_string(&source.path).map_err(KargoError::Io)?;
+        rules.push_str(&format!(
+            "\n# Consumer rules of {}\n{}",
+            source.origin, text
+        ));
+        if !text.ends_with('\n') {
+            rules.push('\n');
+        }
+    }
+    let proguard_rules = out_dir.join(PROGUARD_RULES);
+    fs::write(&proguard_rules, rules).map_err(KargoError::Io)?;
+
+    let baseline_profile = if inputs.baseline_profiles.is_empty() {
+        None
+    } else {
+        let mut texts = Vec::new();
+        for source in &inputs.baseline_profiles {
+            texts.push(fs::read_to_string(&source.path).map_err(KargoError::Io)?);
+        }
+        let path = out_dir.join(BASELINE_PROFILE);
+        fs::write(&path, merge_baseline_profiles(&texts)).map_err(KargoError::Io)?;
+        Some(path)
+    };
+
+    Ok(MergeOutput {
+        manifest,
+        res_dir,
+        proguard_rules,
+        baseline_profile,
+        packages: merged.packages,
+        warnings,
+    })
+}
+
+/// Baseline profile rules from several files, in order, without comments,
+/// blank lines or repeated rules.
+fn merge_baseline_profiles(texts: &[String]) -> String {
+    let mut seen = BTreeSet::new();
+    let mut out = String::new();
+    for line in texts.iter().flat_map(|t| t.lines()).map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || !seen.insert(line) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// XML model
+// ---------------------------------------------------------------------------
+
+/// A parsed XML element. Comments, processing instructions and the XML
+/// declaration are dropped.
+#[derive(Debug, Clone)]
+struct Element {
+    name: String,
+    attrs: Vec<Attr>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+struct Attr {
+    name: String,
+    value: String,
+    /// Index of the input the attribute came from.
+    source: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.children.iter_mut().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// The `tools:node` merge rule.
+    fn tools_node(&self) -> Option<&str> {
+        self.attr("tools:node")
+    }
+
+    /// Comma-separated attribute names of a `tools:` list attribute.
+    fn tools_list(&self, name: &str) -> Vec<String> {
+        self.attr(name)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn parse_xml(text: &str, source: usize) -> Result<Element, String> {
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => stack.push(start_element(&e, source)?),
+            Ok(Event::Empty(e)) => {
+                let element = start_element(&e, source)?;
+                close_element(&mut stack, &mut root, element);
+            }
+            Ok(Event::End(_)) => {
+                let element = stack.pop().ok_or("unbalanced end tag")?;
+                close_element(&mut stack, &mut root, element);
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = t
+                        .unescape()
+                        .map(|c| c.into_owned())
+                        .unwrap_or_else(|_| String::from_utf8_lossy(&t).into_owned());
+                    parent.children.push(Node::Text(text));
+                }
+            }
+            Ok(Event::CData(t)) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = String::from_utf8_lossy(&t.into_inner()).into_owned();
+                    parent.children.push(Node::Text(text));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("at byte {}: {e}", reader.buffer_position())),
+        }
+    }
+    root.ok_or_else(|| "no root element".to_string())
+}
+
+fn start_element(e: &BytesStart, source: usize) -> Result<Element, String> {
+    let mut attrs = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| e.to_string())?;
+        attrs.push(Attr {
+            name: String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+            value: attr
+                .unescape_value()
+                .map_err(|e| e.to_string())?
+                .into_owned(),
+            source,
+        });
+    }
+    Ok(Element {
+        name: String::from_utf8_lossy(e.name().as_ref()).into_owned(),
+        attrs,
+        children: Vec::new(),
+    })
+}
+
+fn close_element(stack: &mut [Element], root: &mut Option<Element>, element: Element) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(Node::Element(element)),
+        None => *root = Some(element),
+    }
+}
+
+/// `element` as a standalone XML document.
+fn render_document(element: &Element) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    write_element(element, 0, &mut out);
+    out
+}
+
+/// Indented XML for `element`. Elements holding text are written on one
+/// line, exactly as parsed, so string resources keep their spacing.
+fn write_element(element: &Element, depth: usize, out: &mut String) {
+    let pad = "    ".repeat(depth);
+    out.push_str(&pad);
+    write_start(element, out);
+    if element.children.is_empty() {
+        out.push_str(" />\n");
+        return;
+    }
+    out.push('>');
+    let has_text = element
+        .children
+        .iter()
+        .any(|n| matches!(n, Node::Text(t) if !t.trim().is_empty()));
+    if has_text {
+        for node in &element.children {
+            write_inline(node, out);
+        }
+    } else {
+        out.push('\n');
+        for child in element.elements() {
+            write_element(child, depth + 1, out);
+        }
+        out.push_str(&pad);
+    }
+    out.push_str(&format!("</{}>\n", element.name));
+}
+
+fn write_start(element: &Element, out: &mut String) {
+    out.push('<');
+    out.push_str(&element.name);
+    for attr in &element.attrs {
+        out.push_str(&format!(" {}=\"{}\"", attr.name, escape(&attr.value, true)));
+    }
+}
+
+fn write_inline(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => out.push_str(&escape(text, false)),
+        Node::Element(element) => {
+            write_start(element, out);
+            if element.children.is_empty() {
+                out.push_str(" />");
+            } else {
+                out.push('>');
+                for child in &element.children {
+                    write_inline(child, out);
+                }
+                out.push_str(&format!("</{}>", element.name));
+            }
+        }
+    }
+}
+
+/// `element` on one line, for comparing definitions.
+fn inline(element: &Element) -> String {
+    let mut out = String::new();
+    write_inline(&Node::Element(element.clone()), &mut out);
+    out
+}
+
+fn escape(s: &str, attr: bool) -> String {
+    let mut out = s
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    if attr {
+        out = out.replace('"', "&quot;");
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Manifests
+// ---------------------------------------------------------------------------
+
+struct MergedManifest {
+    root: Element,
+    packages: Vec<String>,
+}
+
+/// Merge `(origin, xml)` manifests, highest priority first, collecting every
+/// conflict instead of stopping at the first.
+fn merge_manifests(
+    manifests: &[(String, String)],
+    inputs: &MergeInputs,
+) -> Result<MergedManifest, Vec<String>> {
+    let origins: Vec<&str> = manifests.iter().map(|(o, _)| o.as_str()).collect();
+    let mut errors = Vec::new();
+    let mut parsed = Vec::new();
+    for (i, (origin, xml)) in manifests.iter().enumerate() {
+        match parse_xml(xml, i) {
+            Ok(root) if root.name == "manifest" => parsed.push(root),
+            Ok(root) => errors.push(format!("{origin}: root element is <{}>", root.name)),
+            Err(e) => errors.push(format!("{origin}: invalid XML {e}")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let Some(app_id) = parsed[0].attr("package").map(str::to_string) else {
+        return Err(vec![format!("{}: <manifest> has no package", origins[0])]);
+    };
+    let mut vars = inputs.placeholders.clone();
+    vars.insert("applicationId".into(), app_id.clone());
+    vars.insert("packageName".into(), app_id.clone());
+
+    let mut packages = Vec::new();
+    let mut library_packages = Vec::new();
+    for (i, root) in parsed.iter_mut().enumerate() {
+        substitute_placeholders(root, &vars, origins[i], &mut errors);
+        let package = if i == 0 {
+            Some(app_id.clone())
+        } else {
+            let package = root.attr("package").map(str::to_string);
+            root.attrs.retain(|a| a.name != "package");
+            if let Some(p) = package.as_ref().filter(|p| !packages.contains(*p)) {
+                packages.push(p.clone());
+            }
+            package
+        };
+        if let Some(package) = &package {
+            qualify_class_names(root, package);
+        }
+        library_packages.push(package);
+    }
+
+    let mut parsed = parsed.into_iter();
+    let mut root = parsed.next().expect("at least one manifest");
+    let has_uses_sdk = root.elements().any(|e| e.name == "uses-sdk");
+    if let (false, Some(min_sdk)) = (has_uses_sdk, inputs.min_sdk) {
+        let mut attrs = vec![Attr {
+            name: "android:minSdkVersion".into(),
+            value: min_sdk.to_string(),
+            source: 0,
+        }];
+        if let Some(target_sdk) = inputs.target_sdk {
+            attrs.push(Attr {
+                name: "android:targetSdkVersion".into(),
+                value: target_sdk.to_string(),
+                source: 0,
+            });
+        }
+        root.children.insert(
+            0,
+            Node::Element(Element {
+                name: "uses-sdk".into(),
+                attrs,
+                children: Vec::new(),
+            }),
+        );
+    }
+    let uses_sdk = root.elements().find(|e| e.name == "uses-sdk");
+    let min_sdk = uses_sdk
+        .and_then(|e| e.attr("android:minSdkVersion"))
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(1);
+    let overridden = uses_sdk
+        .map(|e| e.tools_list("tools:overrideLibrary"))
+        .unwrap_or_default();
+
+    let mut merger = ManifestMerger {
+        origins: &origins,
+        errors,
+    };
+    for (i, library) in parsed.enumerate() {
+        let i = i + 1;
+        let library_min = library
+            .elements()
+            .find(|e| e.name == "uses-sdk")
+            .and_then(|e| e.attr("android:minSdkVersion"))
+            .and_then(|v| v.parse::<u32>().ok());
+        let package = library_packages[i].as_deref().unwrap_or_default();
+        if let Some(library_min) = library_min.filter(|m| *m > min_sdk) {
+            if !overridden.iter().any(|p| p == package) {
+                merger.errors.push(format!(
+                    "uses-sdk:minSdkVersion {min_sdk} cannot be smaller than version \
+                     {library_min} declared in {}. Raise [targets.android] min-sdk, or add \
+                     tools:overrideLibrary=\"{package}\" to <uses-sdk> in {}.",
+                    origins[i], origins[0]
+                ));
+            }
+        }
+        for attr in library
+            .attrs
+            .iter()
+            .filter(|a| a.name.starts_with("xmlns:"))
+        {
+            if root.attr(&attr.name).is_none() {
+                root.attrs.push(attr.clone());
+            }
+        }
+        merger.merge_children(&mut root, &library);
+    }
+    if !merger.errors.is_empty() {
+        return Err(merger.errors);
+    }
+    apply_tools_directives(&mut root);
+    Ok(MergedManifest { root, packages })
+}
+
+struct ManifestMerger<'a> {
+    origins: &'a [&'a str],
+    errors: Vec<String>,
+}
+
+impl ManifestMerger<'_> {
+    fn merge_children(&mut self, high: &mut Element, low: &Element) {
+        for child in low.elements() {
+            if child.name == "uses-sdk"
+                || high
+                    .elements()
+                    .any(|h| h.name == child.name && h.tools_node() == Some("removeAll"))
+            {
+                continue;
+            }
+            let key = merge_key(child);
+            let Some(h) = high.elements_mut().find(|h| merge_key(h) == key) else {
+                high.children.push(Node::Element(child.clone()));
+                continue;
+            };
+            if !matches!(h.tools_node(), Some("remove" | "replace")) {
+                self.merge_attrs(h, child);
+                self.merge_children(h, child);
+            }
+        }
+    }
+
+    fn merge_attrs(&mut self, high: &mut Element, low: &Element) {
+        let replaced = high.tools_list("tools:replace");
+        for attr in &low.attrs {
+            if attr.name.starts_with("tools:") || attr.name.starts_with("xmlns") {
+                continue;
+            }
+            match high.attrs.iter().find(|a| a.name == attr.name) {
+                None => high.attrs.push(attr.clone()),
+                Some(existing) if existing.value == attr.value || replaced.contains(&attr.name) => {
+                }
+                Some(existing) => self.errors.push(format!(
+                    "Attribute {}@{} value=({}) from {} is also present at {} value=({}). \
+                     Add tools:replace=\"{}\" to <{}> in {} to override.",
+                    high.name,
+                    attr.name,
+                    existing.value,
+                    self.origins[existing.source],
+                    self.origins[attr.source],
+                    attr.value,
+                    attr.name,
+                    high.name,
+                    self.origins[0],
+                )),
+            }
+        }
+    }
+}
+
+/// Identity of a manifest element when merging: its tag and `android:name`,
+/// the tag for singletons, else its full contents (intent filters, `<data>`).
+fn merge_key(element: &Element) -> String {
+    if let Some(name) = element.attr("android:name") {
+        return format!("{}#{name}", element.name);
+    }
+    if SINGLETONS.contains(&element.name.as_str()) {
+        return element.name.clone();
+    }
+    let mut stripped = element.clone();
+    strip_tools(&mut stripped);
+    inline(&stripped)
+}
+
+fn substitute_placeholders(
+    element: &mut Element,
+    vars: &BTreeMap<String, String>,
+    origin: &str,
+    errors: &mut Vec<String>,
+) {
+    for attr in &mut element.attrs {
+        match substitute(&attr.value, vars) {
+            Ok(value) => attr.value = value,
+            Err(name) => errors.push(format!(
+                "{origin}: <{}> {} uses placeholder ${{{name}}}, which is not defined; \
+                 set it in [targets.android] manifest-placeholders",
+                element.name, attr.name
+            )),
+        }
+    }
+    for child in element.elements_mut() {
+        substitute_placeholders(child, vars, origin, errors);
+    }
+}
+
+/// Replace `${name}` in `value`, or return the first undefined name.
+fn substitute(value: &str, vars: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &after[..end];
+        out.push_str(vars.get(name).ok_or_else(|| name.to_string())?);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Qualify `.Foo` and `Foo` class names of components with `package`.
+fn qualify_class_names(element: &mut Element, package: &str) {
+    if COMPONENTS.contains(&element.name.as_str()) {
+        for attr in &mut element.attrs {
+            if !CLASS_ATTRIBUTES.contains(&attr.name.as_str()) {
+                continue;
+            }
+            if attr.value.starts_with('.') {
+                attr.value = format!("{package}{}", attr.value);
+            } else if !attr.value.contains('.') && !attr.value.is_empty() {
+                attr.value = format!("{package}.{}", attr.value);
+            }
+        }
+    }
+    for child in element.elements_mut() {
+        qualify_class_names(child, package);
+    }
+}
+
+/// Drop `tools:node="remove"`/`"removeAll"` elements and `tools:remove`
+/// attributes, then every `tools:` attribute.
+fn apply_tools_directives(element: &mut Element) {
+    let removed = element.tools_list("tools:remove");
+    element.attrs.retain(|a| !removed.contains(&a.name));
+    element.children.retain(|n| match n {
+        Node::Element(e) => !matches!(e.tools_node(), Some("remove" | "removeAll")),
+        Node::Text(_) => true,
+    });
+    for child in element.elements_mut() {
+        apply_tools_directives(child);
+    }
+    strip_tools(element);
+}
+
+fn strip_tools(element: &mut Element) {
+    element
+        .attrs
+        .retain(|a| !a.name.starts_with("tools:") && a.name != "xmlns:tools");
+    for child in element.elements_mut() {
+        strip_tools(child);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Resources
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct MergedResources {
+    warnings: Vec<String>,
+    /// Parsed layouts, for keep rules.
+    layouts: Vec<Element>,
+}
+
+/// A resource chosen for the merged tree.
+struct Resource {
+    source: usize,
+    file: PathBuf,
+    /// The `values*` directory and definition, or `None` for file resources.
+    value: Option<(String, Element)>,
+}
+
+/// Merge `res/` directories, highest priority first, into `out`.
+fn merge_resources(sources: &[MergeSource], out: &Path) -> miette::Result<MergedResources> {
+    // Keyed by (type, qualifiers, name), e.g. ("string", "de", "app_name").
+    let mut resources: BTreeMap<(String, String, String), Resource> = BTreeMap::new();
+    let mut namespaces: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (i, source) in sources.iter().enumerate() {
+        let mut defined: BTreeMap<(String, String, String), PathBuf> = BTreeMap::new();
+        for dir in sorted_entries(&source.path)?
+            .into_iter()
+            .filter(|p| p.is_dir())
+        {
+            let dir_name = file_name(&dir);
+            let (kind, qualifiers) = match dir_name.split_once('-') {
+                Some((kind, qualifiers)) => (kind.to_string(), qualifiers.to_string()),
+                None => (dir_name.clone(), String::new()),
+            };
+            for file in sorted_entries(&dir)?.into_iter().filter(|p| p.is_file()) {
+                let mut found = Vec::new();
+                if kind == "values" {
+                    if !file.extension().is_some_and(|e| e == "xml") {
+                        continue;
+                    }
+                    let text = fs::read_to_string(&file).map_err(KargoError::Io)?;
+                    let root = match parse_xml(&text, i) {
+                        Ok(root) => root,
+                        Err(e) => {
+                            errors.push(format!("{}: invalid XML {e}", file.display()));
+                            continue;
+                        }
+                    };
+                    let ns = namespaces.entry(dir_name.clone()).or_default();
+                    for attr in root.attrs.iter().filter(|a| a.name.starts_with("xmlns:")) {
+                        ns.entry(attr.name.clone())
+                            .or_insert_with(|| attr.value.clone());
+                    }
+                    for element in root.elements() {
+                        if let Some((kind, name)) = value_key(element) {
+                            found.push((kind, name, Some((dir_name.clone(), element.clone()))));
+                        }
+                    }
+                } else {
+                    let name = file_name(&file);
+                    let name = name.split('.').next().unwrap_or_default().to_string();
+                    found.push((kind.clone(), name, None));
+                }
+
+                for (kind, name, value) in found {
+                    let key = (kind, qualifiers.clone(), name);
+                    if let Some(previous) = defined.insert(key.clone(), file.clone()) {
+                        errors.push(format!(
+                            "{} is defined twice in {}: {} and {}",
+                            resource_label(&key),
+                            source.origin,
+                            previous.display(),
+                            file.display()
+                        ));
+                        continue;
+                    }
+                    match resources.get(&key) {
+                        None => {
+                            resources.insert(
+                                key,
+                                Resource {
+                                    source: i,
+                                    file: file.clone(),
+                                    value,
+                                },
+                            );
+                        }
+                        Some(winner) => {
+                            let winner_source = &sources[winner.source];
+                            if winner_source.library
+                                && source.library
+                                && !same_definition(winner, &file, value.as_ref())?
+                            {
+                                warnings.push(format!(
+                                    "{} differs between {} and {}; using {}",
+                                    resource_label(&key),
+                                    winner_source.origin,
+                                    source.origin,
+                                    winner_source.origin
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(KargoError::Generic {
+            message: format!("Resource merging failed:\n  {}", errors.join("\n  ")),
+        }
+        .into());
+    }
+
+    fs::create_dir_all(out).map_err(KargoError::Io)?;
+    let mut values: BTreeMap<&str, Vec<&Element>> = BTreeMap::new();
+    let mut layouts = Vec::new();
+    for ((kind, _, _), resource) in &resources {
+        match &resource.value {
+            Some((dir, element)) => values.entry(dir).or_default().push(element),
+            None => {
+                let dir = out.join(file_name(resource.file.parent().unwrap_or(out)));
+                fs::create_dir_all(&dir).map_err(KargoError::Io)?;
+                fs::copy(&resource.file, dir.join(file_name(&resource.file)))
+                    .map_err(KargoError::Io)?;
+                if kind == "layout" {
+                    let text = fs::read_to_string(&resource.file).map_err(KargoError::Io)?;
+                    layouts.extend(parse_xml(&text, resource.source).ok());
+                }
+            }
+        }
+    }
+    for (dir, elements) in values {
+        let attrs = namespaces
+            .get(dir)
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| Attr {
+                name: name.clone(),
+                value: value.clone(),
+                source: 0,
+            })
+            .collect();
+        let root = Element {
+            name: "resources".into(),
+            attrs,
+            children: elements.into_iter().cloned().map(Node::Element).collect(),
+        };
+        let dir = out.join(dir);
+        fs::create_dir_all(&dir).map_err(KargoError::Io)?;
+        fs::write(dir.join("values.xml"), render_document(&root)).map_err(KargoError::Io)?;
+    }
+    Ok(MergedResources { warnings, layouts })
+}
+
+/// Resource type and name of a `<resources>` child.
+fn value_key(element: &Element) -> Option<(String, String)> {
+    let kind = match element.name.as_str() {
+        "eat-comment" | "skip" | "public" => return None,
+        "item" => element.attr("type")?,
+        "declare-styleable" => "styleable",
+        "string-array" | "integer-array" => "array",
+        other => other,
+    };
+    Some((kind.to_string(), element.attr("name")?.to_string()))
+}
+
+fn same_definition(
+    winner: &Resource,
+    file: &Path,
+    value: Option<&(String, Element)>,
+) -> miette::Result<bool> {
+    Ok(match (&winner.value, value) {
+        (Some((_, a)), Some((_, b))) => inline(a) == inline(b),
+        (None, None) => {
+            fs::read(&winner.file).map_err(KargoError::Io)?
+                == fs::read(file).map_err(KargoError::Io)?
+        }
+        _ => false,
+    })
+}
+
+/// `string/app_name` or `drawable-hdpi/icon`.
+fn resource_label((kind, qualifiers, name): &(String, String, String)) -> String {
+    if qualifiers.is_empty() {
+        format!("{kind}/{name}")
+    } else {
+        format!("{kind}-{qualifiers}/{name}")
+    }
+}
+
+fn sorted_entries(dir: &Path) -> miette::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(KargoError::Io)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// Keep rules
+// ---------------------------------------------------------------------------
+
+/// Keep rules for the classes `manifest` and `layouts` name.
+fn keep_rules(manifest: &Element, layouts: &[Element]) -> String {
+    let mut rules = BTreeSet::new();
+    collect_component_rules(manifest, &mut rules);
+    for layout in layouts {
+        collect_view_rules(layout, &mut rules);
+    }
+    let mut out = String::from("# Generated by Kargo from the merged manifest and layouts.\n");
+    for rule in rules {
+        out.push_str(&rule);
+        out.push('\n');
+    }
+    out
+}
+
+fn collect_component_rules(element: &Element, rules: &mut BTreeSet<String>) {
+    if COMPONENTS.contains(&element.name.as_str()) && element.name != "activity-alias" {
+        for attr in &element.attrs {
+            if CLASS_ATTRIBUTES.contains(&attr.name.as_str())
+                && attr.name != "android:targetActivity"
+            {
+                rules.insert(format!("-keep class {} {{ <init>(); }}", attr.value));
+            }
+        }
+    }
+    for child in element.elements() {
+        collect_component_rules(child, rules);
+    }
+}
+
+fn collect_view_rules(element: &Element, rules: &mut BTreeSet<String>) {
+    let view = match element.name.as_str() {
+        "view" => element.attr("class"),
+        "fragment" => {
+            if let Some(class) = element.attr("android:name").or(element.attr("class")) {
+                rules.insert(format!("-keep class {class} {{ <init>(); }}"));
+            }
+            None
+        }
+        name if name.contains('.') => Some(name),
+        _ => None,
+    };
+    if let Some(view) = view {
+        rules.insert(format!(
+            "-keep class {view} {{ <init>(android.content.Context, android.util.AttributeSet); }}"
+        ));
+    }
+    for child in element.elements() {
+        collect_view_rules(child, rules);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APP: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+    xmlns:tools="http://schemas.android.com/tools"
+    package="com.example.app">
+    <application android:label="@string/app_name" android:allowBackup="false"
+        tools:replace="android:allowBackup">
+        <activity android:name=".MainActivity" android:exported="true">
+            <intent-filter>
+                <action android:name="android.intent.action.MAIN" />
+                <category android:name="android.intent.category.LAUNCHER" />
+            </intent-filter>
+        </activity>
+        <provider android:name="androidx.startup.InitializationProvider"
+            tools:node="remove" />
+    </application>
+</manifest>
+"#;
+
+    const LIBRARY: &str = r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+    package="androidx.startup">
+    <uses-sdk android:minSdkVersion="19" />
+    <uses-permission android:name="android.permission.INTERNET" />
+    <application android:allowBackup="true">
+        <provider android:name=".InitializationProvider"
+            android:authorities="${applicationId}.androidx-startup" />
+        <service android:name="SyncService" />
+    </application>
+</manifest>
+"#;
+
+    fn inputs(min_sdk: u32) -> MergeInputs {
+        MergeInputs {
+            min_sdk: Some(min_sdk),
+            target_sdk: Some(35),
+            ..Default::default()
+        }
+    }
+
+    fn merge_xml(manifests: &[(&str, &str)], inputs: &MergeInputs) -> Result<String, Vec<String>> {
+        let manifests: Vec<(String, String)> = manifests
+            .iter()
+            .map(|(o, x)| (o.to_string(), x.to_string()))
+            .collect();
+        merge_manifests(&manifests, inputs).map(|m| render_document(&m.root))
+    }
+
+    #[test]
+    fn merges_library_manifests() {
+        let merged = merge_xml(
+            &[
+                ("src/main/AndroidManifest.xml", APP),
+                ("androidx.startup:startup-runtime:1.1.1", LIBRARY),
+            ],
+            &inputs(24),
+        )
+        .unwrap();
+        assert_eq!(
+            merged,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="com.example.app">
+    <uses-sdk android:minSdkVersion="24" android:targetSdkVersion="35" />
+    <application android:label="@string/app_name" android:allowBackup="false">
+        <activity android:name="com.example.app.MainActivity" android:exported="true">
+            <intent-filter>
+                <action android:name="android.intent.action.MAIN" />
+                <category android:name="android.intent.category.LAUNCHER" />
+            </intent-filter>
+        </activity>
+        <service android:name="androidx.startup.SyncService" />
+    </application>
+    <uses-permission android:name="android.permission.INTERNET" />
+</manifest>
+"#
+        );
+    }
+
+    #[test]
+    fn reports_conflicts_and_unknown_placeholders() {
+        let app = APP.replace(" tools:replace=\"android:allowBackup\"", "");
+        let errors = merge_xml(
+            &[
+                ("src/main/AndroidManifest.xml", &app),
+                ("lib:a:1.0", LIBRARY),
+            ],
+            &inputs(16),
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with(
+            "uses-sdk:minSdkVersion 16 cannot be smaller than version 19 declared in lib:a:1.0."
+        ));
+        assert!(errors[1].starts_with(
+            "Attribute application@android:allowBackup value=(false) from \
+             src/main/AndroidManifest.xml is also present at lib:a:1.0 value=(true)."
+        ));
+
+        let library = LIBRARY.replace("${applicationId}", "${hostName}");
+        let errors = merge_xml(&[("main", APP), ("lib:a:1.0", &library)], &inputs(24)).unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "lib:a:1.0: <provider> android:authorities uses placeholder ${hostName}, which \
+              is not defined; set it in [targets.android] manifest-placeholders"
+            ]
+        );
+
+        let mut with_host = inputs(24);
+        with_host
+            .placeholders
+            .insert("hostName".into(), "example.com".into());
+        let app = APP.replace(" tools:node=\"remove\"", "");
+        let merged = merge_xml(&[("main", &app), ("lib:a:1.0", &library)], &with_host).unwrap();
+        assert!(merged.contains("android:authorities=\"example.com.androidx-startup\""));
+    }
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    fn source(origin: &str, path: PathBuf, library: bool) -> MergeSource {
+        MergeSource {
+            origin: origin.into(),
+            path,
+            library,
+        }
+    }
+
+    #[test]
+    fn merges_resources_by_priority() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        let lib_a = tmp.path().join("a");
+        let lib_b = tmp.path().join("b");
+        write(
+            &app.join("values/strings.xml"),
+            "<resources><string name=\"app_name\">My <b>App</b></string></resources>",
+        );
+        write(&app.join("layout/main.xml"), "<com.example.ChartView />");
+        write(
+            &lib_a.join("values/values.xml"),
+            "<resources xmlns:xliff=\"urn:oasis:names:tc:xliff:document:1.2\">\n\
+             <string name=\"app_name\">Library</string>\n\
+             <color name=\"accent\">#FF0000</color>\n\
+             <item type=\"id\" name=\"toolbar\" />\n\
+             </resources>",
+        );
+        write(&lib_a.join("drawable-hdpi/icon.png"), "a");
+        write(
+            &lib_b.join("values/values.xml"),
+            "<resources><color name=\"accent\">#00FF00</color></resources>",
+        );
+        write(&lib_b.join("drawable-hdpi/icon.png"), "a");
+
+        let out = tmp.path().join("out");
+        let merged = merge_resources(
+            &[
+                source("src/main/res", app.clone(), false),
+                source("lib:a:1.0", lib_a, true),
+                source("lib:b:1.0", lib_b, true),
+            ],
+            &out,
+        )
+        .unwrap();
+        assert_eq!(
+            merged.warnings,
+            ["color/accent differs between lib:a:1.0 and lib:b:1.0; using lib:a:1.0"]
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("values/values.xml")).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <resources xmlns:xliff=\"urn:oasis:names:tc:xliff:document:1.2\">\n\
+             \x20   <color name=\"accent\">#FF0000</color>\n\
+             \x20   <item type=\"id\" name=\"toolbar\" />\n\
+             \x20   <string name=\"app_name\">My <b>App</b></string>\n\
+             </resources>\n"
+        );
+        assert!(out.join("drawable-hdpi/icon.png").is_file());
+        assert!(out.join("layout/main.xml").is_file());
+        assert_eq!(merged.layouts.len(), 1);
+
+        write(
+            &app.join("values/more.xml"),
+            "<resources><string name=\"app_name\">x</string></resources>",
+        );
+        let err = merge_resources(&[source("src/main/res", app, false)], &out).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("string/app_name is defined twice in src/main/res"));
+    }
+
+    #[test]
+    fn baseline_profiles_are_concatenated_without_duplicates() {
+        let merged = merge_baseline_profiles(&[
+            "# App startup\nHSPLcom/example/app/MainActivity;->onCreate(Landroid/os/Bundle;)V\n\
+             Lcom/example/app/MainActivity;\n"
+                .into(),
+            "Lcom/example/app/MainActivity;\nHSPLandroidx/core/app/ComponentActivity;-><init>()V"
+                .into(),
+        ]);
+        assert_eq!(
+            merged,
+            "HSPLcom/example/app/MainActivity;->onCreate(Landroid/os/Bundle;)V\n\
+             Lcom/example/app/MainActivity;\n\
+             HSPLandroidx/core/app/ComponentActivity;-><init>()V\n"
+        );
+    }
+
+    #[test]
+    fn keep_rules_cover_components_and_custom_views() {
+        let manifest = parse_xml(
+            r#"<manifest package="com.example.app">
+                <application android:name="com.example.app.App">
+                    <activity android:name="com.example.app.MainActivity" />
+                    <activity-alias android:name="Launcher"
+                        android:targetActivity="com.example.app.MainActivity" />
+                </application>
+            </manifest>"#,
+            0,
+        )
+        .unwrap();
+        let layout = parse_xml(
+            r#"<LinearLayout><com.example.ChartView /><fragment android:name="com.example.ListFragment" /></LinearLayout>"#,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            keep_rules(&manifest, &[layout]),
+            "# Generated by Kargo from the merged manifest and layouts.\n\
+             -keep class com.example.ChartView { <init>(android.content.Context, android.util.AttributeSet); }\n\
+             -keep class com.example.ListFragment { <init>(); }\n\
+             -keep class com.example.app.App { <init>(); }\n\
+             -keep class com.example.app.MainActivity { <init>(); }\n"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Operation: `kargo api dump` and `kargo api check`.
+//!
+//! Builds the project and dumps the public ABI of its JVM classes to
+//! `api/<package>.api` in the binary-compatibility-validator format (see
+//! [`kargo_compiler::abi`]). `check` compares a fresh dump against the
+//! committed file and fails on any difference, so ABI changes cannot land
+//! without regenerating the dump.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::target::KotlinTarget;
+use kargo_util::errors::KargoError;
+
+use crate::ops_build::{self, BuildOptions};
+
+/// Path of the committed ABI dump for `package_name`.
+pub fn api_file(project_dir: &Path, package_name: &str) -> PathBuf {
+    project_dir.join("api").join(format!("{package_name}.api"))
+}
+
+/// Build the project and dump its current public ABI.
+async fn current_dump(project_dir: &Path, verbose: bool) -> miette::Result<(PathBuf, String)> {
+    let build_result = ops_build::build(
+        project_dir,
+        &BuildOptions {
+            verbose,
+            ..Default::default()
+        },
+    )
+    .await?;
+    if !matches!(
+        build_result.target,
+        KotlinTarget::Jvm | KotlinTarget::Android
+    ) {
+        return Err(KargoError::Generic {
+            message: format!(
+                "`kargo api` dumps JVM bytecode; the default target is {}",
+                build_result.target
+            ),
+        }
+        .into());
+    }
+    let dump = kargo_compiler::abi::dump_dir(&build_result.classes_dir)?;
+    Ok((
+        api_file(project_dir, &build_result.manifest.package.name),
+        dump,
+    ))
+}
+
+/// Write the current public ABI to `api/<package>.api`.
+pub async fn dump(project_dir: &Path, verbose: bool) -> miette::Result<()> {
+    let (path, dump) = current_dump(project_dir, verbose).await?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    std::fs::write(&path, dump).map_err(KargoError::Io)?;
+    kargo_util::progress::status(
+        "Dumped",
+        &format!("public API to {}", relative(project_dir, &path)),
+    );
+    Ok(())
+}
+
+/// Fail if the public ABI differs from the committed `api/<package>.api`.
+pub async fn check(project_dir: &Path, verbose: bool) -> miette::Result<()> {
+    let (path, dump) = current_dump(project_dir, verbose).await?;
+    let shown = relative(project_dir, &path);
+    let committed = std::fs::read_to_string(&path).map_err(|_| KargoError::Generic {
+        message: format!("No API dump at {shown}; run `kargo api dump` and commit it"),
+    })?;
+    // Tolerate checkouts that converted the dump to CRLF.
+    let committed = committed.replace("\r\n", "\n");
+    if committed == dump {
+        kargo_util::progress::status("Checked", &format!("public API matches {shown}"));
+        return Ok(());
+    }
+
+    eprintln!("--- {shown}");
+    eprintln!("+++ current public API");
+    for line in diff_lines(&committed, &dump) {
+        eprintln!("{line}");
+    }
+    Err(KargoError::Generic {
+        message: format!(
+            "API check failed: the public API differs from {shown}. If the change is \
+             intended, run `kargo api dump` and commit the result."
+        ),
+    }
+    .into())
+}
+
+fn relative(project_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(project_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Largest `old × new` line product diffed line by line; bigger changes
+/// list the removed block followed by the added one.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Lines removed from `old` (`-`) and added in `new` (`+`), in order.
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    let removed = |l: &&str| format!("-{l}");
+    let added = |l: &&str| format!("+{l}");
+    if old.len() * new.len() > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(removed)
+            .chain(new.iter().map(added))
+            .collect();
+    }
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push(added(&new[j]));
+            j += 1;
+        } else {
+            out.push(removed(&old[i]));
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_shows_removed_and_added_members() {
+        let old = "public final class a/A {\n\
+                   \tpublic fun <init> ()V\n\
+                   \tpublic final fun old ()V\n\
+                   }\n";
+        let new = "public final class a/A {\n\
+                   \tpublic fun <init> ()V\n\
+                   \tpublic final fun added (I)V\n\
+                   }\n";
+        assert_eq!(
+            diff_lines(old, new),
+            [
+                "+\tpublic final fun added (I)V",
+                "-\tpublic final fun old ()V"
+            ]
+        );
+        assert!(diff_lines(old, old).is_empty());
+    }
+
+    #[test]
+    fn api_file_is_named_after_the_package() {
+        assert_eq!(
+            api_file(Path::new("/p"), "mylib"),
+            Path::new("/p/api/mylib.api")
+        );
+    }
+}
@@ -1,11 +1,16 @@
-//! Operation: scan resolved dependencies for known vulnerabilities via OSV.
+//! Operation: scan resolved dependencies for known vulnerabilities via OSV,
+//! or submit the lockfile to GitHub's dependency graph so Dependabot can
+//! raise alerts for it.
 
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
 use kargo_core::lockfile::Lockfile;
 use kargo_core::manifest::Manifest;
+use kargo_lint::sarif::{Level, Location, Report, Rule, SarifResult};
 use kargo_maven::cache::LocalCache;
 use kargo_maven::download;
+use kargo_maven::license;
 use kargo_resolver::resolver;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -17,6 +22,70 @@ pub struct AuditOptions {
     pub fail_on: Option<String>,
     /// CVE IDs to ignore (from `[audit] ignore` in Kargo.toml).
     pub ignore: Vec<String>,
+    /// How findings are reported.
+    pub format: AuditFormat,
+}
+
+/// The `[audit]` table of `Kargo.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditConfig {
+    /// Vulnerability IDs to ignore (`ignore`).
+    pub ignore: Vec<String>,
+    /// SPDX identifiers dependencies may be licensed under
+    /// (`allowed-licenses`); no policy when empty.
+    pub allowed_licenses: Vec<String>,
+}
+
+impl AuditConfig {
+    /// Read `[audit]` from the manifest at `manifest_path`. A missing or
+    /// unreadable manifest has no `[audit]` settings.
+    pub fn load(manifest_path: &Path) -> Self {
+        let Some(doc) = std::fs::read_to_string(manifest_path)
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+        else {
+            return Self::default();
+        };
+        let list = |key: &str| -> Vec<String> {
+            doc.get("audit")
+                .and_then(|a| a.get(key))
+                .and_then(|i| i.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            ignore: list("ignore"),
+            allowed_licenses: list("allowed-licenses"),
+        }
+    }
+}
+
+/// Output format of `kargo audit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuditFormat {
+    /// A readable report on stdout.
+    #[default]
+    Human,
+    /// A SARIF 2.1.0 log on stdout, for code scanning.
+    Sarif,
+}
+
+impl AuditFormat {
+    /// Parse a `--format` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "sarif" => Ok(Self::Sarif),
+            other => Err(kargo_util::errors::KargoError::Generic {
+                message: format!("Unknown audit format '{other}'. Available: human, sarif"),
+            }
+            .into()),
+        }
+    }
 }
 
 /// A single vulnerability finding.
@@ -116,9 +185,9 @@ pub async fn audit(project_root: &Path, opts: &AuditOptions) -> miette::Result<(
     let repos = resolver::build_repos(&manifest);
     let cache = LocalCache::new(project_root);
 
-    let lockfile_path = project_root.join("Kargo.lock");
+    let lockfile_path = kargo_core::workspace::lockfile_path(project_root);
     let existing_lock = if lockfile_path.is_file() {
-        Lockfile::from_path(&lockfile_path).ok()
+        Lockfile::for_project(project_root).ok()
     } else {
         None
     };
@@ -133,11 +202,53 @@ pub async fn audit(project_root: &Path, opts: &AuditOptions) -> miette::Result<(
     let sp = kargo_util::progress::spinner(&format!(
         "Scanning {dep_count} dependencies for vulnerabilities..."
     ));
+    let all_findings = scan(&client, &result.artifacts, &opts.ignore).await?;
+    sp.finish_and_clear();
+
+    if opts.format == AuditFormat::Sarif {
+        let lockfile = std::fs::read_to_string(&lockfile_path).unwrap_or_default();
+        let log = sarif_report(&all_findings, &lockfile).to_json();
+        println!("{}", serde_json::to_string_pretty(&log).unwrap_or_default());
+    } else if all_findings.is_empty() {
+        kargo_util::progress::status(
+            "Audit",
+            &format!("{dep_count} dependencies — no vulnerabilities found"),
+        );
+    } else {
+        print_report(&all_findings, dep_count);
+    }
+
+    // Fail based on severity threshold
+    if let Some(ref threshold) = opts.fail_on {
+        let threshold_rank = severity_rank(threshold);
+        let has_severe = all_findings
+            .iter()
+            .any(|f| severity_rank(&f.severity) >= threshold_rank);
+        if has_severe {
+            return Err(kargo_util::errors::KargoError::Audit {
+                message: format!(
+                    "Audit failed: found vulnerabilities at or above '{}' severity",
+                    threshold
+                ),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
 
+/// Known vulnerabilities of `artifacts`, except those in `ignore`, most
+/// severe first.
+pub async fn scan(
+    client: &Client,
+    artifacts: &[resolver::ResolvedArtifact],
+    ignore: &[String],
+) -> miette::Result<Vec<Finding>> {
     let mut all_findings: Vec<Finding> = Vec::new();
 
     // Build queries in batches
-    for chunk in result.artifacts.chunks(BATCH_SIZE) {
+    for chunk in artifacts.chunks(BATCH_SIZE) {
         let queries: Vec<OsvQuery> = chunk
             .iter()
             .map(|a| OsvQuery {
@@ -150,35 +261,68 @@ pub async fn audit(project_root: &Path, opts: &AuditOptions) -> miette::Result<(
             .collect();
 
         let batch = OsvBatchQuery { queries };
-        let findings = query_osv_batch(&client, &batch, chunk).await?;
+        let findings = query_osv_batch(client, &batch, chunk).await?;
         all_findings.extend(findings);
     }
 
     // Apply ignores
-    all_findings.retain(|f| !opts.ignore.contains(&f.id));
+    all_findings.retain(|f| !ignore.contains(&f.id));
 
-    sp.finish_and_clear();
+    // Sort by severity (critical > high > moderate > low)
+    all_findings.sort_by_key(|f| std::cmp::Reverse(severity_rank(&f.severity)));
+    Ok(all_findings)
+}
 
-    if all_findings.is_empty() {
-        kargo_util::progress::status(
-            "Audit",
-            &format!("{dep_count} dependencies — no vulnerabilities found"),
-        );
-        return Ok(());
+/// The `artifacts` whose license is not in `allowed`, as
+/// `group:artifact:version (license)`.
+pub async fn license_violations(
+    client: &Client,
+    repos: &[kargo_maven::repository::MavenRepository],
+    cache: &LocalCache,
+    artifacts: &[resolver::ResolvedArtifact],
+    allowed: &[String],
+) -> Vec<String> {
+    let mut violations = Vec::new();
+    for a in artifacts {
+        let resolved = license::resolve(client, repos, cache, &a.group, &a.artifact, &a.version)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("{e}");
+                None
+            })
+            .unwrap_or_default();
+        let expression = resolved.expression();
+        if !license_allowed(expression.as_deref(), allowed) {
+            violations.push(format!(
+                "{}:{}:{} ({})",
+                a.group,
+                a.artifact,
+                a.version,
+                expression.as_deref().unwrap_or("unknown license")
+            ));
+        }
     }
+    violations
+}
 
-    // Sort by severity (critical > high > moderate > low)
-    all_findings.sort_by(|a, b| severity_rank(&b.severity).cmp(&severity_rank(&a.severity)));
+/// Whether a license `expression` satisfies `allowed`: one of its `OR`
+/// alternatives must be allowed. An unknown license never is.
+pub fn license_allowed(expression: Option<&str>, allowed: &[String]) -> bool {
+    expression.is_some_and(|expr| {
+        expr.split(" OR ")
+            .any(|alt| allowed.iter().any(|a| a.eq_ignore_ascii_case(alt.trim())))
+    })
+}
 
-    // Print report
+fn print_report(findings: &[Finding], dep_count: usize) {
     println!();
     println!(
         "Found {} vulnerabilities in {dep_count} dependencies:",
-        all_findings.len()
+        findings.len()
     );
     println!();
 
-    for f in &all_findings {
+    for f in findings {
         let sev_display = if f.severity.is_empty() {
             "UNKNOWN".to_string()
         } else {
@@ -200,25 +344,102 @@ pub async fn audit(project_root: &Path, opts: &AuditOptions) -> miette::Result<(
         }
         println!();
     }
+}
 
-    // Fail based on severity threshold
-    if let Some(ref threshold) = opts.fail_on {
-        let threshold_rank = severity_rank(threshold);
-        let has_severe = all_findings
-            .iter()
-            .any(|f| severity_rank(&f.severity) >= threshold_rank);
-        if has_severe {
-            return Err(kargo_util::errors::KargoError::Generic {
-                message: format!(
-                    "Audit failed: found vulnerabilities at or above '{}' severity",
-                    threshold
-                ),
-            }
-            .into());
-        }
+/// SARIF log of `findings`, located at their `[[package]]` entries in
+/// `lockfile` (the content of `Kargo.lock`).
+pub fn sarif_report(findings: &[Finding], lockfile: &str) -> Report {
+    let mut report =
+        Report::new("kargo-audit", env!("CARGO_PKG_VERSION")).information_uri("https://osv.dev");
+    for f in findings {
+        let rank = severity_rank(&f.severity);
+        let level = match rank {
+            3.. => Level::Error,
+            1 => Level::Note,
+            _ => Level::Warning,
+        };
+        // GitHub maps these scores back to critical/high/medium/low.
+        let security_severity = match rank {
+            4 => 9.5,
+            3 => 8.0,
+            1 => 2.0,
+            _ => 5.5,
+        };
+        report.add_rule(Rule {
+            id: f.id.clone(),
+            name: None,
+            short_description: if f.summary.is_empty() {
+                f.id.clone()
+            } else {
+                f.summary.clone()
+            },
+            full_description: None,
+            help_uri: (!f.url.is_empty()).then(|| f.url.clone()),
+            default_level: level,
+            tags: vec![
+                "security".into(),
+                "vulnerability".into(),
+                "dependency".into(),
+            ],
+            security_severity: Some(security_severity),
+        });
+        let fix = f
+            .fixed
+            .as_deref()
+            .map(|v| format!("; fixed in {v}"))
+            .unwrap_or_default();
+        let in_lockfile = lockfile_line(lockfile, &f.group, &f.artifact);
+        report.add_result(SarifResult {
+            rule_id: f.id.clone(),
+            level,
+            message: format!(
+                "{}:{}:{} is affected by {}{fix}",
+                f.group, f.artifact, f.version, f.id
+            ),
+            location: Location {
+                uri: if in_lockfile.is_some() {
+                    "Kargo.lock"
+                } else {
+                    "Kargo.toml"
+                }
+                .into(),
+                line: Some(in_lockfile.unwrap_or(1)),
+                column: None,
+            },
+            identity: format!("{}:{}", f.group, f.artifact),
+        });
     }
+    report
+}
 
-    Ok(())
+/// 1-based line of the `[[package]]` entry for `group:name` in a lockfile.
+fn lockfile_line(lockfile: &str, group: &str, name: &str) -> Option<u32> {
+    let quoted = |key: &str, line: &str| {
+        line.strip_prefix(key)
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('='))
+            .map(|v| v.trim().trim_matches('"').to_string())
+    };
+    let mut block: Option<(u32, Option<String>, Option<String>)> = None;
+    for (i, line) in lockfile.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            block = (line == "[[package]]").then_some((i as u32 + 1, None, None));
+            continue;
+        }
+        let Some((start, block_name, block_group)) = block.as_mut() else {
+            continue;
+        };
+        if let Some(v) = quoted("name", line) {
+            *block_name = Some(v);
+        } else if let Some(v) = quoted("group", line) {
+            *block_group = Some(v);
+        }
+        if block_name.as_deref() == Some(name) && block_group.as_deref() == Some(group) {
+            return Some(*start);
+        }
+    }
+    None
 }
 
 async fn query_osv_batch(
@@ -351,3 +572,333 @@ fn severity_rank(severity: &str) -> u8 {
         _ => 0,
     }
 }
+
+/// Dependency submission API `detector` name.
+const DETECTOR_NAME: &str = "kargo";
+const DETECTOR_URL: &str = "https://github.com/dejanradmanovic/kargo";
+
+/// Where and for which commit a dependency snapshot is submitted.
+pub struct SnapshotJob {
+    /// The commit the lockfile belongs to.
+    pub sha: String,
+    /// The fully qualified ref, e.g. `refs/heads/main`.
+    pub git_ref: String,
+    /// Groups snapshots: a newer one replaces older ones with the same value.
+    pub correlator: String,
+    pub id: String,
+    /// RFC 3339 time of the scan.
+    pub scanned: String,
+}
+
+/// Package URL of a locked Maven package.
+fn maven_purl(group: &str, name: &str, version: &str) -> String {
+    format!("pkg:maven/{group}/{name}@{version}")
+}
+
+/// Convert `lockfile` into a GitHub dependency submission snapshot.
+///
+/// Packages declared in `Kargo.toml` (`direct`, as `(group, artifact)`) and
+/// packages nothing else depends on are direct; the rest are indirect.
+/// Test, integration-test and annotation-processor scopes are reported as
+/// `development`, everything else as `runtime`.
+pub fn dependency_snapshot(
+    lockfile: &Lockfile,
+    direct: &HashSet<(String, String)>,
+    job: &SnapshotJob,
+) -> serde_json::Value {
+    let referenced: HashSet<(&str, &str)> = lockfile
+        .package
+        .iter()
+        .flat_map(|p| &p.dependencies)
+        .map(|d| (d.group.as_str(), d.name.as_str()))
+        .collect();
+
+    let mut resolved = BTreeMap::new();
+    for pkg in &lockfile.package {
+        let purl = maven_purl(&pkg.group, &pkg.name, &pkg.version);
+        let is_direct = direct.contains(&(pkg.group.clone(), pkg.name.clone()))
+            || !referenced.contains(&(pkg.group.as_str(), pkg.name.as_str()));
+        let scope = match pkg.scope.as_deref() {
+            Some("test" | "integration-test" | "ksp" | "kapt") => "development",
+            _ => "runtime",
+        };
+        let dependencies: Vec<String> = pkg
+            .dependencies
+            .iter()
+            .map(|d| maven_purl(&d.group, &d.name, &d.version))
+            .collect();
+        resolved.insert(
+            purl.clone(),
+            serde_json::json!({
+                "package_url": purl,
+                "relationship": if is_direct { "direct" } else { "indirect" },
+                "scope": scope,
+                "dependencies": dependencies,
+            }),
+        );
+    }
+    for pkg in &lockfile.npm {
+        let purl = format!(
+            "pkg:npm/{}@{}",
+            pkg.name.replacen('@', "%40", 1),
+            pkg.version
+        );
+        resolved.insert(
+            purl.clone(),
+            serde_json::json!({
+                "package_url": purl,
+                "relationship": "direct",
+                "scope": "runtime",
+                "dependencies": [],
+            }),
+        );
+    }
+
+    serde_json::json!({
+        "version": 0,
+        "sha": job.sha,
+        "ref": job.git_ref,
+        "job": { "correlator": job.correlator, "id": job.id },
+        "detector": {
+            "name": DETECTOR_NAME,
+            "version": env!("CARGO_PKG_VERSION"),
+            "url": DETECTOR_URL,
+        },
+        "scanned": job.scanned,
+        "manifests": {
+            "Kargo.lock": {
+                "name": "Kargo.lock",
+                "file": { "source_location": "Kargo.lock" },
+                "resolved": resolved,
+            }
+        },
+    })
+}
+
+/// Describe the current commit, preferring the GitHub Actions environment.
+fn snapshot_job(project_root: &Path) -> miette::Result<SnapshotJob> {
+    use crate::ops_release::git;
+
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let sha = match env("GITHUB_SHA") {
+        Some(sha) => sha,
+        None => git(project_root, &["rev-parse", "HEAD"])?,
+    };
+    let git_ref = match env("GITHUB_REF") {
+        Some(r) => r,
+        None => git(project_root, &["symbolic-ref", "-q", "HEAD"]).map_err(|_| {
+            kargo_util::errors::KargoError::Generic {
+                message: "Cannot tell which branch to submit for (detached HEAD); set GITHUB_REF"
+                    .to_string(),
+            }
+        })?,
+    };
+    let correlator = match (env("GITHUB_WORKFLOW"), env("GITHUB_JOB")) {
+        (Some(workflow), Some(job)) => format!("kargo-audit {workflow} {job}"),
+        _ => "kargo-audit".to_string(),
+    };
+    let scanned = crate::ops_release::utc_timestamp();
+    let id = env("GITHUB_RUN_ID").unwrap_or_else(|| scanned.clone());
+    Ok(SnapshotJob {
+        sha,
+        git_ref,
+        correlator,
+        id,
+        scanned,
+    })
+}
+
+/// Submit `Kargo.lock` to the GitHub dependency graph of the repository in
+/// `GITHUB_REPOSITORY` or `[package] repository`.
+pub async fn submit_github(project_root: &Path) -> miette::Result<()> {
+    use kargo_util::errors::KargoError;
+
+    let manifest = Manifest::from_path(&project_root.join("Kargo.toml"))?;
+    if !kargo_core::workspace::lockfile_path(project_root).is_file() {
+        return Err(KargoError::Generic {
+            message: "No Kargo.lock found; run `kargo lock` before submitting dependencies"
+                .to_string(),
+        }
+        .into());
+    }
+    let lockfile = Lockfile::for_project(project_root)?;
+
+    let repo = std::env::var("GITHUB_REPOSITORY")
+        .ok()
+        .filter(|r| !r.is_empty())
+        .or_else(|| {
+            manifest
+                .package
+                .repository
+                .as_deref()
+                .and_then(crate::ops_release::github_repo)
+        })
+        .ok_or_else(|| KargoError::Manifest {
+            message: "Set GITHUB_REPOSITORY or a github.com [package] repository to submit \
+                      dependencies"
+                .to_string(),
+        })?;
+    let token = crate::ops_release::github_token().ok_or_else(|| KargoError::Generic {
+        message: "--submit-github needs a token in GITHUB_TOKEN or GH_TOKEN".to_string(),
+    })?;
+
+    let direct: HashSet<(String, String)> = crate::ops_fetch::collect_declared_deps(&manifest)
+        .into_iter()
+        .map(|(group, artifact, _)| (group, artifact))
+        .collect();
+    let snapshot = dependency_snapshot(&lockfile, &direct, &snapshot_job(project_root)?);
+
+    let url = format!(
+        "{}/repos/{repo}/dependency-graph/snapshots",
+        crate::ops_release::github_api_base()
+    );
+    let resp = download::build_client()?
+        .post(&url)
+        .bearer_auth(&token)
+        .header("Accept", "application/vnd.github+json")
+        .json(&snapshot)
+        .send()
+        .await
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to reach GitHub: {e}"),
+        })?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(KargoError::Network {
+            message: format!(
+                "GitHub dependency submission returned HTTP {status}: {}",
+                body.trim()
+            ),
+        }
+        .into());
+    }
+
+    let count = lockfile.package.len() + lockfile.npm.len();
+    kargo_util::progress::status(
+        "Submitted",
+        &format!("{count} dependencies from Kargo.lock to the {repo} dependency graph"),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_core::lockfile::{LockedDependencyRef, LockedPackage};
+
+    fn package(group: &str, name: &str, scope: &str, deps: &[(&str, &str)]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            group: group.to_string(),
+            version: "1.0".to_string(),
+            checksum: None,
+            source: None,
+            scope: Some(scope.to_string()),
+            targets: vec![],
+            dependencies: deps
+                .iter()
+                .map(|(g, n)| LockedDependencyRef {
+                    name: n.to_string(),
+                    group: g.to_string(),
+                    version: "1.0".to_string(),
+                })
+                .collect(),
+            relocated_from: None,
+            members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn snapshot_marks_direct_and_development_dependencies() {
+        let lockfile = Lockfile {
+            package: vec![
+                package("com.a", "app-lib", "compile", &[("com.b", "util")]),
+                package("com.b", "util", "compile", &[]),
+                package("org.junit", "junit", "test", &[]),
+            ],
+            ..Default::default()
+        };
+        let job = SnapshotJob {
+            sha: "0".repeat(40),
+            git_ref: "refs/heads/main".to_string(),
+            correlator: "kargo-audit".to_string(),
+            id: "1".to_string(),
+            scanned: "2024-03-04T05:06:07Z".to_string(),
+        };
+        let snapshot = dependency_snapshot(&lockfile, &HashSet::new(), &job);
+        assert_eq!(snapshot["ref"], "refs/heads/main");
+        assert_eq!(snapshot["detector"]["name"], "kargo");
+
+        let resolved = &snapshot["manifests"]["Kargo.lock"]["resolved"];
+        let app = &resolved["pkg:maven/com.a/app-lib@1.0"];
+        assert_eq!(app["relationship"], "direct");
+        assert_eq!(app["scope"], "runtime");
+        assert_eq!(
+            app["dependencies"],
+            serde_json::json!(["pkg:maven/com.b/util@1.0"])
+        );
+        assert_eq!(
+            resolved["pkg:maven/com.b/util@1.0"]["relationship"],
+            "indirect"
+        );
+        assert_eq!(
+            resolved["pkg:maven/org.junit/junit@1.0"]["scope"],
+            "development"
+        );
+
+        // Declared in Kargo.toml, so direct even though something depends on it.
+        let declared = HashSet::from([("com.b".to_string(), "util".to_string())]);
+        let snapshot = dependency_snapshot(&lockfile, &declared, &job);
+        assert_eq!(
+            snapshot["manifests"]["Kargo.lock"]["resolved"]["pkg:maven/com.b/util@1.0"]
+                ["relationship"],
+            "direct"
+        );
+    }
+
+    #[test]
+    fn sarif_results_point_at_the_lockfile_entry() {
+        let lockfile = "version = 1\n\n\
+                        [[package]]\nname = \"util\"\ngroup = \"com.b\"\nversion = \"1.0\"\n\n\
+                        [[package]]\nname = \"jackson-databind\"\ngroup = \"com.fasterxml.jackson.core\"\n\
+                        version = \"2.9.0\"\n";
+        let finding = Finding {
+            id: "GHSA-57j2-w4cx-62h2".into(),
+            summary: "Deserialization of untrusted data".into(),
+            severity: "high".into(),
+            group: "com.fasterxml.jackson.core".into(),
+            artifact: "jackson-databind".into(),
+            version: "2.9.0".into(),
+            fixed: Some("2.9.10.8".into()),
+            url: "https://osv.dev/vulnerability/GHSA-57j2-w4cx-62h2".into(),
+        };
+        let log = sarif_report(&[finding], lockfile).to_json();
+        let run = &log["runs"][0];
+        let rule = &run["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["id"], "GHSA-57j2-w4cx-62h2");
+        assert_eq!(rule["properties"]["security-severity"], "8.0");
+
+        let result = &run["results"][0];
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "Kargo.lock");
+        assert_eq!(location["region"]["startLine"], 8);
+        assert!(result["message"]["text"]
+            .as_str()
+            .unwrap()
+            .ends_with("fixed in 2.9.10.8"));
+
+        assert_eq!(lockfile_line(lockfile, "com.b", "util"), Some(3));
+        assert_eq!(lockfile_line(lockfile, "com.b", "missing"), None);
+    }
+
+    #[test]
+    fn license_policy_accepts_any_allowed_alternative() {
+        let allowed = vec!["Apache-2.0".to_string(), "mit".to_string()];
+        assert!(license_allowed(Some("Apache-2.0"), &allowed));
+        assert!(license_allowed(Some("EPL-2.0 OR MIT"), &allowed));
+        assert!(!license_allowed(Some("GPL-3.0-only"), &allowed));
+        assert!(!license_allowed(None, &allowed));
+    }
+}
@@ -0,0 +1,300 @@
+//! Operation: Android baseline profiles (`kargo android baseline-profile`).
+//!
+//! A baseline profile lists the classes and methods an app runs at startup
+//! and on its critical paths, as `baseline-prof.txt` rules such as
+//! `HSPLcom/example/MainActivity;->onCreate(Landroid/os/Bundle;)V`. Android
+//! compiles them ahead of time on install, so they run without JIT warm-up.
+//!
+//! The profile is generated on a connected device running Android 13 (API
+//! 33) or newer: the instrumentation tests in `src/androidTest/kotlin` drive
+//! the app, a generated JUnit listener makes ART write the recorded profile
+//! once they finish, and `pm dump-profiles` turns it into rules. Rules for
+//! the tests and the test runner are dropped, and the rest are written to
+//! `src/main/baseline-prof.txt` (`src/androidMain/` in the multiplatform
+//! layout).
+//!
+//! Builds merge that file with the profiles shipped in AARs (see
+//! [`crate::ops_android_merge`]), and packaging compiles the result with the
+//! SDK's `profgen` into `assets/dexopt/baseline.prof` and `baseline.profm`
+//! of the APK.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use kargo_toolchain::sdk::AndroidSdkInfo;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+use kargo_util::progress::status;
+
+use crate::ops_android_merge::{BASELINE_PROFILE, MAIN_SOURCE_SETS};
+use crate::ops_device_test::{self, path_str, run_tool, script, RunPurpose};
+use crate::ops_test::TestOptions;
+
+/// Oldest Android version that can dump a profile as rules.
+pub const MIN_DEVICE_API: u32 = 33;
+
+/// The JUnit listener added to the test APK while generating a profile.
+pub const LISTENER_CLASS: &str = "kargo.baselineprofile.ProfileFlushListener";
+
+/// Source of [`LISTENER_CLASS`].
+pub const LISTENER_SOURCE: &str = "\
+package kargo.baselineprofile
+
+import android.os.Process
+import org.junit.runner.Result
+import org.junit.runner.notification.RunListener
+
+/** Generated by Kargo: makes ART write the profile once the tests have run. */
+class ProfileFlushListener : RunListener() {
+    override fun testRunFinished(result: Result) {
+        Process.sendSignal(Process.myPid(), Process.SIGNAL_USR1)
+        // The profile saver writes asynchronously.
+        Thread.sleep(2000)
+    }
+}
+";
+
+/// Packages of the test runner and of the listener, whose classes never
+/// belong in the app's profile.
+const EXCLUDED_PACKAGES: &[&str] = &[
+    "kargo/baselineprofile/",
+    "androidx/test/",
+    "org/junit/",
+    "junit/",
+    "org/hamcrest/",
+    "kotlin/test/",
+];
+
+/// APK entries of the compiled profile.
+const DEXOPT_ENTRIES: &[&str] = &[
+    "assets/dexopt/baseline.prof",
+    "assets/dexopt/baseline.profm",
+];
+
+/// Run the profile generator tests on a device and write the recorded
+/// rules to the project's `baseline-prof.txt`, returning its path.
+pub async fn generate(project_dir: &Path, opts: &TestOptions) -> miette::Result<PathBuf> {
+    let serial = opts.device.clone().unwrap_or_default();
+    let run = ops_device_test::run(project_dir, opts, &serial, RunPurpose::BaselineProfile)
+        .await?
+        .ok_or_else(|| KargoError::Generic {
+            message: "No profile generator found: baseline profiles are recorded while the \
+                      tests in src/androidTest/kotlin run"
+                .into(),
+        })?;
+
+    status("Collecting", &format!("baseline profile of {}", run.app_id));
+    let adb = |args: &[&str]| {
+        CommandBuilder::new(path_str(&run.adb))
+            .args(["-s", run.serial.as_str()])
+            .args(args.iter().copied())
+    };
+    run_tool(
+        adb(&[
+            "shell",
+            "pm",
+            "dump-profiles",
+            "--dump-classes-and-methods",
+            &run.app_id,
+        ]),
+        "pm dump-profiles",
+    )?;
+    let dump = adb(&[
+        "shell",
+        "cat",
+        &format!("/data/misc/profman/{}-primary.prof.txt", run.app_id),
+    ])
+    .exec()
+    .map_err(|e| KargoError::Generic {
+        message: format!("Failed to run adb: {e}"),
+    })?;
+    let excluded = class_names(&run.test_classes);
+    let rules = filter_rules(&String::from_utf8_lossy(&dump.stdout), &excluded);
+    if rules.is_empty() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{} recorded no profile on {}; make sure the tests exercise the app",
+                run.app_id, run.serial
+            ),
+        }
+        .into());
+    }
+
+    let path = output_path(project_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    let mut text = format!(
+        "# Generated by `kargo android baseline-profile` on {}.\n",
+        run.serial
+    );
+    for rule in &rules {
+        text.push_str(rule);
+        text.push('\n');
+    }
+    std::fs::write(&path, text).map_err(KargoError::Io)?;
+    status("Recorded", &format!("{} rules", rules.len()));
+    Ok(path)
+}
+
+/// Where the project's baseline profile lives: next to its
+/// `AndroidManifest.xml` when it has one, else under `src/main/`.
+pub fn output_path(project_dir: &Path) -> PathBuf {
+    let src = project_dir.join("src");
+    let dir = MAIN_SOURCE_SETS
+        .iter()
+        .map(|name| src.join(name))
+        .find(|dir| dir.join(BASELINE_PROFILE).is_file())
+        .or_else(|| {
+            MAIN_SOURCE_SETS
+                .iter()
+                .map(|name| src.join(name))
+                .find(|dir| dir.join("AndroidManifest.xml").is_file())
+        })
+        .unwrap_or_else(|| src.join("main"));
+    dir.join(BASELINE_PROFILE)
+}
+
+/// The class a rule applies to, in internal form (`com/example/Main`).
+pub fn rule_class(rule: &str) -> Option<&str> {
+    rule.trim_start_matches(['H', 'S', 'P'])
+        .strip_prefix('L')?
+        .split_once(';')
+        .map(|(class, _)| class)
+}
+
+/// The rules of a profile dump, without those for `excluded` classes, the
+/// test runner, or repeats.
+pub fn filter_rules(dump: &str, excluded: &BTreeSet<String>) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    dump.lines()
+        .map(str::trim)
+        .filter(|line| {
+            let Some(class) = rule_class(line) else {
+                return false;
+            };
+            let outer = class.split('$').next().unwrap_or(class);
+            !excluded.contains(outer)
+                && !EXCLUDED_PACKAGES.iter().any(|p| class.starts_with(p))
+                && seen.insert(*line)
+        })
+        .map(String::from)
+        .collect()
+}
+
+/// Internal names of the classes compiled into `dir`.
+fn class_names(dir: &Path) -> BTreeSet<String> {
+    fn walk(root: &Path, dir: &Path, out: &mut BTreeSet<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                walk(root, &path, out);
+            } else if path.extension().is_some_and(|e| e == "class") {
+                if let Ok(relative) = path.with_extension("").strip_prefix(root) {
+                    out.insert(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+    let mut out = BTreeSet::new();
+    walk(dir, dir, &mut out);
+    out
+}
+
+/// The API level of the device `serial`.
+pub fn device_api_level(adb: &Path, serial: &str) -> Option<u32> {
+    let out = CommandBuilder::new(path_str(adb))
+        .args(["-s", serial, "shell", "getprop", "ro.build.version.sdk"])
+        .exec()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout).trim().parse().ok()
+}
+
+/// `profgen` of the SDK's command-line tools.
+pub fn profgen_path(sdk: &AndroidSdkInfo) -> PathBuf {
+    sdk.home
+        .join("cmdline-tools")
+        .join("latest")
+        .join("bin")
+        .join(script("profgen"))
+}
+
+/// Compile `rules` against the dex files of `apk` into `out_dir`, returning
+/// the APK entries to add as `(name, file)`.
+pub fn compile(
+    profgen: &Path,
+    rules: &Path,
+    apk: &Path,
+    out_dir: &Path,
+) -> miette::Result<Vec<(String, PathBuf)>> {
+    std::fs::create_dir_all(out_dir).map_err(KargoError::Io)?;
+    let entries: Vec<(String, PathBuf)> = DEXOPT_ENTRIES
+        .iter()
+        .map(|name| {
+            let file = name.rsplit('/').next().unwrap_or(name);
+            (name.to_string(), out_dir.join(file))
+        })
+        .collect();
+    run_tool(
+        CommandBuilder::new(path_str(profgen))
+            .arg("bin")
+            .arg(path_str(rules))
+            .arg("--apk")
+            .arg(path_str(apk))
+            .arg("--output")
+            .arg(path_str(&entries[0].1))
+            .arg("--output-meta")
+            .arg(path_str(&entries[1].1)),
+        "profgen",
+    )?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_app_rules_only() {
+        let dump = "\
+HSPLcom/example/app/MainActivity;->onCreate(Landroid/os/Bundle;)V
+Lcom/example/app/MainActivity;
+HSPLcom/example/app/StartupTest;->opensMainScreen()V
+Lcom/example/app/StartupTest$1;
+PLandroidx/test/runner/AndroidJUnitRunner;->onStart()V
+Lkargo/baselineprofile/ProfileFlushListener;
+HSPLandroidx/core/app/ComponentActivity;-><init>()V
+Lcom/example/app/MainActivity;
+not a rule
+";
+        let excluded = BTreeSet::from(["com/example/app/StartupTest".to_string()]);
+        assert_eq!(
+            filter_rules(dump, &excluded),
+            [
+                "HSPLcom/example/app/MainActivity;->onCreate(Landroid/os/Bundle;)V",
+                "Lcom/example/app/MainActivity;",
+                "HSPLandroidx/core/app/ComponentActivity;-><init>()V",
+            ]
+        );
+        assert_eq!(rule_class("SPLa/B;->c()V"), Some("a/B"));
+        assert_eq!(rule_class("# comment"), None);
+    }
+
+    #[test]
+    fn profile_lives_next_to_the_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            output_path(tmp.path()),
+            tmp.path().join("src/main/baseline-prof.txt")
+        );
+        let android_main = tmp.path().join("src/androidMain");
+        std::fs::create_dir_all(&android_main).unwrap();
+        std::fs::write(android_main.join("AndroidManifest.xml"), "<manifest/>").unwrap();
+        assert_eq!(
+            output_path(tmp.path()),
+            android_main.join("baseline-prof.txt")
+        );
+    }
+}
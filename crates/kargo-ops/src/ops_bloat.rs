@@ -0,0 +1,382 @@
+//! Operation: `kargo bloat` — how much each dependency adds to the runtime
+//! classpath.
+//!
+//! Sizes come from the cached artifacts of the packages in `Kargo.lock`
+//! that are on the runtime classpath (`compile` and `runtime` scopes).
+//! "Compressed" is the sum of the JAR's compressed entry sizes, which is
+//! what the artifact adds to a fat JAR; "uncompressed" is the sum of the
+//! entry sizes once extracted.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use kargo_core::lockfile::{LockedPackage, Lockfile};
+use kargo_core::manifest::Manifest;
+use kargo_maven::cache::LocalCache;
+use kargo_util::errors::KargoError;
+
+use crate::ops_cache::format_size;
+
+/// Options for `kargo bloat`.
+#[derive(Default)]
+pub struct BloatOptions {
+    /// Attribute sizes to the direct dependency that pulls each artifact in.
+    pub tree: bool,
+    /// Only count packages that apply to this target (kebab-case).
+    pub target: Option<String>,
+}
+
+/// Size of one artifact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactSize {
+    pub compressed: u64,
+    pub uncompressed: u64,
+}
+
+impl std::ops::AddAssign for ArtifactSize {
+    fn add_assign(&mut self, other: Self) {
+        self.compressed += other.compressed;
+        self.uncompressed += other.uncompressed;
+    }
+}
+
+/// Measure a JAR by its entries. Files that are not ZIP archives (klibs
+/// unpacked on disk, corrupt downloads) count their length for both sizes.
+pub fn artifact_size(path: &Path) -> ArtifactSize {
+    let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let archive = std::fs::File::open(path)
+        .ok()
+        .and_then(|f| zip::ZipArchive::new(f).ok());
+    let Some(mut archive) = archive else {
+        return ArtifactSize {
+            compressed: len,
+            uncompressed: len,
+        };
+    };
+    let mut size = ArtifactSize::default();
+    for i in 0..archive.len() {
+        if let Ok(entry) = archive.by_index_raw(i) {
+            size.compressed += entry.compressed_size();
+            size.uncompressed += entry.size();
+        }
+    }
+    size
+}
+
+/// Packages on the runtime classpath, optionally restricted to `target`.
+pub fn runtime_packages<'a>(
+    lockfile: &'a Lockfile,
+    target: Option<&str>,
+) -> Vec<&'a LockedPackage> {
+    lockfile
+        .package
+        .iter()
+        .filter(|p| {
+            matches!(
+                p.scope.as_deref().unwrap_or("compile"),
+                "compile" | "runtime"
+            )
+        })
+        .filter(|p| target.map_or(true, |t| p.applies_to(t)))
+        .collect()
+}
+
+fn key(pkg: &LockedPackage) -> String {
+    format!("{}:{}", pkg.group, pkg.name)
+}
+
+/// For each package (`group:artifact`), the direct dependencies that pull it
+/// in. Packages declared in `Kargo.toml` (`direct`) and packages nothing else
+/// depends on are direct and attributed to themselves.
+pub fn attribute(
+    packages: &[&LockedPackage],
+    direct: &HashSet<(String, String)>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let by_key: HashMap<(&str, &str), &LockedPackage> = packages
+        .iter()
+        .map(|p| ((p.group.as_str(), p.name.as_str()), *p))
+        .collect();
+    let referenced: HashSet<(&str, &str)> = packages
+        .iter()
+        .flat_map(|p| &p.dependencies)
+        .map(|d| (d.group.as_str(), d.name.as_str()))
+        .collect();
+
+    let mut roots: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for root in packages.iter().filter(|p| {
+        direct.contains(&(p.group.clone(), p.name.clone()))
+            || !referenced.contains(&(p.group.as_str(), p.name.as_str()))
+    }) {
+        let root_key = key(root);
+        let mut stack = vec![*root];
+        let mut visited = HashSet::new();
+        while let Some(pkg) = stack.pop() {
+            if !visited.insert(key(pkg)) {
+                continue;
+            }
+            roots.entry(key(pkg)).or_default().insert(root_key.clone());
+            for dep in &pkg.dependencies {
+                if let Some(next) = by_key.get(&(dep.group.as_str(), dep.name.as_str())) {
+                    stack.push(next);
+                }
+            }
+        }
+    }
+    roots
+}
+
+/// Print the size each runtime dependency contributes.
+pub fn bloat(project_root: &Path, opts: &BloatOptions) -> miette::Result<()> {
+    let manifest = Manifest::from_path(&project_root.join("Kargo.toml"))?;
+    if !kargo_core::workspace::lockfile_path(project_root).is_file() {
+        return Err(KargoError::Generic {
+            message: "No Kargo.lock found; run `kargo fetch` first".to_string(),
+        }
+        .into());
+    }
+    let lockfile = Lockfile::for_project(project_root)?;
+    let cache = LocalCache::new(project_root);
+
+    let packages = runtime_packages(&lockfile, opts.target.as_deref());
+    let mut sizes: HashMap<String, ArtifactSize> = HashMap::new();
+    let mut missing = Vec::new();
+    for pkg in &packages {
+        match cache.get_library(&pkg.group, &pkg.name, &pkg.version) {
+            Some(path) => {
+                sizes.insert(key(pkg), artifact_size(&path));
+            }
+            None => missing.push(format!("{}:{}", key(pkg), pkg.version)),
+        }
+    }
+    let versions: HashMap<String, &str> = packages
+        .iter()
+        .map(|p| (key(p), p.version.as_str()))
+        .collect();
+
+    if sizes.is_empty() {
+        println!("No runtime dependencies.");
+    } else if opts.tree {
+        let direct: HashSet<(String, String)> = crate::ops_fetch::collect_declared_deps(&manifest)
+            .into_iter()
+            .map(|(group, artifact, _)| (group, artifact))
+            .collect();
+        print_tree(&attribute(&packages, &direct), &sizes, &versions);
+    } else {
+        print_groups(&sizes, &versions);
+    }
+
+    if !missing.is_empty() {
+        kargo_util::progress::status_warn(
+            "Missing",
+            &format!(
+                "{} artifacts are not cached and were not counted (run `kargo fetch`): {}",
+                missing.len(),
+                missing.join(", ")
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn row(label: &str, size: ArtifactSize) {
+    println!(
+        "{label:<56} {:>10} {:>12}",
+        format_size(size.compressed),
+        format_size(size.uncompressed)
+    );
+}
+
+fn header() {
+    println!(
+        "{:<56} {:>10} {:>12}",
+        "Dependency", "Compressed", "Uncompressed"
+    );
+}
+
+fn sorted_by_size<'a>(
+    items: impl IntoIterator<Item = (&'a String, ArtifactSize)>,
+) -> Vec<(&'a String, ArtifactSize)> {
+    let mut items: Vec<_> = items.into_iter().collect();
+    items.sort_by(|a, b| b.1.compressed.cmp(&a.1.compressed).then(a.0.cmp(b.0)));
+    items
+}
+
+fn print_groups(sizes: &HashMap<String, ArtifactSize>, versions: &HashMap<String, &str>) {
+    let mut groups: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+    for k in sizes.keys() {
+        let group = k.split(':').next().unwrap_or(k);
+        groups.entry(group).or_default().push(k);
+    }
+    let totals: Vec<(String, ArtifactSize)> = groups
+        .iter()
+        .map(|(group, keys)| {
+            let mut total = ArtifactSize::default();
+            for k in keys {
+                total += sizes[*k];
+            }
+            (group.to_string(), total)
+        })
+        .collect();
+
+    header();
+    let mut grand = ArtifactSize::default();
+    for (group, total) in sorted_by_size(totals.iter().map(|(g, s)| (g, *s))) {
+        grand += total;
+        row(group, total);
+        let members = groups[group.as_str()].iter().map(|k| (*k, sizes[*k]));
+        for (k, size) in sorted_by_size(members) {
+            let artifact = k.split_once(':').map_or(k.as_str(), |(_, a)| a);
+            row(&format!("  {artifact} {}", versions[k]), size);
+        }
+    }
+    row("Total", grand);
+}
+
+fn print_tree(
+    roots: &BTreeMap<String, BTreeSet<String>>,
+    sizes: &HashMap<String, ArtifactSize>,
+    versions: &HashMap<String, &str>,
+) {
+    // Artifacts reached from a single direct dependency are charged to it;
+    // those several direct dependencies share are listed separately.
+    let mut exclusive: BTreeMap<&String, Vec<&String>> = BTreeMap::new();
+    let mut shared: Vec<(&String, &BTreeSet<String>)> = Vec::new();
+    for (k, owners) in roots {
+        if !sizes.contains_key(k) {
+            continue;
+        }
+        match owners.iter().next() {
+            Some(owner) if owners.len() == 1 => exclusive.entry(owner).or_default().push(k),
+            _ => shared.push((k, owners)),
+        }
+    }
+
+    header();
+    let totals: Vec<(&String, ArtifactSize)> = exclusive
+        .iter()
+        .map(|(owner, keys)| {
+            let mut total = ArtifactSize::default();
+            for k in keys {
+                total += sizes[*k];
+            }
+            (*owner, total)
+        })
+        .collect();
+    let mut grand = ArtifactSize::default();
+    for (owner, total) in sorted_by_size(totals) {
+        grand += total;
+        row(&format!("{owner} {}", versions[owner]), total);
+        let pulled = exclusive[owner]
+            .iter()
+            .filter(|k| **k != owner)
+            .map(|k| (*k, sizes[*k]));
+        for (k, size) in sorted_by_size(pulled) {
+            row(&format!("  {k} {}", versions[k]), size);
+        }
+    }
+    if !shared.is_empty() {
+        let mut total = ArtifactSize::default();
+        for (k, _) in &shared {
+            total += sizes[*k];
+        }
+        grand += total;
+        row("(shared by several direct dependencies)", total);
+        let owners: HashMap<&String, &BTreeSet<String>> = shared.iter().copied().collect();
+        for (k, size) in sorted_by_size(shared.iter().map(|(k, _)| (*k, sizes[*k]))) {
+            row(&format!("  {k} {}", versions[k]), size);
+            let via: Vec<&str> = owners[k].iter().map(String::as_str).collect();
+            println!("      via {}", via.join(", "));
+        }
+    }
+    row("Total", grand);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_core::lockfile::LockedDependencyRef;
+    use std::io::Write;
+
+    fn package(group: &str, name: &str, scope: &str, deps: &[(&str, &str)]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            group: group.to_string(),
+            version: "1.0".to_string(),
+            checksum: None,
+            source: None,
+            scope: Some(scope.to_string()),
+            targets: vec![],
+            dependencies: deps
+                .iter()
+                .map(|(g, n)| LockedDependencyRef {
+                    name: n.to_string(),
+                    group: g.to_string(),
+                    version: "1.0".to_string(),
+                })
+                .collect(),
+            relocated_from: None,
+            members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn sizes_are_attributed_to_the_direct_dependencies_pulling_them_in() {
+        let lockfile = Lockfile {
+            package: vec![
+                package(
+                    "com.a",
+                    "http",
+                    "compile",
+                    &[("com.c", "okio"), ("com.d", "json")],
+                ),
+                package("com.b", "db", "runtime", &[("com.d", "json")]),
+                package("com.c", "okio", "compile", &[]),
+                package("com.d", "json", "compile", &[]),
+                package("org.junit", "junit", "test", &[]),
+            ],
+            ..Default::default()
+        };
+        let packages = runtime_packages(&lockfile, None);
+        assert_eq!(packages.len(), 4);
+
+        let roots = attribute(&packages, &HashSet::new());
+        let owners = |k: &str| roots[k].iter().cloned().collect::<Vec<_>>();
+        assert_eq!(owners("com.c:okio"), ["com.a:http"]);
+        assert_eq!(owners("com.d:json"), ["com.a:http", "com.b:db"]);
+        assert_eq!(owners("com.b:db"), ["com.b:db"]);
+
+        // Declaring okio directly makes it its own root as well.
+        let direct = HashSet::from([("com.c".to_string(), "okio".to_string())]);
+        assert_eq!(
+            attribute(&packages, &direct)["com.c:okio"].len(),
+            2,
+            "okio is reached from http and from itself"
+        );
+    }
+
+    #[test]
+    fn jar_size_sums_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let jar = dir.path().join("lib.jar");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("a.txt", options).unwrap();
+        zip.write_all(&[b'a'; 4096]).unwrap();
+        zip.finish().unwrap();
+
+        let size = artifact_size(&jar);
+        assert_eq!(size.uncompressed, 4096);
+        assert!(size.compressed > 0 && size.compressed < 4096);
+
+        let klib = dir.path().join("lib.klib");
+        std::fs::write(&klib, b"not a zip").unwrap();
+        assert_eq!(
+            artifact_size(&klib),
+            ArtifactSize {
+                compressed: 9,
+                uncompressed: 9
+            }
+        );
+    }
+}
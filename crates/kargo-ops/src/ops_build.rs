@@ -5,12 +5,13 @@
 //!
 //! The pipeline is split into three phases:
 //! - [`run_annotation_processing`] — KSP/KAPT pre-build
-//! - [`run_main_compilation`] — fingerprinting, incremental check, kotlinc + javac
+//! - [`run_main_compilation`] — fingerprinting, incremental check, kotlinc + javac,
+//!   then the test fixtures via [`crate::ops_fixtures`]
 //! - [`package_output`] — resource copy, JAR packaging
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use kargo_compiler::build_cache::BuildCache;
 use kargo_compiler::dispatch::CompilerDispatch;
@@ -23,7 +24,8 @@ use kargo_compiler::unit_graph::UnitGraph;
 use kargo_core::lockfile::Lockfile;
 use kargo_core::manifest::Manifest;
 use kargo_core::target::KotlinTarget;
-use kargo_util::errors::KargoError;
+use kargo_util::errors::{ErrorFormat, KargoError};
+use serde::Serialize;
 
 use crate::ops_setup;
 
@@ -36,8 +38,63 @@ pub struct BuildOptions {
     pub verbose: bool,
     pub timings: bool,
     pub offline: bool,
+    /// Fail instead of warning if the toolchain or JDK differs from the one
+    /// recorded in `Kargo.lock`.
+    pub locked: bool,
     /// Suppress non-error output (used by `kargo run` / `kargo test`).
     pub quiet: bool,
+    /// Fail the build instead of warning when a `[budgets]` limit is exceeded.
+    pub enforce_budgets: bool,
+    /// Split the main sources into per-package units (even without
+    /// `split-units` in the profile) and check the result against a
+    /// monolithic build.
+    pub verify_split: bool,
+    /// How progress and compiler diagnostics are reported.
+    pub message_format: MessageFormat,
+}
+
+impl BuildOptions {
+    /// Whether human status lines should be printed. JSON consumers own
+    /// stdout, so human output is suppressed entirely in that mode.
+    pub(crate) fn status_output(&self) -> bool {
+        !self.quiet && !self.message_format.is_json()
+    }
+
+    /// Whether verbose human diagnostics should be printed.
+    pub(crate) fn verbose_output(&self) -> bool {
+        self.verbose && !self.message_format.is_json()
+    }
+}
+
+/// Output format for build progress and compiler diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Status lines and rendered diagnostics on stderr.
+    #[default]
+    Human,
+    /// Newline-delimited JSON events on stdout (`start`, `unit`,
+    /// `diagnostic`, `finish`), emitted while the build runs.
+    JsonDiagnostics,
+}
+
+impl MessageFormat {
+    /// Parse a `--message-format` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json-diagnostics" => Ok(Self::JsonDiagnostics),
+            other => Err(KargoError::Generic {
+                message: format!(
+                    "Unknown message format '{other}'. Available: human, json-diagnostics"
+                ),
+            }
+            .into()),
+        }
+    }
+
+    pub fn is_json(self) -> bool {
+        self == Self::JsonDiagnostics
+    }
 }
 
 /// Result of a build operation, carrying enough context for downstream ops.
@@ -46,7 +103,10 @@ pub struct BuildResult {
     pub profile_name: String,
     pub build_dir: PathBuf,
     pub classes_dir: PathBuf,
-    /// Path to the packaged output JAR, if produced.
+    pub resources_dir: PathBuf,
+    pub generated_dir: PathBuf,
+    /// Path to the packaged output, if produced: a JAR, or the bundle
+    /// directory for `wasm-js`.
     pub output_jar: Option<PathBuf>,
     pub success: bool,
     /// Manifest loaded during build (avoids re-parsing in test/run).
@@ -59,6 +119,72 @@ pub struct BuildResult {
     pub classpath: kargo_compiler::classpath::Classpath,
     /// Discovered source sets for reuse by test.
     pub discovered: kargo_compiler::source_set_discovery::DiscoveredSources,
+    /// Compiled test fixtures (`src/fixtures`), if the project has any.
+    pub fixtures_dir: Option<PathBuf>,
+}
+
+/// Machine-readable description of what a build produced and where, written
+/// by `kargo build --out-json` so scripts need not hardcode the layout of
+/// `build/<target>/<profile>/`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildOutputs {
+    pub package: String,
+    pub version: String,
+    pub target: String,
+    pub profile: String,
+    pub build_dir: PathBuf,
+    pub classes_dir: PathBuf,
+    pub resources_dir: PathBuf,
+    pub generated_dir: PathBuf,
+    /// Where `kargo test` compiles unit and integration tests.
+    pub test_classes_dir: PathBuf,
+    pub integration_test_classes_dir: PathBuf,
+    pub fixtures_dir: Option<PathBuf>,
+    /// The packaged JAR (or `wasm-js` bundle directory), if one was produced.
+    pub artifact: Option<PathBuf>,
+    /// R8's mapping file, if the JAR was shrunk.
+    pub shrink_mapping: Option<PathBuf>,
+    /// Dependency JARs needed to run the artifact.
+    pub runtime_classpath: Vec<PathBuf>,
+}
+
+impl BuildOutputs {
+    pub fn from_result(result: &BuildResult) -> Self {
+        let mapping = result.build_dir.join("shrink").join("mapping.txt");
+        Self {
+            package: result.manifest.package.name.clone(),
+            version: result.manifest.package.version.clone(),
+            target: result.target.kebab_name().to_string(),
+            profile: result.profile_name.clone(),
+            build_dir: result.build_dir.clone(),
+            classes_dir: result.classes_dir.clone(),
+            resources_dir: result.resources_dir.clone(),
+            generated_dir: result.generated_dir.clone(),
+            test_classes_dir: result.build_dir.join("test-classes"),
+            integration_test_classes_dir: result.build_dir.join("integration-test-classes"),
+            fixtures_dir: result.fixtures_dir.clone(),
+            artifact: result.output_jar.clone(),
+            shrink_mapping: mapping.is_file().then_some(mapping),
+            runtime_classpath: result.classpath.runtime_jars.clone(),
+        }
+    }
+
+    /// Write the description as pretty JSON to `path`, or to stdout for `-`.
+    pub fn write(&self, path: &Path) -> miette::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| KargoError::Generic {
+            message: format!("Failed to serialize build outputs: {e}"),
+        })?;
+        if path == Path::new("-") {
+            println!("{json}");
+            return Ok(());
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+        }
+        std::fs::write(path, format!("{json}\n")).map_err(KargoError::Io)?;
+        Ok(())
+    }
 }
 
 /// Output from the compilation phase.
@@ -70,17 +196,48 @@ struct CompilationOutput {
 /// Run the full build pipeline.
 pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<BuildResult> {
     let start = Instant::now();
-    use kargo_util::progress::status;
-
-    let ctx = crate::BuildContext::load(
+    kargo_util::events::set_stdout(opts.message_format.is_json());
+    let result = match crate::BuildContext::load(
         project_dir,
         opts.target.as_deref(),
         opts.profile.as_deref(),
         opts.release,
     )
-    .await?;
+    .await
+    {
+        Ok(ctx) => build_pipeline(ctx, opts, start).await,
+        Err(e) => Err(e),
+    };
+    emit_finish(result.is_ok(), start);
+    result
+}
 
-    if opts.verbose {
+/// Run the build pipeline with an already loaded context, e.g. one kept warm
+/// by `kargo daemon`. The target and profile are the context's; those in
+/// `opts` are ignored.
+pub async fn build_with_context(
+    ctx: crate::BuildContext,
+    opts: &BuildOptions,
+) -> miette::Result<BuildResult> {
+    let start = Instant::now();
+    kargo_util::events::set_stdout(opts.message_format.is_json());
+    let result = build_pipeline(ctx, opts, start).await;
+    emit_finish(result.is_ok(), start);
+    result
+}
+
+async fn build_pipeline(
+    mut ctx: crate::BuildContext,
+    opts: &BuildOptions,
+    start: Instant,
+) -> miette::Result<BuildResult> {
+    use kargo_util::progress::status;
+
+    let project_dir = &ctx.project_dir.clone();
+    crate::ops_lock::check_environment(&ctx, opts.locked)?;
+    crate::ops_workspace::add_module_dependencies(&mut ctx, opts.verbose_output()).await?;
+
+    if opts.verbose_output() {
         ops_setup::print_preflight_summary(&ctx.preflight);
         println!();
     }
@@ -88,7 +245,9 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
     let target = ctx.target;
     let profile_name = ctx.profile_name.clone();
 
-    if !opts.quiet {
+    emit_start(&ctx);
+
+    if opts.status_output() {
         status(
             "Compiling",
             &format!(
@@ -98,6 +257,24 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
         );
     }
 
+    let migration_notes = crate::ops_kotlin_migration::check(
+        project_dir,
+        &ctx.manifest,
+        &ctx.preflight.toolchain.version,
+    );
+    if opts.status_output() && !migration_notes.is_empty() {
+        kargo_util::progress::status_warn(
+            "Migrate",
+            &format!(
+                "Kotlin changed to {}; update Kargo.toml:",
+                ctx.preflight.toolchain.version
+            ),
+        );
+        for note in &migration_notes {
+            eprintln!("    {}: {}", note.location, note.message);
+        }
+    }
+
     // Collect main source files
     let mut all_kotlin_dirs: Vec<PathBuf> = Vec::new();
     for ss in &ctx.discovered.main_sources {
@@ -106,12 +283,16 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
     let main_sources = collect_kotlin_files(&all_kotlin_dirs);
 
     if main_sources.is_empty() {
-        println!("No Kotlin source files found to compile.");
+        if opts.status_output() {
+            println!("No Kotlin source files found to compile.");
+        }
         return Ok(BuildResult {
             target,
             profile_name,
             build_dir: ctx.build_dir.clone(),
             classes_dir: ctx.classes_dir.clone(),
+            resources_dir: ctx.resources_dir.clone(),
+            generated_dir: ctx.generated_dir.clone(),
             output_jar: None,
             success: true,
             manifest: ctx.manifest,
@@ -119,16 +300,39 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
             preflight: ctx.preflight,
             classpath: ctx.classpath,
             discovered: ctx.discovered,
+            fixtures_dir: None,
         });
     }
 
+    let mut phases = PhaseTimings::default();
+
     // Generate BuildConfig.kt
+    let phase_start = Instant::now();
     generate_build_config(&ctx, &profile_name)?;
 
+    // Run [generate] code generators whose inputs changed
+    crate::ops_generate::run(
+        &ctx,
+        &crate::ops_generate::GenerateOptions {
+            verbose: opts.verbose_output(),
+            quiet: !opts.status_output(),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // Compile .proto files into generated/proto
+    crate::ops_protobuf::generate(&ctx, opts.verbose_output(), !opts.status_output()).await?;
+
+    // Generate SQLDelight database interfaces from .sq files
+    crate::ops_sqldelight::generate(&ctx, opts.verbose_output(), !opts.status_output()).await?;
+    phases.record("generate", phase_start);
+
     // Phase 1: Annotation processing
     let cache = kargo_maven::cache::LocalCache::new(project_dir);
     let processors = plugins::detect_processors(&ctx.manifest, &cache);
 
+    let phase_start = Instant::now();
     run_annotation_processing(
         &ctx,
         &processors,
@@ -138,20 +342,72 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
         opts,
     )
     .await?;
+    phases.record("annotation-processing", phase_start);
 
     // Phase 2: Main compilation
+    let phase_start = Instant::now();
     let comp_output = run_main_compilation(&ctx, &processors, &main_sources, &cache, opts)?;
 
     if !comp_output.compiled && !comp_output.main_unit.sources.is_empty() {
         // Check for failed build
     }
+    let fixtures_dir = crate::ops_fixtures::compile(&ctx, &comp_output.main_unit, opts)?;
+    phases.record("compile", phase_start);
 
     // Phase 3: Package output
+    let phase_start = Instant::now();
     let output_jar = package_output(&ctx, comp_output.compiled)?;
+    phases.record("package", phase_start);
+
+    // Merge Android manifests and resources (also yields R8 keep rules)
+    if matches!(target, KotlinTarget::Android)
+        && crate::ops_android_merge::project_manifest(&ctx.project_dir).is_some()
+    {
+        let phase_start = Instant::now();
+        let inputs = crate::ops_android_merge::inputs(
+            &ctx.project_dir,
+            &ctx.manifest,
+            &ctx.lockfile,
+            crate::ops_android_merge::APP_SCOPES,
+        )?;
+        let merged = crate::ops_android_merge::merge(
+            &inputs,
+            &ctx.build_dir.join(crate::ops_android_merge::MERGED_DIR),
+        )?;
+        if opts.status_output() {
+            for warning in &merged.warnings {
+                kargo_util::progress::status_warn("Warning", warning);
+            }
+        }
+        phases.record("merge", phase_start);
+    }
+
+    // Phase 4: Shrink the JAR with R8 (only when it was just repackaged)
+    let shrink = ctx.profile.shrink().filter(|_| {
+        comp_output.compiled && matches!(target, KotlinTarget::Jvm | KotlinTarget::Android)
+    });
+    if let (Some(jar), Some(shrink)) = (&output_jar, shrink) {
+        let phase_start = Instant::now();
+        let report = crate::ops_shrink::shrink(&ctx, jar, shrink, opts.verbose_output()).await?;
+        phases.record("shrink", phase_start);
+        if opts.status_output() {
+            status(
+                "Shrunk",
+                &format!(
+                    "{} {report}",
+                    jar.file_name().unwrap_or_default().to_string_lossy()
+                ),
+            );
+        }
+    }
+
+    if let Some(ref output) = output_jar {
+        emit_artifact(target, output, !comp_output.compiled);
+    }
 
     // Print summary
-    if !opts.quiet {
-        let elapsed = start.elapsed();
+    let elapsed = start.elapsed();
+    if opts.status_output() {
         let file_count = comp_output.main_unit.sources.len();
         if comp_output.compiled {
             status(
@@ -181,15 +437,48 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
 
         if opts.timings {
             eprintln!("  Timing breakdown:");
+            let reports = kargo_compiler::build_report::read_dir(&kotlinc_reports_dir(&ctx));
+            for (phase, took) in &phases.0 {
+                eprintln!("    {phase}: {:.2}s", took.as_secs_f64());
+                if *phase == "compile" {
+                    print_kotlinc_phases(&reports);
+                }
+            }
             eprintln!("    total: {:.2}s", elapsed.as_secs_f64());
         }
     }
 
+    if let Some(budgets) = &ctx.manifest.budgets {
+        let jar_size = output_jar
+            .as_deref()
+            .and_then(|jar| std::fs::metadata(jar).ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len());
+        let exceeded = budget_violations(budgets, &phases, elapsed, jar_size);
+        if opts.status_output() {
+            for message in &exceeded {
+                kargo_util::progress::status_warn("Budget", message);
+            }
+        }
+        if opts.enforce_budgets && !exceeded.is_empty() {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "Build exceeded {} budget(s) from [budgets]: {}",
+                    exceeded.len(),
+                    exceeded.join("; ")
+                ),
+            }
+            .into());
+        }
+    }
+
     Ok(BuildResult {
         target,
         profile_name,
         build_dir: ctx.build_dir.clone(),
         classes_dir: ctx.classes_dir.clone(),
+        resources_dir: ctx.resources_dir.clone(),
+        generated_dir: ctx.generated_dir.clone(),
         output_jar,
         success: true,
         manifest: ctx.manifest,
@@ -197,9 +486,80 @@ pub async fn build(project_dir: &Path, opts: &BuildOptions) -> miette::Result<Bu
         preflight: ctx.preflight,
         classpath: ctx.classpath,
         discovered: ctx.discovered,
+        fixtures_dir,
     })
 }
 
+/// Wall-clock time of each build phase, in the order the phases ran.
+#[derive(Debug, Default)]
+struct PhaseTimings(Vec<(&'static str, Duration)>);
+
+impl PhaseTimings {
+    fn record(&mut self, phase: &'static str, start: Instant) {
+        self.0.push((phase, start.elapsed()));
+    }
+}
+
+/// Where kotlinc writes its `-Xdump-perf` reports during `--timings` builds.
+fn kotlinc_reports_dir(ctx: &crate::BuildContext) -> PathBuf {
+    ctx.build_dir.join("reports").join("kotlinc")
+}
+
+/// Print kotlinc's internal phases for each compiled unit, indented under
+/// the `compile` phase of the timing breakdown.
+fn print_kotlinc_phases(reports: &[kargo_compiler::build_report::PerfReport]) {
+    for report in reports {
+        eprintln!("      {}:", report.unit);
+        for phase in &report.phases {
+            let counts = match (phase.files, phase.lines) {
+                (Some(files), Some(lines)) => format!(" ({files} files, {lines} lines)"),
+                _ => String::new(),
+            };
+            eprintln!(
+                "        {}: {:.2}s{counts}",
+                phase.name,
+                phase.millis as f64 / 1000.0
+            );
+        }
+    }
+}
+
+/// Messages for every `[budgets]` limit the build went over.
+fn budget_violations(
+    budgets: &kargo_core::manifest::BudgetConfig,
+    phases: &PhaseTimings,
+    total: Duration,
+    jar_size: Option<u64>,
+) -> Vec<String> {
+    let mut exceeded = Vec::new();
+    if let Some(limit) = budgets.build_time().filter(|limit| total > *limit) {
+        exceeded.push(format!(
+            "build took {:.2}s, over the {:.2}s build-time budget",
+            total.as_secs_f64(),
+            limit.as_secs_f64()
+        ));
+    }
+    for (phase, took) in &phases.0 {
+        if let Some(limit) = budgets.phase_time(phase).filter(|limit| took > limit) {
+            exceeded.push(format!(
+                "{phase} took {:.2}s, over its {:.2}s budget",
+                took.as_secs_f64(),
+                limit.as_secs_f64()
+            ));
+        }
+    }
+    if let (Some(size), Some(limit)) = (jar_size, budgets.jar_size()) {
+        if size > limit {
+            exceeded.push(format!(
+                "JAR is {}, over the {} jar-size budget",
+                crate::ops_cache::format_size(size),
+                crate::ops_cache::format_size(limit)
+            ));
+        }
+    }
+    exceeded
+}
+
 // ---------------------------------------------------------------------------
 // Phase 1: Annotation processing (KSP/KAPT)
 // ---------------------------------------------------------------------------
@@ -231,7 +591,7 @@ async fn run_annotation_processing(
 
     let changed_files = match decision {
         ApDecision::UpToDate => {
-            if opts.verbose {
+            if opts.verbose_output() {
                 println!("  annotation processing: up-to-date (skipped)");
             }
             return Ok(());
@@ -262,6 +622,7 @@ async fn run_annotation_processing(
                 jdk_home: &ctx.preflight.jdk.home,
                 project_dir: &ctx.project_dir,
                 generated_dir: &ctx.generated_dir,
+                sandbox: ctx.manifest.sandbox.as_ref(),
             };
 
             match ksp {
@@ -279,7 +640,7 @@ async fn run_annotation_processing(
                     } else {
                         "KSP2 annotation processing"
                     };
-                    if ran && !opts.quiet {
+                    if ran && opts.status_output() {
                         status("Running", mode);
                     }
                 }
@@ -289,7 +650,7 @@ async fn run_annotation_processing(
                         ..ksp_ap
                     };
                     run_ksp1_pass(ksp, &ksp1_ap, &ctx.profile, &ctx.manifest.ksp_options)?;
-                    if !opts.quiet {
+                    if opts.status_output() {
                         status("Running", "KSP1 annotation processing");
                     }
                 }
@@ -313,9 +674,10 @@ async fn run_annotation_processing(
             jdk_home: &ctx.preflight.jdk.home,
             project_dir: &ctx.project_dir,
             generated_dir: &ctx.generated_dir,
+            sandbox: ctx.manifest.sandbox.as_ref(),
         };
         let generated = plugins::run_kapt_pass(&kapt_ap, &ctx.profile)?;
-        if generated && !opts.quiet {
+        if generated && opts.status_output() {
             status("Running", "KAPT annotation processing");
         }
     }
@@ -344,7 +706,11 @@ fn run_main_compilation(
     let mut compile_classpath = ctx.classpath.compile_jars.clone();
 
     let kotlin_lib = ctx.preflight.toolchain.home.join("lib");
-    for jar_name in kargo_compiler::classpath::STDLIB_RUNTIME_JARS {
+    let jvm_stdlib: &[&str] = match ctx.target {
+        KotlinTarget::Jvm | KotlinTarget::Android => kargo_compiler::classpath::STDLIB_RUNTIME_JARS,
+        _ => &[],
+    };
+    for jar_name in jvm_stdlib {
         let jar = kotlin_lib.join(jar_name);
         if jar.is_file()
             && !compile_classpath
@@ -370,7 +736,7 @@ fn run_main_compilation(
         .filter_map(|p| cache.get_jar(&p.group, &p.artifact, &p.version, None))
         .collect();
 
-    let (gen_dirs, gen_files) = collect_generated_sources(&ctx.generated_dir);
+    let (gen_dirs, gen_files) = collect_generated_sources(&ctx.generated_dir, &ctx.manifest);
     let mut all_main_sources = main_sources.to_vec();
     all_main_sources.extend(gen_files);
 
@@ -390,6 +756,7 @@ fn run_main_compilation(
         is_test: false,
         generated_sources: gen_dirs,
         processor_jars: processor_jar_paths,
+        local_jars: ctx.classpath.local_jars.clone(),
     };
 
     let mut graph = UnitGraph::new();
@@ -400,17 +767,34 @@ fn run_main_compilation(
         fingerprint::storage_dir(&ctx.project_dir, ctx.target.kebab_name(), &ctx.profile_name);
     let decision = incremental::check(&main_unit, &fp_dir, &kotlin_ver);
     let mut compiled = false;
+    let json = opts.message_format.is_json();
+
+    // kotlinc phase timings for `--timings`; the flag stays off `main_unit`
+    // so it does not affect the fingerprint.
+    let reports_dir = kotlinc_reports_dir(ctx);
+    if reports_dir.exists() {
+        std::fs::remove_dir_all(&reports_dir).map_err(KargoError::Io)?;
+    }
+    let compile_unit =
+        if opts.timings && matches!(ctx.target, KotlinTarget::Jvm | KotlinTarget::Android) {
+            std::fs::create_dir_all(&reports_dir).map_err(KargoError::Io)?;
+            kargo_compiler::build_report::with_report(&main_unit, &reports_dir)
+        } else {
+            main_unit.clone()
+        };
 
     match decision {
         IncrementalDecision::UpToDate => {
-            if opts.verbose {
+            emit_unit(&main_unit, true);
+            if opts.verbose_output() {
                 println!("  main: up-to-date (skipped)");
             }
         }
         IncrementalDecision::NeedsRebuild(fp) => {
             let build_cache = BuildCache::new(BuildCache::default_path(), None);
             if build_cache.restore(&fp, &ctx.classes_dir)? {
-                if opts.verbose {
+                emit_unit(&main_unit, true);
+                if opts.verbose_output() {
                     println!("  main: restored from cache");
                 }
                 incremental::mark_complete(&fp_dir, "main", &fp, &main_unit)?;
@@ -423,27 +807,50 @@ fn run_main_compilation(
                     ctx.preflight.java_target.clone(),
                 );
 
-                let output = compiler.compile(&main_unit, &ctx.env)?;
+                let output = if let Some(output) = compile_split(ctx, &compile_unit, opts)? {
+                    output
+                } else if kargo_util::events::enabled() {
+                    emit_unit(&main_unit, false);
+                    compiler.compile_streaming(&compile_unit, &ctx.env, &mut |d| {
+                        emit_diagnostic(&main_unit.name, d)
+                    })?
+                } else {
+                    compiler.compile(&compile_unit, &ctx.env)?
+                };
 
                 if !output.success {
-                    print_diagnostics(&output.diagnostics);
+                    if !json {
+                        print_diagnostics(&output.diagnostics);
+                    }
                     return Err(KargoError::Generic {
                         message: "Compilation failed.".into(),
                     }
                     .into());
                 }
 
-                if !output.diagnostics.is_empty() && opts.verbose {
+                if !output.diagnostics.is_empty() && opts.verbose_output() {
                     print_diagnostics(&output.diagnostics);
                 }
 
                 if has_kapt_java {
-                    compile_kapt_java(
+                    compile_generated_java(
                         &ctx.preflight.jdk.home,
                         &kapt_sources_dir,
                         &ctx.classes_dir,
                         &main_unit.classpath,
                         &ctx.preflight.java_target,
+                        "KAPT",
+                    )?;
+                }
+                let proto_java = crate::ops_protobuf::output_dir(&ctx.generated_dir).join("java");
+                if proto_java.is_dir() {
+                    compile_generated_java(
+                        &ctx.preflight.jdk.home,
+                        &proto_java,
+                        &ctx.classes_dir,
+                        &main_unit.classpath,
+                        &ctx.preflight.java_target,
+                        "protobuf",
                     )?;
                 }
 
@@ -460,6 +867,103 @@ fn run_main_compilation(
     })
 }
 
+/// Compile `unit` as parallel per-package units when the profile sets
+/// `split-units` (or `--verify-split` is given) and its packages allow it.
+/// `None` means the unit should be compiled whole.
+fn compile_split(
+    ctx: &crate::BuildContext,
+    unit: &CompilationUnit,
+    opts: &BuildOptions,
+) -> miette::Result<Option<kargo_compiler::unit::CompilationOutput>> {
+    use kargo_compiler::unit_split::{self, SplitPlan};
+
+    if !(ctx.profile.split_units || opts.verify_split)
+        || !matches!(ctx.target, KotlinTarget::Jvm | KotlinTarget::Android)
+    {
+        return Ok(None);
+    }
+
+    let mut sources = unit.all_sources();
+    sources.sort();
+    sources.dedup();
+    let jobs = ctx.config.build.jobs.max(1) as usize;
+    let waves = match unit_split::plan(&sources, jobs)? {
+        SplitPlan::Split(waves) => waves,
+        SplitPlan::Monolithic(reason) => {
+            if opts.status_output() && (opts.verify_split || opts.verbose) {
+                kargo_util::progress::status_info("Unsplit", &format!("{}: {reason}", unit.name));
+            }
+            return Ok(None);
+        }
+    };
+    if opts.status_output() {
+        let units: usize = waves.iter().map(Vec::len).sum();
+        kargo_util::progress::status(
+            "Splitting",
+            &format!(
+                "{} into {units} units in {} wave(s)",
+                unit.name,
+                waves.len()
+            ),
+        );
+    }
+
+    let new_compiler = || {
+        CompilerDispatch::resolve(
+            ctx.target,
+            ctx.preflight.toolchain.clone(),
+            ctx.preflight.jdk.home.clone(),
+            ctx.preflight.java_target.clone(),
+        )
+    };
+    let base = CompilationUnit {
+        sources,
+        generated_sources: Vec::new(),
+        ..unit.clone()
+    };
+    let split_dir = ctx.build_dir.join("split");
+    let json = opts.message_format.is_json();
+    emit_unit(unit, false);
+    let output = unit_split::compile(&waves, &base, &split_dir, &ctx.env, jobs, &new_compiler)?;
+    for d in &output.diagnostics {
+        emit_diagnostic(&unit.name, d);
+    }
+
+    if output.success && opts.verify_split {
+        let monolithic_dir = split_dir.join("monolithic");
+        if monolithic_dir.exists() {
+            std::fs::remove_dir_all(&monolithic_dir).map_err(KargoError::Io)?;
+        }
+        let mut monolithic = CompilationUnit {
+            output_dir: monolithic_dir.clone(),
+            ..base
+        };
+        monolithic
+            .compiler_args
+            .retain(|a| !a.starts_with(kargo_compiler::build_report::DUMP_PERF_FLAG));
+        let reference = new_compiler().compile(&monolithic, &ctx.env)?;
+        if !reference.success {
+            if !json {
+                print_diagnostics(&reference.diagnostics);
+            }
+            return Err(KargoError::Compilation {
+                message: "the monolithic build for --verify-split has errors, \
+                          although the split build succeeded"
+                    .into(),
+            }
+            .into());
+        }
+        unit_split::verify(&split_dir, &monolithic_dir)?;
+        if opts.status_output() {
+            kargo_util::progress::status(
+                "Verified",
+                "split compilation matches a monolithic build",
+            );
+        }
+    }
+    Ok(Some(output))
+}
+
 // ---------------------------------------------------------------------------
 // Phase 3: Package output
 // ---------------------------------------------------------------------------
@@ -474,6 +978,28 @@ fn package_output(ctx: &crate::BuildContext, compiled: bool) -> miette::Result<O
         .collect();
     copy_resources(&resource_dirs, &ctx.resources_dir);
 
+    if ctx.target == KotlinTarget::WasmJs {
+        let bundle_dir = ctx.build_dir.join("output").join(format!(
+            "{}-{}",
+            ctx.manifest.package.name, ctx.manifest.package.version
+        ));
+        if !compiled {
+            return Ok(bundle_dir.is_dir().then_some(bundle_dir));
+        }
+        let node_modules = crate::ops_npm::node_modules_dir(&ctx.project_dir);
+        return crate::ops_wasm::write_bundle(
+            &ctx.classes_dir,
+            &ctx.resources_dir,
+            &bundle_dir,
+            &ctx.manifest.package.name,
+            &ctx.manifest.package.version,
+            &crate::ops_wasm::NpmBundle {
+                packages: &ctx.lockfile.npm,
+                node_modules: &node_modules,
+            },
+        );
+    }
+
     if compiled {
         let output_dir = ctx.build_dir.join("output");
         std::fs::create_dir_all(&output_dir).map_err(KargoError::Io)?;
@@ -483,7 +1009,7 @@ fn package_output(ctx: &crate::BuildContext, compiled: bool) -> miette::Result<O
         );
         let jar_path = output_dir.join(&jar_name);
         package_jar(
-            &ctx.preflight.jdk.home,
+            &ctx.manifest.package,
             &ctx.classes_dir,
             &ctx.resources_dir,
             &jar_path,
@@ -518,7 +1044,16 @@ fn generate_build_config(ctx: &crate::BuildContext, profile_name: &str) -> miett
             .and_then(kargo_compiler::buildconfig::package_from_main_class)
     });
 
+    use kargo_core::build_config;
+
+    // Base table, then matching `[target.<t>.build-config]` in key order,
+    // then the selected flavors: later layers win.
     let mut build_config_fields = ctx.manifest.build_config.clone();
+    for (key, target_deps) in &ctx.manifest.target {
+        if kargo_core::target::TargetSelector::parse(key).is_some_and(|s| s.matches(ctx.target)) {
+            build_config::merge(&mut build_config_fields, &target_deps.build_config);
+        }
+    }
     if let Some(ref flavors) = ctx.manifest.flavors {
         let selected: std::collections::BTreeMap<String, String> =
             flavors.default.clone().unwrap_or_default();
@@ -526,17 +1061,34 @@ fn generate_build_config(ctx: &crate::BuildContext, profile_name: &str) -> miett
         for (dimension, flavor_name) in &selected {
             if let Some(dim_map) = flavors.dimension_flavors.get(dimension) {
                 if let Some(def) = dim_map.get(flavor_name) {
-                    for (k, v) in &def.build_config {
-                        build_config_fields.insert(k.clone(), v.clone());
-                    }
+                    build_config::merge(&mut build_config_fields, &def.build_config);
                 }
             }
         }
     }
 
+    let timestamp = if build_config::uses_timestamp(&build_config_fields) {
+        build_timestamp(ctx.profile.reproducible)
+    } else {
+        0
+    };
+    let build_config_fields = build_config::resolve_all(&build_config_fields, timestamp, None)
+        .map_err(|message| KargoError::Manifest { message })?;
+
+    for key in build_config::likely_secrets(&build_config_fields) {
+        kargo_util::progress::status_warn(
+            "Warning",
+            &format!(
+                "build-config {key} looks like a secret and will be compiled into the \
+                 artifact; use {{ from-env = true }} to read it at run time instead"
+            ),
+        );
+    }
+
     kargo_compiler::buildconfig::generate(
         &ctx.generated_dir,
         kotlin_package.as_deref(),
+        ctx.target,
         &ctx.manifest.package.name,
         &ctx.manifest.package.version,
         profile_name,
@@ -545,6 +1097,24 @@ fn generate_build_config(ctx: &crate::BuildContext, profile_name: &str) -> miett
     )
 }
 
+/// Epoch milliseconds for `${build:timestamp}`: `SOURCE_DATE_EPOCH` when
+/// set, 0 for reproducible profiles, otherwise the current time.
+fn build_timestamp(reproducible: bool) -> i64 {
+    if let Some(secs) = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+    {
+        return secs.saturating_mul(1000);
+    }
+    if reproducible {
+        return 0;
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
 // ---------------------------------------------------------------------------
 // Helper functions
 // ---------------------------------------------------------------------------
@@ -558,7 +1128,7 @@ fn copy_resources(resource_dirs: &[PathBuf], target: &Path) {
     }
 }
 
-fn copy_dir_contents(src: &Path, dst: &Path) {
+pub(crate) fn copy_dir_contents(src: &Path, dst: &Path) {
     let Ok(entries) = std::fs::read_dir(src) else {
         return;
     };
@@ -580,19 +1150,17 @@ fn copy_dir_contents(src: &Path, dst: &Path) {
     }
 }
 
-/// Package compiled classes and resources into a JAR using `jar` from the JDK.
-fn package_jar(
-    jdk_home: &Path,
+/// Package compiled classes and resources into a JAR.
+///
+/// The manifest carries `main_class`, the `Implementation-*` attributes
+/// derived from `[package]`, and any `[package.jar]` settings.
+pub(crate) fn package_jar(
+    package: &kargo_core::manifest::PackageMetadata,
     classes_dir: &Path,
     resources_dir: &Path,
     jar_path: &Path,
     main_class: Option<&str>,
 ) -> miette::Result<Option<PathBuf>> {
-    let jar_bin = jdk_home.join("bin").join("jar");
-    if !jar_bin.is_file() {
-        return Ok(None);
-    }
-
     let has_classes = classes_dir.is_dir()
         && std::fs::read_dir(classes_dir)
             .map(|rd| rd.flatten().next().is_some())
@@ -601,42 +1169,17 @@ fn package_jar(
         return Ok(None);
     }
 
-    let mut args = vec!["cf".to_string(), jar_path.to_string_lossy().to_string()];
-
-    if let Some(mc) = main_class {
-        args[0] = "cfe".to_string();
-        args.insert(2, mc.to_string());
-    }
-
-    args.push("-C".into());
-    args.push(classes_dir.to_string_lossy().to_string());
-    args.push(".".into());
-
-    if resources_dir.is_dir()
-        && std::fs::read_dir(resources_dir)
-            .map(|rd| rd.flatten().next().is_some())
-            .unwrap_or(false)
-    {
-        args.push("-C".into());
-        args.push(resources_dir.to_string_lossy().to_string());
-        args.push(".".into());
-    }
-
-    let cmd = kargo_util::process::CommandBuilder::new(jar_bin.to_string_lossy().to_string())
-        .args(args)
-        .env("JAVA_HOME", jdk_home.to_string_lossy().to_string());
-
-    let output = cmd.exec().map_err(|e| KargoError::Generic {
-        message: format!("Failed to package JAR: {e}"),
-    })?;
-
-    if output.status.success() {
-        Ok(Some(jar_path.to_path_buf()))
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Warning: JAR packaging failed: {stderr}");
-        Ok(None)
+    let manifest = kargo_compiler::jar::JarManifest::for_package(package, main_class);
+    let summary =
+        kargo_compiler::jar::write_jar(jar_path, &[classes_dir, resources_dir], &manifest)?;
+    if !summary.versions.is_empty() && !manifest.is_multi_release() {
+        kargo_util::progress::status_warn(
+            "Warning",
+            "JAR has META-INF/versions/ entries but is not multi-release; \
+             set [package.jar] multi-release = true",
+        );
     }
+    Ok(Some(jar_path.to_path_buf()))
 }
 
 /// Auto-detect Kotlin compiler plugins needed based on resolved dependencies.
@@ -687,7 +1230,7 @@ fn run_ksp1_pass(
     std::fs::create_dir_all(&ksp_classes).map_err(KargoError::Io)?;
 
     let kotlinc = ap.kotlin_home.join("bin").join("kotlinc");
-    let mut cmd = kargo_util::process::CommandBuilder::new(kotlinc.to_string_lossy().to_string());
+    let mut cmd = ap.command(&kotlinc)?;
 
     for arg in &ksp_args {
         cmd = cmd.arg(arg);
@@ -722,6 +1265,7 @@ fn run_ksp1_pass(
     let output = cmd.exec().map_err(|e| KargoError::Generic {
         message: format!("Failed to run KSP1 pass: {e}"),
     })?;
+    ap.report_violations("KSP1", &output);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -743,13 +1287,14 @@ fn run_ksp1_pass(
     Ok(())
 }
 
-/// Compile KAPT-generated Java sources with `javac`.
-fn compile_kapt_java(
+/// Compile generated Java sources (KAPT stubs, protobuf messages) with `javac`.
+fn compile_generated_java(
     jdk_home: &Path,
     java_source_dir: &Path,
     classes_dir: &Path,
     classpath: &[PathBuf],
     java_target: &str,
+    origin: &str,
 ) -> miette::Result<()> {
     let javac = jdk_home.join("bin").join("javac");
     if !javac.is_file() {
@@ -771,7 +1316,8 @@ fn compile_kapt_java(
     }
     let cp = cp_parts.join(if cfg!(windows) { ";" } else { ":" });
 
-    let mut cmd = kargo_util::process::CommandBuilder::new(javac.to_string_lossy().to_string());
+    let mut cmd = kargo_util::process::CommandBuilder::new(javac.to_string_lossy().to_string())
+        .response_file(true);
     cmd = cmd
         .arg("-classpath")
         .arg(&cp)
@@ -787,13 +1333,13 @@ fn compile_kapt_java(
     }
 
     let output = cmd.exec().map_err(|e| KargoError::Generic {
-        message: format!("Failed to run javac for KAPT sources: {e}"),
+        message: format!("Failed to run javac for {origin} sources: {e}"),
     })?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(KargoError::Generic {
-            message: format!("javac compilation of KAPT-generated sources failed:\n{stderr}"),
+            message: format!("javac compilation of {origin}-generated sources failed:\n{stderr}"),
         }
         .into());
     }
@@ -815,30 +1361,142 @@ fn collect_java_files(dir: &Path, out: &mut Vec<PathBuf>) {
     }
 }
 
-fn print_diagnostics(diagnostics: &[kargo_compiler::unit::Diagnostic]) {
-    use kargo_compiler::unit::DiagnosticSeverity;
+pub(crate) fn print_diagnostics(diagnostics: &[kargo_compiler::unit::Diagnostic]) {
+    let quiet = kargo_util::progress::is_quiet();
     for d in diagnostics {
-        let prefix = match d.severity {
-            DiagnosticSeverity::Error => "error",
-            DiagnosticSeverity::Warning => "warning",
-            DiagnosticSeverity::Info => "info",
-        };
-        let location = match (&d.file, d.line) {
-            (Some(f), Some(l)) => format!("{f}:{l}: "),
-            (Some(f), None) => format!("{f}: "),
+        if quiet && d.severity != kargo_compiler::unit::DiagnosticSeverity::Error {
+            continue;
+        }
+        let location = match (&d.file, d.line, d.column) {
+            (Some(f), Some(l), Some(c)) => format!("{f}:{l}:{c}: "),
+            (Some(f), Some(l), None) => format!("{f}:{l}: "),
+            (Some(f), None, _) => format!("{f}: "),
             _ => String::new(),
         };
-        eprintln!("{location}{prefix}: {}", d.message);
+        let message = match kargo_util::errors::error_format() {
+            ErrorFormat::Human => d.message.clone(),
+            ErrorFormat::Short => kargo_util::errors::single_line(&d.message),
+        };
+        eprintln!("{location}{}: {message}", d.severity.as_str());
     }
 }
 
+// ---------------------------------------------------------------------------
+// Build events (`--message-format json-diagnostics` and the event socket)
+// ---------------------------------------------------------------------------
+
+fn emit_event(event: serde_json::Value) {
+    if kargo_util::events::enabled() {
+        kargo_util::events::emit(event);
+    }
+}
+
+pub(crate) fn emit_start(ctx: &crate::BuildContext) {
+    emit_event(serde_json::json!({
+        "event": "start",
+        "package": ctx.manifest.package.name,
+        "version": ctx.manifest.package.version,
+        "target": ctx.target.kebab_name(),
+        "profile": ctx.profile_name,
+    }));
+}
+
+/// A compilation unit is about to compile (`fresh: false`) or was skipped
+/// as up-to-date / restored from the build cache (`fresh: true`).
+pub(crate) fn emit_unit(unit: &CompilationUnit, fresh: bool) {
+    emit_event(serde_json::json!({
+        "event": "unit",
+        "unit": unit.name,
+        "target": unit.target.kebab_name(),
+        "sources": unit.sources.len(),
+        "fresh": fresh,
+    }));
+}
+
+pub(crate) fn emit_diagnostic(unit: &str, d: &kargo_compiler::unit::Diagnostic) {
+    emit_event(serde_json::json!({
+        "event": "diagnostic",
+        "unit": unit,
+        "severity": d.severity.as_str(),
+        "message": d.message,
+        "file": d.file,
+        "line": d.line,
+        "column": d.column,
+    }));
+}
+
+/// Dependency resolution started (`resolve-start`) or ended
+/// (`resolve-finish`, with the number of resolved artifacts on success).
+pub(crate) fn emit_resolve_start() {
+    emit_event(serde_json::json!({ "event": "resolve-start" }));
+}
+
+pub(crate) fn emit_resolve_finish(dependencies: Option<usize>) {
+    emit_event(serde_json::json!({
+        "event": "resolve-finish",
+        "success": dependencies.is_some(),
+        "dependencies": dependencies,
+    }));
+}
+
+/// The build produced (or kept, when `fresh`) its packaged output.
+pub(crate) fn emit_artifact(target: KotlinTarget, path: &Path, fresh: bool) {
+    emit_event(serde_json::json!({
+        "event": "artifact",
+        "target": target.kebab_name(),
+        "path": path,
+        "fresh": fresh,
+    }));
+}
+
+/// One test case ran; `message` is the first line of a failure.
+pub(crate) fn emit_test(case: &crate::ops_device_test::TestCase) {
+    use crate::ops_device_test::Outcome;
+    emit_event(serde_json::json!({
+        "event": "test",
+        "class": case.class,
+        "name": case.name,
+        "outcome": match case.outcome {
+            Outcome::Passed => "passed",
+            Outcome::Failed => "failed",
+            Outcome::Skipped => "skipped",
+        },
+        "message": case.stack.as_deref().and_then(|s| s.lines().next()),
+        "durationMs": (case.time * 1000.0) as u64,
+    }));
+}
+
+/// A test run ended, with the outcome counts of its reported cases.
+pub(crate) fn emit_test_finish(success: bool, cases: &[crate::ops_device_test::TestCase]) {
+    use crate::ops_device_test::Outcome;
+    let count = |o: Outcome| cases.iter().filter(|c| c.outcome == o).count();
+    emit_event(serde_json::json!({
+        "event": "test-finish",
+        "success": success,
+        "passed": count(Outcome::Passed),
+        "failed": count(Outcome::Failed),
+        "skipped": count(Outcome::Skipped),
+    }));
+}
+
+pub(crate) fn emit_finish(success: bool, start: Instant) {
+    emit_event(serde_json::json!({
+        "event": "finish",
+        "success": success,
+        "durationMs": start.elapsed().as_millis() as u64,
+    }));
+}
+
 /// Collect generated source directories and individual files for compilation.
 /// Returns `(directories, individual_files)`.
 ///
 /// Only includes specific known output directories (ksp/kotlin, ksp/java,
 /// kapt/sources) to avoid recursing into KSP2 internal directories
 /// (caches, backups) that would cause duplicate declarations.
-fn collect_generated_sources(generated_dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+fn collect_generated_sources(
+    generated_dir: &Path,
+    manifest: &Manifest,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
     let mut dirs = Vec::new();
     let mut files = Vec::new();
 
@@ -856,6 +1514,10 @@ fn collect_generated_sources(generated_dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf
         dirs.push(kapt_sources);
     }
 
+    dirs.extend(crate::ops_protobuf::source_dirs(generated_dir));
+    dirs.extend(crate::ops_generate::source_dirs(generated_dir, manifest));
+    dirs.extend(crate::ops_sqldelight::source_dirs(generated_dir, manifest));
+
     // Top-level files (e.g., BuildConfig.kt) — added individually to avoid
     // recursing into the entire generated_dir.
     if let Ok(entries) = std::fs::read_dir(generated_dir) {
@@ -1159,3 +1821,35 @@ fn dir_is_empty(dir: &Path) -> bool {
     }
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_violations_cover_time_phases_and_size() {
+        let budgets = kargo_core::manifest::BudgetConfig {
+            build_time: Some("60s".into()),
+            jar_size: Some("1MB".into()),
+            phases: [("compile".to_string(), "10s".to_string())].into(),
+        };
+        let phases = PhaseTimings(vec![
+            ("generate", Duration::from_secs(30)),
+            ("compile", Duration::from_secs(12)),
+        ]);
+
+        let within = budget_violations(&budgets, &phases, Duration::from_secs(50), Some(1024));
+        assert_eq!(within.len(), 1);
+        assert!(within[0].starts_with("compile took 12.00s"));
+
+        let over = budget_violations(
+            &budgets,
+            &phases,
+            Duration::from_secs(61),
+            Some(2 * 1024 * 1024),
+        );
+        assert_eq!(over.len(), 3);
+        assert!(over[0].contains("build-time"));
+        assert!(over[2].contains("jar-size"));
+    }
+}
@@ -1,6 +1,14 @@
-//! Operation: manage the build cache, compiler metadata, and Kotlin daemon.
+//! Operation: manage the build cache, compiler metadata, and Kotlin daemon,
+//! and move the caches a build used between machines.
+
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use kargo_compiler::build_cache::BuildCache;
+use kargo_core::lockfile::Lockfile;
+use kargo_maven::cache::LocalCache;
 use kargo_util::errors::KargoError;
 use kargo_util::fs::dir_size;
 
@@ -30,12 +38,20 @@ pub fn stats() -> miette::Result<()> {
         if fp_dir.is_dir() {
             println!("  Fingerprints: {}", format_size(dir_size(&fp_dir)));
         }
+
+        let cache = LocalCache::new(&cwd);
+        for shared in cache.roots().filter(|root| *root != deps_dir) {
+            println!();
+            println!("Shared dependencies: {}", shared.display());
+            println!("  Size:    {}", format_size(dir_size(shared)));
+        }
     }
 
     Ok(())
 }
 
-/// Clear the global build cache, cached dependencies, and project-level metadata.
+/// Clear the global build cache, the project's cached dependencies, and
+/// project-level metadata. The per-user dependency cache is left alone.
 pub fn clean() -> miette::Result<()> {
     let cache = BuildCache::new(BuildCache::default_path(), None);
     let freed = cache.clean()?;
@@ -78,6 +94,417 @@ pub fn clean() -> miette::Result<()> {
     Ok(())
 }
 
+/// A cached library that failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptEntry {
+    /// `group:artifact:version`.
+    pub coordinate: String,
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Result of scanning the dependency cache against `Kargo.lock`.
+#[derive(Debug, Default)]
+pub struct CacheScan {
+    /// Libraries checked.
+    pub checked: usize,
+    pub corrupt: Vec<CorruptEntry>,
+    /// Cached coordinates that `Kargo.lock` no longer references.
+    pub orphaned: Vec<String>,
+}
+
+/// Re-hash every library in `cache` against the checksums in `lockfile`,
+/// check that each one is a readable archive, and find libraries the
+/// lockfile does not reference. Libraries in the per-user cache are never
+/// orphaned, since other projects may use them.
+pub fn scan(cache: &LocalCache, lockfile: &Lockfile) -> CacheScan {
+    let locked: HashMap<String, Option<&str>> = lockfile
+        .package
+        .iter()
+        .map(|p| {
+            (
+                format!("{}:{}:{}", p.group, p.name, p.version),
+                p.checksum.as_deref().filter(|c| !c.is_empty()),
+            )
+        })
+        .collect();
+
+    let mut scan = CacheScan::default();
+    let mut to_check = Vec::new();
+    for ((group, artifact, version), path) in cache.libraries() {
+        let coordinate = format!("{group}:{artifact}:{version}");
+        match locked.get(&coordinate) {
+            Some(expected) => to_check.push((coordinate, path, *expected)),
+            None if !cache.is_shared() && path.starts_with(cache.root()) => {
+                scan.orphaned.push(coordinate)
+            }
+            None => {}
+        }
+    }
+    scan.checked = to_check.len();
+
+    // A fixed pool of workers pulls entries off a shared index, so a large
+    // lockfile never hashes more JARs at once than there are cores.
+    let next = AtomicUsize::new(0);
+    let workers = (kargo_util::system::cores() as usize).clamp(1, to_check.len().max(1));
+    let mut found: Vec<(usize, CorruptEntry)> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut found = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((coordinate, path, expected)) = to_check.get(i) else {
+                            break;
+                        };
+                        let reason = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                            check_library(path, *expected)
+                        }))
+                        .unwrap_or_else(|_| Some("the integrity check panicked".to_string()));
+                        if let Some(reason) = reason {
+                            found.push((
+                                i,
+                                CorruptEntry {
+                                    coordinate: coordinate.clone(),
+                                    path: path.clone(),
+                                    reason,
+                                },
+                            ));
+                        }
+                    }
+                    found
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    });
+    found.sort_by_key(|(i, _)| *i);
+    scan.corrupt = found.into_iter().map(|(_, entry)| entry).collect();
+    scan
+}
+
+/// Why `path` is corrupt, or `None` if it is intact.
+fn check_library(path: &Path, expected: Option<&str>) -> Option<String> {
+    let actual = match kargo_util::hash::sha256_file_streaming(path) {
+        Ok(hash) => hash,
+        Err(e) => return Some(format!("unreadable: {e}")),
+    };
+    if let Some(expected) = expected {
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Some(format!(
+                "checksum mismatch: expected {expected}, got {actual}"
+            ));
+        }
+    }
+    let archive = std::fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|f| zip::ZipArchive::new(f).map_err(|e| e.to_string()));
+    archive.err().map(|e| format!("not a valid archive: {e}"))
+}
+
+/// `kargo cache verify`: check every cached dependency of the project in
+/// `project_root` against `Kargo.lock` and list corrupt and orphaned
+/// entries. With `repair`, corrupt files are deleted and downloaded again,
+/// and orphaned ones are removed.
+pub async fn verify(project_root: &Path, repair: bool) -> miette::Result<()> {
+    use kargo_util::progress::{status, status_warn};
+
+    if !kargo_core::workspace::lockfile_path(project_root).is_file() {
+        return Err(KargoError::Generic {
+            message: "No Kargo.lock found. Run `kargo fetch` first.".into(),
+        }
+        .into());
+    }
+    let lockfile = Lockfile::for_project(project_root)?;
+    let cache = LocalCache::new(project_root);
+    let result = scan(&cache, &lockfile);
+
+    for entry in &result.corrupt {
+        status_warn(
+            "Corrupt",
+            &format!("{} ({})", entry.coordinate, entry.reason),
+        );
+    }
+    for coordinate in &result.orphaned {
+        status_warn("Orphaned", &format!("{coordinate} (not in Kargo.lock)"));
+    }
+    let intact = result.checked - result.corrupt.len();
+    status(
+        "Verified",
+        &format!(
+            "{intact} of {} cached libraries in {}",
+            result.checked,
+            cache.root().display()
+        ),
+    );
+
+    if result.corrupt.is_empty() && result.orphaned.is_empty() {
+        return Ok(());
+    }
+    if !repair {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{} corrupt and {} orphaned cache entries. \
+                 Run `kargo cache verify --repair` to fix them.",
+                result.corrupt.len(),
+                result.orphaned.len()
+            ),
+        }
+        .into());
+    }
+
+    if !result.orphaned.is_empty() {
+        let keep: HashSet<(String, String, String)> = lockfile
+            .package
+            .iter()
+            .map(|p| (p.group.clone(), p.name.clone(), p.version.clone()))
+            .collect();
+        let removed = cache.prune(&keep);
+        status("Removed", &format!("{removed} orphaned entries"));
+    }
+    if !result.corrupt.is_empty() {
+        for entry in &result.corrupt {
+            std::fs::remove_file(&entry.path).map_err(KargoError::Io)?;
+        }
+        crate::ops_fetch::fetch(project_root, false).await?;
+        let still_corrupt = scan(&cache, &lockfile).corrupt;
+        if let Some(entry) = still_corrupt.first() {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "{} is still corrupt after re-downloading ({})",
+                    entry.coordinate, entry.reason
+                ),
+            }
+            .into());
+        }
+        status(
+            "Repaired",
+            &format!("{} corrupt entries", result.corrupt.len()),
+        );
+    }
+    Ok(())
+}
+
+/// Top-level directory of build-cache entries in a cache archive.
+const ARCHIVE_BUILD_CACHE: &str = "build-cache";
+/// Top-level directory of dependency version directories in a cache archive.
+const ARCHIVE_DEPENDENCIES: &str = "dependencies";
+
+/// What `kargo cache export` wrote or `kargo cache import` read.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheArchiveSummary {
+    /// Build-cache entries.
+    pub build_entries: usize,
+    /// Dependency version directories.
+    pub dependencies: usize,
+    /// Files left alone on import because they already existed.
+    pub skipped: usize,
+}
+
+/// `kargo cache export`: write the build-cache entries of the project's
+/// last builds and its locked dependencies to the `.tar.gz` `archive`.
+pub fn export(project_root: &Path, archive: &Path) -> miette::Result<CacheArchiveSummary> {
+    let build_cache = BuildCache::new(BuildCache::default_path(), None);
+    let summary = export_caches(
+        project_root,
+        &build_cache,
+        &LocalCache::new(project_root),
+        archive,
+    )?;
+    kargo_util::progress::status(
+        "Exported",
+        &format!(
+            "{} build-cache entries and {} dependencies to {} ({})",
+            summary.build_entries,
+            summary.dependencies,
+            archive.display(),
+            format_size(std::fs::metadata(archive).map(|m| m.len()).unwrap_or(0))
+        ),
+    );
+    Ok(summary)
+}
+
+/// `kargo cache import`: unpack an archive from `kargo cache export` into
+/// the build cache and the project's dependency cache. Existing files are
+/// kept.
+pub fn import(project_root: &Path, archive: &Path) -> miette::Result<CacheArchiveSummary> {
+    let build_cache = BuildCache::new(BuildCache::default_path(), None);
+    let summary = import_caches(&build_cache, &LocalCache::new(project_root), archive)?;
+    kargo_util::progress::status(
+        "Imported",
+        &format!(
+            "{} build-cache entries and {} dependencies from {}",
+            summary.build_entries,
+            summary.dependencies,
+            archive.display()
+        ),
+    );
+    Ok(summary)
+}
+
+/// The build-cache entries of the last builds are those whose keys are the
+/// fingerprints under `.kargo/fingerprints/`; the dependencies are the
+/// `Kargo.lock` packages and the POM-only directories (parents and BOMs)
+/// that resolution reads.
+fn export_caches(
+    project_root: &Path,
+    build_cache: &BuildCache,
+    deps: &LocalCache,
+    archive: &Path,
+) -> miette::Result<CacheArchiveSummary> {
+    let mut summary = CacheArchiveSummary::default();
+    let mut hashes = Vec::new();
+    collect_fingerprints(
+        &project_root.join(".kargo").join("fingerprints"),
+        &mut hashes,
+    );
+    hashes.sort();
+    hashes.dedup();
+
+    let locked: HashSet<(String, String, String)> =
+        if kargo_core::workspace::lockfile_path(project_root).is_file() {
+            Lockfile::for_project(project_root)?
+                .package
+                .into_iter()
+                .map(|p| (p.group, p.name, p.version))
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+    if let Some(parent) = archive.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    let file = std::fs::File::create(archive).map_err(KargoError::Io)?;
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        file,
+        flate2::Compression::default(),
+    ));
+    tar.follow_symlinks(false);
+
+    for hash in &hashes {
+        if let Some(dir) = build_cache.entry(hash) {
+            tar.append_dir_all(Path::new(ARCHIVE_BUILD_CACHE).join(hash), &dir)
+                .map_err(KargoError::Io)?;
+            summary.build_entries += 1;
+        }
+    }
+    if !locked.is_empty() {
+        for ((group, artifact, version), dir) in deps.version_dirs() {
+            let coord = (group, artifact, version);
+            let pom_only = deps.get_library(&coord.0, &coord.1, &coord.2).is_none();
+            if !pom_only && !locked.contains(&coord) {
+                continue;
+            }
+            let (group, artifact, version) = coord;
+            let name = Path::new(ARCHIVE_DEPENDENCIES)
+                .join(group.replace('.', "/"))
+                .join(artifact)
+                .join(version);
+            tar.append_dir_all(name, &dir).map_err(KargoError::Io)?;
+            summary.dependencies += 1;
+        }
+    }
+
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(KargoError::Io)?;
+    Ok(summary)
+}
+
+/// The hashes in the `<unit>.txt` fingerprint files under `dir`.
+fn collect_fingerprints(dir: &Path, hashes: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            collect_fingerprints(&path, hashes);
+        } else if path.extension().is_some_and(|ext| ext == "txt") {
+            let hash = std::fs::read_to_string(&path).unwrap_or_default();
+            let hash = hash.trim();
+            if !hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                hashes.push(hash.to_string());
+            }
+        }
+    }
+}
+
+fn import_caches(
+    build_cache: &BuildCache,
+    deps: &LocalCache,
+    archive: &Path,
+) -> miette::Result<CacheArchiveSummary> {
+    let bad_archive = |e: std::io::Error| KargoError::Generic {
+        message: format!("{} is not a cache archive: {e}", archive.display()),
+    };
+    let file = std::fs::File::open(archive).map_err(KargoError::Io)?;
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let mut build_entries = HashSet::new();
+    let mut dependencies = HashSet::new();
+    let mut summary = CacheArchiveSummary::default();
+
+    for entry in tar.entries().map_err(bad_archive)? {
+        let mut entry = entry.map_err(bad_archive)?;
+        let kind = entry.header().entry_type();
+        if !kind.is_file() && !kind.is_dir() {
+            continue;
+        }
+        let path = entry.path().map_err(bad_archive)?.into_owned();
+        // Only plain relative paths under one of the two top-level
+        // directories are unpacked.
+        let mut parts = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_os_string()),
+                Component::CurDir => {}
+                _ => {
+                    parts.clear();
+                    break;
+                }
+            }
+        }
+        let Some((top, rest)) = parts.split_first() else {
+            continue;
+        };
+        let root = match top.to_str() {
+            Some(ARCHIVE_BUILD_CACHE) => build_cache.root(),
+            Some(ARCHIVE_DEPENDENCIES) => deps.root(),
+            _ => continue,
+        };
+        if rest.is_empty() {
+            continue;
+        }
+        let relative: PathBuf = rest.iter().collect();
+        let dest = root.join(&relative);
+        if kind.is_dir() {
+            std::fs::create_dir_all(&dest).map_err(KargoError::Io)?;
+            continue;
+        }
+        if top == ARCHIVE_BUILD_CACHE {
+            build_entries.insert(rest[0].clone());
+        } else if let Some(dir) = relative.parent() {
+            dependencies.insert(dir.to_path_buf());
+        }
+        if dest.exists() {
+            summary.skipped += 1;
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+        }
+        entry.unpack(&dest).map_err(bad_archive)?;
+    }
+
+    build_cache.rebuild_size();
+    summary.build_entries = build_entries.len();
+    summary.dependencies = dependencies.len();
+    Ok(summary)
+}
+
 /// Stop the Kotlin compiler daemon (if any).
 pub async fn stop_daemon() -> miette::Result<()> {
     let cwd = std::env::current_dir().map_err(KargoError::Io)?;
@@ -121,7 +548,7 @@ pub async fn stop_daemon() -> miette::Result<()> {
     Ok(())
 }
 
-fn format_size(bytes: u64) -> String {
+pub(crate) fn format_size(bytes: u64) -> String {
     if bytes >= 1024 * 1024 * 1024 {
         format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
     } else if bytes >= 1024 * 1024 {
@@ -132,3 +559,176 @@ fn format_size(bytes: u64) -> String {
         format!("{bytes} B")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use kargo_core::lockfile::LockedPackage;
+
+    use super::*;
+
+    fn jar_bytes() -> Vec<u8> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut buf);
+        zip.start_file("A.class", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"class").unwrap();
+        zip.finish().unwrap();
+        buf.into_inner()
+    }
+
+    fn locked(name: &str, checksum: Option<String>) -> LockedPackage {
+        LockedPackage {
+            name: name.into(),
+            group: "org.example".into(),
+            version: "1.0".into(),
+            checksum,
+            source: None,
+            scope: None,
+            targets: Vec::new(),
+            dependencies: Vec::new(),
+            relocated_from: None,
+            members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn scan_finds_corrupt_and_orphaned_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let jar = jar_bytes();
+        for name in ["good", "tampered", "truncated", "stale"] {
+            cache
+                .put_jar("org.example", name, "1.0", None, &jar)
+                .unwrap();
+        }
+        let sum = kargo_util::hash::sha256_bytes(&jar);
+        cache
+            .put_jar("org.example", "tampered", "1.0", None, b"not a jar")
+            .unwrap();
+        cache
+            .put_jar(
+                "org.example",
+                "truncated",
+                "1.0",
+                None,
+                &jar[..jar.len() / 2],
+            )
+            .unwrap();
+
+        let lockfile = Lockfile {
+            package: vec![
+                locked("good", Some(sum.clone())),
+                locked("tampered", Some(sum)),
+                locked("truncated", None),
+            ],
+            ..Lockfile::default()
+        };
+
+        let scan = scan(&cache, &lockfile);
+        assert_eq!(scan.checked, 3);
+        let corrupt: Vec<&str> = scan.corrupt.iter().map(|e| e.coordinate.as_str()).collect();
+        assert_eq!(
+            corrupt,
+            ["org.example:tampered:1.0", "org.example:truncated:1.0"]
+        );
+        assert!(scan.corrupt[0].reason.starts_with("checksum mismatch"));
+        assert!(scan.corrupt[1].reason.starts_with("not a valid archive"));
+        assert_eq!(scan.orphaned, ["org.example:stale:1.0"]);
+    }
+
+    #[test]
+    fn scan_checks_more_entries_than_workers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let jar = jar_bytes();
+        let sum = kargo_util::hash::sha256_bytes(&jar);
+        let count = kargo_util::system::cores() as usize * 3 + 1;
+        let mut package = Vec::new();
+        for i in 0..count {
+            let name = format!("lib{i:03}");
+            let bytes: &[u8] = if i % 2 == 0 { &jar } else { b"not a jar" };
+            cache
+                .put_jar("org.example", &name, "1.0", None, bytes)
+                .unwrap();
+            package.push(locked(&name, Some(sum.clone())));
+        }
+        let lockfile = Lockfile {
+            package,
+            ..Lockfile::default()
+        };
+
+        let scan = scan(&cache, &lockfile);
+        assert_eq!(scan.checked, count);
+        let mut corrupt: Vec<String> = scan.corrupt.iter().map(|e| e.coordinate.clone()).collect();
+        corrupt.sort();
+        let expected: Vec<String> = (0..count)
+            .filter(|i| i % 2 == 1)
+            .map(|i| format!("org.example:lib{i:03}:1.0"))
+            .collect();
+        assert_eq!(corrupt, expected);
+    }
+
+    #[test]
+    fn export_and_import_carry_only_what_the_last_build_used() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("app");
+        let fp_dir = project.join(".kargo/fingerprints/jvm/debug");
+        std::fs::create_dir_all(&fp_dir).unwrap();
+        std::fs::write(fp_dir.join("main.txt"), "ab12").unwrap();
+        std::fs::write(fp_dir.join("main.mtime"), "1 1").unwrap();
+        let build_cache = BuildCache::new(tmp.path().join("build-cache"), None);
+        for hash in ["ab12", "cd34"] {
+            let classes = tmp.path().join("classes").join(hash);
+            std::fs::create_dir_all(&classes).unwrap();
+            std::fs::write(classes.join("Main.class"), hash).unwrap();
+            build_cache
+                .put(
+                    &kargo_compiler::fingerprint::Fingerprint { hash: hash.into() },
+                    &classes,
+                )
+                .unwrap();
+        }
+        let deps = LocalCache::from_root(project.join(".kargo/dependencies"));
+        deps.put_jar("org.example", "lib", "1.0", None, &jar_bytes())
+            .unwrap();
+        deps.put_jar("org.example", "stale", "1.0", None, &jar_bytes())
+            .unwrap();
+        deps.put_pom("org.example", "parent", "1.0", "<project/>")
+            .unwrap();
+        Lockfile {
+            package: vec![locked("lib", None)],
+            ..Lockfile::default()
+        }
+        .write_to(&project.join("Kargo.lock"))
+        .unwrap();
+
+        let archive = tmp.path().join("out").join("cache.tar.gz");
+        let exported = export_caches(&project, &build_cache, &deps, &archive).unwrap();
+        assert_eq!(exported.build_entries, 1);
+        assert_eq!(exported.dependencies, 2);
+
+        let ci = tmp.path().join("ci");
+        let ci_build_cache = BuildCache::new(ci.join("build-cache"), None);
+        let ci_deps = LocalCache::from_root(ci.join("dependencies"));
+        let imported = import_caches(&ci_build_cache, &ci_deps, &archive).unwrap();
+        assert_eq!(
+            imported,
+            CacheArchiveSummary {
+                build_entries: 1,
+                dependencies: 2,
+                skipped: 0,
+            }
+        );
+        let entry = ci_build_cache.entry("ab12").unwrap();
+        assert_eq!(std::fs::read(entry.join("Main.class")).unwrap(), b"ab12");
+        assert!(ci_build_cache.entry("cd34").is_none());
+        assert!(ci_deps.has_artifact("org.example", "lib", "1.0"));
+        assert!(ci_deps.get_pom("org.example", "parent", "1.0").is_some());
+        assert!(!ci_deps.has_artifact("org.example", "stale", "1.0"));
+
+        let again = import_caches(&ci_build_cache, &ci_deps, &archive).unwrap();
+        assert!(again.skipped > 0);
+    }
+}
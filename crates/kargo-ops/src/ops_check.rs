@@ -8,26 +8,60 @@ use std::path::Path;
 use kargo_compiler::dispatch::CompilerDispatch;
 use kargo_compiler::source_set_discovery::collect_kotlin_files;
 use kargo_compiler::unit::CompilationUnit;
-use kargo_util::errors::KargoError;
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::{FormatConfig, KotlinVersionReq, Manifest};
+use kargo_maven::cache::LocalCache;
+use kargo_util::errors::{exit_code, KargoError};
 
+use crate::ops_audit::{self, AuditConfig};
+use crate::ops_build::{self, MessageFormat};
 use crate::ops_setup;
 
 /// Type-check the project without producing output artifacts.
-pub async fn check(project_dir: &Path, verbose: bool) -> miette::Result<()> {
-    let ctx = crate::BuildContext::load(project_dir, None, None, false).await?;
+pub async fn check(
+    project_dir: &Path,
+    verbose: bool,
+    message_format: MessageFormat,
+) -> miette::Result<()> {
+    let start = std::time::Instant::now();
+    kargo_util::events::set_stdout(message_format.is_json());
+    let result = check_sources(project_dir, verbose, message_format).await;
+    ops_build::emit_finish(result.is_ok(), start);
+    result
+}
+
+async fn check_sources(
+    project_dir: &Path,
+    verbose: bool,
+    message_format: MessageFormat,
+) -> miette::Result<()> {
+    let mut ctx = crate::BuildContext::load(project_dir, None, None, false).await?;
+    crate::ops_workspace::add_module_dependencies(&mut ctx, verbose).await?;
+    let json = message_format.is_json();
 
-    if verbose {
+    if verbose && !json {
         ops_setup::print_preflight_summary(&ctx.preflight);
         println!();
     }
 
-    kargo_util::progress::status(
-        "Checking",
-        &format!(
-            "{} v{}",
-            ctx.manifest.package.name, ctx.manifest.package.version
-        ),
-    );
+    ops_build::emit_start(&ctx);
+    if !json {
+        kargo_util::progress::status(
+            "Checking",
+            &format!(
+                "{} v{}",
+                ctx.manifest.package.name, ctx.manifest.package.version
+            ),
+        );
+    }
+
+    let verified = crate::ops_sqldelight::verify(&ctx, verbose && !json).await?;
+    if verified > 0 && !json {
+        kargo_util::progress::status(
+            "Verified",
+            &format!("SQLDelight migrations ({verified} databases)"),
+        );
+    }
 
     let mut all_kotlin_dirs: Vec<std::path::PathBuf> = Vec::new();
     for ss in &ctx.discovered.main_sources {
@@ -36,7 +70,9 @@ pub async fn check(project_dir: &Path, verbose: bool) -> miette::Result<()> {
     let main_sources = collect_kotlin_files(&all_kotlin_dirs);
 
     if main_sources.is_empty() {
-        println!("No Kotlin source files found to check.");
+        if !json {
+            println!("No Kotlin source files found to check.");
+        }
         return Ok(());
     }
 
@@ -51,6 +87,7 @@ pub async fn check(project_dir: &Path, verbose: bool) -> miette::Result<()> {
         is_test: false,
         generated_sources: vec![],
         processor_jars: vec![],
+        local_jars: ctx.classpath.local_jars,
     };
 
     let compiler = CompilerDispatch::resolve(
@@ -60,29 +97,398 @@ pub async fn check(project_dir: &Path, verbose: bool) -> miette::Result<()> {
         ctx.preflight.java_target.clone(),
     );
 
-    let output = compiler.check_only(&unit, &ctx.env)?;
-
-    for d in &output.diagnostics {
-        let prefix = match d.severity {
-            kargo_compiler::unit::DiagnosticSeverity::Error => "error",
-            kargo_compiler::unit::DiagnosticSeverity::Warning => "warning",
-            kargo_compiler::unit::DiagnosticSeverity::Info => "info",
-        };
-        let location = match (&d.file, d.line) {
-            (Some(f), Some(l)) => format!("{f}:{l}: "),
-            (Some(f), None) => format!("{f}: "),
-            _ => String::new(),
-        };
-        eprintln!("{location}{prefix}: {}", d.message);
-    }
+    let output = if kargo_util::events::enabled() {
+        ops_build::emit_unit(&unit, false);
+        let output = compiler.check_only_streaming(&unit, &ctx.env, &mut |d| {
+            ops_build::emit_diagnostic(&unit.name, d)
+        })?;
+        if !json {
+            ops_build::print_diagnostics(&output.diagnostics);
+        }
+        output
+    } else {
+        let output = compiler.check_only(&unit, &ctx.env)?;
+        ops_build::print_diagnostics(&output.diagnostics);
+        output
+    };
 
     if output.success {
-        kargo_util::progress::status("Finished", "check passed");
+        if !json {
+            kargo_util::progress::status("Finished", "check passed");
+        }
         Ok(())
     } else {
-        Err(KargoError::Generic {
-            message: "Type-check failed.".into(),
+        Err(KargoError::Compilation {
+            message: "type-check reported errors".into(),
         }
         .into())
     }
 }
+
+/// A category of `kargo check --all`. Each failing category sets its own
+/// bit in the exit code (above [`exit_code::CHECK_ALL`]), so CI can gate on
+/// some categories only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckCategory {
+    /// `Kargo.toml` validation and manifest lints.
+    Manifest,
+    /// Known vulnerabilities of the dependencies.
+    Audit,
+    /// Dependency licenses against `[audit] allowed-licenses`.
+    Licenses,
+    /// Source layout, as `[format]` configures it.
+    Format,
+    /// Type-checking the main sources.
+    TypeCheck,
+}
+
+impl CheckCategory {
+    pub const ALL: [Self; 5] = [
+        Self::Manifest,
+        Self::Audit,
+        Self::Licenses,
+        Self::Format,
+        Self::TypeCheck,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Manifest => "manifest",
+            Self::Audit => "audit",
+            Self::Licenses => "licenses",
+            Self::Format => "format",
+            Self::TypeCheck => "type-check",
+        }
+    }
+
+    /// The bit this category sets in the exit code when it fails.
+    pub fn exit_bit(self) -> i32 {
+        match self {
+            Self::Manifest => 1,
+            Self::Audit => 2,
+            Self::Licenses => 4,
+            Self::Format => 8,
+            Self::TypeCheck => 16,
+        }
+    }
+}
+
+/// How one category of `kargo check --all` went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    /// The problems found, one line each.
+    Failed(Vec<String>),
+    /// Not run, and why.
+    Skipped(String),
+}
+
+/// The result of `kargo check --all`, one outcome per category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    pub outcomes: Vec<(CheckCategory, CheckOutcome)>,
+}
+
+impl CheckReport {
+    /// 0 when everything passed, otherwise [`exit_code::CHECK_ALL`] plus
+    /// the bits of the failed categories.
+    pub fn exit_code(&self) -> i32 {
+        let bits = self
+            .outcomes
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, CheckOutcome::Failed(_)))
+            .fold(0, |code, (category, _)| code | category.exit_bit());
+        if bits == 0 {
+            exit_code::SUCCESS
+        } else {
+            exit_code::CHECK_ALL | bits
+        }
+    }
+
+    fn print(&self) {
+        use kargo_util::progress::{status, status_info, status_warn};
+        for (category, outcome) in &self.outcomes {
+            let name = category.name();
+            match outcome {
+                CheckOutcome::Passed => status("Passed", name),
+                CheckOutcome::Skipped(reason) => {
+                    status_info("Skipped", &format!("{name}: {reason}"))
+                }
+                CheckOutcome::Failed(problems) => {
+                    status_warn("Failed", &format!("{name}: {} problem(s)", problems.len()));
+                    if !kargo_util::progress::is_quiet() {
+                        for problem in problems {
+                            eprintln!("    {problem}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run every check `kargo check --all` covers and print a combined report.
+///
+/// The manifest is checked first, before type-checking may update
+/// `Kargo.lock`; the dependency audit, license policy, format check and
+/// type-check then run concurrently.
+pub async fn check_all(project_dir: &Path, verbose: bool) -> miette::Result<CheckReport> {
+    let manifest = match Manifest::from_path(&project_dir.join("Kargo.toml")) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let skipped = CheckOutcome::Skipped("Kargo.toml does not load".into());
+            let report = CheckReport {
+                outcomes: CheckCategory::ALL
+                    .iter()
+                    .map(|&c| match c {
+                        CheckCategory::Manifest => (c, CheckOutcome::Failed(vec![e.to_string()])),
+                        _ => (c, skipped.clone()),
+                    })
+                    .collect(),
+            };
+            report.print();
+            return Ok(report);
+        }
+    };
+    let lockfile = if kargo_core::workspace::lockfile_path(project_dir).is_file() {
+        Some(Lockfile::for_project(project_dir)?)
+    } else {
+        None
+    };
+    let manifest_outcome = outcome(manifest_problems(&manifest, lockfile.as_ref()));
+
+    let type_check = tokio::spawn({
+        let project_dir = project_dir.to_path_buf();
+        async move { check_sources(&project_dir, verbose, MessageFormat::Human).await }
+    });
+    let format = tokio::task::spawn_blocking({
+        let project_dir = project_dir.to_path_buf();
+        let config = manifest.format.clone();
+        move || format_problems(&project_dir, config.as_ref())
+    });
+    let (audit_outcome, license_outcome) =
+        check_dependencies(project_dir, &manifest, lockfile.as_ref()).await;
+
+    let format_outcome = match format.await {
+        Ok(problems) => outcome(problems),
+        Err(e) => CheckOutcome::Failed(vec![e.to_string()]),
+    };
+    let type_check_outcome = match type_check.await {
+        Ok(Ok(())) => CheckOutcome::Passed,
+        Ok(Err(e)) => CheckOutcome::Failed(vec![e.to_string()]),
+        Err(e) => CheckOutcome::Failed(vec![e.to_string()]),
+    };
+
+    let report = CheckReport {
+        outcomes: vec![
+            (CheckCategory::Manifest, manifest_outcome),
+            (CheckCategory::Audit, audit_outcome),
+            (CheckCategory::Licenses, license_outcome),
+            (CheckCategory::Format, format_outcome),
+            (CheckCategory::TypeCheck, type_check_outcome),
+        ],
+    };
+    report.print();
+    Ok(report)
+}
+
+fn outcome(problems: Vec<String>) -> CheckOutcome {
+    if problems.is_empty() {
+        CheckOutcome::Passed
+    } else {
+        CheckOutcome::Failed(problems)
+    }
+}
+
+/// Manifest lints beyond what loading `Kargo.toml` validates.
+fn manifest_problems(manifest: &Manifest, lockfile: Option<&Lockfile>) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let declared = crate::ops_fetch::collect_declared_deps(manifest);
+    match lockfile {
+        Some(lockfile) if !lockfile.is_up_to_date(&declared) => {
+            problems.push("Kargo.lock is out of date; run `kargo lock`".to_string());
+        }
+        None if !declared.is_empty() => {
+            problems.push("no Kargo.lock pins the dependencies; run `kargo lock`".to_string());
+        }
+        _ => {}
+    }
+
+    for name in manifest.dependencies.keys() {
+        if manifest.dev_dependencies.contains_key(name) {
+            problems.push(format!(
+                "`{name}` is declared in both [dependencies] and [dev-dependencies]"
+            ));
+        }
+    }
+
+    if let Some(KotlinVersionReq::Exact(kotlin)) = manifest.package.kotlin_req() {
+        if let Ok(kotlin) = kotlin.parse() {
+            // From a version to itself, only plugin version mismatches remain.
+            for note in crate::ops_kotlin_migration::notes(manifest, &kotlin, &kotlin) {
+                problems.push(format!("{}: {}", note.location, note.message));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Layout problems of the Kotlin sources under `src/`, as `path:line: message`.
+fn format_problems(project_dir: &Path, config: Option<&FormatConfig>) -> Vec<String> {
+    let mut problems = Vec::new();
+    let files = collect_kotlin_files(&[project_dir.join("src")]);
+    for file in files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|e| e == "kt"))
+    {
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let display = file.strip_prefix(project_dir).unwrap_or(file).display();
+        for issue in kargo_lint::formatter::check(&source, config) {
+            problems.push(format!("{display}:{}: {}", issue.line, issue.message));
+        }
+    }
+    problems
+}
+
+/// Resolve the dependencies once, then audit them and apply the license
+/// policy concurrently.
+async fn check_dependencies(
+    project_dir: &Path,
+    manifest: &Manifest,
+    lockfile: Option<&Lockfile>,
+) -> (CheckOutcome, CheckOutcome) {
+    let config = AuditConfig::load(&project_dir.join("Kargo.toml"));
+    let repos = kargo_resolver::resolver::build_repos(manifest);
+    let cache = LocalCache::new(project_dir);
+    let resolved = match kargo_maven::download::build_client() {
+        Ok(client) => {
+            kargo_resolver::resolver::resolve(manifest, &repos, &cache, lockfile, &client)
+                .await
+                .map(|result| (client, result))
+        }
+        Err(e) => Err(e),
+    };
+    let (client, result) = match resolved {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            let failed = CheckOutcome::Failed(vec![format!("resolving dependencies: {e}")]);
+            return (failed.clone(), failed);
+        }
+    };
+
+    let audit = async {
+        match ops_audit::scan(&client, &result.artifacts, &config.ignore).await {
+            Ok(findings) => outcome(
+                findings
+                    .iter()
+                    .map(|f| {
+                        format!(
+                            "{}:{}:{} {} [{}]",
+                            f.group,
+                            f.artifact,
+                            f.version,
+                            f.id,
+                            if f.severity.is_empty() {
+                                "unknown"
+                            } else {
+                                &f.severity
+                            }
+                        )
+                    })
+                    .collect(),
+            ),
+            Err(e) => CheckOutcome::Failed(vec![e.to_string()]),
+        }
+    };
+    let licenses = async {
+        if config.allowed_licenses.is_empty() {
+            return CheckOutcome::Skipped("no [audit] allowed-licenses".into());
+        }
+        outcome(
+            ops_audit::license_violations(
+                &client,
+                &repos,
+                &cache,
+                &result.artifacts,
+                &config.allowed_licenses,
+            )
+            .await,
+        )
+    };
+    tokio::join!(audit, licenses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_has_one_bit_per_failed_category() {
+        let report = CheckReport {
+            outcomes: vec![
+                (CheckCategory::Manifest, CheckOutcome::Passed),
+                (
+                    CheckCategory::Audit,
+                    CheckOutcome::Failed(vec!["CVE".into()]),
+                ),
+                (
+                    CheckCategory::Licenses,
+                    CheckOutcome::Skipped("no policy".into()),
+                ),
+                (
+                    CheckCategory::Format,
+                    CheckOutcome::Failed(vec!["tabs".into()]),
+                ),
+                (CheckCategory::TypeCheck, CheckOutcome::Passed),
+            ],
+        };
+        assert_eq!(report.exit_code(), 32 | 2 | 8);
+    }
+
+    #[test]
+    fn manifest_lints() {
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "demo"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+okio = "com.squareup.okio:okio:3.9.0"
+
+[dev-dependencies]
+okio = "com.squareup.okio:okio:3.9.0"
+
+[plugins]
+serialization = { id = "org.jetbrains.kotlin.plugin.serialization", version = "2.2.0" }
+"#,
+        )
+        .unwrap();
+        let problems = manifest_problems(&manifest, None);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].contains("no Kargo.lock"));
+        assert!(problems[1].contains("`okio` is declared in both"));
+        assert!(problems[2].starts_with("plugins.serialization: set version = \"2.3.0\""));
+    }
+
+    #[test]
+    fn format_problems_name_the_file_and_line() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("src").join("main").join("kotlin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Main.kt"), "fun main() {}  \n").unwrap();
+        std::fs::write(dir.join("Util.java"), "class Util {}  \n").unwrap();
+
+        let problems = format_problems(tmp.path(), None);
+        let expected = Path::new("src").join("main").join("kotlin").join("Main.kt");
+        assert_eq!(
+            problems,
+            [format!("{}:1: trailing whitespace", expected.display())]
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! Operation: long-running JSON-RPC daemon for IDE and editor tooling.
+//!
+//! `kargo daemon` listens on a unix socket (`.kargo/daemon.sock` by default)
+//! and speaks newline-delimited JSON-RPC 2.0. The parsed manifest and
+//! lockfile, and the build context of each target and profile built (the
+//! resolved toolchain, JDK and classpath), are kept in memory and only
+//! reloaded when the mtime of `Kargo.toml` or `Kargo.lock` changes, so
+//! `metadata` queries answer without touching the resolver and builds skip
+//! the preflight checks.
+//!
+//! Every request runs on its own task. `resolve`, `build` and `test` take
+//! the project lock and run one at a time, so builds never race each other
+//! for the same output directories; `ping` and `metadata` answer while they
+//! run.
+//!
+//! Supported methods: `ping`, `metadata`, `resolve`, `build`, `test`,
+//! `shutdown`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+
+use crate::ops_build::{self, BuildOptions};
+use crate::BuildContext;
+
+/// Default socket location, relative to the project root.
+pub const DEFAULT_SOCKET: &str = ".kargo/daemon.sock";
+
+/// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Server-defined: the requested operation ran and failed.
+const OPERATION_FAILED: i64 = -32000;
+
+/// A response line queued for a connection's writer.
+struct Outgoing {
+    response: Value,
+    /// Signalled once the response has been written back to the client.
+    /// Only set for `shutdown`, so the daemon does not exit mid-write.
+    written: Option<oneshot::Sender<()>>,
+}
+
+/// A file parsed once and re-read only when its mtime changes.
+struct Cached<T> {
+    mtime: Option<SystemTime>,
+    value: T,
+}
+
+/// Which build a [`BuildContext`] was loaded for: target, profile, release.
+type ContextKey = (Option<String>, Option<String>, bool);
+
+/// A loaded [`BuildContext`] and the mtimes of `Kargo.toml` and `Kargo.lock`
+/// it was loaded from.
+struct WarmContext {
+    mtimes: (Option<SystemTime>, Option<SystemTime>),
+    ctx: BuildContext,
+}
+
+/// Project state kept warm between requests.
+struct WarmState {
+    project_dir: PathBuf,
+    manifest: Option<Cached<Manifest>>,
+    lockfile: Option<Cached<Lockfile>>,
+    /// Build contexts, with their resolved toolchain and classpath, by the
+    /// build they were loaded for.
+    contexts: HashMap<ContextKey, WarmContext>,
+}
+
+impl WarmState {
+    fn new(project_dir: &Path) -> Self {
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            manifest: None,
+            lockfile: None,
+            contexts: HashMap::new(),
+        }
+    }
+
+    fn manifest(&mut self) -> miette::Result<&Manifest> {
+        let path = self.project_dir.join("Kargo.toml");
+        let mtime = mtime_of(&path);
+        let stale = self.manifest.as_ref().map_or(true, |c| c.mtime != mtime);
+        if stale {
+            let value = Manifest::from_path(&path)?;
+            self.manifest = Some(Cached { mtime, value });
+        }
+        Ok(&self.manifest.as_ref().expect("manifest cached above").value)
+    }
+
+    fn lockfile(&mut self) -> &Lockfile {
+        let path = kargo_core::workspace::lockfile_path(&self.project_dir);
+        let mtime = mtime_of(&path);
+        let stale = self.lockfile.as_ref().map_or(true, |c| c.mtime != mtime);
+        if stale {
+            let value = Lockfile::for_project(&self.project_dir).unwrap_or_default();
+            self.lockfile = Some(Cached { mtime, value });
+        }
+        &self.lockfile.as_ref().expect("lockfile cached above").value
+    }
+
+    fn mtimes(&self) -> (Option<SystemTime>, Option<SystemTime>) {
+        (
+            mtime_of(&self.project_dir.join("Kargo.toml")),
+            mtime_of(&kargo_core::workspace::lockfile_path(&self.project_dir)),
+        )
+    }
+
+    /// A copy of the context loaded for `key`, unless the manifest or the
+    /// lockfile changed since. Source sets are discovered again, so new
+    /// source directories are picked up.
+    fn context(&self, key: &ContextKey) -> Option<BuildContext> {
+        let warm = self.contexts.get(key)?;
+        if warm.mtimes != self.mtimes() {
+            return None;
+        }
+        let mut ctx = warm.ctx.clone();
+        ctx.discovered =
+            kargo_compiler::source_set_discovery::discover(&ctx.project_dir, &ctx.manifest);
+        Some(ctx)
+    }
+}
+
+fn mtime_of(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// The daemon's project, shared by the tasks serving its requests.
+struct Daemon {
+    project_dir: PathBuf,
+    warm: Mutex<WarmState>,
+    /// Held for the whole of a `resolve`, `build` or `test`: they write the
+    /// same lockfile and build outputs, so they run one at a time.
+    busy: tokio::sync::Mutex<()>,
+    /// Tells the listener to stop, after a `shutdown` was answered.
+    stop: mpsc::Sender<()>,
+}
+
+impl Daemon {
+    fn new(project_dir: &Path, stop: mpsc::Sender<()>) -> Self {
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            warm: Mutex::new(WarmState::new(project_dir)),
+            busy: tokio::sync::Mutex::new(()),
+            stop,
+        }
+    }
+
+    fn warm(&self) -> std::sync::MutexGuard<'_, WarmState> {
+        self.warm.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The build context for `key`, kept warm across requests. Callers hold
+    /// `busy`, so contexts are never loaded twice at once.
+    async fn context(&self, key: ContextKey) -> miette::Result<BuildContext> {
+        if let Some(ctx) = self.warm().context(&key) {
+            return Ok(ctx);
+        }
+        let ctx = BuildContext::load(&self.project_dir, key.0.as_deref(), key.1.as_deref(), key.2)
+            .await?;
+        let mut warm = self.warm();
+        let mtimes = warm.mtimes();
+        warm.contexts.insert(
+            key,
+            WarmContext {
+                mtimes,
+                ctx: ctx.clone(),
+            },
+        );
+        Ok(ctx)
+    }
+}
+
+/// Run the daemon until a `shutdown` request arrives or the process is
+/// interrupted. `socket` defaults to [`DEFAULT_SOCKET`] under `project_dir`.
+pub async fn serve(project_dir: &Path, socket: Option<&Path>) -> miette::Result<()> {
+    if !project_dir.join("Kargo.toml").is_file() {
+        return Err(KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".into(),
+        }
+        .into());
+    }
+
+    let socket_path = socket
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| project_dir.join(DEFAULT_SOCKET));
+
+    listen(project_dir, &socket_path).await
+}
+
+#[cfg(unix)]
+async fn listen(project_dir: &Path, socket_path: &Path) -> miette::Result<()> {
+    use tokio::net::UnixListener;
+
+    if socket_path.exists() {
+        if std::os::unix::net::UnixStream::connect(socket_path).is_ok() {
+            return Err(KargoError::Generic {
+                message: format!("A daemon is already listening on {}", socket_path.display()),
+            }
+            .into());
+        }
+        // Left behind by a daemon that did not shut down cleanly.
+        std::fs::remove_file(socket_path).map_err(KargoError::Io)?;
+    }
+    if let Some(parent) = socket_path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+
+    let listener = UnixListener::bind(socket_path).map_err(KargoError::Io)?;
+    kargo_util::progress::status("Listening", &socket_path.display().to_string());
+
+    let (stop_tx, mut stop_rx) = mpsc::channel::<()>(1);
+    let daemon = Arc::new(Daemon::new(project_dir, stop_tx));
+
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_connection(stream, daemon.clone()));
+                    }
+                    Err(e) => break Err(KargoError::Io(e).into()),
+                }
+            }
+            _ = stop_rx.recv() => break Ok(()),
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+
+    let _ = std::fs::remove_file(socket_path);
+    kargo_util::progress::status("Stopped", "daemon");
+    result
+}
+
+#[cfg(not(unix))]
+async fn listen(_project_dir: &Path, _socket_path: &Path) -> miette::Result<()> {
+    Err(KargoError::Generic {
+        message: "kargo daemon currently requires unix domain sockets; \
+                  named pipe transport is not available on this platform yet."
+            .into(),
+    }
+    .into())
+}
+
+/// Read newline-delimited requests from one client and answer each on its
+/// own task, so a long build does not hold up a `ping`. Responses are
+/// written as they complete; clients match them to requests by `id`.
+/// Notifications (requests without an `id`) get no response.
+async fn serve_connection<S>(stream: S, daemon: Arc<Daemon>)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let (out, mut queued) = mpsc::unbounded_channel::<Outgoing>();
+    tokio::spawn(async move {
+        while let Some(Outgoing { response, written }) = queued.recv().await {
+            let mut line = response.to_string();
+            line.push('\n');
+            let ok =
+                writer.write_all(line.as_bytes()).await.is_ok() && writer.flush().await.is_ok();
+            if let Some(written) = written {
+                let _ = written.send(());
+            }
+            if !ok {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&line) {
+            Ok(request) => {
+                tokio::spawn(respond(daemon.clone(), request, out.clone()));
+            }
+            Err(e) => {
+                let response =
+                    error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {e}"));
+                let _ = out.send(Outgoing {
+                    response,
+                    written: None,
+                });
+            }
+        }
+    }
+}
+
+/// Execute `request` and queue its response on `out`.
+async fn respond(daemon: Arc<Daemon>, request: Value, out: mpsc::UnboundedSender<Outgoing>) {
+    let is_notification = request.get("id").is_none();
+    let (response, stop) = handle(&daemon, request).await;
+    let (written, wait) = oneshot::channel();
+    let queued = !is_notification
+        && out
+            .send(Outgoing {
+                response,
+                written: stop.then_some(written),
+            })
+            .is_ok();
+    if stop {
+        if queued {
+            let _ = tokio::time::timeout(Duration::from_secs(1), wait).await;
+        }
+        let _ = daemon.stop.send(()).await;
+    }
+}
+
+/// Execute one request. Returns the response and whether the daemon should stop.
+async fn handle(daemon: &Daemon, request: Value) -> (Value, bool) {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return (
+            error_response(id, INVALID_REQUEST, "Missing 'method'"),
+            false,
+        );
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "ping" => Ok(json!("pong")),
+        "metadata" => metadata(daemon),
+        "resolve" => resolve(daemon).await,
+        "build" => match BuildParams::from_value(&params) {
+            Ok(p) => build(daemon, &p).await,
+            Err(msg) => return (error_response(id, INVALID_PARAMS, &msg), false),
+        },
+        "test" => match TestParams::from_value(&params) {
+            Ok(p) => test(daemon, &p).await,
+            Err(msg) => return (error_response(id, INVALID_PARAMS, &msg), false),
+        },
+        "shutdown" => return (ok_response(id, Value::Null), true),
+        other => {
+            return (
+                error_response(id, METHOD_NOT_FOUND, &format!("Unknown method '{other}'")),
+                false,
+            )
+        }
+    };
+
+    let response = match result {
+        Ok(value) => ok_response(id, value),
+        Err(e) => error_response(id, OPERATION_FAILED, &e.to_string()),
+    };
+    (response, false)
+}
+
+fn metadata(daemon: &Daemon) -> miette::Result<Value> {
+    let mut warm = daemon.warm();
+    let manifest = warm.manifest()?.clone();
+    crate::ops_metadata::render(&daemon.project_dir, &manifest, warm.lockfile())
+}
+
+async fn resolve(daemon: &Daemon) -> miette::Result<Value> {
+    let _busy = daemon.busy.lock().await;
+    let start = Instant::now();
+    crate::ops_fetch::fetch(&daemon.project_dir, false).await?;
+    let packages = daemon.warm().lockfile().package.len();
+    Ok(json!({
+        "packages": packages,
+        "durationMs": start.elapsed().as_millis() as u64,
+    }))
+}
+
+struct BuildParams {
+    target: Option<String>,
+    profile: Option<String>,
+    release: bool,
+}
+
+impl BuildParams {
+    fn from_value(params: &Value) -> Result<Self, String> {
+        Ok(Self {
+            target: optional_string(params, "target")?,
+            profile: optional_string(params, "profile")?,
+            release: params
+                .get("release")
+                .map(|v| v.as_bool().ok_or("'release' must be a boolean"))
+                .transpose()?
+                .unwrap_or(false),
+        })
+    }
+}
+
+async fn build(daemon: &Daemon, params: &BuildParams) -> miette::Result<Value> {
+    let _busy = daemon.busy.lock().await;
+    let start = Instant::now();
+    let ctx = daemon
+        .context((
+            params.target.clone(),
+            params.profile.clone(),
+            params.release,
+        ))
+        .await?;
+    let result = ops_build::build_with_context(
+        ctx,
+        &BuildOptions {
+            quiet: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok(json!({
+        "success": result.success,
+        "target": result.target.kebab_name(),
+        "profile": result.profile_name,
+        "classesDir": result.classes_dir.display().to_string(),
+        "outputJar": result.output_jar.map(|p| p.display().to_string()),
+        "durationMs": start.elapsed().as_millis() as u64,
+    }))
+}
+
+struct TestParams {
+    target: Option<String>,
+    filter: Option<String>,
+}
+
+impl TestParams {
+    fn from_value(params: &Value) -> Result<Self, String> {
+        Ok(Self {
+            target: optional_string(params, "target")?,
+            filter: optional_string(params, "filter")?,
+        })
+    }
+}
+
+async fn test(daemon: &Daemon, params: &TestParams) -> miette::Result<Value> {
+    let _busy = daemon.busy.lock().await;
+    let start = Instant::now();
+    let ctx = daemon.context((params.target.clone(), None, false)).await?;
+    crate::ops_test::test_with_context(
+        ctx,
+        &crate::ops_test::TestOptions {
+            target: params.target.clone(),
+            filter: params.filter.clone(),
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(json!({
+        "success": true,
+        "durationMs": start.elapsed().as_millis() as u64,
+    }))
+}
+
+fn optional_string(params: &Value, key: &str) -> Result<Option<String>, String> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("'{key}' must be a string")),
+    }
+}
+
+fn ok_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ping_is_answered_while_a_build_runs() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("Kargo.toml"),
+            "[package]\nname = \"warm\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+        )
+        .unwrap();
+        let (stop, _stopped) = mpsc::channel(1);
+        let daemon = Arc::new(Daemon::new(tmp.path(), stop));
+        // Stands in for a long build: the next build waits for it.
+        let running = daemon.busy.lock().await;
+
+        let (client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve_connection(server, daemon.clone()));
+        let (reader, mut writer) = tokio::io::split(client);
+        writer
+            .write_all(
+                b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"build\"}\n\
+                  {\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}\n\
+                  {\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"metadata\"}\n",
+            )
+            .await
+            .unwrap();
+
+        let mut lines = BufReader::new(reader).lines();
+        let mut answered = Vec::new();
+        for _ in 0..2 {
+            let line = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+                .await
+                .expect("ping and metadata are answered during the build")
+                .unwrap()
+                .unwrap();
+            answered.push(serde_json::from_str::<Value>(&line).unwrap());
+        }
+        answered.sort_by_key(|r| r["id"].as_i64());
+        assert_eq!(answered[0]["id"], 2);
+        assert_eq!(answered[0]["result"], "pong");
+        assert_eq!(answered[1]["id"], 3);
+        assert_eq!(answered[1]["result"]["manifest"]["package"]["name"], "warm");
+        drop(running);
+    }
+}
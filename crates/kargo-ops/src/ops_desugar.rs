@@ -0,0 +1,331 @@
+//! Operation: core library desugaring for Android `min-sdk` below 26.
+//!
+//! D8 always desugars language features (lambdas, default and static
+//! interface methods, ...) down to its `--min-api`. Java library APIs newer
+//! than the device, such as `java.time` (API 26) or `java.util.stream` (API
+//! 24), need core library desugaring instead: D8 rewrites calls to them
+//! with `--desugared-lib`, and L8 dexes the backported implementations from
+//! `desugar_jdk_libs` into the APK.
+//!
+//! `[targets.android] core-library-desugaring` turns this on or off. When it
+//! is unset, desugaring is enabled if the classes being dexed reference one
+//! of those APIs above `min-sdk`.
+
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::Manifest;
+use kargo_maven::cache::LocalCache;
+use kargo_maven::repository::MavenRepository;
+use kargo_util::errors::KargoError;
+
+/// Maven coordinates of the desugared JDK library on Google Maven.
+pub const DESUGAR_GROUP: &str = "com.android.tools";
+pub const DESUGAR_JDK_LIBS: &str = "desugar_jdk_libs";
+pub const DESUGAR_JDK_LIBS_CONFIGURATION: &str = "desugar_jdk_libs_configuration";
+pub const DESUGAR_JDK_LIBS_VERSION: &str = "2.1.2";
+
+/// Desugaring configuration inside the configuration JAR.
+const CONFIGURATION_ENTRY: &str = "META-INF/desugar/d8/desugar.json";
+
+/// Packages core library desugaring backports, with the API level that
+/// added them.
+const DESUGARED_APIS: &[(&str, u32)] = &[
+    ("java/time/", 26),
+    ("java/util/stream/", 24),
+    ("java/util/function/", 24),
+    ("java/util/Optional", 24),
+];
+
+/// The inputs of D8 and L8 for core library desugaring.
+#[derive(Debug, Clone)]
+pub struct DesugarLibrary {
+    /// The `desugar.json` D8 and L8 read with `--desugared-lib`.
+    pub config: PathBuf,
+    /// `desugar_jdk_libs`, which L8 dexes.
+    pub jar: PathBuf,
+}
+
+/// Whether to desugar: `setting` when set, else whenever `detected` (from
+/// [`scan`]) is not empty.
+pub fn enabled(setting: Option<bool>, detected: &BTreeSet<String>) -> bool {
+    setting.unwrap_or(!detected.is_empty())
+}
+
+/// The desugared APIs newer than `min_sdk` referenced by the classes in
+/// `inputs` (class directories and JARs), as dotted names like `java.time`.
+pub fn scan(inputs: &[PathBuf], min_sdk: u32) -> BTreeSet<String> {
+    let wanted: Vec<&str> = DESUGARED_APIS
+        .iter()
+        .filter(|(_, api)| min_sdk < *api)
+        .map(|(prefix, _)| *prefix)
+        .collect();
+    let mut found = BTreeSet::new();
+    if wanted.is_empty() {
+        return found;
+    }
+    let mut check = |bytes: &[u8]| {
+        for name in class_references(bytes) {
+            for prefix in &wanted {
+                let descriptor = format!("L{prefix}");
+                if name.starts_with(prefix) || name.contains(&descriptor) {
+                    found.insert(prefix.trim_end_matches('/').replace('/', "."));
+                }
+            }
+        }
+    };
+    for input in inputs {
+        if input.is_dir() {
+            scan_dir(input, &mut check);
+        } else if let Ok(file) = std::fs::File::open(input) {
+            let Ok(mut archive) = zip::ZipArchive::new(file) else {
+                continue;
+            };
+            for i in 0..archive.len() {
+                let Ok(mut entry) = archive.by_index(i) else {
+                    continue;
+                };
+                if !entry.name().ends_with(".class") {
+                    continue;
+                }
+                let mut bytes = Vec::new();
+                if entry.read_to_end(&mut bytes).is_ok() {
+                    check(&bytes);
+                }
+            }
+        }
+    }
+    found
+}
+
+fn scan_dir(dir: &Path, check: &mut impl FnMut(&[u8])) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            scan_dir(&path, check);
+        } else if path.extension().is_some_and(|e| e == "class") {
+            if let Ok(bytes) = std::fs::read(&path) {
+                check(&bytes);
+            }
+        }
+    }
+}
+
+/// The UTF-8 constants of a class file: class names, descriptors and
+/// member names. Empty for anything that is not a class file.
+pub fn class_references(bytes: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    if bytes.len() < 10 || bytes[..4] != [0xCA, 0xFE, 0xBA, 0xBE] {
+        return out;
+    }
+    let u16_at = |pos: usize| -> Option<usize> {
+        Some(u16::from_be_bytes([*bytes.get(pos)?, *bytes.get(pos + 1)?]) as usize)
+    };
+    let Some(count) = u16_at(8) else {
+        return out;
+    };
+    let mut pos = 10;
+    let mut index = 1;
+    while index < count {
+        let Some(&tag) = bytes.get(pos) else {
+            break;
+        };
+        let size = match tag {
+            1 => {
+                let Some(len) = u16_at(pos + 1) else {
+                    break;
+                };
+                if let Some(s) = bytes.get(pos + 3..pos + 3 + len) {
+                    out.push(String::from_utf8_lossy(s).into_owned());
+                }
+                3 + len
+            }
+            7 | 8 | 16 | 19 | 20 => 3,
+            15 => 4,
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 5,
+            5 | 6 => {
+                // Longs and doubles take two constant pool slots.
+                index += 1;
+                9
+            }
+            _ => break,
+        };
+        pos += size;
+        index += 1;
+    }
+    out
+}
+
+/// Download `desugar_jdk_libs` and its configuration into the project
+/// cache, extracting `desugar.json` next to the configuration JAR.
+pub async fn ensure(cache: &LocalCache) -> miette::Result<DesugarLibrary> {
+    let google = MavenRepository::google();
+    let mut jars = Vec::new();
+    for artifact in [DESUGAR_JDK_LIBS, DESUGAR_JDK_LIBS_CONFIGURATION] {
+        let jar = kargo_compiler::plugins::ensure_maven_jar_from(
+            &google,
+            cache,
+            DESUGAR_GROUP,
+            artifact,
+            DESUGAR_JDK_LIBS_VERSION,
+        )
+        .await?
+        .ok_or_else(|| KargoError::Network {
+            message: format!(
+                "{DESUGAR_GROUP}:{artifact}:{DESUGAR_JDK_LIBS_VERSION} not found on Google Maven"
+            ),
+        })?;
+        jars.push(jar);
+    }
+    let config = cache
+        .artifact_dir(
+            DESUGAR_GROUP,
+            DESUGAR_JDK_LIBS_CONFIGURATION,
+            DESUGAR_JDK_LIBS_VERSION,
+        )
+        .join("desugar.json");
+    if !config.is_file() {
+        let zip_err = |e: zip::result::ZipError| KargoError::Generic {
+            message: format!("{}: {e}", jars[1].display()),
+        };
+        let file = std::fs::File::open(&jars[1]).map_err(KargoError::Io)?;
+        let mut archive = zip::ZipArchive::new(file).map_err(zip_err)?;
+        let mut entry = archive.by_name(CONFIGURATION_ENTRY).map_err(zip_err)?;
+        let mut json = Vec::new();
+        entry.read_to_end(&mut json).map_err(KargoError::Io)?;
+        std::fs::write(&config, json).map_err(KargoError::Io)?;
+    }
+    Ok(DesugarLibrary {
+        config,
+        jar: jars.swap_remove(0),
+    })
+}
+
+/// Arguments for R8's `com.android.tools.r8.L8` entry point, dexing the
+/// desugared JDK library into `output_dir`.
+pub fn l8_args(
+    library: &DesugarLibrary,
+    min_sdk: u32,
+    android_jar: &Path,
+    output_dir: &Path,
+) -> Vec<String> {
+    vec![
+        "--min-api".to_string(),
+        min_sdk.to_string(),
+        "--desugared-lib".to_string(),
+        library.config.to_string_lossy().to_string(),
+        "--lib".to_string(),
+        android_jar.to_string_lossy().to_string(),
+        "--output".to_string(),
+        output_dir.to_string_lossy().to_string(),
+        library.jar.to_string_lossy().to_string(),
+    ]
+}
+
+/// Coordinates of the desugaring artifacts `manifest` may need, so that
+/// `kargo fetch` does not prune them: none unless the `android` target's
+/// `min-sdk` is below 26 and desugaring is not turned off.
+pub fn tool_coordinates(manifest: &Manifest) -> Vec<(String, String, String)> {
+    let Some(android) = manifest.targets.get("android") else {
+        return Vec::new();
+    };
+    let low_min_sdk = android.min_sdk.is_some_and(|min| min < 26);
+    if android.core_library_desugaring == Some(false)
+        || (!low_min_sdk && android.core_library_desugaring.is_none())
+    {
+        return Vec::new();
+    }
+    [DESUGAR_JDK_LIBS, DESUGAR_JDK_LIBS_CONFIGURATION]
+        .into_iter()
+        .map(|artifact| {
+            (
+                DESUGAR_GROUP.to_string(),
+                artifact.to_string(),
+                DESUGAR_JDK_LIBS_VERSION.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A class file whose constant pool names `class` and `descriptor`.
+    fn class_file(class: &str, descriptor: &str) -> Vec<u8> {
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 6];
+        for (i, s) in [class, descriptor].iter().enumerate() {
+            bytes.push(1);
+            bytes.extend((s.len() as u16).to_be_bytes());
+            bytes.extend(s.as_bytes());
+            if i == 0 {
+                // A long between the strings occupies two slots.
+                bytes.push(5);
+                bytes.extend([0; 8]);
+            }
+        }
+        bytes.extend([7, 0, 1]);
+        bytes
+    }
+
+    #[test]
+    fn reads_the_constant_pool() {
+        assert_eq!(
+            class_references(&class_file("java/time/Instant", "()V")),
+            ["java/time/Instant", "()V"]
+        );
+        assert!(class_references(b"not a class").is_empty());
+    }
+
+    #[test]
+    fn detects_apis_newer_than_min_sdk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes/com/example");
+        std::fs::create_dir_all(&classes).unwrap();
+        std::fs::write(
+            classes.join("Clock.class"),
+            class_file(
+                "com/example/Clock",
+                "(Ljava/time/Instant;)Ljava/util/stream/Stream;",
+            ),
+        )
+        .unwrap();
+        let inputs = [tmp.path().join("classes")];
+
+        assert_eq!(
+            scan(&inputs, 21).into_iter().collect::<Vec<_>>(),
+            ["java.time", "java.util.stream"]
+        );
+        assert_eq!(
+            scan(&inputs, 24).into_iter().collect::<Vec<_>>(),
+            ["java.time"]
+        );
+        assert!(scan(&inputs, 26).is_empty());
+
+        let detected = scan(&inputs, 24);
+        assert!(enabled(None, &detected));
+        assert!(!enabled(Some(false), &detected));
+        assert!(!enabled(None, &BTreeSet::new()));
+        assert!(enabled(Some(true), &BTreeSet::new()));
+    }
+
+    #[test]
+    fn desugaring_artifacts_are_kept_for_low_min_sdk() {
+        let manifest = |extra: &str| {
+            Manifest::parse_toml(&format!(
+                "[package]\nname = \"app\"\nversion = \"1.0.0\"\nkotlin = \"2.3.0\"\n\n\
+                 [targets.android]\n{extra}"
+            ))
+            .unwrap()
+        };
+        assert_eq!(tool_coordinates(&manifest("min-sdk = 21\n")).len(), 2);
+        assert!(tool_coordinates(&manifest("min-sdk = 26\n")).is_empty());
+        assert!(
+            tool_coordinates(&manifest("min-sdk = 21\ncore-library-desugaring = false\n"))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,761 @@
+//! Operation: manage Android emulators and iOS simulators for device tests.
+//!
+//! `kargo device list` shows the Android virtual devices (`emulator
+//! -list-avds`), devices attached to `adb`, and the iOS simulators known to
+//! `xcrun simctl`. `kargo device start` boots one, headless by default,
+//! creating the AVD with `avdmanager` from `--image` when it does not exist,
+//! and waits until it is ready: `sys.boot_completed` for an emulator,
+//! `simctl bootstatus` for a simulator. `kargo device stop` shuts it down
+//! again.
+//!
+//! Devices Kargo starts are recorded in `~/.kargo/devices.json` so that
+//! `kargo device stop --all` tears down exactly those. Given a command
+//! (`kargo device start <name> -- kargo test --device`), `start` runs it
+//! with `ANDROID_SERIAL` or `SIMULATOR_UDID` set and always stops the device
+//! afterwards.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use kargo_toolchain::sdk::{self, AndroidSdkInfo};
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+use kargo_util::progress::status;
+
+use crate::ops_device_test::{adb_path, connected_devices};
+
+/// Ports `emulator -port` accepts: even numbers, console on the port and
+/// adb on the next one.
+const EMULATOR_PORTS: std::ops::RangeInclusive<u16> = 5554..=5682;
+
+/// How often readiness is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What kind of device an entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeviceKind {
+    /// An Android virtual device, running or not.
+    Emulator,
+    /// A physical Android device attached to `adb`.
+    Android,
+    IosSimulator,
+}
+
+impl DeviceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DeviceKind::Emulator => "emulator",
+            DeviceKind::Android => "android",
+            DeviceKind::IosSimulator => "simulator",
+        }
+    }
+}
+
+/// A device as shown by `kargo device list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub kind: DeviceKind,
+    /// AVD or simulator name; the serial for physical devices.
+    pub name: String,
+    /// adb serial of a running emulator or device, or simulator UDID.
+    pub id: Option<String>,
+    /// `running`/`stopped` for emulators; the adb state for devices; the
+    /// simctl state (`Booted`, `Shutdown`, ...) for simulators.
+    pub state: String,
+    /// Simulator runtime, e.g. `iOS 17.2`.
+    pub runtime: Option<String>,
+}
+
+/// A device booted by `kargo device start`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartedDevice {
+    pub kind: DeviceKind,
+    pub name: String,
+    /// adb serial or simulator UDID.
+    pub id: String,
+}
+
+/// Options for [`start`].
+#[derive(Debug, Clone)]
+pub struct StartOptions {
+    /// System image (`system-images;android-34;google_apis;x86_64`) to
+    /// create the AVD from if it does not exist.
+    pub image: Option<String>,
+    /// Show the emulator window instead of running headless.
+    pub window: bool,
+    /// Boot the emulator with wiped user data.
+    pub wipe: bool,
+    /// How long to wait for the device to become ready.
+    pub timeout: Duration,
+}
+
+impl Default for StartOptions {
+    fn default() -> Self {
+        Self {
+            image: None,
+            window: false,
+            wipe: false,
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Every device Kargo can see. Android entries need the SDK (found through
+/// `local.properties` in `cwd`, `ANDROID_HOME`, ...), simulators need
+/// Xcode; whichever is missing is skipped.
+pub fn list(cwd: &Path) -> miette::Result<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    if let Some(sdk) = android_sdk(cwd) {
+        let adb = adb_path(&sdk);
+        let attached = if adb.is_file() {
+            connected_devices(&adb)?
+        } else {
+            Vec::new()
+        };
+        let mut running: Vec<(String, String)> = Vec::new();
+        for device in &attached {
+            if device.serial.starts_with("emulator-") {
+                if let Some(name) = emulator_avd_name(&adb, &device.serial) {
+                    running.push((name, device.serial.clone()));
+                }
+            } else {
+                devices.push(DeviceInfo {
+                    kind: DeviceKind::Android,
+                    name: device.serial.clone(),
+                    id: Some(device.serial.clone()),
+                    state: device.state.clone(),
+                    runtime: None,
+                });
+            }
+        }
+        for avd in list_avds(&sdk)? {
+            let serial = running
+                .iter()
+                .find(|(name, _)| *name == avd)
+                .map(|(_, s)| s.clone());
+            devices.push(DeviceInfo {
+                kind: DeviceKind::Emulator,
+                state: if serial.is_some() {
+                    "running"
+                } else {
+                    "stopped"
+                }
+                .into(),
+                name: avd,
+                id: serial,
+                runtime: None,
+            });
+        }
+    }
+    if has_simctl() {
+        devices.extend(list_simulators()?);
+    }
+    Ok(devices)
+}
+
+/// Boot the AVD or simulator `name` (a simulator may also be given by
+/// UDID) and wait until it is ready.
+pub fn start(cwd: &Path, name: &str, opts: &StartOptions) -> miette::Result<StartedDevice> {
+    let sdk = android_sdk(cwd);
+    if let Some(sdk) = &sdk {
+        let avds = list_avds(sdk)?;
+        if avds.iter().any(|a| a == name) || opts.image.is_some() {
+            if !avds.iter().any(|a| a == name) {
+                create_avd(sdk, name, opts.image.as_deref().unwrap_or_default())?;
+            }
+            return start_emulator(sdk, name, opts);
+        }
+    }
+    if has_simctl() {
+        if let Some(sim) = list_simulators()?
+            .into_iter()
+            .find(|s| s.name == name || s.id.as_deref() == Some(name))
+        {
+            return start_simulator(&sim, opts.timeout);
+        }
+    }
+    Err(KargoError::Generic {
+        message: format!(
+            "No AVD or simulator named '{name}'. See `kargo device list`, or create an AVD \
+             with --image <system-image>{}",
+            if sdk.is_none() {
+                " (the Android SDK was not found)"
+            } else {
+                ""
+            }
+        ),
+    }
+    .into())
+}
+
+/// Stop the device `name` (AVD or simulator name, serial or UDID), or with
+/// `all` every device `kargo device start` booted. Returns what was stopped.
+pub fn stop(cwd: &Path, name: Option<&str>, all: bool) -> miette::Result<Vec<StartedDevice>> {
+    let started = load_started();
+    let targets: Vec<StartedDevice> = if all {
+        started
+    } else {
+        let name = name.unwrap_or_default();
+        match started.into_iter().find(|d| d.name == name || d.id == name) {
+            Some(device) => vec![device],
+            None => vec![find_running(cwd, name)?],
+        }
+    };
+    for device in &targets {
+        stop_device(cwd, device)?;
+    }
+    Ok(targets)
+}
+
+/// Start `name`, run `command` with the device's serial or UDID in its
+/// environment, and stop the device again however the command ends. A
+/// device that was already running is left running.
+pub fn run_with_device(
+    cwd: &Path,
+    name: &str,
+    opts: &StartOptions,
+    command: &[String],
+) -> miette::Result<()> {
+    let device = start(cwd, name, opts)?;
+    let booted = load_started().contains(&device);
+    let var = match device.kind {
+        DeviceKind::IosSimulator => "SIMULATOR_UDID",
+        _ => "ANDROID_SERIAL",
+    };
+    let (program, args) = command.split_first().ok_or_else(|| KargoError::Generic {
+        message: "No command given".into(),
+    })?;
+    let result = CommandBuilder::new(program)
+        .args(args.iter().cloned())
+        .env(var, &device.id)
+        .status();
+    if booted {
+        match stop_device(cwd, &device) {
+            Ok(()) => status_line("Stopped", &device),
+            Err(e) => kargo_util::progress::status_warn(
+                "Warning",
+                &format!("could not stop {}: {e}", device.name),
+            ),
+        }
+    }
+    let status = result.map_err(|e| KargoError::Generic {
+        message: format!("Failed to run {program}: {e}"),
+    })?;
+    if !status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "`{}` failed (exit code {})",
+                command.join(" "),
+                status.code().unwrap_or(1)
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn start_emulator(
+    sdk: &AndroidSdkInfo,
+    name: &str,
+    opts: &StartOptions,
+) -> miette::Result<StartedDevice> {
+    let adb = adb_path(sdk);
+    let emulator = sdk.home.join("emulator").join(exe("emulator"));
+    if !emulator.is_file() {
+        return Err(KargoError::Toolchain {
+            message: format!(
+                "{} not found. Install it with: sdkmanager emulator",
+                emulator.display()
+            ),
+        }
+        .into());
+    }
+    let attached = connected_devices(&adb)?;
+    for device in attached
+        .iter()
+        .filter(|d| d.serial.starts_with("emulator-"))
+    {
+        if emulator_avd_name(&adb, &device.serial).as_deref() == Some(name) {
+            let started = StartedDevice {
+                kind: DeviceKind::Emulator,
+                name: name.to_string(),
+                id: device.serial.clone(),
+            };
+            status_line("Running", &started);
+            return Ok(started);
+        }
+    }
+    let used: Vec<&str> = attached.iter().map(|d| d.serial.as_str()).collect();
+    let port = free_emulator_port(&used).ok_or_else(|| KargoError::Generic {
+        message: "No free emulator port; stop an emulator first".into(),
+    })?;
+    let serial = format!("emulator-{port}");
+
+    let log_path = devices_dir().join(format!("{name}.log"));
+    std::fs::create_dir_all(devices_dir()).map_err(KargoError::Io)?;
+    let log = std::fs::File::create(&log_path).map_err(KargoError::Io)?;
+    let mut cmd = Command::new(&emulator);
+    cmd.args([
+        "-avd",
+        name,
+        "-port",
+        &port.to_string(),
+        "-no-snapshot-save",
+    ])
+    .stdin(Stdio::null())
+    .stdout(log.try_clone().map_err(KargoError::Io)?)
+    .stderr(log);
+    if !opts.window {
+        cmd.args(["-no-window", "-no-audio", "-no-boot-anim"]);
+    }
+    if opts.wipe {
+        cmd.arg("-wipe-data");
+    }
+    let mut child = cmd.spawn().map_err(|e| KargoError::Generic {
+        message: format!("Failed to start the emulator: {e}"),
+    })?;
+
+    let device = StartedDevice {
+        kind: DeviceKind::Emulator,
+        name: name.to_string(),
+        id: serial.clone(),
+    };
+    record_started(&device)?;
+    let spinner = kargo_util::progress::spinner(&format!("Booting {name} ({serial})"));
+    let deadline = Instant::now() + opts.timeout;
+    let ready = loop {
+        if let Ok(Some(exit)) = child.try_wait() {
+            break Err(format!("the emulator exited ({exit})"));
+        }
+        let booted = CommandBuilder::new(path_str(&adb))
+            .args(["-s", &serial, "shell", "getprop", "sys.boot_completed"])
+            .exec()
+            .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "1");
+        if booted {
+            break Ok(());
+        }
+        if Instant::now() >= deadline {
+            break Err(format!(
+                "it did not finish booting within {}s",
+                opts.timeout.as_secs()
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    spinner.finish_and_clear();
+
+    if let Err(reason) = ready {
+        let _ = child.kill();
+        let _ = child.wait();
+        forget_started(&device);
+        return Err(KargoError::Generic {
+            message: format!(
+                "Emulator {name} failed to start: {reason}. Log: {}",
+                log_path.display()
+            ),
+        }
+        .into());
+    }
+    // Dismiss the lock screen so UI tests can interact with the device.
+    let _ = CommandBuilder::new(path_str(&adb))
+        .args(["-s", &serial, "shell", "input", "keyevent", "82"])
+        .exec();
+    status_line("Booted", &device);
+    Ok(device)
+}
+
+fn start_simulator(sim: &DeviceInfo, timeout: Duration) -> miette::Result<StartedDevice> {
+    let udid = sim.id.clone().unwrap_or_default();
+    let device = StartedDevice {
+        kind: DeviceKind::IosSimulator,
+        name: sim.name.clone(),
+        id: udid.clone(),
+    };
+    if sim.state == "Booted" {
+        status_line("Running", &device);
+        return Ok(device);
+    }
+    record_started(&device)?;
+    // `bootstatus -b` boots the simulator and returns once it is usable.
+    let spinner = kargo_util::progress::spinner(&format!("Booting {} ({udid})", sim.name));
+    let mut child = Command::new("xcrun")
+        .args(["simctl", "bootstatus", &udid, "-b"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run xcrun simctl: {e}"),
+        })?;
+    let deadline = Instant::now() + timeout;
+    let ready = loop {
+        match child.try_wait() {
+            Ok(Some(exit)) if exit.success() => break Ok(()),
+            Ok(Some(exit)) => break Err(format!("simctl bootstatus failed ({exit})")),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                break Err(format!(
+                    "it did not finish booting within {}s",
+                    timeout.as_secs()
+                ));
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    spinner.finish_and_clear();
+    if let Err(reason) = ready {
+        let _ = simctl(&["shutdown", &udid]);
+        forget_started(&device);
+        return Err(KargoError::Generic {
+            message: format!("Simulator {} failed to start: {reason}", sim.name),
+        }
+        .into());
+    }
+    status_line("Booted", &device);
+    Ok(device)
+}
+
+fn stop_device(cwd: &Path, device: &StartedDevice) -> miette::Result<()> {
+    match device.kind {
+        DeviceKind::IosSimulator => {
+            let out = simctl(&["shutdown", &device.id])?;
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            if !out.status.success() && !stderr.contains("current state: Shutdown") {
+                return Err(KargoError::Generic {
+                    message: format!("simctl shutdown failed: {}", stderr.trim()),
+                }
+                .into());
+            }
+        }
+        DeviceKind::Emulator | DeviceKind::Android => {
+            let sdk = android_sdk(cwd).ok_or_else(|| KargoError::Toolchain {
+                message: "Android SDK not found".into(),
+            })?;
+            let adb = adb_path(&sdk);
+            let attached = connected_devices(&adb)?;
+            if attached.iter().any(|d| d.serial == device.id) {
+                let _ = CommandBuilder::new(path_str(&adb))
+                    .args(["-s", &device.id, "emu", "kill"])
+                    .exec();
+                let deadline = Instant::now() + Duration::from_secs(30);
+                while Instant::now() < deadline
+                    && connected_devices(&adb)?
+                        .iter()
+                        .any(|d| d.serial == device.id)
+                {
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+            }
+        }
+    }
+    forget_started(device);
+    Ok(())
+}
+
+/// A running emulator or booted simulator not started by Kargo.
+fn find_running(cwd: &Path, name: &str) -> miette::Result<StartedDevice> {
+    let found = list(cwd)?.into_iter().find(|d| {
+        (d.name == name || d.id.as_deref() == Some(name))
+            && matches!(d.state.as_str(), "running" | "Booted")
+    });
+    match found {
+        Some(DeviceInfo {
+            kind,
+            name,
+            id: Some(id),
+            ..
+        }) => Ok(StartedDevice { kind, name, id }),
+        _ => Err(KargoError::Generic {
+            message: format!("No running emulator or simulator named '{name}'"),
+        }
+        .into()),
+    }
+}
+
+fn create_avd(sdk: &AndroidSdkInfo, name: &str, image: &str) -> miette::Result<()> {
+    let image_dir = image
+        .split(';')
+        .fold(sdk.home.clone(), |dir, part| dir.join(part));
+    let tools = sdk.home.join("cmdline-tools").join("latest").join("bin");
+    if !image_dir.is_dir() {
+        status("Installing", image);
+        let out = CommandBuilder::new(path_str(&tools.join(script("sdkmanager"))))
+            .arg(image)
+            .arg("emulator")
+            .env("ANDROID_HOME", path_str(&sdk.home))
+            .exec()
+            .map_err(|e| KargoError::Toolchain {
+                message: format!("Failed to run sdkmanager: {e}"),
+            })?;
+        if !out.status.success() {
+            return Err(KargoError::Toolchain {
+                message: format!("sdkmanager failed to install {image}"),
+            }
+            .into());
+        }
+    }
+    status("Creating", &format!("AVD {name} from {image}"));
+    let mut child = Command::new(tools.join(script("avdmanager")))
+        .args([
+            "create",
+            "avd",
+            "--name",
+            name,
+            "--package",
+            image,
+            "--force",
+        ])
+        .env("ANDROID_HOME", &sdk.home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| KargoError::Toolchain {
+            message: format!("Failed to run avdmanager: {e}"),
+        })?;
+    // Answer "no" to "Do you wish to create a custom hardware profile?".
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = std::io::Write::write_all(&mut stdin, b"no\n");
+    }
+    let out = child.wait_with_output().map_err(KargoError::Io)?;
+    if !out.status.success() {
+        return Err(KargoError::Toolchain {
+            message: format!(
+                "avdmanager failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn list_avds(sdk: &AndroidSdkInfo) -> miette::Result<Vec<String>> {
+    let emulator = sdk.home.join("emulator").join(exe("emulator"));
+    if !emulator.is_file() {
+        return Ok(Vec::new());
+    }
+    let out = CommandBuilder::new(path_str(&emulator))
+        .arg("-list-avds")
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run the emulator: {e}"),
+        })?;
+    Ok(parse_avd_list(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// AVD names from `emulator -list-avds`, skipping its log lines.
+pub fn parse_avd_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("INFO") && !l.contains(' '))
+        .map(String::from)
+        .collect()
+}
+
+/// The AVD a running emulator was started from.
+fn emulator_avd_name(adb: &Path, serial: &str) -> Option<String> {
+    let out = CommandBuilder::new(path_str(adb))
+        .args(["-s", serial, "emu", "avd", "name"])
+        .exec()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && *l != "OK")
+        .map(String::from)
+}
+
+/// The first emulator port whose serial is not in `used`.
+pub fn free_emulator_port(used: &[&str]) -> Option<u16> {
+    EMULATOR_PORTS
+        .step_by(2)
+        .find(|port| !used.contains(&format!("emulator-{port}").as_str()))
+}
+
+fn list_simulators() -> miette::Result<Vec<DeviceInfo>> {
+    let out = simctl(&["list", "devices", "available", "-j"])?;
+    if !out.status.success() {
+        return Ok(Vec::new());
+    }
+    parse_simctl_devices(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// Simulators from `xcrun simctl list devices -j`.
+pub fn parse_simctl_devices(json: &str) -> miette::Result<Vec<DeviceInfo>> {
+    #[derive(Deserialize)]
+    struct List {
+        devices: std::collections::BTreeMap<String, Vec<Sim>>,
+    }
+    #[derive(Deserialize)]
+    struct Sim {
+        name: String,
+        udid: String,
+        state: String,
+        #[serde(rename = "isAvailable", default = "available")]
+        is_available: bool,
+    }
+    fn available() -> bool {
+        true
+    }
+
+    let list: List = serde_json::from_str(json).map_err(|e| KargoError::Generic {
+        message: format!("Unexpected `simctl list` output: {e}"),
+    })?;
+    Ok(list
+        .devices
+        .into_iter()
+        .flat_map(|(runtime, sims)| {
+            // com.apple.CoreSimulator.SimRuntime.iOS-17-2 -> iOS 17.2
+            let runtime = runtime
+                .rsplit('.')
+                .next()
+                .unwrap_or(&runtime)
+                .replacen('-', " ", 1)
+                .replace('-', ".");
+            sims.into_iter()
+                .filter(|s| s.is_available)
+                .map(move |s| DeviceInfo {
+                    kind: DeviceKind::IosSimulator,
+                    name: s.name,
+                    id: Some(s.udid),
+                    state: s.state,
+                    runtime: Some(runtime.clone()),
+                })
+        })
+        .collect())
+}
+
+fn simctl(args: &[&str]) -> miette::Result<std::process::Output> {
+    Ok(CommandBuilder::new("xcrun")
+        .arg("simctl")
+        .args(args.iter().copied())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run xcrun simctl: {e}"),
+        })?)
+}
+
+fn has_simctl() -> bool {
+    cfg!(target_os = "macos") && sdk::discover_xcode().is_some()
+}
+
+fn android_sdk(cwd: &Path) -> Option<AndroidSdkInfo> {
+    let local = kargo_util::fs::find_ancestor_with(cwd, "Kargo.toml")
+        .and_then(|root| kargo_core::properties::local_sdk_dir(&root));
+    sdk::discover_android_sdk_from(local.as_deref())
+}
+
+fn devices_dir() -> PathBuf {
+    kargo_util::dirs_path().join("devices")
+}
+
+fn started_path() -> PathBuf {
+    kargo_util::dirs_path().join("devices.json")
+}
+
+fn load_started() -> Vec<StartedDevice> {
+    std::fs::read_to_string(started_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_started(devices: &[StartedDevice]) -> miette::Result<()> {
+    let path = started_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    let json = serde_json::to_string_pretty(devices).map_err(|e| KargoError::Generic {
+        message: e.to_string(),
+    })?;
+    std::fs::write(path, json).map_err(KargoError::Io)?;
+    Ok(())
+}
+
+fn record_started(device: &StartedDevice) -> miette::Result<()> {
+    let mut devices = load_started();
+    devices.retain(|d| d.id != device.id);
+    devices.push(device.clone());
+    save_started(&devices)
+}
+
+fn forget_started(device: &StartedDevice) {
+    let mut devices = load_started();
+    devices.retain(|d| d.id != device.id);
+    let _ = save_started(&devices);
+}
+
+fn status_line(action: &str, device: &StartedDevice) {
+    status(
+        action,
+        &format!("{} {} ({})", device.kind.label(), device.name, device.id),
+    );
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn exe(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    }
+}
+
+fn script(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.bat")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_simctl_devices() {
+        let json = r#"{
+  "devices" : {
+    "com.apple.CoreSimulator.SimRuntime.iOS-17-2" : [
+      { "name" : "iPhone 15", "udid" : "A1B2", "state" : "Booted", "isAvailable" : true },
+      { "name" : "iPhone SE", "udid" : "C3D4", "state" : "Shutdown", "isAvailable" : false }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.watchOS-10-2" : []
+  }
+}"#;
+        let devices = parse_simctl_devices(json).unwrap();
+        assert_eq!(
+            devices,
+            [DeviceInfo {
+                kind: DeviceKind::IosSimulator,
+                name: "iPhone 15".into(),
+                id: Some("A1B2".into()),
+                state: "Booted".into(),
+                runtime: Some("iOS 17.2".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_avd_list_and_picks_ports() {
+        assert_eq!(
+            parse_avd_list("INFO    | Storing crashdata in: /tmp\nPixel_7_API_34\nci\n"),
+            ["Pixel_7_API_34", "ci"]
+        );
+        assert_eq!(free_emulator_port(&[]), Some(5554));
+        assert_eq!(
+            free_emulator_port(&["emulator-5554", "R58M1234", "emulator-5556"]),
+            Some(5558)
+        );
+    }
+}
@@ -0,0 +1,1555 @@
+//! Operation: Android instrumentation tests on a device (`kargo test --device`).
+//!
+//! Compiles `src/androidTest/kotlin` (`src/androidInstrumentedTest/kotlin`
+//! in the multiplatform layout) against the android target's classes and
+//! test dependencies, then packages a self-instrumenting test APK: the
+//! tests, the code under test, its runtime dependencies and the androidx.test
+//! runner are dexed with `d8`, linked by `aapt2` with a generated manifest
+//! merged with the app's (see [`crate::ops_android_merge`]) together with
+//! the merged resources and their `R` classes, aligned and signed with the
+//! debug key. Java APIs newer than `min-sdk` are handled with core library
+//! desugaring (see [`crate::ops_desugar`]), and the merged baseline profile
+//! is compiled into it (see [`crate::ops_baseline_profile`]). The APK is
+//! installed
+//! with `adb` and run with `am instrument`, by default through Android Test
+//! Orchestrator so that each test gets its own process.
+//!
+//! Results are parsed from the instrumentation output as it streams in and
+//! written as JUnit XML next to those of the JVM tests, under
+//! `build/android/<profile>/test-results/`. Logcat is captured for the whole
+//! run; for each failed test the lines it logged are printed with the stack
+//! trace.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use kargo_compiler::dispatch::CompilerDispatch;
+use kargo_compiler::env::BuildEnv;
+use kargo_compiler::fingerprint;
+use kargo_compiler::jar::JarManifest;
+use kargo_compiler::source_set_discovery::collect_kotlin_files;
+use kargo_compiler::unit::CompilationUnit;
+use kargo_core::manifest::{AndroidTestConfig, Manifest};
+use kargo_core::source_set::SourceSet;
+use kargo_core::target::KotlinTarget;
+use kargo_maven::cache::LocalCache;
+use kargo_maven::repository::MavenRepository;
+use kargo_toolchain::sdk::AndroidSdkInfo;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+use kargo_util::progress::status;
+use kargo_util::xml;
+
+use crate::ops_android_merge::{self, MergeSource};
+use crate::ops_baseline_profile;
+use crate::ops_build::{self, BuildOptions};
+use crate::ops_desugar;
+use crate::ops_test::{self, TestOptions};
+
+/// Source sets holding instrumentation tests, in order of preference.
+pub const DEVICE_TEST_SOURCE_SETS: &[&str] = &["androidInstrumentedTest", "androidTest"];
+
+/// Libraries packaged into every test APK so the default runner works:
+/// `(group, artifact, version, repository)`.
+const RUNNER_LIBRARIES: &[(&str, &str, &str, Repo)] = &[
+    ("androidx.test", "runner", "1.6.2", Repo::Google),
+    ("androidx.test", "monitor", "1.7.2", Repo::Google),
+    ("androidx.test.services", "storage", "1.5.0", Repo::Google),
+    ("androidx.tracing", "tracing", "1.1.0", Repo::Google),
+    ("junit", "junit", "4.13.2", Repo::Central),
+    ("org.hamcrest", "hamcrest-core", "1.3", Repo::Central),
+];
+
+/// Android Test Orchestrator and the shell service it runs through.
+const ORCHESTRATOR_APKS: &[(&str, &str, &str)] = &[
+    ("androidx.test", "orchestrator", "1.5.1"),
+    ("androidx.test.services", "test-services", "1.5.0"),
+];
+
+const ORCHESTRATOR_RUNNER: &str =
+    "androidx.test.orchestrator/androidx.test.orchestrator.AndroidTestOrchestrator";
+
+/// `minSdkVersion` of the test APK when `[targets.android] min-sdk` is unset.
+const DEFAULT_MIN_SDK: u32 = 21;
+
+/// Lockfile scopes whose AARs go into the test APK.
+const DEVICE_TEST_SCOPES: &[&str] = &["compile", "runtime", "test"];
+
+#[derive(Debug, Clone, Copy)]
+enum Repo {
+    Google,
+    Central,
+}
+
+/// A device or emulator known to `adb`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdbDevice {
+    pub serial: String,
+    /// `device` when ready; otherwise e.g. `offline` or `unauthorized`.
+    pub state: String,
+}
+
+/// Outcome of one instrumented test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    /// `@Ignore`d, or skipped by a failed assumption.
+    Skipped,
+}
+
+/// One test reported by `am instrument -r`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub class: String,
+    pub name: String,
+    pub outcome: Outcome,
+    /// Stack trace of a failure.
+    pub stack: Option<String>,
+    /// Seconds between the start and end status lines.
+    pub time: f64,
+    /// Output captured while the test ran, from a JUnit XML report.
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+}
+
+/// What a line of instrumentation output completed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Started { class: String, name: String },
+    Finished(TestCase),
+}
+
+/// Incremental parser for the raw (`-r`) output of `am instrument`.
+///
+/// Status blocks are `INSTRUMENTATION_STATUS: key=value` lines, where a
+/// value may continue on the following lines, closed by an
+/// `INSTRUMENTATION_STATUS_CODE`. The run ends with `INSTRUMENTATION_RESULT`
+/// lines and an `INSTRUMENTATION_CODE`, or with `INSTRUMENTATION_FAILED`
+/// when the process could not start.
+#[derive(Debug, Default)]
+pub struct InstrumentationParser {
+    status: BTreeMap<String, String>,
+    result: BTreeMap<String, String>,
+    /// Map and key a continuation line is appended to.
+    last: Option<(bool, String)>,
+    running: Option<(String, String)>,
+    failure: Option<String>,
+    pub cases: Vec<TestCase>,
+}
+
+impl InstrumentationParser {
+    /// Feed one line of output.
+    pub fn line(&mut self, line: &str) -> Option<Event> {
+        if let Some(rest) = line.strip_prefix("INSTRUMENTATION_STATUS: ") {
+            self.set(false, rest);
+        } else if let Some(code) = line.strip_prefix("INSTRUMENTATION_STATUS_CODE: ") {
+            self.last = None;
+            let status = std::mem::take(&mut self.status);
+            return self.status_block(status, code.trim().parse().unwrap_or(0));
+        } else if let Some(rest) = line.strip_prefix("INSTRUMENTATION_RESULT: ") {
+            self.set(true, rest);
+        } else if line.starts_with("INSTRUMENTATION_CODE: ") {
+            self.last = None;
+        } else if let Some(msg) = line
+            .strip_prefix("INSTRUMENTATION_FAILED: ")
+            .or_else(|| line.strip_prefix("INSTRUMENTATION_ABORTED: "))
+        {
+            self.failure = Some(msg.trim().to_string());
+            self.last = None;
+        } else if let Some((is_result, key)) = &self.last {
+            let map = if *is_result {
+                &mut self.result
+            } else {
+                &mut self.status
+            };
+            if let Some(value) = map.get_mut(key) {
+                value.push('\n');
+                value.push_str(line);
+            }
+        }
+        None
+    }
+
+    fn set(&mut self, is_result: bool, entry: &str) {
+        let (key, value) = entry.split_once('=').unwrap_or((entry, ""));
+        let map = if is_result {
+            &mut self.result
+        } else {
+            &mut self.status
+        };
+        map.insert(key.to_string(), value.to_string());
+        self.last = Some((is_result, key.to_string()));
+    }
+
+    fn status_block(&mut self, mut status: BTreeMap<String, String>, code: i32) -> Option<Event> {
+        let class = status.remove("class")?;
+        let name = status.remove("test")?;
+        if code == 1 {
+            self.running = Some((class.clone(), name.clone()));
+            return Some(Event::Started { class, name });
+        }
+        self.running = None;
+        let outcome = match code {
+            0 => Outcome::Passed,
+            -3 | -4 => Outcome::Skipped,
+            _ => Outcome::Failed,
+        };
+        let case = TestCase {
+            class,
+            name,
+            outcome,
+            stack: status.remove("stack").filter(|s| !s.trim().is_empty()),
+            time: 0.0,
+            stdout: None,
+            stderr: None,
+        };
+        self.cases.push(case.clone());
+        Some(Event::Finished(case))
+    }
+
+    /// Why the run ended early: the process crashed or could not start.
+    /// A test that was still running is recorded as failed with the reason.
+    pub fn finish(&mut self) -> Option<String> {
+        let crash = self.failure.take().or_else(|| {
+            let msg = self.result.get("shortMsg")?.trim().to_string();
+            Some(match self.result.get("longMsg") {
+                Some(long) => format!("{msg} {}", long.trim()),
+                None => msg,
+            })
+        });
+        if let Some((class, name)) = self.running.take() {
+            self.cases.push(TestCase {
+                class,
+                name,
+                outcome: Outcome::Failed,
+                stack: Some(
+                    crash
+                        .clone()
+                        .unwrap_or_else(|| "Test did not finish".to_string()),
+                ),
+                time: 0.0,
+                stdout: None,
+                stderr: None,
+            });
+        }
+        crash
+    }
+}
+
+/// Why the instrumentation tests run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunPurpose {
+    /// `kargo test --device`.
+    Test,
+    /// Recording a baseline profile: the tests run in one process and the
+    /// profile is flushed once they finish.
+    BaselineProfile,
+}
+
+/// A finished instrumentation run.
+#[derive(Debug, Clone)]
+pub struct DeviceRun {
+    pub adb: PathBuf,
+    pub serial: String,
+    /// Package of the test APK.
+    pub app_id: String,
+    /// The compiled test classes.
+    pub test_classes: PathBuf,
+}
+
+/// Run the instrumentation tests on the device `serial` (empty: the only
+/// connected device, or `$ANDROID_SERIAL`).
+pub async fn test(project_dir: &Path, opts: &TestOptions, serial: &str) -> miette::Result<()> {
+    run(project_dir, opts, serial, RunPurpose::Test)
+        .await
+        .map(|_| ())
+}
+
+/// Build, install and run the test APK on `serial`. `None` when there are
+/// no device tests.
+pub async fn run(
+    project_dir: &Path,
+    opts: &TestOptions,
+    serial: &str,
+    purpose: RunPurpose,
+) -> miette::Result<Option<DeviceRun>> {
+    if let Some(target) = opts.target.as_deref().filter(|t| *t != "android") {
+        return Err(KargoError::Generic {
+            message: format!("--device runs Android instrumentation tests, not {target} tests"),
+        }
+        .into());
+    }
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    if !manifest.targets.contains_key("android") {
+        return Err(KargoError::Generic {
+            message: "`kargo test --device` needs an `android` target in [targets]".into(),
+        }
+        .into());
+    }
+
+    let verbose = opts.verbose;
+    let build_result = ops_build::build(
+        project_dir,
+        &BuildOptions {
+            target: Some("android".into()),
+            verbose,
+            quiet: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+    if !build_result.success {
+        return Err(KargoError::Compilation {
+            message: "cannot run tests".into(),
+        }
+        .into());
+    }
+    let manifest = &build_result.manifest;
+    let preflight = &build_result.preflight;
+    let sdk = preflight
+        .android_sdk
+        .as_ref()
+        .ok_or_else(|| KargoError::Toolchain {
+            message: "Android SDK not found. Install with: kargo toolchain install --android"
+                .into(),
+        })?;
+    let android = manifest.targets.get("android");
+    let compile_sdk = android.and_then(|t| t.compile_sdk).unwrap_or(35);
+    let min_sdk = android.and_then(|t| t.min_sdk).unwrap_or(DEFAULT_MIN_SDK);
+    let target_sdk = android.and_then(|t| t.target_sdk).unwrap_or(compile_sdk);
+    let tools = SdkTools::locate(sdk, compile_sdk)?;
+
+    let src = project_dir.join("src");
+    let source_set = DEVICE_TEST_SOURCE_SETS
+        .iter()
+        .map(|name| SourceSet::new(*name, src.clone()))
+        .find(SourceSet::exists);
+    let sources = source_set
+        .as_ref()
+        .map(|ss| collect_kotlin_files(&ss.kotlin_dirs))
+        .unwrap_or_default();
+    if sources.is_empty() {
+        status("Testing", "no device test sources found");
+        return Ok(None);
+    }
+
+    let serial = select_device(&tools.adb, serial)?;
+    let mut config = manifest
+        .test
+        .as_ref()
+        .and_then(|t| t.android.clone())
+        .unwrap_or_default();
+    let mut sources = sources;
+    let out_dir = build_result.build_dir.join("device-test");
+    if purpose == RunPurpose::BaselineProfile {
+        let api = ops_baseline_profile::device_api_level(&tools.adb, &serial).unwrap_or(0);
+        if api < ops_baseline_profile::MIN_DEVICE_API {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "Recording a baseline profile needs Android 13 (API {}) or newer; \
+                     device {serial} runs API {api}",
+                    ops_baseline_profile::MIN_DEVICE_API
+                ),
+            }
+            .into());
+        }
+        let listener = out_dir.join("generated").join("ProfileFlushListener.kt");
+        std::fs::create_dir_all(listener.parent().unwrap_or(&out_dir)).map_err(KargoError::Io)?;
+        std::fs::write(&listener, ops_baseline_profile::LISTENER_SOURCE).map_err(KargoError::Io)?;
+        sources.push(listener);
+        // The profile accumulates in one process, which the listener flushes.
+        config.orchestrator = false;
+        let listeners = config
+            .instrumentation_args
+            .entry("listener".into())
+            .or_default();
+        if !listeners.is_empty() {
+            listeners.push(',');
+        }
+        listeners.push_str(ops_baseline_profile::LISTENER_CLASS);
+    }
+    let app_id = manifest.android_test_application_id();
+    status(
+        "Testing",
+        &format!(
+            "{} v{} (device {serial})",
+            manifest.package.name, manifest.package.version
+        ),
+    );
+
+    // Compile the tests against main, android.jar and the runner.
+    let cache = LocalCache::new(project_dir);
+    let mut libraries = Vec::new();
+    for (group, artifact, version, repo) in RUNNER_LIBRARIES {
+        let prefix = format!("{artifact}-");
+        let on_classpath = build_result.classpath.test_runtime_jars.iter().any(|jar| {
+            jar.file_name()
+                .is_some_and(|f| f.to_string_lossy().starts_with(&prefix))
+        });
+        if !on_classpath {
+            libraries.push(ensure_library(&cache, group, artifact, version, *repo).await?);
+        }
+    }
+    let kotlin_lib = preflight.toolchain.home.join("lib");
+    let stdlib: Vec<PathBuf> = kargo_compiler::classpath::STDLIB_RUNTIME_JARS
+        .iter()
+        .chain(&["kotlin-test.jar", "kotlin-test-junit.jar"])
+        .map(|jar| kotlin_lib.join(jar))
+        .filter(|jar| jar.is_file())
+        .collect();
+    let mut dex_jars = stdlib.clone();
+    for jar in build_result
+        .classpath
+        .test_runtime_jars
+        .iter()
+        .chain(&libraries)
+    {
+        if !dex_jars.iter().any(|j| j.file_name() == jar.file_name()) {
+            dex_jars.push(jar.clone());
+        }
+    }
+
+    let classes_dir = out_dir.join("classes");
+    std::fs::create_dir_all(&classes_dir).map_err(KargoError::Io)?;
+    let mut classpath = vec![build_result.classes_dir.clone(), tools.android_jar.clone()];
+    classpath.extend(build_result.fixtures_dir.clone());
+    classpath.extend(build_result.classpath.test_jars.iter().cloned());
+    classpath.extend(dex_jars.iter().cloned());
+    let unit = CompilationUnit {
+        name: "deviceTest".into(),
+        target: KotlinTarget::Android,
+        sources,
+        resource_dirs: vec![],
+        classpath,
+        output_dir: classes_dir.clone(),
+        compiler_args: manifest
+            .profile
+            .get(&build_result.profile_name)
+            .map(|p| p.compiler_args.clone())
+            .unwrap_or_default(),
+        is_test: true,
+        generated_sources: vec![],
+        processor_jars: vec![],
+        local_jars: build_result.classpath.local_jars.clone(),
+    };
+    let kotlin_ver = preflight.toolchain.version.to_string();
+    let env = BuildEnv::new(
+        manifest,
+        project_dir,
+        &build_result.build_dir,
+        KotlinTarget::Android.kebab_name(),
+        &build_result.profile_name,
+        &kotlin_ver,
+        &preflight.toolchain.home,
+        kargo_core::config::GlobalConfig::load()
+            .unwrap_or_default()
+            .build
+            .jobs,
+    );
+    let fp_dir = fingerprint::storage_dir(
+        project_dir,
+        KotlinTarget::Android.kebab_name(),
+        &build_result.profile_name,
+    );
+    ops_test::compile_unit(
+        &unit,
+        CompilerDispatch::resolve(
+            KotlinTarget::Android,
+            preflight.toolchain.clone(),
+            preflight.jdk.home.clone(),
+            preflight.java_target.clone(),
+        )
+        .as_ref(),
+        &env,
+        &fp_dir,
+        &kotlin_ver,
+        verbose,
+    )?;
+
+    // Package, align and sign the test APK.
+    status("Packaging", "test APK");
+    let mut inputs = Vec::new();
+    for (name, dir) in [
+        ("classes.jar", Some(&build_result.classes_dir)),
+        ("fixtures.jar", build_result.fixtures_dir.as_ref()),
+        ("test-classes.jar", Some(&classes_dir)),
+    ] {
+        let Some(dir) = dir.filter(|d| d.is_dir()) else {
+            continue;
+        };
+        let jar = out_dir.join(name);
+        kargo_compiler::jar::write_jar(&jar, &[dir.as_path()], &JarManifest::default())?;
+        inputs.push(jar);
+    }
+    inputs.extend(dex_jars);
+
+    // Merge the test manifest with the app's and its libraries', link the
+    // merged resources, and compile the R classes the code refers to.
+    let test_manifest = out_dir.join("TestManifest.xml");
+    std::fs::write(
+        &test_manifest,
+        render_test_manifest(&app_id, &config.runner, &manifest.package.name),
+    )
+    .map_err(KargoError::Io)?;
+    let mut merge_inputs = ops_android_merge::inputs(
+        project_dir,
+        manifest,
+        &build_result.lockfile,
+        DEVICE_TEST_SCOPES,
+    )?;
+    merge_inputs.manifests.insert(
+        0,
+        MergeSource {
+            origin: "test APK manifest".into(),
+            path: test_manifest,
+            library: false,
+        },
+    );
+    merge_inputs.min_sdk = Some(min_sdk);
+    merge_inputs.target_sdk = Some(target_sdk);
+    let merged =
+        ops_android_merge::merge(&merge_inputs, &out_dir.join(ops_android_merge::MERGED_DIR))?;
+    for warning in &merged.warnings {
+        kargo_util::progress::status_warn("Warning", warning);
+    }
+    let linked = out_dir.join("linked.apk");
+    let r_sources = out_dir.join("r");
+    if r_sources.exists() {
+        std::fs::remove_dir_all(&r_sources).map_err(KargoError::Io)?;
+    }
+    let mut link = CommandBuilder::new(path_str(&tools.aapt2))
+        .args(["link", "-o"])
+        .arg(path_str(&linked))
+        .arg("-I")
+        .arg(path_str(&tools.android_jar))
+        .arg("--manifest")
+        .arg(path_str(&merged.manifest))
+        .args(["--min-sdk-version", &min_sdk.to_string()])
+        .args(["--target-sdk-version", &target_sdk.to_string()]);
+    if let Some(res_dir) = &merged.res_dir {
+        let compiled = out_dir.join("compiled-res.zip");
+        run_tool(
+            CommandBuilder::new(path_str(&tools.aapt2))
+                .args(["compile", "--dir"])
+                .arg(path_str(res_dir))
+                .arg("-o")
+                .arg(path_str(&compiled)),
+            "aapt2 compile",
+        )?;
+        link = link
+            .arg(path_str(&compiled))
+            .arg("--java")
+            .arg(path_str(&r_sources));
+        if !merged.packages.is_empty() {
+            link = link.arg("--extra-packages").arg(merged.packages.join(":"));
+        }
+    }
+    run_tool(link, "aapt2 link")?;
+    if r_sources.is_dir() {
+        let r_classes = out_dir.join("r-classes");
+        if r_classes.exists() {
+            std::fs::remove_dir_all(&r_classes).map_err(KargoError::Io)?;
+        }
+        let mut java_files = Vec::new();
+        collect_java_files(&r_sources, &mut java_files);
+        run_tool(
+            CommandBuilder::new(path_str(&preflight.jdk.home.join("bin").join(exe("javac"))))
+                .response_file(true)
+                .args(["--release", "11", "-nowarn", "-d"])
+                .arg(path_str(&r_classes))
+                .args(java_files.iter().map(|p| path_str(p))),
+            "javac",
+        )?;
+        let r_jar = out_dir.join("r.jar");
+        kargo_compiler::jar::write_jar(&r_jar, &[r_classes.as_path()], &JarManifest::default())?;
+        inputs.push(r_jar);
+    }
+
+    // Core library desugaring for Java APIs newer than min-sdk.
+    let scanned: Vec<PathBuf> = inputs
+        .iter()
+        .filter(|jar| !stdlib.contains(jar) && !libraries.contains(jar))
+        .cloned()
+        .collect();
+    let detected = ops_desugar::scan(&scanned, min_sdk);
+    let setting = android.and_then(|t| t.core_library_desugaring);
+    let desugar = if ops_desugar::enabled(setting, &detected) {
+        let apis: Vec<&str> = detected.iter().map(String::as_str).collect();
+        status(
+            "Desugaring",
+            &format!(
+                "core library APIs for min-sdk {min_sdk}{}",
+                if apis.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", apis.join(", "))
+                }
+            ),
+        );
+        Some(ops_desugar::ensure(&cache).await?)
+    } else {
+        if !detected.is_empty() {
+            kargo_util::progress::status_warn(
+                "Warning",
+                &format!(
+                    "{} need a newer API level than min-sdk {min_sdk} and \
+                     core-library-desugaring is off; calls fail on older devices",
+                    detected.iter().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            );
+        }
+        None
+    };
+
+    let dex_dir = out_dir.join("dex");
+    if dex_dir.exists() {
+        std::fs::remove_dir_all(&dex_dir).map_err(KargoError::Io)?;
+    }
+    std::fs::create_dir_all(&dex_dir).map_err(KargoError::Io)?;
+    let mut d8 = CommandBuilder::new(path_str(&tools.d8))
+        .args(["--debug", "--min-api", &min_sdk.to_string(), "--lib"])
+        .arg(path_str(&tools.android_jar))
+        .arg("--output")
+        .arg(path_str(&dex_dir));
+    if let Some(library) = &desugar {
+        d8 = d8.arg("--desugared-lib").arg(path_str(&library.config));
+    }
+    run_tool(d8.args(inputs.iter().map(|p| path_str(p))), "d8")?;
+    if let Some(library) = &desugar {
+        let l8_dir = out_dir.join("l8");
+        if l8_dir.exists() {
+            std::fs::remove_dir_all(&l8_dir).map_err(KargoError::Io)?;
+        }
+        std::fs::create_dir_all(&l8_dir).map_err(KargoError::Io)?;
+        run_tool(
+            CommandBuilder::new(path_str(&preflight.jdk.home.join("bin").join(exe("java"))))
+                .response_file(preflight.jdk.java_accepts_argfiles())
+                .arg("-cp")
+                .arg(path_str(&tools.d8_jar))
+                .arg("com.android.tools.r8.L8")
+                .args(ops_desugar::l8_args(
+                    library,
+                    min_sdk,
+                    &tools.android_jar,
+                    &l8_dir,
+                )),
+            "L8",
+        )?;
+        append_dex_files(&l8_dir, &dex_dir)?;
+    }
+
+    let unaligned = out_dir.join("unaligned.apk");
+    add_dex_files(&linked, &dex_dir, &[], &unaligned)?;
+    // A profile being recorded must not be shaped by the previous one.
+    if let Some(rules) = merged
+        .baseline_profile
+        .as_ref()
+        .filter(|_| purpose == RunPurpose::Test)
+    {
+        let profgen = ops_baseline_profile::profgen_path(sdk);
+        if profgen.is_file() {
+            let entries = ops_baseline_profile::compile(
+                &profgen,
+                rules,
+                &unaligned,
+                &out_dir.join("dexopt"),
+            )?;
+            add_dex_files(&linked, &dex_dir, &entries, &unaligned)?;
+        } else {
+            kargo_util::progress::status_warn(
+                "Warning",
+                &format!(
+                    "{} not found; the baseline profile is not packaged. Install with: \
+                     sdkmanager \"cmdline-tools;latest\"",
+                    profgen.display()
+                ),
+            );
+        }
+    }
+    let aligned = out_dir.join("aligned.apk");
+    run_tool(
+        CommandBuilder::new(path_str(&tools.zipalign))
+            .args(["-f", "4"])
+            .arg(path_str(&unaligned))
+            .arg(path_str(&aligned)),
+        "zipalign",
+    )?;
+    let keystore = ensure_debug_keystore(&preflight.jdk.home)?;
+    let apk = out_dir.join(format!("{}-device-test.apk", manifest.package.name));
+    run_tool(
+        CommandBuilder::new(path_str(&tools.apksigner))
+            .args(["sign", "--ks"])
+            .arg(path_str(&keystore))
+            .args([
+                "--ks-pass",
+                "pass:android",
+                "--key-pass",
+                "pass:android",
+                "--ks-key-alias",
+                "androiddebugkey",
+                "--out",
+            ])
+            .arg(path_str(&apk))
+            .arg(path_str(&aligned))
+            .env("JAVA_HOME", path_str(&preflight.jdk.home)),
+        "apksigner",
+    )?;
+
+    // Install and run.
+    let adb = |args: &[&str]| {
+        CommandBuilder::new(path_str(&tools.adb))
+            .args(["-s", serial.as_str()])
+            .args(args.iter().copied())
+    };
+    if config.orchestrator {
+        for (group, artifact, version) in ORCHESTRATOR_APKS {
+            let helper = ensure_apk(&cache, group, artifact, version).await?;
+            run_tool(
+                adb(&["install", "-r", "-t", &path_str(&helper)]),
+                "adb install",
+            )?;
+        }
+    }
+    status("Installing", &app_id);
+    run_tool(
+        adb(&["install", "-r", "-t", &path_str(&apk)]),
+        "adb install",
+    )?;
+    if purpose == RunPurpose::BaselineProfile {
+        // Start from an empty profile.
+        let _ = adb(&["shell", "cmd", "package", "compile", "--reset", &app_id]).exec();
+    }
+
+    let results_dir = build_result
+        .build_dir
+        .join("test-results")
+        .join(format!("device-{}", sanitize(&serial)));
+    if results_dir.exists() {
+        std::fs::remove_dir_all(&results_dir).map_err(KargoError::Io)?;
+    }
+    std::fs::create_dir_all(&results_dir).map_err(KargoError::Io)?;
+    let logcat_path = results_dir.join("logcat.txt");
+    let _ = adb(&["logcat", "-c"]).exec();
+    let mut logcat = std::fs::File::create(&logcat_path).ok().and_then(|file| {
+        std::process::Command::new(&tools.adb)
+            .args(["-s", &serial, "logcat", "-v", "threadtime"])
+            .stdout(file)
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .ok()
+    });
+
+    status("Running", "instrumentation tests");
+    let command = instrument_command(&app_id, &config, opts.filter.as_deref());
+    if verbose {
+        println!("  adb -s {serial} shell {command}");
+    }
+    let mut parser = InstrumentationParser::default();
+    let mut started = Instant::now();
+    let run = adb(&["shell", &command]).exec_streaming(|_, line| {
+        if verbose {
+            println!("{line}");
+        }
+        match parser.line(line.trim_end_matches('\r')) {
+            Some(Event::Started { .. }) => started = Instant::now(),
+            Some(Event::Finished(case)) => {
+                if let Some(last) = parser.cases.last_mut() {
+                    last.time = started.elapsed().as_secs_f64();
+                }
+                let label = match case.outcome {
+                    Outcome::Passed => "ok",
+                    Outcome::Failed => "FAILED",
+                    Outcome::Skipped => "skipped",
+                };
+                println!("  {}.{} ... {label}", case.class, case.name);
+            }
+            None => {}
+        }
+    });
+    let crash = parser.finish();
+
+    // Give logcat a moment to catch up with the last test before stopping it.
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    if let Some(child) = logcat.as_mut() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let run = run.map_err(|e| KargoError::Generic {
+        message: format!("Failed to run adb: {e}"),
+    })?;
+
+    let cases = parser.cases;
+    for (class, xml) in render_junit_reports(&cases, &serial) {
+        std::fs::write(results_dir.join(format!("TEST-{class}.xml")), xml)
+            .map_err(KargoError::Io)?;
+    }
+    for case in &cases {
+        crate::ops_build::emit_test(case);
+    }
+    crate::ops_build::emit_test_finish(
+        crash.is_none()
+            && run.status.success()
+            && cases.iter().all(|c| c.outcome != Outcome::Failed),
+        &cases,
+    );
+
+    let failed: Vec<&TestCase> = cases
+        .iter()
+        .filter(|c| c.outcome == Outcome::Failed)
+        .collect();
+    let logcat_text = std::fs::read_to_string(&logcat_path).unwrap_or_default();
+    for case in &failed {
+        eprintln!("\n---- {}.{} ----", case.class, case.name);
+        if let Some(stack) = &case.stack {
+            eprintln!("{}", stack.trim_end());
+        }
+        let excerpt = logcat_excerpt(&logcat_text, &case.class, &case.name);
+        if !excerpt.is_empty() {
+            eprintln!("logcat:");
+            for line in excerpt {
+                eprintln!("  {line}");
+            }
+        }
+    }
+    if let Some(crash) = &crash {
+        eprintln!("\nInstrumentation did not complete: {crash}");
+        for line in logcat_text
+            .lines()
+            .filter(|l| l.contains(" E AndroidRuntime"))
+        {
+            eprintln!("  {line}");
+        }
+    }
+
+    let passed = cases
+        .iter()
+        .filter(|c| c.outcome == Outcome::Passed)
+        .count();
+    let skipped = cases
+        .iter()
+        .filter(|c| c.outcome == Outcome::Skipped)
+        .count();
+    let summary = format!(
+        "{passed} passed; {} failed; {skipped} skipped",
+        failed.len()
+    );
+    if failed.is_empty() && crash.is_none() && run.status.success() {
+        status("Finished", &format!("test result: ok. {summary}"));
+        Ok(Some(DeviceRun {
+            adb: tools.adb.clone(),
+            serial,
+            app_id,
+            test_classes: classes_dir,
+        }))
+    } else {
+        kargo_util::progress::note(&format!(
+            "JUnit reports and logcat in {}",
+            results_dir.display()
+        ));
+        Err(KargoError::Test {
+            message: format!("Device tests failed: {summary}"),
+        }
+        .into())
+    }
+}
+
+/// Tools of the Android SDK that build and run the test APK.
+struct SdkTools {
+    adb: PathBuf,
+    d8: PathBuf,
+    /// D8/R8 as a JAR, for running L8.
+    d8_jar: PathBuf,
+    aapt2: PathBuf,
+    zipalign: PathBuf,
+    apksigner: PathBuf,
+    android_jar: PathBuf,
+}
+
+impl SdkTools {
+    /// Locate the tools in the newest installed build-tools.
+    fn locate(sdk: &AndroidSdkInfo, compile_sdk: u32) -> miette::Result<Self> {
+        let build_tools = sdk
+            .installed_build_tools
+            .iter()
+            .max_by_key(|v| {
+                v.split(['.', '-'])
+                    .map(|p| p.parse::<u32>().unwrap_or(0))
+                    .collect::<Vec<_>>()
+            })
+            .map(|v| sdk.home.join("build-tools").join(v))
+            .ok_or_else(|| KargoError::Toolchain {
+                message: format!(
+                    "No build-tools in the Android SDK at {}. Install with: \
+                     sdkmanager \"build-tools;35.0.0\"",
+                    sdk.home.display()
+                ),
+            })?;
+        let tools = Self {
+            adb: adb_path(sdk),
+            d8: build_tools.join(script("d8")),
+            d8_jar: build_tools.join("lib").join("d8.jar"),
+            aapt2: build_tools.join(exe("aapt2")),
+            zipalign: build_tools.join(exe("zipalign")),
+            apksigner: build_tools.join(script("apksigner")),
+            android_jar: sdk
+                .home
+                .join("platforms")
+                .join(format!("android-{compile_sdk}"))
+                .join("android.jar"),
+        };
+        for path in [
+            &tools.adb,
+            &tools.d8,
+            &tools.d8_jar,
+            &tools.aapt2,
+            &tools.zipalign,
+            &tools.apksigner,
+            &tools.android_jar,
+        ] {
+            if !path.is_file() {
+                return Err(KargoError::Toolchain {
+                    message: format!("{} not found in the Android SDK", path.display()),
+                }
+                .into());
+            }
+        }
+        Ok(tools)
+    }
+}
+
+/// `adb` in the SDK's platform-tools.
+pub fn adb_path(sdk: &AndroidSdkInfo) -> PathBuf {
+    sdk.home.join("platform-tools").join(exe("adb"))
+}
+
+/// Devices and emulators attached to `adb`.
+pub fn connected_devices(adb: &Path) -> miette::Result<Vec<AdbDevice>> {
+    let out = CommandBuilder::new(path_str(adb))
+        .arg("devices")
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run adb: {e}"),
+        })?;
+    Ok(parse_adb_devices(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// Parse the output of `adb devices`.
+pub fn parse_adb_devices(text: &str) -> Vec<AdbDevice> {
+    text.lines()
+        .skip_while(|l| !l.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some(AdbDevice {
+                serial: parts.next()?.to_string(),
+                state: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The serial to test on: `requested`, else `$ANDROID_SERIAL`, else the
+/// only connected device.
+fn select_device(adb: &Path, requested: &str) -> miette::Result<String> {
+    let requested = Some(requested.to_string())
+        .filter(|s| !s.is_empty())
+        .or_else(|| {
+            std::env::var("ANDROID_SERIAL")
+                .ok()
+                .filter(|s| !s.is_empty())
+        });
+    let devices = connected_devices(adb)?;
+    let ready: Vec<&AdbDevice> = devices.iter().filter(|d| d.state == "device").collect();
+    match requested {
+        Some(serial) => match devices.iter().find(|d| d.serial == serial) {
+            Some(d) if d.state == "device" => Ok(serial),
+            Some(d) => Err(KargoError::Generic {
+                message: format!("Device {serial} is {}", d.state),
+            }
+            .into()),
+            None => Err(KargoError::Generic {
+                message: format!("Device {serial} is not connected"),
+            }
+            .into()),
+        },
+        None => match ready.as_slice() {
+            [device] => Ok(device.serial.clone()),
+            [] => Err(KargoError::Generic {
+                message: "No Android device connected. Connect one or start an emulator with \
+                          `kargo device start <avd>`."
+                    .into(),
+            }
+            .into()),
+            many => Err(KargoError::Generic {
+                message: format!(
+                    "Several devices connected ({}); choose one with --device <SERIAL>",
+                    many.iter()
+                        .map(|d| d.serial.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+            .into()),
+        },
+    }
+}
+
+/// `AndroidManifest.xml` of a test APK that instruments itself.
+pub fn render_test_manifest(app_id: &str, runner: &str, label: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <manifest xmlns:android=\"http://schemas.android.com/apk/res/android\"\n    \
+         package=\"{app_id}\">\n\
+         \x20   <application android:debuggable=\"true\">\n\
+         \x20       <uses-library android:name=\"android.test.runner\" android:required=\"false\" />\n\
+         \x20   </application>\n\
+         \x20   <instrumentation\n\
+         \x20       android:name=\"{runner}\"\n\
+         \x20       android:targetPackage=\"{app_id}\"\n\
+         \x20       android:label=\"Tests for {}\" />\n\
+         </manifest>\n",
+        xml::escape(label)
+    )
+}
+
+/// The `adb shell` command line that runs the tests.
+pub fn instrument_command(
+    app_id: &str,
+    config: &AndroidTestConfig,
+    filter: Option<&str>,
+) -> String {
+    let runner = format!("{app_id}/{}", config.runner);
+    let mut args: Vec<(&str, &str)> = config
+        .instrumentation_args
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    if let Some(filter) = filter {
+        args.push(("tests_regex", filter));
+    }
+
+    let mut cmd = String::new();
+    if config.orchestrator {
+        cmd.push_str(
+            "CLASSPATH=$(pm path androidx.test.services) app_process / \
+             androidx.test.services.shellexecutor.ShellMain ",
+        );
+    }
+    cmd.push_str("am instrument -w -r");
+    for (key, value) in args {
+        cmd.push_str(&format!(" -e {} {}", shell_quote(key), shell_quote(value)));
+    }
+    if config.orchestrator {
+        if config.clear_package_data {
+            cmd.push_str(" -e clearPackageData true");
+        }
+        cmd.push_str(&format!(
+            " -e targetInstrumentation {runner} {ORCHESTRATOR_RUNNER}"
+        ));
+    } else {
+        cmd.push(' ');
+        cmd.push_str(&runner);
+    }
+    cmd
+}
+
+/// One JUnit XML report per test class, as `(class, xml)`.
+pub fn render_junit_reports(cases: &[TestCase], hostname: &str) -> Vec<(String, String)> {
+    let mut by_class: BTreeMap<&str, Vec<&TestCase>> = BTreeMap::new();
+    for case in cases {
+        by_class.entry(&case.class).or_default().push(case);
+    }
+    by_class
+        .into_iter()
+        .map(|(class, cases)| {
+            let count = |o: Outcome| cases.iter().filter(|c| c.outcome == o).count();
+            let time: f64 = cases.iter().map(|c| c.time).sum();
+            let mut xml = format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" \
+                 skipped=\"{}\" time=\"{time:.3}\" hostname=\"{}\">\n",
+                xml::escape(class),
+                cases.len(),
+                count(Outcome::Failed),
+                count(Outcome::Skipped),
+                xml::escape(hostname),
+            );
+            for case in cases {
+                let open = format!(
+                    "  <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                    xml::escape(&case.name),
+                    xml::escape(class),
+                    case.time
+                );
+                match case.outcome {
+                    Outcome::Passed => xml.push_str(&format!("{open} />\n")),
+                    Outcome::Skipped => {
+                        xml.push_str(&format!("{open}>\n    <skipped />\n  </testcase>\n"))
+                    }
+                    Outcome::Failed => {
+                        let stack = case.stack.as_deref().unwrap_or("");
+                        let message = stack.lines().next().unwrap_or("");
+                        let kind = message.split(':').next().unwrap_or("");
+                        xml.push_str(&format!(
+                            "{open}>\n    <failure message=\"{}\" type=\"{}\">{}</failure>\n  \
+                             </testcase>\n",
+                            xml::escape(message),
+                            xml::escape(kind),
+                            xml::escape(stack)
+                        ));
+                    }
+                }
+            }
+            xml.push_str("</testsuite>\n");
+            (class.to_string(), xml)
+        })
+        .collect()
+}
+
+/// Logcat lines between the runner's `started` and `finished` lines for a
+/// test.
+pub fn logcat_excerpt<'a>(log: &'a str, class: &str, name: &str) -> Vec<&'a str> {
+    let id = format!("{name}({class})");
+    let started = format!("TestRunner: started: {id}");
+    let finished = format!("TestRunner: finished: {id}");
+    log.lines()
+        .skip_while(|l| !l.contains(&started))
+        .skip(1)
+        .take_while(|l| !l.contains(&finished))
+        .collect()
+}
+
+/// Fetch a runner library and return its JAR. For AARs this is the
+/// `classes.jar` inside, cached as the artifact's JAR.
+async fn ensure_library(
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    repo: Repo,
+) -> miette::Result<PathBuf> {
+    if let Some(jar) = cache.get_jar(group, artifact, version, None) {
+        return Ok(jar);
+    }
+    let missing = || KargoError::Network {
+        message: format!("{group}:{artifact}:{version} not found"),
+    };
+    match repo {
+        Repo::Central => kargo_compiler::plugins::ensure_maven_jar(cache, group, artifact, version)
+            .await?
+            .ok_or_else(|| missing().into()),
+        Repo::Google => {
+            let aar = download(group, artifact, version, "aar")
+                .await?
+                .ok_or_else(missing)?;
+            let classes = read_zip_entry(&aar, "classes.jar").map_err(|e| KargoError::Generic {
+                message: format!("{group}:{artifact}:{version}: {e}"),
+            })?;
+            cache.put_jar(group, artifact, version, None, &classes)
+        }
+    }
+}
+
+/// Fetch an APK published to Google's Maven repository.
+async fn ensure_apk(
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> miette::Result<PathBuf> {
+    let filename = format!("{artifact}-{version}.apk");
+    let path = cache.artifact_dir(group, artifact, version).join(&filename);
+    if path.is_file() {
+        return Ok(path);
+    }
+    let data = download(group, artifact, version, "apk")
+        .await?
+        .ok_or_else(|| KargoError::Network {
+            message: format!("{group}:{artifact}:{version} not found"),
+        })?;
+    cache.put(group, artifact, version, &filename, &data)
+}
+
+async fn download(
+    group: &str,
+    artifact: &str,
+    version: &str,
+    extension: &str,
+) -> miette::Result<Option<Vec<u8>>> {
+    let repo = MavenRepository::google();
+    let client = kargo_maven::download::build_client()?;
+    let url = repo.file_url(
+        group,
+        artifact,
+        version,
+        &format!("{artifact}-{version}.{extension}"),
+    );
+    kargo_maven::download::download_artifact(&client, &repo, &url, &format!("{artifact}:{version}"))
+        .await
+}
+
+fn read_zip_entry(data: &[u8], name: &str) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(data)).map_err(|e| e.to_string())?;
+    let mut entry = archive.by_name(name).map_err(|e| format!("{name}: {e}"))?;
+    let mut out = Vec::new();
+    entry.read_to_end(&mut out).map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// Move the dex files of `from` into `dex_dir`, numbered after those
+/// already there (`classes2.dex`, `classes3.dex`, ...).
+fn append_dex_files(from: &Path, dex_dir: &Path) -> miette::Result<()> {
+    let dex_files = |dir: &Path| -> miette::Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(KargoError::Io)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "dex"))
+            .collect();
+        files.sort();
+        Ok(files)
+    };
+    let first = dex_files(dex_dir)?.len() + 1;
+    for (n, dex) in (first..).zip(dex_files(from)?) {
+        std::fs::rename(&dex, dex_dir.join(format!("classes{n}.dex"))).map_err(KargoError::Io)?;
+    }
+    Ok(())
+}
+
+/// Copy the APK `linked` to `out`, adding the `classes*.dex` of `dex_dir`
+/// and the uncompressed `(name, file)` entries of `stored`.
+fn add_dex_files(
+    linked: &Path,
+    dex_dir: &Path,
+    stored: &[(String, PathBuf)],
+    out: &Path,
+) -> miette::Result<()> {
+    let zip_err = |e: zip::result::ZipError| KargoError::Generic {
+        message: format!("Failed to write {}: {e}", out.display()),
+    };
+    let input = std::fs::File::open(linked).map_err(KargoError::Io)?;
+    let mut archive = zip::ZipArchive::new(input).map_err(zip_err)?;
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(out).map_err(KargoError::Io)?);
+    for i in 0..archive.len() {
+        zip.raw_copy_file(archive.by_index_raw(i).map_err(zip_err)?)
+            .map_err(zip_err)?;
+    }
+    let mut dex_files: Vec<PathBuf> = std::fs::read_dir(dex_dir)
+        .map_err(KargoError::Io)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "dex"))
+        .collect();
+    dex_files.sort();
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for dex in dex_files {
+        let name = dex.file_name().unwrap_or_default().to_string_lossy();
+        zip.start_file(name, options).map_err(zip_err)?;
+        let data = std::fs::read(&dex).map_err(KargoError::Io)?;
+        std::io::Write::write_all(&mut zip, &data).map_err(KargoError::Io)?;
+    }
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (name, file) in stored {
+        zip.start_file(name.as_str(), options).map_err(zip_err)?;
+        let data = std::fs::read(file).map_err(KargoError::Io)?;
+        std::io::Write::write_all(&mut zip, &data).map_err(KargoError::Io)?;
+    }
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+/// The debug keystore shared with Android Studio, created with the JDK's
+/// `keytool` if missing.
+fn ensure_debug_keystore(jdk_home: &Path) -> miette::Result<PathBuf> {
+    let dir = std::env::var("ANDROID_USER_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            kargo_util::dirs::home_dir()
+                .unwrap_or_default()
+                .join(".android")
+        });
+    let keystore = dir.join("debug.keystore");
+    if keystore.is_file() {
+        return Ok(keystore);
+    }
+    std::fs::create_dir_all(&dir).map_err(KargoError::Io)?;
+    run_tool(
+        CommandBuilder::new(path_str(&jdk_home.join("bin").join(exe("keytool"))))
+            .args(["-genkeypair", "-keystore"])
+            .arg(path_str(&keystore))
+            .args([
+                "-storepass",
+                "android",
+                "-alias",
+                "androiddebugkey",
+                "-keypass",
+                "android",
+                "-keyalg",
+                "RSA",
+                "-keysize",
+                "2048",
+                "-validity",
+                "10000",
+                "-dname",
+                "CN=Android Debug,O=Android,C=US",
+            ]),
+        "keytool",
+    )?;
+    Ok(keystore)
+}
+
+/// The `.java` files under `dir`, e.g. the `R.java` files of `aapt2 link`.
+fn collect_java_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            collect_java_files(&path, out);
+        } else if path.extension().is_some_and(|e| e == "java") {
+            out.push(path);
+        }
+    }
+}
+
+/// Run an SDK tool, failing with its output.
+pub(crate) fn run_tool(cmd: CommandBuilder, name: &str) -> miette::Result<()> {
+    let out = cmd.exec().map_err(|e| KargoError::Generic {
+        message: format!("Failed to run {name}: {e}"),
+    })?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        return Err(KargoError::Generic {
+            message: format!("{name} failed: {}{}", stderr.trim(), stdout.trim()),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Quote `s` for the device's `sh` unless it is plainly safe.
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/:=,".contains(c))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+fn sanitize(serial: &str) -> String {
+    serial
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn path_str(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn exe(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    }
+}
+
+pub(crate) fn script(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.bat")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "\
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: current=1
+INSTRUMENTATION_STATUS: id=AndroidJUnitRunner
+INSTRUMENTATION_STATUS: numtests=3
+INSTRUMENTATION_STATUS: stream=
+com.example.LoginTest:
+INSTRUMENTATION_STATUS: test=acceptsValidUser
+INSTRUMENTATION_STATUS_CODE: 1
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: current=1
+INSTRUMENTATION_STATUS: id=AndroidJUnitRunner
+INSTRUMENTATION_STATUS: numtests=3
+INSTRUMENTATION_STATUS: stream=.
+INSTRUMENTATION_STATUS: test=acceptsValidUser
+INSTRUMENTATION_STATUS_CODE: 0
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: test=rejectsEmptyPassword
+INSTRUMENTATION_STATUS_CODE: 1
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: stack=java.lang.AssertionError: expected:<false> but was:<true>
+\tat org.junit.Assert.fail(Assert.java:89)
+\tat com.example.LoginTest.rejectsEmptyPassword(LoginTest.kt:21)
+
+INSTRUMENTATION_STATUS: stream=
+Error in rejectsEmptyPassword(com.example.LoginTest):
+INSTRUMENTATION_STATUS: test=rejectsEmptyPassword
+INSTRUMENTATION_STATUS_CODE: -2
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: test=rememberMe
+INSTRUMENTATION_STATUS_CODE: 1
+INSTRUMENTATION_STATUS: class=com.example.LoginTest
+INSTRUMENTATION_STATUS: test=rememberMe
+INSTRUMENTATION_STATUS_CODE: -3
+INSTRUMENTATION_RESULT: stream=
+
+Time: 0.412
+
+FAILURES!!!
+Tests run: 2,  Failures: 1
+
+INSTRUMENTATION_CODE: -1
+";
+
+    #[test]
+    fn parses_instrumentation_output() {
+        let mut parser = InstrumentationParser::default();
+        let events: Vec<Event> = OUTPUT.lines().filter_map(|l| parser.line(l)).collect();
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[0],
+            Event::Started {
+                class: "com.example.LoginTest".into(),
+                name: "acceptsValidUser".into()
+            }
+        );
+        assert_eq!(parser.finish(), None);
+
+        let outcomes: Vec<(&str, Outcome)> = parser
+            .cases
+            .iter()
+            .map(|c| (c.name.as_str(), c.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("acceptsValidUser", Outcome::Passed),
+                ("rejectsEmptyPassword", Outcome::Failed),
+                ("rememberMe", Outcome::Skipped),
+            ]
+        );
+        let stack = parser.cases[1].stack.as_deref().unwrap();
+        assert!(stack.starts_with("java.lang.AssertionError: expected:<false>"));
+        assert!(stack.contains("\tat com.example.LoginTest.rejectsEmptyPassword(LoginTest.kt:21)"));
+    }
+
+    #[test]
+    fn a_crash_fails_the_running_test() {
+        let mut parser = InstrumentationParser::default();
+        for line in [
+            "INSTRUMENTATION_STATUS: class=com.example.CrashTest",
+            "INSTRUMENTATION_STATUS: test=crashes",
+            "INSTRUMENTATION_STATUS_CODE: 1",
+            "INSTRUMENTATION_RESULT: shortMsg=Process crashed.",
+            "INSTRUMENTATION_CODE: 0",
+        ] {
+            parser.line(line);
+        }
+        assert_eq!(parser.finish().as_deref(), Some("Process crashed."));
+        assert_eq!(parser.cases.len(), 1);
+        assert_eq!(parser.cases[0].outcome, Outcome::Failed);
+        assert_eq!(parser.cases[0].stack.as_deref(), Some("Process crashed."));
+    }
+
+    #[test]
+    fn builds_the_am_instrument_command() {
+        let mut config = AndroidTestConfig {
+            orchestrator: false,
+            ..Default::default()
+        };
+        config
+            .instrumentation_args
+            .insert("size".into(), "small".into());
+        assert_eq!(
+            instrument_command("com.example.test", &config, Some("Login.*")),
+            "am instrument -w -r -e size small -e tests_regex 'Login.*' \
+             com.example.test/androidx.test.runner.AndroidJUnitRunner"
+        );
+
+        config.orchestrator = true;
+        config.clear_package_data = true;
+        let cmd = instrument_command("com.example.test", &config, None);
+        assert!(cmd.starts_with("CLASSPATH=$(pm path androidx.test.services) app_process / "));
+        assert!(cmd.ends_with(
+            "-e clearPackageData true -e targetInstrumentation \
+             com.example.test/androidx.test.runner.AndroidJUnitRunner \
+             androidx.test.orchestrator/androidx.test.orchestrator.AndroidTestOrchestrator"
+        ));
+    }
+
+    #[test]
+    fn junit_report_per_class() {
+        let mut parser = InstrumentationParser::default();
+        for line in OUTPUT.lines() {
+            parser.line(line);
+        }
+        let reports = render_junit_reports(&parser.cases, "emulator-5554");
+        assert_eq!(reports.len(), 1);
+        let (class, xml) = &reports[0];
+        assert_eq!(class, "com.example.LoginTest");
+        assert!(xml.contains(
+            "<testsuite name=\"com.example.LoginTest\" tests=\"3\" failures=\"1\" errors=\"0\" \
+             skipped=\"1\""
+        ));
+        assert!(xml.contains(
+            "<failure message=\"java.lang.AssertionError: expected:&lt;false&gt; but \
+             was:&lt;true&gt;\" type=\"java.lang.AssertionError\">"
+        ));
+        assert!(xml.contains("<testcase name=\"rememberMe\""));
+    }
+
+    #[test]
+    fn parses_adb_devices_and_logcat() {
+        let devices = parse_adb_devices(
+            "* daemon started successfully\nList of devices attached\n\
+             emulator-5554\tdevice\nR58M1234\tunauthorized\n\n",
+        );
+        assert_eq!(
+            devices,
+            [
+                AdbDevice {
+                    serial: "emulator-5554".into(),
+                    state: "device".into()
+                },
+                AdbDevice {
+                    serial: "R58M1234".into(),
+                    state: "unauthorized".into()
+                },
+            ]
+        );
+
+        let log = "\
+01-01 10:00:00.000  100  120 I TestRunner: started: login(com.example.LoginTest)
+01-01 10:00:00.100  100  120 D Login: submitting
+01-01 10:00:00.200  100  120 I TestRunner: finished: login(com.example.LoginTest)
+01-01 10:00:00.300  100  120 D Other: unrelated";
+        assert_eq!(
+            logcat_excerpt(log, "com.example.LoginTest", "login"),
+            ["01-01 10:00:00.100  100  120 D Login: submitting"]
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Operation: open the API documentation of a dependency (`kargo docs`).
+//!
+//! The version is the one `Kargo.lock` resolved. Well-known libraries open
+//! their official reference (kotlinlang.org, api.ktor.io, ...); anything
+//! else opens javadoc.io. When the network is unreachable, or with
+//! `--offline`, the dependency's `-javadoc.jar` is extracted from the cache
+//! and served on localhost instead.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::Manifest;
+use kargo_maven::cache::LocalCache;
+use kargo_maven::download;
+use kargo_resolver::resolver;
+use kargo_util::errors::KargoError;
+use kargo_util::progress::{status, status_warn};
+
+/// How long to wait for the documentation site before going offline.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options for `kargo docs`.
+pub struct DocsOptions {
+    /// Artifact name, `group:artifact`, or `group:artifact:version`.
+    pub dep: String,
+    /// Serve the cached javadoc JAR without trying the network.
+    pub offline: bool,
+}
+
+/// Open the documentation of `opts.dep`.
+pub async fn docs(project_root: &Path, opts: &DocsOptions) -> miette::Result<()> {
+    let (group, artifact, version) = resolve(project_root, &opts.dep)?;
+
+    if !opts.offline {
+        let url = docs_url(&group, &artifact, &version);
+        let client = download::build_client()?;
+        match client.head(&url).timeout(PROBE_TIMEOUT).send().await {
+            Ok(_) => {
+                open_browser(&url);
+                return Ok(());
+            }
+            Err(e) => status_warn(
+                "Offline",
+                &format!("{url} is unreachable ({e}); serving the cached javadoc JAR"),
+            ),
+        }
+    }
+
+    let jar = javadoc_jar(project_root, &group, &artifact, &version, opts.offline).await?;
+    let dir = project_root
+        .join(".kargo")
+        .join("docs")
+        .join(&group)
+        .join(&artifact)
+        .join(&version);
+    if !dir.join("index.html").is_file() {
+        extract(&jar, &dir)?;
+    }
+    serve(&dir).await
+}
+
+/// The `(group, artifact, version)` that `dep` names. A bare artifact or
+/// `group:artifact` takes its version from `Kargo.lock`.
+fn resolve(project_root: &Path, dep: &str) -> miette::Result<(String, String, String)> {
+    let parts: Vec<&str> = dep.split(':').collect();
+    if let [group, artifact, version] = parts[..] {
+        return Ok((group.into(), artifact.into(), version.into()));
+    }
+    if !kargo_core::workspace::lockfile_path(project_root).is_file() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "No Kargo.lock to find the version of {dep} in; run `kargo lock` or pass \
+                 group:artifact:version"
+            ),
+        }
+        .into());
+    }
+    let lockfile = Lockfile::for_project(project_root)?;
+    let mut matches: Vec<_> = lockfile
+        .package
+        .iter()
+        .filter(|p| p.name == dep || format!("{}:{}", p.group, p.name) == dep)
+        .map(|p| (p.group.clone(), p.name.clone(), p.version.clone()))
+        .collect();
+    matches.dedup();
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(KargoError::Generic {
+            message: format!("{dep} is not a dependency in Kargo.lock"),
+        }
+        .into()),
+        _ => Err(KargoError::Generic {
+            message: format!(
+                "{dep} is ambiguous; pass one of: {}",
+                matches
+                    .iter()
+                    .map(|(g, a, _)| format!("{g}:{a}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+        .into()),
+    }
+}
+
+/// The reference documentation of a dependency: the official site of
+/// well-known libraries, javadoc.io for the rest.
+fn docs_url(group: &str, artifact: &str, version: &str) -> String {
+    let module = artifact.strip_suffix("-jvm").unwrap_or(artifact);
+    match group {
+        "org.jetbrains.kotlin" if module.starts_with("kotlin-stdlib") => {
+            "https://kotlinlang.org/api/core/kotlin-stdlib/".to_string()
+        }
+        "org.jetbrains.kotlin" if module.starts_with("kotlin-test") => {
+            "https://kotlinlang.org/api/core/kotlin-test/".to_string()
+        }
+        "org.jetbrains.kotlinx" if module.starts_with("kotlinx-coroutines-") => {
+            format!("https://kotlinlang.org/api/kotlinx.coroutines/{module}/")
+        }
+        "org.jetbrains.kotlinx" if module.starts_with("kotlinx-serialization-") => {
+            format!("https://kotlinlang.org/api/kotlinx.serialization/{module}/")
+        }
+        "io.ktor" => "https://api.ktor.io/".to_string(),
+        g if g.starts_with("androidx.") => {
+            "https://developer.android.com/reference/kotlin/androidx/packages".to_string()
+        }
+        _ => format!("https://javadoc.io/doc/{group}/{artifact}/{version}"),
+    }
+}
+
+/// The dependency's `-javadoc.jar` from the cache, downloaded into it
+/// first unless `offline`.
+async fn javadoc_jar(
+    project_root: &Path,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    offline: bool,
+) -> miette::Result<PathBuf> {
+    let cache = LocalCache::new(project_root);
+    if let Some(jar) = cache.get_jar(group, artifact, version, Some("javadoc")) {
+        return Ok(jar);
+    }
+    let missing = || -> miette::Report {
+        KargoError::Generic {
+            message: format!(
+                "No javadoc JAR for {group}:{artifact}:{version} in the cache; run `kargo docs` \
+                 once while online to download it"
+            ),
+        }
+        .into()
+    };
+    if offline {
+        return Err(missing());
+    }
+    let manifest = Manifest::from_path(&project_root.join("Kargo.toml"))?;
+    let repos = resolver::build_repos(&manifest);
+    let client = download::build_client()?;
+    for repo in kargo_maven::repository::for_group(&repos, group) {
+        let url = repo.jar_url(group, artifact, version, Some("javadoc"));
+        if let Ok(Some(data)) = download::download_bytes(&client, repo, &url).await {
+            return cache.put_jar(group, artifact, version, Some("javadoc"), &data);
+        }
+    }
+    Err(missing())
+}
+
+fn extract(jar: &Path, dir: &Path) -> miette::Result<()> {
+    let zip_err = |e: zip::result::ZipError| KargoError::Generic {
+        message: format!("Failed to extract {}: {e}", jar.display()),
+    };
+    let file = std::fs::File::open(jar).map_err(KargoError::Io)?;
+    zip::ZipArchive::new(file)
+        .map_err(zip_err)?
+        .extract(kargo_util::fs::long_path(dir))
+        .map_err(zip_err)?;
+    Ok(())
+}
+
+/// Serve `dir` on localhost and open it until Ctrl+C.
+async fn serve(dir: &Path) -> miette::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(KargoError::Io)?;
+    let addr = listener.local_addr().map_err(KargoError::Io)?;
+    let root = Arc::new(dir.to_path_buf());
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let root = root.clone();
+            tokio::spawn(async move {
+                let _ = handle_connection(stream, &root).await;
+            });
+        }
+    });
+
+    let url = format!("http://{addr}/");
+    status("Serving", &format!("{url} (Ctrl+C to stop)"));
+    open_browser(&url);
+    let _ = tokio::signal::ctrl_c().await;
+    Ok(())
+}
+
+async fn handle_connection(mut stream: TcpStream, root: &Path) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 8192];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let file = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => static_file(root, path),
+        _ => None,
+    };
+    let (status, content_type, body) = match file.and_then(|f| Some((std::fs::read(&f).ok()?, f))) {
+        Some((body, file)) => ("200 OK", crate::ops_wasm::content_type(&file), body),
+        None => ("404 Not Found", "text/plain", b"not found".to_vec()),
+    };
+    let header = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+/// The file under `root` that a GET of `path` serves.
+fn static_file(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = path.split(['?', '#']).next()?.trim_start_matches('/');
+    if relative
+        .split('/')
+        .any(|seg| seg == ".." || seg.contains('\\'))
+    {
+        return None;
+    }
+    let file = root.join(relative);
+    if file.is_dir() {
+        Some(file.join("index.html"))
+    } else {
+        Some(file)
+    }
+}
+
+/// Open `url` in the default browser, or print it if that fails.
+fn open_browser(url: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = std::process::Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let opened = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    status(if opened { "Opened" } else { "Docs" }, url);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_known_libraries_use_their_official_docs() {
+        assert_eq!(
+            docs_url(
+                "org.jetbrains.kotlinx",
+                "kotlinx-coroutines-core-jvm",
+                "1.8.0"
+            ),
+            "https://kotlinlang.org/api/kotlinx.coroutines/kotlinx-coroutines-core/"
+        );
+        assert_eq!(
+            docs_url("com.squareup.okio", "okio", "3.9.0"),
+            "https://javadoc.io/doc/com.squareup.okio/okio/3.9.0"
+        );
+    }
+
+    #[test]
+    fn versions_come_from_the_lockfile() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("Kargo.lock"),
+            "[[package]]\nname = \"okio\"\ngroup = \"com.squareup.okio\"\nversion = \"3.9.0\"\n\n\
+             [[package]]\nname = \"core\"\ngroup = \"com.example\"\nversion = \"1.0\"\n\n\
+             [[package]]\nname = \"core\"\ngroup = \"org.example\"\nversion = \"2.0\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve(tmp.path(), "okio").unwrap(),
+            ("com.squareup.okio".into(), "okio".into(), "3.9.0".into())
+        );
+        assert_eq!(resolve(tmp.path(), "org.example:core").unwrap().2, "2.0");
+        assert!(resolve(tmp.path(), "core")
+            .unwrap_err()
+            .to_string()
+            .contains("ambiguous"));
+        assert!(resolve(tmp.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn paths_outside_the_docs_are_refused() {
+        let root = Path::new("/docs");
+        assert_eq!(
+            static_file(root, "/index-all.html?x=1"),
+            Some(PathBuf::from("/docs/index-all.html"))
+        );
+        assert_eq!(static_file(root, "/../secret"), None);
+    }
+}
@@ -1,22 +1,30 @@
 //! Operation: resolve and download all dependencies.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use kargo_core::lockfile::{Lockfile, ResolvedPackageInfo};
+use kargo_core::lockfile::{LockedFile, Lockfile, ResolvedPackageInfo};
 use kargo_core::manifest::Manifest;
+use kargo_core::workspace;
 use kargo_maven::cache::LocalCache;
 use kargo_maven::download;
 use kargo_resolver::resolver::{self, ResolutionResult};
+use kargo_util::errors::KargoError;
 use kargo_util::hash::sha256_bytes;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
-const MAX_CONCURRENT_DOWNLOADS: usize = 8;
-
 /// Fetch all dependencies: resolve, download artifacts to the project cache,
 /// and update the lockfile.
+///
+/// Progress is journaled in `.kargo/fetch-journal.jsonl` until the lockfile
+/// is written, so a run that is interrupted (Ctrl+C, a network error, a
+/// killed CI job) is resumed by the next one without re-hashing what it
+/// already verified or downloaded.
 pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
     use kargo_util::progress::{spinner, status};
 
@@ -25,22 +33,27 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
     let repos = resolver::build_repos(&manifest);
     let cache = LocalCache::new(project_root);
 
-    let lockfile_path = project_root.join("Kargo.lock");
-    let existing_lock = if lockfile_path.is_file() {
-        Lockfile::from_path(&lockfile_path).ok()
+    let existing_lock = if workspace::lockfile_path(project_root).is_file() {
+        Lockfile::for_project(project_root).ok()
     } else {
         None
     };
 
+    let mut journal = FetchJournal::open(&project_root.join(".kargo").join(JOURNAL_FILE));
+
     let sp = spinner("Resolving dependencies...");
     let client = download::build_client()?;
-    let result =
-        resolver::resolve(&manifest, &repos, &cache, existing_lock.as_ref(), &client).await?;
+    crate::ops_build::emit_resolve_start();
+    let result = resolver::resolve(&manifest, &repos, &cache, existing_lock.as_ref(), &client)
+        .await
+        .inspect_err(|_| crate::ops_build::emit_resolve_finish(None))?;
+    crate::ops_build::emit_resolve_finish(Some(result.artifacts.len()));
     sp.finish_and_clear();
 
     if !result.conflicts.is_empty() && verbose {
         eprintln!("{}", result.conflicts);
     }
+    warn_relocations(&result);
 
     let artifact_count = result.artifacts.len();
     let mut downloaded = 0u32;
@@ -75,7 +88,7 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
             artifact.group, artifact.artifact, artifact.version
         );
         if let Some(jar_path) =
-            cache.get_jar(&artifact.group, &artifact.artifact, &artifact.version, None)
+            cache.get_library(&artifact.group, &artifact.artifact, &artifact.version)
         {
             cached_entries.push((artifact, coord_key, jar_path));
         } else {
@@ -91,10 +104,13 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
                 let coord_key = coord_key.clone();
                 let jar_path = jar_path.clone();
                 let expected = existing_checksums.get(&coord_key).cloned();
+                let journaled = journal.checksum(&coord_key, &jar_path).map(str::to_string);
                 s.spawn(move || {
-                    let actual = kargo_util::hash::sha256_file_streaming(&jar_path)
-                        .ok()
-                        .unwrap_or_default();
+                    let actual = journaled.unwrap_or_else(|| {
+                        kargo_util::hash::sha256_file_streaming(&jar_path)
+                            .ok()
+                            .unwrap_or_default()
+                    });
                     let intact = match expected {
                         Some(ref exp) if !exp.is_empty() => *exp == actual,
                         _ => true, // no prior checksum to compare against
@@ -112,6 +128,7 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
         if intact {
             up_to_date += 1;
             if !hash.is_empty() {
+                journal.record(&coord_key, &cached_entries[i].2, &hash);
                 checksums.insert(coord_key, hash);
             }
         } else {
@@ -122,9 +139,16 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
         }
     }
 
-    let dl_sp = spinner(&format!("Downloading {artifact_count} dependencies..."));
+    let dl_sp = if journal.is_resume() && !to_download.is_empty() {
+        spinner(&format!(
+            "Resuming {} remaining downloads...",
+            to_download.len()
+        ))
+    } else {
+        spinner(&format!("Downloading {artifact_count} dependencies..."))
+    };
     if !to_download.is_empty() {
-        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS));
+        let semaphore = Arc::new(Semaphore::new(kargo_maven::throttle::max_concurrency()));
         let mut join_set = JoinSet::new();
 
         for (artifact, coord_key) in &to_download {
@@ -135,32 +159,61 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
             let artifact_name = artifact.artifact.clone();
             let version = artifact.version.clone();
             let coord_key = coord_key.clone();
-            let cache_root = cache.root().to_path_buf();
+            let local_cache = cache.clone();
 
             join_set.spawn(async move {
                 let _permit = sem.acquire().await;
-                let local_cache = LocalCache::from_root(cache_root);
-                for repo in &repos {
-                    let url = repo.jar_url(&group, &artifact_name, &version, None);
-                    let label = format!("{artifact_name}:{version}");
-                    match download::download_artifact(&client, repo, &url, &label).await {
-                        Ok(Some(data)) => {
-                            kargo_maven::checksum::verify(&client, repo, &url, &data).await?;
-                            let checksum = sha256_bytes(&data);
-                            local_cache.put_jar(&group, &artifact_name, &version, None, &data)?;
-                            return Ok(Some((coord_key, checksum)));
+                let label = format!("{artifact_name}:{version}");
+                // JAR first, then the klib of non-JVM variants, then the
+                // AAR of Android libraries.
+                for extension in ["jar", "klib", "aar"] {
+                    let filename = format!("{artifact_name}-{version}.{extension}");
+                    for repo in kargo_maven::repository::for_group(&repos, &group) {
+                        let url = repo.file_url(&group, &artifact_name, &version, &filename);
+                        match download::download_artifact(&client, repo, &url, &label).await {
+                            Ok(Some(data)) => {
+                                kargo_maven::checksum::verify(&client, repo, &url, &data).await?;
+                                let checksum = sha256_bytes(&data);
+                                let path = local_cache.put(
+                                    &group,
+                                    &artifact_name,
+                                    &version,
+                                    &filename,
+                                    &data,
+                                )?;
+                                return Ok(Some((coord_key, path, checksum)));
+                            }
+                            Ok(None) => continue,
+                            Err(e) => return Err(e),
                         }
-                        Ok(None) => continue,
-                        Err(e) => return Err(e),
                     }
                 }
                 Ok(None)
             });
         }
 
-        while let Some(result) = join_set.join_next().await {
+        loop {
+            let result = tokio::select! {
+                next = join_set.join_next() => match next {
+                    Some(result) => result,
+                    None => break,
+                },
+                Ok(()) = tokio::signal::ctrl_c() => {
+                    join_set.shutdown().await;
+                    dl_sp.finish_and_clear();
+                    return Err(KargoError::Generic {
+                        message: format!(
+                            "Fetch interrupted after {downloaded} of {} downloads. \
+                             Run `kargo fetch` again to resume.",
+                            to_download.len()
+                        ),
+                    }
+                    .into());
+                }
+            };
             match result {
-                Ok(Ok(Some((coord_key, checksum)))) => {
+                Ok(Ok(Some((coord_key, path, checksum)))) => {
+                    journal.record(&coord_key, &path, &checksum);
                     checksums.insert(coord_key, checksum);
                     downloaded += 1;
                 }
@@ -169,7 +222,7 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
                 }
                 Ok(Err(e)) => return Err(e),
                 Err(e) => {
-                    return Err(kargo_util::errors::KargoError::Generic {
+                    return Err(KargoError::Generic {
                         message: format!("Download task failed: {e}"),
                     }
                     .into())
@@ -203,16 +256,23 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
         .iter()
         .map(|a| (a.group.clone(), a.artifact.clone(), a.version.clone()))
         .collect();
+    keep.extend(result.parent_poms.iter().cloned());
 
     // JUnit platform (auto-provisioned by `kargo test`)
-    let has_kotlin_test = manifest.dev_dependencies.values().any(|dep| {
-        let coord = match dep {
-            kargo_core::dependency::Dependency::Short(s) => s.as_str(),
-            kargo_core::dependency::Dependency::Detailed(d) => d.artifact.as_str(),
-            kargo_core::dependency::Dependency::Catalog(c) => c.catalog.as_str(),
-        };
-        coord.contains("kotlin-test") || coord.contains("junit")
-    });
+    let has_kotlin_test = manifest
+        .dev_dependencies
+        .values()
+        .chain(manifest.integration_test_dependencies.values())
+        .any(|dep| {
+            let coord = match dep {
+                kargo_core::dependency::Dependency::Short(s) => s.as_str(),
+                kargo_core::dependency::Dependency::Detailed(d) => d.artifact.as_str(),
+                kargo_core::dependency::Dependency::Catalog(c) => c.catalog.as_str(),
+                kargo_core::dependency::Dependency::Module(_)
+                | kargo_core::dependency::Dependency::File(_) => return false,
+            };
+            coord.contains("kotlin-test") || coord.contains("junit")
+        });
     if has_kotlin_test {
         keep.insert((
             crate::ops_test::JUNIT_PLATFORM_GROUP.into(),
@@ -228,11 +288,48 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
         }
     }
 
+    // R8 for profiles that shrink their JAR
+    for profile in manifest.profile.values() {
+        if let Some(shrink) = profile.shrink() {
+            keep.insert((
+                crate::ops_shrink::R8_GROUP.into(),
+                crate::ops_shrink::R8_ARTIFACT.into(),
+                crate::ops_shrink::r8_version(shrink).into(),
+            ));
+        }
+    }
+
+    // desugar_jdk_libs for Android targets below API 26
+    keep.extend(crate::ops_desugar::tool_coordinates(&manifest));
+
+    // Tools of built-in [generate] generators
+    keep.extend(
+        manifest
+            .generate
+            .values()
+            .filter_map(crate::ops_generate::tool_coordinate),
+    );
+
+    // protoc and gRPC plugins for .proto sources
+    keep.extend(crate::ops_protobuf::tool_coordinates(
+        project_root,
+        &manifest,
+    ));
+
     let pruned = cache.prune(&keep);
 
     let lock_packages = resolution_to_lockfile_packages(&result, &checksums);
-    let lockfile = Lockfile::generate(lock_packages);
-    lockfile.write_to(&lockfile_path)?;
+    let mut lockfile = Lockfile::generate(lock_packages);
+    lockfile.npm =
+        crate::ops_npm::install(project_root, &manifest, existing_lock.as_ref(), verbose)?;
+    lockfile.file = locked_files(project_root, &manifest)?;
+    warn_published_files(project_root, &lockfile.file);
+    if let Some(existing) = existing_lock {
+        lockfile.toolchain = existing.toolchain;
+        lockfile.environment = existing.environment;
+    }
+    lockfile.write_for_project(project_root)?;
+    journal.finish();
 
     if downloaded > 0 || pruned > 0 || verbose {
         status(
@@ -252,12 +349,226 @@ pub async fn fetch(project_root: &Path, verbose: bool) -> miette::Result<()> {
     Ok(())
 }
 
+/// Journal of an unfinished `kargo fetch`, under `.kargo/`.
+const JOURNAL_FILE: &str = "fetch-journal.jsonl";
+
+/// A library `kargo fetch` verified or downloaded, as one journal line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct JournalEntry {
+    coordinate: String,
+    file: PathBuf,
+    size: u64,
+    /// Modification time in seconds since the Unix epoch.
+    modified: u64,
+    sha256: String,
+}
+
+/// Progress of a fetch that has not written its lockfile yet. Lines are
+/// appended as libraries complete, so whatever finished before an
+/// interruption is on disk; a journal left behind means the last run did
+/// not finish.
+struct FetchJournal {
+    path: PathBuf,
+    entries: HashMap<String, JournalEntry>,
+    resume: bool,
+    file: Option<std::fs::File>,
+}
+
+impl FetchJournal {
+    fn open(path: &Path) -> Self {
+        let entries: HashMap<String, JournalEntry> = std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            // A line cut short by the interruption is skipped.
+            .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+            .map(|entry| (entry.coordinate.clone(), entry))
+            .collect();
+        Self {
+            path: path.to_path_buf(),
+            resume: path.is_file(),
+            entries,
+            file: None,
+        }
+    }
+
+    /// Whether an interrupted fetch left this journal behind.
+    fn is_resume(&self) -> bool {
+        self.resume
+    }
+
+    /// The SHA-256 journaled for `coordinate`, if `file` has not changed
+    /// since.
+    fn checksum(&self, coordinate: &str, file: &Path) -> Option<&str> {
+        let entry = self.entries.get(coordinate)?;
+        let (size, modified) = file_stamp(file)?;
+        (entry.file == file && entry.size == size && entry.modified == modified)
+            .then_some(entry.sha256.as_str())
+    }
+
+    /// Journal that `file` of `coordinate` is complete with this SHA-256.
+    fn record(&mut self, coordinate: &str, file: &Path, sha256: &str) {
+        if self.checksum(coordinate, file) == Some(sha256) {
+            return;
+        }
+        let Some((size, modified)) = file_stamp(file) else {
+            return;
+        };
+        let entry = JournalEntry {
+            coordinate: coordinate.to_string(),
+            file: file.to_path_buf(),
+            size,
+            modified,
+            sha256: sha256.to_string(),
+        };
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            self.file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .inspect_err(|e| {
+                    tracing::warn!("Failed to open fetch journal {}: {e}", self.path.display())
+                })
+                .ok();
+        }
+        if let (Some(file), Ok(line)) = (self.file.as_mut(), serde_json::to_string(&entry)) {
+            if let Err(e) = writeln!(file, "{line}") {
+                tracing::warn!("Failed to write fetch journal {}: {e}", self.path.display());
+            }
+        }
+        self.entries.insert(entry.coordinate.clone(), entry);
+    }
+
+    /// The fetch finished: the lockfile has the checksums now.
+    fn finish(mut self) {
+        self.file = None;
+        if self.path.is_file() {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                tracing::warn!(
+                    "Failed to remove fetch journal {}: {e}",
+                    self.path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Size and modification time of `file`.
+fn file_stamp(file: &Path) -> Option<(u64, u64)> {
+    let meta = std::fs::metadata(file).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some((meta.len(), modified))
+}
+
+/// Which build tools `kargo fetch` provisions on top of the dependencies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrewarmOptions {
+    /// The Kotlin compiler, JDK, and SDKs of every declared target.
+    pub all_targets: bool,
+    /// Tools that only some profiles use, such as R8 for shrinking.
+    pub all_profiles: bool,
+}
+
+impl PrewarmOptions {
+    pub fn is_empty(&self) -> bool {
+        !self.all_targets && !self.all_profiles
+    }
+}
+
+/// Download everything builds would otherwise fetch on demand, so that later
+/// steps (e.g. in a CI image) can run offline. Annotation processors, the KSP
+/// toolchain, and the JUnit launcher are always provisioned; `opts` adds
+/// per-target toolchains and per-profile tools. Run after [`fetch`].
+pub async fn prewarm(project_root: &Path, opts: PrewarmOptions) -> miette::Result<()> {
+    use kargo_compiler::plugins;
+    use kargo_util::progress::status;
+
+    let manifest = Manifest::from_path(&project_root.join("Kargo.toml"))?;
+    let cache = LocalCache::new(project_root);
+
+    if opts.all_targets {
+        let preflight = crate::ops_setup::preflight(project_root).await?;
+        let mut provisioned = vec![
+            format!("Kotlin {}", preflight.toolchain.version),
+            format!("JDK {}", preflight.jdk.version),
+        ];
+        if preflight.android_sdk.is_some() {
+            provisioned.push("Android SDK".to_string());
+        }
+        if preflight.xcode.is_some() {
+            provisioned.push("Xcode".to_string());
+        }
+        let targets: Vec<String> = manifest
+            .declared_targets()
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        status(
+            "Provisioned",
+            &format!("{} for {}", provisioned.join(", "), targets.join(", ")),
+        );
+    }
+
+    let mut tools = Vec::new();
+    let processors = plugins::detect_processors(&manifest, &cache);
+    if !processors.is_empty() {
+        plugins::ensure_processor_jars(&processors, &cache).await?;
+        tools.push(format!("{} annotation processor(s)", processors.len()));
+    }
+    if processors
+        .iter()
+        .any(|p| p.kind == plugins::ProcessorKind::Ksp)
+    {
+        let ksp_version = plugins::resolve_ksp_version(&manifest);
+        if plugins::ensure_ksp_toolchain(&cache, &ksp_version)
+            .await?
+            .is_some()
+        {
+            tools.push(format!("KSP {ksp_version}"));
+        }
+    }
+    if workspace::lockfile_path(project_root).is_file() {
+        let lockfile = Lockfile::for_project(project_root)?;
+        if crate::ops_test::ensure_junit_platform(project_root, &lockfile)
+            .await?
+            .is_some()
+        {
+            tools.push(format!(
+                "JUnit Platform {}",
+                crate::ops_test::JUNIT_PLATFORM_VERSION
+            ));
+        }
+    }
+
+    if opts.all_profiles {
+        let mut r8_versions = std::collections::BTreeSet::new();
+        for profile in manifest.profile.values() {
+            if let Some(shrink) = profile.shrink() {
+                r8_versions.insert(crate::ops_shrink::r8_version(shrink).to_string());
+                crate::ops_shrink::ensure_r8(&cache, shrink).await?;
+            }
+        }
+        tools.extend(r8_versions.into_iter().map(|v| format!("R8 {v}")));
+    }
+
+    if !tools.is_empty() {
+        status("Provisioned", &tools.join(", "));
+    }
+    Ok(())
+}
+
 /// Verify that all cached JARs match their lockfile checksums.
 ///
 /// Reports all mismatches at once rather than failing on the first one.
 pub fn verify_checksums(project_root: &Path) -> miette::Result<()> {
-    let lockfile_path = project_root.join("Kargo.lock");
-    let lockfile = Lockfile::from_path(&lockfile_path)?;
+    let lockfile = Lockfile::for_project(project_root)?;
     let cache = LocalCache::new(project_root);
 
     let mut tasks: Vec<(String, std::path::PathBuf, String)> = Vec::new();
@@ -272,7 +583,7 @@ pub fn verify_checksums(project_root: &Path) -> miette::Result<()> {
             }
         };
 
-        let jar_path = match cache.get_jar(&pkg.group, &pkg.name, &pkg.version, None) {
+        let jar_path = match cache.get_library(&pkg.group, &pkg.name, &pkg.version) {
             Some(p) => p,
             None => {
                 skipped += 1;
@@ -333,7 +644,7 @@ pub fn verify_checksums(project_root: &Path) -> miette::Result<()> {
     } else {
         let count = mismatches.len();
         let details = mismatches.join("\n");
-        Err(kargo_util::errors::KargoError::Generic {
+        Err(kargo_util::errors::KargoError::Resolution {
             message: format!(
                 "{count} checksum mismatch(es) detected:\n{details}\n\n\
                  Cached JARs may be corrupted. Delete .kargo/dependencies and run `kargo fetch`."
@@ -358,7 +669,7 @@ pub fn collect_declared_deps(manifest: &Manifest) -> Vec<(String, String, String
             Dependency::Detailed(d) => {
                 Some((d.group.clone(), d.artifact.clone(), d.version.clone()))
             }
-            Dependency::Catalog(_) => None,
+            Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
         }
     };
 
@@ -372,6 +683,11 @@ pub fn collect_declared_deps(manifest: &Manifest) -> Vec<(String, String, String
             declared.push(t);
         }
     }
+    for dep in manifest.integration_test_dependencies.values() {
+        if let Some(t) = extract(dep) {
+            declared.push(t);
+        }
+    }
     for target_deps in manifest.target.values() {
         for dep in target_deps.dependencies.values() {
             if let Some(t) = extract(dep) {
@@ -395,6 +711,112 @@ pub fn collect_declared_deps(manifest: &Manifest) -> Vec<(String, String, String
     declared
 }
 
+/// The `{ file = "..." }` dependencies of `manifest` with the SHA-256 of
+/// each JAR, by path. `[dev-dependencies]` JARs are locked with the `test`
+/// scope and `[integration-test-dependencies]` ones with `integration-test`.
+pub fn locked_files(project_root: &Path, manifest: &Manifest) -> miette::Result<Vec<LockedFile>> {
+    use kargo_core::dependency::Dependency;
+
+    let sections = [
+        (&manifest.dependencies, None),
+        (&manifest.dev_dependencies, Some("test")),
+        (
+            &manifest.integration_test_dependencies,
+            Some("integration-test"),
+        ),
+    ];
+    let mut files = Vec::new();
+    for (deps, section_scope) in sections {
+        for (key, dep) in deps {
+            let Dependency::File(file) = dep else {
+                continue;
+            };
+            let jar = project_root.join(&file.file);
+            if !jar.is_file() {
+                return Err(KargoError::Manifest {
+                    message: format!("file dependency '{key}': {} does not exist", jar.display()),
+                }
+                .into());
+            }
+            let scope = section_scope.unwrap_or(file.scope.unwrap_or_default().as_str());
+            files.push(LockedFile {
+                path: file.file.replace('\\', "/"),
+                checksum: kargo_util::hash::sha256_file(&jar)?,
+                scope: scope.to_string(),
+                members: Vec::new(),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Warn about project JARs that were published to Maven: declaring their
+/// coordinates instead also resolves their dependencies and lets `kargo
+/// outdated` and `kargo audit` see them.
+pub fn warn_published_files(project_root: &Path, files: &[LockedFile]) {
+    for file in files {
+        if let Some(coordinate) = maven_coordinate(&project_root.join(&file.path)) {
+            kargo_util::progress::status_warn(
+                "Warning",
+                &format!(
+                    "{} is the Maven artifact {coordinate}; consider declaring \"{coordinate}\" instead",
+                    file.path
+                ),
+            );
+        }
+    }
+}
+
+/// The `group:artifact:version` recorded in the `pom.properties` a Maven
+/// build packs into `META-INF/maven/`, if `jar` has one.
+fn maven_coordinate(jar: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(jar).ok()?).ok()?;
+    let entry = archive
+        .file_names()
+        .find(|n| n.starts_with("META-INF/maven/") && n.ends_with("/pom.properties"))?
+        .to_string();
+    let mut properties = String::new();
+    archive
+        .by_name(&entry)
+        .ok()?
+        .read_to_string(&mut properties)
+        .ok()?;
+    let property = |key: &str| {
+        properties.lines().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k.trim() == key).then(|| v.trim().to_string())
+        })
+    };
+    Some(format!(
+        "{}:{}:{}",
+        property("groupId")?,
+        property("artifactId")?,
+        property("version")?
+    ))
+}
+
+/// Warn about declared dependencies that were relocated to other
+/// coordinates, suggesting the new ones for `Kargo.toml`.
+pub fn warn_relocations(result: &ResolutionResult) {
+    for relocation in result.relocations.iter().filter(|r| r.direct) {
+        let message = relocation
+            .message
+            .as_deref()
+            .map(|m| format!(" ({m})"))
+            .unwrap_or_default();
+        kargo_util::progress::status_warn(
+            "Relocated",
+            &format!(
+                "{} has moved to {}{message}; update Kargo.toml to the new coordinates",
+                relocation.from, relocation.to
+            ),
+        );
+    }
+}
+
 /// Convert resolution results into lockfile package descriptors.
 pub fn resolution_to_lockfile_packages(
     result: &ResolutionResult,
@@ -412,13 +834,225 @@ pub fn resolution_to_lockfile_packages(
                 scope: Some(a.scope.clone()),
                 source: Some(a.source.clone()),
                 checksum: checksums.get(&coord_key).cloned(),
-                targets: vec![],
+                targets: a.targets.clone(),
                 dependencies: a
                     .dependencies
                     .iter()
                     .map(|d| (d.group.clone(), d.artifact.clone(), d.version.clone()))
                     .collect(),
+                relocated_from: a.relocated_from.clone(),
             }
         })
         .collect()
 }
+
+/// Print the per-repository request totals gathered so far
+/// (`kargo fetch --stats`), followed by hints about unhealthy repositories.
+pub fn print_repository_stats() {
+    let reports = kargo_maven::metrics::snapshot();
+    if reports.is_empty() {
+        println!("No repository requests were made (everything was cached).");
+        return;
+    }
+
+    let name_width = reports
+        .iter()
+        .map(|r| r.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(10);
+    println!(
+        "{:<name_width$}  {:>8}  {:>6}  {:>6}  {:>6}  {:>10}  {:>8}  {:>8}  {:>10}",
+        "Repository", "Requests", "OK", "404", "Failed", "Received", "Avg", "Max", "Throughput"
+    );
+    for report in &reports {
+        let stats = &report.stats;
+        println!(
+            "{:<name_width$}  {:>8}  {:>6}  {:>6}  {:>6}  {:>10}  {:>8}  {:>8}  {:>10}",
+            report.name,
+            stats.requests,
+            stats.ok,
+            stats.not_found,
+            stats.failed,
+            crate::ops_cache::format_size(stats.bytes),
+            format_latency(stats.mean_latency()),
+            format_latency(stats.max_latency),
+            stats
+                .throughput()
+                .map(|b| format!("{}/s", crate::ops_cache::format_size(b as u64)))
+                .unwrap_or_else(|| "-".to_string()),
+        );
+    }
+
+    for hint in repository_health_hints(&reports) {
+        kargo_util::progress::status_warn("Warning", &hint);
+    }
+}
+
+/// Repositories that failed often, answered slowly, or mostly lacked what
+/// was asked of them.
+fn repository_health_hints(reports: &[kargo_maven::metrics::RepoReport]) -> Vec<String> {
+    const MIN_REQUESTS: u64 = 10;
+    let mut hints = Vec::new();
+    for report in reports {
+        let stats = &report.stats;
+        if stats.failed > 0 && stats.failure_rate() >= 0.1 {
+            hints.push(format!(
+                "{} ({}): {:.0}% of requests failed",
+                report.name,
+                report.url,
+                stats.failure_rate() * 100.0
+            ));
+        }
+        if stats.mean_latency() >= Duration::from_secs(1) {
+            hints.push(format!(
+                "{} ({}) is slow: {} per request on average",
+                report.name,
+                report.url,
+                format_latency(stats.mean_latency())
+            ));
+        }
+        if reports.len() > 1 && stats.requests >= MIN_REQUESTS && stats.not_found_rate() >= 0.5 {
+            hints.push(format!(
+                "{} answered 404 to {:.0}% of requests; limit it to the groups it hosts \
+                 with include-groups or exclude-groups",
+                report.name,
+                stats.not_found_rate() * 100.0
+            ));
+        }
+    }
+    hints
+}
+
+fn format_latency(d: Duration) -> String {
+    if d >= Duration::from_secs(1) {
+        format!("{:.1} s", d.as_secs_f64())
+    } else {
+        format!("{} ms", d.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kargo_maven::metrics::{RepoReport, RepoStats, RequestResult};
+
+    fn report(name: &str, results: &[(RequestResult, u64)]) -> RepoReport {
+        let mut stats = RepoStats::default();
+        for (result, millis) in results {
+            stats.record(*result, Duration::from_millis(*millis));
+        }
+        RepoReport {
+            name: name.to_string(),
+            url: format!("https://{name}.example.com"),
+            stats,
+        }
+    }
+
+    #[test]
+    fn health_hints_flag_failing_slow_and_mismatched_repositories() {
+        let healthy = report("central", &[(RequestResult::Ok(1024), 50); 20]);
+        let flaky = report(
+            "mirror",
+            &[
+                (RequestResult::Ok(1024), 3000),
+                (RequestResult::Failed, 2000),
+            ],
+        );
+        let mut lookups = vec![(RequestResult::NotFound, 30); 12];
+        lookups.push((RequestResult::Ok(10), 30));
+        let internal = report("internal", &lookups);
+
+        let hints = repository_health_hints(&[healthy.clone(), flaky, internal]);
+        assert_eq!(hints.len(), 3, "{hints:?}");
+        assert!(hints[0].starts_with("mirror (https://mirror.example.com): 50% of requests failed"));
+        assert!(hints[1].contains("is slow: 2.5 s per request"));
+        assert!(hints[2].starts_with("internal answered 404 to 92% of requests"));
+
+        assert!(repository_health_hints(&[healthy]).is_empty());
+    }
+
+    #[test]
+    fn journal_survives_an_interruption_until_the_fetch_finishes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(".kargo").join(JOURNAL_FILE);
+        let jar = tmp.path().join("lib-1.0.jar");
+        std::fs::write(&jar, b"jar").unwrap();
+
+        let mut journal = FetchJournal::open(&path);
+        assert!(!journal.is_resume());
+        journal.record("org.example:lib:1.0", &jar, "abc");
+        drop(journal);
+        // The interrupted run was cut off mid-line.
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"coordinate\":\"org.exa")
+            .unwrap();
+
+        let journal = FetchJournal::open(&path);
+        assert!(journal.is_resume());
+        assert_eq!(journal.checksum("org.example:lib:1.0", &jar), Some("abc"));
+        assert_eq!(journal.checksum("org.example:other:1.0", &jar), None);
+        std::fs::write(&jar, b"changed jar").unwrap();
+        assert_eq!(journal.checksum("org.example:lib:1.0", &jar), None);
+
+        journal.finish();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn file_dependencies_are_locked_by_content_and_section() {
+        let tmp = tempfile::tempdir().unwrap();
+        let libs = tmp.path().join("libs");
+        std::fs::create_dir_all(&libs).unwrap();
+        std::fs::write(libs.join("vendor.jar"), b"vendor").unwrap();
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(libs.join("gson.jar")).unwrap());
+        zip.start_file(
+            "META-INF/maven/com.google.code.gson/gson/pom.properties",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(
+            b"#Generated by Maven\nartifactId=gson\ngroupId=com.google.code.gson\nversion=2.11.0\n",
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "demo"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+vendor = { file = "libs/vendor.jar", scope = "provided" }
+
+[dev-dependencies]
+gson = { file = "libs/gson.jar" }
+"#,
+        )
+        .unwrap();
+        let files = locked_files(tmp.path(), &manifest).unwrap();
+        let locked: Vec<(&str, &str)> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.scope.as_str()))
+            .collect();
+        assert_eq!(
+            locked,
+            [("libs/gson.jar", "test"), ("libs/vendor.jar", "provided")]
+        );
+        assert_eq!(files[1].checksum, sha256_bytes(b"vendor"));
+
+        assert_eq!(
+            maven_coordinate(&libs.join("gson.jar")).as_deref(),
+            Some("com.google.code.gson:gson:2.11.0")
+        );
+        assert_eq!(maven_coordinate(&libs.join("vendor.jar")), None);
+
+        std::fs::remove_file(libs.join("vendor.jar")).unwrap();
+        assert!(locked_files(tmp.path(), &manifest).is_err());
+    }
+}
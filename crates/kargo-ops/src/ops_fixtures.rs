@@ -0,0 +1,257 @@
+//! Operation: test fixtures (`src/fixtures`) and workspace module test deps.
+//!
+//! Fixtures are compiled after the main sources, against main plus the
+//! `[dependencies]` and `[dev-dependencies]`, into `fixtures-classes/` and
+//! packaged as `<name>-<version>-test-fixtures.jar` next to the main JAR.
+//! The project's own tests see them automatically.
+//!
+//! Other workspace members consume them with a module dependency in a test
+//! section, e.g. `[dev-dependencies] lib = { module = "lib", fixtures = true }`:
+//! the member is built first and its classes (plus fixtures) and runtime
+//! dependencies are added to the consuming test classpath.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::build_cache::BuildCache;
+use kargo_compiler::dispatch::CompilerDispatch;
+use kargo_compiler::fingerprint;
+use kargo_compiler::incremental::{self, IncrementalDecision};
+use kargo_compiler::source_set_discovery::collect_kotlin_files;
+use kargo_compiler::unit::CompilationUnit;
+use kargo_core::dependency::{Dependency, ModuleDependency};
+use kargo_core::target::KotlinTarget;
+use kargo_util::errors::KargoError;
+
+use crate::ops_build::{self, BuildOptions};
+use crate::ops_workspace;
+
+/// Name of the fixtures compilation unit (and its fingerprint).
+const UNIT_NAME: &str = "fixtures";
+
+/// Directory the fixtures are compiled into.
+pub fn classes_dir(build_dir: &Path) -> PathBuf {
+    build_dir.join("fixtures-classes")
+}
+
+/// Compile `src/fixtures` if it has sources. Returns the fixtures classes
+/// directory, or `None` if the project has no fixtures or the target is not
+/// a JVM target.
+pub(crate) fn compile(
+    ctx: &crate::BuildContext,
+    main_unit: &CompilationUnit,
+    opts: &BuildOptions,
+) -> miette::Result<Option<PathBuf>> {
+    if !matches!(ctx.target, KotlinTarget::Jvm | KotlinTarget::Android) {
+        return Ok(None);
+    }
+    let dirs: Vec<PathBuf> = ctx
+        .discovered
+        .fixtures_sources
+        .iter()
+        .flat_map(|ss| ss.kotlin_dirs.clone())
+        .collect();
+    let sources = collect_kotlin_files(&dirs);
+    if sources.is_empty() {
+        return Ok(None);
+    }
+
+    let output_dir = classes_dir(&ctx.build_dir);
+    std::fs::create_dir_all(&output_dir).map_err(KargoError::Io)?;
+
+    let mut classpath = vec![ctx.classes_dir.clone()];
+    classpath.extend(ctx.classpath.test_jars.iter().cloned());
+    for jar in &main_unit.classpath {
+        if !classpath.contains(jar) {
+            classpath.push(jar.clone());
+        }
+    }
+
+    let unit = CompilationUnit {
+        name: UNIT_NAME.into(),
+        target: ctx.target,
+        sources,
+        resource_dirs: ctx
+            .discovered
+            .fixtures_sources
+            .iter()
+            .flat_map(|ss| ss.resource_dirs.clone())
+            .collect(),
+        classpath,
+        output_dir: output_dir.clone(),
+        compiler_args: main_unit.compiler_args.clone(),
+        is_test: true,
+        generated_sources: vec![],
+        processor_jars: vec![],
+        local_jars: ctx.classpath.local_jars.clone(),
+    };
+
+    let kotlin_ver = ctx.preflight.toolchain.version.to_string();
+    let fp_dir =
+        fingerprint::storage_dir(&ctx.project_dir, ctx.target.kebab_name(), &ctx.profile_name);
+    let json = opts.message_format.is_json();
+    let jar_path = ctx.build_dir.join("output").join(format!(
+        "{}-{}-test-fixtures.jar",
+        ctx.manifest.package.name, ctx.manifest.package.version
+    ));
+
+    let fresh = match incremental::check(&unit, &fp_dir, &kotlin_ver) {
+        IncrementalDecision::UpToDate => true,
+        IncrementalDecision::NeedsRebuild(fp) => {
+            let build_cache = BuildCache::new(BuildCache::default_path(), None);
+            if build_cache.restore(&fp, &output_dir)? {
+                incremental::mark_complete(&fp_dir, UNIT_NAME, &fp, &unit)?;
+                true
+            } else {
+                let compiler = CompilerDispatch::resolve(
+                    ctx.target,
+                    ctx.preflight.toolchain.clone(),
+                    ctx.preflight.jdk.home.clone(),
+                    ctx.preflight.java_target.clone(),
+                );
+                let output = if kargo_util::events::enabled() {
+                    ops_build::emit_unit(&unit, false);
+                    compiler.compile_streaming(&unit, &ctx.env, &mut |d| {
+                        ops_build::emit_diagnostic(UNIT_NAME, d)
+                    })?
+                } else {
+                    compiler.compile(&unit, &ctx.env)?
+                };
+                if !output.success {
+                    if !json {
+                        ops_build::print_diagnostics(&output.diagnostics);
+                    }
+                    return Err(KargoError::Generic {
+                        message: "Test fixtures compilation failed.".into(),
+                    }
+                    .into());
+                }
+                incremental::mark_complete(&fp_dir, UNIT_NAME, &fp, &unit)?;
+                let _ = build_cache.put(&fp, &output_dir);
+                std::fs::remove_file(&jar_path).ok();
+                false
+            }
+        }
+    };
+    if fresh {
+        ops_build::emit_unit(&unit, true);
+    }
+    if opts.verbose_output() && fresh {
+        println!("  {UNIT_NAME}: up-to-date (skipped)");
+    }
+
+    if !jar_path.is_file() {
+        std::fs::create_dir_all(ctx.build_dir.join("output")).map_err(KargoError::Io)?;
+        let resources_dir = ctx.build_dir.join("fixtures-resources");
+        let _ = std::fs::remove_dir_all(&resources_dir);
+        for dir in &unit.resource_dirs {
+            if dir.is_dir() {
+                ops_build::copy_dir_contents(dir, &resources_dir);
+            }
+        }
+        ops_build::package_jar(
+            &ctx.manifest.package,
+            &output_dir,
+            &resources_dir,
+            &jar_path,
+            None,
+        )?;
+    }
+
+    Ok(Some(output_dir))
+}
+
+/// Workspace module dependencies declared in a test section.
+pub fn module_dependencies(deps: &BTreeMap<String, Dependency>) -> Vec<&ModuleDependency> {
+    deps.values()
+        .filter_map(|d| match d {
+            Dependency::Module(m) => Some(m),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build each workspace member in `modules` for `target`/`profile` and
+/// return the classpath entries a consuming test needs: the member's
+/// classes, its fixtures if requested, and its runtime dependencies.
+pub(crate) async fn module_classpath(
+    project_dir: &Path,
+    modules: Vec<&ModuleDependency>,
+    target: KotlinTarget,
+    profile: &str,
+    verbose: bool,
+) -> miette::Result<Vec<PathBuf>> {
+    let Some(first) = modules.first() else {
+        return Ok(Vec::new());
+    };
+    let (root, config) = ops_workspace::module_workspace(project_dir, &first.module)?;
+
+    let mut classpath = Vec::new();
+    for module in &modules {
+        let (member_dir, member) = ops_workspace::build_member(
+            &root,
+            &config,
+            project_dir,
+            &module.module,
+            target,
+            profile,
+            verbose,
+        )
+        .await?;
+
+        classpath.push(member.classes_dir.clone());
+        let resources = member.build_dir.join("resources");
+        if resources.is_dir() {
+            classpath.push(resources);
+        }
+        if module.fixtures {
+            let fixtures = member.fixtures_dir.ok_or_else(|| KargoError::Manifest {
+                message: format!(
+                    "module dependency '{}' requests fixtures, but {} has no \
+                     src/fixtures/kotlin sources",
+                    module.module,
+                    member_dir.display()
+                ),
+            })?;
+            classpath.push(fixtures);
+            classpath.extend(member.classpath.test_jars);
+        } else {
+            classpath.extend(member.classpath.compile_jars);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    classpath.retain(|p| seen.insert(p.clone()));
+    Ok(classpath)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_dependencies_are_picked_from_test_sections() {
+        let manifest = kargo_core::manifest::Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dev-dependencies]
+kotlin-test = "org.jetbrains.kotlin:kotlin-test:2.3.0"
+lib = { module = "lib", fixtures = true }
+util = { module = "libs/util" }
+"#,
+        )
+        .unwrap();
+        assert!(manifest.validate().is_ok());
+
+        let modules = module_dependencies(&manifest.dev_dependencies);
+        let found: Vec<(&str, bool)> = modules
+            .iter()
+            .map(|m| (m.module.as_str(), m.fixtures))
+            .collect();
+        assert_eq!(found, [("lib", true), ("libs/util", false)]);
+    }
+}
@@ -0,0 +1,449 @@
+//! Operation: run the `[generate]` code generators.
+//!
+//! Each `[generate.<name>]` entry writes into its own directory,
+//! `<generated>/gen/<name>/`, which is added to the main compilation unit.
+//! A generator is re-run only when its fingerprint changes: the hash of
+//! its configuration and of every file matched by `inputs` (plus the spec
+//! of built-in generators). Declared `outputs` must exist afterwards, and
+//! a missing output forces the generator to run again.
+
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::fingerprint;
+use kargo_core::manifest::{GeneratorConfig, Manifest};
+use kargo_util::errors::KargoError;
+
+/// openapi-generator release used when `version` is not set.
+pub const DEFAULT_OPENAPI_GENERATOR_VERSION: &str = "7.8.0";
+
+const OPENAPI_GENERATOR_GROUP: &str = "org.openapitools";
+const OPENAPI_GENERATOR_ARTIFACT: &str = "openapi-generator-cli";
+
+/// Placeholder in `command` arguments replaced by the output directory.
+const OUT_PLACEHOLDER: &str = "{out}";
+
+/// Options for a generation run.
+#[derive(Debug, Default)]
+pub struct GenerateOptions {
+    /// Run only this generator.
+    pub only: Option<String>,
+    /// Run even when the fingerprint is unchanged.
+    pub force: bool,
+    pub verbose: bool,
+    pub quiet: bool,
+}
+
+/// Maven coordinates of the tool a built-in generator downloads.
+pub fn tool_coordinate(config: &GeneratorConfig) -> Option<(String, String, String)> {
+    match config.generator.as_deref() {
+        Some("openapi") => Some((
+            OPENAPI_GENERATOR_GROUP.into(),
+            OPENAPI_GENERATOR_ARTIFACT.into(),
+            config
+                .version
+                .as_deref()
+                .unwrap_or(DEFAULT_OPENAPI_GENERATOR_VERSION)
+                .into(),
+        )),
+        _ => None,
+    }
+}
+
+/// Output directory of generator `name`.
+pub fn output_dir(generated_dir: &Path, name: &str) -> PathBuf {
+    generated_dir.join("gen").join(name)
+}
+
+/// Directory holding the sources a generator writes below `out`.
+fn source_root(out: &Path, config: &GeneratorConfig) -> PathBuf {
+    match config.generator.as_deref() {
+        Some("openapi") => out.join("src").join("main").join("kotlin"),
+        _ => out.to_path_buf(),
+    }
+}
+
+/// Existing source directories produced by the manifest's generators.
+pub fn source_dirs(generated_dir: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    manifest
+        .generate
+        .iter()
+        .map(|(name, config)| source_root(&output_dir(generated_dir, name), config))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// `command` with every `{out}` replaced by `out`.
+pub fn expand_command(command: &[String], out: &Path) -> Vec<String> {
+    let out = out.to_string_lossy();
+    command
+        .iter()
+        .map(|arg| arg.replace(OUT_PLACEHOLDER, &out))
+        .collect()
+}
+
+/// Files matched by `patterns`, sorted and de-duplicated.
+///
+/// A pattern is a file, a directory (all files below it), or a glob
+/// relative to `project_dir`. A plain path that does not exist is an error.
+pub fn input_files(project_dir: &Path, patterns: &[String]) -> miette::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let is_glob = pattern.contains(['*', '?', '[', '{']);
+        if !is_glob {
+            let path = project_dir.join(pattern);
+            if path.is_file() {
+                files.push(path);
+            } else if path.is_dir() {
+                walk(&path, &mut files);
+            } else {
+                return Err(KargoError::Manifest {
+                    message: format!("[generate] input '{pattern}' does not exist"),
+                }
+                .into());
+            }
+            continue;
+        }
+
+        let matcher = globset::Glob::new(pattern)
+            .map_err(|e| KargoError::Manifest {
+                message: format!("[generate] invalid input pattern '{pattern}': {e}"),
+            })?
+            .compile_matcher();
+        // Only walk the literal directory prefix of the pattern.
+        let base: PathBuf = pattern
+            .split('/')
+            .take_while(|seg| !seg.contains(['*', '?', '[', '{']))
+            .collect();
+        let mut candidates = Vec::new();
+        walk(&project_dir.join(base), &mut candidates);
+        files.extend(candidates.into_iter().filter(|f| {
+            f.strip_prefix(project_dir)
+                .is_ok_and(|rel| matcher.is_match(rel))
+        }));
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// Content fingerprint of a generator: its configuration plus the relative
+/// path and hash of each input file.
+pub fn compute_fingerprint(
+    name: &str,
+    config: &GeneratorConfig,
+    project_dir: &Path,
+    inputs: &[PathBuf],
+) -> miette::Result<String> {
+    let mut data = format!(
+        "{name}\n{}\n",
+        serde_json::to_string(config).unwrap_or_default()
+    );
+    for file in inputs {
+        let rel = file.strip_prefix(project_dir).unwrap_or(file);
+        data.push_str(&format!(
+            "{} {}\n",
+            rel.display(),
+            kargo_util::hash::sha256_file(file)?
+        ));
+    }
+    Ok(kargo_util::hash::sha256_bytes(data.as_bytes()))
+}
+
+fn fingerprint_file(fp_dir: &Path, name: &str) -> PathBuf {
+    fp_dir.join(format!("generate-{name}.fingerprint"))
+}
+
+/// Run the generators whose inputs changed. Returns how many ran.
+pub async fn run(ctx: &crate::BuildContext, opts: &GenerateOptions) -> miette::Result<usize> {
+    if let Some(only) = &opts.only {
+        if !ctx.manifest.generate.contains_key(only) {
+            return Err(KargoError::Manifest {
+                message: format!("No generator '{only}' in [generate]"),
+            }
+            .into());
+        }
+    }
+
+    let fp_dir =
+        fingerprint::storage_dir(&ctx.project_dir, ctx.target.kebab_name(), &ctx.profile_name);
+    let mut ran = 0;
+    for (name, config) in &ctx.manifest.generate {
+        if opts.only.as_ref().is_some_and(|only| only != name) {
+            continue;
+        }
+        let out = output_dir(&ctx.generated_dir, name);
+        let mut patterns = config.inputs.clone();
+        patterns.extend(config.spec.clone());
+        let inputs = input_files(&ctx.project_dir, &patterns)?;
+        let fp = compute_fingerprint(name, config, &ctx.project_dir, &inputs)?;
+        let fp_path = fingerprint_file(&fp_dir, name);
+
+        let up_to_date = !opts.force
+            && out.is_dir()
+            && missing_outputs(&out, config).is_empty()
+            && std::fs::read_to_string(&fp_path).is_ok_and(|stored| stored.trim() == fp);
+        if up_to_date {
+            if opts.verbose {
+                eprintln!("  generate: {name} is up to date");
+            }
+            continue;
+        }
+
+        if !opts.quiet {
+            kargo_util::progress::status("Generating", name);
+        }
+        let _ = std::fs::remove_file(&fp_path);
+        let _ = std::fs::remove_dir_all(&out);
+        std::fs::create_dir_all(&out).map_err(KargoError::Io)?;
+        match config.generator.as_deref() {
+            Some("openapi") => run_openapi(ctx, config, &out, opts.verbose).await?,
+            Some(other) => {
+                return Err(KargoError::Manifest {
+                    message: format!("[generate.{name}]: unknown generator '{other}'"),
+                }
+                .into());
+            }
+            None => run_command(ctx, name, config, &out, opts.verbose)?,
+        }
+
+        let missing = missing_outputs(&out, config);
+        if !missing.is_empty() {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "Generator '{name}' did not produce its declared outputs: {}",
+                    missing.join(", ")
+                ),
+            }
+            .into());
+        }
+        std::fs::create_dir_all(&fp_dir).map_err(KargoError::Io)?;
+        std::fs::write(&fp_path, &fp).map_err(KargoError::Io)?;
+        ran += 1;
+    }
+    Ok(ran)
+}
+
+fn missing_outputs(out: &Path, config: &GeneratorConfig) -> Vec<String> {
+    config
+        .outputs
+        .iter()
+        .filter(|o| !out.join(o).exists())
+        .cloned()
+        .collect()
+}
+
+fn run_command(
+    ctx: &crate::BuildContext,
+    name: &str,
+    config: &GeneratorConfig,
+    out: &Path,
+    verbose: bool,
+) -> miette::Result<()> {
+    let args = expand_command(&config.command, out);
+    if verbose {
+        eprintln!("  generate: {}", args.join(" "));
+    }
+    // Relative program paths such as `./gen.sh` are taken from the project root.
+    let program = if args[0].contains(['/', '\\']) {
+        ctx.project_dir.join(&args[0]).to_string_lossy().to_string()
+    } else {
+        args[0].clone()
+    };
+    let mut cmd = kargo_util::process::CommandBuilder::new(program)
+        .args(&args[1..])
+        .cwd(ctx.project_dir.to_string_lossy().to_string());
+    for (key, value) in &ctx.env.vars {
+        cmd = cmd.env(key, value);
+    }
+    let output = cmd
+        .env("KARGO_GEN_NAME", name)
+        .env("KARGO_GEN_OUT", out.to_string_lossy().to_string())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run generator '{name}': {e}"),
+        })?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "Generator '{name}' failed ({}):\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Generate a Kotlin client from an OpenAPI document with openapi-generator.
+async fn run_openapi(
+    ctx: &crate::BuildContext,
+    config: &GeneratorConfig,
+    out: &Path,
+    verbose: bool,
+) -> miette::Result<()> {
+    let version = config
+        .version
+        .as_deref()
+        .unwrap_or(DEFAULT_OPENAPI_GENERATOR_VERSION);
+    let cache = kargo_maven::cache::LocalCache::new(&ctx.project_dir);
+    let jar = kargo_compiler::plugins::ensure_maven_jar(
+        &cache,
+        OPENAPI_GENERATOR_GROUP,
+        OPENAPI_GENERATOR_ARTIFACT,
+        version,
+    )
+    .await?
+    .ok_or_else(|| KargoError::Generic {
+        message: format!(
+            "openapi-generator {version} ({OPENAPI_GENERATOR_GROUP}:{OPENAPI_GENERATOR_ARTIFACT}) \
+             not found"
+        ),
+    })?;
+
+    let spec = ctx
+        .project_dir
+        .join(config.spec.as_deref().unwrap_or_default());
+    let mut args = vec![
+        "-jar".to_string(),
+        jar.to_string_lossy().to_string(),
+        "generate".into(),
+        "-g".into(),
+        "kotlin".into(),
+        "-i".into(),
+        spec.to_string_lossy().to_string(),
+        "-o".into(),
+        out.to_string_lossy().to_string(),
+        "--global-property".into(),
+        "apiTests=false,modelTests=false,apiDocs=false,modelDocs=false".into(),
+    ];
+    if let Some(package) = &config.package {
+        args.push(format!("--additional-properties=packageName={package}"));
+    }
+    if verbose {
+        eprintln!("  openapi-generator: {}", args.join(" "));
+    }
+    let java_bin = ctx.preflight.jdk.home.join("bin").join("java");
+    let output = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(ctx.preflight.jdk.java_accepts_argfiles())
+        .args(args)
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run openapi-generator: {e}"),
+        })?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "openapi-generator failed:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_placeholder_is_replaced_by_the_output_dir() {
+        let command = vec![
+            "gen".to_string(),
+            "--out={out}".to_string(),
+            "x".to_string(),
+        ];
+        assert_eq!(
+            expand_command(&command, Path::new("/b/gen/api")),
+            ["gen", "--out=/b/gen/api", "x"]
+        );
+    }
+
+    #[test]
+    fn inputs_expand_files_dirs_and_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("schema/nested")).unwrap();
+        std::fs::write(root.join("schema/a.json"), "{}").unwrap();
+        std::fs::write(root.join("schema/nested/b.json"), "{}").unwrap();
+        std::fs::write(root.join("schema/readme.md"), "").unwrap();
+        std::fs::write(root.join("gen.sh"), "").unwrap();
+
+        let files = input_files(root, &["schema/**/*.json".into(), "gen.sh".into()]).unwrap();
+        let rel: Vec<_> = files
+            .iter()
+            .map(|f| f.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            rel,
+            [
+                PathBuf::from("gen.sh"),
+                PathBuf::from("schema/a.json"),
+                PathBuf::from("schema/nested/b.json")
+            ]
+        );
+        assert_eq!(input_files(root, &["schema".into()]).unwrap().len(), 3);
+        assert!(input_files(root, &["missing.txt".into()]).is_err());
+    }
+
+    #[test]
+    fn fingerprint_changes_with_inputs_and_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("in.txt");
+        std::fs::write(&input, "one").unwrap();
+        let config = GeneratorConfig {
+            command: vec!["gen".into()],
+            ..Default::default()
+        };
+        let inputs = [input.clone()];
+        let fp = compute_fingerprint("g", &config, dir.path(), &inputs).unwrap();
+        assert_eq!(
+            fp,
+            compute_fingerprint("g", &config, dir.path(), &inputs).unwrap()
+        );
+
+        std::fs::write(&input, "two").unwrap();
+        let changed = compute_fingerprint("g", &config, dir.path(), &inputs).unwrap();
+        assert_ne!(fp, changed);
+
+        let other = GeneratorConfig {
+            command: vec!["gen".into(), "--verbose".into()],
+            ..Default::default()
+        };
+        assert_ne!(
+            changed,
+            compute_fingerprint("g", &other, dir.path(), &inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn openapi_sources_live_below_the_output_dir() {
+        let config = GeneratorConfig {
+            generator: Some("openapi".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            source_root(Path::new("/g/api"), &config),
+            Path::new("/g/api/src/main/kotlin")
+        );
+        assert_eq!(
+            source_root(Path::new("/g/cmd"), &GeneratorConfig::default()),
+            Path::new("/g/cmd")
+        );
+    }
+}
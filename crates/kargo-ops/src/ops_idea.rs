@@ -0,0 +1,561 @@
+//! Operation: export an IntelliJ IDEA project model (`kargo idea`).
+//!
+//! Writes a `.iml` module file and the `.idea/` project files IDEA needs to
+//! open a Kargo project directly, without a Gradle import:
+//!
+//! - `<name>.iml` — content root, source/test/resource/generated roots,
+//!   excluded directories, compiler output, and library order entries
+//! - `.idea/modules.xml` — registers the module
+//! - `.idea/misc.xml` — project JDK and language level
+//! - `.idea/kotlinc.xml` — Kotlin compiler version for the JPS plugin
+//! - `.idea/libraries/*.xml` — one library per locked dependency, pointing at
+//!   the cached JAR (and its `-sources.jar` when cached), plus the Kotlin
+//!   stdlib from the active toolchain
+//!
+//! The model is built from a [`BuildContext`](crate::BuildContext), so roots
+//! and classpath match exactly what `kargo build` compiles.
+//!
+//! The `.iml` and library files belong to Kargo and are rewritten on every
+//! export. The project files belong to the user once they exist: `misc.xml`
+//! and `kotlinc.xml` are only created when missing, and `modules.xml` only
+//! gains the module entry if it lacks one. `force` regenerates them all.
+
+use std::path::{Path, PathBuf};
+
+use kargo_maven::cache::LocalCache;
+use kargo_util::errors::KargoError;
+use kargo_util::xml;
+
+/// Scope of a library order entry in the module file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryScope {
+    Compile,
+    Test,
+}
+
+/// A project-level library backed by a cached JAR.
+#[derive(Debug, Clone)]
+pub struct IdeaLibrary {
+    /// Display name, e.g. `Kargo: com.squareup.okio:okio:3.9.0`.
+    pub name: String,
+    pub classes: PathBuf,
+    pub sources: Option<PathBuf>,
+    pub scope: LibraryScope,
+}
+
+/// Everything needed to render the IDEA project files.
+#[derive(Debug, Clone)]
+pub struct IdeaModel {
+    pub module_name: String,
+    pub source_roots: Vec<PathBuf>,
+    pub test_roots: Vec<PathBuf>,
+    pub resource_roots: Vec<PathBuf>,
+    pub test_resource_roots: Vec<PathBuf>,
+    pub generated_roots: Vec<PathBuf>,
+    pub excluded: Vec<PathBuf>,
+    pub output_dir: PathBuf,
+    pub test_output_dir: PathBuf,
+    pub libraries: Vec<IdeaLibrary>,
+    /// Name of the project SDK as registered in IDEA (the JDK major version).
+    pub jdk_name: String,
+    /// JVM target (e.g. `17`), used for the project language level.
+    pub language_level: String,
+    pub kotlin_version: String,
+}
+
+/// Name of the library holding the toolchain's Kotlin stdlib.
+const KOTLIN_RUNTIME_LIBRARY: &str = "KotlinJavaRuntime";
+
+impl IdeaModel {
+    /// Assemble the model from a loaded build context.
+    pub fn from_context(ctx: &crate::BuildContext) -> Self {
+        let existing = |dirs: Vec<PathBuf>| -> Vec<PathBuf> {
+            dirs.into_iter().filter(|d| d.is_dir()).collect()
+        };
+
+        let main = &ctx.discovered.main_sources;
+        let test: Vec<_> = ctx
+            .discovered
+            .fixtures_sources
+            .iter()
+            .chain(&ctx.discovered.test_sources)
+            .chain(&ctx.discovered.integration_test_sources)
+            .collect();
+
+        let mut generated_roots = Vec::new();
+        for sub in [["ksp", "kotlin"], ["ksp", "java"], ["kapt", "sources"]] {
+            let dir = ctx.generated_dir.join(sub[0]).join(sub[1]);
+            if dir.is_dir() {
+                generated_roots.push(dir);
+            }
+        }
+        // BuildConfig.kt is written at the top of the generated directory.
+        if ctx.generated_dir.is_dir() {
+            generated_roots.push(ctx.generated_dir.clone());
+        }
+
+        let cache = LocalCache::new(&ctx.project_dir);
+        let mut libraries: Vec<IdeaLibrary> = ctx
+            .lockfile
+            .package
+            .iter()
+            .filter_map(|pkg| {
+                let scope = match pkg.scope.as_deref().unwrap_or("compile") {
+                    "test" | "integration-test" => LibraryScope::Test,
+                    "ksp" | "kapt" => return None,
+                    _ => LibraryScope::Compile,
+                };
+                let classes = cache.get_jar(&pkg.group, &pkg.name, &pkg.version, None)?;
+                Some(IdeaLibrary {
+                    name: format!("Kargo: {}:{}:{}", pkg.group, pkg.name, pkg.version),
+                    classes,
+                    sources: cache.get_jar(&pkg.group, &pkg.name, &pkg.version, Some("sources")),
+                    scope,
+                })
+            })
+            .collect();
+        libraries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let kotlin_lib = ctx.preflight.toolchain.home.join("lib");
+        let stdlib = kotlin_lib.join("kotlin-stdlib.jar");
+        if stdlib.is_file() {
+            let sources = kotlin_lib.join("kotlin-stdlib-sources.jar");
+            libraries.insert(
+                0,
+                IdeaLibrary {
+                    name: KOTLIN_RUNTIME_LIBRARY.into(),
+                    classes: stdlib,
+                    sources: sources.is_file().then_some(sources),
+                    scope: LibraryScope::Compile,
+                },
+            );
+        }
+
+        let jdk_major = ctx
+            .preflight
+            .jdk
+            .version
+            .split(['.', '-', '+'])
+            .next()
+            .unwrap_or(&ctx.preflight.jdk.version)
+            .to_string();
+
+        IdeaModel {
+            module_name: ctx.manifest.package.name.clone(),
+            source_roots: existing(main.iter().flat_map(|s| s.kotlin_dirs.clone()).collect()),
+            test_roots: existing(test.iter().flat_map(|s| s.kotlin_dirs.clone()).collect()),
+            resource_roots: existing(main.iter().flat_map(|s| s.resource_dirs.clone()).collect()),
+            test_resource_roots: existing(
+                test.iter().flat_map(|s| s.resource_dirs.clone()).collect(),
+            ),
+            generated_roots,
+            excluded: vec![
+                ctx.project_dir.join("build"),
+                ctx.project_dir.join(".kargo"),
+            ],
+            output_dir: ctx.classes_dir.clone(),
+            test_output_dir: ctx.build_dir.join("test-classes"),
+            libraries,
+            jdk_name: jdk_major,
+            language_level: ctx.preflight.java_target.clone(),
+            kotlin_version: ctx.preflight.toolchain.version.to_string(),
+        }
+    }
+}
+
+/// Load the project and write its IDEA model. Returns the paths written.
+pub async fn export(project_dir: &Path, force: bool) -> miette::Result<Vec<PathBuf>> {
+    let ctx = crate::BuildContext::load(project_dir, None, None, false).await?;
+    let model = IdeaModel::from_context(&ctx);
+    write(project_dir, &model, force)
+}
+
+/// Write the module and `.idea/` files for `model` under `project_dir`,
+/// keeping existing project files unless `force`. Returns the paths
+/// written.
+pub fn write(project_dir: &Path, model: &IdeaModel, force: bool) -> miette::Result<Vec<PathBuf>> {
+    let idea_dir = project_dir.join(".idea");
+    let libraries_dir = idea_dir.join("libraries");
+    std::fs::create_dir_all(&libraries_dir).map_err(KargoError::Io)?;
+
+    // Drop library files from a previous export so removed dependencies
+    // do not linger in the library table.
+    if let Ok(entries) = std::fs::read_dir(&libraries_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_ours = std::fs::read_to_string(&path)
+                .map(|c| c.contains(GENERATED_MARKER))
+                .unwrap_or(false);
+            if is_ours {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    let mut files = vec![(
+        project_dir.join(format!("{}.iml", model.module_name)),
+        render_module(project_dir, model),
+    )];
+    let modules_xml = idea_dir.join("modules.xml");
+    let existing = match force {
+        true => None,
+        false => std::fs::read_to_string(&modules_xml).ok(),
+    };
+    match existing {
+        None => files.push((modules_xml, render_modules_xml(model))),
+        Some(content) => {
+            if let Some(merged) = add_module_entry(&content, model) {
+                files.push((modules_xml, merged));
+            }
+        }
+    }
+    for (name, content) in [
+        ("misc.xml", render_misc_xml(model)),
+        ("kotlinc.xml", render_kotlinc_xml(model)),
+    ] {
+        let path = idea_dir.join(name);
+        if force || !path.exists() {
+            files.push((path, content));
+        }
+    }
+    for lib in &model.libraries {
+        files.push((
+            libraries_dir.join(format!("{}.xml", library_file_stem(&lib.name))),
+            render_library(project_dir, lib),
+        ));
+    }
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, content) in files {
+        std::fs::write(&path, content).map_err(KargoError::Io)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Comment placed in every generated file so re-exports can tell them apart
+/// from files the user created by hand.
+const GENERATED_MARKER: &str = "<!-- Generated by kargo idea -->";
+
+fn render_module(project_dir: &Path, model: &IdeaModel) -> String {
+    let url = |p: &Path| file_url(p, project_dir, "$MODULE_DIR$");
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(GENERATED_MARKER);
+    out.push('\n');
+    out.push_str("<module type=\"JAVA_MODULE\" version=\"4\">\n");
+    out.push_str("  <component name=\"NewModuleRootManager\" inherit-compiler-output=\"false\">\n");
+    out.push_str(&format!(
+        "    <output url=\"{}\" />\n",
+        url(&model.output_dir)
+    ));
+    out.push_str(&format!(
+        "    <output-test url=\"{}\" />\n",
+        url(&model.test_output_dir)
+    ));
+    out.push_str("    <content url=\"file://$MODULE_DIR$\">\n");
+    for root in &model.source_roots {
+        out.push_str(&format!(
+            "      <sourceFolder url=\"{}\" isTestSource=\"false\" />\n",
+            url(root)
+        ));
+    }
+    for root in &model.generated_roots {
+        out.push_str(&format!(
+            "      <sourceFolder url=\"{}\" isTestSource=\"false\" generated=\"true\" />\n",
+            url(root)
+        ));
+    }
+    for root in &model.resource_roots {
+        out.push_str(&format!(
+            "      <sourceFolder url=\"{}\" type=\"java-resource\" />\n",
+            url(root)
+        ));
+    }
+    for root in &model.test_roots {
+        out.push_str(&format!(
+            "      <sourceFolder url=\"{}\" isTestSource=\"true\" />\n",
+            url(root)
+        ));
+    }
+    for root in &model.test_resource_roots {
+        out.push_str(&format!(
+            "      <sourceFolder url=\"{}\" type=\"java-test-resource\" />\n",
+            url(root)
+        ));
+    }
+    for dir in &model.excluded {
+        out.push_str(&format!("      <excludeFolder url=\"{}\" />\n", url(dir)));
+    }
+    out.push_str("    </content>\n");
+    out.push_str("    <orderEntry type=\"inheritedJdk\" />\n");
+    out.push_str("    <orderEntry type=\"sourceFolder\" forTests=\"false\" />\n");
+    for lib in &model.libraries {
+        let scope = match lib.scope {
+            LibraryScope::Compile => "",
+            LibraryScope::Test => " scope=\"TEST\"",
+        };
+        out.push_str(&format!(
+            "    <orderEntry type=\"library\"{scope} name=\"{}\" level=\"project\" />\n",
+            xml::escape(&lib.name)
+        ));
+    }
+    out.push_str("  </component>\n");
+    out.push_str("</module>\n");
+    out
+}
+
+fn render_modules_xml(model: &IdeaModel) -> String {
+    let iml = format!("$PROJECT_DIR$/{}.iml", xml::escape(&model.module_name));
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         {GENERATED_MARKER}\n\
+         <project version=\"4\">\n  \
+           <component name=\"ProjectModuleManager\">\n    \
+             <modules>\n      \
+               <module fileurl=\"file://{iml}\" filepath=\"{iml}\" />\n    \
+             </modules>\n  \
+           </component>\n\
+         </project>\n"
+    )
+}
+
+/// `modules.xml` content with the module of `model` registered, or `None`
+/// if it already is (or the file has no `<modules>` list to add it to).
+fn add_module_entry(content: &str, model: &IdeaModel) -> Option<String> {
+    let iml = format!("$PROJECT_DIR$/{}.iml", xml::escape(&model.module_name));
+    if content.contains(&format!("filepath=\"{iml}\"")) {
+        return None;
+    }
+    let end = content.find("</modules>")?;
+    let indent = content[..end]
+        .rsplit('\n')
+        .next()
+        .filter(|s| s.trim().is_empty())
+        .unwrap_or("");
+    let entry = format!("  <module fileurl=\"file://{iml}\" filepath=\"{iml}\" />\n{indent}");
+    let mut merged = content.to_string();
+    merged.insert_str(end, &entry);
+    Some(merged)
+}
+
+fn render_misc_xml(model: &IdeaModel) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         {GENERATED_MARKER}\n\
+         <project version=\"4\">\n  \
+           <component name=\"ProjectRootManager\" version=\"2\" languageLevel=\"{}\" \
+         project-jdk-name=\"{}\" project-jdk-type=\"JavaSDK\">\n    \
+             <output url=\"file://$PROJECT_DIR$/build/idea\" />\n  \
+           </component>\n\
+         </project>\n",
+        language_level(&model.language_level),
+        xml::escape(&model.jdk_name)
+    )
+}
+
+fn render_kotlinc_xml(model: &IdeaModel) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         {GENERATED_MARKER}\n\
+         <project version=\"4\">\n  \
+           <component name=\"KotlinJpsPluginSettings\">\n    \
+             <option name=\"version\" value=\"{}\" />\n  \
+           </component>\n\
+         </project>\n",
+        xml::escape(&model.kotlin_version)
+    )
+}
+
+fn render_library(project_dir: &Path, lib: &IdeaLibrary) -> String {
+    let root = |p: &Path| format!("      <root url=\"{}\" />\n", jar_url(p, project_dir));
+    let classes = root(&lib.classes);
+    let sources = lib.sources.as_deref().map(root).unwrap_or_default();
+    format!(
+        "{GENERATED_MARKER}\n\
+         <component name=\"libraryTable\">\n  \
+           <library name=\"{}\">\n    \
+             <CLASSES>\n{classes}    </CLASSES>\n    \
+             <JAVADOC />\n    \
+             <SOURCES>\n{sources}    </SOURCES>\n  \
+           </library>\n\
+         </component>\n",
+        xml::escape(&lib.name)
+    )
+}
+
+/// `file://` URL, relative to `macro` when `path` lives under `base`.
+fn file_url(path: &Path, base: &Path, macro_name: &str) -> String {
+    format!("file://{}", idea_path(path, base, macro_name))
+}
+
+/// `jar://` URL for a project-level library root.
+fn jar_url(path: &Path, project_dir: &Path) -> String {
+    format!("jar://{}!/", idea_path(path, project_dir, "$PROJECT_DIR$"))
+}
+
+fn idea_path(path: &Path, base: &Path, macro_name: &str) -> String {
+    let rendered = match path.strip_prefix(base) {
+        Ok(rel) if rel.as_os_str().is_empty() => macro_name.to_string(),
+        Ok(rel) => format!("{macro_name}/{}", rel.to_string_lossy()),
+        Err(_) => path.to_string_lossy().to_string(),
+    };
+    xml::escape(&rendered.replace('\\', "/"))
+}
+
+/// IDEA language level for a JVM target: `1.8` → `JDK_1_8`, `17` → `JDK_17`.
+fn language_level(java_target: &str) -> String {
+    format!("JDK_{}", java_target.replace('.', "_"))
+}
+
+/// File name IDEA would choose for a library: non-alphanumerics become `_`.
+fn library_file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_model(root: &Path) -> IdeaModel {
+        IdeaModel {
+            module_name: "demo".into(),
+            source_roots: vec![root.join("src/main/kotlin")],
+            test_roots: vec![root.join("src/test/kotlin")],
+            resource_roots: vec![],
+            test_resource_roots: vec![],
+            generated_roots: vec![root.join("build/jvm/dev/generated")],
+            excluded: vec![root.join("build"), root.join(".kargo")],
+            output_dir: root.join("build/jvm/dev/classes"),
+            test_output_dir: root.join("build/jvm/dev/test-classes"),
+            libraries: vec![
+                IdeaLibrary {
+                    name: "Kargo: com.example:core:1.0".into(),
+                    classes: root.join(".kargo/dependencies/com/example/core/1.0/core-1.0.jar"),
+                    sources: Some(
+                        root.join(".kargo/dependencies/com/example/core/1.0/core-1.0-sources.jar"),
+                    ),
+                    scope: LibraryScope::Compile,
+                },
+                IdeaLibrary {
+                    name: "Kargo: junit:junit:4.13".into(),
+                    classes: PathBuf::from("/opt/m2/junit-4.13.jar"),
+                    sources: None,
+                    scope: LibraryScope::Test,
+                },
+            ],
+            jdk_name: "21".into(),
+            language_level: "17".into(),
+            kotlin_version: "2.3.0".into(),
+        }
+    }
+
+    #[test]
+    fn writes_module_and_project_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let written = write(root, &sample_model(root), false).unwrap();
+        assert_eq!(written.len(), 6);
+
+        let iml = std::fs::read_to_string(root.join("demo.iml")).unwrap();
+        assert!(iml.contains(
+            r#"<sourceFolder url="file://$MODULE_DIR$/src/main/kotlin" isTestSource="false" />"#
+        ));
+        assert!(iml.contains(
+            r#"<sourceFolder url="file://$MODULE_DIR$/src/test/kotlin" isTestSource="true" />"#
+        ));
+        assert!(iml.contains(r#"generated="true""#));
+        assert!(iml.contains(r#"<excludeFolder url="file://$MODULE_DIR$/build" />"#));
+        assert!(iml.contains(
+            r#"<orderEntry type="library" scope="TEST" name="Kargo: junit:junit:4.13" level="project" />"#
+        ));
+
+        let misc = std::fs::read_to_string(root.join(".idea/misc.xml")).unwrap();
+        assert!(misc.contains(r#"languageLevel="JDK_17""#));
+        assert!(misc.contains(r#"project-jdk-name="21""#));
+
+        let lib =
+            std::fs::read_to_string(root.join(".idea/libraries/Kargo__com_example_core_1_0.xml"))
+                .unwrap();
+        assert!(lib.contains(
+            "jar://$PROJECT_DIR$/.kargo/dependencies/com/example/core/1.0/core-1.0.jar!/"
+        ));
+        assert!(lib.contains("core-1.0-sources.jar!/"));
+
+        let kotlinc = std::fs::read_to_string(root.join(".idea/kotlinc.xml")).unwrap();
+        assert!(kotlinc.contains(r#"value="2.3.0""#));
+    }
+
+    #[test]
+    fn re_export_removes_stale_libraries_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mut model = sample_model(root);
+        write(root, &model, false).unwrap();
+
+        let custom = root.join(".idea/libraries/handmade.xml");
+        std::fs::write(&custom, "<component name=\"libraryTable\" />").unwrap();
+
+        model.libraries.pop();
+        write(root, &model, false).unwrap();
+
+        assert!(!root
+            .join(".idea/libraries/Kargo__junit_junit_4_13.xml")
+            .exists());
+        assert!(root
+            .join(".idea/libraries/Kargo__com_example_core_1_0.xml")
+            .exists());
+        assert!(custom.exists());
+    }
+
+    #[test]
+    fn re_export_keeps_user_project_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let model = sample_model(root);
+        let idea = root.join(".idea");
+        std::fs::create_dir_all(&idea).unwrap();
+        let misc = "<project version=\"4\"><component name=\"ProjectRootManager\" \
+                    project-jdk-name=\"corretto-21\" /></project>\n";
+        std::fs::write(idea.join("misc.xml"), misc).unwrap();
+        std::fs::write(
+            idea.join("modules.xml"),
+            "<project version=\"4\">\n  <component name=\"ProjectModuleManager\">\n    \
+             <modules>\n      <module fileurl=\"file://$PROJECT_DIR$/tools.iml\" \
+             filepath=\"$PROJECT_DIR$/tools.iml\" />\n    </modules>\n  </component>\n\
+             </project>\n",
+        )
+        .unwrap();
+
+        let written = write(root, &model, false).unwrap();
+        assert!(!written.contains(&idea.join("misc.xml")));
+        assert_eq!(
+            std::fs::read_to_string(idea.join("misc.xml")).unwrap(),
+            misc
+        );
+        let modules = std::fs::read_to_string(idea.join("modules.xml")).unwrap();
+        assert!(modules.contains("$PROJECT_DIR$/tools.iml"));
+        assert!(modules.contains(
+            "\n      <module fileurl=\"file://$PROJECT_DIR$/demo.iml\" \
+             filepath=\"$PROJECT_DIR$/demo.iml\" />\n    </modules>"
+        ));
+        assert!(idea.join("kotlinc.xml").is_file());
+
+        let written = write(root, &model, false).unwrap();
+        assert!(!written.contains(&idea.join("modules.xml")));
+
+        write(root, &model, true).unwrap();
+        let misc = std::fs::read_to_string(idea.join("misc.xml")).unwrap();
+        assert!(misc.contains(r#"project-jdk-name="21""#));
+        let modules = std::fs::read_to_string(idea.join("modules.xml")).unwrap();
+        assert!(!modules.contains("tools.iml"));
+    }
+
+    #[test]
+    fn language_level_handles_legacy_targets() {
+        assert_eq!(language_level("1.8"), "JDK_1_8");
+        assert_eq!(language_level("21"), "JDK_21");
+    }
+}
@@ -1 +1,93 @@
 //! Operation: initialize Kargo in an existing directory.
+//!
+//! When the directory holds a Gradle project, settings from
+//! `gradle.properties` that have a `Kargo.toml` equivalent are carried over
+//! into the new manifest.
+
+use std::path::Path;
+
+use kargo_core::properties::{self, GradleImport, GRADLE_PROPERTIES};
+use kargo_util::errors::KargoError;
+use toml_edit::{Array, DocumentMut};
+
+/// Apply the mappable keys of `project_dir/gradle.properties` to
+/// `project_dir/Kargo.toml`. Keys already set in `Kargo.toml` are left
+/// alone. Returns what was found, or `None` without a `gradle.properties`.
+pub fn import_gradle_properties(project_dir: &Path) -> miette::Result<Option<GradleImport>> {
+    let props_path = project_dir.join(GRADLE_PROPERTIES);
+    if !props_path.is_file() {
+        return Ok(None);
+    }
+    let import = GradleImport::from_properties(&properties::load_properties(&props_path)?);
+    if import.is_empty() {
+        return Ok(Some(import));
+    }
+
+    let manifest_path = project_dir.join("Kargo.toml");
+    let content = std::fs::read_to_string(&manifest_path).map_err(KargoError::Io)?;
+    let mut doc: DocumentMut = content.parse().map_err(|e| KargoError::Manifest {
+        message: format!("Failed to parse Kargo.toml: {e}"),
+    })?;
+
+    if !import.jvm_args.is_empty() {
+        let test = doc.entry("test").or_insert(toml_edit::table());
+        if let Some(test) = test.as_table_mut() {
+            if !test.contains_key("jvm-args") {
+                let args: Array = import.jvm_args.iter().map(String::as_str).collect();
+                test["jvm-args"] = toml_edit::value(args);
+            }
+        }
+    }
+    if let Some(style) = &import.code_style {
+        let format = doc.entry("format").or_insert(toml_edit::table());
+        if let Some(format) = format.as_table_mut() {
+            if !format.contains_key("style") {
+                format["style"] = toml_edit::value(style.as_str());
+            }
+        }
+    }
+
+    std::fs::write(&manifest_path, doc.to_string()).map_err(KargoError::Io)?;
+    Ok(Some(import))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradle_properties_are_merged_into_the_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("Kargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[format]\nstyle = \"intellij\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.path().join(GRADLE_PROPERTIES),
+            "org.gradle.jvmargs=-Xmx2g -Dfile.encoding=UTF-8\n\
+             kotlin.code.style=official\n\
+             android.useAndroidX=true\n",
+        )
+        .unwrap();
+
+        let import = import_gradle_properties(tmp.path()).unwrap().unwrap();
+        assert_eq!(import.unmapped, ["android.useAndroidX"]);
+
+        let manifest: toml::Value =
+            toml::from_str(&std::fs::read_to_string(tmp.path().join("Kargo.toml")).unwrap())
+                .unwrap();
+        assert_eq!(
+            manifest["test"]["jvm-args"],
+            toml::Value::Array(vec!["-Xmx2g".into(), "-Dfile.encoding=UTF-8".into()])
+        );
+        // Settings already in Kargo.toml win.
+        assert_eq!(manifest["format"]["style"].as_str(), Some("intellij"));
+    }
+
+    #[test]
+    fn no_gradle_properties_is_not_an_import() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(import_gradle_properties(tmp.path()).unwrap().is_none());
+    }
+}
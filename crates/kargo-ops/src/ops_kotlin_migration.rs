@@ -0,0 +1,265 @@
+//! Operation: migration notes when a project moves to another Kotlin version.
+//!
+//! The Kotlin version of the last build is kept in `.kargo/kotlin-version`.
+//! When it changes, the profiles' `compiler-args` and the `[plugins]` table
+//! are checked against [`FLAG_CHANGES`] and [`PLUGIN_RENAMES`], and the
+//! first build with the new version prints what to change.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::{Manifest, PluginRef};
+use kargo_toolchain::version::KotlinVersion;
+
+/// A compiler flag removed or renamed in a Kotlin release.
+struct FlagChange {
+    /// The flag as written, matched alone or followed by `=value`.
+    flag: &'static str,
+    /// The first release without the flag.
+    since: &'static str,
+    replacement: Option<&'static str>,
+    note: &'static str,
+}
+
+const FLAG_CHANGES: &[FlagChange] = &[
+    FlagChange {
+        flag: "-Xopt-in",
+        since: "1.7.0",
+        replacement: Some("-opt-in"),
+        note: "opt-in is stable",
+    },
+    FlagChange {
+        flag: "-Xuse-k2",
+        since: "2.0.0",
+        replacement: None,
+        note: "K2 is the default compiler; use `-language-version 1.9` to keep the old one",
+    },
+    FlagChange {
+        flag: "-Xjvm-default=all",
+        since: "2.2.0",
+        replacement: Some("-jvm-default=no-compatibility"),
+        note: "`-Xjvm-default` is replaced by the stable `-jvm-default`",
+    },
+    FlagChange {
+        flag: "-Xjvm-default=all-compatibility",
+        since: "2.2.0",
+        replacement: Some("-jvm-default=enable"),
+        note: "`-Xjvm-default` is replaced by the stable `-jvm-default`",
+    },
+    FlagChange {
+        flag: "-Xjvm-default=disable",
+        since: "2.2.0",
+        replacement: Some("-jvm-default=disable"),
+        note: "`-Xjvm-default` is replaced by the stable `-jvm-default`",
+    },
+    FlagChange {
+        flag: "-Xcontext-receivers",
+        since: "2.2.0",
+        replacement: Some("-Xcontext-parameters"),
+        note: "context receivers are superseded by context parameters; \
+               `context(...)` declarations need to name their parameters",
+    },
+];
+
+/// A compiler plugin that moved to another id in a Kotlin release.
+struct PluginRename {
+    from: &'static str,
+    to: &'static str,
+    since: &'static str,
+    note: &'static str,
+}
+
+const PLUGIN_RENAMES: &[PluginRename] = &[PluginRename {
+    from: "org.jetbrains.compose",
+    to: "org.jetbrains.kotlin.plugin.compose",
+    since: "2.0.0",
+    note: "the Compose compiler is released with Kotlin; declare it next to the \
+           Compose Multiplatform plugin",
+}];
+
+/// Plugins with this id prefix are released with the compiler and must
+/// match its version.
+const KOTLIN_PLUGIN_PREFIX: &str = "org.jetbrains.kotlin.";
+
+/// Something to change in `Kargo.toml` for the new Kotlin version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationNote {
+    /// Where in `Kargo.toml`, e.g. `profile.release.compiler-args`.
+    pub location: String,
+    pub message: String,
+}
+
+/// The file recording the Kotlin version of the last build.
+pub fn record_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".kargo").join("kotlin-version")
+}
+
+/// Record `version` as the project's Kotlin version and return the notes
+/// for moving to it from the previously recorded one. Empty when the
+/// version is unchanged or nothing was recorded yet.
+pub fn check(
+    project_dir: &Path,
+    manifest: &Manifest,
+    version: &KotlinVersion,
+) -> Vec<MigrationNote> {
+    let path = record_path(project_dir);
+    let previous: Option<KotlinVersion> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+    if previous.as_ref() == Some(version) {
+        return Vec::new();
+    }
+    let written = std::fs::create_dir_all(path.parent().unwrap_or(project_dir))
+        .and_then(|_| std::fs::write(&path, format!("{version}\n")));
+    if let Err(e) = written {
+        tracing::warn!(
+            "Failed to record the Kotlin version in {}: {e}",
+            path.display()
+        );
+    }
+    match previous {
+        Some(previous) => notes(manifest, &previous, version),
+        None => Vec::new(),
+    }
+}
+
+/// The notes for moving `manifest` from Kotlin `from` to `to`: flags and
+/// plugin ids changed by releases after `from` up to `to`, and Kotlin
+/// compiler plugins pinned to a version other than `to`.
+pub fn notes(manifest: &Manifest, from: &KotlinVersion, to: &KotlinVersion) -> Vec<MigrationNote> {
+    let crossed = |since: &str| {
+        since
+            .parse::<KotlinVersion>()
+            .is_ok_and(|since| *from < since && since <= *to)
+    };
+    let mut notes = Vec::new();
+
+    for (name, profile) in &manifest.profile {
+        for arg in &profile.compiler_args {
+            let Some(change) = FLAG_CHANGES.iter().find(|c| {
+                crossed(c.since)
+                    && (arg == c.flag
+                        || arg
+                            .strip_prefix(c.flag)
+                            .is_some_and(|rest| rest.starts_with('=')))
+            }) else {
+                continue;
+            };
+            let action = match change.replacement {
+                Some(replacement) => format!("replace `{arg}` with `{replacement}`"),
+                None => format!("remove `{arg}`"),
+            };
+            notes.push(MigrationNote {
+                location: format!("profile.{name}.compiler-args"),
+                message: format!("{action} (Kotlin {}: {})", change.since, change.note),
+            });
+        }
+    }
+
+    for (name, plugin) in &manifest.plugins {
+        let (id, version) = match plugin {
+            PluginRef::Id(id) => (id.as_str(), None),
+            PluginRef::Detailed { id, version } => (id.as_str(), version.as_deref()),
+        };
+        if let Some(rename) = PLUGIN_RENAMES
+            .iter()
+            .find(|r| r.from == id && crossed(r.since))
+        {
+            notes.push(MigrationNote {
+                location: format!("plugins.{name}"),
+                message: format!(
+                    "the compiler plugin of `{id}` is now `{}` (Kotlin {}: {})",
+                    rename.to, rename.since, rename.note
+                ),
+            });
+        }
+        if let Some(version) = version {
+            if id.starts_with(KOTLIN_PLUGIN_PREFIX) && version != to.to_string() {
+                notes.push(MigrationNote {
+                    location: format!("plugins.{name}"),
+                    message: format!(
+                        "set version = \"{to}\" or drop it: `{id}` {version} was \
+                         built for another Kotlin compiler"
+                    ),
+                });
+            }
+        }
+    }
+
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "demo"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[profile.dev]
+compiler-args = ["-Xopt-in=kotlin.RequiresOptIn", "-Xjvm-default=all", "-Xcontext-receivers"]
+
+[profile.release]
+compiler-args = ["-Xuse-k2", "-Xjvm-default=all-compatibility"]
+
+[plugins]
+compose = "org.jetbrains.compose"
+serialization = { id = "org.jetbrains.kotlin.plugin.serialization", version = "1.9.24" }
+"#;
+
+    fn version(v: &str) -> KotlinVersion {
+        v.parse().unwrap()
+    }
+
+    fn notes_for(manifest: &Manifest, from: &str, to: &str) -> Vec<MigrationNote> {
+        notes(manifest, &version(from), &version(to))
+    }
+
+    #[test]
+    fn notes_cover_changes_between_the_versions() {
+        let manifest = Manifest::parse_toml(MANIFEST).unwrap();
+        let notes = notes_for(&manifest, "1.9.24", "2.1.0");
+        let locations: Vec<&str> = notes.iter().map(|n| n.location.as_str()).collect();
+        assert_eq!(
+            locations,
+            [
+                "profile.release.compiler-args",
+                "plugins.compose",
+                "plugins.serialization"
+            ]
+        );
+        assert!(notes[0].message.starts_with("remove `-Xuse-k2`"));
+        assert!(notes[2].message.contains("version = \"2.1.0\""));
+
+        let notes = notes_for(&manifest, "2.1.0", "2.2.0");
+        assert!(notes.iter().any(|n| n
+            .message
+            .contains("replace `-Xjvm-default=all` with `-jvm-default=no-compatibility`")));
+        assert!(notes
+            .iter()
+            .any(|n| n.message.contains("`-jvm-default=enable`")));
+        assert!(notes
+            .iter()
+            .any(|n| n.message.contains("`-Xcontext-parameters`")));
+        assert!(!notes.iter().any(|n| n.message.contains("-Xopt-in")));
+    }
+
+    #[test]
+    fn check_reports_once_per_version_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = Manifest::parse_toml(MANIFEST).unwrap();
+
+        // Nothing recorded yet: record without notes.
+        assert!(check(tmp.path(), &manifest, &version("1.9.24")).is_empty());
+        assert!(check(tmp.path(), &manifest, &version("1.9.24")).is_empty());
+
+        assert!(!check(tmp.path(), &manifest, &version("2.0.0")).is_empty());
+        assert!(check(tmp.path(), &manifest, &version("2.0.0")).is_empty());
+        assert_eq!(
+            std::fs::read_to_string(record_path(tmp.path())).unwrap(),
+            "2.0.0\n"
+        );
+    }
+}
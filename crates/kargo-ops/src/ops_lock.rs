@@ -1,13 +1,20 @@
 //! Operation: resolve all dependencies and regenerate Kargo.lock.
+//!
+//! Also records the build environment (Kotlin, KSP, JDK) in the lockfile's
+//! `[environment]` table and reports drift from it on later builds, and
+//! exports the locked set for other tools (`kargo lock --export`).
 
 use std::collections::HashMap;
 use std::path::Path;
 
-use kargo_core::lockfile::Lockfile;
+use kargo_core::lockfile::{LockedEnvironment, LockedPackage, Lockfile};
 use kargo_core::manifest::Manifest;
+use kargo_core::workspace;
 use kargo_maven::cache::LocalCache;
 use kargo_maven::download;
 use kargo_resolver::resolver;
+use kargo_util::errors::KargoError;
+use kargo_util::xml;
 
 use crate::ops_fetch::resolution_to_lockfile_packages;
 
@@ -24,12 +31,17 @@ pub async fn lock(project_root: &Path, verbose: bool) -> miette::Result<()> {
     let client = download::build_client()?;
 
     // Force fresh resolution (no lockfile fast-path)
-    let result = resolver::resolve(&manifest, &repos, &cache, None, &client).await?;
+    crate::ops_build::emit_resolve_start();
+    let result = resolver::resolve(&manifest, &repos, &cache, None, &client)
+        .await
+        .inspect_err(|_| crate::ops_build::emit_resolve_finish(None))?;
+    crate::ops_build::emit_resolve_finish(Some(result.artifacts.len()));
     sp.finish_and_clear();
 
     if !result.conflicts.is_empty() && verbose {
         eprintln!("{}", result.conflicts);
     }
+    crate::ops_fetch::warn_relocations(&result);
 
     // Compute checksums from cached JARs in parallel
     let jar_entries: Vec<_> = result
@@ -69,9 +81,16 @@ pub async fn lock(project_root: &Path, verbose: bool) -> miette::Result<()> {
     });
 
     let lock_packages = resolution_to_lockfile_packages(&result, &checksums);
-    let lockfile = Lockfile::generate(lock_packages);
-    let lockfile_path = project_root.join("Kargo.lock");
-    lockfile.write_to(&lockfile_path)?;
+    // The environment is deliberately not carried over: the next build
+    // records the one this lockfile is used with.
+    let existing_lock = Lockfile::for_project(project_root).ok();
+    let mut lockfile = Lockfile::generate(lock_packages);
+    lockfile.npm =
+        crate::ops_npm::install(project_root, &manifest, existing_lock.as_ref(), verbose)?;
+    lockfile.toolchain = existing_lock.and_then(|lf| lf.toolchain);
+    lockfile.file = crate::ops_fetch::locked_files(project_root, &manifest)?;
+    crate::ops_fetch::warn_published_files(project_root, &lockfile.file);
+    lockfile.write_for_project(project_root)?;
 
     status(
         "Resolved",
@@ -80,3 +99,419 @@ pub async fn lock(project_root: &Path, verbose: bool) -> miette::Result<()> {
 
     Ok(())
 }
+
+/// The environment a build runs in: the resolved Kotlin version, the KSP
+/// version if the project declares processors, and the JDK major version.
+pub fn current_environment(ctx: &crate::BuildContext) -> LockedEnvironment {
+    LockedEnvironment {
+        kotlin: ctx.preflight.toolchain.version.to_string(),
+        ksp: (!ctx.manifest.ksp.is_empty())
+            .then(|| kargo_compiler::plugins::resolve_ksp_version(&ctx.manifest)),
+        jdk: Some(ctx.preflight.jdk.version.clone()),
+    }
+}
+
+/// Resolve dependencies the way `kargo fetch` would, honouring the pins in
+/// `Kargo.lock`, and print every resolution decision without writing the
+/// lockfile.
+pub async fn explain(project_root: &Path) -> miette::Result<()> {
+    use kargo_util::progress::spinner;
+
+    let manifest = Manifest::from_path(&project_root.join("Kargo.toml"))?;
+    let repos = resolver::build_repos(&manifest);
+    let cache = LocalCache::new(project_root);
+    let existing = Lockfile::for_project(project_root).ok();
+
+    let sp = spinner("Resolving dependencies...");
+    let client = download::build_client()?;
+    let result = resolver::resolve(&manifest, &repos, &cache, existing.as_ref(), &client).await?;
+    sp.finish_and_clear();
+
+    if result.explanation.steps.is_empty() {
+        println!("No dependencies to resolve.");
+    } else {
+        print!("{}", result.explanation);
+        println!(
+            "\n{} dependencies resolved, {} conflict(s); Kargo.lock not written.",
+            result.artifacts.len(),
+            result.conflicts.len()
+        );
+    }
+    Ok(())
+}
+
+/// Compare the current environment against the one recorded in `Kargo.lock`.
+///
+/// Records the environment if the lockfile has none yet (e.g. after
+/// `kargo lock`). Otherwise each difference is reported as a warning, or as
+/// an error when `locked` is set. Projects without a `Kargo.lock` are skipped.
+pub fn check_environment(ctx: &crate::BuildContext, locked: bool) -> miette::Result<()> {
+    if !workspace::lockfile_path(&ctx.project_dir).is_file() {
+        return Ok(());
+    }
+    let current = current_environment(ctx);
+
+    let Some(recorded) = &ctx.lockfile.environment else {
+        if locked {
+            return Err(KargoError::Resolution {
+                message: "Kargo.lock does not record a build environment, \
+                          and --locked prevents updating it.\n  \
+                          Run `kargo build` without --locked to record it."
+                    .into(),
+            }
+            .into());
+        }
+        let mut lockfile = ctx.lockfile.clone();
+        lockfile.environment = Some(current);
+        return lockfile.write_for_project(&ctx.project_dir);
+    };
+
+    let drift = recorded.drift(&current);
+    if drift.is_empty() {
+        return Ok(());
+    }
+    if locked {
+        return Err(KargoError::Resolution {
+            message: format!(
+                "Build environment differs from Kargo.lock (--locked):\n  {}\n  \
+                 Switch toolchains or run `kargo lock` to re-lock.",
+                drift.join("\n  ")
+            ),
+        }
+        .into());
+    }
+    for d in &drift {
+        kargo_util::progress::status_warn(
+            "Warning",
+            &format!("build environment differs from Kargo.lock: {d}"),
+        );
+    }
+    Ok(())
+}
+
+/// Maven Central, which package URLs leave implicit.
+const MAVEN_CENTRAL: &str = "https://repo.maven.apache.org/maven2";
+
+/// Format of `kargo lock --export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Gradle's `gradle/verification-metadata.xml`, with the SHA-256 of
+    /// each locked JAR.
+    GradleVerification,
+    /// One row per locked package, with a header.
+    Csv,
+    /// One package URL per line, for security scanners.
+    Purl,
+}
+
+impl ExportFormat {
+    /// Parse an `--export` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "gradle-verification" => Ok(Self::GradleVerification),
+            "csv" => Ok(Self::Csv),
+            "purl" => Ok(Self::Purl),
+            other => Err(KargoError::Generic {
+                message: format!(
+                    "Unknown export format '{other}'. Available: gradle-verification, csv, purl"
+                ),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Write the packages of `Kargo.lock` in `format` to `output`, or to
+/// stdout.
+pub fn export(
+    project_root: &Path,
+    format: ExportFormat,
+    output: Option<&Path>,
+) -> miette::Result<()> {
+    if !workspace::lockfile_path(project_root).is_file() {
+        return Err(KargoError::Resolution {
+            message: "No Kargo.lock to export; run `kargo lock` first".into(),
+        }
+        .into());
+    }
+    let lockfile = Lockfile::for_project(project_root)?;
+    if format == ExportFormat::GradleVerification {
+        for pkg in lockfile.package.iter().filter(|p| p.checksum.is_none()) {
+            kargo_util::progress::status_warn(
+                "Skipped",
+                &format!(
+                    "{}:{}:{} has no checksum in Kargo.lock",
+                    pkg.group, pkg.name, pkg.version
+                ),
+            );
+        }
+    }
+    let rendered = render_export(&lockfile, format);
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+            }
+            std::fs::write(path, rendered).map_err(KargoError::Io)?;
+            kargo_util::progress::status(
+                "Exported",
+                &format!(
+                    "{} packages to {}",
+                    lockfile.package.len() + lockfile.npm.len(),
+                    path.display()
+                ),
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// `lockfile` in `format`, packages sorted by coordinates.
+pub fn render_export(lockfile: &Lockfile, format: ExportFormat) -> String {
+    let mut packages: Vec<&LockedPackage> = lockfile.package.iter().collect();
+    packages.sort_by(|a, b| (&a.group, &a.name, &a.version).cmp(&(&b.group, &b.name, &b.version)));
+    packages.dedup_by(|a, b| (&a.group, &a.name, &a.version) == (&b.group, &b.name, &b.version));
+    let mut npm: Vec<_> = lockfile.npm.iter().collect();
+    npm.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    let mut out = String::new();
+    match format {
+        ExportFormat::GradleVerification => {
+            out.push_str(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <verification-metadata xmlns=\"https://schema.gradle.org/dependency-verification\" \
+                 xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+                 xsi:schemaLocation=\"https://schema.gradle.org/dependency-verification \
+                 https://schema.gradle.org/dependency-verification/dependency-verification-1.3.xsd\">\n\
+                 \x20  <configuration>\n\
+                 \x20     <verify-metadata>false</verify-metadata>\n\
+                 \x20     <verify-signatures>false</verify-signatures>\n\
+                 \x20  </configuration>\n\
+                 \x20  <components>\n",
+            );
+            for pkg in packages {
+                let Some(checksum) = &pkg.checksum else {
+                    continue;
+                };
+                out.push_str(&format!(
+                    "      <component group=\"{}\" name=\"{}\" version=\"{}\">\n\
+                     \x20        <artifact name=\"{}-{}.jar\">\n\
+                     \x20           <sha256 value=\"{}\" origin=\"Generated from Kargo.lock\"/>\n\
+                     \x20        </artifact>\n\
+                     \x20     </component>\n",
+                    xml::escape(&pkg.group),
+                    xml::escape(&pkg.name),
+                    xml::escape(&pkg.version),
+                    xml::escape(&pkg.name),
+                    xml::escape(&pkg.version),
+                    xml::escape(checksum),
+                ));
+            }
+            out.push_str("   </components>\n</verification-metadata>\n");
+        }
+        ExportFormat::Csv => {
+            out.push_str("ecosystem,group,name,version,scope,targets,sha256,source\n");
+            for pkg in packages {
+                let row = [
+                    "maven",
+                    &pkg.group,
+                    &pkg.name,
+                    &pkg.version,
+                    pkg.scope.as_deref().unwrap_or_default(),
+                    &pkg.targets.join(" "),
+                    pkg.checksum.as_deref().unwrap_or_default(),
+                    pkg.source.as_deref().unwrap_or_default(),
+                ];
+                out.push_str(&csv_row(&row));
+            }
+            for pkg in npm {
+                out.push_str(&csv_row(&[
+                    "npm",
+                    "",
+                    &pkg.name,
+                    &pkg.version,
+                    "",
+                    "",
+                    "",
+                    "",
+                ]));
+            }
+        }
+        ExportFormat::Purl => {
+            for pkg in packages {
+                out.push_str(&maven_purl(pkg));
+                out.push('\n');
+            }
+            for pkg in npm {
+                let name = pkg.name.replacen('@', "%40", 1);
+                out.push_str(&format!("pkg:npm/{name}@{}\n", pkg.version));
+            }
+        }
+    }
+    out
+}
+
+/// The package URL of `pkg`, naming its repository unless it is Maven
+/// Central.
+fn maven_purl(pkg: &LockedPackage) -> String {
+    let mut purl = format!("pkg:maven/{}/{}@{}", pkg.group, pkg.name, pkg.version);
+    if let Some(source) = pkg
+        .source
+        .as_deref()
+        .map(|s| s.trim_end_matches('/'))
+        .filter(|s| *s != MAVEN_CENTRAL && *s != "https://repo1.maven.org/maven2")
+    {
+        purl.push_str("?repository_url=");
+        purl.push_str(
+            &source
+                .replace('%', "%25")
+                .replace('?', "%3F")
+                .replace('#', "%23"),
+        );
+    }
+    purl
+}
+
+/// A CSV line with fields quoted when they contain commas, quotes or line
+/// breaks.
+fn csv_row(fields: &[&str]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", quoted.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+[[package]]
+name = "okio"
+group = "com.squareup.okio"
+version = "3.9.0"
+checksum = "abc123"
+source = "https://repo.maven.apache.org/maven2"
+scope = "compile"
+
+[[package]]
+name = "internal-lib"
+group = "com.example"
+version = "1.0.0"
+source = "https://maven.example.com/releases"
+scope = "test"
+targets = ["jvm", "android"]
+
+[[npm]]
+name = "@js-joda/core"
+requested = "^5.6.0"
+version = "5.6.3"
+"#;
+
+    fn lockfile() -> Lockfile {
+        toml::from_str(LOCKFILE).unwrap()
+    }
+
+    #[tokio::test]
+    async fn lock_keeps_npm_pins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            r#"
+[package]
+name = "web"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[targets]
+js = {}
+
+[npm-dependencies]
+"@js-joda/core" = "^5.6.0"
+"#,
+        )
+        .unwrap();
+        let pinned: Lockfile = toml::from_str(
+            r#"
+[[npm]]
+name = "@js-joda/core"
+requested = "^5.6.0"
+version = "5.6.3"
+"#,
+        )
+        .unwrap();
+        pinned.write_to(&root.join("Kargo.lock")).unwrap();
+
+        // Installed at the pinned version, so no package manager is run.
+        let manifest = Manifest::from_path(&root.join("Kargo.toml")).unwrap();
+        let npm_dir = crate::ops_npm::npm_dir(root);
+        let installed = npm_dir.join("node_modules/@js-joda/core");
+        std::fs::create_dir_all(&installed).unwrap();
+        std::fs::write(
+            installed.join("package.json"),
+            r#"{ "name": "@js-joda/core", "version": "5.6.3" }"#,
+        )
+        .unwrap();
+        let mut package_json =
+            serde_json::to_string_pretty(&crate::ops_npm::package_json(&manifest, Some(&pinned)))
+                .unwrap();
+        package_json.push('\n');
+        std::fs::write(npm_dir.join("package.json"), package_json).unwrap();
+
+        lock(root, false).await.unwrap();
+
+        let relocked = Lockfile::from_path(&root.join("Kargo.lock")).unwrap();
+        assert_eq!(relocked.npm, pinned.npm);
+    }
+
+    #[test]
+    fn purls_name_repositories_other_than_central() {
+        let out = render_export(&lockfile(), ExportFormat::Purl);
+        assert_eq!(
+            out,
+            "pkg:maven/com.example/internal-lib@1.0.0?repository_url=https://maven.example.com/releases\n\
+             pkg:maven/com.squareup.okio/okio@3.9.0\n\
+             pkg:npm/%40js-joda/core@5.6.3\n"
+        );
+    }
+
+    #[test]
+    fn csv_has_one_row_per_package() {
+        let out = render_export(&lockfile(), ExportFormat::Csv);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "ecosystem,group,name,version,scope,targets,sha256,source"
+        );
+        assert_eq!(
+            lines[1],
+            "maven,com.example,internal-lib,1.0.0,test,jvm android,,https://maven.example.com/releases"
+        );
+        assert_eq!(lines[3], "npm,,@js-joda/core,5.6.3,,,,");
+        assert_eq!(
+            csv_row(&["a,b", "say \"hi\""]),
+            "\"a,b\",\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn gradle_verification_lists_checksummed_jars() {
+        let out = render_export(&lockfile(), ExportFormat::GradleVerification);
+        assert!(out.contains(
+            "<component group=\"com.squareup.okio\" name=\"okio\" version=\"3.9.0\">\n         \
+             <artifact name=\"okio-3.9.0.jar\">\n            \
+             <sha256 value=\"abc123\" origin=\"Generated from Kargo.lock\"/>"
+        ));
+        assert!(!out.contains("internal-lib"));
+        assert!(out.ends_with("   </components>\n</verification-metadata>\n"));
+    }
+}
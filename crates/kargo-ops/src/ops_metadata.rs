@@ -0,0 +1,46 @@
+//! Operation: machine-readable project metadata (`kargo metadata`).
+//!
+//! The same JSON document is served by the daemon's `metadata` method and
+//! handed to external subcommands (see [`crate::ops_plugin`]): the project
+//! root, the parsed `Kargo.toml` and the lockfile.
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+
+/// Metadata of the project at `project_dir`.
+pub fn metadata(project_dir: &Path) -> miette::Result<Value> {
+    let manifest_path = project_dir.join("Kargo.toml");
+    if !manifest_path.is_file() {
+        return Err(KargoError::Manifest {
+            message: "No Kargo.toml found in current directory".into(),
+        }
+        .into());
+    }
+    let manifest = Manifest::from_path(&manifest_path)?;
+    let lockfile = Lockfile::for_project(project_dir).unwrap_or_default();
+    render(project_dir, &manifest, &lockfile)
+}
+
+/// The metadata document for an already-loaded manifest and lockfile.
+pub fn render(
+    project_dir: &Path,
+    manifest: &Manifest,
+    lockfile: &Lockfile,
+) -> miette::Result<Value> {
+    let manifest = serde_json::to_value(manifest).map_err(|e| KargoError::Generic {
+        message: format!("Failed to serialize manifest: {e}"),
+    })?;
+    let lockfile = serde_json::to_value(lockfile).map_err(|e| KargoError::Generic {
+        message: format!("Failed to serialize lockfile: {e}"),
+    })?;
+    Ok(json!({
+        "projectDir": project_dir.display().to_string(),
+        "manifest": manifest,
+        "lockfile": lockfile,
+    }))
+}
@@ -1 +1,104 @@
 //! Operation: scaffold a new Kargo project.
+//!
+//! Template rendering lives in [`kargo_core::template`]; this module handles
+//! the version-control setup that follows it (`git init` and an optional
+//! initial commit), mirroring `cargo new --vcs`.
+
+use std::path::Path;
+
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+
+/// Version control system to initialize for a freshly scaffolded project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vcs {
+    Git,
+    None,
+}
+
+impl Vcs {
+    /// Parse a `--vcs` value (`git` or `none`).
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "git" => Ok(Self::Git),
+            "none" => Ok(Self::None),
+            other => Err(KargoError::Generic {
+                message: format!("Unknown VCS '{other}'. Available: git, none"),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Initialize version control in `project_dir`.
+///
+/// With [`Vcs::Git`], a repository is created unless `project_dir` already
+/// lives inside a git work tree (matching Cargo's behavior). When `commit`
+/// is set and a repository was created, all scaffolded files are committed.
+///
+/// Failures are reported as warnings — the project itself is already on disk.
+pub fn init_vcs(project_dir: &Path, vcs: Vcs, commit: bool) {
+    if vcs == Vcs::None {
+        return;
+    }
+
+    if is_inside_git_repo(project_dir) {
+        tracing::debug!(
+            "{} is already inside a git repository, skipping git init",
+            project_dir.display()
+        );
+        return;
+    }
+
+    let dir = project_dir.to_string_lossy().to_string();
+    if let Err(msg) = run_git(&dir, &["init", "--quiet"]) {
+        kargo_util::progress::status_warn("Warning", &format!("git init failed: {msg}"));
+        return;
+    }
+
+    if !commit {
+        return;
+    }
+
+    let result = run_git(&dir, &["add", "--all"]).and_then(|_| {
+        run_git(
+            &dir,
+            &[
+                "commit",
+                "--quiet",
+                "--message",
+                "Initial commit (kargo new)",
+            ],
+        )
+    });
+    match result {
+        Ok(()) => kargo_util::progress::status("Committed", "initial project files"),
+        Err(msg) => kargo_util::progress::status_warn(
+            "Warning",
+            &format!("could not create initial commit: {msg}"),
+        ),
+    }
+}
+
+/// Returns `true` if `dir` is inside an existing git work tree.
+pub fn is_inside_git_repo(dir: &Path) -> bool {
+    CommandBuilder::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .cwd(dir.to_string_lossy())
+        .exec()
+        .map(|out| out.status.success())
+        .unwrap_or(false)
+}
+
+fn run_git(dir: &str, args: &[&str]) -> Result<(), String> {
+    let out = CommandBuilder::new("git")
+        .args(args.iter().copied())
+        .cwd(dir)
+        .exec()
+        .map_err(|e| e.to_string())?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&out.stderr).trim().to_string())
+    }
+}
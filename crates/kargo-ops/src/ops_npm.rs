@@ -0,0 +1,361 @@
+//! Operation: install `[npm-dependencies]` for the `js` and `wasm-js` targets.
+//!
+//! Kargo generates `.kargo/npm/package.json` from the manifest and runs the
+//! configured package manager (`[npm] package-manager`: npm, yarn, or pnpm)
+//! there during `kargo fetch` and `kargo lock`. The exact versions installed
+//! are recorded in the `[[npm]]` entries of `Kargo.lock`; later installs pin
+//! every direct dependency whose range is unchanged to its locked version.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use kargo_core::lockfile::{LockedNpmPackage, Lockfile};
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+
+/// Package manager used to install `[npm-dependencies]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+impl PackageManager {
+    /// The manager selected by `[npm] package-manager`, defaulting to npm.
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        match manifest
+            .npm
+            .as_ref()
+            .and_then(|n| n.package_manager.as_deref())
+        {
+            Some("yarn") => Self::Yarn,
+            Some("pnpm") => Self::Pnpm,
+            _ => Self::Npm,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Pnpm => "pnpm",
+        }
+    }
+
+    fn program(self) -> String {
+        if cfg!(windows) {
+            format!("{}.cmd", self.name())
+        } else {
+            self.name().to_string()
+        }
+    }
+
+    fn install_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Npm => &["install", "--no-audit", "--no-fund"],
+            Self::Yarn | Self::Pnpm => &["install"],
+        }
+    }
+}
+
+/// Directory holding the generated `package.json` and `node_modules`.
+pub fn npm_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".kargo").join("npm")
+}
+
+/// The installed `node_modules` directory.
+pub fn node_modules_dir(project_dir: &Path) -> PathBuf {
+    npm_dir(project_dir).join("node_modules")
+}
+
+/// Generate the `package.json` used to install `[npm-dependencies]`.
+pub fn package_json(manifest: &Manifest, lockfile: Option<&Lockfile>) -> Value {
+    let dependencies: Map<String, Value> = manifest
+        .npm_dependencies
+        .iter()
+        .map(|(name, requested)| {
+            let version = lockfile
+                .and_then(|lf| lf.locked_npm_version(name, requested))
+                .unwrap_or(requested);
+            (name.clone(), json!(version))
+        })
+        .collect();
+    json!({
+        "name": format!("{}-npm", manifest.package.name),
+        "version": manifest.package.version,
+        "private": true,
+        "dependencies": dependencies,
+    })
+}
+
+/// Install `[npm-dependencies]` and return the lockfile entries for them.
+///
+/// Skips the package manager when every dependency is already installed at
+/// its locked version. Returns an empty list if there are no npm dependencies.
+pub fn install(
+    project_dir: &Path,
+    manifest: &Manifest,
+    lockfile: Option<&Lockfile>,
+    verbose: bool,
+) -> miette::Result<Vec<LockedNpmPackage>> {
+    if manifest.npm_dependencies.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let dir = npm_dir(project_dir);
+    std::fs::create_dir_all(&dir).map_err(KargoError::Io)?;
+    let mut content =
+        serde_json::to_string_pretty(&package_json(manifest, lockfile)).map_err(|e| {
+            KargoError::Generic {
+                message: format!("Failed to serialize package.json: {e}"),
+            }
+        })?;
+    content.push('\n');
+    let package_json_path = dir.join("package.json");
+    let changed = std::fs::read_to_string(&package_json_path).ok().as_deref() != Some(&content);
+    if changed {
+        std::fs::write(&package_json_path, &content).map_err(KargoError::Io)?;
+    }
+
+    let node_modules = node_modules_dir(project_dir);
+    let installed = !changed
+        && manifest.npm_dependencies.iter().all(|(name, requested)| {
+            let locked = lockfile.and_then(|lf| lf.locked_npm_version(name, requested));
+            locked.is_some() && installed_version(&node_modules, name).as_deref() == locked
+        });
+
+    if !installed {
+        let pm = PackageManager::from_manifest(manifest);
+        kargo_util::progress::status(
+            "Installing",
+            &format!(
+                "{} npm package(s) with {}",
+                manifest.npm_dependencies.len(),
+                pm.name()
+            ),
+        );
+        let output = kargo_util::process::CommandBuilder::new(pm.program())
+            .args(pm.install_args().iter().map(|a| a.to_string()))
+            .cwd(dir.to_string_lossy().to_string())
+            .exec()
+            .map_err(|e| KargoError::Generic {
+                message: format!(
+                    "Failed to run `{} install` (is {} on PATH?): {e}",
+                    pm.name(),
+                    pm.name()
+                ),
+            })?;
+        if verbose {
+            eprint!("{}", String::from_utf8_lossy(&output.stdout));
+        }
+        if !output.status.success() {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "`{} install` failed:\n{}",
+                    pm.name(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            }
+            .into());
+        }
+    }
+
+    manifest
+        .npm_dependencies
+        .iter()
+        .map(|(name, requested)| {
+            let version =
+                installed_version(&node_modules, name).ok_or_else(|| KargoError::Generic {
+                    message: format!(
+                        "npm package '{name}' is missing from node_modules after install"
+                    ),
+                })?;
+            Ok(LockedNpmPackage {
+                name: name.clone(),
+                requested: requested.clone(),
+                version,
+                members: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+fn read_package_json(node_modules: &Path, name: &str) -> Option<Value> {
+    let content = std::fs::read_to_string(node_modules.join(name).join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Version of an installed package, from its `package.json`.
+pub fn installed_version(node_modules: &Path, name: &str) -> Option<String> {
+    read_package_json(node_modules, name)?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// ES module entry point of an installed package, relative to its directory:
+/// `module`, then a string `exports`, then `main`, then `index.js`.
+pub fn module_entry(node_modules: &Path, name: &str) -> Option<String> {
+    let pkg = read_package_json(node_modules, name)?;
+    let entry = ["module", "exports", "main"]
+        .iter()
+        .find_map(|key| pkg.get(*key).and_then(Value::as_str))
+        .unwrap_or("index.js");
+    Some(entry.trim_start_matches("./").to_string())
+}
+
+/// Browser import map resolving each package to `<prefix><name>/<entry>`.
+pub fn import_map(node_modules: &Path, packages: &[LockedNpmPackage], prefix: &str) -> Value {
+    let imports: Map<String, Value> = packages
+        .iter()
+        .filter_map(|p| {
+            let entry = module_entry(node_modules, &p.name)?;
+            Some((p.name.clone(), json!(format!("{prefix}{}/{entry}", p.name))))
+        })
+        .collect();
+    json!({ "imports": imports })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest::parse_toml(
+            r#"
+[package]
+name = "web"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[targets]
+wasm-js = {}
+
+[npm]
+package-manager = "pnpm"
+
+[npm-dependencies]
+left-pad = "^1.3.0"
+"@js-joda/core" = "^5.6.0"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn install_fake(node_modules: &Path, name: &str, version: &str, extra: Value) {
+        let dir = node_modules.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut pkg = json!({ "name": name, "version": version });
+        pkg.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        std::fs::write(dir.join("package.json"), pkg.to_string()).unwrap();
+    }
+
+    #[test]
+    fn package_json_pins_locked_versions() {
+        let m = manifest();
+        assert_eq!(PackageManager::from_manifest(&m), PackageManager::Pnpm);
+
+        let unlocked = package_json(&m, None);
+        assert_eq!(unlocked["dependencies"]["left-pad"], "^1.3.0");
+
+        let lockfile = Lockfile {
+            toolchain: None,
+            environment: None,
+            package: vec![],
+            npm: vec![
+                LockedNpmPackage {
+                    name: "left-pad".into(),
+                    requested: "^1.3.0".into(),
+                    version: "1.3.0".into(),
+                    members: Vec::new(),
+                },
+                LockedNpmPackage {
+                    name: "@js-joda/core".into(),
+                    requested: "^4.0.0".into(),
+                    version: "4.3.1".into(),
+                    members: Vec::new(),
+                },
+            ],
+            file: Vec::new(),
+            members: Vec::new(),
+        };
+        let pinned = package_json(&m, Some(&lockfile));
+        assert_eq!(pinned["dependencies"]["left-pad"], "1.3.0");
+        // Range changed since locking: not pinned.
+        assert_eq!(pinned["dependencies"]["@js-joda/core"], "^5.6.0");
+    }
+
+    #[test]
+    fn install_skips_package_manager_when_up_to_date() {
+        let tmp = tempfile::tempdir().unwrap();
+        let m = manifest();
+        let node_modules = node_modules_dir(tmp.path());
+        install_fake(&node_modules, "left-pad", "1.3.0", json!({}));
+        install_fake(&node_modules, "@js-joda/core", "5.6.3", json!({}));
+
+        let lockfile = Lockfile {
+            toolchain: None,
+            environment: None,
+            package: vec![],
+            npm: vec![
+                LockedNpmPackage {
+                    name: "left-pad".into(),
+                    requested: "^1.3.0".into(),
+                    version: "1.3.0".into(),
+                    members: Vec::new(),
+                },
+                LockedNpmPackage {
+                    name: "@js-joda/core".into(),
+                    requested: "^5.6.0".into(),
+                    version: "5.6.3".into(),
+                    members: Vec::new(),
+                },
+            ],
+            file: Vec::new(),
+            members: Vec::new(),
+        };
+        std::fs::create_dir_all(npm_dir(tmp.path())).unwrap();
+        let mut content = serde_json::to_string_pretty(&package_json(&m, Some(&lockfile))).unwrap();
+        content.push('\n');
+        std::fs::write(npm_dir(tmp.path()).join("package.json"), content).unwrap();
+
+        // pnpm is not needed (and not installed here): nothing changed.
+        let locked = install(tmp.path(), &m, Some(&lockfile), false).unwrap();
+        assert_eq!(
+            locked,
+            lockfile.npm.iter().rev().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn import_map_uses_module_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        install_fake(
+            tmp.path(),
+            "a",
+            "1.0.0",
+            json!({ "module": "./dist/a.mjs", "main": "a.js" }),
+        );
+        install_fake(tmp.path(), "b", "1.0.0", json!({ "main": "lib/b.js" }));
+        install_fake(tmp.path(), "c", "1.0.0", json!({}));
+        let packages: Vec<LockedNpmPackage> = ["a", "b", "c", "missing"]
+            .iter()
+            .map(|n| LockedNpmPackage {
+                name: n.to_string(),
+                requested: "*".into(),
+                version: "1.0.0".into(),
+                members: Vec::new(),
+            })
+            .collect();
+
+        let map = import_map(tmp.path(), &packages, "./node_modules/");
+        assert_eq!(map["imports"]["a"], "./node_modules/a/dist/a.mjs");
+        assert_eq!(map["imports"]["b"], "./node_modules/b/lib/b.js");
+        assert_eq!(map["imports"]["c"], "./node_modules/c/index.js");
+        assert!(map["imports"].get("missing").is_none());
+    }
+}
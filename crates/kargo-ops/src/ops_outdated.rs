@@ -6,11 +6,13 @@ use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
-use kargo_core::manifest::Manifest;
+use kargo_core::manifest::{Manifest, UpdatePolicy};
 use kargo_maven::download;
-use kargo_maven::metadata;
+use kargo_maven::metadata::{self, MavenMetadata};
+use kargo_maven::repository::MavenRepository;
 use kargo_resolver::resolver;
 use kargo_resolver::version::MavenVersion;
+use kargo_util::http_cache::HttpCache;
 
 /// Options for `kargo outdated`.
 #[derive(Default)]
@@ -27,6 +29,9 @@ struct OutdatedEntry {
     latest: String,
     is_major: bool,
     section: String,
+    /// The `[update-policy]` rule that stops `kargo update` from reaching
+    /// `latest`.
+    held_back: Option<UpdatePolicy>,
 }
 
 /// Check direct dependencies for available updates and print a report.
@@ -34,18 +39,31 @@ pub async fn outdated(project_root: &Path, opts: &OutdatedOptions) -> miette::Re
     let manifest_path = project_root.join("Kargo.toml");
     let manifest = Manifest::from_path(&manifest_path)?;
     let repos = resolver::build_repos(&manifest);
+    resolver::configure_network(&manifest);
     let sp = kargo_util::progress::spinner("Checking for outdated dependencies...");
     let client = download::build_client()?;
 
     let mut declared = collect_declared_deps_with_section(&manifest);
 
-    // Include the Kotlin version from [package]
-    declared.push((
-        "org.jetbrains.kotlin".to_string(),
-        "kotlin-stdlib".to_string(),
-        manifest.package.kotlin.clone(),
-        "package.kotlin".to_string(),
-    ));
+    // Include the Kotlin version from [package]; aliases compare their
+    // Kargo.lock pin (unpinned aliases always track the newest release).
+    let kotlin_current = match manifest.package.kotlin_req() {
+        Some(req) if !req.is_exact() => kargo_core::lockfile::Lockfile::for_project(project_root)
+            .ok()
+            .and_then(|lf| {
+                lf.pinned_kotlin(&manifest.package.kotlin)
+                    .map(str::to_string)
+            }),
+        _ => Some(manifest.package.kotlin.clone()),
+    };
+    if let Some(current) = kotlin_current {
+        declared.push((
+            "org.jetbrains.kotlin".to_string(),
+            "kotlin-stdlib".to_string(),
+            current,
+            "package.kotlin".to_string(),
+        ));
+    }
 
     let semaphore = Arc::new(Semaphore::new(8));
     let mut join_set = JoinSet::new();
@@ -54,34 +72,29 @@ pub async fn outdated(project_root: &Path, opts: &OutdatedOptions) -> miette::Re
         let repos = repos.clone();
         let client = client.clone();
         let sem = semaphore.clone();
+        let policy = manifest.update_policy_for(&group, &artifact);
 
         join_set.spawn(async move {
             let _permit = sem.acquire().await.unwrap();
-            for repo in &repos {
-                let url = repo.metadata_url(&group, &artifact);
-                match download::download_text(&client, repo, &url).await {
-                    Ok(Some(xml)) => {
-                        if let Ok(meta) = metadata::parse_metadata(&xml) {
-                            if let Some(ref latest) = meta.release.or(meta.latest) {
-                                let current = MavenVersion::parse(&version);
-                                let latest_v = MavenVersion::parse(latest);
-                                if latest_v > current {
-                                    let is_major = is_major_bump(&version, latest);
-                                    return Ok(Some(OutdatedEntry {
-                                        group,
-                                        artifact,
-                                        current: version,
-                                        latest: latest.clone(),
-                                        is_major,
-                                        section,
-                                    }));
-                                }
-                            }
-                        }
-                        break;
-                    }
-                    Ok(None) => continue,
-                    Err(e) => return Err(e),
+            let Some(meta) = fetch_metadata(&client, &repos, &group, &artifact).await? else {
+                return Ok(None);
+            };
+            if let Some(ref latest) = meta.release.or(meta.latest) {
+                let current = MavenVersion::parse(&version);
+                let latest_v = MavenVersion::parse(latest);
+                if latest_v > current {
+                    let is_major = is_major_bump(&version, latest);
+                    let held_back =
+                        policy.filter(|p| !crate::ops_update::policy_allows(*p, &version, latest));
+                    return Ok(Some(OutdatedEntry {
+                        group,
+                        artifact,
+                        current: version,
+                        latest: latest.clone(),
+                        is_major,
+                        section,
+                        held_back,
+                    }));
                 }
             }
             Ok(None)
@@ -120,7 +133,10 @@ pub async fn outdated(project_root: &Path, opts: &OutdatedOptions) -> miette::Re
         } else {
             format!("{}:{}", entry.group, entry.artifact)
         };
-        let marker = if entry.is_major { " (major)" } else { "" };
+        let mut marker = if entry.is_major { " (major)" } else { "" }.to_string();
+        if let Some(policy) = entry.held_back {
+            marker.push_str(&format!(" (held back: update-policy {})", policy.as_str()));
+        }
         println!(
             "{:<50} {:<15} {:<15} {}{}",
             name, entry.current, entry.latest, entry.section, marker
@@ -130,6 +146,65 @@ pub async fn outdated(project_root: &Path, opts: &OutdatedOptions) -> miette::Re
     Ok(())
 }
 
+/// Fetch `maven-metadata.xml` for `group:artifact` from every repository
+/// and merge them, so versions published only to a secondary repository
+/// (e.g. a dev or EAP repository) are not missed. A repository that fails
+/// is skipped as long as another one answers.
+pub(crate) async fn fetch_metadata(
+    client: &reqwest::Client,
+    repos: &[MavenRepository],
+    group: &str,
+    artifact: &str,
+) -> miette::Result<Option<MavenMetadata>> {
+    let mut found = Vec::new();
+    let mut first_error = None;
+    let http_cache = HttpCache::user();
+    for repo in kargo_maven::repository::for_group(repos, group) {
+        let url = repo.metadata_url(group, artifact);
+        match download::download_text_cached(client, repo, &url, &http_cache).await {
+            Ok(Some(xml)) => {
+                if let Ok(meta) = metadata::parse_metadata(&xml) {
+                    found.push(meta);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("{group}:{artifact}: metadata from {} failed: {e}", repo.url);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match (merge_metadata(found), first_error) {
+        (None, Some(e)) => Err(e),
+        (merged, _) => Ok(merged),
+    }
+}
+
+/// Merge metadata from several repositories: the union of their versions,
+/// and the highest `release` and `latest` markers.
+pub(crate) fn merge_metadata(metas: Vec<MavenMetadata>) -> Option<MavenMetadata> {
+    let highest = |a: Option<String>, b: Option<String>| match (a, b) {
+        (Some(a), Some(b)) => {
+            if MavenVersion::parse(&b) > MavenVersion::parse(&a) {
+                Some(b)
+            } else {
+                Some(a)
+            }
+        }
+        (a, b) => a.or(b),
+    };
+    metas.into_iter().reduce(|mut merged, meta| {
+        for version in meta.versions {
+            if !merged.versions.contains(&version) {
+                merged.versions.push(version);
+            }
+        }
+        merged.release = highest(merged.release.take(), meta.release);
+        merged.latest = highest(merged.latest.take(), meta.latest);
+        merged
+    })
+}
+
 /// Collect direct dependencies with their section label for display.
 fn collect_declared_deps_with_section(
     manifest: &Manifest,
@@ -147,7 +222,7 @@ fn collect_declared_deps_with_section(
             Dependency::Detailed(d) => {
                 Some((d.group.clone(), d.artifact.clone(), d.version.clone()))
             }
-            Dependency::Catalog(_) => None,
+            Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
         }
     };
 
@@ -161,6 +236,11 @@ fn collect_declared_deps_with_section(
             declared.push((g, a, v, "dev-dependencies".to_string()));
         }
     }
+    for dep in manifest.integration_test_dependencies.values() {
+        if let Some((g, a, v)) = extract(dep) {
+            declared.push((g, a, v, "integration-test-dependencies".to_string()));
+        }
+    }
     for (target_name, target_deps) in &manifest.target {
         for dep in target_deps.dependencies.values() {
             if let Some((g, a, v)) = extract(dep) {
@@ -194,3 +274,30 @@ fn is_major_bump(current: &str, latest: &str) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(release: &str, versions: &[&str]) -> MavenMetadata {
+        MavenMetadata {
+            release: Some(release.to_string()),
+            latest: Some(release.to_string()),
+            versions: versions.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn metadata_from_all_repositories_is_merged() {
+        let central = meta("1.7.0", &["1.6.0", "1.7.0"]);
+        let dev = meta("1.8.0-dev-123", &["1.7.0", "1.8.0-dev-123"]);
+        let merged = merge_metadata(vec![central, dev]).unwrap();
+        assert_eq!(merged.versions, ["1.6.0", "1.7.0", "1.8.0-dev-123"]);
+        assert_eq!(merged.release.as_deref(), Some("1.8.0-dev-123"));
+
+        let secondary_newer = merge_metadata(vec![meta("2.0.0", &[]), meta("2.1.0", &[])]);
+        assert_eq!(secondary_newer.unwrap().latest.as_deref(), Some("2.1.0"));
+        assert!(merge_metadata(Vec::new()).is_none());
+    }
+}
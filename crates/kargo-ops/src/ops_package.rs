@@ -0,0 +1,511 @@
+//! Operation: package the release JAR with its sources and documentation
+//! (`kargo package --sources --javadoc`).
+//!
+//! The classified JARs sit next to the release JAR in
+//! `build/<target>/release/output/`, named as Maven expects them:
+//!
+//! ```text
+//! <name>-<version>.jar
+//! <name>-<version>-sources.jar   main source sets, by package
+//! <name>-<version>-javadoc.jar   Dokka HTML of the main source sets
+//! ```
+//!
+//! With a single main source set the sources JAR holds its packages at the
+//! root; with several (`commonMain`, `jvmMain`, ...) each one is under a
+//! directory named after it, as the Kotlin Gradle plugin lays them out.
+//! Dokka has no standalone distribution, so its CLI and plugins are
+//! resolved into the project cache, checked against `kargo.trust` JAR by
+//! JAR, and run on the project's JDK. Each JAR
+//! is rewritten only when the files it is made from change.
+
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::fingerprint;
+use kargo_compiler::jar::JarManifest;
+use kargo_core::target::KotlinTarget;
+use kargo_util::errors::KargoError;
+use kargo_util::progress::status;
+
+use crate::ops_build::{BuildOptions, BuildResult};
+
+/// Dokka release used for `-javadoc.jar`.
+pub const DOKKA_VERSION: &str = "2.0.0";
+
+/// Entry point of the Dokka CLI.
+const DOKKA_MAIN_CLASS: &str = "org.jetbrains.dokka.MainKt";
+
+/// Dokka plugins producing the HTML output, without their version for the
+/// `org.jetbrains.dokka` ones.
+const DOKKA_PLUGINS: &[&str] = &[
+    "org.jetbrains.dokka:dokka-base",
+    "org.jetbrains.dokka:analysis-kotlin-descriptors",
+    "org.jetbrains.kotlinx:kotlinx-html-jvm:0.8.0",
+    "org.freemarker:freemarker:2.3.32",
+];
+
+/// Options for `kargo package`.
+#[derive(Debug, Clone, Default)]
+pub struct PackageOptions {
+    pub target: Option<String>,
+    /// Also write `-sources.jar`.
+    pub sources: bool,
+    /// Also write `-javadoc.jar`.
+    pub javadoc: bool,
+    pub verbose: bool,
+}
+
+/// The JARs `kargo package` produced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageOutput {
+    pub jar: PathBuf,
+    pub sources_jar: Option<PathBuf>,
+    pub javadoc_jar: Option<PathBuf>,
+}
+
+/// Build the release JAR of the project in `project_dir` and the
+/// classified JARs `opts` asks for.
+pub async fn package(project_dir: &Path, opts: &PackageOptions) -> miette::Result<PackageOutput> {
+    let build = crate::ops_build::build(
+        project_dir,
+        &BuildOptions {
+            target: opts.target.clone(),
+            release: true,
+            verbose: opts.verbose,
+            ..Default::default()
+        },
+    )
+    .await?;
+    let jar = build
+        .output_jar
+        .clone()
+        .ok_or_else(|| KargoError::Generic {
+            message: format!("The release build for {} produced no JAR", build.target),
+        })?;
+    if (opts.sources || opts.javadoc)
+        && !matches!(build.target, KotlinTarget::Jvm | KotlinTarget::Android)
+    {
+        return Err(KargoError::Generic {
+            message: format!(
+                "--sources and --javadoc package JVM libraries; the {} target has no JAR to \
+                 publish them with",
+                build.target
+            ),
+        }
+        .into());
+    }
+
+    let sources_jar = match opts.sources {
+        true => Some(sources_jar(project_dir, &build)?),
+        false => None,
+    };
+    let javadoc_jar = match opts.javadoc {
+        true => Some(javadoc_jar(project_dir, &build, opts.verbose).await?),
+        false => None,
+    };
+    Ok(PackageOutput {
+        jar,
+        sources_jar,
+        javadoc_jar,
+    })
+}
+
+/// Path of the `<name>-<version>-<classifier>.jar` next to the build's JAR.
+pub fn classifier_jar_path(build: &BuildResult, classifier: &str) -> PathBuf {
+    build.build_dir.join("output").join(format!(
+        "{}-{}-{classifier}.jar",
+        build.manifest.package.name, build.manifest.package.version
+    ))
+}
+
+/// The existing Kotlin directories of each main source set, by name.
+fn main_source_sets(build: &BuildResult) -> Vec<(String, Vec<PathBuf>)> {
+    build
+        .discovered
+        .main_sources
+        .iter()
+        .map(|ss| {
+            let dirs = ss.kotlin_dirs.iter().filter(|d| d.is_dir()).cloned();
+            (ss.name.clone(), dirs.collect::<Vec<_>>())
+        })
+        .filter(|(_, dirs)| !dirs.is_empty())
+        .collect()
+}
+
+/// Write `-sources.jar` for `build`, unless it is up to date.
+pub fn sources_jar(project_dir: &Path, build: &BuildResult) -> miette::Result<PathBuf> {
+    let jar = classifier_jar_path(build, "sources");
+    let fp_dir =
+        fingerprint::storage_dir(project_dir, build.target.kebab_name(), &build.profile_name);
+    let sets = main_source_sets(build);
+    let staging = build.build_dir.join("package").join("sources");
+    if write_sources_jar(project_dir, &sets, &staging, &jar, &fp_dir)? {
+        status("Packaged", &file_name(&jar));
+    }
+    Ok(jar)
+}
+
+/// Write `jar` from the source sets `sets`, staging a prefixed layout in
+/// `staging` when there are several. Returns false when the fingerprint in
+/// `fp_dir` shows the JAR is up to date.
+fn write_sources_jar(
+    project_dir: &Path,
+    sets: &[(String, Vec<PathBuf>)],
+    staging: &Path,
+    jar: &Path,
+    fp_dir: &Path,
+) -> miette::Result<bool> {
+    let dirs: Vec<PathBuf> = sets.iter().flat_map(|(_, d)| d.iter().cloned()).collect();
+    let mut data = String::from("sources\n");
+    for (name, _) in sets {
+        data.push_str(&format!("set {name}\n"));
+    }
+    let fp = inputs_fingerprint(project_dir, &dirs, &data)?;
+    let fp_path = fp_dir.join("sources-jar.fingerprint");
+    if is_fresh(jar, &fp_path, &fp) {
+        return Ok(false);
+    }
+
+    let _ = std::fs::remove_dir_all(staging);
+    let roots: Vec<PathBuf> = match sets {
+        [(_, dirs)] => dirs.clone(),
+        _ => {
+            for (name, dirs) in sets {
+                for dir in dirs {
+                    copy_tree(dir, &staging.join(name))?;
+                }
+            }
+            vec![staging.to_path_buf()]
+        }
+    };
+    let roots: Vec<&Path> = roots.iter().map(PathBuf::as_path).collect();
+    kargo_compiler::jar::write_jar(jar, &roots, &classifier_manifest())?;
+    save_fingerprint(&fp_path, &fp)?;
+    Ok(true)
+}
+
+/// Write `-javadoc.jar` for `build` from Dokka's HTML output, unless it is
+/// up to date.
+pub async fn javadoc_jar(
+    project_dir: &Path,
+    build: &BuildResult,
+    verbose: bool,
+) -> miette::Result<PathBuf> {
+    let jar = classifier_jar_path(build, "javadoc");
+    let fp_dir =
+        fingerprint::storage_dir(project_dir, build.target.kebab_name(), &build.profile_name);
+    let dirs: Vec<PathBuf> = main_source_sets(build)
+        .into_iter()
+        .flat_map(|(_, dirs)| dirs)
+        .collect();
+    let mut data = format!("javadoc\ndokka {DOKKA_VERSION}\n");
+    for dep in &build.classpath.compile_jars {
+        data.push_str(&format!("classpath {}\n", file_name(dep)));
+    }
+    let fp = inputs_fingerprint(project_dir, &dirs, &data)?;
+    let fp_path = fp_dir.join("javadoc-jar.fingerprint");
+    if is_fresh(&jar, &fp_path, &fp) {
+        return Ok(jar);
+    }
+
+    status(
+        "Documenting",
+        &format!(
+            "{} v{} with Dokka {DOKKA_VERSION}",
+            build.manifest.package.name, build.manifest.package.version
+        ),
+    );
+    let cli = crate::resolve_pinned_tool_classpath(
+        project_dir,
+        &build.manifest,
+        "dokka",
+        [format!("org.jetbrains.dokka:dokka-cli:{DOKKA_VERSION}").as_str()],
+    )
+    .await?;
+    let plugin_coords = dokka_plugin_coords();
+    let plugins = crate::resolve_pinned_tool_classpath(
+        project_dir,
+        &build.manifest,
+        "dokka",
+        plugin_coords.iter().map(String::as_str),
+    )
+    .await?;
+
+    let out = build.build_dir.join("package").join("javadoc");
+    let _ = std::fs::remove_dir_all(&out);
+    std::fs::create_dir_all(&out).map_err(KargoError::Io)?;
+    let args = dokka_args(
+        &build.manifest.package.name,
+        &build.manifest.package.version,
+        &out,
+        &plugins,
+        &dirs,
+        &build.classpath.compile_jars,
+        &build.preflight.java_target,
+    );
+    let cp = std::env::join_paths(&cli)
+        .map_err(|e| KargoError::Generic {
+            message: format!("Invalid Dokka classpath: {e}"),
+        })?
+        .to_string_lossy()
+        .to_string();
+    if verbose {
+        eprintln!("  dokka: {DOKKA_MAIN_CLASS} {}", args.join(" "));
+    }
+    let java_bin = build.preflight.jdk.home.join("bin").join("java");
+    let output = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(build.preflight.jdk.java_accepts_argfiles())
+        .arg("-cp")
+        .arg(cp)
+        .arg(DOKKA_MAIN_CLASS)
+        .args(args)
+        .cwd(project_dir.to_string_lossy().to_string())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run Dokka: {e}"),
+        })?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "Dokka failed to document {}:\n{}",
+                build.manifest.package.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+
+    kargo_compiler::jar::write_jar(&jar, &[out.as_path()], &classifier_manifest())?;
+    save_fingerprint(&fp_path, &fp)?;
+    status("Packaged", &file_name(&jar));
+    Ok(jar)
+}
+
+/// Maven coordinates of the Dokka plugins, with versions.
+fn dokka_plugin_coords() -> Vec<String> {
+    DOKKA_PLUGINS
+        .iter()
+        .map(|coord| match coord.matches(':').count() {
+            1 => format!("{coord}:{DOKKA_VERSION}"),
+            _ => coord.to_string(),
+        })
+        .collect()
+}
+
+/// Dokka CLI arguments documenting `sources` as one JVM source set.
+fn dokka_args(
+    name: &str,
+    version: &str,
+    out: &Path,
+    plugins: &[PathBuf],
+    sources: &[PathBuf],
+    classpath: &[PathBuf],
+    java_target: &str,
+) -> Vec<String> {
+    let list = |paths: &[PathBuf]| {
+        paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join(";")
+    };
+    let jdk = java_target.strip_prefix("1.").unwrap_or(java_target);
+    let mut source_set = format!(
+        "-src {} -analysisPlatform jvm -jdkVersion {jdk}",
+        list(sources)
+    );
+    if !classpath.is_empty() {
+        source_set.push_str(&format!(" -classpath {}", list(classpath)));
+    }
+    vec![
+        "-moduleName".into(),
+        name.into(),
+        "-moduleVersion".into(),
+        version.into(),
+        "-outputDir".into(),
+        out.to_string_lossy().to_string(),
+        "-pluginsClasspath".into(),
+        list(plugins),
+        "-sourceSet".into(),
+        source_set,
+    ]
+}
+
+/// Manifest of a classified JAR: only `Manifest-Version` and `Created-By`.
+fn classifier_manifest() -> JarManifest {
+    let mut manifest = JarManifest::default();
+    manifest.set("Manifest-Version", "1.0");
+    manifest.set("Created-By", "Kargo");
+    manifest
+}
+
+/// Hash of `extra` and the relative path and content of every file below
+/// `dirs`.
+fn inputs_fingerprint(project_dir: &Path, dirs: &[PathBuf], extra: &str) -> miette::Result<String> {
+    let mut data = extra.to_string();
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_all_files(dir, &mut files);
+        files.sort();
+        for file in files {
+            let rel = file.strip_prefix(project_dir).unwrap_or(&file);
+            data.push_str(&format!(
+                "{} {}\n",
+                rel.display(),
+                kargo_util::hash::sha256_file(&file)?
+            ));
+        }
+    }
+    Ok(kargo_util::hash::sha256_bytes(data.as_bytes()))
+}
+
+fn is_fresh(jar: &Path, fp_path: &Path, fp: &str) -> bool {
+    jar.is_file() && std::fs::read_to_string(fp_path).is_ok_and(|stored| stored.trim() == fp)
+}
+
+fn save_fingerprint(fp_path: &Path, fp: &str) -> miette::Result<()> {
+    if let Some(parent) = fp_path.parent() {
+        std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+    }
+    std::fs::write(fp_path, fp).map_err(KargoError::Io)?;
+    Ok(())
+}
+
+fn collect_all_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_all_files(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// Copy the files below `from` into `to`, keeping their relative paths.
+fn copy_tree(from: &Path, to: &Path) -> miette::Result<()> {
+    let mut files = Vec::new();
+    collect_all_files(from, &mut files);
+    for file in files {
+        let Ok(rel) = file.strip_prefix(from) else {
+            continue;
+        };
+        let dest = to.join(rel);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+        }
+        std::fs::copy(&file, &dest).map_err(KargoError::Io)?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jar_entries(jar: &Path) -> Vec<String> {
+        let archive = zip::ZipArchive::new(std::fs::File::open(jar).unwrap()).unwrap();
+        archive
+            .file_names()
+            .filter(|n| !n.ends_with('/') && !n.starts_with("META-INF/"))
+            .map(String::from)
+            .collect()
+    }
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn sources_jar_is_flat_for_one_set_and_prefixed_for_several() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let common = root.join("src/commonMain/kotlin");
+        let jvm = root.join("src/jvmMain/kotlin");
+        write(&common.join("com/example/Lib.kt"), "package com.example");
+        write(&jvm.join("com/example/Jvm.kt"), "package com.example");
+        let fp_dir = root.join("fp");
+        let staging = root.join("staging");
+
+        let single = root.join("single-sources.jar");
+        let sets = vec![("main".to_string(), vec![common.clone()])];
+        assert!(write_sources_jar(root, &sets, &staging, &single, &fp_dir).unwrap());
+        assert_eq!(jar_entries(&single), ["com/example/Lib.kt"]);
+
+        let multi = root.join("multi-sources.jar");
+        let sets = vec![
+            ("commonMain".to_string(), vec![common]),
+            ("jvmMain".to_string(), vec![jvm]),
+        ];
+        assert!(write_sources_jar(root, &sets, &staging, &multi, &root.join("fp2")).unwrap());
+        let mut entries = jar_entries(&multi);
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                "commonMain/com/example/Lib.kt",
+                "jvmMain/com/example/Jvm.kt"
+            ]
+        );
+    }
+
+    #[test]
+    fn sources_jar_is_rewritten_only_when_sources_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let dir = root.join("src/main/kotlin");
+        write(&dir.join("Main.kt"), "fun main() {}");
+        let sets = vec![("main".to_string(), vec![dir.clone()])];
+        let jar = root.join("app-sources.jar");
+        let staging = root.join("staging");
+        let fp_dir = root.join("fp");
+
+        assert!(write_sources_jar(root, &sets, &staging, &jar, &fp_dir).unwrap());
+        assert!(!write_sources_jar(root, &sets, &staging, &jar, &fp_dir).unwrap());
+        write(&dir.join("Main.kt"), "fun main() { println() }");
+        assert!(write_sources_jar(root, &sets, &staging, &jar, &fp_dir).unwrap());
+        std::fs::remove_file(&jar).unwrap();
+        assert!(write_sources_jar(root, &sets, &staging, &jar, &fp_dir).unwrap());
+    }
+
+    #[test]
+    fn dokka_documents_one_jvm_source_set() {
+        let args = dokka_args(
+            "lib",
+            "1.0.0",
+            Path::new("out"),
+            &[PathBuf::from("base.jar"), PathBuf::from("html.jar")],
+            &[PathBuf::from("src/main/kotlin")],
+            &[PathBuf::from("dep.jar")],
+            "1.8",
+        );
+        assert_eq!(
+            args[..8],
+            [
+                "-moduleName",
+                "lib",
+                "-moduleVersion",
+                "1.0.0",
+                "-outputDir",
+                "out",
+                "-pluginsClasspath",
+                "base.jar;html.jar"
+            ]
+        );
+        assert_eq!(
+            args[9],
+            "-src src/main/kotlin -analysisPlatform jvm -jdkVersion 8 -classpath dep.jar"
+        );
+        assert!(dokka_plugin_coords()
+            .contains(&format!("org.jetbrains.dokka:dokka-base:{DOKKA_VERSION}")));
+    }
+}
@@ -0,0 +1,73 @@
+//! Operation: external subcommands (`kargo <cmd>` running `kargo-<cmd>`).
+//!
+//! Commands Kargo does not know are looked up as `kargo-<cmd>` executables
+//! in `~/.kargo/bin` and on `PATH` (see [`kargo_plugin::registry`]) and run
+//! with the remaining arguments, in the current directory, with stdio
+//! inherited. Their exit code becomes Kargo's. The environment tells them
+//! where they run:
+//!
+//! - `KARGO`: the running `kargo` executable, to call back into it.
+//! - `KARGO_PLUGIN`: the subcommand name.
+//! - `KARGO_PROJECT_DIR`: the root of the enclosing project, if any.
+//! - `KARGO_METADATA`: inside a project, a JSON file holding what
+//!   `kargo metadata` prints.
+
+use std::path::Path;
+
+use kargo_plugin::registry::{self, ExternalCommand};
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+
+/// Installed external subcommands.
+pub fn list() -> Vec<ExternalCommand> {
+    registry::list()
+}
+
+/// Run `kargo <name> <args>` through its external executable, returning
+/// the exit code.
+pub fn run(cwd: &Path, name: &str, args: &[String]) -> miette::Result<i32> {
+    let command = registry::find(name).ok_or_else(|| KargoError::Generic {
+        message: format!(
+            "no such command: `{name}`\n\n\
+             Run `kargo --list` to see the built-in commands and installed plugins, \
+             which are executables named `{}{name}` on PATH.",
+            registry::SUBCOMMAND_PREFIX
+        ),
+    })?;
+
+    let mut cmd = CommandBuilder::new(command.path.to_string_lossy())
+        .args(args.iter().cloned())
+        .env("KARGO_PLUGIN", name);
+    if let Ok(exe) = std::env::current_exe() {
+        cmd = cmd.env("KARGO", exe.to_string_lossy());
+    }
+    // Kept alive until the plugin exits.
+    let mut metadata_file = None;
+    if let Some(project_dir) = kargo_util::fs::find_ancestor_with(cwd, "Kargo.toml") {
+        cmd = cmd.env("KARGO_PROJECT_DIR", project_dir.to_string_lossy());
+        match crate::ops_metadata::metadata(&project_dir) {
+            Ok(metadata) => {
+                let file = tempfile::Builder::new()
+                    .prefix("kargo-metadata-")
+                    .suffix(".json")
+                    .tempfile()
+                    .map_err(KargoError::Io)?;
+                serde_json::to_writer(file.as_file(), &metadata).map_err(|e| {
+                    KargoError::Generic {
+                        message: format!("Failed to write project metadata: {e}"),
+                    }
+                })?;
+                cmd = cmd.env("KARGO_METADATA", file.path().to_string_lossy());
+                metadata_file = Some(file);
+            }
+            // A broken manifest should not stop plugins that do not need it.
+            Err(e) => tracing::warn!("No project metadata for kargo-{name}: {e}"),
+        }
+    }
+
+    let status = cmd.status().map_err(|e| KargoError::Generic {
+        message: format!("Failed to run {}: {e}", command.path.display()),
+    })?;
+    drop(metadata_file);
+    Ok(kargo_util::process::exit_code(status))
+}
@@ -0,0 +1,250 @@
+//! Operation: CocoaPods podspecs for the XCFramework.
+//!
+//! `kargo pod spec` writes `<Name>.podspec` next to `Kargo.toml`. Without a
+//! URL the pod vendors the local `build/xcframework/release/` bundle, for
+//! consumption with `pod '<Name>', :path => '...'`; with `--url` it points
+//! at the released `<Name>.xcframework.zip`. `kargo pod publish` writes the
+//! latter and pushes it to a private spec repository.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+
+/// Where the pod's XCFramework comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PodSource {
+    /// `build/xcframework/<profile>/` of this project.
+    Local { profile: String },
+    /// A zip containing `<Name>.xcframework` at its root.
+    Http { url: String },
+}
+
+/// `<Name>.podspec` in `project_dir`.
+pub fn podspec_path(project_dir: &Path, manifest: &Manifest) -> PathBuf {
+    project_dir.join(format!("{}.podspec", manifest.framework_name()))
+}
+
+/// Contents of the podspec for `manifest`.
+pub fn render_podspec(manifest: &Manifest, source: &PodSource) -> String {
+    let pkg = &manifest.package;
+    let name = manifest.framework_name();
+    let mut fields: Vec<(String, String)> = vec![
+        ("name".into(), quote(&name)),
+        ("version".into(), quote(&pkg.version)),
+        (
+            "summary".into(),
+            quote(pkg.description.as_deref().unwrap_or(&pkg.name)),
+        ),
+    ];
+    if let Some(homepage) = &pkg.repository {
+        fields.push(("homepage".into(), quote(homepage)));
+    }
+    if let Some(license) = &pkg.license {
+        fields.push((
+            "license".into(),
+            format!("{{ :type => {} }}", quote(license)),
+        ));
+    }
+    if !pkg.authors.is_empty() {
+        let authors: Vec<String> = pkg
+            .authors
+            .iter()
+            .map(|a| {
+                let (name, email) = split_author(a);
+                format!("{} => {}", quote(name), quote(email))
+            })
+            .collect();
+        fields.push(("authors".into(), format!("{{ {} }}", authors.join(", "))));
+    }
+    let bundle = format!("{name}.xcframework");
+    match source {
+        PodSource::Local { profile } => {
+            fields.push(("source".into(), "{ :http => '' }".into()));
+            fields.push((
+                "vendored_frameworks".into(),
+                quote(&format!("build/xcframework/{profile}/{bundle}")),
+            ));
+        }
+        PodSource::Http { url } => {
+            fields.push(("source".into(), format!("{{ :http => {} }}", quote(url))));
+            fields.push(("vendored_frameworks".into(), quote(&bundle)));
+        }
+    }
+    let mut platforms: Vec<&str> = manifest
+        .declared_targets()
+        .iter()
+        .filter_map(|t| t.apple_platform())
+        .collect();
+    platforms.sort_unstable();
+    platforms.dedup();
+    for platform in platforms {
+        if let Some(version) = manifest.deployment_target(platform) {
+            let key = if platform == "macos" { "osx" } else { platform };
+            fields.push((format!("{key}.deployment_target"), quote(&version)));
+        }
+    }
+
+    let width = fields.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    let mut out = String::from(
+        "# Generated by `kargo pod spec` from Kargo.toml. Do not edit.\n\
+         Pod::Spec.new do |spec|\n",
+    );
+    for (key, value) in fields {
+        out.push_str(&format!("  spec.{key:<width$} = {value}\n"));
+    }
+    out.push_str("end\n");
+    out
+}
+
+/// Write `<Name>.podspec` for `source`.
+pub fn spec(project_dir: &Path, source: &PodSource) -> miette::Result<PathBuf> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    check_apple_targets(&manifest)?;
+    let path = podspec_path(project_dir, &manifest);
+    std::fs::write(&path, render_podspec(&manifest, source)).map_err(KargoError::Io)?;
+    Ok(path)
+}
+
+/// Write the podspec for the XCFramework zip at `url` and push it to the
+/// spec repository `repo` (as added with `pod repo add`).
+pub fn publish(project_dir: &Path, repo: &str, url: &str) -> miette::Result<PathBuf> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    check_apple_targets(&manifest)?;
+    let pkg = &manifest.package;
+    let missing: Vec<&str> = [
+        ("package.repository", pkg.repository.is_none()),
+        ("package.license", pkg.license.is_none()),
+        ("package.authors", pkg.authors.is_empty()),
+    ]
+    .into_iter()
+    .filter(|(_, missing)| *missing)
+    .map(|(key, _)| key)
+    .collect();
+    if !missing.is_empty() {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "A published pod needs {} in Kargo.toml (used for homepage, license and authors)",
+                missing.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    let path = podspec_path(project_dir, &manifest);
+    let source = PodSource::Http {
+        url: url.to_string(),
+    };
+    std::fs::write(&path, render_podspec(&manifest, &source)).map_err(KargoError::Io)?;
+
+    let status = CommandBuilder::new("pod")
+        .args(["repo", "push", repo])
+        .arg(path.to_string_lossy())
+        .cwd(project_dir.to_string_lossy())
+        .status()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run `pod` (is CocoaPods installed?): {e}"),
+        })?;
+    if !status.success() {
+        return Err(KargoError::Generic {
+            message: format!("`pod repo push {repo}` failed"),
+        }
+        .into());
+    }
+    Ok(path)
+}
+
+fn check_apple_targets(manifest: &Manifest) -> miette::Result<()> {
+    if manifest.declared_targets().iter().any(|t| t.is_apple()) {
+        return Ok(());
+    }
+    Err(KargoError::Generic {
+        message: "No Apple target in [targets]; a pod needs at least one.".into(),
+    }
+    .into())
+}
+
+/// `"Jane Doe <jane@example.com>"` -> `("Jane Doe", "jane@example.com")`.
+fn split_author(author: &str) -> (&str, &str) {
+    match author.split_once('<') {
+        Some((name, rest)) => (name.trim(), rest.trim_end().trim_end_matches('>').trim()),
+        None => (author.trim(), ""),
+    }
+}
+
+/// A single-quoted Ruby string literal.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "shared-core"
+version = "1.2.0"
+kotlin = "2.3.0"
+description = "Shared code"
+authors = ["Jane Doe <jane@example.com>"]
+license = "Apache-2.0"
+repository = "https://github.com/example/shared"
+
+[targets]
+jvm = {}
+ios-arm64 = {}
+ios-simulator-arm64 = {}
+macos-arm64 = {}
+
+[apple]
+deployment-target = { ios = "15.0" }
+"#;
+
+    #[test]
+    fn podspec_for_a_released_zip() {
+        let manifest = Manifest::parse_toml(MANIFEST).unwrap();
+        let spec = render_podspec(
+            &manifest,
+            &PodSource::Http {
+                url: "https://example.com/SharedCore.xcframework.zip".into(),
+            },
+        );
+        assert_eq!(
+            spec,
+            "# Generated by `kargo pod spec` from Kargo.toml. Do not edit.\n\
+             Pod::Spec.new do |spec|\n\
+             \x20 spec.name                  = 'SharedCore'\n\
+             \x20 spec.version               = '1.2.0'\n\
+             \x20 spec.summary               = 'Shared code'\n\
+             \x20 spec.homepage              = 'https://github.com/example/shared'\n\
+             \x20 spec.license               = { :type => 'Apache-2.0' }\n\
+             \x20 spec.authors               = { 'Jane Doe' => 'jane@example.com' }\n\
+             \x20 spec.source                = { :http => 'https://example.com/SharedCore.xcframework.zip' }\n\
+             \x20 spec.vendored_frameworks   = 'SharedCore.xcframework'\n\
+             \x20 spec.ios.deployment_target = '15.0'\n\
+             \x20 spec.osx.deployment_target = '11.0'\n\
+             end\n"
+        );
+    }
+
+    #[test]
+    fn local_podspec_vendors_the_build_output() {
+        let manifest = Manifest::parse_toml(MANIFEST).unwrap();
+        let spec = render_podspec(
+            &manifest,
+            &PodSource::Local {
+                profile: "release".into(),
+            },
+        );
+        assert!(spec.contains("spec.source                = { :http => '' }\n"));
+        assert!(spec.contains("= 'build/xcframework/release/SharedCore.xcframework'\n"));
+    }
+
+    #[test]
+    fn quotes_ruby_strings() {
+        assert_eq!(quote("it's a \\ test"), "'it\\'s a \\\\ test'");
+        assert_eq!(split_author("Jane"), ("Jane", ""));
+    }
+}
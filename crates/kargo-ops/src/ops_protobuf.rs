@@ -0,0 +1,575 @@
+//! Operation: compile `.proto` files with protoc before compilation.
+//!
+//! Runs for JVM and Android targets when `src/main/proto` (or the
+//! `[protobuf] source-dirs`) holds `.proto` files. protoc and the gRPC
+//! plugins are native executables published on Maven Central; they are
+//! downloaded into the project cache like any other artifact. Java messages
+//! go to `<generated>/proto/java` (compiled with javac after Kotlin), and
+//! Kotlin DSL builders and gRPC-Kotlin stubs go to `<generated>/proto/kotlin`.
+//! `.proto` files inside dependency JARs are extracted so they can be
+//! imported. Generation is skipped while the `.proto` inputs, the
+//! `[protobuf]` settings and the dependency JARs are unchanged.
+
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::fingerprint;
+use kargo_core::manifest::{Manifest, ProtobufConfig};
+use kargo_core::target::KotlinTarget;
+use kargo_maven::cache::LocalCache;
+use kargo_maven::repository::MavenRepository;
+use kargo_util::errors::KargoError;
+
+/// protoc release used when `[protobuf] version` is not set.
+pub const DEFAULT_PROTOC_VERSION: &str = "4.28.2";
+/// protoc-gen-grpc-java release used when `grpc-version` is not set.
+pub const DEFAULT_GRPC_VERSION: &str = "1.66.0";
+/// protoc-gen-grpc-kotlin release used when `grpc-kotlin-version` is not set.
+pub const DEFAULT_GRPC_KOTLIN_VERSION: &str = "1.4.1";
+
+const PROTOC_GROUP: &str = "com.google.protobuf";
+const PROTOC_ARTIFACT: &str = "protoc";
+const GRPC_GROUP: &str = "io.grpc";
+const GRPC_JAVA_ARTIFACT: &str = "protoc-gen-grpc-java";
+const GRPC_KOTLIN_ARTIFACT: &str = "protoc-gen-grpc-kotlin";
+const GRPC_KOTLIN_CLASSIFIER: &str = "jdk8";
+
+/// Default directory holding `.proto` files.
+pub const DEFAULT_PROTO_DIR: &str = "src/main/proto";
+
+/// Root of the protoc output below the generated directory.
+pub fn output_dir(generated_dir: &Path) -> PathBuf {
+    generated_dir.join("proto")
+}
+
+/// Source directories protoc wrote to, for the compilation unit.
+pub fn source_dirs(generated_dir: &Path) -> Vec<PathBuf> {
+    let out = output_dir(generated_dir);
+    [out.join("java"), out.join("kotlin")]
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// The `[protobuf]` settings, or the defaults when the section is absent.
+fn config(manifest: &Manifest) -> ProtobufConfig {
+    manifest.protobuf.clone().unwrap_or_default()
+}
+
+/// Directories searched for `.proto` files.
+pub fn proto_dirs(project_dir: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    let config = config(manifest);
+    if config.source_dirs.is_empty() {
+        vec![project_dir.join(DEFAULT_PROTO_DIR)]
+    } else {
+        config
+            .source_dirs
+            .iter()
+            .map(|d| project_dir.join(d))
+            .collect()
+    }
+}
+
+/// `.proto` files below `dirs`, sorted.
+pub fn proto_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_protos(dir, &mut files);
+    }
+    files.sort();
+    files
+}
+
+fn collect_protos(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_protos(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "proto") {
+            out.push(path);
+        }
+    }
+}
+
+/// Maven coordinates of the protoc tools the manifest needs, for cache
+/// pruning. Empty when the project has no `.proto` files.
+pub fn tool_coordinates(project_dir: &Path, manifest: &Manifest) -> Vec<(String, String, String)> {
+    if proto_files(&proto_dirs(project_dir, manifest)).is_empty() {
+        return Vec::new();
+    }
+    let config = config(manifest);
+    let mut coords = vec![(
+        PROTOC_GROUP.to_string(),
+        PROTOC_ARTIFACT.to_string(),
+        protoc_version(&config).to_string(),
+    )];
+    if config.grpc {
+        coords.push((
+            GRPC_GROUP.into(),
+            GRPC_JAVA_ARTIFACT.into(),
+            grpc_version(&config).into(),
+        ));
+        if kotlin_enabled(&config) {
+            coords.push((
+                GRPC_GROUP.into(),
+                GRPC_KOTLIN_ARTIFACT.into(),
+                grpc_kotlin_version(&config).into(),
+            ));
+        }
+    }
+    coords
+}
+
+fn protoc_version(config: &ProtobufConfig) -> &str {
+    config.version.as_deref().unwrap_or(DEFAULT_PROTOC_VERSION)
+}
+
+fn grpc_version(config: &ProtobufConfig) -> &str {
+    config
+        .grpc_version
+        .as_deref()
+        .unwrap_or(DEFAULT_GRPC_VERSION)
+}
+
+fn grpc_kotlin_version(config: &ProtobufConfig) -> &str {
+    config
+        .grpc_kotlin_version
+        .as_deref()
+        .unwrap_or(DEFAULT_GRPC_KOTLIN_VERSION)
+}
+
+fn kotlin_enabled(config: &ProtobufConfig) -> bool {
+    config.kotlin.unwrap_or(true)
+}
+
+/// Maven classifier of the native protoc executables for this host.
+pub fn host_classifier() -> Option<String> {
+    let os = if cfg!(target_os = "linux") {
+        "linux"
+    } else if cfg!(target_os = "macos") {
+        "osx"
+    } else if cfg!(target_os = "windows") {
+        "windows"
+    } else {
+        return None;
+    };
+    let arch = if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "aarch64") {
+        "aarch_64"
+    } else {
+        return None;
+    };
+    Some(format!("{os}-{arch}"))
+}
+
+/// Download a native executable published as `<artifact>-<version>-<os>-<arch>.exe`.
+async fn ensure_executable(
+    cache: &LocalCache,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> miette::Result<PathBuf> {
+    let classifier = host_classifier().ok_or_else(|| KargoError::Toolchain {
+        message: format!("{artifact} is not published for this platform"),
+    })?;
+    let filename = format!("{artifact}-{version}-{classifier}.exe");
+    let mut path = cache.artifact_dir(group, artifact, version).join(&filename);
+    if !path.is_file() {
+        let repo = MavenRepository::maven_central();
+        let client = kargo_maven::download::build_client()?;
+        let url = repo.file_url(group, artifact, version, &filename);
+        let label = format!("{artifact}:{version}");
+        let data = kargo_maven::download::download_artifact(&client, &repo, &url, &label)
+            .await?
+            .ok_or_else(|| KargoError::Toolchain {
+                message: format!("{group}:{artifact}:{version} ({classifier}) not found"),
+            })?;
+        path = cache.put(group, artifact, version, &filename, &data)?;
+    }
+    make_executable(&path)?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> miette::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = std::fs::metadata(path)
+        .map_err(KargoError::Io)?
+        .permissions();
+    if perms.mode() & 0o111 != 0o111 {
+        perms.set_mode(0o755);
+        std::fs::set_permissions(path, perms).map_err(KargoError::Io)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> miette::Result<()> {
+    Ok(())
+}
+
+/// Write a launcher that runs the grpc-kotlin plugin JAR, since protoc
+/// plugins must be executables.
+fn write_jar_launcher(dir: &Path, java: &Path, jar: &Path) -> miette::Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(KargoError::Io)?;
+    let (path, script) = if cfg!(windows) {
+        (
+            dir.join("protoc-gen-grpckt.bat"),
+            format!(
+                "@echo off\r\n\"{}\" -jar \"{}\" %*\r\n",
+                java.display(),
+                jar.display()
+            ),
+        )
+    } else {
+        (
+            dir.join("protoc-gen-grpckt"),
+            format!(
+                "#!/bin/sh\nexec \"{}\" -jar \"{}\" \"$@\"\n",
+                java.display(),
+                jar.display()
+            ),
+        )
+    };
+    std::fs::write(&path, script).map_err(KargoError::Io)?;
+    make_executable(&path)?;
+    Ok(path)
+}
+
+/// protoc plugins to run in addition to the built-in Java/Kotlin output.
+#[derive(Debug, Default)]
+pub struct ProtocPlugins {
+    pub grpc_java: Option<PathBuf>,
+    pub grpc_kotlin: Option<PathBuf>,
+}
+
+/// Arguments for one protoc invocation over `files`.
+pub fn protoc_args(
+    include_dirs: &[PathBuf],
+    out: &Path,
+    kotlin: bool,
+    plugins: &ProtocPlugins,
+    files: &[PathBuf],
+) -> Vec<String> {
+    let java_out = out.join("java");
+    let kotlin_out = out.join("kotlin");
+    let mut args: Vec<String> = include_dirs
+        .iter()
+        .map(|d| format!("-I{}", d.display()))
+        .collect();
+    args.push(format!("--java_out={}", java_out.display()));
+    if kotlin {
+        args.push(format!("--kotlin_out={}", kotlin_out.display()));
+    }
+    if let Some(plugin) = &plugins.grpc_java {
+        args.push(format!(
+            "--plugin=protoc-gen-grpc-java={}",
+            plugin.display()
+        ));
+        args.push(format!("--grpc-java_out={}", java_out.display()));
+    }
+    if let Some(plugin) = &plugins.grpc_kotlin {
+        args.push(format!("--plugin=protoc-gen-grpckt={}", plugin.display()));
+        args.push(format!("--grpckt_out={}", kotlin_out.display()));
+    }
+    args.extend(files.iter().map(|f| f.display().to_string()));
+    args
+}
+
+/// Extract the `.proto` files of dependency JARs into `dest` so they can be
+/// imported. Returns whether any were found.
+fn extract_dependency_protos(jars: &[PathBuf], dest: &Path) -> miette::Result<bool> {
+    let _ = std::fs::remove_dir_all(dest);
+    let mut found = false;
+    for jar in jars {
+        let Ok(file) = std::fs::File::open(jar) else {
+            continue;
+        };
+        let Ok(mut archive) = zip::ZipArchive::new(file) else {
+            continue;
+        };
+        for i in 0..archive.len() {
+            let Ok(mut entry) = archive.by_index(i) else {
+                continue;
+            };
+            if !entry.name().ends_with(".proto") {
+                continue;
+            }
+            let Some(rel) = entry.enclosed_name() else {
+                continue;
+            };
+            let target = dest.join(rel);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+            }
+            let mut out = std::fs::File::create(&target).map_err(KargoError::Io)?;
+            std::io::copy(&mut entry, &mut out).map_err(KargoError::Io)?;
+            found = true;
+        }
+    }
+    Ok(found)
+}
+
+/// Hash of the `.proto` inputs, the settings and the dependency JAR names.
+pub fn compute_fingerprint(
+    config: &ProtobufConfig,
+    project_dir: &Path,
+    files: &[PathBuf],
+    dependency_jars: &[PathBuf],
+) -> miette::Result<String> {
+    let mut data = format!("{}\n", serde_json::to_string(config).unwrap_or_default());
+    for file in files {
+        let rel = file.strip_prefix(project_dir).unwrap_or(file);
+        data.push_str(&format!(
+            "src {} {}\n",
+            rel.display(),
+            kargo_util::hash::sha256_file(file)?
+        ));
+    }
+    let mut jars: Vec<String> = dependency_jars
+        .iter()
+        .filter_map(|j| j.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+    jars.sort();
+    for jar in jars {
+        data.push_str(&format!("dep {jar}\n"));
+    }
+    Ok(kargo_util::hash::sha256_bytes(data.as_bytes()))
+}
+
+/// Run protoc if the `.proto` inputs changed. Returns whether it ran.
+pub async fn generate(
+    ctx: &crate::BuildContext,
+    verbose: bool,
+    quiet: bool,
+) -> miette::Result<bool> {
+    let dirs: Vec<PathBuf> = proto_dirs(&ctx.project_dir, &ctx.manifest)
+        .into_iter()
+        .filter(|d| d.is_dir())
+        .collect();
+    let files = proto_files(&dirs);
+    let out = output_dir(&ctx.generated_dir);
+    if files.is_empty() {
+        let _ = std::fs::remove_dir_all(&out);
+        return Ok(false);
+    }
+    if !matches!(ctx.target, KotlinTarget::Jvm | KotlinTarget::Android) {
+        if !quiet {
+            kargo_util::progress::status_warn(
+                "Skipped",
+                &format!(
+                    "protobuf generation is only supported on JVM targets, not {}",
+                    ctx.target
+                ),
+            );
+        }
+        return Ok(false);
+    }
+
+    let config = config(&ctx.manifest);
+    let deps = &ctx.classpath.compile_jars;
+    let fp = compute_fingerprint(&config, &ctx.project_dir, &files, deps)?;
+    let fp_dir =
+        fingerprint::storage_dir(&ctx.project_dir, ctx.target.kebab_name(), &ctx.profile_name);
+    let fp_path = fp_dir.join("protobuf.fingerprint");
+    if out.is_dir() && std::fs::read_to_string(&fp_path).is_ok_and(|stored| stored.trim() == fp) {
+        if verbose {
+            eprintln!("  protobuf: up-to-date (skipped)");
+        }
+        return Ok(false);
+    }
+
+    if !quiet {
+        kargo_util::progress::status(
+            "Generating",
+            &format!("protobuf sources ({} files)", files.len()),
+        );
+    }
+    let cache = LocalCache::new(&ctx.project_dir);
+    let protoc = ensure_executable(
+        &cache,
+        PROTOC_GROUP,
+        PROTOC_ARTIFACT,
+        protoc_version(&config),
+    )
+    .await?;
+    let kotlin = kotlin_enabled(&config);
+    let mut plugins = ProtocPlugins::default();
+    if config.grpc {
+        plugins.grpc_java = Some(
+            ensure_executable(
+                &cache,
+                GRPC_GROUP,
+                GRPC_JAVA_ARTIFACT,
+                grpc_version(&config),
+            )
+            .await?,
+        );
+        if kotlin {
+            let version = grpc_kotlin_version(&config);
+            let jar = match cache.get_jar(
+                GRPC_GROUP,
+                GRPC_KOTLIN_ARTIFACT,
+                version,
+                Some(GRPC_KOTLIN_CLASSIFIER),
+            ) {
+                Some(jar) => jar,
+                None => download_grpc_kotlin(&cache, version).await?,
+            };
+            let java = ctx.preflight.jdk.home.join("bin").join("java");
+            plugins.grpc_kotlin = Some(write_jar_launcher(
+                &ctx.build_dir.join("protoc"),
+                &java,
+                &jar,
+            )?);
+        }
+    }
+
+    let _ = std::fs::remove_file(&fp_path);
+    let _ = std::fs::remove_dir_all(&out);
+    for sub in ["java", "kotlin"] {
+        std::fs::create_dir_all(out.join(sub)).map_err(KargoError::Io)?;
+    }
+    let mut include_dirs = dirs.clone();
+    let dep_include = ctx.build_dir.join("proto-include");
+    if extract_dependency_protos(deps, &dep_include)? {
+        include_dirs.push(dep_include);
+    }
+
+    let args = protoc_args(&include_dirs, &out, kotlin, &plugins, &files);
+    if verbose {
+        eprintln!("  protoc: {} {}", protoc.display(), args.join(" "));
+    }
+    let output = kargo_util::process::CommandBuilder::new(protoc.to_string_lossy().to_string())
+        .args(args)
+        .cwd(ctx.project_dir.to_string_lossy().to_string())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run protoc: {e}"),
+        })?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "protoc failed:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+
+    std::fs::create_dir_all(&fp_dir).map_err(KargoError::Io)?;
+    std::fs::write(&fp_path, &fp).map_err(KargoError::Io)?;
+    Ok(true)
+}
+
+async fn download_grpc_kotlin(cache: &LocalCache, version: &str) -> miette::Result<PathBuf> {
+    let repo = MavenRepository::maven_central();
+    let client = kargo_maven::download::build_client()?;
+    let url = repo.jar_url(
+        GRPC_GROUP,
+        GRPC_KOTLIN_ARTIFACT,
+        version,
+        Some(GRPC_KOTLIN_CLASSIFIER),
+    );
+    let label = format!("{GRPC_KOTLIN_ARTIFACT}:{version}");
+    let data = kargo_maven::download::download_artifact(&client, &repo, &url, &label)
+        .await?
+        .ok_or_else(|| KargoError::Toolchain {
+            message: format!("{GRPC_GROUP}:{GRPC_KOTLIN_ARTIFACT}:{version} not found"),
+        })?;
+    cache.put_jar(
+        GRPC_GROUP,
+        GRPC_KOTLIN_ARTIFACT,
+        version,
+        Some(GRPC_KOTLIN_CLASSIFIER),
+        &data,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protoc_args_route_java_and_kotlin_outputs() {
+        let plugins = ProtocPlugins {
+            grpc_java: Some(PathBuf::from("/c/grpc-java.exe")),
+            grpc_kotlin: Some(PathBuf::from("/b/protoc-gen-grpckt")),
+        };
+        let args = protoc_args(
+            &[PathBuf::from("/p/src/main/proto")],
+            Path::new("/g/proto"),
+            true,
+            &plugins,
+            &[PathBuf::from("/p/src/main/proto/a.proto")],
+        );
+        assert_eq!(
+            args,
+            [
+                "-I/p/src/main/proto",
+                "--java_out=/g/proto/java",
+                "--kotlin_out=/g/proto/kotlin",
+                "--plugin=protoc-gen-grpc-java=/c/grpc-java.exe",
+                "--grpc-java_out=/g/proto/java",
+                "--plugin=protoc-gen-grpckt=/b/protoc-gen-grpckt",
+                "--grpckt_out=/g/proto/kotlin",
+                "/p/src/main/proto/a.proto",
+            ]
+        );
+
+        let plain = protoc_args(
+            &[],
+            Path::new("/g/proto"),
+            false,
+            &ProtocPlugins::default(),
+            &[],
+        );
+        assert_eq!(plain, ["--java_out=/g/proto/java"]);
+    }
+
+    #[test]
+    fn fingerprint_tracks_proto_contents_and_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let proto = dir.path().join("a.proto");
+        std::fs::write(&proto, "syntax = \"proto3\";").unwrap();
+        let files = [proto.clone()];
+        let config = ProtobufConfig::default();
+        let fp = compute_fingerprint(&config, dir.path(), &files, &[]).unwrap();
+
+        std::fs::write(&proto, "syntax = \"proto3\";\nmessage A {}").unwrap();
+        let edited = compute_fingerprint(&config, dir.path(), &files, &[]).unwrap();
+        assert_ne!(fp, edited);
+
+        let grpc = ProtobufConfig {
+            grpc: true,
+            ..Default::default()
+        };
+        assert_ne!(
+            edited,
+            compute_fingerprint(&grpc, dir.path(), &files, &[]).unwrap()
+        );
+        assert_ne!(
+            edited,
+            compute_fingerprint(&config, dir.path(), &files, &[PathBuf::from("x-1.0.jar")])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn protos_are_found_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("proto");
+        std::fs::create_dir_all(root.join("api/v1")).unwrap();
+        std::fs::write(root.join("api/v1/user.proto"), "").unwrap();
+        std::fs::write(root.join("common.proto"), "").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+        let files = proto_files(std::slice::from_ref(&root));
+        assert_eq!(
+            files,
+            [root.join("api/v1/user.proto"), root.join("common.proto")]
+        );
+    }
+}
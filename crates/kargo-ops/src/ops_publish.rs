@@ -0,0 +1,911 @@
+//! Operation: publishing to Maven repositories (`kargo publish ...`).
+//!
+//! `kargo publish verify-namespace` checks before a release that the Central
+//! Portal token may publish under the package's group, instead of finding
+//! out when the upload is rejected. When it may not, it explains how to
+//! verify the namespace: a DNS TXT record on the domain for reverse-domain
+//! groups, or a temporary public repository for `io.github.<user>`-style
+//! groups. With the verification key from the portal it also checks that
+//! the TXT record is visible.
+//!
+//! `kargo publish` builds the package with the release profile and
+//! publishes its JAR, `-sources` and `-javadoc` JARs and a POM generated
+//! from `Kargo.toml`, each signed with the GPG key of `[signing]`. Maven
+//! Central takes them as one bundle through the Central Portal upload API;
+//! a `[repositories]` entry named with `--repository` takes them one by one
+//! in the Maven layout. `--dry-run` stops before the upload.
+//!
+//! The token is the Central Portal user token, from
+//! `KARGO_CENTRAL_USERNAME` and `KARGO_CENTRAL_PASSWORD`, or from
+//! `[credentials.central]` in `~/.kargo/config.toml`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use kargo_core::config::GlobalConfig;
+use kargo_core::dependency::{Dependency, DependencyScope};
+use kargo_core::manifest::Manifest;
+use kargo_core::target::KotlinTarget;
+use kargo_maven::pom::{PomDependency, PomExclusion};
+use kargo_maven::publish::{PomProject, PublishFile};
+use kargo_maven::repository::MavenRepository;
+use kargo_util::errors::KargoError;
+use kargo_util::progress::{note, status, status_info, status_warn};
+
+use crate::ops_build::BuildResult;
+
+/// Central Portal API base URL, overridable with `KARGO_CENTRAL_URL`.
+const CENTRAL_URL: &str = "https://central.sonatype.com";
+/// DNS-over-HTTPS resolver (JSON API), overridable with `KARGO_DOH_URL`.
+const DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// Code hosts whose `io.<host>.<user>` namespaces the portal verifies
+/// through a repository instead of DNS.
+const CODE_HOSTS: &[(&str, &str)] = &[
+    ("github", "https://github.com"),
+    ("gitlab", "https://gitlab.com"),
+    ("bitbucket", "https://bitbucket.org"),
+];
+
+/// The namespace a group ID is published under, and how it is verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Namespace {
+    /// `com.example` for `com.example.lib`, verified with a TXT record on
+    /// `domain`.
+    Domain { namespace: String, domain: String },
+    /// `io.github.user`, verified with a public repository named after the
+    /// verification key.
+    CodeHost {
+        namespace: String,
+        host_url: String,
+        user: String,
+    },
+}
+
+impl Namespace {
+    /// The namespace `group` falls under.
+    pub fn of(group: &str) -> Option<Self> {
+        let parts: Vec<&str> = group.split('.').filter(|p| !p.is_empty()).collect();
+        if parts.len() < 2 {
+            return None;
+        }
+        if parts.len() >= 3 && matches!(parts[0], "io" | "com") {
+            if let Some((host, url)) = CODE_HOSTS.iter().find(|(h, _)| *h == parts[1]) {
+                return Some(Self::CodeHost {
+                    namespace: format!("io.{host}.{}", parts[2]),
+                    host_url: url.to_string(),
+                    user: parts[2].to_string(),
+                });
+            }
+        }
+        Some(Self::Domain {
+            namespace: format!("{}.{}", parts[0], parts[1]),
+            domain: format!("{}.{}", parts[1], parts[0]),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Domain { namespace, .. } | Self::CodeHost { namespace, .. } => namespace,
+        }
+    }
+}
+
+/// What the portal says about the token and the namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PortalCheck {
+    /// The token may publish to the namespace; `published` if this version
+    /// already is.
+    Verified {
+        published: bool,
+    },
+    NotVerified,
+    BadToken,
+}
+
+/// Check that the Central Portal token may publish the package in `cwd`,
+/// explaining how to verify its namespace if not. `key` is the
+/// verification key the portal shows for the namespace.
+pub async fn verify_namespace(cwd: &Path, key: Option<&str>) -> miette::Result<()> {
+    let manifest = Manifest::from_path(&cwd.join("Kargo.toml"))?;
+    let group = manifest
+        .package
+        .group
+        .as_deref()
+        .ok_or_else(|| KargoError::Manifest {
+            message: "`kargo publish verify-namespace` needs [package] group".to_string(),
+        })?;
+    let namespace = Namespace::of(group).ok_or_else(|| KargoError::Manifest {
+        message: format!(
+            "group `{group}` is not a reverse-domain name like `com.example`, so it cannot \
+             be published to Maven Central"
+        ),
+    })?;
+    status(
+        "Checking",
+        &format!("namespace {} for group {group}", namespace.name()),
+    );
+
+    match token()? {
+        Some(token) => {
+            let check = portal_check(
+                &token,
+                group,
+                &manifest.package.name,
+                &manifest.package.version,
+            )
+            .await?;
+            match check {
+                PortalCheck::Verified { published } => {
+                    status(
+                        "Verified",
+                        &format!("your token can publish to {}", namespace.name()),
+                    );
+                    if published {
+                        status_warn(
+                            "Published",
+                            &format!(
+                                "{group}:{}:{} is already on Maven Central; bump the version \
+                                 before publishing",
+                                manifest.package.name, manifest.package.version
+                            ),
+                        );
+                    }
+                    return Ok(());
+                }
+                PortalCheck::BadToken => {
+                    return Err(KargoError::Network {
+                        message: "the Central Portal rejected the token; generate a user token \
+                                  at https://central.sonatype.com/account"
+                            .to_string(),
+                    }
+                    .into())
+                }
+                PortalCheck::NotVerified => {}
+            }
+        }
+        None => status_warn(
+            "Skipped",
+            "no Central Portal token in KARGO_CENTRAL_USERNAME/KARGO_CENTRAL_PASSWORD or \
+             [credentials.central]; only checking the verification record",
+        ),
+    }
+
+    explain(&namespace, key);
+    if let (Namespace::Domain { domain, .. }, Some(key)) = (&namespace, key) {
+        let records = txt_records(domain).await?;
+        if records.iter().any(|r| r.contains(key)) {
+            status(
+                "Found",
+                &format!("TXT record {key} on {domain}; press \"Verify Namespace\" in the portal"),
+            );
+        } else {
+            status_warn(
+                "Missing",
+                &format!(
+                    "no TXT record {key} on {domain} yet (DNS changes can take a while to \
+                     propagate)"
+                ),
+            );
+        }
+    }
+    Err(KargoError::Generic {
+        message: format!(
+            "namespace {} is not verified for your Central Portal account",
+            namespace.name()
+        ),
+    }
+    .into())
+}
+
+/// Print the steps to verify `namespace`.
+fn explain(namespace: &Namespace, key: Option<&str>) {
+    let key = key.unwrap_or("<verification key>");
+    note(&format!(
+        "To verify {}:\n  1. Open https://central.sonatype.com/publishing/namespaces and add the \
+         namespace {0}, if it is not listed, to get its verification key.",
+        namespace.name()
+    ));
+    match namespace {
+        Namespace::Domain { domain, .. } => note(&format!(
+            "  2. Add a DNS TXT record to {domain} whose value is the key:\n       {domain}.  TXT  \
+             \"{key}\"\n  3. Run `kargo publish verify-namespace --key {key}` to check the record, \
+             then press \"Verify Namespace\" in the portal."
+        )),
+        Namespace::CodeHost { host_url, user, .. } => note(&format!(
+            "  2. Create a public repository named after the key: {host_url}/{user}/{key}\n  3. \
+             Press \"Verify Namespace\" in the portal, then delete the repository."
+        )),
+    }
+}
+
+/// The Central Portal user token as a bearer token, if one is configured.
+fn token() -> miette::Result<Option<String>> {
+    let env = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+    let (username, password) = match (env("KARGO_CENTRAL_USERNAME"), env("KARGO_CENTRAL_PASSWORD"))
+    {
+        (Some(u), Some(p)) => (u, p),
+        _ => {
+            let config = GlobalConfig::load()?;
+            match config.credentials.get("central") {
+                Some(entry) => match (&entry.username, &entry.password) {
+                    (Some(u), Some(p)) => (u.clone(), p.clone()),
+                    _ => return Ok(None),
+                },
+                None => return Ok(None),
+            }
+        }
+    };
+    let credentials = format!("{username}:{password}");
+    Ok(Some(BASE64_STANDARD.encode(credentials)))
+}
+
+/// Ask the portal whether `group:name:version` is published. The answer
+/// is only given for namespaces the token may publish to.
+async fn portal_check(
+    token: &str,
+    group: &str,
+    name: &str,
+    version: &str,
+) -> miette::Result<PortalCheck> {
+    let client = client()?;
+    let resp = client
+        .get(format!("{}/api/v1/publisher/published", central_url()))
+        .query(&[("namespace", group), ("name", name), ("version", version)])
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to reach the Central Portal: {e}"),
+        })?;
+    match resp.status().as_u16() {
+        200..=299 => {
+            let body: serde_json::Value = resp.json().await.map_err(|e| KargoError::Network {
+                message: format!("Failed to parse the Central Portal response: {e}"),
+            })?;
+            Ok(PortalCheck::Verified {
+                published: body["published"].as_bool().unwrap_or(false),
+            })
+        }
+        401 => Ok(PortalCheck::BadToken),
+        400 | 403 | 404 => Ok(PortalCheck::NotVerified),
+        code => Err(KargoError::Network {
+            message: format!("the Central Portal returned HTTP {code}"),
+        }
+        .into()),
+    }
+}
+
+/// TXT records of `domain`, resolved over DNS-over-HTTPS.
+async fn txt_records(domain: &str) -> miette::Result<Vec<String>> {
+    let url = std::env::var("KARGO_DOH_URL").unwrap_or_else(|_| DOH_URL.to_string());
+    let body: serde_json::Value = client()?
+        .get(url)
+        .query(&[("name", domain), ("type", "TXT")])
+        .header("Accept", "application/dns-json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to look up TXT records of {domain}: {e}"),
+        })?
+        .json()
+        .await
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to parse TXT records of {domain}: {e}"),
+        })?;
+    Ok(parse_txt_answer(&body))
+}
+
+/// TXT record values in a DNS JSON answer, without their quotes.
+fn parse_txt_answer(body: &serde_json::Value) -> Vec<String> {
+    body["Answer"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|a| a["type"].as_u64().unwrap_or(16) == 16)
+        .filter_map(|a| a["data"].as_str())
+        .map(|data| data.split('"').filter(|s| !s.trim().is_empty()).collect())
+        .collect()
+}
+
+/// Options for [`publish`].
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// The `[repositories]` entry to publish to; Maven Central if `None`.
+    pub repository: Option<String>,
+    /// Build, sign and list the files without uploading them.
+    pub dry_run: bool,
+    pub verbose: bool,
+}
+
+/// Where a publication is uploaded.
+#[derive(Debug, Clone)]
+pub enum PublishTarget {
+    /// Maven Central, through a Central Portal bundle upload.
+    Central,
+    /// A `[repositories]` entry, in the Maven layout.
+    Repository(MavenRepository),
+}
+
+impl PublishTarget {
+    fn describe(&self) -> String {
+        match self {
+            Self::Central => "Maven Central".to_string(),
+            Self::Repository(repo) => format!("{} ({})", repo.name, repo.url),
+        }
+    }
+}
+
+/// Build the package in `cwd` with the release profile and publish its
+/// JAR, sources, javadoc and POM, signed with `[signing]`, to
+/// `opts.repository` or Maven Central.
+pub async fn publish(cwd: &Path, opts: &PublishOptions) -> miette::Result<()> {
+    let manifest_path = cwd.join("Kargo.toml");
+    let content = std::fs::read_to_string(&manifest_path).map_err(KargoError::Io)?;
+    let env = kargo_core::properties::load_env_file(&cwd.join(".kargo.env"))?;
+    let unset = unset_env_references(&content, &env);
+    if !unset.is_empty() {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "Kargo.toml references unset variables: {}; set them in the environment \
+                 or in .kargo.env before publishing",
+                unset.join(", ")
+            ),
+        }
+        .into());
+    }
+    let manifest = Manifest::from_path(&manifest_path)?;
+    let target = match &opts.repository {
+        Some(name) => PublishTarget::Repository(
+            kargo_resolver::resolver::build_repos(&manifest)
+                .into_iter()
+                .find(|r| &r.name == name)
+                .ok_or_else(|| KargoError::Manifest {
+                    message: format!("No repository named '{name}' in [repositories]"),
+                })?,
+        ),
+        None => PublishTarget::Central,
+    };
+    check_publishable(&manifest, &target)?;
+    // Dependencies the POM cannot name fail here rather than after the build.
+    pom_project(&manifest, &[])?;
+    let token = match (&target, opts.dry_run) {
+        (PublishTarget::Central, false) => Some(token()?.ok_or_else(|| {
+            KargoError::Generic {
+                message: "no Central Portal token; set KARGO_CENTRAL_USERNAME and \
+                      KARGO_CENTRAL_PASSWORD or [credentials.central] in \
+                      ~/.kargo/config.toml"
+                    .to_string(),
+            }
+        })?),
+        _ => None,
+    };
+
+    let build = crate::ops_build::build(
+        cwd,
+        &crate::ops_build::BuildOptions {
+            release: true,
+            verbose: opts.verbose,
+            ..Default::default()
+        },
+    )
+    .await?;
+    if !build.success {
+        return Err(KargoError::Compilation {
+            message: "The release build failed; nothing was published".to_string(),
+        }
+        .into());
+    }
+    let files = publication_files(cwd, &build, opts.verbose).await?;
+
+    let package = &build.manifest.package;
+    let group = package.group.as_deref().unwrap_or_default();
+    let coordinate = format!("{group}:{}:{}", package.name, package.version);
+    if opts.dry_run {
+        status_info(
+            "Dry run",
+            &format!("would publish {coordinate} to {}", target.describe()),
+        );
+        for file in &files {
+            println!("  {}", file.remote_name(&package.name, &package.version));
+        }
+        return Ok(());
+    }
+
+    status(
+        "Publishing",
+        &format!("{coordinate} to {}", target.describe()),
+    );
+    match &target {
+        PublishTarget::Repository(repo) => {
+            kargo_maven::publish::publish_artifact(
+                repo,
+                group,
+                &package.name,
+                &package.version,
+                &files,
+            )
+            .await?;
+            status("Published", &format!("{coordinate} to {}", repo.name));
+        }
+        PublishTarget::Central => {
+            let bundle =
+                kargo_maven::publish::bundle(group, &package.name, &package.version, &files)?;
+            let token = token.unwrap_or_default();
+            let deployment = upload_bundle(&token, &coordinate, bundle).await?;
+            status(
+                "Uploaded",
+                &format!("{coordinate} as deployment {deployment}"),
+            );
+            note(
+                "The portal validates the deployment and then publishes it; follow it at \
+                 https://central.sonatype.com/publishing/deployments",
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Write the POM of `build` next to its JAR, and return the JAR, sources,
+/// javadoc and POM, each followed by its signature if `[signing]` names a
+/// GPG key.
+pub(crate) async fn publication_files(
+    project_dir: &Path,
+    build: &BuildResult,
+    verbose: bool,
+) -> miette::Result<Vec<PublishFile>> {
+    let jar = build
+        .output_jar
+        .clone()
+        .ok_or_else(|| KargoError::Generic {
+            message: format!(
+                "The release build for {} produced no JAR to publish",
+                build.target
+            ),
+        })?;
+    let manifest = &build.manifest;
+    let pom = jar.with_extension("pom");
+    let project = pom_project(manifest, &[build.target])?;
+    std::fs::write(&pom, kargo_maven::publish::pom_xml(&project)).map_err(KargoError::Io)?;
+
+    let signing = manifest.signing.as_ref().and_then(|s| {
+        s.gpg_key
+            .as_deref()
+            .map(|key| (key, s.gpg_password.as_deref()))
+    });
+    let mut files = Vec::new();
+    for (classifier, path) in [
+        (None, jar),
+        (
+            Some("sources"),
+            crate::ops_package::sources_jar(project_dir, build)?,
+        ),
+        (
+            Some("javadoc"),
+            crate::ops_package::javadoc_jar(project_dir, build, verbose).await?,
+        ),
+        (None, pom),
+    ] {
+        let file = PublishFile {
+            classifier: classifier.map(String::from),
+            path,
+        };
+        let signature = match &signing {
+            Some((key, passphrase)) => Some(PublishFile {
+                classifier: file.classifier.clone(),
+                path: sign(&file.path, key, *passphrase)?,
+            }),
+            None => None,
+        };
+        files.push(file);
+        files.extend(signature);
+    }
+    Ok(files)
+}
+
+/// Check what the target requires of the manifest before building.
+fn check_publishable(manifest: &Manifest, target: &PublishTarget) -> miette::Result<()> {
+    let package = &manifest.package;
+    let group = package
+        .group
+        .as_deref()
+        .ok_or_else(|| KargoError::Manifest {
+            message: "`kargo publish` needs [package] group".to_string(),
+        })?;
+    let PublishTarget::Central = target else {
+        return Ok(());
+    };
+    if Namespace::of(group).is_none() {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "group `{group}` is not a reverse-domain name like `com.example`, so it \
+                 cannot be published to Maven Central"
+            ),
+        }
+        .into());
+    }
+    if package.version.ends_with("-SNAPSHOT") {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "Maven Central does not accept snapshots like {}; publish them to a \
+                 repository with --repository",
+                package.version
+            ),
+        }
+        .into());
+    }
+    let mut missing = Vec::new();
+    if package.description.is_none() {
+        missing.push("[package] description");
+    }
+    if package.license.is_none() {
+        missing.push("[package] license");
+    }
+    if package.repository.is_none() {
+        missing.push("[package] repository");
+    }
+    if package.authors.is_empty() {
+        missing.push("[package] authors");
+    }
+    if manifest
+        .signing
+        .as_ref()
+        .and_then(|s| s.gpg_key.as_ref())
+        .is_none()
+    {
+        missing.push("[signing] gpg-key");
+    }
+    if !missing.is_empty() {
+        return Err(KargoError::Manifest {
+            message: format!("Maven Central requires {}", missing.join(", ")),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// The POM of `manifest`: its `[dependencies]` and those of the
+/// `[target]` sections matching one of `targets`, and the Kotlin standard
+/// library unless it is declared.
+fn pom_project(manifest: &Manifest, targets: &[KotlinTarget]) -> miette::Result<PomProject> {
+    let package = &manifest.package;
+    let sections = std::iter::once(&manifest.dependencies).chain(
+        manifest
+            .target
+            .iter()
+            .filter(|(selector, _)| {
+                manifest
+                    .targets_matching(selector)
+                    .iter()
+                    .any(|t| targets.contains(t))
+            })
+            .map(|(_, deps)| &deps.dependencies),
+    );
+    let mut dependencies = Vec::new();
+    for (name, dep) in sections.flatten() {
+        let coord = kargo_resolver::resolver::resolve_dep_coordinate(dep, name, manifest)
+            .ok_or_else(|| KargoError::Manifest {
+                message: format!(
+                    "dependency `{name}` is not a Maven artifact, so the published POM \
+                     cannot refer to it; publish it to a repository and depend on its \
+                     coordinate instead"
+                ),
+            })?;
+        let mut pom_dep = PomDependency {
+            group_id: coord.group_id,
+            artifact_id: coord.artifact_id,
+            version: Some(coord.version),
+            scope: None,
+            optional: false,
+            classifier: None,
+            type_: None,
+            exclusions: Vec::new(),
+        };
+        if let Dependency::Detailed(d) = dep {
+            pom_dep.scope = d
+                .scope
+                .filter(|s| *s != DependencyScope::Compile)
+                .map(|s| s.as_str().to_string());
+            pom_dep.optional = d.optional;
+            pom_dep.classifier = d.classifier.clone();
+            pom_dep.exclusions = d
+                .exclusions
+                .iter()
+                .map(|e| PomExclusion {
+                    group_id: e.group.clone(),
+                    artifact_id: e.artifact.clone(),
+                })
+                .collect();
+        }
+        dependencies.push(pom_dep);
+    }
+    if !dependencies
+        .iter()
+        .any(|d| d.group_id == "org.jetbrains.kotlin" && d.artifact_id == "kotlin-stdlib")
+    {
+        dependencies.insert(
+            0,
+            PomDependency {
+                group_id: "org.jetbrains.kotlin".to_string(),
+                artifact_id: "kotlin-stdlib".to_string(),
+                version: Some(package.kotlin.clone()),
+                scope: None,
+                optional: false,
+                classifier: None,
+                type_: None,
+                exclusions: Vec::new(),
+            },
+        );
+    }
+    Ok(PomProject {
+        group: package.group.clone().unwrap_or_default(),
+        artifact: package.name.clone(),
+        version: package.version.clone(),
+        description: package.description.clone(),
+        url: package.repository.clone(),
+        license: package.license.clone(),
+        authors: package.authors.clone(),
+        dependencies,
+    })
+}
+
+/// Write the detached ASCII-armored signature of `file` with the GPG key
+/// `key` to `<file>.asc`.
+fn sign(file: &Path, key: &str, passphrase: Option<&str>) -> miette::Result<PathBuf> {
+    let mut asc = file.as_os_str().to_owned();
+    asc.push(".asc");
+    let asc = PathBuf::from(asc);
+    let mut cmd = std::process::Command::new("gpg");
+    cmd.args([
+        "--batch",
+        "--yes",
+        "--armor",
+        "--detach-sign",
+        "--local-user",
+        key,
+    ]);
+    if passphrase.is_some() {
+        cmd.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+    }
+    let mut child = cmd
+        .arg("--output")
+        .arg(&asc)
+        .arg(file)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run gpg to sign artifacts ({e}); install GnuPG"),
+        })?;
+    if let (Some(passphrase), Some(mut stdin)) = (passphrase, child.stdin.take()) {
+        writeln!(stdin, "{passphrase}").map_err(KargoError::Io)?;
+    }
+    let output = child.wait_with_output().map_err(KargoError::Io)?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "gpg could not sign {} with key {key}: {}",
+                file.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(asc)
+}
+
+/// Upload a Central Portal `bundle` for automatic publishing; returns the
+/// deployment id.
+async fn upload_bundle(token: &str, name: &str, bundle: Vec<u8>) -> miette::Result<String> {
+    const BOUNDARY: &str = "kargo-bundle-boundary";
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"bundle\"; \
+         filename=\"bundle.zip\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend(bundle);
+    body.extend(format!("\r\n--{BOUNDARY}--\r\n").into_bytes());
+
+    let resp = client()?
+        .post(format!("{}/api/v1/publisher/upload", central_url()))
+        .query(&[("publishingType", "AUTOMATIC"), ("name", name)])
+        .bearer_auth(token)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to reach the Central Portal: {e}"),
+        })?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    match status.as_u16() {
+        200..=299 => Ok(text.trim().to_string()),
+        401 => Err(KargoError::Network {
+            message: "the Central Portal rejected the token; generate a user token at \
+                      https://central.sonatype.com/account"
+                .to_string(),
+        }
+        .into()),
+        code => Err(KargoError::Network {
+            message: format!(
+                "the Central Portal rejected the upload (HTTP {code}): {}",
+                text.trim()
+            ),
+        }
+        .into()),
+    }
+}
+
+/// Variables referenced as `${env:VAR}` in `content` that are neither in
+/// `env` (`.kargo.env`) nor set in the process environment.
+fn unset_env_references(content: &str, env: &BTreeMap<String, String>) -> Vec<String> {
+    let mut unset = Vec::new();
+    for (start, _) in content.match_indices("${env:") {
+        let rest = &content[start + 6..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let key = &rest[..end];
+        if !env.contains_key(key)
+            && std::env::var_os(key).is_none()
+            && !unset.iter().any(|k| k == key)
+        {
+            unset.push(key.to_string());
+        }
+    }
+    unset
+}
+
+/// The Central Portal base URL.
+fn central_url() -> String {
+    std::env::var("KARGO_CENTRAL_URL")
+        .map(|u| u.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| CENTRAL_URL.to_string())
+}
+
+fn client() -> miette::Result<reqwest::Client> {
+    Ok(kargo_util::http::client_builder()?
+        .user_agent("kargo")
+        .build()
+        .map_err(|e| KargoError::Network {
+            message: format!("HTTP client error: {e}"),
+        })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_map_to_their_namespace() {
+        assert_eq!(
+            Namespace::of("com.example.lib"),
+            Some(Namespace::Domain {
+                namespace: "com.example".into(),
+                domain: "example.com".into(),
+            })
+        );
+        assert_eq!(
+            Namespace::of("io.github.octocat.tools"),
+            Some(Namespace::CodeHost {
+                namespace: "io.github.octocat".into(),
+                host_url: "https://github.com".into(),
+                user: "octocat".into(),
+            })
+        );
+        assert_eq!(Namespace::of("mylib"), None);
+    }
+
+    #[test]
+    fn txt_answers_are_unquoted() {
+        let body = serde_json::json!({
+            "Answer": [
+                { "name": "example.com", "type": 16, "data": "\"abc123\"" },
+                { "name": "example.com", "type": 16, "data": "\"v=spf1 \" \"-all\"" },
+                { "name": "example.com", "type": 5, "data": "alias.example.com" }
+            ]
+        });
+        assert_eq!(parse_txt_answer(&body), ["abc123", "v=spf1 -all"]);
+    }
+
+    const LIBRARY: &str = r#"
+[package]
+name = "lib"
+group = "com.example"
+version = "1.0.0"
+kotlin = "2.3.0"
+description = "A library"
+license = "MIT"
+repository = "https://github.com/example/lib"
+authors = ["Ana <ana@example.com>"]
+
+[targets]
+jvm = {}
+ios-arm64 = {}
+
+[signing]
+gpg-key = "ABCD1234"
+
+[dependencies]
+okio = "com.squareup.okio:okio:3.9.0"
+json = { group = "org.json", artifact = "json", version = "20240303", scope = "runtime", exclusions = [{ group = "org.slf4j" }] }
+
+[dev-dependencies]
+junit = "junit:junit:4.13.2"
+
+[target.jvm.dependencies]
+logback = "ch.qos.logback:logback-classic:1.5.6"
+
+[target.ios-arm64.dependencies]
+ktor = "io.ktor:ktor-client-darwin:3.0.0"
+"#;
+
+    #[test]
+    fn central_requires_metadata_and_signing() {
+        let mut manifest = Manifest::parse_toml(LIBRARY).unwrap();
+        assert!(check_publishable(&manifest, &PublishTarget::Central).is_ok());
+
+        manifest.package.license = None;
+        manifest.signing = None;
+        let err = check_publishable(&manifest, &PublishTarget::Central).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("[package] license, [signing] gpg-key"));
+        let repo = PublishTarget::Repository(MavenRepository::maven_central());
+        assert!(check_publishable(&manifest, &repo).is_ok());
+
+        manifest.package.version = "1.1.0-SNAPSHOT".into();
+        assert!(check_publishable(&manifest, &PublishTarget::Central).is_err());
+        manifest.package.group = None;
+        assert!(check_publishable(&manifest, &repo).is_err());
+    }
+
+    #[test]
+    fn pom_lists_dependencies_of_the_published_target() {
+        let manifest = Manifest::parse_toml(LIBRARY).unwrap();
+        let project = pom_project(&manifest, &[KotlinTarget::Jvm]).unwrap();
+        let deps: Vec<(&str, Option<&str>)> = project
+            .dependencies
+            .iter()
+            .map(|d| (d.artifact_id.as_str(), d.scope.as_deref()))
+            .collect();
+        assert_eq!(
+            deps,
+            [
+                ("kotlin-stdlib", None),
+                ("json", Some("runtime")),
+                ("okio", None),
+                ("logback-classic", None),
+            ]
+        );
+        assert_eq!(project.dependencies[0].version.as_deref(), Some("2.3.0"));
+        assert_eq!(project.dependencies[1].exclusions[0].group_id, "org.slf4j");
+
+        let manifest = Manifest::parse_toml(&format!(
+            "{LIBRARY}\n[dependencies.vendored]\nfile = \"libs/vendored.jar\"\n"
+        ))
+        .unwrap();
+        let err = pom_project(&manifest, &[KotlinTarget::Jvm]).unwrap_err();
+        assert!(err.to_string().contains("`vendored`"));
+    }
+
+    #[test]
+    fn env_references_must_be_set() {
+        let env = BTreeMap::from([("GPG_KEY".to_string(), "ABCD1234".to_string())]);
+        let content = "gpg-key = \"${env:GPG_KEY}\"\n\
+                       gpg-password = \"${env:KARGO_TEST_UNSET_PASSPHRASE}\"\n\
+                       password = \"${env:KARGO_TEST_UNSET_PASSPHRASE}\"\n";
+        assert_eq!(
+            unset_env_references(content, &env),
+            ["KARGO_TEST_UNSET_PASSPHRASE"]
+        );
+    }
+}
@@ -0,0 +1,799 @@
+//! Operation: `kargo release`.
+//!
+//! Cuts a release from the `## [Unreleased]` section of `CHANGELOG.md`
+//! (the keep-a-changelog layout): bumps `package.version`, dates the
+//! changelog section, commits both files, tags `v<version>` with the
+//! section as the tag message, and builds the release JAR. Publishing to a
+//! `[repositories]` entry (with the `-sources.jar` and `-javadoc.jar` of
+//! [`crate::ops_package`]), pushing, and creating a GitHub Release with the
+//! JAR attached are opt-in.
+//!
+//! If a step fails before anything leaves the machine, the local changes
+//! are rolled back: the tag is deleted, the release commit is undone, and
+//! `Kargo.toml` and `CHANGELOG.md` are restored. Publishing, pushing and the
+//! GitHub Release run only after that; once one of them has started,
+//! nothing is rolled back and the error says what remains to do.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+use semver::Version;
+use toml_edit::DocumentMut;
+
+use crate::ops_build::{self, BuildOptions};
+
+/// GitHub REST API used when `GITHUB_API_URL` is not set.
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// Options for `kargo release`.
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseOptions {
+    /// `major`, `minor`, `patch`, or an explicit version.
+    pub level: String,
+    /// Print the plan without changing anything.
+    pub dry_run: bool,
+    /// Name of the `[repositories]` entry to publish to.
+    pub publish: Option<String>,
+    /// Push the release commit and tag to the `origin` remote.
+    pub push: bool,
+    /// Create a GitHub Release for the tag (implies `push`).
+    pub github: bool,
+    /// Allow uncommitted changes in the working tree.
+    pub allow_dirty: bool,
+    pub verbose: bool,
+}
+
+/// The version that follows `current` for `level`.
+///
+/// A pre-release is finished by `patch` (`1.2.0-rc.1` becomes `1.2.0`);
+/// an explicit version must be greater than `current`.
+pub fn bump_version(current: &Version, level: &str) -> miette::Result<Version> {
+    let next = match level {
+        "major" => Version::new(current.major + 1, 0, 0),
+        "minor" => Version::new(current.major, current.minor + 1, 0),
+        "patch" if !current.pre.is_empty() => {
+            Version::new(current.major, current.minor, current.patch)
+        }
+        "patch" => Version::new(current.major, current.minor, current.patch + 1),
+        explicit => {
+            let next = Version::parse(explicit.trim_start_matches('v')).map_err(|e| {
+                KargoError::Generic {
+                    message: format!(
+                        "Invalid release version '{explicit}' (expected major, minor, patch, \
+                         or a semantic version): {e}"
+                    ),
+                }
+            })?;
+            if next <= *current {
+                return Err(KargoError::Generic {
+                    message: format!("Release version {next} is not newer than {current}"),
+                }
+                .into());
+            }
+            next
+        }
+    };
+    Ok(next)
+}
+
+/// Rewrite `Kargo.toml` content with `package.version` set to `version`,
+/// keeping formatting and comments.
+pub fn set_package_version(content: &str, version: &Version) -> miette::Result<String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| KargoError::Manifest {
+        message: format!("Failed to parse Kargo.toml: {e}"),
+    })?;
+    let item = doc
+        .get_mut("package")
+        .and_then(|p| p.get_mut("version"))
+        .filter(|v| v.is_str())
+        .ok_or_else(|| KargoError::Manifest {
+            message: "[package] version must be a string to be bumped by `kargo release`"
+                .to_string(),
+        })?;
+    let decor = item.as_value().map(|v| v.decor().clone());
+    *item = toml_edit::value(version.to_string());
+    if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+        *value.decor_mut() = decor;
+    }
+    Ok(doc.to_string())
+}
+
+/// A changelog with its `Unreleased` section turned into a release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangelogRelease {
+    /// The full rewritten changelog.
+    pub content: String,
+    /// The body of the released section, used as release notes.
+    pub notes: String,
+}
+
+/// Turn the `## [Unreleased]` section of `changelog` into
+/// `## [<version>] - <date>` under a fresh, empty `Unreleased` heading.
+///
+/// A keep-a-changelog `[Unreleased]: <base>/compare/<prev>...HEAD` link is
+/// moved to the new tag and a link for the released version is added.
+pub fn release_changelog(
+    changelog: &str,
+    version: &Version,
+    tag: &str,
+    date: &str,
+) -> miette::Result<ChangelogRelease> {
+    let lines: Vec<&str> = changelog.lines().collect();
+    let heading = lines
+        .iter()
+        .position(|l| is_unreleased_heading(l))
+        .ok_or_else(|| KargoError::Generic {
+            message: "CHANGELOG.md has no `## [Unreleased]` section".to_string(),
+        })?;
+    let end = lines[heading + 1..]
+        .iter()
+        .position(|l| l.starts_with("## ") || is_link_definition(l))
+        .map_or(lines.len(), |i| heading + 1 + i);
+    let notes = lines[heading + 1..end].join("\n").trim().to_string();
+    if notes.is_empty() {
+        return Err(KargoError::Generic {
+            message: "CHANGELOG.md has no entries under `## [Unreleased]`; describe the \
+                      release there first"
+                .to_string(),
+        }
+        .into());
+    }
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len() + 3);
+    out.extend(lines[..heading].iter().map(|l| l.to_string()));
+    out.push("## [Unreleased]".to_string());
+    out.push(String::new());
+    out.push(format!("## [{version}] - {date}"));
+    for line in &lines[heading + 1..] {
+        match compare_link(line) {
+            Some((base, previous)) => {
+                out.push(format!("[Unreleased]: {base}/compare/{tag}...HEAD"));
+                out.push(format!("[{version}]: {base}/compare/{previous}...{tag}"));
+            }
+            None => out.push(line.to_string()),
+        }
+    }
+    let mut content = out.join("\n");
+    if changelog.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(ChangelogRelease { content, notes })
+}
+
+fn is_unreleased_heading(line: &str) -> bool {
+    line.strip_prefix("## ").is_some_and(|title| {
+        let title = title.trim().trim_start_matches('[');
+        title
+            .get(..10)
+            .is_some_and(|t| t.eq_ignore_ascii_case("unreleased"))
+    })
+}
+
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[') && line.contains("]: ")
+}
+
+/// `(base, previous tag)` of an `[Unreleased]: <base>/compare/<prev>...HEAD` line.
+fn compare_link(line: &str) -> Option<(&str, &str)> {
+    let (label, url) = line.split_once("]: ")?;
+    if !label
+        .trim_start_matches('[')
+        .eq_ignore_ascii_case("unreleased")
+    {
+        return None;
+    }
+    let (base, range) = url.trim().rsplit_once("/compare/")?;
+    let previous = range.strip_suffix("...HEAD")?;
+    Some((base, previous))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let (y, m, d) = civil_from_days((unix_now() / 86_400) as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// The current UTC time as an RFC 3339 timestamp (`YYYY-MM-DDTHH:MM:SSZ`).
+pub(crate) fn utc_timestamp() -> String {
+    let secs = unix_now();
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let day = secs % 86_400;
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+        day / 3600,
+        day % 3600 / 60,
+        day % 60
+    )
+}
+
+/// Proleptic Gregorian `(year, month, day)` for days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `owner/repo` of a GitHub repository URL (`https://github.com/o/r[.git]`
+/// or `git@github.com:o/r.git`).
+pub fn github_repo(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.split_once("github.com/").map(|(_, p)| p))?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let mut parts = path.split('/');
+    let (owner, repo) = (parts.next()?, parts.next()?);
+    (!owner.is_empty() && !repo.is_empty()).then(|| format!("{owner}/{repo}"))
+}
+
+/// A GitHub token from `GITHUB_TOKEN` or `GH_TOKEN`.
+pub(crate) fn github_token() -> Option<String> {
+    std::env::var("GITHUB_TOKEN")
+        .or_else(|_| std::env::var("GH_TOKEN"))
+        .ok()
+        .filter(|t| !t.is_empty())
+}
+
+/// The GitHub REST API base URL, honouring `GITHUB_API_URL` (set by
+/// GitHub Actions, including on GitHub Enterprise Server).
+pub(crate) fn github_api_base() -> String {
+    std::env::var("GITHUB_API_URL")
+        .map(|u| u.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| GITHUB_API_BASE.to_string())
+}
+
+/// Run `git` in `dir` and return its trimmed stdout.
+pub(crate) fn git(dir: &Path, args: &[&str]) -> miette::Result<String> {
+    let out = CommandBuilder::new("git")
+        .args(args.iter().copied())
+        .cwd(dir.to_string_lossy())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run git: {e}"),
+        })?;
+    if !out.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&out.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Local changes made so far, undone in reverse if a later step fails.
+struct Journal {
+    head: String,
+    files: Vec<(PathBuf, String)>,
+    committed: bool,
+    tag: Option<String>,
+}
+
+impl Journal {
+    fn rollback(&self, project_dir: &Path) {
+        let mut failures = Vec::new();
+        if let Some(tag) = &self.tag {
+            if let Err(e) = git(project_dir, &["tag", "-d", tag]) {
+                failures.push(e.to_string());
+            }
+        }
+        if self.committed {
+            if let Err(e) = git(project_dir, &["reset", "-q", "--soft", &self.head]) {
+                failures.push(e.to_string());
+            }
+        }
+        for (path, content) in &self.files {
+            if let Err(e) = std::fs::write(path, content) {
+                failures.push(format!("{}: {e}", path.display()));
+            }
+        }
+        let mut unstage = vec!["reset", "-q", "--"];
+        unstage.extend(
+            self.files
+                .iter()
+                .filter_map(|(p, _)| p.file_name()?.to_str()),
+        );
+        let _ = git(project_dir, &unstage);
+
+        if failures.is_empty() {
+            kargo_util::progress::status_warn("Rolled back", "local release changes");
+        } else {
+            for failure in failures {
+                kargo_util::progress::status_warn("Rollback", &failure);
+            }
+        }
+    }
+}
+
+/// Cut a release as described in the module docs.
+pub async fn release(project_dir: &Path, opts: &ReleaseOptions) -> miette::Result<()> {
+    let manifest_path = project_dir.join("Kargo.toml");
+    let manifest = Manifest::from_path(&manifest_path)?;
+    let current = Version::parse(&manifest.package.version).map_err(|e| KargoError::Manifest {
+        message: format!(
+            "[package] version '{}' is not a semantic version: {e}",
+            manifest.package.version
+        ),
+    })?;
+    let next = bump_version(&current, &opts.level)?;
+    let tag = format!("v{next}");
+
+    let changelog_path = project_dir.join("CHANGELOG.md");
+    let changelog = std::fs::read_to_string(&changelog_path).map_err(|_| KargoError::Generic {
+        message: "No CHANGELOG.md found; `kargo release` takes its notes from the \
+                  `## [Unreleased]` section"
+            .to_string(),
+    })?;
+    let released = release_changelog(&changelog, &next, &tag, &today())?;
+    let manifest_content = std::fs::read_to_string(&manifest_path).map_err(KargoError::Io)?;
+    let new_manifest = set_package_version(&manifest_content, &next)?;
+
+    // Check everything that can fail without side effects up front.
+    let publish_to = match &opts.publish {
+        Some(name) => {
+            let repo = kargo_resolver::resolver::build_repos(&manifest)
+                .into_iter()
+                .find(|r| &r.name == name)
+                .ok_or_else(|| KargoError::Manifest {
+                    message: format!("No repository named '{name}' in [repositories]"),
+                })?;
+            let group = manifest
+                .package
+                .group
+                .clone()
+                .ok_or_else(|| KargoError::Manifest {
+                    message: "Publishing needs [package] group".to_string(),
+                })?;
+            Some((repo, group))
+        }
+        None => None,
+    };
+    let github = if opts.github {
+        let repo = manifest
+            .package
+            .repository
+            .as_deref()
+            .and_then(github_repo)
+            .ok_or_else(|| KargoError::Manifest {
+                message: "--github needs [package] repository to be a github.com URL".to_string(),
+            })?;
+        let token = github_token().ok_or_else(|| KargoError::Generic {
+            message: "--github needs a token in GITHUB_TOKEN or GH_TOKEN".to_string(),
+        })?;
+        Some((repo, token))
+    } else {
+        None
+    };
+    if !crate::ops_new::is_inside_git_repo(project_dir) {
+        return Err(KargoError::Generic {
+            message: "`kargo release` must run inside a git repository".to_string(),
+        }
+        .into());
+    }
+    if !opts.allow_dirty && !git(project_dir, &["status", "--porcelain"])?.is_empty() {
+        return Err(KargoError::Generic {
+            message: "The working tree has uncommitted changes; commit them or pass \
+                      --allow-dirty"
+                .to_string(),
+        }
+        .into());
+    }
+    let tag_ref = format!("refs/tags/{tag}");
+    if git(project_dir, &["rev-parse", "-q", "--verify", &tag_ref]).is_ok() {
+        return Err(KargoError::Generic {
+            message: format!("Tag {tag} already exists"),
+        }
+        .into());
+    }
+
+    let push = opts.push || opts.github;
+    if opts.dry_run {
+        kargo_util::progress::status_info("Release", &format!("{current} -> {next}"));
+        let mut steps = vec![
+            format!("set [package] version = \"{next}\" in Kargo.toml"),
+            format!("date the Unreleased section of CHANGELOG.md as {next}"),
+            format!("commit \"Release {tag}\" and tag {tag}"),
+            "build the release JAR".to_string(),
+        ];
+        if let Some((repo, _)) = &publish_to {
+            steps.push(format!("publish to {} ({})", repo.name, repo.url));
+        }
+        if push {
+            steps.push(format!("push HEAD and {tag} to origin"));
+        }
+        if let Some((repo, _)) = &github {
+            steps.push(format!("create GitHub Release {tag} on {repo}"));
+        }
+        for (i, step) in steps.iter().enumerate() {
+            println!("  {}. {step}", i + 1);
+        }
+        println!("\nRelease notes:\n\n{}", released.notes);
+        kargo_util::progress::status_warn("Dry run", "no changes were made");
+        return Ok(());
+    }
+
+    let mut journal = Journal {
+        head: git(project_dir, &["rev-parse", "HEAD"])?,
+        files: vec![
+            (manifest_path, manifest_content),
+            (changelog_path, changelog),
+        ],
+        committed: false,
+        tag: None,
+    };
+    let prepared = match prepare(
+        project_dir,
+        opts,
+        &new_manifest,
+        &released,
+        &tag,
+        publish_to.is_some(),
+        &mut journal,
+    )
+    .await
+    {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            journal.rollback(project_dir);
+            return Err(e);
+        }
+    };
+
+    // Past this point the release may be public; report instead of undoing.
+    let remaining = |pushed: bool| -> String {
+        let mut steps = Vec::new();
+        if push && !pushed {
+            steps.push(format!("`git push origin HEAD {tag}`"));
+        }
+        if github.is_some() {
+            steps.push(format!("create the GitHub Release for {tag}"));
+        }
+        if steps.is_empty() {
+            String::new()
+        } else {
+            format!("; then {}", steps.join(" and "))
+        }
+    };
+    if let Some((repo, group)) = &publish_to {
+        let version = next.to_string();
+        kargo_maven::publish::publish_artifact(
+            repo,
+            group,
+            &manifest.package.name,
+            &version,
+            &prepared.files,
+        )
+        .await
+        .map_err(|e| KargoError::Generic {
+            message: format!(
+                "{e}\n  The release commit and tag {tag} exist locally and some files \
+                 may already be in {}; finish publishing with \
+                 `kargo publish --repository {}`{}",
+                repo.name,
+                repo.name,
+                remaining(false)
+            ),
+        })?;
+        kargo_util::progress::status("Published", &format!("{version} to {}", repo.name));
+    }
+    if push {
+        git(project_dir, &["push", "origin", "HEAD", &tag_ref]).map_err(|e| {
+            KargoError::Generic {
+                message: format!(
+                    "{e}\n  The release commit and tag {tag} exist locally; push them with \
+                     `git push origin HEAD {tag}`{}",
+                    remaining(true)
+                ),
+            }
+        })?;
+        kargo_util::progress::status("Pushed", &format!("{tag} to origin"));
+    }
+    if let Some((repo, token)) = &github {
+        let url = create_github_release(repo, token, &tag, &released.notes, &prepared.jar)
+            .await
+            .map_err(|e| KargoError::Network {
+                message: format!(
+                    "{e}\n  {tag} is pushed; create the GitHub Release by hand or rerun \
+                     the step from the GitHub UI"
+                ),
+            })?;
+        kargo_util::progress::status("Released", &format!("GitHub Release {url}"));
+    }
+
+    kargo_util::progress::status(
+        "Released",
+        &format!("{} {next} ({tag})", manifest.package.name),
+    );
+    Ok(())
+}
+
+/// What [`prepare`] leaves ready to ship.
+struct Prepared {
+    /// The release JAR.
+    jar: PathBuf,
+    /// The files to publish, when publishing.
+    files: Vec<kargo_maven::publish::PublishFile>,
+}
+
+/// The local, reversible steps: edit, commit, tag, build, and (when
+/// `publish` is set) write the sources, javadoc, POM and signatures.
+/// Nothing leaves the machine here.
+async fn prepare(
+    project_dir: &Path,
+    opts: &ReleaseOptions,
+    new_manifest: &str,
+    released: &ChangelogRelease,
+    tag: &str,
+    publish: bool,
+    journal: &mut Journal,
+) -> miette::Result<Prepared> {
+    let manifest_path = journal.files[0].0.clone();
+    let changelog_path = journal.files[1].0.clone();
+    std::fs::write(&manifest_path, new_manifest).map_err(KargoError::Io)?;
+    std::fs::write(&changelog_path, &released.content).map_err(KargoError::Io)?;
+
+    commit_release(project_dir, tag)?;
+    journal.committed = true;
+    git(
+        project_dir,
+        &[
+            "tag",
+            "-a",
+            tag,
+            "-m",
+            &format!("{tag}\n\n{}", released.notes),
+        ],
+    )?;
+    journal.tag = Some(tag.to_string());
+    kargo_util::progress::status("Tagged", tag);
+
+    let build = ops_build::build(
+        project_dir,
+        &BuildOptions {
+            release: true,
+            verbose: opts.verbose,
+            ..Default::default()
+        },
+    )
+    .await?;
+    let jar = build
+        .output_jar
+        .clone()
+        .ok_or_else(|| KargoError::Generic {
+            message: format!(
+                "The release build for {} produced no JAR to release",
+                build.target
+            ),
+        })?;
+
+    let files = if publish {
+        crate::ops_publish::publication_files(project_dir, &build, opts.verbose).await?
+    } else {
+        Vec::new()
+    };
+    Ok(Prepared { jar, files })
+}
+
+/// Commit `Kargo.toml` and `CHANGELOG.md` as `Release <tag>`. Only those two
+/// files are committed; anything else staged (with `--allow-dirty`) stays
+/// staged and out of the release.
+fn commit_release(project_dir: &Path, tag: &str) -> miette::Result<()> {
+    git(
+        project_dir,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            &format!("Release {tag}"),
+            "--",
+            "Kargo.toml",
+            "CHANGELOG.md",
+        ],
+    )?;
+    Ok(())
+}
+
+/// Create a GitHub Release for `tag` with `jar` attached; returns its URL.
+async fn create_github_release(
+    repo: &str,
+    token: &str,
+    tag: &str,
+    notes: &str,
+    jar: &Path,
+) -> Result<String, String> {
+    let api = github_api_base();
+    let client = kargo_util::http::client_builder()
+        .map_err(|e| e.to_string())?
+        .user_agent("kargo-release")
+        .build()
+        .map_err(|e| format!("HTTP client error: {e}"))?;
+
+    let resp = client
+        .post(format!("{api}/repos/{repo}/releases"))
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .json(&serde_json::json!({ "tag_name": tag, "name": tag, "body": notes }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach GitHub: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "GitHub API returned HTTP {} creating release {tag}",
+            resp.status()
+        ));
+    }
+    let release: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GitHub release JSON: {e}"))?;
+    let html_url = release["html_url"].as_str().unwrap_or_default().to_string();
+    // `upload_url` is a URI template: `.../assets{?name,label}`.
+    let upload_url = release["upload_url"]
+        .as_str()
+        .and_then(|u| u.split('{').next())
+        .ok_or("GitHub release response has no upload_url")?;
+
+    let name = jar
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "release.jar".to_string());
+    let bytes = std::fs::read(jar).map_err(|e| format!("{}: {e}", jar.display()))?;
+    let resp = client
+        .post(upload_url)
+        .query(&[("name", name.as_str())])
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("Content-Type", "application/java-archive")
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to upload {name}: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "GitHub API returned HTTP {} uploading {name}",
+            resp.status()
+        ));
+    }
+    Ok(html_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn bump_levels_and_explicit_versions() {
+        assert_eq!(bump_version(&v("1.2.3"), "major").unwrap(), v("2.0.0"));
+        assert_eq!(bump_version(&v("1.2.3"), "minor").unwrap(), v("1.3.0"));
+        assert_eq!(bump_version(&v("1.2.3"), "patch").unwrap(), v("1.2.4"));
+        assert_eq!(bump_version(&v("1.3.0-rc.1"), "patch").unwrap(), v("1.3.0"));
+        assert_eq!(bump_version(&v("1.2.3"), "v1.5.0").unwrap(), v("1.5.0"));
+        assert!(bump_version(&v("1.2.3"), "1.2.3").is_err());
+        assert!(bump_version(&v("1.2.3"), "next").is_err());
+    }
+
+    #[test]
+    fn version_is_set_in_place() {
+        let toml = "[package]\nname = \"app\"\nversion = \"0.1.0\" # bumped by release\nkotlin = \"2.3.0\"\n";
+        assert_eq!(
+            set_package_version(toml, &v("0.2.0")).unwrap(),
+            "[package]\nname = \"app\"\nversion = \"0.2.0\" # bumped by release\nkotlin = \"2.3.0\"\n"
+        );
+        assert!(set_package_version("[package]\nversion.workspace = true\n", &v("1.0.0")).is_err());
+    }
+
+    #[test]
+    fn changelog_section_is_released() {
+        let changelog = "# Changelog\n\
+                         \n\
+                         ## [Unreleased]\n\
+                         \n\
+                         ### Added\n\
+                         - `kargo release`\n\
+                         \n\
+                         ## [0.1.0] - 2024-01-02\n\
+                         - First\n\
+                         \n\
+                         [Unreleased]: https://github.com/o/r/compare/v0.1.0...HEAD\n\
+                         [0.1.0]: https://github.com/o/r/releases/tag/v0.1.0\n";
+        let released = release_changelog(changelog, &v("0.2.0"), "v0.2.0", "2024-03-04").unwrap();
+        assert_eq!(released.notes, "### Added\n- `kargo release`");
+        assert_eq!(
+            released.content,
+            "# Changelog\n\
+             \n\
+             ## [Unreleased]\n\
+             \n\
+             ## [0.2.0] - 2024-03-04\n\
+             \n\
+             ### Added\n\
+             - `kargo release`\n\
+             \n\
+             ## [0.1.0] - 2024-01-02\n\
+             - First\n\
+             \n\
+             [Unreleased]: https://github.com/o/r/compare/v0.2.0...HEAD\n\
+             [0.2.0]: https://github.com/o/r/compare/v0.1.0...v0.2.0\n\
+             [0.1.0]: https://github.com/o/r/releases/tag/v0.1.0\n"
+        );
+    }
+
+    #[test]
+    fn empty_or_missing_unreleased_section_is_an_error() {
+        let empty = "## [Unreleased]\n\n## [0.1.0] - 2024-01-02\n- First\n";
+        assert!(release_changelog(empty, &v("0.2.0"), "v0.2.0", "2024-03-04").is_err());
+        assert!(release_changelog("# Changelog\n", &v("0.2.0"), "v0.2.0", "2024-03-04").is_err());
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn github_repos_from_urls() {
+        assert_eq!(
+            github_repo("https://github.com/o/r").as_deref(),
+            Some("o/r")
+        );
+        assert_eq!(
+            github_repo("https://github.com/o/r.git/").as_deref(),
+            Some("o/r")
+        );
+        assert_eq!(
+            github_repo("git@github.com:o/r.git").as_deref(),
+            Some("o/r")
+        );
+        assert_eq!(github_repo("https://gitlab.com/o/r"), None);
+    }
+
+    #[test]
+    fn release_commit_leaves_other_staged_changes_out() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        git(dir, &["init", "-q"]).unwrap();
+        git(dir, &["config", "user.email", "dev@example.com"]).unwrap();
+        git(dir, &["config", "user.name", "Dev"]).unwrap();
+        for file in ["Kargo.toml", "CHANGELOG.md", "notes.txt"] {
+            std::fs::write(dir.join(file), "old\n").unwrap();
+        }
+        git(dir, &["add", "."]).unwrap();
+        git(dir, &["commit", "-q", "-m", "init"]).unwrap();
+
+        std::fs::write(dir.join("notes.txt"), "staged\n").unwrap();
+        git(dir, &["add", "notes.txt"]).unwrap();
+        std::fs::write(dir.join("Kargo.toml"), "new\n").unwrap();
+        std::fs::write(dir.join("CHANGELOG.md"), "new\n").unwrap();
+        commit_release(dir, "v1.0.0").unwrap();
+
+        let committed = git(dir, &["show", "--name-only", "--format=%s", "HEAD"]).unwrap();
+        assert_eq!(committed, "Release v1.0.0\n\nCHANGELOG.md\nKargo.toml");
+        let staged = git(dir, &["diff", "--cached", "--name-only"]).unwrap();
+        assert_eq!(staged, "notes.txt");
+    }
+}
@@ -1,26 +1,52 @@
 //! Operation: build and run the project.
 //!
 //! Compiles main sources via `ops_build`, then invokes `java` with the
-//! compiled classpath to run the application.
+//! compiled classpath to run the application. `wasm-js` builds are served
+//! by the development server in [`crate::ops_wasm`] instead.
+//!
+//! The application inherits Kargo's stdin, stdout, and stderr, and its exit
+//! code is returned unchanged. With [`RunOptions::exec`] (Unix only) the
+//! Kargo process is replaced by the JVM, so signals go straight to the
+//! application. [`RunOptions::debug`] adds a JDWP agent to the application
+//! JVM only; the compiler JVMs started by the build never see it.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use kargo_compiler::classpath;
+use kargo_core::manifest::Manifest;
+use kargo_core::target::KotlinTarget;
 use kargo_util::errors::KargoError;
 
 use crate::ops_build::{self, BuildOptions};
 
-/// Run the project after building.
-pub async fn run(
-    project_dir: &Path,
-    target: Option<&str>,
-    run_args: &[String],
-    verbose: bool,
-) -> miette::Result<()> {
+/// Options for [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub target: Option<String>,
+    /// Arguments passed to the application unchanged.
+    pub args: Vec<String>,
+    /// Replace the Kargo process with the JVM instead of spawning it.
+    pub exec: bool,
+    /// JDWP port: the application JVM starts suspended until a debugger
+    /// attaches. Only the application gets the agent, not the build.
+    pub debug: Option<u16>,
+    pub verbose: bool,
+}
+
+/// Run the project after building. Returns the application's exit code.
+pub async fn run(project_dir: &Path, opts: &RunOptions) -> miette::Result<i32> {
+    let verbose = opts.verbose;
+    if opts.exec && !cfg!(unix) {
+        return Err(KargoError::Generic {
+            message: "`kargo run --exec` is only supported on Unix".into(),
+        }
+        .into());
+    }
+
     let build_result = ops_build::build(
         project_dir,
         &BuildOptions {
-            target: target.map(String::from),
+            target: opts.target.clone(),
             verbose,
             quiet: true,
             ..Default::default()
@@ -29,47 +55,26 @@ pub async fn run(
     .await?;
 
     if !build_result.success {
-        return Err(KargoError::Generic {
-            message: "Build failed, cannot run.".into(),
+        return Err(KargoError::Compilation {
+            message: "cannot run the program".into(),
         }
         .into());
     }
 
-    let manifest = &build_result.manifest;
-    let preflight = &build_result.preflight;
-
-    let main_class = manifest
-        .package
-        .main_class
-        .clone()
-        .or_else(|| detect_main_class(project_dir))
-        .ok_or_else(|| KargoError::Generic {
-            message: "Could not detect main class. Set [package] main-class in Kargo.toml \
-                      or add a file containing `fun main()`."
+    if build_result.target == KotlinTarget::WasmJs {
+        let bundle = build_result.output_jar.ok_or_else(|| KargoError::Generic {
+            message: "No wasm-js bundle was produced. Add a `fun main()` to the main sources."
                 .into(),
         })?;
-
-    let cp = &build_result.classpath;
-    let mut cp_parts = vec![build_result.classes_dir.to_string_lossy().to_string()];
-
-    let resources_dir = build_result.build_dir.join("resources");
-    if resources_dir.is_dir() {
-        cp_parts.push(resources_dir.to_string_lossy().to_string());
+        crate::ops_wasm::serve(project_dir, &bundle, verbose).await?;
+        return Ok(0);
     }
 
-    let kotlin_lib = preflight.toolchain.home.join("lib");
-    for jar_name in kargo_compiler::classpath::STDLIB_RUNTIME_JARS {
-        let jar = kotlin_lib.join(jar_name);
-        if jar.is_file() {
-            cp_parts.push(jar.to_string_lossy().to_string());
-        }
-    }
-
-    if !cp.compile_jars.is_empty() {
-        cp_parts.push(classpath::to_classpath_string(&cp.compile_jars));
-    }
+    let manifest = &build_result.manifest;
+    let preflight = &build_result.preflight;
 
-    let classpath_str = cp_parts.join(if cfg!(windows) { ";" } else { ":" });
+    let main_class = main_class(manifest, project_dir)?;
+    let classpath_str = classpath::to_classpath_string(&launch_classpath(&build_result));
     let java_bin = preflight.jdk.home.join("bin").join("java");
 
     kargo_util::progress::status("Running", &main_class);
@@ -77,39 +82,122 @@ pub async fn run(
         eprintln!("  java: {}", java_bin.display());
     }
 
-    let mut cmd = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
-        .arg("-cp")
-        .arg(&classpath_str)
-        .arg(&main_class)
-        .args(run_args.iter().cloned());
-
-    cmd = cmd.env(
-        "JAVA_HOME",
-        preflight.jdk.home.to_string_lossy().to_string(),
+    let cmd = java_command(
+        &java_bin,
+        preflight.jdk.java_accepts_argfiles(),
+        &preflight.jdk.home,
+        &classpath_str,
+        &main_class,
+        opts,
     );
 
-    let output = cmd.exec().map_err(|e| KargoError::Generic {
-        message: format!("Failed to execute java: {e}"),
-    })?;
+    #[cfg(unix)]
+    if opts.exec {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        let e = cmd.exec_replace();
+        return Err(KargoError::Generic {
+            message: format!("Failed to execute java: {e}"),
+        }
+        .into());
+    }
+
+    run_foreground(cmd).await
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stdout.is_empty() {
-        print!("{stdout}");
+/// The `java` command line running `main_class`: JVM options, then the
+/// classpath and main class, then the application's arguments unchanged.
+fn java_command(
+    java_bin: &Path,
+    argfiles: bool,
+    java_home: &Path,
+    classpath: &str,
+    main_class: &str,
+    opts: &RunOptions,
+) -> kargo_util::process::CommandBuilder {
+    let mut cmd = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(argfiles);
+    if let Some(port) = opts.debug {
+        cmd = cmd.arg(jdwp_agent(port));
     }
-    if !stderr.is_empty() {
-        eprint!("{stderr}");
+    cmd.arg("-cp")
+        .arg(classpath)
+        .arg(main_class)
+        .args(opts.args.iter().cloned())
+        .env("JAVA_HOME", java_home.to_string_lossy().to_string())
+}
+
+/// The `-agentlib:jdwp` option that suspends the JVM until a debugger
+/// attaches on `port`.
+pub(crate) fn jdwp_agent(port: u16) -> String {
+    format!("-agentlib:jdwp=transport=dt_socket,server=y,suspend=y,address=*:{port}")
+}
+
+/// Run the JVM command `cmd` and return its exit code. The program shares
+/// the terminal's process group, so Ctrl-C reaches it directly. Kargo keeps
+/// waiting so the program decides how (and whether) to exit, and its exit
+/// code is reported unchanged.
+pub(crate) async fn run_foreground(
+    cmd: kargo_util::process::CommandBuilder,
+) -> miette::Result<i32> {
+    let child = tokio::task::spawn_blocking(move || cmd.status());
+    tokio::pin!(child);
+    let status = loop {
+        tokio::select! {
+            joined = &mut child => break joined,
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    };
+    let status = status
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to wait for java: {e}"),
+        })?
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to execute java: {e}"),
+        })?;
+
+    Ok(kargo_util::process::exit_code(status))
+}
+
+/// The application's main class: `[package] main-class`, or the file
+/// declaring `fun main()`.
+pub(crate) fn main_class(manifest: &Manifest, project_dir: &Path) -> miette::Result<String> {
+    manifest
+        .package
+        .main_class
+        .clone()
+        .or_else(|| detect_main_class(project_dir))
+        .ok_or_else(|| {
+            KargoError::Generic {
+                message: "Could not detect main class. Set [package] main-class in Kargo.toml \
+                          or add a file containing `fun main()`."
+                    .into(),
+            }
+            .into()
+        })
+}
+
+/// Classpath the application runs with: compiled classes, resources, the
+/// Kotlin stdlib and the runtime dependencies, in that order.
+pub(crate) fn launch_classpath(build_result: &ops_build::BuildResult) -> Vec<PathBuf> {
+    let mut entries = vec![build_result.classes_dir.clone()];
+
+    let resources_dir = build_result.build_dir.join("resources");
+    if resources_dir.is_dir() {
+        entries.push(resources_dir);
     }
 
-    if !output.status.success() {
-        let code = output.status.code().unwrap_or(1);
-        return Err(KargoError::Generic {
-            message: format!("Process exited with code {code}"),
+    let kotlin_lib = build_result.preflight.toolchain.home.join("lib");
+    for jar_name in classpath::STDLIB_RUNTIME_JARS {
+        let jar = kotlin_lib.join(jar_name);
+        if jar.is_file() {
+            entries.push(jar);
         }
-        .into());
     }
 
-    Ok(())
+    entries.extend(build_result.classpath.runtime_jars.iter().cloned());
+    entries
 }
 
 fn detect_main_class(project_dir: &Path) -> Option<String> {
@@ -167,3 +255,51 @@ fn derive_main_class(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in `java` that records its arguments and exits with 3.
+    #[cfg(unix)]
+    fn fake_java(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let java = dir.join("java");
+        std::fs::write(
+            &java,
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > \"$(dirname \"$0\")/args\"\nexit 3\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755)).unwrap();
+        java
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn application_gets_its_args_unchanged_and_reports_its_exit_code() {
+        let tmp = tempfile::tempdir().unwrap();
+        let java = fake_java(tmp.path());
+        let opts = RunOptions {
+            args: vec!["--port".into(), "-v".into(), "run".into()],
+            debug: Some(5006),
+            ..Default::default()
+        };
+
+        let cmd = java_command(&java, false, tmp.path(), "app.jar", "MainKt", &opts);
+        assert_eq!(run_foreground(cmd).await.unwrap(), 3);
+
+        let args = std::fs::read_to_string(tmp.path().join("args")).unwrap();
+        assert_eq!(
+            args.lines().collect::<Vec<_>>(),
+            [
+                "-agentlib:jdwp=transport=dt_socket,server=y,suspend=y,address=*:5006",
+                "-cp",
+                "app.jar",
+                "MainKt",
+                "--port",
+                "-v",
+                "run",
+            ]
+        );
+    }
+}
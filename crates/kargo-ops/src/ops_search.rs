@@ -0,0 +1,286 @@
+//! Operation: search Maven Central for artifacts (`kargo search`).
+//!
+//! Queries the search.maven.org Solr API, or the `[search] url` of the
+//! global config, and prints the matching artifacts with their latest
+//! version. The search API has no descriptions, so they are read from the
+//! POM of each hit's latest version. `--add` adds the chosen hit to
+//! `Kargo.toml` like `kargo add`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use kargo_core::config::GlobalConfig;
+use kargo_maven::download;
+use kargo_maven::pom;
+use kargo_maven::repository::MavenRepository;
+use kargo_util::errors::KargoError;
+
+/// Default search endpoint.
+const SEARCH_URL: &str = "https://search.maven.org/solrsearch/select";
+
+/// Output format of `kargo search`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchFormat {
+    /// A table on stdout.
+    #[default]
+    Human,
+    /// A JSON array of hits on stdout.
+    Json,
+}
+
+impl SearchFormat {
+    /// Parse a `--format` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            other => Err(KargoError::Generic {
+                message: format!("Unknown search format '{other}'. Available: human, json"),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Options for `kargo search`.
+pub struct SearchOptions {
+    pub query: String,
+    /// Maximum number of hits.
+    pub limit: usize,
+    pub format: SearchFormat,
+    /// Add the chosen hit to the `Kargo.toml` in this directory.
+    pub add: Option<std::path::PathBuf>,
+}
+
+/// One artifact found by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub group: String,
+    pub artifact: String,
+    pub latest: String,
+    pub description: Option<String>,
+}
+
+impl SearchHit {
+    /// `group:artifact:latest`, as `kargo add` takes it.
+    pub fn coordinate(&self) -> String {
+        format!("{}:{}:{}", self.group, self.artifact, self.latest)
+    }
+}
+
+/// Search for `opts.query` and print the hits, or add the chosen one.
+pub async fn search(opts: &SearchOptions) -> miette::Result<()> {
+    let sp = kargo_util::progress::spinner(&format!("Searching for {}...", opts.query));
+    let client = download::build_client()?;
+    let mut hits = query(&client, &opts.query, opts.limit).await?;
+    describe(&client, &mut hits).await;
+    sp.finish_and_clear();
+
+    if let Some(ref project_root) = opts.add {
+        return add(project_root, &opts.query, &hits).await;
+    }
+    match opts.format {
+        SearchFormat::Json => {
+            let hits: Vec<serde_json::Value> = hits
+                .iter()
+                .map(|h| {
+                    serde_json::json!({
+                        "group": h.group,
+                        "artifact": h.artifact,
+                        "latest": h.latest,
+                        "description": h.description,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&hits).unwrap_or_default()
+            );
+        }
+        SearchFormat::Human if hits.is_empty() => {
+            kargo_util::progress::status("Search", &format!("no artifacts match {}", opts.query));
+        }
+        SearchFormat::Human => {
+            println!("{:<50} {:<15} Description", "Artifact", "Latest");
+            println!("{}", "-".repeat(90));
+            for hit in &hits {
+                println!(
+                    "{:<50} {:<15} {}",
+                    format!("{}:{}", hit.group, hit.artifact),
+                    hit.latest,
+                    summary(hit.description.as_deref().unwrap_or(""), 60)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hits for `text` from the search endpoint.
+async fn query(
+    client: &reqwest::Client,
+    text: &str,
+    limit: usize,
+) -> miette::Result<Vec<SearchHit>> {
+    let url = GlobalConfig::load()
+        .ok()
+        .and_then(|c| c.search)
+        .map(|s| s.url)
+        .unwrap_or_else(|| SEARCH_URL.to_string());
+    let body: serde_json::Value = client
+        .get(&url)
+        .query(&[("q", text), ("rows", &limit.to_string()), ("wt", "json")])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| KargoError::Network {
+            message: format!("Search request to {url} failed: {e}"),
+        })?
+        .json()
+        .await
+        .map_err(|e| KargoError::Network {
+            message: format!("Failed to parse the response of {url}: {e}"),
+        })?;
+    Ok(parse_hits(&body))
+}
+
+/// The hits in a Solr `select` response.
+fn parse_hits(body: &serde_json::Value) -> Vec<SearchHit> {
+    body["response"]["docs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|doc| {
+            Some(SearchHit {
+                group: doc["g"].as_str()?.to_string(),
+                artifact: doc["a"].as_str()?.to_string(),
+                latest: doc["latestVersion"]
+                    .as_str()
+                    .or(doc["v"].as_str())?
+                    .to_string(),
+                description: None,
+            })
+        })
+        .collect()
+}
+
+/// Fill in descriptions from the POMs on Maven Central. Hits whose POM
+/// cannot be fetched keep none.
+async fn describe(client: &reqwest::Client, hits: &mut [SearchHit]) {
+    let semaphore = Arc::new(Semaphore::new(8));
+    let mut join_set = JoinSet::new();
+    for (i, hit) in hits.iter().enumerate() {
+        let client = client.clone();
+        let sem = semaphore.clone();
+        let hit = hit.clone();
+        join_set.spawn(async move {
+            let _permit = sem.acquire().await.ok()?;
+            let repo = MavenRepository::maven_central();
+            let url = repo.pom_url(&hit.group, &hit.artifact, &hit.latest);
+            let xml = download::download_text(&client, &repo, &url).await.ok()??;
+            let description = pom::parse_pom(&xml).ok()?.description?;
+            Some((i, description))
+        });
+    }
+    while let Some(result) = join_set.join_next().await {
+        if let Ok(Some((i, description))) = result {
+            hits[i].description =
+                Some(description.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+    }
+}
+
+/// Add one of `hits` to the project, asking which one if there are several.
+async fn add(project_root: &Path, query: &str, hits: &[SearchHit]) -> miette::Result<()> {
+    let hit = match hits {
+        [] => {
+            return Err(KargoError::Generic {
+                message: format!("No artifacts match {query}"),
+            }
+            .into())
+        }
+        [hit] => hit,
+        _ if atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr) => {
+            let items: Vec<String> = hits
+                .iter()
+                .map(|h| {
+                    let description = summary(h.description.as_deref().unwrap_or(""), 50);
+                    format!("{}  {description}", h.coordinate())
+                })
+                .collect();
+            let chosen = dialoguer::Select::new()
+                .with_prompt("Add which artifact?")
+                .items(&items)
+                .default(0)
+                .interact()
+                .map_err(|e| KargoError::Generic {
+                    message: format!("Prompt error: {e}"),
+                })?;
+            &hits[chosen]
+        }
+        _ => {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "{} artifacts match {query}; `kargo search --add` needs an interactive \
+                     terminal to choose one, or a query with a single match",
+                    hits.len()
+                ),
+            }
+            .into())
+        }
+    };
+
+    let spec = hit.coordinate();
+    crate::ops_add::add_dependency(
+        &project_root.join("Kargo.toml"),
+        &crate::ops_add::AddOptions {
+            spec: spec.clone(),
+            dev: false,
+            target: None,
+            flavor: None,
+        },
+    )?;
+    kargo_util::progress::note(&format!("Added {spec} to [dependencies]"));
+    crate::ops_fetch::fetch(project_root, false).await
+}
+
+/// `text` cut to `width` characters.
+fn summary(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(width - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solr_docs_become_hits() {
+        let body = serde_json::json!({
+            "response": {
+                "numFound": 3,
+                "docs": [
+                    { "id": "io.ktor:ktor-client-core", "g": "io.ktor", "a": "ktor-client-core", "latestVersion": "3.0.1" },
+                    { "id": "io.ktor:ktor-client-cio:2.3.0", "g": "io.ktor", "a": "ktor-client-cio", "v": "2.3.0" },
+                    { "id": "broken" }
+                ]
+            }
+        });
+        let hits = parse_hits(&body);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].coordinate(), "io.ktor:ktor-client-core:3.0.1");
+        assert_eq!(hits[1].latest, "2.3.0");
+    }
+
+    #[test]
+    fn long_descriptions_are_cut() {
+        assert_eq!(summary("Ktor client", 20), "Ktor client");
+        assert_eq!(summary("A multiplatform HTTP client", 12), "A multipl...");
+    }
+}
@@ -1,4 +1,4 @@
-//! Self-management operations: info, clean, update.
+//! Self-management operations: info, clean, update, migrate-home.
 
 use std::fs;
 
@@ -7,6 +7,8 @@ use miette::Result;
 use kargo_core::config::GlobalConfig;
 use kargo_toolchain::install;
 use kargo_toolchain::sdk;
+use kargo_util::dirs::{KargoDirs, Layout};
+use kargo_util::errors::KargoError;
 use kargo_util::fs::dir_size;
 
 use crate::ops_self_update::{self, UpdateCheck};
@@ -26,11 +28,18 @@ pub fn cmd_info(pkg_version: &str) -> Result<()> {
         }
     );
 
-    let cache_dir = kargo_util::dirs_path().join("cache");
-    let cache_size = dir_size(&cache_dir);
+    let dirs = KargoDirs::from_env();
+    let origin = match dirs.layout {
+        Layout::KargoHome => " (KARGO_HOME)",
+        Layout::Legacy => " (legacy; run `kargo self migrate-home` to move it)",
+        Layout::Platform => "",
+    };
+    println!("  Data:           {}{origin}", dirs.data.display());
+    let cache_size =
+        dir_size(&dirs.cache.join("cache")) + dir_size(&dirs.cache.join("build-cache"));
     println!(
         "  Cache:          {} ({})",
-        cache_dir.display(),
+        dirs.cache.display(),
         format_bytes(cache_size)
     );
 
@@ -101,22 +110,30 @@ pub fn cmd_info(pkg_version: &str) -> Result<()> {
 pub fn cmd_clean() -> Result<()> {
     let mut total_freed: u64 = 0;
 
-    let cache_dir = kargo_util::dirs_path().join("cache");
+    let cache_dir = kargo_util::cache_path().join("cache");
     if cache_dir.is_dir() {
         let size = dir_size(&cache_dir);
-        fs::remove_dir_all(&cache_dir).map_err(kargo_util::errors::KargoError::Io)?;
+        fs::remove_dir_all(&cache_dir).map_err(KargoError::Io)?;
         println!("  Removed dependency cache ({}).", format_bytes(size));
         total_freed += size;
     }
 
-    let build_cache = kargo_util::dirs_path().join("build-cache");
+    let build_cache = kargo_util::cache_path().join("build-cache");
     if build_cache.is_dir() {
         let size = dir_size(&build_cache);
-        fs::remove_dir_all(&build_cache).map_err(kargo_util::errors::KargoError::Io)?;
+        fs::remove_dir_all(&build_cache).map_err(KargoError::Io)?;
         println!("  Removed build cache ({}).", format_bytes(size));
         total_freed += size;
     }
 
+    let http_cache = kargo_util::http_cache::HttpCache::user();
+    if http_cache.dir().is_dir() {
+        let size = dir_size(http_cache.dir());
+        fs::remove_dir_all(http_cache.dir()).map_err(KargoError::Io)?;
+        println!("  Removed HTTP metadata cache ({}).", format_bytes(size));
+        total_freed += size;
+    }
+
     if total_freed == 0 {
         println!("  Nothing to clean.");
     } else {
@@ -126,6 +143,25 @@ pub fn cmd_clean() -> Result<()> {
     Ok(())
 }
 
+pub fn cmd_migrate_home() -> Result<()> {
+    if KargoDirs::from_env().layout == Layout::KargoHome {
+        println!("  KARGO_HOME is set; nothing to migrate.");
+        return Ok(());
+    }
+    match kargo_util::dirs::migrate_legacy_home().map_err(KargoError::Io)? {
+        None => println!("  No ~/.kargo to migrate."),
+        Some(migration) => {
+            for (from, to) in &migration.moved {
+                println!("  Moved {} -> {}", from.display(), to.display());
+            }
+            println!();
+            println!("  Data:  {}", migration.to.data.display());
+            println!("  Cache: {}", migration.to.cache.display());
+        }
+    }
+    Ok(())
+}
+
 pub async fn cmd_update(pkg_version: &str, check_only: bool) -> Result<()> {
     println!("  Kargo {pkg_version} (current)");
     println!("  Checking for updates...");
@@ -10,6 +10,7 @@ use semver::Version;
 use serde::Deserialize;
 
 use kargo_util::errors::KargoError;
+use kargo_util::http_cache::HttpCache;
 
 const GITHUB_REPO: &str = "dejanradmanovic/kargo";
 const GITHUB_API_BASE: &str = "https://api.github.com";
@@ -119,7 +120,13 @@ pub async fn apply_update(info: &UpdateInfo) -> miette::Result<()> {
     kargo_toolchain::download::download_file(&info.asset_url, &archive_path).await?;
 
     let checksum_url = format!("{}.sha256", info.asset_url);
-    match reqwest::get(&checksum_url).await {
+    let client = kargo_util::http::client_builder()?
+        .user_agent("kargo-self-update")
+        .build()
+        .map_err(|e| KargoError::Network {
+            message: format!("HTTP client error: {e}"),
+        })?;
+    match client.get(&checksum_url).send().await {
         Ok(resp) if resp.status().is_success() => {
             if let Ok(expected) = resp.text().await {
                 let expected = expected.split_whitespace().next().unwrap_or("").to_string();
@@ -156,22 +163,27 @@ pub async fn apply_update(info: &UpdateInfo) -> miette::Result<()> {
 async fn fetch_latest_release() -> miette::Result<GhRelease> {
     let url = format!("{}/repos/{}/releases/latest", GITHUB_API_BASE, GITHUB_REPO);
 
-    let client = reqwest::Client::builder()
+    let client = kargo_util::http::client_builder()?
         .user_agent("kargo-self-update")
         .build()
         .map_err(|e| KargoError::Network {
             message: format!("HTTP client error: {e}"),
         })?;
 
-    let resp = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| KargoError::Network {
-            message: format!("Failed to reach GitHub: {e}"),
-        })?;
+    // GitHub answers a matching ETag with 304, which does not count
+    // against the API rate limit.
+    let http_cache = HttpCache::user();
+    let cached = http_cache.lookup(&url);
+    let mut req = client.get(&url);
+    if let Some(cached) = &cached {
+        req = cached.conditional(req);
+    }
+    let resp = req.send().await.map_err(|e| KargoError::Network {
+        message: format!("Failed to reach GitHub: {e}"),
+    })?;
 
     if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        http_cache.remove(&url);
         return Err(KargoError::Network {
             message: format!(
                 "No releases found for {GITHUB_REPO}.\n  \
@@ -181,14 +193,25 @@ async fn fetch_latest_release() -> miette::Result<GhRelease> {
         .into());
     }
 
-    if !resp.status().is_success() {
-        return Err(KargoError::Network {
-            message: format!("GitHub API returned HTTP {} for {url}", resp.status()),
+    let body = match cached {
+        Some(cached) if resp.status() == reqwest::StatusCode::NOT_MODIFIED => cached.body,
+        _ => {
+            if !resp.status().is_success() {
+                return Err(KargoError::Network {
+                    message: format!("GitHub API returned HTTP {} for {url}", resp.status()),
+                }
+                .into());
+            }
+            let headers = resp.headers().clone();
+            let body = resp.bytes().await.map_err(|e| KargoError::Network {
+                message: format!("Failed to read the GitHub release: {e}"),
+            })?;
+            http_cache.store(&url, &headers, &body);
+            body.to_vec()
         }
-        .into());
-    }
+    };
 
-    resp.json::<GhRelease>().await.map_err(|e| {
+    serde_json::from_slice::<GhRelease>(&body).map_err(|e| {
         KargoError::Network {
             message: format!("Failed to parse GitHub release JSON: {e}"),
         }
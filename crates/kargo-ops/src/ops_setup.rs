@@ -11,7 +11,6 @@ use kargo_core::manifest::Manifest;
 use kargo_toolchain::discovery::ToolchainPaths;
 use kargo_toolchain::install;
 use kargo_toolchain::sdk;
-use kargo_toolchain::version::KotlinVersion;
 use kargo_util::errors::KargoError;
 
 // ---------------------------------------------------------------------------
@@ -19,6 +18,7 @@ use kargo_util::errors::KargoError;
 // ---------------------------------------------------------------------------
 
 /// Result of a successful preflight check.
+#[derive(Clone)]
 pub struct PreflightResult {
     pub toolchain: ToolchainPaths,
     pub jdk: sdk::JdkInfo,
@@ -44,15 +44,8 @@ pub async fn preflight(project_dir: &Path) -> miette::Result<PreflightResult> {
     };
     let mirror = config.toolchain.kotlin_mirror.as_deref();
 
-    // 1. Kotlin compiler
-    let version: KotlinVersion =
-        manifest
-            .package
-            .kotlin
-            .parse()
-            .map_err(|e| KargoError::Toolchain {
-                message: format!("Invalid kotlin version '{}': {e}", manifest.package.kotlin),
-            })?;
+    // 1. Kotlin compiler (aliases resolve through the Kargo.lock pin)
+    let version = crate::ops_toolchain::resolve_project_kotlin(project_dir, &manifest).await?;
 
     let toolchain = kargo_toolchain::discovery::resolve_toolchain(
         &version,
@@ -103,10 +96,12 @@ pub async fn preflight(project_dir: &Path) -> miette::Result<PreflightResult> {
             .and_then(|tc| tc.compile_sdk)
             .unwrap_or(35);
 
-        let info = sdk::discover_android_sdk().ok_or_else(|| KargoError::Toolchain {
+        let local_sdk_dir = kargo_core::properties::local_sdk_dir(project_dir);
+        let info = sdk::discover_android_sdk_from(local_sdk_dir.as_deref()).ok_or_else(|| KargoError::Toolchain {
             message: format!(
                 "Android SDK not found (required for android target, compile-sdk {compile_sdk}).\n  \
-                 Set ANDROID_HOME or install with: kargo toolchain install --android"
+                 Set sdk.dir in local.properties, ANDROID_HOME, or install with: \
+                 kargo toolchain install --android"
             ),
         })?;
 
@@ -201,19 +196,15 @@ pub async fn post_scaffold(project_dir: &Path) {
     };
     let mirror = config.toolchain.kotlin_mirror.as_deref();
 
-    setup_kotlin(&manifest_path, &config, mirror).await;
-
-    let manifest_content = match std::fs::read_to_string(&manifest_path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let manifest = match Manifest::parse_toml(&manifest_content) {
+    let manifest = match Manifest::from_path(&manifest_path) {
         Ok(m) => m,
         Err(_) => return,
     };
 
+    setup_kotlin(project_dir, &manifest, &config, mirror).await;
+
     setup_jdk(&config, &manifest).await;
-    setup_target_sdks(&manifest).await;
+    setup_target_sdks(project_dir, &manifest).await;
 
     resolve_lockfile(project_dir).await;
 
@@ -235,8 +226,13 @@ fn load_manifest(project_dir: &Path) -> miette::Result<Manifest> {
     Manifest::from_path(&manifest_path)
 }
 
-async fn setup_kotlin(manifest_path: &Path, config: &GlobalConfig, mirror: Option<&str>) {
-    let version = match KotlinVersion::from_manifest(manifest_path) {
+async fn setup_kotlin(
+    project_dir: &Path,
+    manifest: &Manifest,
+    config: &GlobalConfig,
+    mirror: Option<&str>,
+) {
+    let version = match crate::ops_toolchain::resolve_project_kotlin(project_dir, manifest).await {
         Ok(v) => v,
         Err(e) => {
             println!("  Warning: could not read Kotlin version: {e}");
@@ -304,7 +300,7 @@ async fn setup_jdk(config: &GlobalConfig, manifest: &Manifest) {
     }
 }
 
-async fn setup_target_sdks(manifest: &Manifest) {
+async fn setup_target_sdks(project_dir: &Path, manifest: &Manifest) {
     let has_android = manifest.targets.keys().any(|k| k == "android");
     let has_ios = manifest
         .targets
@@ -318,7 +314,8 @@ async fn setup_target_sdks(manifest: &Manifest) {
             .and_then(|tc| tc.compile_sdk)
             .unwrap_or(35);
 
-        match sdk::discover_android_sdk() {
+        let local_sdk_dir = kargo_core::properties::local_sdk_dir(project_dir);
+        match sdk::discover_android_sdk_from(local_sdk_dir.as_deref()) {
             Some(android) => {
                 println!("  Android SDK found at {}", android.home.display());
                 if sdk::has_platform(&android, compile_sdk) {
@@ -337,7 +334,8 @@ async fn setup_target_sdks(manifest: &Manifest) {
                 Err(_) => {
                     println!("  Warning: Android SDK not configured.");
                     println!(
-                        "  Set ANDROID_HOME or install with: kargo toolchain install --android"
+                        "  Set sdk.dir in local.properties, ANDROID_HOME, or install with: \
+                         kargo toolchain install --android"
                     );
                 }
             },
@@ -368,7 +366,10 @@ async fn resolve_lockfile(project_dir: &Path) {
         Err(_) => return,
     };
 
-    if manifest.dependencies.is_empty() && manifest.dev_dependencies.is_empty() {
+    if manifest.dependencies.is_empty()
+        && manifest.dev_dependencies.is_empty()
+        && manifest.integration_test_dependencies.is_empty()
+    {
         return;
     }
 
@@ -390,18 +391,27 @@ pub async fn ensure_lockfile(project_dir: &Path) -> miette::Result<()> {
 
     if manifest.dependencies.is_empty()
         && manifest.dev_dependencies.is_empty()
+        && manifest.integration_test_dependencies.is_empty()
         && manifest.ksp.is_empty()
         && manifest.kapt.is_empty()
+        && manifest.npm_dependencies.is_empty()
     {
         return Ok(());
     }
 
-    let lockfile_path = project_dir.join("Kargo.lock");
+    let lockfile_path = kargo_core::workspace::lockfile_path(project_dir);
     let needs_resolve = if lockfile_path.is_file() {
-        match kargo_core::lockfile::Lockfile::from_path(&lockfile_path) {
+        match kargo_core::lockfile::Lockfile::for_project(project_dir) {
             Ok(lf) => {
                 let declared = crate::ops_fetch::collect_declared_deps(&manifest);
-                if !lf.is_up_to_date(&declared) {
+                let npm_missing = !manifest.npm_dependencies.is_empty()
+                    && !crate::ops_npm::node_modules_dir(project_dir).is_dir();
+                // Project JARs are re-hashed so a replaced one is re-locked.
+                if !lf.is_up_to_date(&declared)
+                    || !lf.npm_up_to_date(&manifest.npm_dependencies)
+                    || npm_missing
+                    || lf.file != crate::ops_fetch::locked_files(project_dir, &manifest)?
+                {
                     true
                 } else {
                     let cache = kargo_maven::cache::LocalCache::new(project_dir);
@@ -411,7 +421,7 @@ pub async fn ensure_lockfile(project_dir: &Path) -> miette::Result<()> {
                             return false;
                         }
                         cache
-                            .get_jar(&pkg.group, &pkg.name, &pkg.version, None)
+                            .get_library(&pkg.group, &pkg.name, &pkg.version)
                             .is_none()
                     })
                 }
@@ -427,7 +437,7 @@ pub async fn ensure_lockfile(project_dir: &Path) -> miette::Result<()> {
     }
 
     // Verify cached JAR checksums against the lockfile
-    if let Ok(lf) = kargo_core::lockfile::Lockfile::from_path(&lockfile_path) {
+    if let Ok(lf) = kargo_core::lockfile::Lockfile::for_project(project_dir) {
         verify_cached_checksums(project_dir, &lf)?;
     }
 
@@ -476,7 +486,7 @@ fn verify_cached_checksums(
                             }
                         })?;
                     if actual != expected {
-                        return Err(KargoError::Generic {
+                        return Err(KargoError::Resolution {
                             message: format!(
                                 "Checksum mismatch for {key}\n  \
                                  expected: {expected}\n  \
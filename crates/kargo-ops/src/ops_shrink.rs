@@ -0,0 +1,284 @@
+//! Operation: shrink the output JAR with R8.
+//!
+//! Runs after packaging when the active profile has an enabled
+//! `[profile.<name>.shrink]` table. R8 works in class-file mode over the
+//! JAR; dependencies, the Kotlin stdlib and the JDK are library inputs, so
+//! only the project's own classes are shrunk. The JAR's entry points
+//! (`Main-Class` and the Java agent classes named in its manifest) are
+//! always kept, as are the Android components and custom views named by
+//! the merged Android manifest and layouts. The shrunk classes are
+//! repackaged with the original manifest and resources, and R8's mapping
+//! file is left next to them.
+
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::jar::JarManifest;
+use kargo_core::profile::ShrinkConfig;
+use kargo_util::errors::KargoError;
+
+/// R8 release used when `r8-version` is not set.
+pub const DEFAULT_R8_VERSION: &str = "8.5.35";
+
+/// Maven coordinates of R8 on Google Maven.
+pub const R8_GROUP: &str = "com.android.tools";
+pub const R8_ARTIFACT: &str = "r8";
+
+/// The R8 version `config` asks for.
+pub fn r8_version(config: &ShrinkConfig) -> &str {
+    config.r8_version.as_deref().unwrap_or(DEFAULT_R8_VERSION)
+}
+
+/// Download the R8 JAR for `config` into the project cache.
+pub async fn ensure_r8(
+    cache: &kargo_maven::cache::LocalCache,
+    config: &ShrinkConfig,
+) -> miette::Result<PathBuf> {
+    let version = r8_version(config);
+    let google = kargo_maven::repository::MavenRepository::google();
+    kargo_compiler::plugins::ensure_maven_jar_from(&google, cache, R8_GROUP, R8_ARTIFACT, version)
+        .await?
+        .ok_or_else(|| {
+            KargoError::Generic {
+                message: format!(
+                    "R8 {version} ({R8_GROUP}:{R8_ARTIFACT}) not found on Google Maven"
+                ),
+            }
+            .into()
+        })
+}
+
+/// Manifest attributes naming classes the JVM loads by name.
+const ENTRY_POINT_ATTRIBUTES: &[(&str, &str)] = &[
+    ("Main-Class", "public static void main(java.lang.String[]);"),
+    ("Premain-Class", "public static void premain(...);"),
+    ("Agent-Class", "public static void agentmain(...);"),
+    ("Launcher-Agent-Class", "public static void agentmain(...);"),
+];
+
+/// Size of the output JAR before and after shrinking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShrinkReport {
+    pub before: u64,
+    pub after: u64,
+}
+
+impl std::fmt::Display for ShrinkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let saved = if self.before == 0 {
+            0.0
+        } else {
+            (self.before as f64 - self.after as f64) / self.before as f64 * 100.0
+        };
+        write!(
+            f,
+            "{} -> {} ({saved:.1}% smaller)",
+            crate::ops_cache::format_size(self.before),
+            crate::ops_cache::format_size(self.after)
+        )
+    }
+}
+
+/// Keep rules for the entry points named in `manifest`, plus the attributes
+/// Kotlin reflection and readable stack traces rely on.
+pub fn keep_rules(manifest: &JarManifest) -> String {
+    let mut rules = String::from(
+        "# Generated by Kargo.\n\
+         -keepattributes *Annotation*,Signature,InnerClasses,EnclosingMethod,SourceFile,LineNumberTable\n\
+         -keep class kotlin.Metadata { *; }\n",
+    );
+    for (attribute, member) in ENTRY_POINT_ATTRIBUTES {
+        if let Some(class) = manifest.get(attribute) {
+            rules.push_str(&format!("-keep class {class} {{ {member} }}\n"));
+        }
+    }
+    rules
+}
+
+/// Arguments for R8's `com.android.tools.r8.R8` entry point.
+pub fn r8_args(
+    input: &Path,
+    output_dir: &Path,
+    mapping: &Path,
+    libraries: &[PathBuf],
+    rules: &[PathBuf],
+) -> Vec<String> {
+    let mut args = vec![
+        "--classfile".to_string(),
+        "--release".to_string(),
+        "--output".to_string(),
+        output_dir.to_string_lossy().to_string(),
+        "--pg-map-output".to_string(),
+        mapping.to_string_lossy().to_string(),
+    ];
+    for lib in libraries {
+        args.push("--lib".into());
+        args.push(lib.to_string_lossy().to_string());
+    }
+    for rule in rules {
+        args.push("--pg-conf".into());
+        args.push(rule.to_string_lossy().to_string());
+    }
+    args.push(input.to_string_lossy().to_string());
+    args
+}
+
+/// Shrink `jar` in place and report the size change.
+pub async fn shrink(
+    ctx: &crate::BuildContext,
+    jar: &Path,
+    config: &ShrinkConfig,
+    verbose: bool,
+) -> miette::Result<ShrinkReport> {
+    let before = std::fs::metadata(jar).map_err(KargoError::Io)?.len();
+
+    let cache = kargo_maven::cache::LocalCache::new(&ctx.project_dir);
+    let r8_jar = ensure_r8(&cache, config).await?;
+
+    let shrink_dir = ctx.build_dir.join("shrink");
+    let _ = std::fs::remove_dir_all(&shrink_dir);
+    let classes = shrink_dir.join("classes");
+    std::fs::create_dir_all(&classes).map_err(KargoError::Io)?;
+
+    let manifest = JarManifest::for_package(
+        &ctx.manifest.package,
+        ctx.manifest.package.main_class.as_deref(),
+    );
+    let generated_rules = shrink_dir.join("kargo-rules.pro");
+    std::fs::write(&generated_rules, keep_rules(&manifest)).map_err(KargoError::Io)?;
+    let mut rules = vec![generated_rules];
+    let android_rules = ctx
+        .build_dir
+        .join(crate::ops_android_merge::MERGED_DIR)
+        .join(crate::ops_android_merge::PROGUARD_RULES);
+    if android_rules.is_file() {
+        rules.push(android_rules);
+    }
+    for rule in &config.rules {
+        let path = ctx.project_dir.join(rule);
+        if !path.is_file() {
+            return Err(KargoError::Manifest {
+                message: format!(
+                    "[profile.{}.shrink] rules file '{rule}' does not exist",
+                    ctx.profile_name
+                ),
+            }
+            .into());
+        }
+        rules.push(path);
+    }
+
+    let mut libraries = vec![ctx.preflight.jdk.home.clone()];
+    let kotlin_lib = ctx.preflight.toolchain.home.join("lib");
+    for name in kargo_compiler::classpath::STDLIB_RUNTIME_JARS {
+        let stdlib = kotlin_lib.join(name);
+        if stdlib.is_file() {
+            libraries.push(stdlib);
+        }
+    }
+    for dep in &ctx.classpath.runtime_jars {
+        if !libraries.iter().any(|l| l.file_name() == dep.file_name()) {
+            libraries.push(dep.clone());
+        }
+    }
+
+    let input = shrink_dir.join("input.jar");
+    std::fs::copy(jar, &input).map_err(KargoError::Io)?;
+    let args = r8_args(
+        &input,
+        &classes,
+        &shrink_dir.join("mapping.txt"),
+        &libraries,
+        &rules,
+    );
+    let java_bin = ctx.preflight.jdk.home.join("bin").join("java");
+    if verbose {
+        eprintln!("  r8: {} {}", r8_jar.display(), args.join(" "));
+    }
+    let output = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(ctx.preflight.jdk.java_accepts_argfiles())
+        .arg("-cp")
+        .arg(r8_jar.to_string_lossy().to_string())
+        .arg("com.android.tools.r8.R8")
+        .args(args)
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Failed to run R8: {e}"),
+        })?;
+    if !output.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "R8 failed:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+
+    kargo_compiler::jar::write_jar(jar, &[&classes, &ctx.resources_dir], &manifest)?;
+    let after = std::fs::metadata(jar).map_err(KargoError::Io)?.len();
+    Ok(ShrinkReport { before, after })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_rules_cover_manifest_entry_points() {
+        let mut manifest = JarManifest::default();
+        manifest.set("Main-Class", "com.example.MainKt");
+        manifest.set("Premain-Class", "com.example.Agent");
+        let rules = keep_rules(&manifest);
+        assert!(rules.contains(
+            "-keep class com.example.MainKt { public static void main(java.lang.String[]); }\n"
+        ));
+        assert!(
+            rules.contains("-keep class com.example.Agent { public static void premain(...); }\n")
+        );
+        assert!(!rules.contains("agentmain"));
+
+        let library = keep_rules(&JarManifest::default());
+        assert!(!library.contains("-keep class com."));
+        assert!(library.contains("-keep class kotlin.Metadata"));
+    }
+
+    #[test]
+    fn r8_args_pass_libraries_rules_and_input_last() {
+        let args = r8_args(
+            Path::new("in.jar"),
+            Path::new("out"),
+            Path::new("mapping.txt"),
+            &[PathBuf::from("/jdk"), PathBuf::from("dep.jar")],
+            &[PathBuf::from("kargo-rules.pro"), PathBuf::from("app.pro")],
+        );
+        assert_eq!(
+            args,
+            [
+                "--classfile",
+                "--release",
+                "--output",
+                "out",
+                "--pg-map-output",
+                "mapping.txt",
+                "--lib",
+                "/jdk",
+                "--lib",
+                "dep.jar",
+                "--pg-conf",
+                "kargo-rules.pro",
+                "--pg-conf",
+                "app.pro",
+                "in.jar",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_shows_savings() {
+        let report = ShrinkReport {
+            before: 2 * 1024 * 1024,
+            after: 512 * 1024,
+        };
+        assert_eq!(report.to_string(), "2.0 MB -> 512.0 KB (75.0% smaller)");
+    }
+}
@@ -0,0 +1,228 @@
+//! Operation: Swift Package Manager manifests for the XCFramework.
+//!
+//! `kargo spm package` writes a `Package.swift` whose only product is a
+//! binary target for `<Name>.xcframework`. Without a URL it points at the
+//! local `build/xcframework/<profile>/` bundle, which suits a package that
+//! lives in the same repository as the app. With `--url` it references the
+//! released zip together with its SHA-256 checksum (what
+//! `swift package compute-checksum` prints), and `--upload` first PUTs the
+//! zip to that URL.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::manifest::Manifest;
+use kargo_maven::repository::MavenRepository;
+use kargo_util::errors::KargoError;
+
+use crate::ops_xcode;
+
+/// Name of the generated manifest.
+pub const PACKAGE_SWIFT: &str = "Package.swift";
+
+/// Where the binary target's XCFramework comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinarySource {
+    /// `path:` relative to the package root.
+    Path(String),
+    /// `url:` of the zip with its `checksum:`.
+    Url { url: String, checksum: String },
+}
+
+/// Contents of `Package.swift` for `manifest`.
+pub fn render_package_swift(manifest: &Manifest, source: &BinarySource) -> String {
+    let name = manifest.framework_name();
+    let mut platforms: Vec<&str> = manifest
+        .declared_targets()
+        .iter()
+        .filter_map(|t| t.apple_platform())
+        .collect();
+    platforms.sort_unstable();
+    platforms.dedup();
+
+    let mut out = String::from(
+        "// swift-tools-version:5.3\n\
+         // Generated by `kargo spm package` from Kargo.toml. Do not edit.\n\
+         import PackageDescription\n\
+         \n\
+         let package = Package(\n",
+    );
+    out.push_str(&format!("    name: \"{name}\",\n"));
+    out.push_str("    platforms: [\n");
+    for platform in platforms {
+        let swift = match platform {
+            "ios" => "iOS",
+            "macos" => "macOS",
+            "tvos" => "tvOS",
+            _ => "watchOS",
+        };
+        if let Some(version) = manifest.deployment_target(platform) {
+            out.push_str(&format!("        .{swift}(\"{version}\"),\n"));
+        }
+    }
+    out.push_str("    ],\n");
+    out.push_str(&format!(
+        "    products: [\n        .library(name: \"{name}\", targets: [\"{name}\"]),\n    ],\n"
+    ));
+    out.push_str("    targets: [\n        .binaryTarget(\n");
+    out.push_str(&format!("            name: \"{name}\",\n"));
+    match source {
+        BinarySource::Path(path) => {
+            out.push_str(&format!("            path: \"{path}\"\n"));
+        }
+        BinarySource::Url { url, checksum } => {
+            out.push_str(&format!("            url: \"{url}\",\n"));
+            out.push_str(&format!("            checksum: \"{checksum}\"\n"));
+        }
+    }
+    out.push_str("        ),\n    ]\n)\n");
+    out
+}
+
+/// Write `Package.swift` into `project_dir`. With `url`, the checksum is
+/// taken from the XCFramework zip built for `profile`, which is uploaded to
+/// `url` first when `upload` is set.
+pub async fn package(
+    project_dir: &Path,
+    profile: &str,
+    url: Option<&str>,
+    upload: bool,
+) -> miette::Result<PathBuf> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    if !manifest.declared_targets().iter().any(|t| t.is_apple()) {
+        return Err(KargoError::Generic {
+            message: "No Apple target in [targets]; a Swift package needs at least one.".into(),
+        }
+        .into());
+    }
+    let name = manifest.framework_name();
+    let dir = ops_xcode::xcframework_dir(project_dir, profile);
+
+    let source = match url {
+        None => BinarySource::Path(format!("build/xcframework/{profile}/{name}.xcframework")),
+        Some(url) => {
+            let zip = dir.join(format!("{name}.xcframework.zip"));
+            if !zip.is_file() {
+                return Err(KargoError::Generic {
+                    message: format!(
+                        "{} not found. Run `kargo xcode xcframework --profile {profile}` first.",
+                        zip.display()
+                    ),
+                }
+                .into());
+            }
+            if upload {
+                upload_zip(&manifest, &zip, url).await?;
+                kargo_util::progress::status("Uploaded", url);
+            }
+            BinarySource::Url {
+                url: url.to_string(),
+                checksum: kargo_util::hash::sha256_file(&zip)?,
+            }
+        }
+    };
+
+    let path = project_dir.join(PACKAGE_SWIFT);
+    std::fs::write(&path, render_package_swift(&manifest, &source)).map_err(KargoError::Io)?;
+    Ok(path)
+}
+
+/// PUT `zip` to `url`, with the credentials of the `[repositories]` entry
+/// whose URL it starts with, if any.
+async fn upload_zip(manifest: &Manifest, zip: &Path, url: &str) -> miette::Result<()> {
+    let client = kargo_maven::download::build_client()?;
+    let bytes = std::fs::read(zip).map_err(KargoError::Io)?;
+    let mut request = client
+        .put(url)
+        .header("Content-Type", "application/zip")
+        .body(bytes);
+    let repo = manifest
+        .repositories
+        .iter()
+        .map(|(name, entry)| MavenRepository::from_entry(name, entry))
+        .find(|r| url.starts_with(&r.url));
+    if let Some(repo) = &repo {
+        request = kargo_maven::auth::apply_auth(request, repo);
+    }
+    let resp = request.send().await.map_err(|e| KargoError::Network {
+        message: format!("Failed to upload {}: {e}", zip.display()),
+    })?;
+    if !resp.status().is_success() {
+        return Err(KargoError::Network {
+            message: format!("HTTP {} uploading to {url}", resp.status()),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest::parse_toml(
+            r#"
+[package]
+name = "shared-core"
+version = "1.2.0"
+kotlin = "2.3.0"
+
+[targets]
+ios-arm64 = {}
+ios-simulator-arm64 = {}
+macos-arm64 = {}
+
+[apple]
+deployment-target = { ios = "15.0" }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn package_swift_for_a_released_zip() {
+        let swift = render_package_swift(
+            &manifest(),
+            &BinarySource::Url {
+                url: "https://example.com/SharedCore.xcframework.zip".into(),
+                checksum: "abc123".into(),
+            },
+        );
+        assert_eq!(
+            swift,
+            r#"// swift-tools-version:5.3
+// Generated by `kargo spm package` from Kargo.toml. Do not edit.
+import PackageDescription
+
+let package = Package(
+    name: "SharedCore",
+    platforms: [
+        .iOS("15.0"),
+        .macOS("11.0"),
+    ],
+    products: [
+        .library(name: "SharedCore", targets: ["SharedCore"]),
+    ],
+    targets: [
+        .binaryTarget(
+            name: "SharedCore",
+            url: "https://example.com/SharedCore.xcframework.zip",
+            checksum: "abc123"
+        ),
+    ]
+)
+"#
+        );
+    }
+
+    #[test]
+    fn local_package_points_at_the_build_output() {
+        let swift = render_package_swift(
+            &manifest(),
+            &BinarySource::Path("build/xcframework/release/SharedCore.xcframework".into()),
+        );
+        assert!(swift
+            .contains("            path: \"build/xcframework/release/SharedCore.xcframework\"\n"));
+        assert!(!swift.contains("checksum"));
+    }
+}
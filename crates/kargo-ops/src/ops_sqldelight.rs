@@ -0,0 +1,417 @@
+//! Operation: generate SQLDelight database interfaces.
+//!
+//! Each `[sqldelight.databases.<Name>]` entry compiles the `.sq` and `.sqm`
+//! files of its source directories into `<generated>/sqldelight/<Name>/`,
+//! which is added to the main compilation unit. SQLDelight itself only
+//! ships a Gradle plugin, so the compiler is a CLI JAR named by
+//! `[sqldelight] compiler`; Kargo resolves it (and the dialect modules)
+//! with their transitive dependencies into the project cache and runs
+//! `main-class` with:
+//!
+//! ```text
+//! generate --name <Name> --package <pkg> [--dialect <g:a:v>] --output <dir> <source dir>...
+//! verify   --name <Name> --package <pkg> [--dialect <g:a:v>] <source dir>...
+//! ```
+//!
+//! `verify` runs from `kargo check` for databases with `verify-migrations`.
+//! Generation is skipped while the schema files and settings are unchanged.
+
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::fingerprint;
+use kargo_core::manifest::{Manifest, SqlDelightConfig, SqlDelightDatabase};
+use kargo_util::errors::KargoError;
+
+/// Default directory holding `.sq` files.
+pub const DEFAULT_SQLDELIGHT_DIR: &str = "src/main/sqldelight";
+
+/// Group of the SQLDelight runtime and drivers.
+const SQLDELIGHT_GROUP: &str = "app.cash.sqldelight";
+
+/// Output directory of database `name`.
+pub fn output_dir(generated_dir: &Path, name: &str) -> PathBuf {
+    generated_dir.join("sqldelight").join(name)
+}
+
+/// Existing source directories generated for the manifest's databases.
+pub fn source_dirs(generated_dir: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    let Some(config) = &manifest.sqldelight else {
+        return Vec::new();
+    };
+    config
+        .databases
+        .keys()
+        .map(|name| output_dir(generated_dir, name))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Directories holding the schema files of `db`.
+pub fn schema_dirs(project_dir: &Path, db: &SqlDelightDatabase) -> Vec<PathBuf> {
+    if db.source_dirs.is_empty() {
+        vec![project_dir.join(DEFAULT_SQLDELIGHT_DIR)]
+    } else {
+        db.source_dirs.iter().map(|d| project_dir.join(d)).collect()
+    }
+}
+
+/// `.sq` and `.sqm` files below `dirs`, sorted.
+pub fn schema_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_schema_files(dir, &mut files);
+    }
+    files.sort();
+    files
+}
+
+fn collect_schema_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_schema_files(&path, out);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "sq" || ext == "sqm")
+        {
+            out.push(path);
+        }
+    }
+}
+
+/// Whether the manifest declares a SQLDelight runtime or driver dependency.
+fn uses_sqldelight_runtime(manifest: &Manifest) -> bool {
+    use kargo_core::dependency::Dependency;
+    manifest
+        .dependencies
+        .values()
+        .chain(
+            manifest
+                .target
+                .values()
+                .flat_map(|t| t.dependencies.values()),
+        )
+        .any(|dep| match dep {
+            Dependency::Short(s) => s.starts_with(&format!("{SQLDELIGHT_GROUP}:")),
+            Dependency::Detailed(d) => d.group == SQLDELIGHT_GROUP,
+            Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => false,
+        })
+}
+
+/// Arguments for one invocation of the compiler CLI. `output` is set for
+/// `generate` and omitted for `verify`.
+pub fn cli_args(
+    command: &str,
+    name: &str,
+    db: &SqlDelightDatabase,
+    output: Option<&Path>,
+    dirs: &[PathBuf],
+) -> Vec<String> {
+    let mut args = vec![
+        command.to_string(),
+        "--name".into(),
+        name.into(),
+        "--package".into(),
+        db.package.clone(),
+    ];
+    if let Some(dialect) = &db.dialect {
+        args.push("--dialect".into());
+        args.push(dialect.clone());
+    }
+    if let Some(output) = output {
+        args.push("--output".into());
+        args.push(output.to_string_lossy().to_string());
+    }
+    args.extend(dirs.iter().map(|d| d.to_string_lossy().to_string()));
+    args
+}
+
+/// Hash of a database's settings, the compiler and its schema files.
+pub fn compute_fingerprint(
+    name: &str,
+    config: &SqlDelightConfig,
+    db: &SqlDelightDatabase,
+    project_dir: &Path,
+    files: &[PathBuf],
+) -> miette::Result<String> {
+    let mut data = format!(
+        "{name}\n{:?}\n{:?}\n{}\n",
+        config.compiler,
+        config.main_class,
+        serde_json::to_string(db).unwrap_or_default()
+    );
+    for file in files {
+        let rel = file.strip_prefix(project_dir).unwrap_or(file);
+        data.push_str(&format!(
+            "{} {}\n",
+            rel.display(),
+            kargo_util::hash::sha256_file(file)?
+        ));
+    }
+    Ok(kargo_util::hash::sha256_bytes(data.as_bytes()))
+}
+
+/// Resolve the compiler CLI and dialect modules with their dependencies and
+/// return the JARs to run them with.
+async fn compiler_classpath(
+    ctx: &crate::BuildContext,
+    config: &SqlDelightConfig,
+) -> miette::Result<Vec<PathBuf>> {
+    let coords = config.compiler.iter().chain(
+        config
+            .databases
+            .values()
+            .filter_map(|db| db.dialect.as_ref()),
+    );
+    crate::resolve_tool_classpath(
+        &ctx.project_dir,
+        &ctx.manifest,
+        "sqldelight",
+        coords.map(String::as_str),
+    )
+    .await
+}
+
+/// Run the compiler CLI with `args`.
+fn run_cli(
+    ctx: &crate::BuildContext,
+    config: &SqlDelightConfig,
+    classpath: &[PathBuf],
+    args: Vec<String>,
+    verbose: bool,
+) -> miette::Result<std::process::Output> {
+    let main_class = config.main_class.as_deref().unwrap_or_default();
+    let cp = std::env::join_paths(classpath)
+        .map_err(|e| KargoError::Generic {
+            message: format!("Invalid SQLDelight compiler classpath: {e}"),
+        })?
+        .to_string_lossy()
+        .to_string();
+    if verbose {
+        eprintln!("  sqldelight: {main_class} {}", args.join(" "));
+    }
+    let java_bin = ctx.preflight.jdk.home.join("bin").join("java");
+    kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(ctx.preflight.jdk.java_accepts_argfiles())
+        .arg("-cp")
+        .arg(cp)
+        .arg(main_class)
+        .args(args)
+        .cwd(ctx.project_dir.to_string_lossy().to_string())
+        .exec()
+        .map_err(|e| {
+            KargoError::Generic {
+                message: format!("Failed to run the SQLDelight compiler: {e}"),
+            }
+            .into()
+        })
+}
+
+/// Generate the interfaces of databases whose schema changed. Returns how
+/// many were generated.
+pub async fn generate(
+    ctx: &crate::BuildContext,
+    verbose: bool,
+    quiet: bool,
+) -> miette::Result<usize> {
+    let Some(config) = &ctx.manifest.sqldelight else {
+        let default_dir = ctx.project_dir.join(DEFAULT_SQLDELIGHT_DIR);
+        if !quiet
+            && uses_sqldelight_runtime(&ctx.manifest)
+            && !schema_files(&[default_dir]).is_empty()
+        {
+            kargo_util::progress::status_warn(
+                "Warning",
+                &format!(
+                    "{DEFAULT_SQLDELIGHT_DIR} has SQLDelight files but no [sqldelight.databases] \
+                     are declared; the database interfaces are not generated"
+                ),
+            );
+        }
+        return Ok(0);
+    };
+
+    let fp_dir =
+        fingerprint::storage_dir(&ctx.project_dir, ctx.target.kebab_name(), &ctx.profile_name);
+    let mut classpath = None;
+    let mut generated = 0;
+    for (name, db) in &config.databases {
+        let dirs = schema_dirs(&ctx.project_dir, db);
+        let files = schema_files(&dirs);
+        let fp = compute_fingerprint(name, config, db, &ctx.project_dir, &files)?;
+        let fp_path = fp_dir.join(format!("sqldelight-{name}.fingerprint"));
+        let out = output_dir(&ctx.generated_dir, name);
+        if out.is_dir() && std::fs::read_to_string(&fp_path).is_ok_and(|stored| stored.trim() == fp)
+        {
+            if verbose {
+                eprintln!("  sqldelight: {name} is up to date");
+            }
+            continue;
+        }
+
+        if !quiet {
+            kargo_util::progress::status("Generating", &format!("SQLDelight database {name}"));
+        }
+        if classpath.is_none() {
+            classpath = Some(compiler_classpath(ctx, config).await?);
+        }
+        let _ = std::fs::remove_file(&fp_path);
+        let _ = std::fs::remove_dir_all(&out);
+        std::fs::create_dir_all(&out).map_err(KargoError::Io)?;
+        let existing: Vec<PathBuf> = dirs.into_iter().filter(|d| d.is_dir()).collect();
+        let args = cli_args("generate", name, db, Some(&out), &existing);
+        let output = run_cli(
+            ctx,
+            config,
+            classpath.as_deref().unwrap_or_default(),
+            args,
+            verbose,
+        )?;
+        if !output.status.success() {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "SQLDelight generation for {name} failed:\n{}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            }
+            .into());
+        }
+        std::fs::create_dir_all(&fp_dir).map_err(KargoError::Io)?;
+        std::fs::write(&fp_path, &fp).map_err(KargoError::Io)?;
+        generated += 1;
+    }
+    Ok(generated)
+}
+
+/// Verify the migrations of every database with `verify-migrations`.
+/// Returns how many databases were verified.
+pub async fn verify(ctx: &crate::BuildContext, verbose: bool) -> miette::Result<usize> {
+    let Some(config) = &ctx.manifest.sqldelight else {
+        return Ok(0);
+    };
+    let databases: Vec<_> = config
+        .databases
+        .iter()
+        .filter(|(_, db)| db.verify_migrations)
+        .collect();
+    if databases.is_empty() {
+        return Ok(0);
+    }
+
+    let classpath = compiler_classpath(ctx, config).await?;
+    for (name, db) in &databases {
+        let dirs: Vec<PathBuf> = schema_dirs(&ctx.project_dir, db)
+            .into_iter()
+            .filter(|d| d.is_dir())
+            .collect();
+        let args = cli_args("verify", name, db, None, &dirs);
+        let output = run_cli(ctx, config, &classpath, args, verbose)?;
+        if !output.status.success() {
+            let mut details = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                details.push('\n');
+                details.push_str(stderr.trim());
+            }
+            return Err(KargoError::Generic {
+                message: format!(
+                    "SQLDelight migrations of {name} do not match its schema:\n{details}"
+                ),
+            }
+            .into());
+        }
+    }
+    Ok(databases.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database() -> SqlDelightDatabase {
+        SqlDelightDatabase {
+            package: "com.example.db".into(),
+            dialect: Some("app.cash.sqldelight:sqlite-3-38-dialect:2.0.2".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cli_args_follow_the_compiler_contract() {
+        let dirs = [PathBuf::from("/p/src/main/sqldelight")];
+        assert_eq!(
+            cli_args(
+                "generate",
+                "AppDatabase",
+                &database(),
+                Some(Path::new("/g/AppDatabase")),
+                &dirs
+            ),
+            [
+                "generate",
+                "--name",
+                "AppDatabase",
+                "--package",
+                "com.example.db",
+                "--dialect",
+                "app.cash.sqldelight:sqlite-3-38-dialect:2.0.2",
+                "--output",
+                "/g/AppDatabase",
+                "/p/src/main/sqldelight",
+            ]
+        );
+        let verify = cli_args(
+            "verify",
+            "AppDatabase",
+            &SqlDelightDatabase::default(),
+            None,
+            &[],
+        );
+        assert_eq!(verify, ["verify", "--name", "AppDatabase", "--package", ""]);
+    }
+
+    #[test]
+    fn schema_and_migration_files_are_collected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("sqldelight");
+        std::fs::create_dir_all(root.join("com/example")).unwrap();
+        std::fs::write(root.join("com/example/Player.sq"), "").unwrap();
+        std::fs::write(root.join("1.sqm"), "").unwrap();
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+        assert_eq!(
+            schema_files(std::slice::from_ref(&root)),
+            [root.join("1.sqm"), root.join("com/example/Player.sq")]
+        );
+    }
+
+    #[test]
+    fn fingerprint_tracks_schema_and_compiler() {
+        let dir = tempfile::tempdir().unwrap();
+        let sq = dir.path().join("Player.sq");
+        std::fs::write(&sq, "CREATE TABLE player (id INTEGER);").unwrap();
+        let files = [sq.clone()];
+        let config = SqlDelightConfig {
+            compiler: Some("com.example:sqldelight-cli:1.0.0".into()),
+            main_class: Some("com.example.MainKt".into()),
+            ..Default::default()
+        };
+        let db = database();
+        let fp = compute_fingerprint("Db", &config, &db, dir.path(), &files).unwrap();
+
+        std::fs::write(&sq, "CREATE TABLE player (id INTEGER, name TEXT);").unwrap();
+        let edited = compute_fingerprint("Db", &config, &db, dir.path(), &files).unwrap();
+        assert_ne!(fp, edited);
+
+        let upgraded = SqlDelightConfig {
+            compiler: Some("com.example:sqldelight-cli:1.1.0".into()),
+            ..config.clone()
+        };
+        assert_ne!(
+            edited,
+            compute_fingerprint("Db", &upgraded, &db, dir.path(), &files).unwrap()
+        );
+    }
+}
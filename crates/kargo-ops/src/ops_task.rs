@@ -0,0 +1,217 @@
+//! Operation: manifest task pipelines (`kargo task <name>`).
+//!
+//! `[tasks.<name>]` declares a list of `steps` and the tasks it
+//! `depends-on`. Running a task runs its dependencies first, in dependency
+//! order and each at most once, then its own steps one after another,
+//! stopping at the first that fails.
+//!
+//! A step whose first word is a Kargo command, such as `build --release`,
+//! runs that command with the running `kargo` executable (a leading `kargo`
+//! is accepted too). Any other step is a shell command (`sh -c`, or
+//! `cmd /C` on Windows) run from the project root. Steps see the variables
+//! of `.kargo.env`, the package variables (`KARGO_MANIFEST_DIR`,
+//! `KARGO_PKG_NAME`, `KARGO_PKG_VERSION`), `KARGO` and `KARGO_TASK`, and the
+//! task's own `env` table, which takes precedence.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use kargo_core::manifest::{Manifest, TaskConfig};
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+use kargo_util::progress::{note, status};
+
+/// The tasks declared in the manifest at `project_dir`.
+pub fn list(project_dir: &Path) -> miette::Result<BTreeMap<String, TaskConfig>> {
+    Ok(load(project_dir)?.tasks)
+}
+
+/// Run task `name` of the project at `project_dir` after its dependencies.
+///
+/// `builtins` are the names of Kargo's own commands: steps starting with one
+/// of them run through `kargo`.
+pub fn run(project_dir: &Path, name: &str, builtins: &[String]) -> miette::Result<()> {
+    let manifest = load(project_dir)?;
+    let order = plan(&manifest.tasks, name)?;
+
+    let mut env = kargo_core::properties::load_env_file(&project_dir.join(".kargo.env"))?;
+    env.insert(
+        "KARGO_MANIFEST_DIR".into(),
+        project_dir.to_string_lossy().into(),
+    );
+    env.insert("KARGO_PKG_NAME".into(), manifest.package.name.clone());
+    env.insert("KARGO_PKG_VERSION".into(), manifest.package.version.clone());
+    let kargo = std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "kargo".into());
+    env.insert("KARGO".into(), kargo);
+
+    for task_name in &order {
+        let task = &manifest.tasks[task_name];
+        status("Running", &format!("task `{task_name}`"));
+        for step in &task.steps {
+            note(&format!("  $ {step}"));
+            let mut cmd = shell(&step_command(step, builtins))
+                .cwd(project_dir.to_string_lossy())
+                .env("KARGO_TASK", task_name.as_str());
+            for (key, value) in env.iter().chain(&task.env) {
+                cmd = cmd.env(key, value);
+            }
+            let exit = cmd.status().map_err(|e| KargoError::Generic {
+                message: format!("Task `{task_name}`: failed to run `{step}`: {e}"),
+            })?;
+            if !exit.success() {
+                return Err(KargoError::Generic {
+                    message: format!(
+                        "Task `{task_name}` failed: `{step}` exited with code {}",
+                        kargo_util::process::exit_code(exit)
+                    ),
+                }
+                .into());
+            }
+        }
+    }
+    status("Finished", &format!("task `{name}`"));
+    Ok(())
+}
+
+/// The tasks to run for `name`, dependencies first, each once.
+pub fn plan(tasks: &BTreeMap<String, TaskConfig>, name: &str) -> miette::Result<Vec<String>> {
+    fn visit(
+        tasks: &BTreeMap<String, TaskConfig>,
+        name: &str,
+        stack: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> miette::Result<()> {
+        if order.iter().any(|done| done == name) {
+            return Ok(());
+        }
+        if let Some(start) = stack.iter().position(|t| t == name) {
+            let cycle: Vec<&str> = stack[start..]
+                .iter()
+                .map(String::as_str)
+                .chain([name])
+                .collect();
+            return Err(KargoError::Manifest {
+                message: format!("[tasks] dependency cycle: {}", cycle.join(" -> ")),
+            }
+            .into());
+        }
+        let task = tasks.get(name).ok_or_else(|| KargoError::Manifest {
+            message: format!(
+                "no task named `{name}` in [tasks]{}",
+                if tasks.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " (available: {})",
+                        tasks.keys().cloned().collect::<Vec<_>>().join(", ")
+                    )
+                }
+            ),
+        })?;
+        stack.push(name.to_string());
+        for dep in &task.depends_on {
+            visit(tasks, dep, stack, order)?;
+        }
+        stack.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(tasks, name, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
+
+/// The shell command line for `step`: Kargo commands are rewritten to call
+/// the executable in `$KARGO`.
+pub fn step_command(step: &str, builtins: &[String]) -> String {
+    let step = step.trim();
+    let args = match step.split_once(char::is_whitespace) {
+        Some(("kargo", rest)) => Some(rest.trim_start()),
+        _ if step == "kargo" => Some(""),
+        _ => {
+            let first = step.split_whitespace().next().unwrap_or_default();
+            builtins.iter().any(|b| b == first).then_some(step)
+        }
+    };
+    match args {
+        Some(args) if cfg!(windows) => format!("\"%KARGO%\" {args}").trim_end().to_string(),
+        Some(args) => format!("\"$KARGO\" {args}").trim_end().to_string(),
+        None => step.to_string(),
+    }
+}
+
+fn shell(command: &str) -> CommandBuilder {
+    if cfg!(windows) {
+        CommandBuilder::new("cmd").args(["/C", command])
+    } else {
+        CommandBuilder::new("sh").args(["-c", command])
+    }
+}
+
+fn load(project_dir: &Path) -> miette::Result<Manifest> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(depends_on: &[&str]) -> TaskConfig {
+        TaskConfig {
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            steps: vec!["true".into()],
+            ..TaskConfig::default()
+        }
+    }
+
+    #[test]
+    fn dependencies_run_first_and_once() {
+        let tasks = BTreeMap::from([
+            ("build".to_string(), task(&[])),
+            ("image".to_string(), task(&["build"])),
+            ("docs".to_string(), task(&["build"])),
+            ("deploy".to_string(), task(&["image", "docs"])),
+        ]);
+        assert_eq!(
+            plan(&tasks, "deploy").unwrap(),
+            ["build", "image", "docs", "deploy"]
+        );
+        assert!(plan(&tasks, "missing")
+            .unwrap_err()
+            .to_string()
+            .contains("available: build, deploy, docs, image"));
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let tasks = BTreeMap::from([
+            ("a".to_string(), task(&["b"])),
+            ("b".to_string(), task(&["c"])),
+            ("c".to_string(), task(&["a"])),
+        ]);
+        assert!(plan(&tasks, "a")
+            .unwrap_err()
+            .to_string()
+            .contains("dependency cycle: a -> b -> c -> a"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kargo_commands_call_back_into_kargo() {
+        let builtins = ["build".to_string(), "test".to_string()];
+        assert_eq!(
+            step_command("build --release", &builtins),
+            "\"$KARGO\" build --release"
+        );
+        assert_eq!(step_command("kargo fmt", &builtins), "\"$KARGO\" fmt");
+        assert_eq!(
+            step_command("docker build -t app .", &builtins),
+            "docker build -t app ."
+        );
+    }
+}
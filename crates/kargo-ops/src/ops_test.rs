@@ -2,50 +2,174 @@
 //!
 //! Compiles main sources, then compiles test sources against main + test
 //! dependencies, and runs the tests using JUnit console launcher or
-//! kotlin-test runner.
+//! kotlin-test runner. `kargo test --integration` additionally compiles
+//! `src/integrationTest` against main, the unit tests, and the
+//! `[integration-test-dependencies]`, and runs only those tests.
+//!
+//! The test JVM gets the `[test] jvm-args`, `system-properties`, and `env`
+//! from the manifest, with `-D`/`--env` values from the command line taking
+//! precedence. `kargo test --device` runs Android instrumentation tests
+//! instead; see [`crate::ops_device_test`].
+//!
+//! The JUnit Platform captures what each test writes to stdout and stderr,
+//! up to `[test] max-output` per stream, into the `<system-out>` and
+//! `<system-err>` of its report entry. The console shows the captured
+//! output of failed tests, or of every test with `--show-output`;
+//! `--capture=no` streams the output as it is written instead.
+//!
+//! `kargo test --shard 2/5` runs only the test classes whose name hashes to
+//! the second of five shards, so CI jobs can split a suite; see [`Shard`].
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use kargo_compiler::build_cache::BuildCache;
 use kargo_compiler::classpath;
-use kargo_compiler::dispatch::CompilerDispatch;
+use kargo_compiler::dispatch::{CompilerDispatch, TargetCompiler};
 use kargo_compiler::env::BuildEnv;
 use kargo_compiler::fingerprint;
 use kargo_compiler::incremental::{self, IncrementalDecision};
 use kargo_compiler::source_set_discovery::collect_kotlin_files;
 use kargo_compiler::unit::CompilationUnit;
 use kargo_maven::cache::LocalCache;
-use kargo_util::errors::KargoError;
+use kargo_util::errors::{ErrorFormat, KargoError};
+use kargo_util::process::StreamKind;
 
 use crate::ops_build::{self, BuildOptions};
+use crate::ops_device_test::{Outcome, TestCase};
 
 pub const JUNIT_PLATFORM_GROUP: &str = "org.junit.platform";
 pub const JUNIT_PLATFORM_STANDALONE: &str = "junit-platform-console-standalone";
 pub const JUNIT_PLATFORM_VERSION: &str = "1.11.4";
 
+/// Captured output kept per test and stream without `[test] max-output`.
+pub const DEFAULT_MAX_OUTPUT: u64 = 1024 * 1024;
+
+/// Options for [`test`].
+#[derive(Debug, Clone, Default)]
+pub struct TestOptions {
+    pub target: Option<String>,
+    pub filter: Option<String>,
+    /// `KEY=VALUE` environment variables from `--env`.
+    pub env: Vec<(String, String)>,
+    /// `key=value` system properties from `-D`.
+    pub system_properties: Vec<(String, String)>,
+    /// Run the integration tests in `src/integrationTest` instead of the
+    /// unit tests.
+    pub integration: bool,
+    /// Run the Android instrumentation tests on a device instead: the
+    /// serial to use, or empty for the only connected device.
+    pub device: Option<String>,
+    /// Let tests write to the console as they run instead of capturing
+    /// their output into the report (`--capture=no`).
+    pub no_capture: bool,
+    /// Print the captured output of passed tests too.
+    pub show_output: bool,
+    /// Run only the test classes of this shard.
+    pub shard: Option<Shard>,
+    pub verbose: bool,
+}
+
+/// One of `total` parts of a test suite, from `--shard <index>/<total>`.
+///
+/// Test classes are assigned by a hash of their fully qualified name, so a
+/// class stays in its shard as other classes are added or removed, and
+/// every class runs in exactly one shard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 1-based.
+    pub index: u32,
+    pub total: u32,
+}
+
+impl Shard {
+    /// Parse a `--shard` value such as `2/5`.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        let shard = s
+            .split_once('/')
+            .and_then(|(i, n)| Some((i.trim().parse().ok()?, n.trim().parse().ok()?)))
+            .map(|(index, total)| Self { index, total })
+            .filter(|s| s.index >= 1 && s.index <= s.total);
+        shard.ok_or_else(|| {
+            KargoError::Generic {
+                message: format!(
+                    "Invalid --shard '{s}': expected INDEX/TOTAL with 1 <= INDEX <= TOTAL, \
+                     e.g. 2/5"
+                ),
+            }
+            .into()
+        })
+    }
+
+    /// Whether the test class `class` belongs to this shard.
+    pub fn contains(&self, class: &str) -> bool {
+        // FNV-1a: stable across platforms and Kargo versions.
+        let hash = class.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+        hash % u64::from(self.total) == u64::from(self.index - 1)
+    }
+
+    /// `shard-<index>-of-<total>`, naming this shard's reports.
+    pub fn label(&self) -> String {
+        format!("shard-{}-of-{}", self.index, self.total)
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
 /// Run project tests.
-pub async fn test(
+///
+/// Runs the unit tests, or with [`TestOptions::integration`] only the
+/// integration tests, which are compiled against main and the unit tests.
+/// JUnit XML reports go to `build/<target>/<profile>/test-results/`.
+pub async fn test(project_dir: &Path, opts: &TestOptions) -> miette::Result<()> {
+    if let Some(serial) = &opts.device {
+        return crate::ops_device_test::test(project_dir, opts, serial).await;
+    }
+
+    let build_result = ops_build::build(project_dir, &build_options(opts)).await?;
+    run_tests(project_dir, build_result, opts).await
+}
+
+/// Like [`test`], building with an already loaded context, e.g. one kept
+/// warm by `kargo daemon`. The target is the context's.
+pub async fn test_with_context(ctx: crate::BuildContext, opts: &TestOptions) -> miette::Result<()> {
+    let project_dir = ctx.project_dir.clone();
+    if let Some(serial) = &opts.device {
+        return crate::ops_device_test::test(&project_dir, opts, serial).await;
+    }
+
+    let build_result = ops_build::build_with_context(ctx, &build_options(opts)).await?;
+    run_tests(&project_dir, build_result, opts).await
+}
+
+fn build_options(opts: &TestOptions) -> BuildOptions {
+    BuildOptions {
+        target: opts.target.clone(),
+        verbose: opts.verbose,
+        quiet: true,
+        ..Default::default()
+    }
+}
+
+/// Compile and run the tests of the project built into `build_result`.
+async fn run_tests(
     project_dir: &Path,
-    target: Option<&str>,
-    filter: Option<&str>,
-    verbose: bool,
+    build_result: ops_build::BuildResult,
+    opts: &TestOptions,
 ) -> miette::Result<()> {
     use kargo_util::progress::status;
 
-    let build_result = ops_build::build(
-        project_dir,
-        &BuildOptions {
-            target: target.map(String::from),
-            verbose,
-            quiet: true,
-            ..Default::default()
-        },
-    )
-    .await?;
-
+    let verbose = opts.verbose;
+    let filter = opts.filter.as_deref();
     if !build_result.success {
-        return Err(KargoError::Generic {
-            message: "Build failed, cannot run tests.".into(),
+        return Err(KargoError::Compilation {
+            message: "cannot run tests".into(),
         }
         .into());
     }
@@ -56,20 +180,34 @@ pub async fn test(
     let preflight = &build_result.preflight;
 
     let discovered = &build_result.discovered;
-    let mut test_kotlin_dirs: Vec<PathBuf> = Vec::new();
-    for ss in &discovered.test_sources {
-        test_kotlin_dirs.extend(ss.kotlin_dirs.clone());
-    }
-    let test_sources = collect_kotlin_files(&test_kotlin_dirs);
+    let test_sources = collect_kotlin_files(&kotlin_dirs(&discovered.test_sources));
+    let it_sources = if opts.integration {
+        collect_kotlin_files(&kotlin_dirs(&discovered.integration_test_sources))
+    } else {
+        Vec::new()
+    };
 
-    if test_sources.is_empty() {
+    if opts.integration && it_sources.is_empty() {
+        status("Testing", "no integration test sources found");
+        return Ok(());
+    }
+    if !opts.integration && test_sources.is_empty() {
         status("Testing", "no test sources found");
         return Ok(());
     }
 
     status(
         "Testing",
-        &format!("{} v{}", manifest.package.name, manifest.package.version),
+        &format!(
+            "{} v{}{}",
+            manifest.package.name,
+            manifest.package.version,
+            if opts.integration {
+                " (integration)"
+            } else {
+                ""
+            }
+        ),
     );
 
     let config = match kargo_core::config::GlobalConfig::load() {
@@ -84,18 +222,18 @@ pub async fn test(
     std::fs::create_dir_all(&test_classes_dir).map_err(KargoError::Io)?;
 
     let cp = &build_result.classpath;
-    let mut test_classpath = vec![build_result.classes_dir.clone()];
+    let mut base_classpath = vec![build_result.classes_dir.clone()];
+    base_classpath.extend(build_result.fixtures_dir.clone());
 
     let gen_base = build_result.build_dir.join("generated");
     for subdir in &["ksp/classes", "kapt/classes"] {
         let dir = gen_base.join(subdir);
         if dir.is_dir() {
-            test_classpath.push(dir);
+            base_classpath.push(dir);
         }
     }
 
-    test_classpath.extend(cp.test_jars.clone());
-
+    let mut runtime_jars = Vec::new();
     let kotlin_lib = preflight.toolchain.home.join("lib");
     for jar_name in kargo_compiler::classpath::STDLIB_RUNTIME_JARS
         .iter()
@@ -106,20 +244,58 @@ pub async fn test(
         ])
     {
         let jar = kotlin_lib.join(jar_name);
-        if jar.is_file()
-            && !test_classpath
-                .iter()
-                .any(|p| p.file_name() == jar.file_name())
-        {
-            test_classpath.push(jar);
+        if jar.is_file() {
+            runtime_jars.push(jar);
         }
     }
 
-    let junit_standalone = ensure_junit_platform(project_dir, lockfile).await?;
-    if let Some(ref jar) = junit_standalone {
-        test_classpath.push(jar.clone());
+    // Workspace members used by the tests, e.g. for their fixtures.
+    let module_classpath = |deps| {
+        crate::ops_fixtures::module_classpath(
+            project_dir,
+            crate::ops_fixtures::module_dependencies(deps),
+            build_result.target,
+            &build_result.profile_name,
+            verbose,
+        )
+    };
+    // Tests compile against the test-compile classpath and run on the
+    // test-runtime one; neither contains processor-scoped artifacts.
+    let test_modules = module_classpath(&manifest.dev_dependencies).await?;
+    let test_jars = [test_modules.as_slice(), &cp.test_jars].concat();
+    let test_runtime_jars = [test_modules.as_slice(), &cp.test_runtime_jars].concat();
+    let mut it_jars = Vec::new();
+    let mut it_runtime_jars = Vec::new();
+    if opts.integration {
+        let it_modules = module_classpath(&manifest.integration_test_dependencies).await?;
+        it_jars = [
+            it_modules.as_slice(),
+            &test_modules,
+            &cp.integration_test_jars,
+        ]
+        .concat();
+        it_runtime_jars = [
+            it_modules.as_slice(),
+            &test_modules,
+            &cp.integration_test_runtime_jars,
+        ]
+        .concat();
     }
 
+    let junit_standalone = ensure_junit_platform(project_dir, lockfile).await?;
+    let with_jars = |mut classpath: Vec<PathBuf>, jars: &[PathBuf]| {
+        classpath.extend(jars.iter().cloned());
+        for jar in &runtime_jars {
+            if !classpath.iter().any(|p| p.file_name() == jar.file_name()) {
+                classpath.push(jar.clone());
+            }
+        }
+        if let Some(ref jar) = junit_standalone {
+            classpath.push(jar.clone());
+        }
+        classpath
+    };
+
     let profile = manifest
         .profile
         .get(&build_result.profile_name)
@@ -142,12 +318,13 @@ pub async fn test(
             .iter()
             .flat_map(|ss| ss.resource_dirs.clone())
             .collect(),
-        classpath: test_classpath.clone(),
+        classpath: with_jars(base_classpath.clone(), &test_jars),
         output_dir: test_classes_dir.clone(),
-        compiler_args: test_compiler_args,
+        compiler_args: test_compiler_args.clone(),
         is_test: true,
         generated_sources: vec![],
         processor_jars: vec![],
+        local_jars: cp.local_jars.clone(),
     };
     let kotlin_ver = preflight.toolchain.version.to_string();
     let env = BuildEnv::new(
@@ -166,88 +343,122 @@ pub async fn test(
         build_result.target.kebab_name(),
         &build_result.profile_name,
     );
-    let decision = incremental::check(&test_unit, &fp_dir, &kotlin_ver);
-
-    match decision {
-        IncrementalDecision::UpToDate => {
-            if verbose {
-                println!("  test: up-to-date (skipped)");
-            }
-        }
-        IncrementalDecision::NeedsRebuild(fp) => {
-            let build_cache = BuildCache::new(BuildCache::default_path(), None);
-            if build_cache.restore(&fp, &test_classes_dir)? {
-                if verbose {
-                    println!("  test: restored from cache");
-                }
-                incremental::mark_complete(&fp_dir, "test", &fp, &test_unit)?;
-            } else {
-                let compiler = CompilerDispatch::resolve(
-                    build_result.target,
-                    preflight.toolchain.clone(),
-                    preflight.jdk.home.clone(),
-                    preflight.java_target.clone(),
-                );
-
-                let compile_output = compiler.compile(&test_unit, &env)?;
-                if !compile_output.success {
-                    for d in &compile_output.diagnostics {
-                        eprintln!(
-                            "{}: {}",
-                            match d.severity {
-                                kargo_compiler::unit::DiagnosticSeverity::Error => "error",
-                                kargo_compiler::unit::DiagnosticSeverity::Warning => "warning",
-                                kargo_compiler::unit::DiagnosticSeverity::Info => "info",
-                            },
-                            d.message
-                        );
-                    }
-                    return Err(KargoError::Generic {
-                        message: "Test compilation failed.".into(),
-                    }
-                    .into());
-                }
+    let compiler = CompilerDispatch::resolve(
+        build_result.target,
+        preflight.toolchain.clone(),
+        preflight.jdk.home.clone(),
+        preflight.java_target.clone(),
+    );
 
-                incremental::mark_complete(&fp_dir, "test", &fp, &test_unit)?;
-                let _ = build_cache.put(&fp, &test_classes_dir);
-            }
-        }
+    if !test_unit.sources.is_empty() {
+        compile_unit(
+            &test_unit,
+            compiler.as_ref(),
+            &env,
+            &fp_dir,
+            &kotlin_ver,
+            verbose,
+        )?;
     }
 
+    // Integration tests compile against main plus the unit-test classes.
+    let it_classes_dir = build_result.build_dir.join("integration-test-classes");
+    let suite = if opts.integration {
+        std::fs::create_dir_all(&it_classes_dir).map_err(KargoError::Io)?;
+        let mut it_classpath = vec![test_classes_dir.clone()];
+        it_classpath.extend(base_classpath);
+        let it_unit = CompilationUnit {
+            name: "integrationTest".into(),
+            target: build_result.target,
+            sources: it_sources,
+            resource_dirs: discovered
+                .integration_test_sources
+                .iter()
+                .flat_map(|ss| ss.resource_dirs.clone())
+                .collect(),
+            classpath: with_jars(it_classpath, &it_jars),
+            output_dir: it_classes_dir.clone(),
+            compiler_args: test_compiler_args,
+            is_test: true,
+            generated_sources: vec![],
+            processor_jars: vec![],
+            local_jars: cp.local_jars.clone(),
+        };
+        compile_unit(
+            &it_unit,
+            compiler.as_ref(),
+            &env,
+            &fp_dir,
+            &kotlin_ver,
+            verbose,
+        )?;
+        it_unit
+    } else {
+        test_unit
+    };
+    let suite_jars = if opts.integration {
+        &it_runtime_jars
+    } else {
+        &test_runtime_jars
+    };
+
     // 5. Run tests using java
-    status("Running", &format!("{} test(s)", test_unit.sources.len()));
+    status("Running", &format!("{} test(s)", suite.sources.len()));
     let java_bin = preflight.jdk.home.join("bin").join("java");
 
-    let mut run_cp = vec![
-        test_classes_dir.to_string_lossy().to_string(),
-        build_result.classes_dir.to_string_lossy().to_string(),
-    ];
+    let mut run_cp = Vec::new();
+    if opts.integration {
+        run_cp.push(it_classes_dir.to_string_lossy().to_string());
+    }
+    run_cp.push(test_classes_dir.to_string_lossy().to_string());
+    run_cp.push(build_result.classes_dir.to_string_lossy().to_string());
+    if let Some(ref fixtures) = build_result.fixtures_dir {
+        run_cp.push(fixtures.to_string_lossy().to_string());
+    }
 
     let resources_dir = build_result.build_dir.join("resources");
     if resources_dir.is_dir() {
         run_cp.push(resources_dir.to_string_lossy().to_string());
     }
 
-    for jar_name in kargo_compiler::classpath::STDLIB_RUNTIME_JARS
-        .iter()
-        .chain(&[
-            "kotlin-test.jar",
-            "kotlin-test-junit5.jar",
-            "kotlin-test-junit.jar",
-        ])
-    {
-        let jar = kotlin_lib.join(jar_name);
-        if jar.is_file() {
-            run_cp.push(jar.to_string_lossy().to_string());
-        }
+    for jar in &runtime_jars {
+        run_cp.push(jar.to_string_lossy().to_string());
     }
 
-    run_cp.push(classpath::to_classpath_string(&cp.test_jars));
+    run_cp.push(classpath::to_classpath_string(suite_jars));
 
     let classpath_str = run_cp.join(if cfg!(windows) { ";" } else { ":" });
+    let (jvm_args, test_env) = jvm_launch_config(manifest.test.as_ref(), opts);
+    let max_output = manifest
+        .test
+        .as_ref()
+        .and_then(|t| t.max_output())
+        .unwrap_or(DEFAULT_MAX_OUTPUT);
+    let run = |cmd: kargo_util::process::CommandBuilder| {
+        if !opts.no_capture {
+            return cmd.exec();
+        }
+        cmd.exec_streaming(|kind, line| match kind {
+            StreamKind::Stdout => println!("{line}"),
+            StreamKind::Stderr => eprintln!("{line}"),
+        })
+    };
+    let java_cmd = || {
+        let mut cmd =
+            kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+                .response_file(preflight.jdk.java_accepts_argfiles())
+                .args(jvm_args.iter().cloned())
+                .env(
+                    "JAVA_HOME",
+                    preflight.jdk.home.to_string_lossy().to_string(),
+                );
+        for (k, v) in &test_env {
+            cmd = cmd.env(k, v);
+        }
+        cmd
+    };
 
-    let junit_jar = cp
-        .test_jars
+    let junit_jar = suite_jars
         .iter()
         .find(|p| {
             p.file_name()
@@ -258,31 +469,87 @@ pub async fn test(
                 .unwrap_or(false)
         })
         .cloned()
-        .or(junit_standalone);
+        .or(junit_standalone.clone());
 
+    let mut reports_dir = build_result
+        .build_dir
+        .join("test-results")
+        .join(if opts.integration {
+            "integration-test"
+        } else {
+            "test"
+        });
+    if let Some(shard) = &opts.shard {
+        reports_dir.push(shard.label());
+    }
     let output = if let Some(junit) = junit_jar {
-        let mut cmd =
-            kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
-                .arg("-jar")
-                .arg(junit.to_string_lossy().to_string())
-                .arg("execute")
-                .arg("--class-path")
-                .arg(&classpath_str)
-                .arg("--scan-class-path");
+        // Reports of earlier runs would be read back as this run's results.
+        if reports_dir.exists() {
+            std::fs::remove_dir_all(&reports_dir).map_err(KargoError::Io)?;
+        }
+        let mut cmd = java_cmd()
+            .arg("-jar")
+            .arg(junit.to_string_lossy().to_string())
+            .arg("execute")
+            .arg("--class-path")
+            .arg(&classpath_str);
+
+        if let Some(shard) = &opts.shard {
+            let suite_classes_dir = if opts.integration {
+                &it_classes_dir
+            } else {
+                &test_classes_dir
+            };
+            let classes = test_class_names(suite_classes_dir);
+            let selected: Vec<&String> = classes.iter().filter(|c| shard.contains(c)).collect();
+            status(
+                "Sharding",
+                &format!(
+                    "shard {shard}: {} of {} test classes",
+                    selected.len(),
+                    classes.len()
+                ),
+            );
+            if selected.is_empty() {
+                return Ok(());
+            }
+            for class in selected {
+                cmd = cmd.arg("--select-class").arg(class);
+            }
+            cmd = cmd
+                .arg("--reports-dir")
+                .arg(reports_dir.to_string_lossy().to_string());
+        } else {
+            cmd = cmd
+                .arg("--scan-class-path")
+                .arg("--reports-dir")
+                .arg(reports_dir.to_string_lossy().to_string());
+
+            // Only discover the integration tests, not the unit tests that
+            // are also on the classpath.
+            if opts.integration {
+                cmd = cmd.arg(it_classes_dir.to_string_lossy().to_string());
+            }
+        }
 
         if let Some(f) = filter {
             cmd = cmd.arg("--include-classname").arg(f);
         }
+        if !opts.no_capture {
+            for setting in capture_config(max_output) {
+                cmd = cmd.arg("--config").arg(setting);
+            }
+        }
 
-        cmd = cmd.env(
-            "JAVA_HOME",
-            preflight.jdk.home.to_string_lossy().to_string(),
-        );
-        cmd.exec().map_err(|e| KargoError::Generic {
+        let output = run(cmd).map_err(|e| KargoError::Generic {
             message: format!("Failed to execute JUnit: {e}"),
-        })?
+        })?;
+        if let Some(shard) = &opts.shard {
+            label_reports(&reports_dir, shard)?;
+        }
+        output
     } else {
-        let test_main_classes = detect_test_main_classes(&test_unit.sources, project_dir);
+        let test_main_classes = detect_test_main_classes(&suite.sources, project_dir);
 
         if test_main_classes.is_empty() {
             return Err(KargoError::Generic {
@@ -295,56 +562,412 @@ pub async fn test(
 
         let mut last_output = None;
         for main_class in &test_main_classes {
-            if let Some(ref f) = filter {
+            if let Some(f) = filter {
                 if !main_class.contains(f) {
                     continue;
                 }
             }
+            if opts.shard.is_some_and(|shard| !shard.contains(main_class)) {
+                continue;
+            }
 
-            let cmd =
-                kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
-                    .arg("-cp")
-                    .arg(&classpath_str)
-                    .arg(main_class)
-                    .env(
-                        "JAVA_HOME",
-                        preflight.jdk.home.to_string_lossy().to_string(),
-                    );
-
-            let result = cmd.exec().map_err(|e| KargoError::Generic {
+            let cmd = java_cmd().arg("-cp").arg(&classpath_str).arg(main_class);
+
+            let result = run(cmd).map_err(|e| KargoError::Generic {
                 message: format!("Failed to execute test {main_class}: {e}"),
             })?;
 
             last_output = Some(result);
         }
 
-        last_output.ok_or_else(|| KargoError::Generic {
-            message: "No test classes matched the filter.".into(),
-        })?
+        match (last_output, &opts.shard) {
+            (Some(output), _) => output,
+            (None, Some(shard)) => {
+                status("Sharding", &format!("shard {shard}: no test classes"));
+                return Ok(());
+            }
+            (None, None) => {
+                return Err(KargoError::Generic {
+                    message: "No test classes matched the filter.".into(),
+                }
+                .into())
+            }
+        }
     };
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !opts.no_capture {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if !stdout.is_empty() {
+            print!("{stdout}");
+        }
+        if !stderr.is_empty() {
+            eprint!("{stderr}");
+        }
 
-    if !stdout.is_empty() {
-        print!("{stdout}");
+        let cases = read_junit_reports(&reports_dir);
+        let shown = cases
+            .iter()
+            .filter(|case| opts.show_output || case.outcome == Outcome::Failed);
+        for section in captured_output(shown, max_output) {
+            print!("{section}");
+        }
     }
-    if !stderr.is_empty() {
-        eprint!("{stderr}");
+
+    if kargo_util::events::enabled() {
+        let cases = read_junit_reports(&reports_dir);
+        for case in &cases {
+            ops_build::emit_test(case);
+        }
+        ops_build::emit_test_finish(output.status.success(), &cases);
     }
 
     if output.status.success() {
         status("Finished", "test result: ok");
         Ok(())
     } else {
+        if kargo_util::errors::error_format() == ErrorFormat::Short {
+            for line in short_failures(&read_junit_reports(&reports_dir)) {
+                eprintln!("{line}");
+            }
+        }
         let code = output.status.code().unwrap_or(1);
-        Err(KargoError::Generic {
+        Err(KargoError::Test {
             message: format!("Tests failed (exit code {code})"),
         }
         .into())
     }
 }
 
+/// Fully qualified names of the top-level classes compiled into
+/// `classes_dir`, sorted.
+fn test_class_names(classes_dir: &Path) -> Vec<String> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(root, &path, out);
+                continue;
+            }
+            let Some(stem) = path
+                .strip_prefix(root)
+                .ok()
+                .filter(|rel| rel.extension().is_some_and(|e| e == "class"))
+                .map(|rel| rel.with_extension(""))
+            else {
+                continue;
+            };
+            let name: Vec<String> = stem
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            let name = name.join(".");
+            let simple = name.rsplit('.').next().unwrap_or_default();
+            if !name.contains('$') && simple != "module-info" && simple != "package-info" {
+                out.push(name);
+            }
+        }
+    }
+    let mut classes = Vec::new();
+    walk(classes_dir, classes_dir, &mut classes);
+    classes.sort();
+    classes
+}
+
+/// Add the shard's label to the report files in `reports_dir`, so the
+/// reports of all shards can be collected into one directory.
+fn label_reports(reports_dir: &Path, shard: &Shard) -> miette::Result<()> {
+    let Ok(entries) = std::fs::read_dir(reports_dir) else {
+        return Ok(());
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        let Some(stem) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".xml"))
+            .filter(|n| n.starts_with("TEST-"))
+        else {
+            continue;
+        };
+        let labeled = path.with_file_name(format!("{stem}-{}.xml", shard.label()));
+        std::fs::rename(&path, labeled).map_err(KargoError::Io)?;
+    }
+    Ok(())
+}
+
+/// JUnit Platform settings capturing each test's stdout and stderr, up to
+/// `max_output` bytes per stream.
+fn capture_config(max_output: u64) -> [String; 3] {
+    [
+        "junit.platform.output.capture.stdout=true".to_string(),
+        "junit.platform.output.capture.stderr=true".to_string(),
+        format!(
+            "junit.platform.output.capture.maxBuffer={}",
+            max_output.min(i32::MAX as u64)
+        ),
+    ]
+}
+
+/// A `---- <class>.<name> stdout ----` section per captured stream of
+/// `cases`, noting output that reached the `max_output` limit.
+fn captured_output<'a>(
+    cases: impl IntoIterator<Item = &'a TestCase>,
+    max_output: u64,
+) -> Vec<String> {
+    let mut sections = Vec::new();
+    for case in cases {
+        for (stream, text) in [("stdout", &case.stdout), ("stderr", &case.stderr)] {
+            let Some(text) = text else {
+                continue;
+            };
+            let mut section = format!(
+                "\n---- {}.{} {stream} ----\n{text}\n",
+                case.class, case.name
+            );
+            if text.len() as u64 >= max_output {
+                section.push_str(&format!(
+                    "[output truncated at {max_output} bytes, see [test] max-output]\n"
+                ));
+            }
+            sections.push(section);
+        }
+    }
+    sections
+}
+
+/// The failed `cases` for `--error-format short`, one
+/// `error[test]: <class>.<name>: <first line of the failure>` each.
+fn short_failures(cases: &[TestCase]) -> Vec<String> {
+    cases
+        .iter()
+        .filter(|case| case.outcome == Outcome::Failed)
+        .map(|case| {
+            let reason = case
+                .stack
+                .as_deref()
+                .and_then(|stack| stack.lines().map(str::trim).find(|l| !l.is_empty()))
+                .unwrap_or("failed");
+            format!("error[test]: {}.{}: {reason}", case.class, case.name)
+        })
+        .collect()
+}
+
+/// The test cases recorded in the JUnit XML reports (`TEST-*.xml`) in `dir`.
+pub fn read_junit_reports(dir: &Path) -> Vec<TestCase> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("TEST-") && n.ends_with(".xml"))
+        })
+        .collect();
+    reports.sort();
+    reports
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .flat_map(|xml| parse_junit_report(&xml))
+        .collect()
+}
+
+/// The test cases of one JUnit XML report. A case with a `<failure>` or
+/// `<error>` failed, with its text (or `message`) as the stack; one with
+/// `<skipped>` was skipped. Its `<system-out>` and `<system-err>` blocks are
+/// the captured output, except the JUnit Platform's `unique-id:` block.
+pub fn parse_junit_report(xml: &str) -> Vec<TestCase> {
+    use quick_xml::events::{BytesStart, Event};
+
+    fn attr(e: &BytesStart, name: &str) -> Option<String> {
+        e.attributes()
+            .flatten()
+            .find(|a| a.key.as_ref() == name.as_bytes())
+            .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+    }
+
+    /// The element of the current test case whose text is being read.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Reading {
+        Nothing,
+        Failure,
+        Stdout,
+        Stderr,
+    }
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut cases = Vec::new();
+    let mut current: Option<TestCase> = None;
+    let mut reading = Reading::Nothing;
+    let mut text = String::new();
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::Text(t)) if reading != Reading::Nothing => {
+                if let Ok(t) = t.unescape() {
+                    text.push_str(&t);
+                }
+                continue;
+            }
+            Ok(Event::CData(t)) if reading != Reading::Nothing => {
+                text.push_str(&String::from_utf8_lossy(&t));
+                continue;
+            }
+            Ok(Event::End(e)) => {
+                match e.name().as_ref() {
+                    b"testcase" => cases.extend(current.take()),
+                    b"failure" | b"error" | b"system-out" | b"system-err" => {
+                        if let Some(case) = current.as_mut() {
+                            let block = std::mem::take(&mut text);
+                            match reading {
+                                Reading::Failure if !block.trim().is_empty() => {
+                                    case.stack = Some(block.trim().to_string());
+                                }
+                                Reading::Stdout => append_output(&mut case.stdout, &block),
+                                Reading::Stderr => append_output(&mut case.stderr, &block),
+                                _ => {}
+                            }
+                        }
+                        reading = Reading::Nothing;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        match e.name().as_ref() {
+            b"testcase" => {
+                let case = TestCase {
+                    class: attr(&e, "classname").unwrap_or_default(),
+                    name: attr(&e, "name").unwrap_or_default(),
+                    outcome: Outcome::Passed,
+                    stack: None,
+                    time: attr(&e, "time")
+                        .and_then(|t| t.parse().ok())
+                        .unwrap_or_default(),
+                    stdout: None,
+                    stderr: None,
+                };
+                if empty {
+                    cases.push(case);
+                } else {
+                    current = Some(case);
+                }
+            }
+            b"failure" | b"error" => {
+                if let Some(case) = current.as_mut() {
+                    case.outcome = Outcome::Failed;
+                    case.stack = attr(&e, "message");
+                    if !empty {
+                        reading = Reading::Failure;
+                    }
+                }
+            }
+            b"system-out" if current.is_some() && !empty => reading = Reading::Stdout,
+            b"system-err" if current.is_some() && !empty => reading = Reading::Stderr,
+            b"skipped" => {
+                if let Some(case) = current.as_mut() {
+                    case.outcome = Outcome::Skipped;
+                }
+            }
+            _ => {}
+        }
+    }
+    cases
+}
+
+/// Add a `<system-out>` or `<system-err>` block to `output`, skipping the
+/// test identity the JUnit Platform writes before the captured output.
+fn append_output(output: &mut Option<String>, block: &str) {
+    let block = block.trim_matches(['\r', '\n']);
+    if block.trim().is_empty() || block.trim_start().starts_with("unique-id:") {
+        return;
+    }
+    let output = output.get_or_insert_with(String::new);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(block);
+}
+
+fn kotlin_dirs(source_sets: &[kargo_core::source_set::SourceSet]) -> Vec<PathBuf> {
+    source_sets
+        .iter()
+        .flat_map(|ss| ss.kotlin_dirs.clone())
+        .collect()
+}
+
+/// Compile a test unit unless it is up-to-date or restorable from the
+/// build cache.
+pub(crate) fn compile_unit(
+    unit: &CompilationUnit,
+    compiler: &dyn TargetCompiler,
+    env: &BuildEnv,
+    fp_dir: &Path,
+    kotlin_ver: &str,
+    verbose: bool,
+) -> miette::Result<()> {
+    match incremental::check(unit, fp_dir, kotlin_ver) {
+        IncrementalDecision::UpToDate => {
+            if verbose {
+                println!("  {}: up-to-date (skipped)", unit.name);
+            }
+        }
+        IncrementalDecision::NeedsRebuild(fp) => {
+            let build_cache = BuildCache::new(BuildCache::default_path(), None);
+            if build_cache.restore(&fp, &unit.output_dir)? {
+                if verbose {
+                    println!("  {}: restored from cache", unit.name);
+                }
+                incremental::mark_complete(fp_dir, &unit.name, &fp, unit)?;
+            } else {
+                let compile_output = compiler.compile(unit, env)?;
+                if !compile_output.success {
+                    ops_build::print_diagnostics(&compile_output.diagnostics);
+                    return Err(KargoError::Compilation {
+                        message: "test sources".into(),
+                    }
+                    .into());
+                }
+
+                incremental::mark_complete(fp_dir, &unit.name, &fp, unit)?;
+                let _ = build_cache.put(&fp, &unit.output_dir);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// JVM arguments and environment for the test JVM: `[test] jvm-args`, then
+/// one `-Dkey=value` per system property. Command-line values override
+/// manifest values with the same key.
+fn jvm_launch_config(
+    config: Option<&kargo_core::manifest::TestConfig>,
+    opts: &TestOptions,
+) -> (Vec<String>, BTreeMap<String, String>) {
+    let mut props = BTreeMap::new();
+    let mut env = BTreeMap::new();
+    let mut args = Vec::new();
+    if let Some(config) = config {
+        args.extend(config.jvm_args.iter().cloned());
+        props.extend(config.system_properties.clone());
+        env.extend(config.env.clone());
+    }
+    props.extend(opts.system_properties.iter().cloned());
+    env.extend(opts.env.iter().cloned());
+    args.extend(props.iter().map(|(k, v)| format!("-D{k}={v}")));
+    (args, env)
+}
+
 fn detect_test_main_classes(test_sources: &[PathBuf], project_dir: &Path) -> Vec<String> {
     let mut classes = Vec::new();
 
@@ -361,7 +984,9 @@ fn detect_test_main_classes(test_sources: &[PathBuf], project_dir: &Path) -> Vec
     classes
 }
 
-async fn ensure_junit_platform(
+/// Download the JUnit console launcher if the locked dependencies include a
+/// test framework. Returns `None` when the project has none.
+pub(crate) async fn ensure_junit_platform(
     project_dir: &Path,
     lockfile: &kargo_core::lockfile::Lockfile,
 ) -> miette::Result<Option<PathBuf>> {
@@ -413,6 +1038,7 @@ fn derive_test_class_name(file: &Path, content: &str, project_dir: &Path) -> Opt
 
     let test_roots = [
         project_dir.join("src/test/kotlin"),
+        project_dir.join("src/integrationTest/kotlin"),
         project_dir.join("src/commonTest/kotlin"),
         project_dir.join("src/jvmTest/kotlin"),
     ];
@@ -429,3 +1055,204 @@ fn derive_test_class_name(file: &Path, content: &str, project_dir: &Path) -> Opt
 
     Some(format!("{stem}Kt"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_junit_report_outcomes() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="JUnit Jupiter" tests="3" failures="1" skipped="1">
+  <testcase name="adds()" classname="com.example.CalcTest" time="0.012"/>
+  <testcase name="divides()" classname="com.example.CalcTest" time="0.004">
+    <failure message="expected: &lt;2&gt; but was: &lt;3&gt;" type="org.opentest4j.AssertionFailedError">org.opentest4j.AssertionFailedError: expected: &lt;2&gt; but was: &lt;3&gt;
+	at com.example.CalcTest.divides(CalcTest.kt:14)</failure>
+    <system-out><![CDATA[unique-id: [engine:junit-jupiter]]]></system-out>
+  </testcase>
+  <testcase name="rounds()" classname="com.example.CalcTest" time="0">
+    <skipped/>
+  </testcase>
+</testsuite>
+"#;
+        let cases = parse_junit_report(xml);
+        assert_eq!(cases.len(), 3);
+        assert_eq!(cases[0].name, "adds()");
+        assert_eq!(cases[0].outcome, Outcome::Passed);
+        assert_eq!(cases[1].outcome, Outcome::Failed);
+        assert_eq!(
+            cases[1].stack.as_deref().and_then(|s| s.lines().next()),
+            Some("org.opentest4j.AssertionFailedError: expected: <2> but was: <3>")
+        );
+        assert_eq!(cases[2].class, "com.example.CalcTest");
+        assert_eq!(cases[2].outcome, Outcome::Skipped);
+    }
+
+    #[test]
+    fn shards_partition_classes_deterministically() {
+        assert_eq!(Shard::parse("2/5").unwrap(), Shard { index: 2, total: 5 });
+        for bad in ["0/5", "6/5", "2", "a/b", "1/0"] {
+            assert!(Shard::parse(bad).is_err(), "{bad}");
+        }
+
+        let classes: Vec<String> = (0..200).map(|i| format!("com.example.Test{i}")).collect();
+        let shards: Vec<Shard> = (1..=4).map(|index| Shard { index, total: 4 }).collect();
+        let mut sizes = Vec::new();
+        for class in &classes {
+            let owners = shards.iter().filter(|s| s.contains(class)).count();
+            assert_eq!(owners, 1, "{class}");
+        }
+        for shard in &shards {
+            sizes.push(classes.iter().filter(|c| shard.contains(c)).count());
+        }
+        assert!(sizes.iter().all(|&n| n > 30), "{sizes:?}");
+        // The assignment is part of the CLI contract: CI jobs rely on it
+        // not changing between Kargo versions.
+        assert!(Shard { index: 1, total: 1 }.contains("com.example.CalcTest"));
+        assert!(Shard { index: 2, total: 4 }.contains("com.example.CalcTest"));
+        assert!(Shard { index: 5, total: 5 }.contains("com.example.CalcTest"));
+    }
+
+    #[test]
+    fn shard_reports_are_labeled_and_classes_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        for file in [
+            "com/example/CalcTest.class",
+            "com/example/CalcTest$Nested.class",
+            "com/example/UtilKt.class",
+            "module-info.class",
+            "com/example/data.txt",
+        ] {
+            let path = classes.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        assert_eq!(
+            test_class_names(&classes),
+            ["com.example.CalcTest", "com.example.UtilKt"]
+        );
+
+        let reports = tmp.path().join("reports");
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(reports.join("TEST-junit-jupiter.xml"), "").unwrap();
+        label_reports(&reports, &Shard { index: 2, total: 5 }).unwrap();
+        assert!(reports
+            .join("TEST-junit-jupiter-shard-2-of-5.xml")
+            .is_file());
+    }
+
+    #[test]
+    fn reads_captured_output_without_the_test_identity() {
+        let xml = r#"<testsuite name="JUnit Jupiter" tests="2">
+  <testcase name="logs()" classname="com.example.LogTest" time="0.01">
+    <system-out><![CDATA[
+unique-id: [engine:junit-jupiter]/[class:com.example.LogTest]/[method:logs()]
+display-name: logs()
+]]></system-out>
+    <system-out><![CDATA[
+connecting to db
+connected
+]]></system-out>
+    <system-err><![CDATA[
+warning: slow query
+]]></system-err>
+  </testcase>
+  <testcase name="quiet()" classname="com.example.LogTest" time="0.01">
+    <system-out><![CDATA[unique-id: [engine:junit-jupiter]]]></system-out>
+  </testcase>
+</testsuite>
+"#;
+        let cases = parse_junit_report(xml);
+        assert_eq!(
+            cases[0].stdout.as_deref(),
+            Some("connecting to db\nconnected")
+        );
+        assert_eq!(cases[0].stderr.as_deref(), Some("warning: slow query"));
+        assert_eq!(cases[1].stdout, None);
+
+        let sections = captured_output(&cases, 16);
+        assert_eq!(
+            sections,
+            [
+                "\n---- com.example.LogTest.logs() stdout ----\nconnecting to db\nconnected\n\
+                 [output truncated at 16 bytes, see [test] max-output]\n",
+                "\n---- com.example.LogTest.logs() stderr ----\nwarning: slow query\n\
+                 [output truncated at 16 bytes, see [test] max-output]\n",
+            ]
+        );
+        assert_eq!(
+            capture_config(DEFAULT_MAX_OUTPUT)[2],
+            "junit.platform.output.capture.maxBuffer=1048576"
+        );
+    }
+
+    #[test]
+    fn short_failures_are_one_line_per_failed_test() {
+        let failed = |name: &str, stack: Option<&str>| TestCase {
+            class: "com.example.CalcTest".into(),
+            name: name.into(),
+            outcome: Outcome::Failed,
+            stack: stack.map(String::from),
+            time: 0.0,
+            stdout: None,
+            stderr: None,
+        };
+        let cases = [
+            failed(
+                "divides()",
+                Some("\nAssertionFailedError: expected: <2>\n\tat CalcTest.kt:14"),
+            ),
+            TestCase {
+                outcome: Outcome::Passed,
+                ..failed("adds()", None)
+            },
+            failed("rounds()", None),
+        ];
+        assert_eq!(
+            short_failures(&cases),
+            [
+                "error[test]: com.example.CalcTest.divides(): AssertionFailedError: expected: <2>",
+                "error[test]: com.example.CalcTest.rounds(): failed",
+            ]
+        );
+    }
+
+    #[test]
+    fn launch_config_merges_manifest_and_cli() {
+        let manifest = kargo_core::manifest::Manifest::parse_toml(
+            r#"
+[package]
+name = "svc"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[test]
+jvm-args = ["-Xmx1g"]
+system-properties = { "db.url" = "jdbc:h2:mem:test", "db.user" = "sa" }
+env = { API_URL = "http://localhost:8080" }
+"#,
+        )
+        .unwrap();
+        let opts = TestOptions {
+            system_properties: vec![("db.url".into(), "jdbc:postgresql://localhost/test".into())],
+            env: vec![("TOKEN".into(), "t".into())],
+            ..Default::default()
+        };
+
+        let (args, env) = jvm_launch_config(manifest.test.as_ref(), &opts);
+        assert_eq!(
+            args,
+            [
+                "-Xmx1g",
+                "-Ddb.url=jdbc:postgresql://localhost/test",
+                "-Ddb.user=sa",
+            ]
+        );
+        assert_eq!(env["API_URL"], "http://localhost:8080");
+        assert_eq!(env["TOKEN"], "t");
+
+        let (args, env) = jvm_launch_config(None, &TestOptions::default());
+        assert!(args.is_empty() && env.is_empty());
+    }
+}
@@ -0,0 +1,801 @@
+//! Operation: resolve and run JVM tools published to Maven (`kargo tool run`).
+//!
+//! A tool is named by its coordinate, `group:artifact[:version]`. It is
+//! resolved with its runtime dependencies from the repositories of the
+//! project in the current directory, with their credentials, or from Maven
+//! Central outside a project. The JARs and the main class are recorded in
+//! `tools/<group>/<artifact>/<version>.toml` (under `.kargo/` in a project,
+//! under the Kargo cache directory otherwise), so later runs start the JVM
+//! without resolving again.
+//!
+//! `kargo tool install` pins a tool for the user in `tools.toml` in the
+//! Kargo data directory (`~/.kargo/tools.toml`) and writes a launcher shim
+//! named after it to `~/.kargo/bin`, which runs `kargo tool run <name>`.
+//! Installed tools are resolved from Maven Central whatever the current
+//! directory, so the shim behaves the same in every project.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use kargo_core::config::GlobalConfig;
+use kargo_core::manifest::Manifest;
+use kargo_maven::cache::LocalCache;
+use kargo_maven::repository::MavenRepository;
+use kargo_toolchain::sdk;
+use kargo_util::errors::KargoError;
+
+/// Options for [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct ToolRunOptions {
+    /// `group:artifact[:version]`; the latest release when the version is
+    /// left out.
+    pub coordinate: String,
+    /// Class to run instead of the `Main-Class` of the tool's JAR.
+    pub main_class: Option<String>,
+    /// Arguments passed to the tool unchanged.
+    pub args: Vec<String>,
+    /// Resolve the tool again even when its classpath is recorded.
+    pub refresh: bool,
+    pub verbose: bool,
+}
+
+/// Options for [`install`].
+#[derive(Debug, Clone, Default)]
+pub struct ToolInstallOptions {
+    /// A name from [`KNOWN_TOOLS`] or `group:artifact[:version]`.
+    pub tool: String,
+    /// Version to pin instead of the latest release.
+    pub version: Option<String>,
+    /// Name of the shim; defaults to the known name or the artifact ID
+    /// without a `-cli` suffix.
+    pub name: Option<String>,
+    /// Class to run instead of the `Main-Class` of the tool's JAR.
+    pub main_class: Option<String>,
+    pub verbose: bool,
+}
+
+/// Tools `kargo tool install` takes by name.
+pub const KNOWN_TOOLS: &[(&str, &str)] = &[
+    ("detekt", "io.gitlab.arturbosch.detekt:detekt-cli"),
+    ("ktlint", "com.pinterest.ktlint:ktlint-cli"),
+];
+
+/// A tool coordinate as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolSpec {
+    pub group: String,
+    pub artifact: String,
+    pub version: Option<String>,
+}
+
+impl ToolSpec {
+    /// Parse `group:artifact` or `group:artifact:version`.
+    pub fn parse(coordinate: &str) -> miette::Result<Self> {
+        let parts: Vec<&str> = coordinate.split(':').collect();
+        match parts.as_slice() {
+            [group, artifact] | [group, artifact, _]
+                if !group.is_empty() && !artifact.is_empty() =>
+            {
+                Ok(Self {
+                    group: group.to_string(),
+                    artifact: artifact.to_string(),
+                    version: parts
+                        .get(2)
+                        .filter(|v| !v.is_empty())
+                        .map(|v| v.to_string()),
+                })
+            }
+            _ => Err(KargoError::Generic {
+                message: format!(
+                    "invalid tool '{coordinate}': expected group:artifact[:version], \
+                     e.g. com.pinterest.ktlint:ktlint-cli:1.5.0"
+                ),
+            }
+            .into()),
+        }
+    }
+}
+
+/// The resolved classpath and main class of a tool version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ToolRecord {
+    /// `Main-Class` of the tool's own JAR, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_class: Option<String>,
+    /// The tool's JAR first, then its dependencies.
+    pub classpath: Vec<PathBuf>,
+}
+
+impl ToolRecord {
+    /// The record at `path`, unless it is missing or one of its JARs was
+    /// removed since (e.g. by `kargo cache clean`).
+    pub fn load(path: &Path) -> Option<Self> {
+        let record: Self = toml::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        record
+            .classpath
+            .iter()
+            .all(|jar| jar.is_file())
+            .then_some(record)
+    }
+
+    fn save(&self, path: &Path) -> miette::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| KargoError::Generic {
+            message: format!("Failed to serialize {}: {e}", path.display()),
+        })?;
+        std::fs::write(path, text).map_err(KargoError::Io)?;
+        Ok(())
+    }
+}
+
+/// The tools installed for the user, from `tools.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledTools {
+    #[serde(default)]
+    pub tools: BTreeMap<String, InstalledTool>,
+}
+
+/// A tool pinned by `kargo tool install`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InstalledTool {
+    /// `group:artifact`.
+    pub coordinate: String,
+    pub version: String,
+    /// Class run instead of the `Main-Class` of the tool's JAR.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_class: Option<String>,
+}
+
+impl InstalledTool {
+    fn spec(&self) -> miette::Result<ToolSpec> {
+        let mut spec = ToolSpec::parse(&self.coordinate)?;
+        spec.version = Some(self.version.clone());
+        Ok(spec)
+    }
+}
+
+impl InstalledTools {
+    /// `tools.toml` in the Kargo data directory.
+    pub fn path() -> PathBuf {
+        kargo_util::dirs_path().join("tools.toml")
+    }
+
+    /// The tools in `path`; none if it does not exist.
+    pub fn load(path: &Path) -> miette::Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).map_err(KargoError::Io)?;
+        toml::from_str(&text).map_err(|e| {
+            KargoError::Generic {
+                message: format!("Failed to parse {}: {e}", path.display()),
+            }
+            .into()
+        })
+    }
+
+    fn save(&self, path: &Path) -> miette::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(KargoError::Io)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| KargoError::Generic {
+            message: format!("Failed to serialize {}: {e}", path.display()),
+        })?;
+        std::fs::write(path, text).map_err(KargoError::Io)?;
+        Ok(())
+    }
+}
+
+/// Where tools are resolved from and recorded.
+struct ToolEnv {
+    repos: Vec<MavenRepository>,
+    cache: LocalCache,
+    kotlin: String,
+    tools_dir: PathBuf,
+}
+
+impl ToolEnv {
+    /// The project in `cwd`, or the user-wide defaults outside a project
+    /// (or in a virtual workspace root).
+    fn load(cwd: &Path, config: &GlobalConfig) -> miette::Result<Self> {
+        let manifest_path = cwd.join("Kargo.toml");
+        if manifest_path.is_file() && !kargo_core::workspace::is_virtual_root(cwd) {
+            let manifest = Manifest::from_path(&manifest_path)?;
+            return Ok(Self {
+                repos: kargo_resolver::resolver::build_repos(&manifest),
+                cache: LocalCache::for_project(cwd, &config.cache),
+                kotlin: manifest.package.kotlin,
+                tools_dir: cwd.join(".kargo").join("tools"),
+            });
+        }
+        Ok(Self::user(config))
+    }
+
+    /// The user-wide defaults: Maven Central and the shared cache.
+    fn user(config: &GlobalConfig) -> Self {
+        Self {
+            repos: vec![MavenRepository::maven_central()],
+            cache: LocalCache::from_root(config.cache.shared_dependencies_dir()),
+            kotlin: kargo_core::DEFAULT_KOTLIN_VERSION.to_string(),
+            tools_dir: kargo_util::cache_path().join("tools"),
+        }
+    }
+
+    fn record_path(&self, spec: &ToolSpec, version: &str) -> PathBuf {
+        self.tools_dir
+            .join(&spec.group)
+            .join(&spec.artifact)
+            .join(format!("{version}.toml"))
+    }
+}
+
+/// Resolve the tool `opts.coordinate` if needed and run it in `cwd`. A
+/// name without `:` runs the installed tool of that name.
+/// Returns the tool's exit code.
+pub async fn run(cwd: &Path, opts: &ToolRunOptions) -> miette::Result<i32> {
+    let config = load_config();
+    let installed = if opts.coordinate.contains(':') {
+        None
+    } else {
+        InstalledTools::load(&InstalledTools::path())?
+            .tools
+            .remove(&opts.coordinate)
+    };
+    let (env, spec, main_class) = match installed {
+        Some(tool) => (
+            ToolEnv::user(&config),
+            tool.spec()?,
+            opts.main_class.clone().or(tool.main_class),
+        ),
+        None => (
+            ToolEnv::load(cwd, &config)?,
+            ToolSpec::parse(&opts.coordinate)?,
+            opts.main_class.clone(),
+        ),
+    };
+
+    let version = match &spec.version {
+        Some(version) => version.clone(),
+        None => latest_release(&env, &spec).await?,
+    };
+    let coordinate = format!("{}:{}:{version}", spec.group, spec.artifact);
+    let record = tool_record(&env, &spec, &version, opts.refresh).await?;
+
+    let main_class = main_class
+        .or(record.main_class)
+        .ok_or_else(|| KargoError::Generic {
+            message: format!(
+                "{coordinate} does not name a Main-Class in its manifest; \
+                 pass the class to run with --main"
+            ),
+        })?;
+    let jdk = sdk::discover_jdk(config.toolchain.jdk.as_deref()).ok_or_else(|| {
+        KargoError::Toolchain {
+            message: "No JDK found to run the tool with. Set JAVA_HOME, configure \
+                      [toolchain].jdk in ~/.kargo/config.toml, or install one with: \
+                      kargo toolchain install --jdk 21"
+                .into(),
+        }
+    })?;
+
+    let classpath = kargo_compiler::classpath::to_classpath_string(&record.classpath);
+    let java_bin = jdk.home.join("bin").join("java");
+    if opts.verbose {
+        eprintln!("  java: {} {main_class}", java_bin.display());
+    }
+    let cmd = kargo_util::process::CommandBuilder::new(java_bin.to_string_lossy().to_string())
+        .response_file(jdk.java_accepts_argfiles())
+        .arg("-cp")
+        .arg(&classpath)
+        .arg(&main_class)
+        .args(opts.args.iter().cloned())
+        .cwd(cwd.to_string_lossy().to_string())
+        .env("JAVA_HOME", jdk.home.to_string_lossy().to_string());
+    crate::ops_run::run_foreground(cmd).await
+}
+
+/// The recorded classpath of `spec` at `version`, resolving it first if it
+/// is not recorded or `refresh` is set.
+async fn tool_record(
+    env: &ToolEnv,
+    spec: &ToolSpec,
+    version: &str,
+    refresh: bool,
+) -> miette::Result<ToolRecord> {
+    use kargo_util::progress::{spinner, status};
+
+    let record_path = env.record_path(spec, version);
+    if let Some(record) = ToolRecord::load(&record_path).filter(|_| !refresh) {
+        return Ok(record);
+    }
+    let coordinate = format!("{}:{}:{version}", spec.group, spec.artifact);
+    let sp = spinner(&format!("Resolving {coordinate}..."));
+    let jars = crate::resolve_tool_jars(
+        &env.repos,
+        &env.cache,
+        &env.kotlin,
+        &spec.artifact,
+        [coordinate.as_str()],
+    )
+    .await;
+    sp.finish_and_clear();
+    let mut jars = jars?;
+    let own = env
+        .cache
+        .get_jar(&spec.group, &spec.artifact, version, None)
+        .ok_or_else(|| KargoError::Resolution {
+            message: format!("{coordinate} has no JAR to run"),
+        })?;
+    jars.retain(|jar| *jar != own);
+    jars.insert(0, own.clone());
+    let record = ToolRecord {
+        main_class: main_class_of(&own),
+        classpath: jars,
+    };
+    record.save(&record_path)?;
+    status(
+        "Resolved",
+        &format!("{coordinate} ({} JARs)", record.classpath.len()),
+    );
+    Ok(record)
+}
+
+/// Pin a tool in `tools.toml`, resolve it and write its launcher shim.
+pub async fn install(opts: &ToolInstallOptions) -> miette::Result<()> {
+    use kargo_util::progress::{note, status};
+
+    let (default_name, mut spec) = install_spec(&opts.tool)?;
+    if let Some(version) = &opts.version {
+        if spec.version.as_ref().is_some_and(|v| v != version) {
+            return Err(KargoError::Generic {
+                message: format!(
+                    "'{}' already names a version; drop --version or the version in the \
+                     coordinate",
+                    opts.tool
+                ),
+            }
+            .into());
+        }
+        spec.version = Some(version.clone());
+    }
+    let name = opts.name.clone().unwrap_or(default_name);
+    check_tool_name(&name)?;
+
+    let env = ToolEnv::user(&load_config());
+    let version = match &spec.version {
+        Some(version) => version.clone(),
+        None => latest_release(&env, &spec).await?,
+    };
+    let record = tool_record(&env, &spec, &version, false).await?;
+    if record.main_class.is_none() && opts.main_class.is_none() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{}:{}:{version} does not name a Main-Class in its manifest; pass the \
+                 class to run with --main",
+                spec.group, spec.artifact
+            ),
+        }
+        .into());
+    }
+
+    let tools_path = InstalledTools::path();
+    let mut tools = InstalledTools::load(&tools_path)?;
+    let tool = InstalledTool {
+        coordinate: format!("{}:{}", spec.group, spec.artifact),
+        version: version.clone(),
+        main_class: opts.main_class.clone(),
+    };
+    let bin_dir = kargo_util::dirs_path().join("bin");
+    let shim = write_shim(&bin_dir, &name)?;
+    let replaced = tools.tools.insert(name.clone(), tool.clone());
+    tools.save(&tools_path)?;
+    if opts.verbose {
+        eprintln!("  shim: {}", shim.display());
+    }
+
+    let label = match replaced {
+        Some(old) if old.version != version => format!("{name} {} -> {version}", old.version),
+        _ => format!("{name} {version}"),
+    };
+    status("Installed", &format!("{label} ({})", tool.coordinate));
+    if !on_path(&bin_dir) {
+        note(&format!(
+            "{} is not on PATH; add it to run `{name}` directly",
+            bin_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Move the installed tool `name`, or every installed tool, to its latest
+/// release.
+pub async fn upgrade(name: Option<&str>) -> miette::Result<()> {
+    use kargo_util::progress::status;
+
+    let tools_path = InstalledTools::path();
+    let mut tools = InstalledTools::load(&tools_path)?;
+    let names: Vec<String> = match name {
+        Some(name) => {
+            installed(&tools, name)?;
+            vec![name.to_string()]
+        }
+        None => tools.tools.keys().cloned().collect(),
+    };
+    let env = ToolEnv::user(&load_config());
+    for name in names {
+        let tool = tools.tools[&name].clone();
+        let mut spec = tool.spec()?;
+        let latest = latest_release(&env, &spec).await?;
+        if kargo_resolver::version::MavenVersion::parse(&latest)
+            <= kargo_resolver::version::MavenVersion::parse(&tool.version)
+        {
+            status(
+                "Unchanged",
+                &format!("{name} {} is the latest", tool.version),
+            );
+            continue;
+        }
+        spec.version = Some(latest.clone());
+        tool_record(&env, &spec, &latest, false).await?;
+        status("Upgraded", &format!("{name} {} -> {latest}", tool.version));
+        if let Some(entry) = tools.tools.get_mut(&name) {
+            entry.version = latest;
+        }
+        tools.save(&tools_path)?;
+    }
+    Ok(())
+}
+
+/// Remove the installed tool `name` and its shim.
+pub fn uninstall(name: &str) -> miette::Result<()> {
+    let tools_path = InstalledTools::path();
+    let mut tools = InstalledTools::load(&tools_path)?;
+    let tool = installed(&tools, name)?.clone();
+    tools.tools.remove(name);
+    tools.save(&tools_path)?;
+    remove_shim(&kargo_util::dirs_path().join("bin"), name)?;
+    kargo_util::progress::status(
+        "Removed",
+        &format!("{name} {} ({})", tool.version, tool.coordinate),
+    );
+    Ok(())
+}
+
+/// The installed tool `name`.
+fn installed<'a>(tools: &'a InstalledTools, name: &str) -> miette::Result<&'a InstalledTool> {
+    tools.tools.get(name).ok_or_else(|| {
+        KargoError::Generic {
+            message: format!(
+                "no tool named '{name}' is installed; `kargo tool list` shows the installed tools"
+            ),
+        }
+        .into()
+    })
+}
+
+/// The default shim name and the coordinate of `tool`, a name from
+/// [`KNOWN_TOOLS`] or a coordinate.
+fn install_spec(tool: &str) -> miette::Result<(String, ToolSpec)> {
+    if let Some((name, coordinate)) = KNOWN_TOOLS.iter().find(|(name, _)| *name == tool) {
+        return Ok((name.to_string(), ToolSpec::parse(coordinate)?));
+    }
+    if !tool.contains(':') {
+        return Err(KargoError::Generic {
+            message: format!(
+                "unknown tool '{tool}': pass its coordinate, group:artifact[:version], or one \
+                 of {}",
+                KNOWN_TOOLS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+        .into());
+    }
+    let spec = ToolSpec::parse(tool)?;
+    let name = spec
+        .artifact
+        .strip_suffix("-cli")
+        .unwrap_or(&spec.artifact)
+        .to_string();
+    Ok((name, spec))
+}
+
+/// Shim names become file names and `kargo tool run` arguments. They share
+/// `~/.kargo/bin` with the `kargo` launcher and `kargo-<name>` plugins, so
+/// those names are taken.
+fn check_tool_name(name: &str) -> miette::Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with(['.', '-'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(KargoError::Generic {
+            message: format!("invalid tool name '{name}': use letters, digits, '-', '_' and '.'"),
+        }
+        .into());
+    }
+    let lower = name.to_ascii_lowercase();
+    let stem = lower.split('.').next().unwrap_or_default();
+    if matches!(stem, "kargo" | "kargow") || lower.starts_with("kargo-") {
+        return Err(KargoError::Generic {
+            message: format!(
+                "tool name '{name}' is reserved for Kargo and its plugins; pick another with \
+                 --name"
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// First line after the shebang of every shim, marking it as ours.
+const SHIM_MARKER: &str = "Installed by `kargo tool install`.";
+
+/// Whether the file at `path` is a shim written by [`write_shim`].
+fn is_shim(path: &Path) -> bool {
+    std::fs::read(path).is_ok_and(|bytes| String::from_utf8_lossy(&bytes).contains(SHIM_MARKER))
+}
+
+/// Write the launcher of the installed tool `name` to `bin_dir`.
+fn write_shim(bin_dir: &Path, name: &str) -> miette::Result<PathBuf> {
+    std::fs::create_dir_all(bin_dir).map_err(KargoError::Io)?;
+    let kargo = std::env::current_exe().map_err(KargoError::Io)?;
+    let kargo = kargo.to_string_lossy();
+    let path = shim_path(bin_dir, name);
+    if path.exists() && !is_shim(&path) {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{} exists and was not installed by `kargo tool install`; remove it or pick \
+                 another name with --name",
+                path.display()
+            ),
+        }
+        .into());
+    }
+    let script = if cfg!(windows) {
+        format!(
+            "@echo off\r\nrem {SHIM_MARKER}\r\n\
+             \"{kargo}\" tool run {name} -- %*\r\n"
+        )
+    } else {
+        format!(
+            "#!/bin/sh\n# {SHIM_MARKER}\n\
+             exec '{}' tool run {name} -- \"$@\"\n",
+            kargo.replace('\'', "'\\''")
+        )
+    };
+    std::fs::write(&path, script).map_err(KargoError::Io)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .map_err(KargoError::Io)?;
+    }
+    Ok(path)
+}
+
+fn remove_shim(bin_dir: &Path, name: &str) -> miette::Result<()> {
+    let path = shim_path(bin_dir, name);
+    if path.exists() && !is_shim(&path) {
+        kargo_util::progress::status_warn(
+            "Kept",
+            &format!("{}; it was not installed by Kargo", path.display()),
+        );
+        return Ok(());
+    }
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(KargoError::Io(e).into()),
+        _ => Ok(()),
+    }
+}
+
+fn shim_path(bin_dir: &Path, name: &str) -> PathBuf {
+    if cfg!(windows) {
+        bin_dir.join(format!("{name}.cmd"))
+    } else {
+        bin_dir.join(name)
+    }
+}
+
+fn on_path(dir: &Path) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| std::env::split_paths(&path).any(|p| p == dir))
+}
+
+fn load_config() -> GlobalConfig {
+    GlobalConfig::load().unwrap_or_else(|e| {
+        tracing::warn!("Failed to load global config, using defaults: {e}");
+        GlobalConfig::default()
+    })
+}
+
+/// The newest release of the tool `spec` in the repositories of `env`.
+async fn latest_release(env: &ToolEnv, spec: &ToolSpec) -> miette::Result<String> {
+    let client = kargo_maven::download::build_client()?;
+    crate::ops_outdated::fetch_metadata(&client, &env.repos, &spec.group, &spec.artifact)
+        .await?
+        .and_then(|meta| meta.release.or(meta.latest))
+        .ok_or_else(|| {
+            KargoError::Resolution {
+                message: format!(
+                    "{}:{} was not found in {}",
+                    spec.group,
+                    spec.artifact,
+                    env.repos
+                        .iter()
+                        .map(|r| r.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+            .into()
+        })
+}
+
+/// The `Main-Class` in the manifest of `jar`, if it names one.
+fn main_class_of(jar: &Path) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(jar).ok()?).ok()?;
+    let mut text = String::new();
+    archive
+        .by_name("META-INF/MANIFEST.MF")
+        .ok()?
+        .read_to_string(&mut text)
+        .ok()?;
+    kargo_compiler::jar::JarManifest::parse(&text)
+        .get("Main-Class")
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn tool_coordinates_take_an_optional_version() {
+        assert_eq!(
+            ToolSpec::parse("com.pinterest.ktlint:ktlint-cli").unwrap(),
+            ToolSpec {
+                group: "com.pinterest.ktlint".into(),
+                artifact: "ktlint-cli".into(),
+                version: None,
+            }
+        );
+        assert_eq!(
+            ToolSpec::parse("com.pinterest.ktlint:ktlint-cli:1.5.0")
+                .unwrap()
+                .version
+                .as_deref(),
+            Some("1.5.0")
+        );
+        for bad in ["ktlint", ":ktlint", "a:b:c:d"] {
+            assert!(ToolSpec::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn install_takes_known_names_and_coordinates() {
+        let (name, spec) = install_spec("detekt").unwrap();
+        assert_eq!(name, "detekt");
+        assert_eq!(spec.artifact, "detekt-cli");
+
+        let (name, spec) = install_spec("com.pinterest.ktlint:ktlint-cli:1.5.0").unwrap();
+        assert_eq!(name, "ktlint");
+        assert_eq!(spec.version.as_deref(), Some("1.5.0"));
+
+        let err = install_spec("detekd").unwrap_err().to_string();
+        assert!(err.contains("detekt, ktlint"), "{err}");
+        for bad in [
+            "",
+            "-x",
+            "../x",
+            "a b",
+            "kargo",
+            "Kargo.exe",
+            "kargow",
+            "kargo-fmt",
+        ] {
+            assert!(check_tool_name(bad).is_err(), "{bad}");
+        }
+        assert!(check_tool_name("kargolint").is_ok());
+        assert!(check_tool_name("ktlint-1.5").is_ok());
+    }
+
+    #[test]
+    fn installed_tools_round_trip_with_their_shims() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("tools.toml");
+        assert_eq!(
+            InstalledTools::load(&path).unwrap(),
+            InstalledTools::default()
+        );
+
+        let mut tools = InstalledTools::default();
+        tools.tools.insert(
+            "detekt".into(),
+            InstalledTool {
+                coordinate: "io.gitlab.arturbosch.detekt:detekt-cli".into(),
+                version: "1.23.7".into(),
+                main_class: None,
+            },
+        );
+        tools.save(&path).unwrap();
+        let loaded = InstalledTools::load(&path).unwrap();
+        assert_eq!(loaded, tools);
+        assert_eq!(
+            loaded.tools["detekt"].spec().unwrap().version.as_deref(),
+            Some("1.23.7")
+        );
+
+        let bin = tmp.path().join("bin");
+        let shim = write_shim(&bin, "detekt").unwrap();
+        let script = std::fs::read_to_string(&shim).unwrap();
+        assert!(script.contains("tool run detekt --"), "{script}");
+        remove_shim(&bin, "detekt").unwrap();
+        assert!(!shim.exists());
+        remove_shim(&bin, "detekt").unwrap();
+    }
+
+    #[test]
+    fn shims_never_replace_files_kargo_did_not_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bin = tmp.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let foreign = shim_path(&bin, "ktlint");
+        std::fs::write(&foreign, "#!/bin/sh\necho mine\n").unwrap();
+
+        let err = write_shim(&bin, "ktlint").unwrap_err().to_string();
+        assert!(
+            err.contains("not installed by `kargo tool install`"),
+            "{err}"
+        );
+        remove_shim(&bin, "ktlint").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&foreign).unwrap(),
+            "#!/bin/sh\necho mine\n"
+        );
+
+        write_shim(&bin, "detekt").unwrap();
+        write_shim(&bin, "detekt").unwrap();
+    }
+
+    #[test]
+    fn records_with_missing_jars_are_resolved_again() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("ktlint-cli-1.5.0.jar");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&jar).unwrap());
+        zip.start_file(
+            "META-INF/MANIFEST.MF",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(b"Manifest-Version: 1.0\r\nMain-Class: com.pinterest.ktlint.Main\r\n\r\n")
+            .unwrap();
+        zip.finish().unwrap();
+        assert_eq!(
+            main_class_of(&jar).as_deref(),
+            Some("com.pinterest.ktlint.Main")
+        );
+
+        let record = ToolRecord {
+            main_class: main_class_of(&jar),
+            classpath: vec![jar.clone()],
+        };
+        let path = tmp.path().join("tools").join("1.5.0.toml");
+        record.save(&path).unwrap();
+        assert_eq!(ToolRecord::load(&path), Some(record));
+
+        std::fs::remove_file(&jar).unwrap();
+        assert_eq!(ToolRecord::load(&path), None);
+    }
+}
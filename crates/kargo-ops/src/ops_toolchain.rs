@@ -1,19 +1,21 @@
 //! Toolchain management operations: install, list, remove, use, path.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use miette::Result;
 
 use kargo_core::config::GlobalConfig;
-use kargo_core::manifest::Manifest;
+use kargo_core::lockfile::{LockedToolchain, Lockfile};
+use kargo_core::manifest::{KotlinVersionReq, Manifest};
 use kargo_toolchain::install;
 use kargo_toolchain::sdk;
 use kargo_toolchain::version::KotlinVersion;
 
-fn try_load_project_manifest() -> Option<Manifest> {
+fn try_load_project_manifest() -> Option<(PathBuf, Manifest)> {
     let cwd = std::env::current_dir().ok()?;
     let manifest_dir = kargo_util::fs::find_ancestor_with(&cwd, "Kargo.toml")?;
-    Manifest::from_path(&manifest_dir.join("Kargo.toml")).ok()
+    let manifest = Manifest::from_path(&manifest_dir.join("Kargo.toml")).ok()?;
+    Some((manifest_dir, manifest))
 }
 
 pub async fn cmd_install(
@@ -21,13 +23,15 @@ pub async fn cmd_install(
     jdk_version: Option<&str>,
     android_version: Option<&str>,
 ) -> Result<()> {
-    let manifest = try_load_project_manifest();
+    let project = try_load_project_manifest();
+    let manifest = project.as_ref().map(|(_, m)| m);
     let wants_jdk = jdk_version.is_some();
     let wants_android = android_version.is_some();
 
-    let kotlin_version: Option<String> = match version_str {
-        Some(v) => Some(v.to_string()),
-        None => manifest.as_ref().map(|m| m.package.kotlin.clone()),
+    let kotlin_version: Option<String> = match (version_str, &project) {
+        (Some(v), _) => Some(v.to_string()),
+        (None, Some((dir, m))) => Some(resolve_project_kotlin(dir, m).await?.to_string()),
+        (None, None) => None,
     };
 
     if kotlin_version.is_none() && !wants_jdk && !wants_android {
@@ -78,9 +82,8 @@ pub async fn cmd_install(
 
     if wants_jdk {
         let explicit_jdk = jdk_version.unwrap_or("21");
-        let manifest_java_target = manifest
-            .as_ref()
-            .and_then(|m| m.targets.values().find_map(|tc| tc.java_target.clone()));
+        let manifest_java_target =
+            manifest.and_then(|m| m.targets.values().find_map(|tc| tc.java_target.clone()));
 
         let (java_ver, from) = if explicit_jdk != "21" {
             (explicit_jdk.to_string(), "argument")
@@ -111,7 +114,6 @@ pub async fn cmd_install(
         let explicit_android = android_version.unwrap_or("35");
 
         let manifest_compile_sdk = manifest
-            .as_ref()
             .and_then(|m| m.targets.get("android"))
             .and_then(|tc| tc.compile_sdk);
 
@@ -197,6 +199,137 @@ pub fn cmd_list() -> Result<()> {
     Ok(())
 }
 
+/// A published Kotlin version, as `kargo toolchain list --remote` shows it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RemoteVersion {
+    pub version: String,
+    /// Not a beta, release candidate or other pre-release.
+    pub stable: bool,
+    pub installed: bool,
+    /// The default set with `kargo toolchain use`.
+    pub default: bool,
+    /// The version the current project builds with.
+    pub pinned: bool,
+}
+
+/// List the Kotlin versions published to the project's repositories (Maven
+/// Central outside a project), newest first.
+pub async fn cmd_list_remote(json: bool) -> Result<()> {
+    let project = try_load_project_manifest();
+    let repos = match &project {
+        Some((_, manifest)) => kargo_resolver::resolver::build_repos(manifest),
+        None => vec![kargo_maven::repository::MavenRepository::maven_central()],
+    };
+
+    let sp = kargo_util::progress::spinner("Fetching Kotlin releases...");
+    let published = published_kotlin_versions(&repos).await;
+    sp.finish_and_clear();
+    let published = published?;
+
+    let pinned = project
+        .as_ref()
+        .and_then(|(dir, manifest)| project_kotlin_pin(dir, manifest, &published));
+    let installed = install::list_installed();
+    let default = install::get_default();
+    let versions = remote_versions(&published, &installed, default.as_ref(), pinned.as_deref());
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "versions": versions }))
+                .unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    if versions.is_empty() {
+        println!("No published Kotlin versions found.");
+        return Ok(());
+    }
+    println!("{:<20} {:<12} Status", "Version", "Channel");
+    println!("{}", "-".repeat(50));
+    for v in &versions {
+        let mut status = Vec::new();
+        if v.installed {
+            status.push("installed");
+        }
+        if v.default {
+            status.push("default");
+        }
+        if v.pinned {
+            status.push("pinned");
+        }
+        let channel = if v.stable { "stable" } else { "pre-release" };
+        println!("{:<20} {:<12} {}", v.version, channel, status.join(", "));
+    }
+    Ok(())
+}
+
+/// Every Kotlin version published to `repos`, from the `maven-metadata.xml`
+/// of `org.jetbrains.kotlin:kotlin-stdlib`.
+async fn published_kotlin_versions(
+    repos: &[kargo_maven::repository::MavenRepository],
+) -> Result<Vec<String>> {
+    let client = kargo_maven::download::build_client()?;
+    let http_cache = kargo_util::http_cache::HttpCache::user();
+    let mut versions = Vec::new();
+    for repo in kargo_maven::repository::for_group(repos, "org.jetbrains.kotlin") {
+        let url = repo.metadata_url("org.jetbrains.kotlin", "kotlin-stdlib");
+        let Some(xml) =
+            kargo_maven::download::download_text_cached(&client, repo, &url, &http_cache).await?
+        else {
+            continue;
+        };
+        let meta = kargo_maven::metadata::parse_metadata(&xml)?;
+        versions.extend(meta.versions);
+    }
+    Ok(versions)
+}
+
+/// The Kotlin version the project builds with, without pinning anything: an
+/// exact `package.kotlin`, the alias's pin in `Kargo.lock`, or else the
+/// version the alias would pin among `published`.
+fn project_kotlin_pin(
+    project_dir: &Path,
+    manifest: &Manifest,
+    published: &[String],
+) -> Option<String> {
+    let requested = &manifest.package.kotlin;
+    match manifest.package.kotlin_req()? {
+        KotlinVersionReq::Exact(v) => Some(v),
+        alias => Lockfile::for_project(project_dir)
+            .ok()
+            .and_then(|lf| lf.pinned_kotlin(requested).map(str::to_string))
+            .or_else(|| alias.select(published.iter().map(String::as_str))),
+    }
+}
+
+/// Mark each of the `published` versions, deduplicated and sorted newest
+/// first. Versions that are not semver (very old releases) are left out.
+fn remote_versions(
+    published: &[String],
+    installed: &[KotlinVersion],
+    default: Option<&KotlinVersion>,
+    pinned: Option<&str>,
+) -> Vec<RemoteVersion> {
+    let mut parsed: Vec<(KotlinVersion, &str)> = published
+        .iter()
+        .filter_map(|v| v.parse().ok().map(|parsed| (parsed, v.as_str())))
+        .collect();
+    parsed.sort_by(|a, b| b.0.cmp(&a.0));
+    parsed.dedup_by(|a, b| a.0 == b.0);
+    parsed
+        .into_iter()
+        .map(|(parsed, version)| RemoteVersion {
+            version: version.to_string(),
+            stable: parsed.is_stable(),
+            installed: installed.contains(&parsed),
+            default: default == Some(&parsed),
+            pinned: pinned == Some(version),
+        })
+        .collect()
+}
+
 pub fn cmd_remove(
     version_str: Option<&str>,
     jdk_version: Option<&str>,
@@ -275,17 +408,21 @@ pub async fn cmd_path() -> Result<PathBuf> {
     let config = GlobalConfig::load()?;
     let mirror = config.toolchain.kotlin_mirror.as_deref();
 
-    let cwd = std::env::current_dir().map_err(kargo_util::errors::KargoError::Io)?;
-
-    let paths = match kargo_toolchain::discovery::resolve_project_toolchain(
-        &cwd,
-        config.toolchain.auto_download,
-        mirror,
-    )
-    .await
-    {
-        Ok(p) => p,
-        Err(_) => {
+    let project_version = match try_load_project_manifest() {
+        Some((dir, manifest)) => resolve_project_kotlin(&dir, &manifest).await.ok(),
+        None => None,
+    };
+
+    let paths = match project_version {
+        Some(version) => {
+            kargo_toolchain::discovery::resolve_toolchain(
+                &version,
+                config.toolchain.auto_download,
+                mirror,
+            )
+            .await?
+        }
+        None => {
             let default = install::get_default().ok_or_else(|| {
                 kargo_util::errors::KargoError::Toolchain {
                     message: "No Kargo project found and no default toolchain set".to_string(),
@@ -302,3 +439,146 @@ pub async fn cmd_path() -> Result<PathBuf> {
 
     Ok(paths.home)
 }
+
+// ---------------------------------------------------------------------------
+// Kotlin version aliases
+// ---------------------------------------------------------------------------
+
+/// Resolve the concrete Kotlin compiler version for a project.
+///
+/// Exact versions in `package.kotlin` are used as-is. Aliases (`2.3`,
+/// `latest-stable`, `latest`) use the version pinned in `Kargo.lock`; if no
+/// pin exists for the current requirement, release metadata is queried once
+/// and the result is pinned so later builds are reproducible.
+pub async fn resolve_project_kotlin(
+    project_dir: &Path,
+    manifest: &Manifest,
+) -> Result<KotlinVersion> {
+    let requested = &manifest.package.kotlin;
+    let req =
+        manifest
+            .package
+            .kotlin_req()
+            .ok_or_else(|| kargo_util::errors::KargoError::Toolchain {
+                message: format!("Invalid kotlin version '{requested}'"),
+            })?;
+
+    let concrete = match req {
+        KotlinVersionReq::Exact(v) => v,
+        ref alias => {
+            let lockfile = Lockfile::for_project(project_dir).ok();
+            match lockfile.as_ref().and_then(|lf| lf.pinned_kotlin(requested)) {
+                Some(pinned) => pinned.to_string(),
+                None => {
+                    let resolved = query_kotlin_release(manifest, alias).await?;
+                    pin_kotlin_version(project_dir, requested, &resolved)?;
+                    kargo_util::progress::status(
+                        "Pinned",
+                        &format!("kotlin {requested} -> {resolved} in Kargo.lock"),
+                    );
+                    resolved
+                }
+            }
+        }
+    };
+
+    concrete.parse().map_err(|e| {
+        kargo_util::errors::KargoError::Toolchain {
+            message: format!("Invalid kotlin version '{concrete}': {e}"),
+        }
+        .into()
+    })
+}
+
+/// Find the newest published Kotlin release satisfying `req`.
+///
+/// Reads `maven-metadata.xml` for `org.jetbrains.kotlin:kotlin-stdlib` from
+/// the project's repositories; every compiler release publishes a matching
+/// stdlib.
+pub async fn query_kotlin_release(manifest: &Manifest, req: &KotlinVersionReq) -> Result<String> {
+    let repos = kargo_resolver::resolver::build_repos(manifest);
+    let client = kargo_maven::download::build_client()?;
+
+    let http_cache = kargo_util::http_cache::HttpCache::user();
+    for repo in kargo_maven::repository::for_group(&repos, "org.jetbrains.kotlin") {
+        let url = repo.metadata_url("org.jetbrains.kotlin", "kotlin-stdlib");
+        let Some(xml) =
+            kargo_maven::download::download_text_cached(&client, repo, &url, &http_cache).await?
+        else {
+            continue;
+        };
+        let meta = kargo_maven::metadata::parse_metadata(&xml)?;
+        if let Some(v) = req.select(meta.versions.iter().map(String::as_str)) {
+            return Ok(v);
+        }
+    }
+
+    Err(kargo_util::errors::KargoError::Toolchain {
+        message: format!("No published Kotlin release matches '{req}'"),
+    }
+    .into())
+}
+
+/// Record `resolved` as the Kotlin version for `requested` in `Kargo.lock`,
+/// creating the lockfile if it does not exist yet.
+pub fn pin_kotlin_version(project_dir: &Path, requested: &str, resolved: &str) -> Result<()> {
+    let mut lockfile = Lockfile::for_project(project_dir).unwrap_or_default();
+    lockfile.toolchain = Some(LockedToolchain {
+        requested: requested.to_string(),
+        kotlin: resolved.to_string(),
+    });
+    lockfile.write_for_project(project_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn published(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn remote_versions_are_newest_first_and_marked() {
+        let published = published(&["2.2.21", "2.3.0", "2.3.20-RC", "2.2.21", "1.0.0-beta-1038"]);
+        let installed = vec![KotlinVersion::new(2, 2, 21), KotlinVersion::new(2, 3, 0)];
+        let default = KotlinVersion::new(2, 2, 21);
+
+        let versions = remote_versions(&published, &installed, Some(&default), Some("2.3.0"));
+        let names: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(names, ["2.3.20-RC", "2.3.0", "2.2.21", "1.0.0-beta-1038"]);
+        assert!(!versions[0].stable && !versions[0].installed);
+        assert!(versions[1].stable && versions[1].installed && versions[1].pinned);
+        assert!(!versions[1].default);
+        assert!(versions[2].installed && versions[2].default && !versions[2].pinned);
+    }
+
+    #[test]
+    fn project_pin_prefers_the_lockfile_over_metadata() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = |kotlin: &str| {
+            Manifest::parse_toml(&format!(
+                "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nkotlin = \"{kotlin}\"\n"
+            ))
+            .unwrap()
+        };
+        let published = published(&["2.3.0", "2.3.10", "2.4.0-Beta1"]);
+
+        let exact = manifest("2.2.21");
+        assert_eq!(
+            project_kotlin_pin(tmp.path(), &exact, &published).as_deref(),
+            Some("2.2.21")
+        );
+
+        let alias = manifest("2.3");
+        assert_eq!(
+            project_kotlin_pin(tmp.path(), &alias, &published).as_deref(),
+            Some("2.3.10")
+        );
+        pin_kotlin_version(tmp.path(), "2.3", "2.3.0").unwrap();
+        assert_eq!(
+            project_kotlin_pin(tmp.path(), &alias, &published).as_deref(),
+            Some("2.3.0")
+        );
+    }
+}
@@ -1,12 +1,14 @@
 //! Operation: display the dependency tree.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use kargo_core::lockfile::Lockfile;
 use kargo_core::manifest::Manifest;
 use kargo_maven::cache::LocalCache;
 use kargo_maven::download;
-use kargo_resolver::resolver;
+use kargo_maven::license;
+use kargo_resolver::resolver::{self, OptionalDependency};
 
 /// Options for `kargo tree`.
 #[derive(Default)]
@@ -19,10 +21,13 @@ pub struct TreeOptions {
     pub duplicates: bool,
     /// Show version conflicts.
     pub conflicts: bool,
-    /// Show licenses from POM metadata.
+    /// Show licenses from POM metadata, following parent POMs and
+    /// normalized to SPDX identifiers.
     pub licenses: bool,
     /// Show inverted tree (dependents instead of dependencies).
     pub inverted: bool,
+    /// Show optional dependencies declared by resolved POMs.
+    pub optional: bool,
 }
 
 /// Display the dependency tree for the project.
@@ -32,9 +37,8 @@ pub async fn tree(project_root: &Path, opts: &TreeOptions) -> miette::Result<()>
     let repos = resolver::build_repos(&manifest);
     let cache = LocalCache::new(project_root);
 
-    let lockfile_path = project_root.join("Kargo.lock");
-    let existing_lock = if lockfile_path.is_file() {
-        Lockfile::from_path(&lockfile_path).ok()
+    let existing_lock = if kargo_core::workspace::lockfile_path(project_root).is_file() {
+        Lockfile::for_project(project_root).ok()
     } else {
         None
     };
@@ -66,21 +70,80 @@ pub async fn tree(project_root: &Path, opts: &TreeOptions) -> miette::Result<()>
         } else {
             print!("{}", result.conflicts);
         }
+        let cycles = result.graph.cycles();
+        if !cycles.is_empty() {
+            println!(
+                "Dependency cycles ({}), broken at their last edge:",
+                cycles.len()
+            );
+            for cycle in cycles {
+                println!("  {cycle}");
+            }
+        }
         return Ok(());
     }
 
     // Handle --licenses
     if opts.licenses {
+        let sp = kargo_util::progress::spinner("Resolving licenses...");
+        let mut lines = Vec::new();
         for artifact in &result.artifacts {
-            let pom = cache.get_pom(&artifact.group, &artifact.artifact, &artifact.version);
-            let license = pom
-                .and_then(|p| p.licenses.first().and_then(|l| l.name.clone()))
-                .unwrap_or_else(|| "Unknown".to_string());
-            println!(
+            let resolved = license::resolve(
+                &client,
+                &repos,
+                &cache,
+                &artifact.group,
+                &artifact.artifact,
+                &artifact.version,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("{e}");
+                None
+            })
+            .unwrap_or_default();
+            let license = match (resolved.expression(), &resolved.inherited_from) {
+                (Some(expr), Some(parent)) => format!("{expr} (from {parent})"),
+                (Some(expr), None) => expr,
+                (None, _) => "Unknown".to_string(),
+            };
+            lines.push(format!(
                 "{}:{}:{} — {}",
                 artifact.group, artifact.artifact, artifact.version, license
-            );
+            ));
+        }
+        sp.finish_and_clear();
+        for line in lines {
+            println!("{line}");
+        }
+        return Ok(());
+    }
+
+    // Handle --optional
+    if opts.optional {
+        if result.optional.is_empty() {
+            println!("No optional dependencies.");
+            return Ok(());
+        }
+        let mut by_pom: BTreeMap<&str, Vec<&OptionalDependency>> = BTreeMap::new();
+        for dep in &result.optional {
+            by_pom.entry(&dep.declared_by).or_default().push(dep);
+        }
+        for (pom, deps) in by_pom {
+            println!("{pom}");
+            for dep in deps {
+                let state = if dep.included { "included" } else { "optional" };
+                println!(
+                    "  {}:{}:{} ({state})",
+                    dep.group,
+                    dep.artifact,
+                    dep.version.as_deref().unwrap_or("?")
+                );
+            }
         }
+        println!(
+            "\nOpt in with `include-optional = [\"group:artifact\"]` on the declared dependency."
+        );
         return Ok(());
     }
 
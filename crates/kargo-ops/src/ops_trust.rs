@@ -0,0 +1,142 @@
+//! Operation: manage `kargo.trust`, the pinned digests of downloaded tools.
+//!
+//! `kargo trust init` creates the file, after which every tool download is
+//! pinned on first use and must match its pin later (see
+//! [`kargo_util::trust`]). `kargo trust update` downloads each pinned
+//! archive again and, after confirmation, replaces pins whose digest
+//! changed.
+
+use std::path::{Path, PathBuf};
+
+use kargo_core::workspace;
+use kargo_util::errors::KargoError;
+use kargo_util::progress::{status, status_info, status_warn};
+use kargo_util::trust::{TrustFile, TRUST_FILE};
+
+/// Create an empty `kargo.trust` at the workspace root, or next to the
+/// `Kargo.toml` in `cwd`.
+pub fn init(cwd: &Path) -> miette::Result<PathBuf> {
+    let root = match workspace::find_root(cwd) {
+        Some((root, _)) => root,
+        None if cwd.join("Kargo.toml").is_file() => cwd.to_path_buf(),
+        None => {
+            return Err(KargoError::Manifest {
+                message: "No Kargo.toml found in current directory".to_string(),
+            }
+            .into())
+        }
+    };
+    let path = root.join(TRUST_FILE);
+    if path.exists() {
+        return Err(KargoError::Generic {
+            message: format!("{} already exists", path.display()),
+        }
+        .into());
+    }
+    TrustFile {
+        path: path.clone(),
+        ..Default::default()
+    }
+    .save()?;
+    status("Created", &path.display().to_string());
+    Ok(path)
+}
+
+/// Re-download the pinned archives (only those of `tool`, if given) and
+/// replace the pins that changed, asking first unless `yes`.
+pub async fn update(cwd: &Path, tool: Option<&str>, yes: bool) -> miette::Result<()> {
+    let path = TrustFile::find(cwd).ok_or_else(|| KargoError::Generic {
+        message: format!("No {TRUST_FILE} found; create one with `kargo trust init`"),
+    })?;
+    let mut trust = TrustFile::load(&path)?;
+    let interactive = atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr);
+
+    let pins: Vec<(String, String, String, String)> = trust
+        .tools
+        .iter()
+        .filter(|(name, _)| tool.is_none() || tool == Some(name.as_str()))
+        .flat_map(|(name, versions)| {
+            versions.iter().map(move |(version, pin)| {
+                (
+                    name.clone(),
+                    version.clone(),
+                    pin.url.clone(),
+                    pin.sha256.clone(),
+                )
+            })
+        })
+        .collect();
+    if pins.is_empty() {
+        status_info("Trust", "nothing is pinned yet");
+        return Ok(());
+    }
+
+    let tmp = tempfile::tempdir().map_err(KargoError::Io)?;
+    let mut changed = 0;
+    for (name, version, url, pinned) in pins {
+        let file = tmp.path().join("download");
+        kargo_toolchain::download::download_file(&url, &file).await?;
+        let sha256 = kargo_util::hash::sha256_file(&file)?;
+        if sha256.eq_ignore_ascii_case(&pinned) {
+            status("Fresh", &format!("{name} {version}"));
+            continue;
+        }
+        eprintln!("  {name} {version} ({url})\n    pinned:  {pinned}\n    current: {sha256}");
+        let accept = if yes {
+            true
+        } else if interactive {
+            dialoguer::Confirm::new()
+                .with_prompt(format!("Trust the new {name} {version}?"))
+                .default(false)
+                .interact()
+                .map_err(|e| KargoError::Generic {
+                    message: format!("Prompt error: {e}"),
+                })?
+        } else {
+            status_warn(
+                "Kept",
+                &format!("{name} {version}; pass --yes to accept changes without a terminal"),
+            );
+            false
+        };
+        if accept {
+            let pin = trust.tools.get_mut(&name).and_then(|v| v.get_mut(&version));
+            if let Some(pin) = pin {
+                pin.sha256 = sha256;
+            }
+            changed += 1;
+            status("Updated", &format!("{name} {version}"));
+        }
+    }
+    if changed > 0 {
+        trust.save()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_creates_the_file_at_the_workspace_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(
+            root.join("app/Kargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n",
+        )
+        .unwrap();
+
+        let path = init(&root.join("app")).unwrap();
+        assert_eq!(path, root.join(TRUST_FILE));
+        assert!(TrustFile::load(&path).unwrap().tools.is_empty());
+        assert!(init(root).is_err());
+    }
+}
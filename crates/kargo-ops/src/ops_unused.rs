@@ -0,0 +1,458 @@
+//! Operation: `kargo unused` — find leftover build outputs and resources
+//! nothing refers to.
+//!
+//! Three kinds of findings are reported for the selected target and
+//! profile:
+//!
+//! - **Stale packaged resources**: files in the build's `resources/`
+//!   directory (which is packaged into the JAR) whose source resource was
+//!   deleted or renamed. Builds copy resources but never remove them, so
+//!   these keep shipping until cleaned.
+//! - **Stale generated outputs**: directories under `generated/` left by a
+//!   generator, SQLDelight database, protobuf sources or annotation
+//!   processor that the project no longer uses.
+//! - **Unreferenced resources**: source resources whose path or file name
+//!   appears in no source file, other resource or `Kargo.toml`. This is a
+//!   heuristic (resources can be looked up by computed names), so they are
+//!   listed for review and never deleted.
+//!
+//! With `--delete`, the stale packaged resources and generated outputs are
+//! removed.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::plugins::{self, ProcessorKind};
+use kargo_core::manifest::Manifest;
+use kargo_util::errors::KargoError;
+
+/// Resources loaded by convention rather than by name in code.
+const CONVENTIONAL_RESOURCES: &[&str] = &[
+    "application.conf",
+    "application.properties",
+    "application.yaml",
+    "application.yml",
+    "reference.conf",
+    "logback.xml",
+    "logback-test.xml",
+    "log4j2.xml",
+    "log4j2.properties",
+    "simplelogger.properties",
+];
+
+/// Text files larger than this are not searched for resource references.
+const MAX_SEARCHED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A packaged resource without a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleResource {
+    /// Path relative to the packaged resources directory, `/`-separated.
+    pub path: String,
+    /// Whether the current output JAR contains it.
+    pub in_jar: bool,
+}
+
+/// Findings of `kargo unused`.
+#[derive(Debug, Default)]
+pub struct UnusedReport {
+    pub stale_resources: Vec<StaleResource>,
+    pub stale_generated: Vec<PathBuf>,
+    pub unreferenced_resources: Vec<PathBuf>,
+}
+
+impl UnusedReport {
+    pub fn is_empty(&self) -> bool {
+        self.stale_resources.is_empty()
+            && self.stale_generated.is_empty()
+            && self.unreferenced_resources.is_empty()
+    }
+}
+
+/// Files below `dir`, as `/`-separated paths relative to it.
+fn relative_files(dir: &Path) -> Vec<String> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(root, &path, out);
+            } else if let Ok(rel) = path.strip_prefix(root) {
+                out.push(
+                    rel.components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                );
+            }
+        }
+    }
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files);
+    files.sort();
+    files
+}
+
+/// Packaged resources in `packaged_dir` that none of `source_dirs` provides.
+pub fn stale_resources(
+    source_dirs: &[PathBuf],
+    packaged_dir: &Path,
+    jar_entries: &HashSet<String>,
+) -> Vec<StaleResource> {
+    let sources: HashSet<String> = source_dirs.iter().flat_map(|d| relative_files(d)).collect();
+    relative_files(packaged_dir)
+        .into_iter()
+        .filter(|path| !sources.contains(path))
+        .map(|path| StaleResource {
+            in_jar: jar_entries.contains(&path),
+            path,
+        })
+        .collect()
+}
+
+/// Output directories under `generated_dir` that the current configuration
+/// no longer produces.
+pub fn stale_generated(
+    generated_dir: &Path,
+    manifest: &Manifest,
+    has_protos: bool,
+    processor_kinds: &[ProcessorKind],
+) -> Vec<PathBuf> {
+    let mut stale = Vec::new();
+    let children = |dir: &Path| -> Vec<(String, PathBuf)> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            .collect();
+        found.sort();
+        found
+    };
+
+    for (name, path) in children(&generated_dir.join("gen")) {
+        if !manifest.generate.contains_key(&name) {
+            stale.push(path);
+        }
+    }
+    let databases = manifest.sqldelight.as_ref().map(|s| &s.databases);
+    for (name, path) in children(&generated_dir.join("sqldelight")) {
+        if !databases.is_some_and(|dbs| dbs.contains_key(&name)) {
+            stale.push(path);
+        }
+    }
+    let unused_tools = [
+        ("proto", has_protos),
+        ("ksp", processor_kinds.contains(&ProcessorKind::Ksp)),
+        ("kapt", processor_kinds.contains(&ProcessorKind::Kapt)),
+    ];
+    for (dir, in_use) in unused_tools {
+        let path = generated_dir.join(dir);
+        if !in_use && path.is_dir() {
+            stale.push(path);
+        }
+    }
+    stale
+}
+
+/// Source resources (`(resource dir, file)`) whose relative path or file
+/// name occurs in none of `texts` other than their own.
+pub fn unreferenced_resources(
+    resources: &[(PathBuf, PathBuf)],
+    texts: &[(PathBuf, String)],
+) -> Vec<PathBuf> {
+    resources
+        .iter()
+        .filter(|(root, file)| {
+            let rel = file
+                .strip_prefix(root)
+                .unwrap_or(file)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let name = file
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            !rel.starts_with("META-INF/")
+                && !CONVENTIONAL_RESOURCES.contains(&name.as_str())
+                && !texts.iter().any(|(path, text)| {
+                    path != file && (text.contains(&rel) || text.contains(&name))
+                })
+        })
+        .map(|(_, file)| file.clone())
+        .collect()
+}
+
+/// Collect the text of every readable file below `dir`.
+fn collect_texts(dir: &Path, texts: &mut Vec<(PathBuf, String)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_texts(&path, texts);
+            continue;
+        }
+        if entry
+            .metadata()
+            .is_ok_and(|m| m.len() > MAX_SEARCHED_FILE_SIZE)
+        {
+            continue;
+        }
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            texts.push((path, text));
+        }
+    }
+}
+
+fn jar_entries(jar: &Path) -> HashSet<String> {
+    let Ok(file) = std::fs::File::open(jar) else {
+        return HashSet::new();
+    };
+    let Ok(archive) = zip::ZipArchive::new(file) else {
+        return HashSet::new();
+    };
+    archive.file_names().map(String::from).collect()
+}
+
+/// Analyze the build outputs of `ctx`.
+pub fn analyze(ctx: &crate::BuildContext) -> UnusedReport {
+    let source_sets = ctx
+        .discovered
+        .main_sources
+        .iter()
+        .chain(&ctx.discovered.fixtures_sources)
+        .chain(&ctx.discovered.test_sources)
+        .chain(&ctx.discovered.integration_test_sources);
+    let main_resource_dirs: Vec<PathBuf> = ctx
+        .discovered
+        .main_sources
+        .iter()
+        .flat_map(|ss| ss.resource_dirs.clone())
+        .collect();
+
+    let jar = ctx.build_dir.join("output").join(format!(
+        "{}-{}.jar",
+        ctx.manifest.package.name, ctx.manifest.package.version
+    ));
+    let stale_resources =
+        stale_resources(&main_resource_dirs, &ctx.resources_dir, &jar_entries(&jar));
+
+    let cache = kargo_maven::cache::LocalCache::new(&ctx.project_dir);
+    let kinds: Vec<ProcessorKind> = plugins::detect_processors(&ctx.manifest, &cache)
+        .iter()
+        .map(|p| p.kind)
+        .collect();
+    let has_protos = !crate::ops_protobuf::proto_files(&crate::ops_protobuf::proto_dirs(
+        &ctx.project_dir,
+        &ctx.manifest,
+    ))
+    .is_empty();
+    let stale_generated = stale_generated(&ctx.generated_dir, &ctx.manifest, has_protos, &kinds);
+
+    // Main resources are checked against every source set's code and
+    // resources, the generated sources and the manifest.
+    let resources: Vec<(PathBuf, PathBuf)> = main_resource_dirs
+        .iter()
+        .flat_map(|root| {
+            relative_files(root)
+                .into_iter()
+                .map(move |rel| (root.clone(), root.join(rel)))
+        })
+        .collect();
+    let mut texts = Vec::new();
+    let manifest_path = ctx.project_dir.join("Kargo.toml");
+    if let Ok(text) = std::fs::read_to_string(&manifest_path) {
+        texts.push((manifest_path, text));
+    }
+    for ss in source_sets {
+        for dir in ss.kotlin_dirs.iter().chain(&ss.resource_dirs) {
+            collect_texts(dir, &mut texts);
+        }
+    }
+    collect_texts(&ctx.generated_dir, &mut texts);
+
+    UnusedReport {
+        stale_resources,
+        stale_generated,
+        unreferenced_resources: unreferenced_resources(&resources, &texts),
+    }
+}
+
+/// Report (and with `delete`, remove) unused build outputs and resources.
+pub async fn unused(
+    project_dir: &Path,
+    target: Option<&str>,
+    profile: Option<&str>,
+    release: bool,
+    delete: bool,
+) -> miette::Result<()> {
+    let ctx = crate::BuildContext::load(project_dir, target, profile, release).await?;
+    let report = analyze(&ctx);
+    let shown = |p: &Path| {
+        p.strip_prefix(project_dir)
+            .unwrap_or(p)
+            .display()
+            .to_string()
+    };
+
+    if report.is_empty() {
+        kargo_util::progress::status("Unused", "nothing found");
+        return Ok(());
+    }
+
+    if !report.stale_resources.is_empty() {
+        println!(
+            "Stale packaged resources (no longer in the source resources, in {}):",
+            shown(&ctx.resources_dir)
+        );
+        for r in &report.stale_resources {
+            let jar = if r.in_jar { "  [in JAR]" } else { "" };
+            println!("  {}{jar}", r.path);
+        }
+        println!();
+    }
+    if !report.stale_generated.is_empty() {
+        println!("Stale generated outputs:");
+        for dir in &report.stale_generated {
+            println!("  {}", shown(dir));
+        }
+        println!();
+    }
+    if !report.unreferenced_resources.is_empty() {
+        println!("Resources not referenced by any source, resource or Kargo.toml (review):");
+        for file in &report.unreferenced_resources {
+            println!("  {}", shown(file));
+        }
+        println!();
+    }
+
+    let removable = report.stale_resources.len() + report.stale_generated.len();
+    if !delete {
+        if removable > 0 {
+            kargo_util::progress::status_info(
+                "Hint",
+                &format!("run `kargo unused --delete` to remove the {removable} stale outputs"),
+            );
+        }
+        return Ok(());
+    }
+
+    for r in &report.stale_resources {
+        std::fs::remove_file(ctx.resources_dir.join(&r.path)).map_err(KargoError::Io)?;
+    }
+    for dir in &report.stale_generated {
+        std::fs::remove_dir_all(dir).map_err(KargoError::Io)?;
+    }
+    kargo_util::progress::status(
+        "Removed",
+        &format!("{removable} stale outputs; rebuild to repackage the JAR"),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packaged_resources_without_a_source_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src/main/resources");
+        let packaged = dir.path().join("build/resources");
+        for (root, file) in [
+            (&src, "config/app.properties"),
+            (&packaged, "config/app.properties"),
+            (&packaged, "config/old.properties"),
+            (&packaged, "banner.txt"),
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let jar: HashSet<String> = ["banner.txt".to_string()].into();
+        assert_eq!(
+            stale_resources(&[src], &packaged, &jar),
+            [
+                StaleResource {
+                    path: "banner.txt".into(),
+                    in_jar: true
+                },
+                StaleResource {
+                    path: "config/old.properties".into(),
+                    in_jar: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn outputs_of_removed_generators_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let generated = dir.path();
+        for sub in [
+            "gen/schema",
+            "gen/removed",
+            "proto/java",
+            "ksp/kotlin",
+            "kapt/sources",
+        ] {
+            std::fs::create_dir_all(generated.join(sub)).unwrap();
+        }
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[generate.schema]
+command = ["gen"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stale_generated(generated, &manifest, false, &[ProcessorKind::Ksp]),
+            [
+                generated.join("gen/removed"),
+                generated.join("proto"),
+                generated.join("kapt")
+            ]
+        );
+    }
+
+    #[test]
+    fn resources_named_in_other_files_are_referenced() {
+        let root = PathBuf::from("/r");
+        let resources = [
+            (root.clone(), root.join("images/logo.png")),
+            (root.clone(), root.join("templates/mail.html")),
+            (root.clone(), root.join("orphan.json")),
+            (root.clone(), root.join("META-INF/services/x.Y")),
+            (root.clone(), root.join("logback.xml")),
+        ];
+        let texts = [
+            (
+                PathBuf::from("/src/Main.kt"),
+                "val logo = javaClass.getResource(\"/images/logo.png\")".to_string(),
+            ),
+            (
+                root.join("orphan.json"),
+                "{\"self\": \"orphan.json\"}".to_string(),
+            ),
+            (
+                root.join("index.html"),
+                "<link href=\"mail.html\">".to_string(),
+            ),
+        ];
+        assert_eq!(
+            unreferenced_resources(&resources, &texts),
+            [root.join("orphan.json")]
+        );
+    }
+}
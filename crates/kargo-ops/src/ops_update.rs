@@ -1,14 +1,19 @@
 //! Operation: update direct dependencies to their latest compatible versions.
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
-use kargo_core::manifest::Manifest;
+use kargo_core::dependency::{Dependency, MavenCoordinate};
+use kargo_core::lockfile::Lockfile;
+use kargo_core::manifest::{KotlinVersionReq, Manifest, UpdatePolicy};
+use kargo_maven::cache::LocalCache;
 use kargo_maven::download;
-use kargo_maven::metadata;
+use kargo_maven::pom::Pom;
+use kargo_maven::repository::MavenRepository;
 use kargo_resolver::resolver;
 use kargo_resolver::version::MavenVersion;
 use toml_edit::{DocumentMut, Item, Value};
@@ -22,6 +27,34 @@ pub struct UpdateOptions {
     pub dep: Option<String>,
     /// Dry-run: show what would be updated without changing files.
     pub dry_run: bool,
+    pub format: UpdateFormat,
+    /// Let the user pick which of the found updates to apply.
+    pub interactive: bool,
+}
+
+/// Output format of `kargo update`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdateFormat {
+    /// Progress lines on stderr.
+    #[default]
+    Human,
+    /// One JSON document on stdout describing every proposed update, for
+    /// bots that open a pull request per dependency.
+    Json,
+}
+
+impl UpdateFormat {
+    /// Parse a `--format` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            other => Err(kargo_util::errors::KargoError::Generic {
+                message: format!("Unknown update format '{other}'. Available: human, json"),
+            }
+            .into()),
+        }
+    }
 }
 
 struct UpdateEntry {
@@ -38,19 +71,24 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
     let manifest_path = project_root.join("Kargo.toml");
     let manifest = Manifest::from_path(&manifest_path)?;
     let repos = resolver::build_repos(&manifest);
+    resolver::configure_network(&manifest);
     let sp = kargo_util::progress::spinner("Checking for updates...");
     let client = download::build_client()?;
 
     let mut declared = collect_updatable_deps(&manifest);
 
-    // Include the Kotlin version from [package]
-    declared.push((
-        "kotlin".to_string(),
-        "org.jetbrains.kotlin".to_string(),
-        "kotlin-stdlib".to_string(),
-        manifest.package.kotlin.clone(),
-        "package.kotlin".to_string(),
-    ));
+    // Include the Kotlin version from [package]. Aliases such as
+    // `latest-stable` stay in Kargo.toml; only their lockfile pin moves.
+    let kotlin_alias = manifest.package.kotlin_req().filter(|r| !r.is_exact());
+    if kotlin_alias.is_none() {
+        declared.push((
+            "kotlin".to_string(),
+            "org.jetbrains.kotlin".to_string(),
+            "kotlin-stdlib".to_string(),
+            manifest.package.kotlin.clone(),
+            "package.kotlin".to_string(),
+        ));
+    }
 
     let semaphore = Arc::new(Semaphore::new(8));
     let mut join_set = JoinSet::new();
@@ -69,39 +107,38 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
         let repos = repos.clone();
         let client = client.clone();
         let sem = semaphore.clone();
-        let allow_major = opts.major;
+        let policy = manifest
+            .update_policy_for(&group, &artifact)
+            .unwrap_or(if opts.major {
+                UpdatePolicy::Major
+            } else {
+                UpdatePolicy::Minor
+            });
+        if policy == UpdatePolicy::None {
+            continue;
+        }
 
         join_set.spawn(async move {
             let _permit = sem.acquire().await.unwrap();
-            for repo in &repos {
-                let url = repo.metadata_url(&group, &artifact);
-                match download::download_text(&client, repo, &url).await {
-                    Ok(Some(xml)) => {
-                        if let Ok(meta) = metadata::parse_metadata(&xml) {
-                            let best = find_best_update(
-                                &current_version,
-                                &meta.release.or(meta.latest),
-                                &meta.versions,
-                                allow_major,
-                            );
-                            if let Some(new_version) = best {
-                                return Ok(Some(UpdateEntry {
-                                    key: toml_key,
-                                    group,
-                                    artifact,
-                                    old_version: current_version,
-                                    new_version,
-                                    section,
-                                }));
-                            }
-                        }
-                        break;
-                    }
-                    Ok(None) => continue,
-                    Err(e) => return Err(e),
-                }
-            }
-            Ok(None)
+            let Some(meta) =
+                crate::ops_outdated::fetch_metadata(&client, &repos, &group, &artifact).await?
+            else {
+                return Ok(None);
+            };
+            let best = find_best_update(
+                &current_version,
+                &meta.release.or(meta.latest),
+                &meta.versions,
+                policy,
+            );
+            Ok(best.map(|new_version| UpdateEntry {
+                key: toml_key,
+                group,
+                artifact,
+                old_version: current_version,
+                new_version,
+                section,
+            }))
         });
     }
 
@@ -117,12 +154,58 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
 
     sp.finish_and_clear();
 
+    let wants_kotlin = opts.dep.as_deref().map_or(true, |d| d == "kotlin");
+    if let (Some(req), true) = (kotlin_alias, wants_kotlin) {
+        update_kotlin_pin(project_root, &manifest, &req, opts.dry_run).await?;
+    }
+
+    updates.sort_by(|a, b| (&a.section, &a.key).cmp(&(&b.section, &b.key)));
+
+    if opts.format == UpdateFormat::Json {
+        let cache = LocalCache::new(project_root);
+        let mut entries = Vec::new();
+        for u in &updates {
+            let mut pom = None;
+            for repo in kargo_maven::repository::for_group(&repos, &u.group) {
+                if let Ok(Some(found)) = cache
+                    .fetch_pom(&client, repo, &u.group, &u.artifact, &u.new_version)
+                    .await
+                {
+                    pom = Some(found);
+                    break;
+                }
+            }
+            entries.push(update_json(u, pom.as_ref(), !opts.dry_run));
+        }
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "updates": entries }))
+                .unwrap_or_default()
+        );
+    }
+
+    if opts.interactive && !updates.is_empty() {
+        updates = select_updates(updates)?;
+        if updates.is_empty() {
+            kargo_util::progress::status_info("Skipped", "no updates selected");
+            return Ok(());
+        }
+    }
+
     if updates.is_empty() {
-        kargo_util::progress::status("Updated", "all dependencies at latest compatible version");
+        if opts.format == UpdateFormat::Human {
+            kargo_util::progress::status(
+                "Updated",
+                "all dependencies at latest compatible version",
+            );
+        }
         return Ok(());
     }
 
-    for u in &updates {
+    for u in updates
+        .iter()
+        .filter(|_| opts.format == UpdateFormat::Human)
+    {
         let arrow = if opts.dry_run {
             "would update"
         } else {
@@ -139,6 +222,22 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
         );
     }
 
+    if let Some(kotlin) = updates.iter().find(|u| u.section == "package.kotlin") {
+        let overrides: HashMap<(String, String), String> = updates
+            .iter()
+            .map(|u| ((u.group.clone(), u.artifact.clone()), u.new_version.clone()))
+            .collect();
+        check_kotlin_compat(
+            project_root,
+            &manifest,
+            &kotlin.new_version,
+            &overrides,
+            &repos,
+            &client,
+        )
+        .await;
+    }
+
     if opts.dry_run {
         return Ok(());
     }
@@ -165,9 +264,9 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
                 let new_coord = format!("{}:{}:{}", u.group, u.artifact, u.new_version);
                 doc["dependencies"][&u.key] = Item::Value(Value::from(new_coord));
             }
-            "dev-dependencies" => {
+            section @ ("dev-dependencies" | "integration-test-dependencies") => {
                 let new_coord = format!("{}:{}:{}", u.group, u.artifact, u.new_version);
-                doc["dev-dependencies"][&u.key] = Item::Value(Value::from(new_coord));
+                doc[section][&u.key] = Item::Value(Value::from(new_coord));
             }
             s if s.starts_with("target.") => {
                 let new_coord = format!("{}:{}:{}", u.group, u.artifact, u.new_version);
@@ -180,23 +279,318 @@ pub async fn update(project_root: &Path, opts: &UpdateOptions) -> miette::Result
 
     std::fs::write(&manifest_path, doc.to_string()).map_err(kargo_util::errors::KargoError::Io)?;
 
-    eprintln!("Re-resolving dependencies...");
+    kargo_util::progress::note("Re-resolving dependencies...");
     crate::ops_fetch::fetch(project_root, false).await?;
 
-    eprintln!("Updated {} dependencies.", updates.len());
+    kargo_util::progress::note(&format!("Updated {} dependencies.", updates.len()));
     Ok(())
 }
 
+/// Order updates for the selection list: grouped by semver impact, major
+/// first, then by section and key.
+fn order_for_selection(updates: &mut [UpdateEntry]) {
+    let rank = |u: &UpdateEntry| match version_delta(&u.old_version, &u.new_version) {
+        "major" => 0,
+        "minor" => 1,
+        "patch" => 2,
+        _ => 3,
+    };
+    updates.sort_by(|a, b| (rank(a), &a.section, &a.key).cmp(&(rank(b), &b.section, &b.key)));
+}
+
+/// Checkbox label for an update, and whether it starts checked. Major
+/// updates start unchecked.
+fn selection_item(u: &UpdateEntry) -> (String, bool) {
+    let delta = version_delta(&u.old_version, &u.new_version);
+    let label = format!(
+        "{delta:<5}  {}:{}  {} -> {}  [{}]",
+        u.group, u.artifact, u.old_version, u.new_version, u.section
+    );
+    (label, delta != "major")
+}
+
+/// Ask which updates to apply.
+fn select_updates(mut updates: Vec<UpdateEntry>) -> miette::Result<Vec<UpdateEntry>> {
+    use kargo_util::errors::KargoError;
+
+    if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
+        return Err(KargoError::Generic {
+            message: "`kargo update -i` needs an interactive terminal".into(),
+        }
+        .into());
+    }
+    order_for_selection(&mut updates);
+    let items: Vec<(String, bool)> = updates.iter().map(selection_item).collect();
+    let chosen = dialoguer::MultiSelect::new()
+        .with_prompt("Select updates to apply (space to toggle, enter to confirm)")
+        .items_checked(&items)
+        .interact()
+        .map_err(|e| KargoError::Generic {
+            message: format!("Prompt error: {e}"),
+        })?;
+    Ok(updates
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| chosen.contains(i))
+        .map(|(_, u)| u)
+        .collect())
+}
+
+/// JSON description of one proposed update.
+fn update_json(u: &UpdateEntry, pom: Option<&Pom>, applied: bool) -> serde_json::Value {
+    serde_json::json!({
+        "coordinate": format!("{}:{}", u.group, u.artifact),
+        "group": u.group,
+        "artifact": u.artifact,
+        "key": u.key,
+        "section": u.section,
+        "old_version": u.old_version,
+        "new_version": u.new_version,
+        "delta": version_delta(&u.old_version, &u.new_version),
+        "changelog_url": pom.and_then(changelog_url),
+        "applied": applied,
+    })
+}
+
+/// How far apart two versions are: `major`, `minor`, `patch`, or `other`
+/// when only qualifiers or later segments differ.
+fn version_delta(old: &str, new: &str) -> &'static str {
+    let segments = |v: &str| -> Vec<String> {
+        v.split(['.', '-'])
+            .take(3)
+            .map(|s| s.to_ascii_lowercase())
+            .collect()
+    };
+    let (old, new) = (segments(old), segments(new));
+    match (0..3).find(|&i| old.get(i) != new.get(i)) {
+        Some(0) => "major",
+        Some(1) => "minor",
+        Some(2) => "patch",
+        _ => "other",
+    }
+}
+
+/// Best guess at where a release's changelog lives, from the POM's SCM or
+/// project URL: the releases page for GitHub and GitLab repositories, the
+/// URL itself otherwise.
+fn changelog_url(pom: &Pom) -> Option<String> {
+    let raw = pom.scm_url.as_deref().or(pom.url.as_deref())?.trim();
+    let raw = raw.trim_start_matches("scm:");
+    let raw = match raw.strip_prefix("git:") {
+        Some(rest) if !rest.starts_with("//") => rest,
+        _ => raw,
+    };
+    let url = raw
+        .replacen("git@github.com:", "https://github.com/", 1)
+        .replacen("git://", "https://", 1)
+        .replacen("http://", "https://", 1);
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    if !url.starts_with("https://") {
+        return None;
+    }
+    // Drop `/tree/<branch>` and similar suffixes after owner/repo.
+    let repo_root = |host: &str| -> Option<String> {
+        let rest = url.strip_prefix(&format!("https://{host}/"))?;
+        let mut parts = rest.split('/');
+        Some(format!(
+            "https://{host}/{}/{}",
+            parts.next()?,
+            parts.next()?
+        ))
+    };
+    if let Some(repo) = repo_root("github.com") {
+        return Some(format!("{repo}/releases"));
+    }
+    if let Some(repo) = repo_root("gitlab.com") {
+        return Some(format!("{repo}/-/releases"));
+    }
+    Some(url.to_string())
+}
+
+/// Re-resolve an aliased `package.kotlin` and move its `Kargo.lock` pin.
+async fn update_kotlin_pin(
+    project_root: &Path,
+    manifest: &Manifest,
+    req: &KotlinVersionReq,
+    dry_run: bool,
+) -> miette::Result<()> {
+    let requested = &manifest.package.kotlin;
+    let resolved = crate::ops_toolchain::query_kotlin_release(manifest, req).await?;
+    let current = Lockfile::for_project(project_root)
+        .ok()
+        .and_then(|lf| lf.pinned_kotlin(requested).map(str::to_string));
+
+    if current.as_deref() == Some(resolved.as_str()) {
+        return Ok(());
+    }
+
+    let repos = resolver::build_repos(manifest);
+    let client = download::build_client()?;
+    check_kotlin_compat(
+        project_root,
+        manifest,
+        &resolved,
+        &HashMap::new(),
+        &repos,
+        &client,
+    )
+    .await;
+
+    let arrow = if dry_run { "would pin" } else { "pinned" };
+    eprintln!(
+        "  {arrow} kotlin {} -> {resolved} [{requested}]",
+        current.as_deref().unwrap_or("(unpinned)")
+    );
+
+    if !dry_run {
+        crate::ops_toolchain::pin_kotlin_version(project_root, requested, &resolved)?;
+    }
+    Ok(())
+}
+
+/// Dependencies whose compatibility with a new Kotlin compiler is checked:
+/// kotlinx libraries, Compose, and KSP processors, as `(group, artifact,
+/// version)`. `overrides` holds versions the same update moves them to.
+fn key_dependencies(
+    manifest: &Manifest,
+    overrides: &HashMap<(String, String), String>,
+) -> Vec<(String, String, String)> {
+    let processors: Vec<(String, String)> = manifest
+        .ksp
+        .values()
+        .filter_map(|dep| match dep {
+            Dependency::Short(s) => MavenCoordinate::parse(s).map(|c| (c.group_id, c.artifact_id)),
+            Dependency::Detailed(d) => Some((d.group.clone(), d.artifact.clone())),
+            Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
+        })
+        .collect();
+    let mut deps: Vec<(String, String, String)> = crate::ops_fetch::collect_declared_deps(manifest)
+        .into_iter()
+        .filter(|(group, artifact, _)| {
+            group.starts_with("org.jetbrains.kotlinx")
+                || group.starts_with("org.jetbrains.compose")
+                || group.starts_with("androidx.compose")
+                || processors.contains(&(group.clone(), artifact.clone()))
+        })
+        .map(|(group, artifact, version)| {
+            let version = overrides
+                .get(&(group.clone(), artifact.clone()))
+                .cloned()
+                .unwrap_or(version);
+            (group, artifact, version)
+        })
+        .collect();
+    deps.sort();
+    deps.dedup();
+    deps
+}
+
+/// The `kotlin-stdlib` version a POM depends on, if it declares one.
+fn stdlib_requirement(pom: &Pom) -> Option<String> {
+    let mut pom = pom.clone();
+    pom.resolve_properties();
+    pom.dependencies
+        .iter()
+        .find(|d| {
+            d.group_id == "org.jetbrains.kotlin" && d.artifact_id.starts_with("kotlin-stdlib")
+        })
+        .and_then(|d| d.version.clone())
+        .filter(|v| !v.contains("${"))
+}
+
+/// `(major, minor)` of a version string.
+fn major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split(['.', '-']);
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// Known problems with compiling the project using Kotlin `kotlin`.
+/// `requirements` maps dependencies (`group:artifact:version`) to the
+/// `kotlin-stdlib` they were built against.
+fn kotlin_compat_warnings(
+    kotlin: &str,
+    manifest: &Manifest,
+    requirements: &[(String, String)],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(target) = major_minor(kotlin) else {
+        return warnings;
+    };
+
+    for (dep, stdlib) in requirements {
+        if major_minor(stdlib).is_some_and(|required| required > target) {
+            warnings.push(format!(
+                "{dep} is built with Kotlin {stdlib}, newer than Kotlin {kotlin}; \
+                 the compiler may be unable to read its metadata"
+            ));
+        }
+    }
+
+    if let Some(ksp) = manifest.package.ksp_version.as_deref() {
+        if let Some((ksp_kotlin, _)) = ksp.split_once('-') {
+            if !kargo_compiler::plugins::ksp::is_ksp2(ksp) && ksp_kotlin != kotlin {
+                warnings.push(format!(
+                    "ksp-version {ksp} is built for Kotlin {ksp_kotlin}; \
+                     use the KSP release matching Kotlin {kotlin}"
+                ));
+            }
+        }
+    }
+
+    if manifest.compose.as_ref().is_some_and(|c| c.enabled) && target < (2, 0) {
+        warnings.push(format!(
+            "[compose] needs Kotlin 2.0 or newer, which ships the Compose compiler plugin; \
+             Kotlin {kotlin} does not"
+        ));
+    }
+    warnings
+}
+
+/// Warn about key dependencies that may not work with Kotlin `kotlin`.
+async fn check_kotlin_compat(
+    project_root: &Path,
+    manifest: &Manifest,
+    kotlin: &str,
+    overrides: &HashMap<(String, String), String>,
+    repos: &[MavenRepository],
+    client: &reqwest::Client,
+) {
+    let cache = LocalCache::new(project_root);
+    let mut requirements = Vec::new();
+    for (group, artifact, version) in key_dependencies(manifest, overrides) {
+        for repo in kargo_maven::repository::for_group(repos, &group) {
+            if let Ok(Some(pom)) = cache
+                .fetch_pom(client, repo, &group, &artifact, &version)
+                .await
+            {
+                if let Some(stdlib) = stdlib_requirement(&pom) {
+                    requirements.push((format!("{group}:{artifact}:{version}"), stdlib));
+                }
+                break;
+            }
+        }
+    }
+    for warning in kotlin_compat_warnings(kotlin, manifest, &requirements) {
+        kargo_util::progress::status_warn("Warning", &warning);
+    }
+}
+
 /// Select the best version to update to.
 ///
-/// Without `--major`, stays within the same major version.
+/// `policy` caps the change: `minor` (the default without `--major`) stays
+/// within the same major version, `patch` within the same minor version,
+/// and `none` never updates.
 /// Prefers the release/latest marker, falls back to the highest from the versions list.
 fn find_best_update(
     current: &str,
     release: &Option<String>,
     versions: &[String],
-    allow_major: bool,
+    policy: UpdatePolicy,
 ) -> Option<String> {
+    if policy == UpdatePolicy::None {
+        return None;
+    }
+    let allow_major = policy == UpdatePolicy::Major;
     let current_v = MavenVersion::parse(current);
     let current_major: Option<u64> = current.split('.').next().and_then(|s| s.parse().ok());
 
@@ -247,10 +641,24 @@ fn find_best_update(
         }
     }
 
+    if policy == UpdatePolicy::Patch {
+        candidates.retain(|(v, _)| policy_allows(policy, current, v));
+    }
+
     candidates.sort_by(|a, b| b.1.cmp(&a.1));
     candidates.first().map(|(v, _)| v.to_string())
 }
 
+/// Whether `policy` permits moving from `current` to `candidate`.
+pub(crate) fn policy_allows(policy: UpdatePolicy, current: &str, candidate: &str) -> bool {
+    let needed = match version_delta(current, candidate) {
+        "major" => UpdatePolicy::Major,
+        "minor" => UpdatePolicy::Minor,
+        _ => UpdatePolicy::Patch,
+    };
+    policy >= needed
+}
+
 /// Collect updatable direct dependencies: `(toml_key, group, artifact, version, section)`.
 fn collect_updatable_deps(manifest: &Manifest) -> Vec<(String, String, String, String, String)> {
     use kargo_core::dependency::{Dependency, MavenCoordinate};
@@ -266,7 +674,7 @@ fn collect_updatable_deps(manifest: &Manifest) -> Vec<(String, String, String, S
             Dependency::Detailed(d) => {
                 Some((d.group.clone(), d.artifact.clone(), d.version.clone()))
             }
-            Dependency::Catalog(_) => None,
+            Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
         }
     };
 
@@ -280,6 +688,17 @@ fn collect_updatable_deps(manifest: &Manifest) -> Vec<(String, String, String, S
             deps.push((key.clone(), g, a, v, "dev-dependencies".to_string()));
         }
     }
+    for (key, dep) in &manifest.integration_test_dependencies {
+        if let Some((g, a, v)) = extract(dep) {
+            deps.push((
+                key.clone(),
+                g,
+                a,
+                v,
+                "integration-test-dependencies".to_string(),
+            ));
+        }
+    }
     for (target_name, target_deps) in &manifest.target {
         for (key, dep) in &target_deps.dependencies {
             if let Some((g, a, v)) = extract(dep) {
@@ -314,7 +733,12 @@ mod tests {
             "1.8.0".to_string(),
             "2.0.0".to_string(),
         ];
-        let best = find_best_update("1.7.0", &Some("2.0.0".to_string()), &versions, false);
+        let best = find_best_update(
+            "1.7.0",
+            &Some("2.0.0".to_string()),
+            &versions,
+            UpdatePolicy::Minor,
+        );
         assert_eq!(best, Some("1.8.0".to_string()));
     }
 
@@ -325,14 +749,24 @@ mod tests {
             "2.0.0".to_string(),
             "2.1.0".to_string(),
         ];
-        let best = find_best_update("1.7.0", &Some("2.1.0".to_string()), &versions, true);
+        let best = find_best_update(
+            "1.7.0",
+            &Some("2.1.0".to_string()),
+            &versions,
+            UpdatePolicy::Major,
+        );
         assert_eq!(best, Some("2.1.0".to_string()));
     }
 
     #[test]
     fn no_update_available() {
         let versions = vec!["1.0.0".to_string(), "1.1.0".to_string()];
-        let best = find_best_update("1.1.0", &Some("1.1.0".to_string()), &versions, false);
+        let best = find_best_update(
+            "1.1.0",
+            &Some("1.1.0".to_string()),
+            &versions,
+            UpdatePolicy::Minor,
+        );
         assert_eq!(best, None);
     }
 
@@ -344,7 +778,174 @@ mod tests {
             "1.1.0-RC1".to_string(),
             "1.2.0-SNAPSHOT".to_string(),
         ];
-        let best = find_best_update("1.0.0", &None, &versions, false);
+        let best = find_best_update("1.0.0", &None, &versions, UpdatePolicy::Minor);
         assert_eq!(best, None);
     }
+
+    #[test]
+    fn version_delta_names_the_first_changed_segment() {
+        assert_eq!(version_delta("1.7.3", "2.0.0"), "major");
+        assert_eq!(version_delta("1.7.3", "1.8.0"), "minor");
+        assert_eq!(version_delta("1.7.3", "1.7.4"), "patch");
+        assert_eq!(version_delta("1.7", "1.7.1"), "patch");
+        assert_eq!(version_delta("32.1.2-jre", "32.1.2-android"), "other");
+    }
+
+    #[test]
+    fn changelog_url_is_guessed_from_scm() {
+        let pom = |scm: Option<&str>, url: Option<&str>| Pom {
+            scm_url: scm.map(String::from),
+            url: url.map(String::from),
+            ..Default::default()
+        };
+        assert_eq!(
+            changelog_url(&pom(
+                Some("https://github.com/square/okhttp/tree/master"),
+                None
+            ))
+            .as_deref(),
+            Some("https://github.com/square/okhttp/releases")
+        );
+        assert_eq!(
+            changelog_url(&pom(
+                Some("scm:git:git@github.com:Kotlin/kotlinx.coroutines.git"),
+                None
+            ))
+            .as_deref(),
+            Some("https://github.com/Kotlin/kotlinx.coroutines/releases")
+        );
+        assert_eq!(
+            changelog_url(&pom(
+                Some("scm:git:git://github.com/google/guava.git"),
+                None
+            ))
+            .as_deref(),
+            Some("https://github.com/google/guava/releases")
+        );
+        assert_eq!(
+            changelog_url(&pom(None, Some("https://gitlab.com/acme/lib"))).as_deref(),
+            Some("https://gitlab.com/acme/lib/-/releases")
+        );
+        assert_eq!(
+            changelog_url(&pom(None, Some("https://example.org/lib/"))).as_deref(),
+            Some("https://example.org/lib")
+        );
+        assert_eq!(changelog_url(&pom(None, None)), None);
+    }
+
+    #[test]
+    fn update_policy_caps_the_bump() {
+        let versions = vec![
+            "1.7.1".to_string(),
+            "1.8.0".to_string(),
+            "2.0.0".to_string(),
+        ];
+        let release = Some("2.0.0".to_string());
+        let best = |policy| find_best_update("1.7.0", &release, &versions, policy);
+        assert_eq!(best(UpdatePolicy::Patch).as_deref(), Some("1.7.1"));
+        assert_eq!(best(UpdatePolicy::Minor).as_deref(), Some("1.8.0"));
+        assert_eq!(best(UpdatePolicy::Major).as_deref(), Some("2.0.0"));
+        assert_eq!(best(UpdatePolicy::None), None);
+        assert!(!policy_allows(UpdatePolicy::Minor, "1.7.0", "2.0.0"));
+        assert!(policy_allows(UpdatePolicy::Patch, "1.7", "1.7.1"));
+    }
+
+    #[test]
+    fn selection_is_grouped_by_impact_with_majors_unchecked() {
+        let entry = |key: &str, old: &str, new: &str| UpdateEntry {
+            key: key.to_string(),
+            group: "com.example".to_string(),
+            artifact: key.to_string(),
+            old_version: old.to_string(),
+            new_version: new.to_string(),
+            section: "dependencies".to_string(),
+        };
+        let mut updates = vec![
+            entry("a", "1.0.0", "1.0.1"),
+            entry("b", "1.0.0", "2.0.0"),
+            entry("c", "1.0.0", "1.1.0"),
+        ];
+        order_for_selection(&mut updates);
+        let items: Vec<(String, bool)> = updates.iter().map(selection_item).collect();
+        assert_eq!(
+            items,
+            [
+                (
+                    "major  com.example:b  1.0.0 -> 2.0.0  [dependencies]".to_string(),
+                    false
+                ),
+                (
+                    "minor  com.example:c  1.0.0 -> 1.1.0  [dependencies]".to_string(),
+                    true
+                ),
+                (
+                    "patch  com.example:a  1.0.0 -> 1.0.1  [dependencies]".to_string(),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn kotlin_bump_warns_about_incompatible_dependencies() {
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "1.9.24"
+ksp-version = "1.9.24-1.0.20"
+
+[compose]
+enabled = true
+"#,
+        )
+        .unwrap();
+        let requirements = vec![
+            (
+                "org.jetbrains.kotlinx:kotlinx-serialization-json:1.7.3".to_string(),
+                "2.0.20".to_string(),
+            ),
+            (
+                "org.jetbrains.kotlinx:kotlinx-coroutines-core:1.8.1".to_string(),
+                "1.9.21".to_string(),
+            ),
+        ];
+
+        let warnings = kotlin_compat_warnings("1.9.25", &manifest, &requirements);
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        assert!(warnings[0].starts_with("org.jetbrains.kotlinx:kotlinx-serialization-json"));
+        assert!(warnings[1].starts_with("ksp-version 1.9.24-1.0.20"));
+        assert!(warnings[2].starts_with("[compose]"));
+
+        let warnings = kotlin_compat_warnings("2.0.21", &manifest, &requirements);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].starts_with("ksp-version"));
+    }
+
+    #[test]
+    fn stdlib_requirement_is_read_from_the_pom() {
+        let pom = kargo_maven::pom::parse_pom(
+            r#"<project>
+    <groupId>org.jetbrains.kotlinx</groupId>
+    <artifactId>kotlinx-coroutines-core-jvm</artifactId>
+    <version>1.8.1</version>
+    <properties><kotlin.version>1.9.21</kotlin.version></properties>
+    <dependencies>
+        <dependency>
+            <groupId>org.jetbrains</groupId>
+            <artifactId>annotations</artifactId>
+            <version>23.0.0</version>
+        </dependency>
+        <dependency>
+            <groupId>org.jetbrains.kotlin</groupId>
+            <artifactId>kotlin-stdlib</artifactId>
+            <version>${kotlin.version}</version>
+        </dependency>
+    </dependencies>
+</project>"#,
+        )
+        .unwrap();
+        assert_eq!(stdlib_requirement(&pom).as_deref(), Some("1.9.21"));
+    }
 }
@@ -0,0 +1,298 @@
+//! Operation: generate VS Code workspace files (`kargo idea --vscode`).
+//!
+//! Writes three files under `.vscode/`, each merged into any existing file so
+//! user-defined entries survive a re-run:
+//!
+//! - `tasks.json` — `kargo: build`, `kargo: test`, `kargo: run`, and
+//!   `kargo: run (debug)`, with a problem matcher for kotlinc diagnostics
+//! - `launch.json` — a JDWP attach configuration wired to the debug task
+//! - `settings.json` — classpath and source path hints for the Java/Kotlin
+//!   extensions
+//!
+//! Tasks and launch configurations owned by Kargo are identified by their
+//! `kargo: ` label prefix and replaced on every run; everything else is kept.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use kargo_util::errors::KargoError;
+
+use crate::ops_idea::IdeaModel;
+
+/// JDWP port used by the `kargo: run (debug)` task and the attach configuration.
+pub const DEBUG_PORT: u16 = 5005;
+
+const LABEL_PREFIX: &str = "kargo: ";
+
+/// Load the project and write its VS Code files. Returns the paths written.
+pub async fn export(project_dir: &Path) -> miette::Result<Vec<PathBuf>> {
+    let ctx = crate::BuildContext::load(project_dir, None, None, false).await?;
+    let model = IdeaModel::from_context(&ctx);
+    write(project_dir, &model)
+}
+
+/// Write `.vscode/{tasks,launch,settings}.json` for `model` under `project_dir`.
+pub fn write(project_dir: &Path, model: &IdeaModel) -> miette::Result<Vec<PathBuf>> {
+    let vscode_dir = project_dir.join(".vscode");
+    std::fs::create_dir_all(&vscode_dir).map_err(KargoError::Io)?;
+
+    let tasks_path = vscode_dir.join("tasks.json");
+    let mut tasks = read_json(&tasks_path)?;
+    merge_list(&mut tasks, "tasks", "label", kargo_tasks());
+    tasks.entry("version").or_insert_with(|| json!("2.0.0"));
+
+    let launch_path = vscode_dir.join("launch.json");
+    let mut launch = read_json(&launch_path)?;
+    merge_list(
+        &mut launch,
+        "configurations",
+        "name",
+        kargo_launch_configs(),
+    );
+    launch.entry("version").or_insert_with(|| json!("0.2.0"));
+
+    let settings_path = vscode_dir.join("settings.json");
+    let mut settings = read_json(&settings_path)?;
+    for (key, value) in kargo_settings(project_dir, model) {
+        settings.insert(key, value);
+    }
+
+    let mut written = Vec::new();
+    for (path, doc) in [
+        (tasks_path, tasks),
+        (launch_path, launch),
+        (settings_path, settings),
+    ] {
+        let mut content =
+            serde_json::to_string_pretty(&Value::Object(doc)).map_err(|e| KargoError::Generic {
+                message: format!("Failed to serialize {}: {e}", path.display()),
+            })?;
+        content.push('\n');
+        std::fs::write(&path, content).map_err(KargoError::Io)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn kargo_tasks() -> Vec<Value> {
+    let problem_matcher = json!({
+        "owner": "kotlin",
+        "fileLocation": ["autoDetect", "${workspaceFolder}"],
+        "pattern": {
+            "regexp": "^(.*?):(\\d+):(\\d+): (error|warning|info): (.*)$",
+            "file": 1,
+            "line": 2,
+            "column": 3,
+            "severity": 4,
+            "message": 5
+        }
+    });
+
+    vec![
+        json!({
+            "label": "kargo: build",
+            "type": "shell",
+            "command": "kargo",
+            "args": ["build"],
+            "group": { "kind": "build", "isDefault": true },
+            "problemMatcher": problem_matcher,
+        }),
+        json!({
+            "label": "kargo: test",
+            "type": "shell",
+            "command": "kargo",
+            "args": ["test"],
+            "group": { "kind": "test", "isDefault": true },
+            "problemMatcher": problem_matcher,
+        }),
+        json!({
+            "label": "kargo: run",
+            "type": "shell",
+            "command": "kargo",
+            "args": ["run"],
+            "problemMatcher": problem_matcher,
+        }),
+        json!({
+            "label": "kargo: run (debug)",
+            "type": "shell",
+            "command": "kargo",
+            "args": ["run", format!("--debug={DEBUG_PORT}")],
+            "isBackground": true,
+            "problemMatcher": {
+                "owner": "kotlin",
+                "pattern": { "regexp": "^__never_matches__$" },
+                "background": {
+                    "beginsPattern": "^.*(Compiling|Running).*$",
+                    "endsPattern": "^Listening for transport dt_socket.*$"
+                }
+            },
+        }),
+    ]
+}
+
+fn kargo_launch_configs() -> Vec<Value> {
+    vec![json!({
+        "name": "kargo: debug",
+        "type": "java",
+        "request": "attach",
+        "hostName": "localhost",
+        "port": DEBUG_PORT,
+        "preLaunchTask": "kargo: run (debug)",
+    })]
+}
+
+fn kargo_settings(project_dir: &Path, model: &IdeaModel) -> Vec<(String, Value)> {
+    let rel = |p: &Path| -> String {
+        p.strip_prefix(project_dir)
+            .unwrap_or(p)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    let libraries: Vec<String> = model.libraries.iter().map(|l| rel(&l.classes)).collect();
+    let mut source_paths: Vec<String> = model.source_roots.iter().map(|p| rel(p)).collect();
+    source_paths.extend(model.generated_roots.iter().map(|p| rel(p)));
+    source_paths.extend(model.test_roots.iter().map(|p| rel(p)));
+
+    vec![
+        ("java.project.referencedLibraries".into(), json!(libraries)),
+        ("java.project.sourcePaths".into(), json!(source_paths)),
+        (
+            "java.project.outputPath".into(),
+            json!(rel(&model.output_dir)),
+        ),
+        (
+            "kotlin.compiler.jvm.target".into(),
+            json!(model.language_level),
+        ),
+    ]
+}
+
+/// Read a JSON object from `path`, or an empty object if the file is absent.
+fn read_json(path: &Path) -> miette::Result<Map<String, Value>> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Ok(Map::new());
+    };
+    match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(KargoError::Generic {
+            message: format!(
+                "{} is not a plain JSON object (comments are not supported); \
+                 refusing to overwrite it",
+                path.display()
+            ),
+        }
+        .into()),
+    }
+}
+
+/// Replace Kargo-owned entries (by `key` prefix) in the `list` array of `doc`.
+fn merge_list(doc: &mut Map<String, Value>, list: &str, key: &str, ours: Vec<Value>) {
+    let mut entries: Vec<Value> = match doc.remove(list) {
+        Some(Value::Array(items)) => items,
+        _ => Vec::new(),
+    };
+    entries.retain(|item| {
+        !item
+            .get(key)
+            .and_then(Value::as_str)
+            .is_some_and(|label| label.starts_with(LABEL_PREFIX))
+    });
+    entries.extend(ours);
+    doc.insert(list.to_string(), Value::Array(entries));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops_idea::{IdeaLibrary, LibraryScope};
+
+    fn model(root: &Path) -> IdeaModel {
+        IdeaModel {
+            module_name: "demo".into(),
+            source_roots: vec![root.join("src/main/kotlin")],
+            test_roots: vec![root.join("src/test/kotlin")],
+            resource_roots: vec![],
+            test_resource_roots: vec![],
+            generated_roots: vec![],
+            excluded: vec![],
+            output_dir: root.join("build/jvm/dev/classes"),
+            test_output_dir: root.join("build/jvm/dev/test-classes"),
+            libraries: vec![IdeaLibrary {
+                name: "Kargo: com.example:core:1.0".into(),
+                classes: root.join(".kargo/dependencies/core-1.0.jar"),
+                sources: None,
+                scope: LibraryScope::Compile,
+            }],
+            jdk_name: "21".into(),
+            language_level: "17".into(),
+            kotlin_version: "2.3.0".into(),
+        }
+    }
+
+    #[test]
+    fn writes_tasks_launch_and_settings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(root, &model(root)).unwrap();
+
+        let tasks = std::fs::read_to_string(root.join(".vscode/tasks.json")).unwrap();
+        assert!(tasks.contains("\"kargo: build\""));
+        assert!(tasks.contains("\"--debug=5005\""));
+        assert!(!tasks.contains("JAVA_TOOL_OPTIONS"));
+
+        let launch = std::fs::read_to_string(root.join(".vscode/launch.json")).unwrap();
+        assert!(launch.contains("\"preLaunchTask\": \"kargo: run (debug)\""));
+
+        let settings = std::fs::read_to_string(root.join(".vscode/settings.json")).unwrap();
+        assert!(settings.contains(".kargo/dependencies/core-1.0.jar"));
+        assert!(settings.contains("\"src/main/kotlin\""));
+    }
+
+    #[test]
+    fn preserves_user_entries_on_rerun() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let vscode = root.join(".vscode");
+        std::fs::create_dir_all(&vscode).unwrap();
+        std::fs::write(
+            vscode.join("tasks.json"),
+            r#"{"version":"2.0.0","tasks":[{"label":"lint","command":"ktlint"},{"label":"kargo: build","command":"old"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(vscode.join("settings.json"), r#"{"editor.tabSize": 4}"#).unwrap();
+
+        write(root, &model(root)).unwrap();
+        write(root, &model(root)).unwrap();
+
+        let tasks: Value =
+            serde_json::from_str(&std::fs::read_to_string(vscode.join("tasks.json")).unwrap())
+                .unwrap();
+        let labels: Vec<&str> = tasks["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels.iter().filter(|l| **l == "kargo: build").count(), 1);
+        assert!(labels.contains(&"lint"));
+        assert!(!tasks.to_string().contains("\"old\""));
+
+        let settings = std::fs::read_to_string(vscode.join("settings.json")).unwrap();
+        assert!(settings.contains("editor.tabSize"));
+    }
+
+    #[test]
+    fn refuses_to_overwrite_jsonc() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let vscode = root.join(".vscode");
+        std::fs::create_dir_all(&vscode).unwrap();
+        std::fs::write(vscode.join("launch.json"), "// my configs\n{}").unwrap();
+
+        assert!(write(root, &model(root)).is_err());
+        let launch = std::fs::read_to_string(vscode.join("launch.json")).unwrap();
+        assert!(launch.starts_with("// my configs"));
+    }
+}
@@ -0,0 +1,461 @@
+//! Operation: package and serve Kotlin/Wasm (`wasm-js`) output.
+//!
+//! A `wasm-js` build links `<name>.wasm` and its `<name>.mjs` loader into the
+//! classes directory. [`write_bundle`] turns that into a self-contained
+//! directory under `output/` with the main resources, an `index.html` (unless
+//! the resources provide one), and a `package.json`.
+//!
+//! `kargo run --target wasm-js` calls [`serve`], a small development HTTP
+//! server for the bundle. It rebuilds when sources change and the page
+//! reloads itself by polling [`RELOAD_PATH`].
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use kargo_core::lockfile::LockedNpmPackage;
+use kargo_util::errors::KargoError;
+
+use crate::ops_build::{self, BuildOptions};
+
+/// Port the development server tries first before falling back to any free port.
+pub const DEV_SERVER_PORT: u16 = 8080;
+
+/// Endpoint polled by the injected reload script; returns the build generation.
+pub const RELOAD_PATH: &str = "/__kargo/reload";
+
+const DEBOUNCE_MS: u64 = 300;
+
+/// Build outputs copied from the classes directory into the bundle.
+const BUNDLE_EXTENSIONS: &[&str] = &["wasm", "mjs", "js", "map"];
+
+/// Installed `[npm-dependencies]` the bundle imports at runtime.
+pub struct NpmBundle<'a> {
+    pub packages: &'a [LockedNpmPackage],
+    /// Where the packages are installed, for resolving their entry points.
+    pub node_modules: &'a Path,
+}
+
+/// Assemble the distributable bundle for a linked `wasm-js` module.
+///
+/// With npm packages, the bundle's `package.json` lists them as
+/// `dependencies` and the default `index.html` carries an import map that
+/// resolves them from `./node_modules/`.
+///
+/// `bundle_dir` is recreated from scratch. Returns `None` if the classes
+/// directory holds no linked `.wasm` module.
+pub fn write_bundle(
+    classes_dir: &Path,
+    resources_dir: &Path,
+    bundle_dir: &Path,
+    name: &str,
+    version: &str,
+    npm: &NpmBundle,
+) -> miette::Result<Option<PathBuf>> {
+    let outputs: Vec<PathBuf> = std::fs::read_dir(classes_dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.is_file()
+                        && p.extension()
+                            .and_then(|e| e.to_str())
+                            .is_some_and(|e| BUNDLE_EXTENSIONS.contains(&e))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !outputs
+        .iter()
+        .any(|p| p.extension().is_some_and(|e| e == "wasm"))
+    {
+        return Ok(None);
+    }
+
+    if bundle_dir.exists() {
+        std::fs::remove_dir_all(bundle_dir).map_err(KargoError::Io)?;
+    }
+    std::fs::create_dir_all(bundle_dir).map_err(KargoError::Io)?;
+
+    if resources_dir.is_dir() {
+        ops_build::copy_dir_contents(resources_dir, bundle_dir);
+    }
+    for path in &outputs {
+        if let Some(file_name) = path.file_name() {
+            std::fs::copy(path, bundle_dir.join(file_name)).map_err(KargoError::Io)?;
+        }
+    }
+
+    let index = bundle_dir.join("index.html");
+    if !index.is_file() {
+        let import_map = if npm.packages.is_empty() {
+            String::new()
+        } else {
+            let map = crate::ops_npm::import_map(npm.node_modules, npm.packages, "./node_modules/");
+            format!("  <script type=\"importmap\">{map}</script>\n")
+        };
+        let html = format!(
+            "<!DOCTYPE html>\n\
+             <html>\n\
+             <head>\n  <meta charset=\"utf-8\">\n  <title>{name}</title>\n{import_map}</head>\n\
+             <body>\n  <script type=\"module\" src=\"./{name}.mjs\"></script>\n</body>\n\
+             </html>\n"
+        );
+        std::fs::write(&index, html).map_err(KargoError::Io)?;
+    }
+
+    let dependencies: serde_json::Map<String, serde_json::Value> = npm
+        .packages
+        .iter()
+        .map(|p| (p.name.clone(), serde_json::json!(p.version)))
+        .collect();
+    let mut package_json = serde_json::json!({
+        "name": name,
+        "version": version,
+        "type": "module",
+        "main": format!("./{name}.mjs"),
+    });
+    if !dependencies.is_empty() {
+        package_json["dependencies"] = serde_json::Value::Object(dependencies);
+    }
+    let mut content = serde_json::to_string_pretty(&package_json).unwrap_or_default();
+    content.push('\n');
+    std::fs::write(bundle_dir.join("package.json"), content).map_err(KargoError::Io)?;
+
+    Ok(Some(bundle_dir.to_path_buf()))
+}
+
+/// Serve `bundle_dir` over HTTP, rebuilding on source changes until Ctrl+C.
+pub async fn serve(project_dir: &Path, bundle_dir: &Path, verbose: bool) -> miette::Result<()> {
+    let listener = match TcpListener::bind(("127.0.0.1", DEV_SERVER_PORT)).await {
+        Ok(l) => l,
+        Err(_) => TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(KargoError::Io)?,
+    };
+    let addr: SocketAddr = listener.local_addr().map_err(KargoError::Io)?;
+    let generation = Arc::new(AtomicU64::new(0));
+
+    // `/node_modules/...` falls back to the installed [npm-dependencies].
+    let roots = Arc::new(vec![
+        bundle_dir.to_path_buf(),
+        crate::ops_npm::npm_dir(project_dir),
+    ]);
+    let served = generation.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let roots = roots.clone();
+            let generation = served.load(Ordering::SeqCst);
+            tokio::spawn(async move {
+                let _ = handle_connection(stream, &roots, generation).await;
+            });
+        }
+    });
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if matches!(
+                event.kind,
+                notify::EventKind::Create(_)
+                    | notify::EventKind::Modify(_)
+                    | notify::EventKind::Remove(_)
+            ) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|e| KargoError::Generic {
+        message: format!("Failed to create file watcher: {e}"),
+    })?;
+    for path in watch_paths(project_dir)? {
+        let mode = if path.is_dir() {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher
+            .watch(&path, mode)
+            .map_err(|e| KargoError::Generic {
+                message: format!("Failed to watch {}: {e}", path.display()),
+            })?;
+    }
+
+    kargo_util::progress::status(
+        "Serving",
+        &format!("http://{addr}/ (live reload, Ctrl+C to stop)"),
+    );
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            event = rx.recv() => {
+                if event.is_none() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(DEBOUNCE_MS)).await;
+                while rx.try_recv().is_ok() {}
+
+                kargo_util::progress::status("Detected", "change, rebuilding...");
+                let result = ops_build::build(
+                    project_dir,
+                    &BuildOptions {
+                        target: Some("wasm-js".into()),
+                        verbose,
+                        ..Default::default()
+                    },
+                )
+                .await;
+                match result {
+                    Ok(r) if r.success => {
+                        generation.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Ok(_) => {}
+                    Err(e) => kargo_util::progress::status_warn("Error", &format!("{e}")),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Source, resource, and manifest paths whose changes trigger a rebuild.
+fn watch_paths(project_dir: &Path) -> miette::Result<Vec<PathBuf>> {
+    let manifest = kargo_core::manifest::Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    let discovered = kargo_compiler::source_set_discovery::discover(project_dir, &manifest);
+
+    let mut paths: Vec<PathBuf> = discovered
+        .main_sources
+        .iter()
+        .flat_map(|ss| ss.kotlin_dirs.iter().chain(ss.resource_dirs.iter()))
+        .filter(|d| d.is_dir())
+        .cloned()
+        .collect();
+    paths.push(project_dir.join("Kargo.toml"));
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    roots: &[PathBuf],
+    generation: u64,
+) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 8192];
+    let mut len = 0;
+    while len < buf.len() {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => respond(roots, path, generation),
+        _ => (405, "text/plain", b"method not allowed".to_vec()),
+    };
+
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    let header = format!(
+        "HTTP/1.1 {status} {reason}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-store\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+/// Produce `(status, content type, body)` for a GET of `path`, served from
+/// the first of `roots` that has the file.
+fn respond(roots: &[PathBuf], path: &str, generation: u64) -> (u16, &'static str, Vec<u8>) {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    if path == RELOAD_PATH {
+        return (200, "text/plain", generation.to_string().into_bytes());
+    }
+
+    let relative = path.trim_start_matches('/');
+    let relative = if relative.is_empty() || relative.ends_with('/') {
+        format!("{relative}index.html")
+    } else {
+        relative.to_string()
+    };
+    if relative
+        .split('/')
+        .any(|seg| seg == ".." || seg.contains('\\'))
+    {
+        return (404, "text/plain", b"not found".to_vec());
+    }
+
+    let Some((file, mut body)) = roots.iter().find_map(|root| {
+        let file = root.join(&relative);
+        std::fs::read(&file).ok().map(|body| (file, body))
+    }) else {
+        return (404, "text/plain", b"not found".to_vec());
+    };
+    let content_type = content_type(&file);
+    if content_type.starts_with("text/html") {
+        body = inject_reload_script(&String::from_utf8_lossy(&body), generation).into_bytes();
+    }
+    (200, content_type, body)
+}
+
+pub(crate) fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "mjs" | "js" => "text/javascript; charset=utf-8",
+        "wasm" => "application/wasm",
+        "json" | "map" => "application/json",
+        "css" => "text/css; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "ico" => "image/x-icon",
+        "txt" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Add a script that reloads the page once the build generation moves past
+/// `generation`.
+fn inject_reload_script(html: &str, generation: u64) -> String {
+    let script = format!(
+        "<script>setInterval(async () => {{ try {{ \
+         const r = await fetch('{RELOAD_PATH}'); \
+         if ((await r.text()) !== '{generation}') location.reload(); \
+         }} catch (_) {{}} }}, 1000);</script>"
+    );
+    match html.rfind("</body>") {
+        Some(i) => format!("{}{script}\n{}", &html[..i], &html[i..]),
+        None => format!("{html}{script}\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_copies_outputs_and_resources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        let resources = tmp.path().join("resources");
+        std::fs::create_dir_all(classes.join("klib")).unwrap();
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::write(classes.join("demo.wasm"), b"\0asm").unwrap();
+        std::fs::write(classes.join("demo.mjs"), "export {}").unwrap();
+        std::fs::write(classes.join("klib/demo.klib"), b"").unwrap();
+        std::fs::write(resources.join("style.css"), "body {}").unwrap();
+        let node_modules = tmp.path().join("node_modules");
+        std::fs::create_dir_all(node_modules.join("left-pad")).unwrap();
+        std::fs::write(
+            node_modules.join("left-pad/package.json"),
+            r#"{"version":"1.3.0","main":"index.js"}"#,
+        )
+        .unwrap();
+        let packages = [LockedNpmPackage {
+            name: "left-pad".into(),
+            requested: "^1.3.0".into(),
+            version: "1.3.0".into(),
+            members: Vec::new(),
+        }];
+        let npm = NpmBundle {
+            packages: &packages,
+            node_modules: &node_modules,
+        };
+
+        let bundle = tmp.path().join("output/demo-0.1.0");
+        let written = write_bundle(&classes, &resources, &bundle, "demo", "0.1.0", &npm).unwrap();
+        assert_eq!(written.as_deref(), Some(bundle.as_path()));
+
+        assert!(bundle.join("demo.wasm").is_file());
+        assert!(bundle.join("style.css").is_file());
+        assert!(!bundle.join("klib").exists());
+        let index = std::fs::read_to_string(bundle.join("index.html")).unwrap();
+        assert!(index.contains("src=\"./demo.mjs\""));
+        assert!(index.contains(r#""left-pad":"./node_modules/left-pad/index.js""#));
+        let pkg: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(bundle.join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(pkg["main"], "./demo.mjs");
+        assert_eq!(pkg["type"], "module");
+        assert_eq!(pkg["dependencies"]["left-pad"], "1.3.0");
+    }
+
+    #[test]
+    fn bundle_requires_linked_module() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bundle = tmp.path().join("out");
+        let npm = NpmBundle {
+            packages: &[],
+            node_modules: tmp.path(),
+        };
+        let written = write_bundle(
+            tmp.path(),
+            &tmp.path().join("res"),
+            &bundle,
+            "demo",
+            "1.0",
+            &npm,
+        )
+        .unwrap();
+        assert!(written.is_none());
+        assert!(!bundle.exists());
+    }
+
+    #[test]
+    fn respond_serves_files_and_reload_generation() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("index.html"), "<html><body></body></html>").unwrap();
+        std::fs::write(tmp.path().join("demo.wasm"), b"\0asm").unwrap();
+        let npm_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(npm_dir.path().join("node_modules/a")).unwrap();
+        std::fs::write(npm_dir.path().join("node_modules/a/index.js"), "").unwrap();
+        let roots = [tmp.path().to_path_buf(), npm_dir.path().to_path_buf()];
+
+        let (status, ty, body) = respond(&roots, "/", 3);
+        assert_eq!(status, 200);
+        assert!(ty.starts_with("text/html"));
+        let html = String::from_utf8(body).unwrap();
+        assert!(html.contains(RELOAD_PATH));
+        assert!(html.contains("!== '3'"));
+        assert!(html.ends_with("</body></html>"));
+
+        let (status, ty, _) = respond(&roots, "/demo.wasm?v=1", 0);
+        assert_eq!((status, ty), (200, "application/wasm"));
+
+        let (_, _, body) = respond(&roots, RELOAD_PATH, 7);
+        assert_eq!(body, b"7");
+
+        let (status, ty, _) = respond(&roots, "/node_modules/a/index.js", 0);
+        assert_eq!((status, ty), (200, "text/javascript; charset=utf-8"));
+
+        assert_eq!(respond(&roots, "/../secret", 0).0, 404);
+        assert_eq!(respond(&roots, "/missing.js", 0).0, 404);
+    }
+}
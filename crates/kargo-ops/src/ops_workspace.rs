@@ -0,0 +1,892 @@
+//! Operation: workspace-wide builds and dependency report (`kargo build` at
+//! a workspace root or with `-p <member>`, `kargo workspace deps`).
+//!
+//! Members are built in dependency order: a member named by another's
+//! `{ module = "..." }` dependency is built first. A member's own build also
+//! builds the members its `[dependencies]` name this way and compiles and
+//! runs against their classes. All members share the
+//! `Kargo.lock` at the workspace root, where each package lists the members
+//! using it.
+//!
+//! Collects the Maven dependencies every member declares and groups them by
+//! `group:artifact`. A dependency is inconsistent when members declare it at
+//! more than one version. `--fix` hoists each inconsistent dependency into
+//! `[workspace.catalog.libraries]` of the workspace root at the highest
+//! declared version and points the members at it with `{ catalog = "..." }`.
+//! Declarations that carry settings a catalog reference cannot express
+//! (scope, exclusions, classifier, ...) or that go through the member's own
+//! `[catalog]` are reported and left alone.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use kargo_compiler::classpath::Classpath;
+use kargo_core::dependency::{Dependency, MavenCoordinate};
+use kargo_core::manifest::{CatalogConfig, Manifest, WorkspaceConfig};
+use kargo_core::target::KotlinTarget;
+use kargo_core::workspace;
+use kargo_resolver::version::MavenVersion;
+use kargo_util::errors::KargoError;
+use kargo_util::progress::{note, status, status_warn};
+use toml_edit::{DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::ops_build::{self, BuildOptions, BuildResult};
+
+/// Options for `kargo workspace deps`.
+#[derive(Default)]
+pub struct DepsOptions {
+    /// Only report dependencies declared at diverging versions.
+    pub inconsistent: bool,
+    /// Hoist inconsistent dependencies into the workspace catalog.
+    pub fix: bool,
+}
+
+/// One member's declaration of a dependency.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    /// Package name of the member.
+    pub member: String,
+    pub manifest_path: PathBuf,
+    /// Manifest section, e.g. `dev-dependencies` or `target.jvm`.
+    pub section: String,
+    /// Key of the dependency within its section.
+    pub key: String,
+    pub version: String,
+    pub kind: DeclarationKind,
+}
+
+/// How a dependency is declared, which decides whether `--fix` can rewrite it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeclarationKind {
+    /// `group:artifact:version`, or the detailed form with nothing else set.
+    Version,
+    /// The detailed form with a scope, exclusions, classifier or the like.
+    Detailed,
+    /// A catalog reference; `inherited` when it resolves through
+    /// `[workspace.catalog]` rather than the member's own `[catalog]`.
+    Catalog { alias: String, inherited: bool },
+}
+
+/// Print the dependencies of the workspace enclosing `cwd`; with
+/// `--inconsistent`, only those declared at diverging versions.
+pub fn deps(cwd: &Path, opts: &DepsOptions) -> miette::Result<()> {
+    let (root, _) = workspace::find_root(cwd).ok_or_else(|| KargoError::Manifest {
+        message: format!("no [workspace] found at or above {}", cwd.display()),
+    })?;
+    let declared = declarations(&root)?;
+    let inconsistent = inconsistent(&declared);
+
+    let shown: Vec<(&(String, String), &Vec<Declaration>)> = if opts.inconsistent || opts.fix {
+        inconsistent.iter().map(|(k, v)| (*k, *v)).collect()
+    } else {
+        declared.iter().collect()
+    };
+    for ((group, artifact), decls) in &shown {
+        println!("{group}:{artifact}");
+        for (version, users) in by_version(decls) {
+            println!("    {version:<15} {}", users.join(", "));
+        }
+    }
+
+    if inconsistent.is_empty() {
+        status("Finished", "all members agree on dependency versions");
+        return Ok(());
+    }
+    if opts.fix {
+        return fix(&root, &inconsistent);
+    }
+    if opts.inconsistent {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{} dependencies are declared at diverging versions \
+                 (run `kargo workspace deps --fix` to hoist them into [workspace.catalog])",
+                inconsistent.len()
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Every Maven dependency declared by a member of the workspace at `root`,
+/// keyed by `(group, artifact)`.
+pub fn declarations(root: &Path) -> miette::Result<BTreeMap<(String, String), Vec<Declaration>>> {
+    let (_, config) = workspace::find_root(root).ok_or_else(|| KargoError::Manifest {
+        message: format!("no [workspace] found in {}", root.display()),
+    })?;
+    let mut declared: BTreeMap<(String, String), Vec<Declaration>> = BTreeMap::new();
+    for dir in workspace::member_dirs(root, &config) {
+        let manifest_path = dir.join("Kargo.toml");
+        let manifest = Manifest::from_path(&manifest_path)?;
+        // The member's own catalog, before the workspace catalog is merged in.
+        #[derive(serde::Deserialize)]
+        struct Own {
+            catalog: Option<CatalogConfig>,
+        }
+        let own_catalog = toml::from_str::<Own>(&read(&manifest_path)?)
+            .ok()
+            .and_then(|own| own.catalog)
+            .unwrap_or_default();
+
+        for (section, deps) in sections(&manifest) {
+            for (key, dep) in deps {
+                let Some((coord, kind)) = classify(dep, &manifest, &own_catalog) else {
+                    continue;
+                };
+                declared
+                    .entry((coord.group_id, coord.artifact_id))
+                    .or_default()
+                    .push(Declaration {
+                        member: manifest.package.name.clone(),
+                        manifest_path: manifest_path.clone(),
+                        section: section.clone(),
+                        key: key.clone(),
+                        version: coord.version,
+                        kind,
+                    });
+            }
+        }
+    }
+    Ok(declared)
+}
+
+/// The entries of `declared` with more than one distinct version.
+pub fn inconsistent(
+    declared: &BTreeMap<(String, String), Vec<Declaration>>,
+) -> BTreeMap<&(String, String), &Vec<Declaration>> {
+    declared
+        .iter()
+        .filter(|(_, decls)| decls.iter().any(|d| d.version != decls[0].version))
+        .collect()
+}
+
+/// Hoist each of `inconsistent` into the workspace catalog at `root`.
+fn fix(
+    root: &Path,
+    inconsistent: &BTreeMap<&(String, String), &Vec<Declaration>>,
+) -> miette::Result<()> {
+    let root_manifest = root.join("Kargo.toml");
+    let mut docs: BTreeMap<PathBuf, DocumentMut> = BTreeMap::new();
+    docs.insert(root_manifest.clone(), parse(&root_manifest)?);
+
+    for ((group, artifact), decls) in inconsistent {
+        let version = decls
+            .iter()
+            .map(|d| d.version.as_str())
+            .max_by_key(|v| MavenVersion::parse(v))
+            .unwrap_or_default()
+            .to_string();
+        let alias = hoist(
+            docs.get_mut(&root_manifest)
+                .expect("root manifest is loaded"),
+            group,
+            artifact,
+            &version,
+        );
+        status(
+            "Hoisting",
+            &format!("{group}:{artifact} {version} as catalog `{alias}`"),
+        );
+
+        for decl in decls.iter() {
+            let rewritable = match &decl.kind {
+                DeclarationKind::Version => true,
+                DeclarationKind::Catalog { inherited, .. } => *inherited,
+                DeclarationKind::Detailed => false,
+            };
+            if !docs.contains_key(&decl.manifest_path) {
+                docs.insert(decl.manifest_path.clone(), parse(&decl.manifest_path)?);
+            }
+            let doc = docs.get_mut(&decl.manifest_path).expect("member is loaded");
+            let shadowed = doc
+                .get("catalog")
+                .and_then(|c| c.get("libraries"))
+                .and_then(|l| l.get(&alias))
+                .is_some();
+            if !rewritable || shadowed {
+                status_warn(
+                    "Skipping",
+                    &format!(
+                        "{} [{}] {} {}: {}",
+                        decl.member,
+                        decl.section,
+                        decl.key,
+                        decl.version,
+                        if shadowed {
+                            format!("its own [catalog] defines `{alias}`")
+                        } else {
+                            "declared with settings a catalog reference cannot carry".into()
+                        }
+                    ),
+                );
+                continue;
+            }
+            let mut reference = InlineTable::new();
+            reference.insert("catalog", Value::from(alias.as_str()));
+            section_table(doc, &decl.section)[&decl.key] =
+                Item::Value(Value::InlineTable(reference));
+        }
+    }
+
+    for (path, doc) in docs {
+        std::fs::write(&path, doc.to_string()).map_err(KargoError::Io)?;
+    }
+    note("Members now inherit these versions from [workspace.catalog].");
+    Ok(())
+}
+
+/// Add `group:artifact:version` to `[workspace.catalog.libraries]` of `doc`
+/// and return its alias. An existing entry for the same coordinates is
+/// reused and moved to `version`.
+fn hoist(doc: &mut DocumentMut, group: &str, artifact: &str, version: &str) -> String {
+    let catalog = implicit_table(doc.as_table_mut(), "workspace");
+    let catalog = implicit_table(catalog, "catalog");
+    let existing = catalog
+        .get("libraries")
+        .and_then(Item::as_table_like)
+        .and_then(|libs| {
+            libs.iter().find_map(|(alias, lib)| {
+                let same = lib.get("group").and_then(Item::as_str) == Some(group)
+                    && lib.get("artifact").and_then(Item::as_str) == Some(artifact);
+                same.then(|| {
+                    (
+                        alias.to_string(),
+                        lib.get("version.ref")
+                            .and_then(Item::as_str)
+                            .map(String::from),
+                    )
+                })
+            })
+        });
+    if let Some((alias, version_ref)) = existing {
+        match version_ref {
+            Some(name) => implicit_table(catalog, "versions")[&name] = toml_edit::value(version),
+            None => catalog["libraries"][&alias]["version"] = toml_edit::value(version),
+        }
+        return alias;
+    }
+
+    let libraries = implicit_table(catalog, "libraries");
+    libraries.set_implicit(false);
+    let alias = if libraries.contains_key(artifact) {
+        format!("{}-{artifact}", group.replace('.', "-"))
+    } else {
+        artifact.to_string()
+    };
+    let mut library = InlineTable::new();
+    library.insert("group", Value::from(group));
+    library.insert("artifact", Value::from(artifact));
+    library.insert("version", Value::from(version));
+    libraries.insert(&alias, Item::Value(Value::InlineTable(library)));
+    alias
+}
+
+/// The dependency sections of `manifest` with their labels.
+fn sections(manifest: &Manifest) -> Vec<(String, &BTreeMap<String, Dependency>)> {
+    let mut sections = vec![
+        ("dependencies".to_string(), &manifest.dependencies),
+        ("dev-dependencies".to_string(), &manifest.dev_dependencies),
+        (
+            "integration-test-dependencies".to_string(),
+            &manifest.integration_test_dependencies,
+        ),
+    ];
+    for (target, deps) in &manifest.target {
+        sections.push((format!("target.{target}"), &deps.dependencies));
+    }
+    sections
+}
+
+/// The table of `doc` holding the dependencies of `section`.
+fn section_table<'a>(doc: &'a mut DocumentMut, section: &str) -> &'a mut Table {
+    let table = doc.as_table_mut();
+    match section.strip_prefix("target.") {
+        Some(target) => {
+            let targets = implicit_table(table, "target");
+            let target = implicit_table(targets, target);
+            implicit_table(target, "dependencies")
+        }
+        None => implicit_table(table, section),
+    }
+}
+
+/// The sub-table `key` of `table`, created as an implicit table if missing.
+fn implicit_table<'a>(table: &'a mut Table, key: &str) -> &'a mut Table {
+    let item = table.entry(key).or_insert_with(|| {
+        let mut t = Table::new();
+        t.set_implicit(true);
+        Item::Table(t)
+    });
+    if let Item::Value(Value::InlineTable(inline)) = item {
+        *item = Item::Table(std::mem::take(inline).into_table());
+    }
+    item.as_table_mut().expect("manifest section is a table")
+}
+
+/// The coordinates of `dep` and how it is declared; `None` for module
+/// dependencies and unresolvable catalog references.
+fn classify(
+    dep: &Dependency,
+    manifest: &Manifest,
+    own_catalog: &CatalogConfig,
+) -> Option<(MavenCoordinate, DeclarationKind)> {
+    match dep {
+        Dependency::Short(s) => Some((MavenCoordinate::parse(s)?, DeclarationKind::Version)),
+        Dependency::Detailed(d) => {
+            let plain = d.scope.is_none()
+                && !d.optional
+                && d.exclusions.is_empty()
+                && d.classifier.is_none()
+                && d.include_optional.is_empty();
+            let coord = MavenCoordinate {
+                group_id: d.group.clone(),
+                artifact_id: d.artifact.clone(),
+                version: d.version.clone(),
+            };
+            let kind = if plain {
+                DeclarationKind::Version
+            } else {
+                DeclarationKind::Detailed
+            };
+            Some((coord, kind))
+        }
+        Dependency::Catalog(c) if !c.bundle => {
+            let catalog = manifest.catalog.as_ref()?;
+            let (_, entry) = kargo_core::version_catalog::resolve_catalog(catalog)
+                .into_iter()
+                .find(|(name, _)| *name == c.catalog)?;
+            let coord = MavenCoordinate {
+                group_id: entry.group,
+                artifact_id: entry.artifact,
+                version: entry.version,
+            };
+            let kind = DeclarationKind::Catalog {
+                alias: c.catalog.clone(),
+                inherited: !own_catalog.libraries.contains_key(&c.catalog),
+            };
+            Some((coord, kind))
+        }
+        Dependency::Catalog(_) | Dependency::Module(_) | Dependency::File(_) => None,
+    }
+}
+
+/// Members using each version of a dependency, highest version first.
+fn by_version(decls: &[Declaration]) -> Vec<(String, Vec<String>)> {
+    let mut versions: BTreeMap<MavenVersion, (String, Vec<String>)> = BTreeMap::new();
+    for d in decls {
+        let entry = versions
+            .entry(MavenVersion::parse(&d.version))
+            .or_insert_with(|| (d.version.clone(), Vec::new()));
+        entry.1.push(format!("{} ({})", d.member, d.section));
+    }
+    versions.into_values().rev().collect()
+}
+
+/// The members of a workspace and the module dependencies between them.
+pub struct MemberGraph {
+    /// Member directories, with symlinks resolved, and their manifests in
+    /// workspace order.
+    pub members: Vec<(PathBuf, Manifest)>,
+    /// For each member, the indices of the members it depends on through
+    /// `{ module = "..." }`.
+    pub depends_on: Vec<BTreeSet<usize>>,
+}
+
+impl MemberGraph {
+    /// Load the members of the workspace at `root`.
+    pub fn load(root: &Path, config: &WorkspaceConfig) -> miette::Result<Self> {
+        let root = canonical(root);
+        let mut members = Vec::new();
+        for dir in workspace::member_dirs(&root, config) {
+            let manifest = Manifest::from_path(&dir.join("Kargo.toml"))?;
+            members.push((canonical(&dir), manifest));
+        }
+        let mut depends_on: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); members.len()];
+        for (i, (_, manifest)) in members.iter().enumerate() {
+            for module in module_dependencies(manifest) {
+                let Some(dir) = workspace::find_member(&root, config, module) else {
+                    continue;
+                };
+                if let Some(j) = members.iter().position(|(d, _)| *d == canonical(&dir)) {
+                    depends_on[i].insert(j);
+                }
+            }
+        }
+        Ok(Self {
+            members,
+            depends_on,
+        })
+    }
+
+    /// The `selected` members, dependencies before their dependents.
+    /// Members in a module cycle keep workspace order.
+    pub fn order(&self, selected: &BTreeSet<usize>) -> Vec<usize> {
+        fn visit(
+            i: usize,
+            depends_on: &[BTreeSet<usize>],
+            visiting: &mut BTreeSet<usize>,
+            order: &mut Vec<usize>,
+        ) {
+            if order.contains(&i) || !visiting.insert(i) {
+                return;
+            }
+            for &dep in &depends_on[i] {
+                visit(dep, depends_on, visiting, order);
+            }
+            order.push(i);
+        }
+        let mut order = Vec::new();
+        let mut visiting = BTreeSet::new();
+        for &i in selected {
+            visit(i, &self.depends_on, &mut visiting, &mut order);
+        }
+        order.retain(|i| selected.contains(i));
+        order
+    }
+
+    /// Index of the member named `name`: its package name, or its
+    /// directory relative to the workspace root.
+    fn find(&self, root: &Path, config: &WorkspaceConfig, name: &str) -> Option<usize> {
+        let dir = canonical(&workspace::find_member(root, config, name)?);
+        self.members.iter().position(|(d, _)| *d == dir)
+    }
+}
+
+/// Build the members `packages` names (every member when empty) of the
+/// workspace enclosing `cwd`, in dependency order. Stops after the first
+/// member that fails to compile; its result is the last one returned.
+pub async fn build(
+    cwd: &Path,
+    packages: &[String],
+    opts: &BuildOptions,
+) -> miette::Result<Vec<BuildResult>> {
+    let (root, config) = workspace::find_root(cwd).ok_or_else(|| KargoError::Manifest {
+        message: format!(
+            "-p selects a workspace member, but no [workspace] was found at or above {}",
+            cwd.display()
+        ),
+    })?;
+    let graph = MemberGraph::load(&root, &config)?;
+    let selected: BTreeSet<usize> = if packages.is_empty() {
+        (0..graph.members.len()).collect()
+    } else {
+        packages
+            .iter()
+            .map(|name| {
+                graph.find(&root, &config, name).ok_or_else(|| {
+                    let names: Vec<&str> = graph
+                        .members
+                        .iter()
+                        .map(|(_, m)| m.package.name.as_str())
+                        .collect();
+                    KargoError::Manifest {
+                        message: format!(
+                            "package '{name}' is not a member of the workspace at {} \
+                             (members: {})",
+                            root.display(),
+                            names.join(", ")
+                        ),
+                    }
+                })
+            })
+            .collect::<Result<_, _>>()?
+    };
+    if selected.is_empty() {
+        return Err(KargoError::Manifest {
+            message: format!("the workspace at {} has no members", root.display()),
+        }
+        .into());
+    }
+
+    let order = graph.order(&selected);
+    if order.len() > 1 {
+        let names: Vec<&str> = order
+            .iter()
+            .map(|&i| graph.members[i].1.package.name.as_str())
+            .collect();
+        status("Workspace", &format!("building {}", names.join(", ")));
+    }
+    let mut results = Vec::new();
+    for i in order {
+        let result = ops_build::build(&graph.members[i].0, opts).await?;
+        let success = result.success;
+        results.push(result);
+        if !success {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+/// Build the members named by `{ module = "..." }` entries in
+/// `[dependencies]` for the target and profile of `ctx`, and put their
+/// outputs on its classpaths: each member's classes and resources on all of
+/// them, its compile dependencies on the compile classpaths and its runtime
+/// dependencies on the runtime ones.
+pub(crate) async fn add_module_dependencies(
+    ctx: &mut crate::BuildContext,
+    verbose: bool,
+) -> miette::Result<()> {
+    let modules = crate::ops_fixtures::module_dependencies(&ctx.manifest.dependencies);
+    let Some(first) = modules.first() else {
+        return Ok(());
+    };
+    if !matches!(ctx.target, KotlinTarget::Jvm | KotlinTarget::Android) {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "module dependency '{}' in [dependencies]: workspace module dependencies \
+                 are only supported for the jvm and android targets, not {}",
+                first.module, ctx.target
+            ),
+        }
+        .into());
+    }
+    let (root, config) = module_workspace(&ctx.project_dir, &first.module)?;
+    if let Some(cycle) = module_cycle(&root, &config, &ctx.project_dir)? {
+        return Err(KargoError::Manifest {
+            message: format!(
+                "workspace module dependencies form a cycle: {} -> {}",
+                ctx.manifest.package.name,
+                cycle.join(" -> ")
+            ),
+        }
+        .into());
+    }
+
+    for module in modules {
+        let (_, member) = build_member(
+            &root,
+            &config,
+            &ctx.project_dir,
+            &module.module,
+            ctx.target,
+            &ctx.profile_name,
+            verbose,
+        )
+        .await?;
+        add_member_outputs(&mut ctx.classpath, &member);
+    }
+    Ok(())
+}
+
+/// The workspace a module dependency named `module` of the project in
+/// `project_dir` refers to.
+pub(crate) fn module_workspace(
+    project_dir: &Path,
+    module: &str,
+) -> miette::Result<(PathBuf, WorkspaceConfig)> {
+    workspace::find_root(project_dir).ok_or_else(|| {
+        KargoError::Manifest {
+            message: format!(
+                "module dependency '{module}' requires a workspace, but no [workspace] was \
+                 found above {}",
+                project_dir.display()
+            ),
+        }
+        .into()
+    })
+}
+
+/// Build the member `module` names for `target`/`profile` on behalf of the
+/// project in `project_dir`, returning its directory and build result.
+/// Fails if the member does not compile.
+pub(crate) async fn build_member(
+    root: &Path,
+    config: &WorkspaceConfig,
+    project_dir: &Path,
+    module: &str,
+    target: KotlinTarget,
+    profile: &str,
+    verbose: bool,
+) -> miette::Result<(PathBuf, BuildResult)> {
+    let member_dir =
+        workspace::find_member(root, config, module).ok_or_else(|| KargoError::Manifest {
+            message: format!(
+                "module dependency '{module}' is not a member of the workspace at {}",
+                root.display()
+            ),
+        })?;
+    if canonical(&member_dir) == canonical(project_dir) {
+        return Err(KargoError::Manifest {
+            message: format!("module dependency '{module}' refers to itself"),
+        }
+        .into());
+    }
+
+    // Boxed: building a member builds its own module dependencies first.
+    let member = Box::pin(ops_build::build(
+        &member_dir,
+        &BuildOptions {
+            target: Some(target.kebab_name().to_string()),
+            profile: Some(profile.to_string()),
+            verbose,
+            quiet: true,
+            ..Default::default()
+        },
+    ))
+    .await?;
+    if !member.success {
+        return Err(KargoError::Generic {
+            message: format!("module dependency '{module}' failed to compile"),
+        }
+        .into());
+    }
+    Ok((member_dir, member))
+}
+
+/// Add the outputs of the built workspace member `member` to `classpath`.
+fn add_member_outputs(classpath: &mut Classpath, member: &BuildResult) {
+    let mut outputs = vec![member.classes_dir.clone()];
+    if member.resources_dir.is_dir() {
+        outputs.push(member.resources_dir.clone());
+    }
+    let add = |into: &mut Vec<PathBuf>, jars: &[PathBuf]| {
+        for path in outputs.iter().chain(jars) {
+            if !into.contains(path) {
+                into.push(path.clone());
+            }
+        }
+    };
+    let compile = &member.classpath.compile_jars;
+    let runtime = &member.classpath.runtime_jars;
+    add(&mut classpath.compile_jars, compile);
+    add(&mut classpath.runtime_jars, runtime);
+    add(&mut classpath.test_jars, compile);
+    add(&mut classpath.test_runtime_jars, runtime);
+    add(&mut classpath.integration_test_jars, compile);
+    add(&mut classpath.integration_test_runtime_jars, runtime);
+}
+
+/// The chain of `[dependencies]` module names leading from the member in
+/// `project_dir` back to itself, if there is one.
+fn module_cycle(
+    root: &Path,
+    config: &WorkspaceConfig,
+    project_dir: &Path,
+) -> miette::Result<Option<Vec<String>>> {
+    fn visit(
+        root: &Path,
+        config: &WorkspaceConfig,
+        dir: &Path,
+        start: &Path,
+        visited: &mut BTreeSet<PathBuf>,
+        chain: &mut Vec<String>,
+    ) -> miette::Result<bool> {
+        let manifest = Manifest::from_path(&dir.join("Kargo.toml"))?;
+        for module in crate::ops_fixtures::module_dependencies(&manifest.dependencies) {
+            let Some(dep) = workspace::find_member(root, config, &module.module) else {
+                continue;
+            };
+            let dep = canonical(&dep);
+            chain.push(module.module.clone());
+            if dep == start
+                || (visited.insert(dep.clone())
+                    && visit(root, config, &dep, start, visited, chain)?)
+            {
+                return Ok(true);
+            }
+            chain.pop();
+        }
+        Ok(false)
+    }
+    let start = canonical(project_dir);
+    let mut chain = Vec::new();
+    let found = visit(
+        root,
+        config,
+        &start,
+        &start,
+        &mut BTreeSet::new(),
+        &mut chain,
+    )?;
+    Ok(found.then_some(chain))
+}
+
+/// Members named by `{ module = "..." }` dependencies of `manifest`.
+fn module_dependencies(manifest: &Manifest) -> impl Iterator<Item = &str> {
+    manifest
+        .dependencies
+        .values()
+        .chain(manifest.dev_dependencies.values())
+        .chain(manifest.integration_test_dependencies.values())
+        .filter_map(|dep| match dep {
+            Dependency::Module(m) => Some(m.module.as_str()),
+            _ => None,
+        })
+}
+
+/// `path` with symlinks resolved where it exists, so paths from git and
+/// from the workspace compare equal. Deleted files keep their parent's
+/// resolution.
+pub(crate) fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => canonical(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn read(path: &Path) -> miette::Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        KargoError::Manifest {
+            message: format!("Failed to read {}: {e}", path.display()),
+        }
+        .into()
+    })
+}
+
+fn parse(path: &Path) -> miette::Result<DocumentMut> {
+    read(path)?.parse().map_err(|e| {
+        KargoError::Manifest {
+            message: format!("Failed to parse {}: {e}", path.display()),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(root: &Path, dir: &str, body: &str) {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(
+            root.join(dir).join("Kargo.toml"),
+            format!(
+                "[package]\nname = \"{dir}\"\nversion = \"0.1.0\"\nkotlin = \"2.3.0\"\n\n{body}"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn members_are_ordered_after_the_members_they_depend_on() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"util\"]\n",
+        )
+        .unwrap();
+        member(
+            root,
+            "app",
+            "[dev-dependencies]\ncore = { module = \"core\" }\n",
+        );
+        member(
+            root,
+            "core",
+            "[integration-test-dependencies]\nutil = { module = \"util\" }\n",
+        );
+        member(root, "util", "");
+        let (_, config) = workspace::find_root(root).unwrap();
+        let graph = MemberGraph::load(root, &config).unwrap();
+        let names = |order: Vec<usize>| -> Vec<String> {
+            order
+                .into_iter()
+                .map(|i| graph.members[i].1.package.name.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            names(graph.order(&(0..3).collect())),
+            ["util", "core", "app"]
+        );
+        // Only the selected members, still in dependency order.
+        assert_eq!(names(graph.order(&[0, 2].into())), ["util", "app"]);
+        assert_eq!(graph.find(root, &config, "core"), Some(1));
+        assert_eq!(graph.find(root, &config, "missing"), None);
+    }
+
+    #[test]
+    fn main_module_dependencies_order_members_and_reject_cycles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            "[workspace]\nmembers = [\"b\", \"a\", \"c\"]\n",
+        )
+        .unwrap();
+        member(root, "b", "[dependencies]\na = { module = \"a\" }\n");
+        member(root, "a", "");
+        member(root, "c", "[dependencies]\nb = { module = \"b\" }\n");
+        let (_, config) = workspace::find_root(root).unwrap();
+        let graph = MemberGraph::load(root, &config).unwrap();
+        let order: Vec<&str> = graph
+            .order(&(0..3).collect())
+            .into_iter()
+            .map(|i| graph.members[i].1.package.name.as_str())
+            .collect();
+        assert_eq!(order, ["a", "b", "c"]);
+        assert_eq!(module_cycle(root, &config, &root.join("c")).unwrap(), None);
+
+        member(root, "a", "[dependencies]\nc = { module = \"c\" }\n");
+        assert_eq!(
+            module_cycle(root, &config, &root.join("b")).unwrap(),
+            Some(vec!["a".to_string(), "c".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn diverging_versions_are_hoisted_into_the_workspace_catalog() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Kargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"legacy\"]\n",
+        )
+        .unwrap();
+        member(
+            root,
+            "app",
+            "[dependencies]\n\"kotlinx-coroutines-core\" = \"org.jetbrains.kotlinx:kotlinx-coroutines-core:1.8.0\"\nokio = \"com.squareup.okio:okio:3.9.0\"\n",
+        );
+        member(
+            root,
+            "core",
+            "[dev-dependencies]\ncoroutines = { group = \"org.jetbrains.kotlinx\", artifact = \"kotlinx-coroutines-core\", version = \"1.10.1\" }\n",
+        );
+        member(
+            root,
+            "legacy",
+            "[dependencies]\nokio = \"com.squareup.okio:okio:3.9.0\"\ncoroutines = { group = \"org.jetbrains.kotlinx\", artifact = \"kotlinx-coroutines-core\", version = \"1.7.3\", classifier = \"jvm\" }\n",
+        );
+
+        let declared = declarations(root).unwrap();
+        let found = inconsistent(&declared);
+        assert_eq!(found.len(), 1);
+        let coroutines = (
+            "org.jetbrains.kotlinx".to_string(),
+            "kotlinx-coroutines-core".to_string(),
+        );
+        assert_eq!(found[&coroutines].len(), 3);
+        assert_eq!(
+            by_version(found[&coroutines])[0],
+            (
+                "1.10.1".to_string(),
+                vec!["core (dev-dependencies)".to_string()]
+            )
+        );
+
+        fix(root, &found).unwrap();
+        let workspace = std::fs::read_to_string(root.join("Kargo.toml")).unwrap();
+        assert!(workspace.contains("[workspace.catalog.libraries]"));
+        assert!(workspace.contains(
+            "kotlinx-coroutines-core = { group = \"org.jetbrains.kotlinx\", artifact = \"kotlinx-coroutines-core\", version = \"1.10.1\" }"
+        ));
+        let core = std::fs::read_to_string(root.join("core/Kargo.toml")).unwrap();
+        assert!(core.contains("coroutines = { catalog = \"kotlinx-coroutines-core\" }"));
+        // The classifier cannot go through the catalog, so legacy keeps its pin.
+        let legacy = std::fs::read_to_string(root.join("legacy/Kargo.toml")).unwrap();
+        assert!(legacy.contains("version = \"1.7.3\", classifier = \"jvm\""));
+
+        let declared = declarations(root).unwrap();
+        let versions: Vec<&str> = declared[&coroutines]
+            .iter()
+            .map(|d| d.version.as_str())
+            .collect();
+        assert_eq!(versions, ["1.10.1", "1.10.1", "1.7.3"]);
+    }
+}
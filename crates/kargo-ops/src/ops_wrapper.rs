@@ -0,0 +1,43 @@
+//! Operation: generate `kargow` wrapper scripts.
+//!
+//! The wrapper scripts read the Kargo version pinned in `.kargo-version` and
+//! download that release on first run, so contributors and CI can build the
+//! project without installing Kargo themselves.
+
+use std::path::{Path, PathBuf};
+
+use kargo_util::errors::KargoError;
+
+/// File holding the Kargo version the wrapper scripts should run.
+pub const VERSION_FILE: &str = ".kargo-version";
+
+const KARGOW_SH: &str = include_str!("../wrapper/kargow");
+const KARGOW_BAT: &str = include_str!("../wrapper/kargow.bat");
+
+/// Write `kargow`, `kargow.bat`, and `.kargo-version` into `project_dir`.
+///
+/// Existing wrapper files are overwritten so re-running `kargo wrapper`
+/// upgrades both the scripts and the pinned version.
+pub fn generate(project_dir: &Path, kargo_version: &str) -> miette::Result<Vec<PathBuf>> {
+    let version = kargo_version.trim_start_matches('v');
+    semver::Version::parse(version).map_err(|e| KargoError::Generic {
+        message: format!("Invalid Kargo version '{kargo_version}': {e}"),
+    })?;
+
+    let sh = project_dir.join("kargow");
+    let bat = project_dir.join("kargow.bat");
+    let pin = project_dir.join(VERSION_FILE);
+
+    std::fs::write(&sh, KARGOW_SH).map_err(KargoError::Io)?;
+    std::fs::write(&bat, KARGOW_BAT.replace('\n', "\r\n")).map_err(KargoError::Io)?;
+    std::fs::write(&pin, format!("{version}\n")).map_err(KargoError::Io)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&sh, std::fs::Permissions::from_mode(0o755))
+            .map_err(KargoError::Io)?;
+    }
+
+    Ok(vec![sh, bat, pin])
+}
@@ -0,0 +1,516 @@
+//! Operation: let Xcode projects consume Kargo-built Kotlin frameworks.
+//!
+//! `kargo xcode integrate` writes two files next to the Xcode project:
+//!
+//! - `Kargo.xcconfig` points `FRAMEWORK_SEARCH_PATHS` at the framework Kargo
+//!   builds for each SDK and architecture, and links it;
+//! - `kargo-xcode.sh` is the Run Script build phase. It runs
+//!   `kargo xcode build`, which refreshes `Kargo.xcconfig` from `Kargo.toml`
+//!   and builds the targets matching the SDK and architectures Xcode is
+//!   building for.
+//!
+//! `kargo xcode xcframework` instead bundles the frameworks of every Apple
+//! target into one `<Name>.xcframework` (and a zip of it), the form that
+//! CocoaPods and Swift Package Manager distribute.
+
+use std::path::{Component, Path, PathBuf};
+
+use kargo_core::manifest::Manifest;
+use kargo_core::target::KotlinTarget;
+use kargo_util::errors::KargoError;
+use kargo_util::process::CommandBuilder;
+
+use crate::ops_build::{self, BuildOptions};
+
+/// Build settings file the Xcode project's configurations are based on.
+pub const XCCONFIG: &str = "Kargo.xcconfig";
+
+/// Script to add as a Run Script build phase.
+pub const BUILD_PHASE_SCRIPT: &str = "kargo-xcode.sh";
+
+/// Where a build for `target` puts `<FrameworkName>.framework`.
+pub fn framework_dir(project_dir: &Path, target: KotlinTarget, profile: &str) -> PathBuf {
+    project_dir
+        .join("build")
+        .join(target.kebab_name())
+        .join(profile)
+        .join("framework")
+}
+
+/// Where `kargo xcode xcframework` writes `<Name>.xcframework` and
+/// `<Name>.xcframework.zip`.
+pub fn xcframework_dir(project_dir: &Path, profile: &str) -> PathBuf {
+    project_dir.join("build").join("xcframework").join(profile)
+}
+
+/// The Xcode SDK (`PLATFORM_NAME`) and architecture an Apple target is
+/// built for.
+pub fn xcode_sdk(target: KotlinTarget) -> Option<(&'static str, &'static str)> {
+    Some(match target {
+        KotlinTarget::IosArm64 => ("iphoneos", "arm64"),
+        KotlinTarget::IosSimulatorArm64 => ("iphonesimulator", "arm64"),
+        KotlinTarget::IosX64 => ("iphonesimulator", "x86_64"),
+        KotlinTarget::MacosArm64 => ("macosx", "arm64"),
+        KotlinTarget::MacosX64 => ("macosx", "x86_64"),
+        KotlinTarget::TvosArm64 => ("appletvos", "arm64"),
+        KotlinTarget::TvosSimulatorArm64 => ("appletvsimulator", "arm64"),
+        KotlinTarget::WatchosArm64 => ("watchos", "arm64"),
+        KotlinTarget::WatchosSimulatorArm64 => ("watchsimulator", "arm64"),
+        _ => return None,
+    })
+}
+
+/// The `declared` targets Xcode needs when building for `platform_name`
+/// with the space-separated `archs`.
+pub fn targets_for_sdk(
+    declared: &[KotlinTarget],
+    platform_name: &str,
+    archs: &str,
+) -> Vec<KotlinTarget> {
+    declared
+        .iter()
+        .copied()
+        .filter(|t| {
+            xcode_sdk(*t).is_some_and(|(sdk, arch)| {
+                sdk == platform_name && archs.split_whitespace().any(|a| a == arch)
+            })
+        })
+        .collect()
+}
+
+/// Contents of `Kargo.xcconfig`. `project_rel` is the Kargo project
+/// relative to the Xcode project directory (`SRCROOT`).
+pub fn render_xcconfig(manifest: &Manifest, project_rel: &str) -> String {
+    let project_dir = if project_rel.is_empty() || project_rel == "." {
+        "$(SRCROOT)".to_string()
+    } else {
+        format!("$(SRCROOT)/{project_rel}")
+    };
+    let mut out = format!(
+        "// Generated by `kargo xcode integrate` and refreshed by every\n\
+         // `kargo xcode build`. Do not edit; change Kargo.toml instead.\n\
+         \n\
+         KARGO_PROJECT_DIR = {project_dir}\n\
+         KARGO_PROFILE = dev\n\
+         KARGO_PROFILE[config=Release] = release\n\
+         \n"
+    );
+    for target in manifest.declared_targets() {
+        let Some((sdk, arch)) = xcode_sdk(target) else {
+            continue;
+        };
+        out.push_str(&format!(
+            "FRAMEWORK_SEARCH_PATHS[sdk={sdk}*][arch={arch}] = $(inherited) \
+             $(KARGO_PROJECT_DIR)/build/{}/$(KARGO_PROFILE)/framework\n",
+            target.kebab_name()
+        ));
+    }
+    out.push_str(&format!(
+        "OTHER_LDFLAGS = $(inherited) -framework {}\n",
+        manifest.framework_name()
+    ));
+    out
+}
+
+/// Contents of the Run Script build phase. Prefers the project's `kargow`
+/// wrapper when there is one.
+pub fn render_script(project_rel: &str) -> String {
+    format!(
+        "#!/bin/sh\n\
+         # Generated by `kargo xcode integrate`. Add a Run Script build phase,\n\
+         # before \"Link Binary With Libraries\", that runs:\n\
+         #   \"$SRCROOT/{BUILD_PHASE_SCRIPT}\"\n\
+         set -e\n\
+         export PATH=\"$HOME/.kargo/bin:$HOME/.cargo/bin:$PATH\"\n\
+         cd \"$SRCROOT/{project_rel}\"\n\
+         if [ -z \"$KARGO\" ] && [ -x ./kargow ]; then\n\
+         \x20   KARGO=./kargow\n\
+         fi\n\
+         exec \"${{KARGO:-kargo}}\" xcode build\n"
+    )
+}
+
+/// Write `Kargo.xcconfig` and the build phase script into `xcode_dir`.
+pub fn integrate(project_dir: &Path, xcode_dir: &Path) -> miette::Result<Vec<PathBuf>> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    if !manifest.declared_targets().iter().any(|t| t.is_apple()) {
+        return Err(KargoError::Generic {
+            message: "No Apple target in [targets]. Add one first, e.g. `ios-arm64 = {}` \
+                      and `ios-simulator-arm64 = {}`."
+                .into(),
+        }
+        .into());
+    }
+    std::fs::create_dir_all(xcode_dir).map_err(KargoError::Io)?;
+    let rel = project_rel(xcode_dir, project_dir)?;
+
+    let xcconfig = xcode_dir.join(XCCONFIG);
+    let script = xcode_dir.join(BUILD_PHASE_SCRIPT);
+    std::fs::write(&xcconfig, render_xcconfig(&manifest, &rel)).map_err(KargoError::Io)?;
+    std::fs::write(&script, render_script(&rel)).map_err(KargoError::Io)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+            .map_err(KargoError::Io)?;
+    }
+
+    Ok(vec![xcconfig, script])
+}
+
+/// `kargo xcode build`, run by the build phase script: bring
+/// `Kargo.xcconfig` up to date and build the frameworks for the SDK and
+/// architectures in Xcode's environment (`PLATFORM_NAME`, `ARCHS`,
+/// `KARGO_PROFILE` or `CONFIGURATION`, `SRCROOT`).
+pub async fn build(project_dir: &Path, verbose: bool) -> miette::Result<()> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let platform = env("PLATFORM_NAME").ok_or_else(|| KargoError::Generic {
+        message: "`kargo xcode build` runs from an Xcode build phase (PLATFORM_NAME is not set). \
+                  Use `kargo build --target <target>` to build a framework directly."
+            .into(),
+    })?;
+    let archs = env("ARCHS").unwrap_or_else(|| "arm64".into());
+    let profile = env("KARGO_PROFILE").unwrap_or_else(|| {
+        match env("CONFIGURATION").as_deref() {
+            Some("Release") => "release",
+            _ => "dev",
+        }
+        .to_string()
+    });
+
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    if let Some(srcroot) = env("SRCROOT") {
+        let xcode_dir = PathBuf::from(srcroot);
+        let rel = project_rel(&xcode_dir, project_dir)?;
+        let xcconfig = xcode_dir.join(XCCONFIG);
+        let content = render_xcconfig(&manifest, &rel);
+        if std::fs::read_to_string(&xcconfig).ok().as_deref() != Some(content.as_str()) {
+            std::fs::write(&xcconfig, content).map_err(KargoError::Io)?;
+            kargo_util::progress::status("Updated", &xcconfig.display().to_string());
+        }
+    }
+
+    let targets = targets_for_sdk(&manifest.declared_targets(), &platform, &archs);
+    if targets.is_empty() {
+        let wanted: Vec<&str> = KotlinTarget::ALL
+            .iter()
+            .filter(|t| targets_for_sdk(&[**t], &platform, &archs).len() == 1)
+            .map(|t| t.kebab_name())
+            .collect();
+        return Err(KargoError::Generic {
+            message: format!(
+                "No target in [targets] matches Xcode SDK {platform} ({archs}). Add {} to [targets].",
+                if wanted.is_empty() {
+                    "a matching Apple target".to_string()
+                } else {
+                    wanted.join(" or ")
+                }
+            ),
+        }
+        .into());
+    }
+
+    for target in targets {
+        let opts = BuildOptions {
+            target: Some(target.kebab_name().to_string()),
+            profile: Some(profile.clone()),
+            verbose,
+            ..Default::default()
+        };
+        let result = ops_build::build(project_dir, &opts).await?;
+        if !result.success {
+            return Err(KargoError::Compilation {
+                message: format!("the {} framework", target.kebab_name()),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// An assembled XCFramework.
+#[derive(Debug, Clone)]
+pub struct XcFramework {
+    pub path: PathBuf,
+    /// `<Name>.xcframework.zip`, for binary distribution.
+    pub zip: PathBuf,
+}
+
+/// Build every declared Apple target and bundle the frameworks into an
+/// XCFramework. Requires Xcode's command line tools.
+pub async fn xcframework(
+    project_dir: &Path,
+    profile: Option<&str>,
+    release: bool,
+    verbose: bool,
+) -> miette::Result<XcFramework> {
+    let manifest = Manifest::from_path(&project_dir.join("Kargo.toml"))?;
+    let targets: Vec<KotlinTarget> = manifest
+        .declared_targets()
+        .into_iter()
+        .filter(|t| t.is_apple())
+        .collect();
+    if targets.is_empty() {
+        return Err(KargoError::Generic {
+            message: "No Apple target in [targets] to put in an XCFramework.".into(),
+        }
+        .into());
+    }
+
+    let mut profile_name = String::new();
+    for target in &targets {
+        let opts = BuildOptions {
+            target: Some(target.kebab_name().to_string()),
+            profile: profile.map(String::from),
+            release,
+            verbose,
+            ..Default::default()
+        };
+        let result = ops_build::build(project_dir, &opts).await?;
+        if !result.success {
+            return Err(KargoError::Compilation {
+                message: format!("the {} framework", target.kebab_name()),
+            }
+            .into());
+        }
+        profile_name = result.profile_name;
+    }
+    assemble_xcframework(project_dir, &manifest, &targets, &profile_name)
+}
+
+/// Bundle the already built frameworks of `targets` into
+/// `<Name>.xcframework`. Targets sharing an SDK (e.g. the arm64 and x86_64
+/// simulators) are first merged into one universal framework with `lipo`.
+pub fn assemble_xcframework(
+    project_dir: &Path,
+    manifest: &Manifest,
+    targets: &[KotlinTarget],
+    profile: &str,
+) -> miette::Result<XcFramework> {
+    let name = manifest.framework_name();
+    let bundle = format!("{name}.framework");
+    let missing: Vec<&str> = targets
+        .iter()
+        .filter(|t| {
+            !framework_dir(project_dir, **t, profile)
+                .join(&bundle)
+                .is_dir()
+        })
+        .map(|t| t.kebab_name())
+        .collect();
+    if !missing.is_empty() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{bundle} was not built for: {}. Build with `kargo build --target <target> \
+                 --profile {profile}`.",
+                missing.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    let out_dir = xcframework_dir(project_dir, profile);
+    if out_dir.exists() {
+        std::fs::remove_dir_all(&out_dir).map_err(KargoError::Io)?;
+    }
+    std::fs::create_dir_all(&out_dir).map_err(KargoError::Io)?;
+
+    let mut create = vec!["-create-xcframework".to_string()];
+    for (sdk, group) in group_by_sdk(targets) {
+        let first = framework_dir(project_dir, group[0], profile).join(&bundle);
+        let framework = if group.len() == 1 {
+            first
+        } else {
+            let merged = out_dir.join(sdk).join(&bundle);
+            run_tool(
+                "ditto",
+                &[
+                    first.to_string_lossy().as_ref(),
+                    merged.to_string_lossy().as_ref(),
+                ],
+            )?;
+            let mut lipo = vec!["-create".to_string()];
+            for target in &group {
+                lipo.push(
+                    framework_dir(project_dir, *target, profile)
+                        .join(&bundle)
+                        .join(&name)
+                        .to_string_lossy()
+                        .into_owned(),
+                );
+            }
+            lipo.push("-output".into());
+            lipo.push(merged.join(&name).to_string_lossy().into_owned());
+            run_tool("lipo", &lipo.iter().map(String::as_str).collect::<Vec<_>>())?;
+            merged
+        };
+        create.push("-framework".into());
+        create.push(framework.to_string_lossy().into_owned());
+    }
+    let path = out_dir.join(format!("{name}.xcframework"));
+    create.push("-output".into());
+    create.push(path.to_string_lossy().into_owned());
+    run_tool(
+        "xcodebuild",
+        &create.iter().map(String::as_str).collect::<Vec<_>>(),
+    )?;
+
+    let zip = out_dir.join(format!("{name}.xcframework.zip"));
+    run_tool(
+        "ditto",
+        &[
+            "-c",
+            "-k",
+            "--sequesterRsrc",
+            "--keepParent",
+            path.to_string_lossy().as_ref(),
+            zip.to_string_lossy().as_ref(),
+        ],
+    )?;
+    Ok(XcFramework { path, zip })
+}
+
+/// `targets` grouped by Xcode SDK, in declaration order.
+fn group_by_sdk(targets: &[KotlinTarget]) -> Vec<(&'static str, Vec<KotlinTarget>)> {
+    let mut groups: Vec<(&'static str, Vec<KotlinTarget>)> = Vec::new();
+    for target in targets {
+        let Some((sdk, _)) = xcode_sdk(*target) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(s, _)| *s == sdk) {
+            Some((_, group)) => group.push(*target),
+            None => groups.push((sdk, vec![*target])),
+        }
+    }
+    groups
+}
+
+/// Run an Xcode command line tool, failing with its stderr.
+fn run_tool(program: &str, args: &[&str]) -> miette::Result<()> {
+    let out = CommandBuilder::new(program)
+        .args(args.iter().copied())
+        .exec()
+        .map_err(|e| KargoError::Generic {
+            message: format!(
+                "Failed to run {program} (are Xcode's command line tools installed?): {e}"
+            ),
+        })?;
+    if !out.status.success() {
+        return Err(KargoError::Generic {
+            message: format!(
+                "{program} failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// `project_dir` relative to `xcode_dir`, with `/` separators.
+fn project_rel(xcode_dir: &Path, project_dir: &Path) -> miette::Result<String> {
+    let from = std::fs::canonicalize(xcode_dir).map_err(KargoError::Io)?;
+    let to = std::fs::canonicalize(project_dir).map_err(KargoError::Io)?;
+    let rel = relative_path(&from, &to);
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    Ok(if rel.is_empty() { ".".into() } else { rel })
+}
+
+/// The path that leads from directory `from` to `to`; both are absolute.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut rel = PathBuf::new();
+    for _ in common..from.len() {
+        rel.push("..");
+    }
+    for component in &to[common..] {
+        rel.push(component);
+    }
+    rel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(targets: &str) -> Manifest {
+        Manifest::parse_toml(&format!(
+            "[package]\nname = \"shared-core\"\nversion = \"1.0.0\"\nkotlin = \"2.3.0\"\n\
+             [targets]\n{targets}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn picks_targets_for_the_xcode_sdk() {
+        let declared = [
+            KotlinTarget::Jvm,
+            KotlinTarget::IosArm64,
+            KotlinTarget::IosSimulatorArm64,
+            KotlinTarget::IosX64,
+        ];
+        assert_eq!(
+            targets_for_sdk(&declared, "iphoneos", "arm64"),
+            [KotlinTarget::IosArm64]
+        );
+        assert_eq!(
+            targets_for_sdk(&declared, "iphonesimulator", "arm64 x86_64"),
+            [KotlinTarget::IosSimulatorArm64, KotlinTarget::IosX64]
+        );
+        assert!(targets_for_sdk(&declared, "macosx", "arm64").is_empty());
+    }
+
+    #[test]
+    fn xcconfig_lists_a_search_path_per_apple_target() {
+        let m = manifest("jvm = {}\nios-arm64 = {}\nios-simulator-arm64 = {}\n");
+        let xcconfig = render_xcconfig(&m, "..");
+        assert!(xcconfig.contains("KARGO_PROJECT_DIR = $(SRCROOT)/..\n"));
+        assert!(xcconfig.contains(
+            "FRAMEWORK_SEARCH_PATHS[sdk=iphoneos*][arch=arm64] = $(inherited) \
+             $(KARGO_PROJECT_DIR)/build/ios-arm64/$(KARGO_PROFILE)/framework\n"
+        ));
+        assert!(xcconfig.contains("[sdk=iphonesimulator*][arch=arm64]"));
+        assert!(!xcconfig.contains("jvm"));
+        assert!(xcconfig.ends_with("OTHER_LDFLAGS = $(inherited) -framework SharedCore\n"));
+    }
+
+    #[test]
+    fn simulator_architectures_share_a_framework() {
+        let groups = group_by_sdk(&[
+            KotlinTarget::IosArm64,
+            KotlinTarget::IosSimulatorArm64,
+            KotlinTarget::MacosArm64,
+            KotlinTarget::IosX64,
+        ]);
+        assert_eq!(
+            groups,
+            [
+                ("iphoneos", vec![KotlinTarget::IosArm64]),
+                (
+                    "iphonesimulator",
+                    vec![KotlinTarget::IosSimulatorArm64, KotlinTarget::IosX64]
+                ),
+                ("macosx", vec![KotlinTarget::MacosArm64]),
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_path_walks_up_and_down() {
+        assert_eq!(
+            relative_path(Path::new("/p/iosApp"), Path::new("/p")),
+            PathBuf::from("..")
+        );
+        assert_eq!(
+            relative_path(Path::new("/p/apps/ios"), Path::new("/p/shared")),
+            PathBuf::from("../../shared")
+        );
+        assert_eq!(
+            relative_path(Path::new("/p"), Path::new("/p")),
+            PathBuf::new()
+        );
+    }
+}
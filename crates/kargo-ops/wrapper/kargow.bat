@@ -0,0 +1,54 @@
+@echo off
+rem Kargo wrapper - generated by `kargo wrapper`. Commit this file.
+rem
+rem Runs the Kargo version pinned in .kargo-version, downloading it on first use
+rem into %USERPROFILE%\.kargo\wrapper\<version>\ after checking it against the
+rem release's .sha256 file. Set KARGO_WRAPPER_BASE_URL to use a mirror.
+
+setlocal
+
+set "APP_DIR=%~dp0"
+set "VERSION_FILE=%APP_DIR%.kargo-version"
+
+if not exist "%VERSION_FILE%" (
+    echo kargow: %VERSION_FILE% not found ^(run 'kargo wrapper' to regenerate^) 1>&2
+    exit /b 1
+)
+set /p KARGO_VERSION=<"%VERSION_FILE%"
+set "KARGO_VERSION=%KARGO_VERSION: =%"
+
+if "%KARGO_WRAPPER_BASE_URL%"=="" set "KARGO_WRAPPER_BASE_URL=https://github.com/dejanradmanovic/kargo/releases/download"
+if "%KARGO_HOME%"=="" set "KARGO_HOME=%USERPROFILE%\.kargo"
+set "INSTALL_DIR=%KARGO_HOME%\wrapper\%KARGO_VERSION%"
+set "KARGO_BIN=%INSTALL_DIR%\kargo.exe"
+
+if exist "%KARGO_BIN%" goto run
+
+set "ASSET=kargo-x86_64-pc-windows-msvc.zip"
+if /i "%PROCESSOR_ARCHITECTURE%"=="ARM64" set "ASSET=kargo-aarch64-pc-windows-msvc.zip"
+set "URL=%KARGO_WRAPPER_BASE_URL%/v%KARGO_VERSION%/%ASSET%"
+set "TMP_DIR=%TEMP%\kargow-%RANDOM%"
+
+echo kargow: downloading Kargo %KARGO_VERSION% from %URL% 1>&2
+powershell -NoProfile -ExecutionPolicy Bypass -Command ^
+    "$ErrorActionPreference = 'Stop';" ^
+    "New-Item -ItemType Directory -Force -Path '%TMP_DIR%' | Out-Null;" ^
+    "Invoke-WebRequest -UseBasicParsing -Uri '%URL%' -OutFile '%TMP_DIR%\%ASSET%';" ^
+    "Invoke-WebRequest -UseBasicParsing -Uri '%URL%.sha256' -OutFile '%TMP_DIR%\%ASSET%.sha256';" ^
+    "$expected = ((Get-Content -Raw '%TMP_DIR%\%ASSET%.sha256').Trim() -split '\s+')[0];" ^
+    "$actual = (Get-FileHash -Algorithm SHA256 -Path '%TMP_DIR%\%ASSET%').Hash;" ^
+    "if (-not $expected -or $expected -ne $actual) { Remove-Item -Recurse -Force '%TMP_DIR%'; throw ('checksum mismatch for %ASSET%: expected ' + $expected + ', got ' + $actual) };" ^
+    "Expand-Archive -Force -Path '%TMP_DIR%\%ASSET%' -DestinationPath '%TMP_DIR%';" ^
+    "$bin = Get-ChildItem -Path '%TMP_DIR%' -Recurse -Filter kargo.exe | Select-Object -First 1;" ^
+    "if (-not $bin) { throw 'no kargo.exe found in %ASSET%' };" ^
+    "New-Item -ItemType Directory -Force -Path '%INSTALL_DIR%' | Out-Null;" ^
+    "Copy-Item -Force $bin.FullName '%KARGO_BIN%';" ^
+    "Remove-Item -Recurse -Force '%TMP_DIR%'"
+if errorlevel 1 (
+    echo kargow: failed to download Kargo %KARGO_VERSION% 1>&2
+    exit /b 1
+)
+
+:run
+"%KARGO_BIN%" %*
+exit /b %ERRORLEVEL%
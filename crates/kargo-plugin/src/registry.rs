@@ -1 +1,165 @@
 //! Plugin registry: discovery, installation, and management.
+//!
+//! External subcommands are executables named `kargo-<cmd>` in
+//! `~/.kargo/bin` or on `PATH`; `kargo <cmd>` runs the first one found, as
+//! Cargo does for `cargo-<cmd>`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name prefix of external subcommands.
+pub const SUBCOMMAND_PREFIX: &str = "kargo-";
+
+/// An executable providing `kargo <name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Directories searched for external subcommands, in order:
+/// `~/.kargo/bin`, then `PATH`.
+pub fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![kargo_util::dirs_path().join("bin")];
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs
+}
+
+/// The executable for `kargo <name>`, if one is installed.
+pub fn find(name: &str) -> Option<ExternalCommand> {
+    find_in(&search_dirs(), name)
+}
+
+/// The first executable for `kargo <name>` in `dirs`.
+pub fn find_in(dirs: &[PathBuf], name: &str) -> Option<ExternalCommand> {
+    dirs.iter().find_map(|dir| {
+        executable_names(&format!("{SUBCOMMAND_PREFIX}{name}"))
+            .into_iter()
+            .map(|file| dir.join(file))
+            .find(|path| is_executable(path))
+            .map(|path| ExternalCommand {
+                name: name.to_string(),
+                path,
+            })
+    })
+}
+
+/// Every installed external subcommand, by name. A name found in several
+/// directories maps to the first, which is the one that runs.
+pub fn list() -> Vec<ExternalCommand> {
+    list_in(&search_dirs())
+}
+
+/// Every external subcommand in `dirs`, by name.
+pub fn list_in(dirs: &[PathBuf]) -> Vec<ExternalCommand> {
+    let mut found: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let Some(name) = path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .and_then(command_name)
+            else {
+                continue;
+            };
+            if !found.contains_key(&name) && is_executable(&path) {
+                found.insert(name, path);
+            }
+        }
+    }
+    found
+        .into_iter()
+        .map(|(name, path)| ExternalCommand { name, path })
+        .collect()
+}
+
+/// The subcommand an executable named `file_name` provides.
+fn command_name(file_name: &str) -> Option<String> {
+    let name = file_name.strip_prefix(SUBCOMMAND_PREFIX)?;
+    let name = if cfg!(windows) {
+        let lower = name.to_ascii_lowercase();
+        let ext = [".exe", ".cmd", ".bat"]
+            .into_iter()
+            .find(|ext| lower.ends_with(ext))?;
+        &name[..name.len() - ext.len()]
+    } else {
+        name
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// File names an executable called `stem` may have on this platform.
+fn executable_names(stem: &str) -> Vec<String> {
+    if cfg!(windows) {
+        [".exe", ".cmd", ".bat"]
+            .iter()
+            .map(|ext| format!("{stem}{ext}"))
+            .collect()
+    } else {
+        vec![stem.to_string()]
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn touch(path: &Path, mode: u32) {
+        std::fs::write(path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn discovers_executables_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let first = tmp.path().join("first");
+        let second = tmp.path().join("second");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        touch(&first.join("kargo-fmt"), 0o755);
+        touch(&first.join("kargo-notes"), 0o644);
+        touch(&second.join("kargo-fmt"), 0o755);
+        touch(&second.join("kargo-deploy"), 0o755);
+        touch(&second.join("kargo-"), 0o755);
+        touch(&second.join("other"), 0o755);
+
+        let dirs = [first.clone(), second.clone(), tmp.path().join("missing")];
+        assert_eq!(
+            list_in(&dirs),
+            [
+                ExternalCommand {
+                    name: "deploy".into(),
+                    path: second.join("kargo-deploy"),
+                },
+                ExternalCommand {
+                    name: "fmt".into(),
+                    path: first.join("kargo-fmt"),
+                },
+            ]
+        );
+        assert_eq!(
+            find_in(&dirs, "fmt").map(|c| c.path),
+            Some(first.join("kargo-fmt"))
+        );
+        assert_eq!(find_in(&dirs, "notes"), None);
+    }
+}
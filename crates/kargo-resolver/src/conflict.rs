@@ -4,6 +4,7 @@ use std::fmt;
 
 /// A report of all version conflicts encountered during resolution.
 #[derive(Debug, Default)]
+#[non_exhaustive]
 pub struct ConflictReport {
     pub conflicts: Vec<VersionConflict>,
 }
@@ -11,6 +12,7 @@ pub struct ConflictReport {
 /// A single version conflict where multiple versions of the same artifact
 /// were requested but only one was resolved.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct VersionConflict {
     pub group: String,
     pub artifact: String,
@@ -0,0 +1,169 @@
+//! Step-by-step record of resolution decisions, printed by
+//! `kargo lock --explain`.
+
+use std::fmt;
+
+use crate::conflict::VersionConflict;
+use crate::graph::DependencyCycle;
+
+/// A single decision taken while resolving the dependency graph.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ResolutionStep {
+    /// A dependency declared in a manifest section.
+    Declared { coordinate: String, section: String },
+    /// A transitive dependency contributed by a POM.
+    Transitive {
+        coordinate: String,
+        scope: String,
+        via: String,
+    },
+    /// A POM profile whose activation conditions matched.
+    ProfileActivated { coordinate: String, profile: String },
+    /// A parent POM merged into a dependency's POM.
+    Inherited { coordinate: String, parent: String },
+    /// A BOM imported into a POM's `<dependencyManagement>`.
+    BomImported { coordinate: String, bom: String },
+    /// A missing version supplied by a `<dependencyManagement>` entry,
+    /// possibly inherited from a parent POM.
+    ManagedVersion {
+        key: String,
+        version: String,
+        source: String,
+    },
+    /// A transitive version pinned by `Kargo.lock` instead of the one the
+    /// POM requested.
+    LockPinned {
+        key: String,
+        requested: String,
+        locked: String,
+    },
+    /// A dependency resolved at the coordinates its POM relocated it to.
+    Relocated { from: String, to: String },
+    /// A POM dependency that was not followed.
+    Skipped {
+        key: String,
+        via: String,
+        reason: String,
+    },
+    /// A version conflict and how it was resolved.
+    Conflict(VersionConflict),
+    /// A dependency on an ancestor that was dropped to break a cycle.
+    Cycle(DependencyCycle),
+}
+
+impl fmt::Display for ResolutionStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Declared {
+                coordinate,
+                section,
+            } => write!(f, "declared   {coordinate} in {section}"),
+            Self::Transitive {
+                coordinate,
+                scope,
+                via,
+            } => write!(f, "added      {coordinate} ({scope}) from the POM of {via}"),
+            Self::ProfileActivated {
+                coordinate,
+                profile,
+            } => write!(f, "profile    {coordinate} activated profile '{profile}'"),
+            Self::Inherited { coordinate, parent } => {
+                write!(f, "inherited  {coordinate} from parent POM {parent}")
+            }
+            Self::BomImported { coordinate, bom } => {
+                write!(f, "imported   BOM {bom} into {coordinate}")
+            }
+            Self::ManagedVersion {
+                key,
+                version,
+                source,
+            } => write!(
+                f,
+                "managed    {key} -> {version} by <dependencyManagement> in {source}"
+            ),
+            Self::LockPinned {
+                key,
+                requested,
+                locked,
+            } => {
+                if requested == locked {
+                    write!(f, "locked     {key} {locked} (Kargo.lock)")
+                } else {
+                    write!(
+                        f,
+                        "locked     {key} {locked} (Kargo.lock) instead of {requested}"
+                    )
+                }
+            }
+            Self::Relocated { from, to } => {
+                write!(f, "relocated  {from} -> {to} by <relocation> in its POM")
+            }
+            Self::Skipped { key, via, reason } => {
+                write!(f, "skipped    {key} from the POM of {via} ({reason})")
+            }
+            Self::Conflict(c) => write!(
+                f,
+                "conflict   {}:{} kept {} over {} ({})",
+                c.group, c.artifact, c.resolved, c.requested, c.reason
+            ),
+            Self::Cycle(cycle) => write!(f, "cycle      {cycle} broken at its last edge"),
+        }
+    }
+}
+
+/// Ordered log of every [`ResolutionStep`] taken during one resolution.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Explanation {
+    pub steps: Vec<ResolutionStep>,
+}
+
+impl Explanation {
+    pub fn push(&mut self, step: ResolutionStep) {
+        self.steps.push(step);
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.steps.len().to_string().len();
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{:>width$}. {step}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_numbered() {
+        let mut explanation = Explanation::default();
+        explanation.push(ResolutionStep::Declared {
+            coordinate: "org.example:lib:1.0".into(),
+            section: "[dependencies]".into(),
+        });
+        explanation.push(ResolutionStep::LockPinned {
+            key: "org.example:util".into(),
+            requested: "2.1".into(),
+            locked: "2.0".into(),
+        });
+        explanation.push(ResolutionStep::Conflict(VersionConflict {
+            group: "org.example".into(),
+            artifact: "util".into(),
+            requested: "1.5".into(),
+            resolved: "2.0".into(),
+            reason: "nearest wins (depth 2 vs 3)".into(),
+        }));
+
+        assert_eq!(
+            explanation.to_string(),
+            "1. declared   org.example:lib:1.0 in [dependencies]\n\
+             2. locked     org.example:util 2.0 (Kargo.lock) instead of 2.1\n\
+             3. conflict   org.example:util kept 2.0 over 1.5 (nearest wins (depth 2 vs 3))\n"
+        );
+    }
+}
@@ -1,14 +1,16 @@
 //! Dependency graph construction and traversal.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use petgraph::Direction;
+use serde::{Deserialize, Serialize};
 
 /// A node in the resolved dependency graph.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ResolvedNode {
     pub group: String,
     pub artifact: String,
@@ -17,6 +19,15 @@ pub struct ResolvedNode {
 }
 
 impl ResolvedNode {
+    pub fn new(group: &str, artifact: &str, version: &str, scope: &str) -> Self {
+        Self {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            scope: scope.to_string(),
+        }
+    }
+
     /// `group:artifact` identifier (without version).
     pub fn key(&self) -> String {
         format!("{}:{}", self.group, self.artifact)
@@ -30,18 +41,53 @@ impl fmt::Display for ResolvedNode {
 }
 
 /// Edge label in the dependency graph.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct DepEdge {
     pub scope: String,
     pub optional: bool,
 }
 
+impl DepEdge {
+    pub fn new(scope: &str, optional: bool) -> Self {
+        Self {
+            scope: scope.to_string(),
+            optional,
+        }
+    }
+}
+
+/// A circular chain of dependencies: each node depends on the next, and
+/// the last one on the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DependencyCycle {
+    pub path: Vec<ResolvedNode>,
+}
+
+impl fmt::Display for DependencyCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in &self.path {
+            write!(f, "{node} -> ")?;
+        }
+        match self.path.first() {
+            Some(first) => write!(f, "{first}"),
+            None => Ok(()),
+        }
+    }
+}
+
 /// A resolved dependency graph backed by petgraph.
+///
+/// The graph is kept acyclic: an edge that would close a cycle is not
+/// added but recorded as a [`DependencyCycle`], so the first edges added
+/// (nearest the root, in resolution order) are the ones kept.
 pub struct DependencyGraph {
     graph: DiGraph<ResolvedNode, DepEdge>,
     /// Lookup from `group:artifact` to node index (only the resolved version).
     index: HashMap<String, NodeIndex>,
     pub root: Option<NodeIndex>,
+    cycles: Vec<DependencyCycle>,
 }
 
 impl DependencyGraph {
@@ -50,6 +96,7 @@ impl DependencyGraph {
             graph: DiGraph::new(),
             index: HashMap::new(),
             root: None,
+            cycles: Vec::new(),
         }
     }
 
@@ -69,11 +116,58 @@ impl DependencyGraph {
         self.root = Some(idx);
     }
 
-    /// Add a dependency edge from `from` to `to`.
+    /// Add a dependency edge from `from` to `to`, unless it would close a
+    /// cycle (see [`check_cycle`](Self::check_cycle)).
     pub fn add_edge(&mut self, from: NodeIndex, to: NodeIndex, edge: DepEdge) {
-        if !self.graph.edges(from).any(|e| e.target() == to) {
-            self.graph.add_edge(from, to, edge);
+        if self.graph.edges(from).any(|e| e.target() == to) || self.check_cycle(from, to) {
+            return;
+        }
+        self.graph.add_edge(from, to, edge);
+    }
+
+    /// Whether an edge from `from` to `to` would close a cycle, that is
+    /// whether `to` already reaches `from`. The cycle is recorded if so.
+    pub fn check_cycle(&mut self, from: NodeIndex, to: NodeIndex) -> bool {
+        // Breadth-first, so the shortest cycle is the one reported.
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::from([to]);
+        let mut found = to == from;
+        while let Some(current) = queue.pop_front() {
+            if found {
+                break;
+            }
+            for next in self.graph.neighbors(current) {
+                if next == to || previous.contains_key(&next) {
+                    continue;
+                }
+                previous.insert(next, current);
+                if next == from {
+                    found = true;
+                    break;
+                }
+                queue.push_back(next);
+            }
+        }
+        if !found {
+            return false;
+        }
+        let mut path = vec![from];
+        while let Some(&prev) = path.last().and_then(|idx| previous.get(idx)) {
+            path.push(prev);
         }
+        path.reverse();
+        let cycle = DependencyCycle {
+            path: path.iter().map(|&idx| self.graph[idx].clone()).collect(),
+        };
+        if !self.cycles.contains(&cycle) {
+            self.cycles.push(cycle);
+        }
+        true
+    }
+
+    /// Cycles found while building the graph, in the order they were found.
+    pub fn cycles(&self) -> &[DependencyCycle] {
+        &self.cycles
     }
 
     /// Look up a node by `group:artifact`.
@@ -88,11 +182,98 @@ impl DependencyGraph {
 
     /// All resolved nodes (excluding root).
     pub fn all_nodes(&self) -> Vec<&ResolvedNode> {
+        self.nodes().collect()
+    }
+
+    /// The project itself, whose dependencies are the declared ones.
+    pub fn root_node(&self) -> Option<&ResolvedNode> {
+        self.root.map(|idx| &self.graph[idx])
+    }
+
+    /// Resolved nodes (excluding root), in resolution order.
+    pub fn nodes(&self) -> impl Iterator<Item = &ResolvedNode> + '_ {
         self.graph
             .node_indices()
-            .filter(|&idx| Some(idx) != self.root)
-            .map(|idx| &self.graph[idx])
-            .collect()
+            .filter(move |&idx| Some(idx) != self.root)
+            .map(move |idx| &self.graph[idx])
+    }
+
+    /// Every `(dependent, dependency, edge)`, including the edges from the
+    /// root to declared dependencies, in the order they were added.
+    pub fn edges(&self) -> impl Iterator<Item = (&ResolvedNode, &ResolvedNode, &DepEdge)> + '_ {
+        self.graph.edge_indices().filter_map(move |e| {
+            let (from, to) = self.graph.edge_endpoints(e)?;
+            Some((&self.graph[from], &self.graph[to], &self.graph[e]))
+        })
+    }
+
+    /// A plain-data copy of the graph that can be serialized, e.g. to JSON.
+    pub fn to_serialized(&self) -> SerializedGraph {
+        SerializedGraph {
+            format: SerializedGraph::FORMAT,
+            root: self.root_node().cloned(),
+            nodes: self.nodes().cloned().collect(),
+            edges: self
+                .edges()
+                .map(|(from, to, edge)| SerializedEdge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    scope: edge.scope.clone(),
+                    optional: edge.optional,
+                })
+                .collect(),
+            cycles: self
+                .cycles
+                .iter()
+                .map(|c| c.path.iter().map(|n| n.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    /// Rebuild a graph from [`to_serialized`](Self::to_serialized) output.
+    pub fn from_serialized(data: &SerializedGraph) -> miette::Result<Self> {
+        if data.format != SerializedGraph::FORMAT {
+            return Err(kargo_util::errors::KargoError::Generic {
+                message: format!(
+                    "Unsupported dependency graph format {} (expected {})",
+                    data.format,
+                    SerializedGraph::FORMAT
+                ),
+            }
+            .into());
+        }
+        let mut graph = Self::new();
+        let mut by_coordinate = HashMap::new();
+        if let Some(root) = &data.root {
+            let idx = graph.add_node(root.clone());
+            graph.set_root(idx);
+            by_coordinate.insert(root.to_string(), idx);
+        }
+        for node in &data.nodes {
+            by_coordinate.insert(node.to_string(), graph.add_node(node.clone()));
+        }
+        let lookup = |coordinate: &str| {
+            by_coordinate.get(coordinate).copied().ok_or_else(|| {
+                kargo_util::errors::KargoError::Generic {
+                    message: format!("Dependency graph edge refers to unknown node {coordinate}"),
+                }
+            })
+        };
+        for edge in &data.edges {
+            let (from, to) = (lookup(&edge.from)?, lookup(&edge.to)?);
+            graph.add_edge(from, to, DepEdge::new(&edge.scope, edge.optional));
+        }
+        for path in &data.cycles {
+            let path = path
+                .iter()
+                .map(|c| lookup(c).map(|idx| graph.graph[idx].clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let cycle = DependencyCycle { path };
+            if !graph.cycles.contains(&cycle) {
+                graph.cycles.push(cycle);
+            }
+        }
+        Ok(graph)
     }
 
     /// Direct dependencies of a node.
@@ -126,21 +307,25 @@ impl DependencyGraph {
 
         let mut compile_deps: Vec<(NodeIndex, &DepEdge)> = Vec::new();
         let mut test_deps: Vec<(NodeIndex, &DepEdge)> = Vec::new();
+        let mut it_deps: Vec<(NodeIndex, &DepEdge)> = Vec::new();
         let mut ksp_deps: Vec<(NodeIndex, &DepEdge)> = Vec::new();
         let mut kapt_deps: Vec<(NodeIndex, &DepEdge)> = Vec::new();
 
         for (idx, edge) in &deps {
             match edge.scope.as_str() {
                 "test" => test_deps.push((*idx, edge)),
+                "integration-test" => it_deps.push((*idx, edge)),
                 "ksp" => ksp_deps.push((*idx, edge)),
                 "kapt" => kapt_deps.push((*idx, edge)),
                 _ => compile_deps.push((*idx, edge)),
             }
         }
 
-        let has_non_compile =
-            !test_deps.is_empty() || !ksp_deps.is_empty() || !kapt_deps.is_empty();
-        let section_count = [&compile_deps, &test_deps, &ksp_deps, &kapt_deps]
+        let has_non_compile = !test_deps.is_empty()
+            || !it_deps.is_empty()
+            || !ksp_deps.is_empty()
+            || !kapt_deps.is_empty();
+        let section_count = [&compile_deps, &test_deps, &it_deps, &ksp_deps, &kapt_deps]
             .iter()
             .filter(|s| !s.is_empty())
             .count();
@@ -154,6 +339,7 @@ impl DependencyGraph {
         for (label, deps_list) in [
             ("[dependencies]", &compile_deps),
             ("[dev-dependencies]", &test_deps),
+            ("[integration-test-dependencies]", &it_deps),
             ("[ksp]", &ksp_deps),
             ("[kapt]", &kapt_deps),
         ] {
@@ -343,7 +529,7 @@ impl DependencyGraph {
             .filter(|&idx| idx != root)
             .map(|idx| (idx, &self.graph[idx]))
             .collect();
-        nodes.sort_by(|a, b| a.1.key().cmp(&b.1.key()));
+        nodes.sort_by_key(|a| a.1.key());
 
         for (idx, node) in &nodes {
             let dependents = self.dependents_of(*idx);
@@ -385,6 +571,35 @@ impl Default for DependencyGraph {
     }
 }
 
+/// Serializable form of a [`DependencyGraph`]. Nodes are referred to by
+/// their `group:artifact:version` coordinate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SerializedGraph {
+    /// Layout version, bumped on incompatible changes.
+    pub format: u32,
+    pub root: Option<ResolvedNode>,
+    pub nodes: Vec<ResolvedNode>,
+    pub edges: Vec<SerializedEdge>,
+    /// Each cycle that was broken, as a coordinate path.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl SerializedGraph {
+    /// The layout written by this version of Kargo.
+    pub const FORMAT: u32 = 1;
+}
+
+/// An edge of a [`SerializedGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SerializedEdge {
+    pub from: String,
+    pub to: String,
+    pub scope: String,
+    pub optional: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,4 +823,66 @@ mod tests {
         assert_eq!(path.len(), 2);
         assert_eq!(path[1].artifact, "my-lib");
     }
+
+    #[test]
+    fn cycles_are_broken_and_recorded() {
+        let mut g = DependencyGraph::new();
+        let root = g.add_node(make_node("com.example", "app", "1.0"));
+        g.set_root(root);
+        let a = g.add_node(make_node("org.a", "a", "1.0"));
+        let b = g.add_node(make_node("org.b", "b", "1.0"));
+        let edge = || DepEdge {
+            scope: "compile".into(),
+            optional: false,
+        };
+        g.add_edge(root, a, edge());
+        g.add_edge(a, b, edge());
+        g.add_edge(b, a, edge());
+        g.add_edge(b, b, edge());
+
+        assert!(g.dependents_of(a).iter().all(|(idx, _)| *idx == root));
+        assert!(g.dependencies_of(b).is_empty());
+        let cycles: Vec<String> = g.cycles().iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            cycles,
+            [
+                "org.a:a:1.0 -> org.b:b:1.0 -> org.a:a:1.0",
+                "org.b:b:1.0 -> org.b:b:1.0",
+            ]
+        );
+        assert!(g.print_tree(None).contains("org.b:b:1.0"));
+    }
+
+    #[test]
+    fn serialized_graph_round_trips() {
+        let mut g = DependencyGraph::new();
+        let root = g.add_node(make_node("com.example", "app", "1.0"));
+        g.set_root(root);
+        let a = g.add_node(make_node("org.a", "a", "1.0"));
+        let b = g.add_node(make_node("org.b", "b", "2.0"));
+        g.add_edge(root, a, DepEdge::new("compile", false));
+        g.add_edge(a, b, DepEdge::new("runtime", true));
+        g.add_edge(b, a, DepEdge::new("compile", false));
+
+        let edges: Vec<String> = g
+            .edges()
+            .map(|(from, to, edge)| format!("{from} -> {to} {}", edge.scope))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                "com.example:app:1.0 -> org.a:a:1.0 compile",
+                "org.a:a:1.0 -> org.b:b:2.0 runtime",
+            ]
+        );
+
+        let data = g.to_serialized();
+        let text = toml::to_string(&data).unwrap();
+        let parsed: SerializedGraph = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, data);
+        let rebuilt = DependencyGraph::from_serialized(&parsed).unwrap();
+        assert_eq!(rebuilt.to_serialized(), data);
+        assert_eq!(rebuilt.cycles().len(), 1);
+        assert_eq!(rebuilt.print_tree(None), g.print_tree(None));
+    }
 }
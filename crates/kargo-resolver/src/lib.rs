@@ -1,8 +1,38 @@
 //! Dependency resolution engine: Maven-compatible nearest-wins algorithm,
 //! transitive dependency resolution, scope propagation, and lockfile management.
+//!
+//! Tools that embed Kargo's resolution start from
+//! [`resolve_manifest_path`], then walk the [`DependencyGraph`] with its
+//! iterators or save it with [`DependencyGraph::to_serialized`]:
+//!
+//! ```no_run
+//! # async fn example() -> miette::Result<()> {
+//! let result = kargo_resolver::resolve_manifest_path("Kargo.toml".as_ref()).await?;
+//! for (dependent, dependency, edge) in result.graph.edges() {
+//!     println!("{dependent} -> {dependency} ({})", edge.scope);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The items re-exported here are the stable interface and follow semver.
+//! Their structs and enums are `#[non_exhaustive]`, so fields and variants
+//! are added in minor releases. The modules below them are Kargo's own
+//! plumbing and may change in any release.
 
 pub mod cache;
 pub mod conflict;
+pub mod explain;
 pub mod graph;
 pub mod resolver;
 pub mod version;
+
+pub use conflict::{ConflictReport, VersionConflict};
+pub use explain::{Explanation, ResolutionStep};
+pub use graph::{
+    DepEdge, DependencyCycle, DependencyGraph, ResolvedNode, SerializedEdge, SerializedGraph,
+};
+pub use resolver::{
+    resolve_manifest_path, ArtifactRef, OptionalDependency, RelocatedArtifact, ResolutionResult,
+    ResolvedArtifact,
+};
@@ -1,25 +1,33 @@
 //! Core dependency resolution algorithm: nearest-wins BFS, scope propagation,
 //! exclusions, optional dependency handling, and BOM imports.
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use kargo_core::dependency::{Dependency, MavenCoordinate};
 use kargo_core::lockfile::Lockfile;
 use kargo_core::manifest::Manifest;
 use kargo_maven::cache::LocalCache;
-use kargo_maven::pom::Pom;
+use kargo_maven::pom::{ActivationContext, Pom};
 use kargo_maven::repository::MavenRepository;
 use reqwest::Client;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
 use crate::conflict::{ConflictReport, VersionConflict};
+use crate::explain::{Explanation, ResolutionStep};
 use crate::graph::{DepEdge, DependencyGraph, ResolvedNode};
 
-const MAX_CONCURRENT_FETCHES: usize = 8;
+/// Longest parent POM chain followed before giving up.
+const MAX_PARENT_DEPTH: usize = 16;
+/// Longest chain of `<relocation>`s followed before giving up.
+const MAX_RELOCATIONS: usize = 8;
+/// Most BOMs imported into one POM, counting nested imports.
+const MAX_BOM_IMPORTS: usize = 64;
 
 /// The output of dependency resolution.
+#[non_exhaustive]
 pub struct ResolutionResult {
     pub graph: DependencyGraph,
     pub conflicts: ConflictReport,
@@ -28,10 +36,37 @@ pub struct ResolutionResult {
     /// All versions requested for each `group:artifact` during resolution.
     /// Artifacts with more than one entry were requested at multiple versions.
     pub version_requests: HashMap<String, HashSet<String>>,
+    /// Every decision taken during resolution, in order.
+    pub explanation: Explanation,
+    /// Optional dependencies declared by resolved POMs, whether or not they
+    /// were opted in with `include-optional`.
+    pub optional: Vec<OptionalDependency>,
+    /// `(group, artifact, version)` of every parent and imported BOM POM
+    /// read while resolving. They are cached alongside the artifacts and
+    /// must survive pruning.
+    pub parent_poms: BTreeSet<(String, String, String)>,
+    /// Dependencies whose POM relocated them to other coordinates.
+    pub relocations: Vec<RelocatedArtifact>,
+}
+
+/// A dependency that was resolved at the coordinates its POM's
+/// `<relocation>` points to.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RelocatedArtifact {
+    /// `group:artifact:version` as requested.
+    pub from: String,
+    /// `group:artifact:version` resolved instead.
+    pub to: String,
+    /// The `<message>` of the relocation, if any.
+    pub message: Option<String>,
+    /// Whether `from` is declared in the manifest.
+    pub direct: bool,
 }
 
 /// A single resolved artifact with its source repository.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ResolvedArtifact {
     pub group: String,
     pub artifact: String,
@@ -40,16 +75,48 @@ pub struct ResolvedArtifact {
     pub source: String,
     pub checksum: Option<String>,
     pub dependencies: Vec<ArtifactRef>,
+    /// Targets (kebab-case) this artifact is needed for. Empty means all targets.
+    pub targets: Vec<String>,
+    /// `group:artifact:version` that was relocated to this artifact.
+    pub relocated_from: Option<String>,
+}
+
+/// An `<optional>true</optional>` dependency found in a resolved POM.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct OptionalDependency {
+    /// `group:artifact:version` of the POM that declares it.
+    pub declared_by: String,
+    pub group: String,
+    pub artifact: String,
+    pub version: Option<String>,
+    /// Whether it was resolved because of `include-optional`.
+    pub included: bool,
 }
 
 /// A reference to a dependency within a resolved artifact.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ArtifactRef {
     pub group: String,
     pub artifact: String,
     pub version: String,
 }
 
+/// A POM with its activated profiles, parent chain and imported BOMs
+/// merged in.
+struct EffectivePom {
+    pom: Pom,
+    /// `(group, artifact, version)` of each parent applied, nearest first.
+    parents: Vec<(String, String, String)>,
+    /// `(group, artifact, version)` of each BOM imported into its
+    /// `<dependencyManagement>`, in order of precedence.
+    boms: Vec<(String, String, String)>,
+    /// `(pom, profile id)` of each activated profile, including those of
+    /// parent POMs.
+    profiles: Vec<(String, String)>,
+}
+
 /// Entry in the BFS queue.
 struct QueueEntry {
     group: String,
@@ -58,7 +125,28 @@ struct QueueEntry {
     scope: String,
     depth: usize,
     parent_key: Option<String>,
+    /// `group:artifact:version` of the POM that declared this entry.
+    via: Option<String>,
     exclusions: HashSet<String>,
+    /// Optional dependencies (`group:artifact`) to follow in this subtree.
+    include_optional: HashSet<String>,
+}
+
+/// Resolve the dependencies of the `Kargo.toml` at `manifest_path` the way
+/// `kargo tree` does: with the repositories it declares, the project's
+/// dependency cache, and the versions pinned by its `Kargo.lock`, if any.
+pub async fn resolve_manifest_path(manifest_path: &Path) -> miette::Result<ResolutionResult> {
+    let manifest = Manifest::from_path(manifest_path)?;
+    let project_root = manifest_path.parent().unwrap_or(Path::new("."));
+    let lockfile = if kargo_core::workspace::lockfile_path(project_root).is_file() {
+        Lockfile::for_project(project_root).ok()
+    } else {
+        None
+    };
+    let repos = build_repos(&manifest);
+    let cache = LocalCache::new(project_root);
+    let client = kargo_maven::download::build_client()?;
+    resolve(&manifest, &repos, &cache, lockfile.as_ref(), &client).await
 }
 
 /// Resolve all dependencies declared in a manifest.
@@ -73,6 +161,7 @@ pub async fn resolve(
 ) -> miette::Result<ResolutionResult> {
     let mut graph = DependencyGraph::new();
     let mut conflicts = ConflictReport::new();
+    let mut explanation = Explanation::default();
 
     let root = graph.add_node(ResolvedNode {
         group: manifest.package.group.clone().unwrap_or_default(),
@@ -86,31 +175,61 @@ pub async fn resolve(
     let mut direct_deps = Vec::new();
     for (name, dep) in &manifest.dependencies {
         if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+            explanation.push(declared(&coord, "[dependencies]"));
             direct_deps.push((coord, "compile".to_string()));
         }
     }
     for (name, dep) in &manifest.dev_dependencies {
         if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+            explanation.push(declared(&coord, "[dev-dependencies]"));
             direct_deps.push((coord, "test".to_string()));
         }
     }
-    // Per-target deps
-    for target_deps in manifest.target.values() {
+    for (name, dep) in &manifest.integration_test_dependencies {
+        if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+            explanation.push(declared(&coord, "[integration-test-dependencies]"));
+            direct_deps.push((coord, "integration-test".to_string()));
+        }
+    }
+    // Per-target deps, restricted to the declared targets their selector matches.
+    // Deps from every other section apply to all targets.
+    let mut direct_targets: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let target_section_start = direct_deps.len();
+    for (selector, target_deps) in &manifest.target {
+        let targets: BTreeSet<String> = manifest
+            .targets_matching(selector)
+            .iter()
+            .map(|t| t.kebab_name().to_string())
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
         for (name, dep) in &target_deps.dependencies {
             if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+                direct_targets
+                    .entry(format!("{}:{}", coord.group_id, coord.artifact_id))
+                    .or_default()
+                    .extend(targets.iter().cloned());
+                explanation.push(declared(
+                    &coord,
+                    &format!("[target.'{selector}'.dependencies]"),
+                ));
                 direct_deps.push((coord, "compile".to_string()));
             }
         }
     }
+    let target_section = target_section_start..direct_deps.len();
     // KSP processor deps — build-time only, excluded from runtime classpath
     for (name, dep) in &manifest.ksp {
         if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+            explanation.push(declared(&coord, "[ksp]"));
             direct_deps.push((coord, "ksp".to_string()));
         }
     }
     // KAPT processor deps — build-time only, excluded from runtime classpath
     for (name, dep) in &manifest.kapt {
         if let Some(coord) = resolve_dep_coordinate(dep, name, manifest) {
+            explanation.push(declared(&coord, "[kapt]"));
             direct_deps.push((coord, "kapt".to_string()));
         }
     }
@@ -129,6 +248,15 @@ pub async fn resolve(
     let mut resolved: HashMap<String, (String, usize)> = HashMap::new();
     let mut version_requests: HashMap<String, HashSet<String>> = HashMap::new();
     let mut pom_cache: HashMap<String, Pom> = HashMap::new();
+    // Parent chain (nearest first) of each POM in `pom_cache`.
+    let mut parent_chains: HashMap<String, Vec<String>> = HashMap::new();
+    let mut active_profiles: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut bom_imports: HashMap<String, Vec<String>> = HashMap::new();
+    let mut parent_poms: BTreeSet<(String, String, String)> = BTreeSet::new();
+
+    let direct_includes = direct_include_optional(manifest);
+    let mut optional: Vec<OptionalDependency> = Vec::new();
+    let mut relocations: Vec<RelocatedArtifact> = Vec::new();
 
     let direct_keys: HashSet<String> = direct_deps
         .iter()
@@ -136,6 +264,7 @@ pub async fn resolve(
         .collect();
 
     for (coord, scope) in &direct_deps {
+        let key = format!("{}:{}", coord.group_id, coord.artifact_id);
         queue.push_back(QueueEntry {
             group: coord.group_id.clone(),
             artifact: coord.artifact_id.clone(),
@@ -143,11 +272,15 @@ pub async fn resolve(
             scope: scope.clone(),
             depth: 1,
             parent_key: None,
+            via: None,
             exclusions: HashSet::new(),
+            include_optional: direct_includes.get(&key).cloned().unwrap_or_default(),
         });
     }
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_FETCHES));
+    configure_network(manifest);
+    let semaphore = Arc::new(Semaphore::new(kargo_maven::throttle::max_concurrency()));
+    let activation = Arc::new(activation_context(manifest));
 
     while !queue.is_empty() {
         // Drain the current depth level from the front of the queue
@@ -157,8 +290,9 @@ pub async fn resolve(
             level.push(queue.pop_front().unwrap());
         }
 
-        // Prefetch POMs for this level in parallel
-        let coords_to_fetch: Vec<(String, String, String)> = level
+        // Prefetch POMs for this level in parallel, then those of the
+        // coordinates they relocate to
+        let mut coords_to_fetch: Vec<(String, String, String)> = level
             .iter()
             .map(|e| (e.group.clone(), e.artifact.clone(), e.version.clone()))
             .filter(|(g, a, v)| {
@@ -169,37 +303,84 @@ pub async fn resolve(
             .into_iter()
             .collect();
 
-        if !coords_to_fetch.is_empty() {
+        for _ in 0..=MAX_RELOCATIONS {
+            if coords_to_fetch.is_empty() {
+                break;
+            }
             let mut join_set = JoinSet::new();
             for (group, artifact, version) in coords_to_fetch {
                 let client = client.clone();
                 let repos = repos.to_vec();
-                let cache_root = cache.root().to_path_buf();
+                let local_cache = cache.clone();
                 let sem = semaphore.clone();
+                let activation = activation.clone();
                 join_set.spawn(async move {
                     let _permit = sem.acquire().await;
-                    let local_cache = LocalCache::from_root(cache_root);
-                    let result = fetch_pom_from_repos(
+                    let result = fetch_effective_pom(
                         &client,
                         &repos,
                         &local_cache,
-                        &group,
-                        &artifact,
-                        &version,
+                        &activation,
+                        (&group, &artifact, &version),
                     )
                     .await;
-                    (format!("{group}:{artifact}:{version}"), result)
+                    ((group, artifact, version), result)
                 });
             }
+            let mut relocated = HashSet::new();
             while let Some(result) = join_set.join_next().await {
-                if let Ok((coord_key, Ok(Some(pom)))) = result {
-                    pom_cache.insert(coord_key, pom);
+                if let Ok(((group, artifact, version), Ok(Some(effective)))) = result {
+                    let coord_key = format!("{group}:{artifact}:{version}");
+                    if let Some(target) = effective.pom.relocated_to(&group, &artifact, &version) {
+                        relocated.insert(target);
+                    }
+                    parent_chains.insert(
+                        coord_key.clone(),
+                        effective
+                            .parents
+                            .iter()
+                            .map(|(g, a, v)| format!("{g}:{a}:{v}"))
+                            .collect(),
+                    );
+                    active_profiles.insert(coord_key.clone(), effective.profiles);
+                    bom_imports.insert(
+                        coord_key.clone(),
+                        effective
+                            .boms
+                            .iter()
+                            .map(|(g, a, v)| format!("{g}:{a}:{v}"))
+                            .collect(),
+                    );
+                    parent_poms.extend(effective.parents);
+                    parent_poms.extend(effective.boms);
+                    pom_cache.insert(coord_key, effective.pom);
                 }
             }
+            coords_to_fetch = relocated
+                .into_iter()
+                .filter(|(g, a, v)| !pom_cache.contains_key(&format!("{g}:{a}:{v}")))
+                .collect();
         }
 
         // Process entries at this depth level
-        for entry in level {
+        for mut entry in level {
+            if let Some(((group, artifact, version), message)) =
+                follow_relocations(&pom_cache, &entry.group, &entry.artifact, &entry.version)
+            {
+                let from = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+                let to = format!("{group}:{artifact}:{version}");
+                explanation.push(ResolutionStep::Relocated {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+                relocations.push(RelocatedArtifact {
+                    from,
+                    to,
+                    message,
+                    direct: entry.depth == 1,
+                });
+                (entry.group, entry.artifact, entry.version) = (group, artifact, version);
+            }
             let key = format!("{}:{}", entry.group, entry.artifact);
 
             version_requests
@@ -210,7 +391,7 @@ pub async fn resolve(
             if let Some((existing_ver, existing_depth)) = resolved.get(&key) {
                 if *existing_depth <= entry.depth {
                     if *existing_ver != entry.version {
-                        conflicts.add(VersionConflict {
+                        let conflict = VersionConflict {
                             group: entry.group.clone(),
                             artifact: entry.artifact.clone(),
                             requested: entry.version.clone(),
@@ -219,13 +400,32 @@ pub async fn resolve(
                                 "nearest wins (depth {} vs {})",
                                 existing_depth, entry.depth
                             ),
-                        });
+                        };
+                        explanation.push(ResolutionStep::Conflict(conflict.clone()));
+                        conflicts.add(conflict);
+                    }
+                    let parent = entry.parent_key.as_deref().and_then(|k| graph.find(k));
+                    if let (Some(parent_idx), Some(existing_idx)) = (parent, graph.find(&key)) {
+                        let known = graph.cycles().len();
+                        if graph.check_cycle(parent_idx, existing_idx)
+                            && graph.cycles().len() > known
+                        {
+                            explanation.push(ResolutionStep::Cycle(graph.cycles()[known].clone()));
+                        }
                     }
                     continue;
                 }
             }
 
             resolved.insert(key.clone(), (entry.version.clone(), entry.depth));
+            let coord_key = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+            if let Some(via) = &entry.via {
+                explanation.push(ResolutionStep::Transitive {
+                    coordinate: coord_key.clone(),
+                    scope: entry.scope.clone(),
+                    via: via.clone(),
+                });
+            }
 
             let node = graph.add_node(ResolvedNode {
                 group: entry.group.clone(),
@@ -256,47 +456,98 @@ pub async fn resolve(
                 );
             }
 
-            let coord_key = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+            for (pom, profile) in active_profiles.get(&coord_key).into_iter().flatten() {
+                explanation.push(ResolutionStep::ProfileActivated {
+                    coordinate: pom.clone(),
+                    profile: profile.clone(),
+                });
+            }
+            for parent in parent_chains.get(&coord_key).into_iter().flatten() {
+                explanation.push(ResolutionStep::Inherited {
+                    coordinate: coord_key.clone(),
+                    parent: parent.clone(),
+                });
+            }
+            for bom in bom_imports.get(&coord_key).into_iter().flatten() {
+                explanation.push(ResolutionStep::BomImported {
+                    coordinate: coord_key.clone(),
+                    bom: bom.clone(),
+                });
+            }
+
             let pom = pom_cache.get(&coord_key).cloned();
 
             if let Some(mut pom) = pom {
                 pom.resolve_properties();
 
                 for dep in &pom.dependencies {
+                    let dep_key = format!("{}:{}", dep.group_id, dep.artifact_id);
+                    let skip = |reason: &str| ResolutionStep::Skipped {
+                        key: dep_key.clone(),
+                        via: coord_key.clone(),
+                        reason: reason.to_string(),
+                    };
+
                     if dep.optional {
-                        continue;
+                        let included = entry.include_optional.contains(&dep_key);
+                        optional.push(OptionalDependency {
+                            declared_by: coord_key.clone(),
+                            group: dep.group_id.clone(),
+                            artifact: dep.artifact_id.clone(),
+                            version: dep.version.clone().or_else(|| {
+                                pom.managed_version(&dep.group_id, &dep.artifact_id)
+                                    .map(str::to_string)
+                            }),
+                            included,
+                        });
+                        if !included {
+                            explanation.push(skip("optional"));
+                            continue;
+                        }
                     }
                     let dep_scope = dep.scope.as_deref().unwrap_or("compile");
                     if dep_scope == "test" || dep_scope == "provided" || dep_scope == "system" {
+                        explanation.push(skip(&format!("{dep_scope} scope")));
                         continue;
                     }
 
-                    let dep_key = format!("{}:{}", dep.group_id, dep.artifact_id);
-
                     if entry.exclusions.contains(&dep_key)
                         || entry.exclusions.contains(&dep.group_id)
                     {
+                        explanation.push(skip("excluded"));
                         continue;
                     }
 
-                    let version = dep
-                        .version
-                        .clone()
-                        .or_else(|| {
-                            pom.managed_version(&dep.group_id, &dep.artifact_id)
-                                .map(|s| s.to_string())
-                        })
-                        .unwrap_or_default();
+                    let version = match &dep.version {
+                        Some(v) => v.clone(),
+                        None => match pom.managed_version(&dep.group_id, &dep.artifact_id) {
+                            Some(v) => {
+                                explanation.push(ResolutionStep::ManagedVersion {
+                                    key: dep_key.clone(),
+                                    version: v.to_string(),
+                                    source: coord_key.clone(),
+                                });
+                                v.to_string()
+                            }
+                            None => String::new(),
+                        },
+                    };
 
                     if version.is_empty() {
+                        explanation.push(skip("no version"));
                         continue;
                     }
 
-                    let dep_key = format!("{}:{}", dep.group_id, dep.artifact_id);
-                    let version = if !direct_keys.contains(&dep_key) {
-                        locked_versions.get(&dep_key).cloned().unwrap_or(version)
-                    } else {
-                        version
+                    let version = match locked_versions.get(&dep_key) {
+                        Some(locked) if !direct_keys.contains(&dep_key) => {
+                            explanation.push(ResolutionStep::LockPinned {
+                                key: dep_key.clone(),
+                                requested: version,
+                                locked: locked.clone(),
+                            });
+                            locked.clone()
+                        }
+                        _ => version,
                     };
 
                     let propagated_scope = propagate_scope(&entry.scope, dep_scope);
@@ -317,7 +568,9 @@ pub async fn resolve(
                         scope: propagated_scope,
                         depth: entry.depth + 1,
                         parent_key: Some(key.clone()),
+                        via: Some(coord_key.clone()),
                         exclusions: child_exclusions,
+                        include_optional: entry.include_optional.clone(),
                     });
                 }
             }
@@ -325,18 +578,107 @@ pub async fn resolve(
     }
 
     // Build flat artifact list for lockfile
-    let artifacts = build_artifact_list(&graph, repos);
+    let mut artifacts = build_artifact_list(&graph, repos);
+    let all_target_keys: HashSet<String> = direct_deps
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !target_section.contains(i))
+        .map(|(_, (c, _))| format!("{}:{}", c.group_id, c.artifact_id))
+        .collect();
+    assign_targets(&mut artifacts, &all_target_keys, &direct_targets);
+    for relocation in &relocations {
+        if let Some(artifact) = artifacts
+            .iter_mut()
+            .find(|a| format!("{}:{}:{}", a.group, a.artifact, a.version) == relocation.to)
+        {
+            artifact
+                .relocated_from
+                .get_or_insert_with(|| relocation.from.clone());
+        }
+    }
 
     Ok(ResolutionResult {
         graph,
         conflicts,
         artifacts,
         version_requests,
+        explanation,
+        optional,
+        parent_poms,
+        relocations,
     })
 }
 
+/// The coordinates `group:artifact:version` ends up at after following the
+/// `<relocation>`s of the POMs in `pom_cache`, with the message of the
+/// first one. `None` if its POM does not relocate it.
+fn follow_relocations(
+    pom_cache: &HashMap<String, Pom>,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> Option<((String, String, String), Option<String>)> {
+    let mut current = (group.to_string(), artifact.to_string(), version.to_string());
+    let mut message = None;
+    let mut seen = HashSet::new();
+    for hop in 0..MAX_RELOCATIONS {
+        let (g, a, v) = &current;
+        let key = format!("{g}:{a}:{v}");
+        if !seen.insert(key.clone()) {
+            tracing::warn!("Relocation cycle at {key}");
+            break;
+        }
+        let Some(pom) = pom_cache.get(&key) else {
+            break;
+        };
+        let Some(next) = pom.relocated_to(g, a, v) else {
+            break;
+        };
+        if hop == 0 {
+            message = pom.relocation.as_ref().and_then(|r| r.message.clone());
+        }
+        current = next;
+    }
+    (current != (group.to_string(), artifact.to_string(), version.to_string()))
+        .then_some((current, message))
+}
+
+/// `include-optional` entries of every declared dependency, keyed by the
+/// dependency's `group:artifact`.
+fn direct_include_optional(manifest: &Manifest) -> HashMap<String, HashSet<String>> {
+    let sections = [
+        &manifest.dependencies,
+        &manifest.dev_dependencies,
+        &manifest.integration_test_dependencies,
+        &manifest.ksp,
+        &manifest.kapt,
+    ];
+    let target_sections = manifest.target.values().map(|t| &t.dependencies);
+    let mut includes: HashMap<String, HashSet<String>> = HashMap::new();
+    for deps in sections.into_iter().chain(target_sections) {
+        for dep in deps.values() {
+            if let Dependency::Detailed(d) = dep {
+                if !d.include_optional.is_empty() {
+                    includes
+                        .entry(format!("{}:{}", d.group, d.artifact))
+                        .or_default()
+                        .extend(d.include_optional.iter().cloned());
+                }
+            }
+        }
+    }
+    includes
+}
+
+fn declared(coord: &MavenCoordinate, section: &str) -> ResolutionStep {
+    ResolutionStep::Declared {
+        coordinate: format!("{}:{}:{}", coord.group_id, coord.artifact_id, coord.version),
+        section: section.to_string(),
+    }
+}
+
 /// Resolve a `Dependency` enum to `MavenCoordinate`.
-fn resolve_dep_coordinate(
+pub fn resolve_dep_coordinate(
     dep: &Dependency,
     _name: &str,
     manifest: &Manifest,
@@ -362,6 +704,9 @@ fn resolve_dep_coordinate(
                 version,
             })
         }
+        // Workspace members are built from source and prebuilt JARs are
+        // taken as they are, not resolved from Maven.
+        Dependency::Module(_) | Dependency::File(_) => None,
     }
 }
 
@@ -446,7 +791,7 @@ async fn fetch_pom_from_repos(
         return Ok(Some(pom));
     }
 
-    for repo in repos {
+    for repo in kargo_maven::repository::for_group(repos, group) {
         match cache
             .fetch_pom(client, repo, group, artifact, version)
             .await?
@@ -459,8 +804,227 @@ async fn fetch_pom_from_repos(
     Ok(None)
 }
 
+/// Fetch a POM, merge its parent chain into it, then import the BOMs its
+/// `<dependencyManagement>` lists (see [`import_boms`]).
+async fn fetch_effective_pom(
+    client: &Client,
+    repos: &[MavenRepository],
+    cache: &LocalCache,
+    activation: &ActivationContext,
+    coord: (&str, &str, &str),
+) -> miette::Result<Option<EffectivePom>> {
+    let Some(mut effective) = fetch_inherited_pom(client, repos, cache, activation, coord).await?
+    else {
+        return Ok(None);
+    };
+    let (group, artifact, version) = coord;
+    effective.boms = import_boms(
+        client,
+        repos,
+        cache,
+        activation,
+        &mut effective.pom,
+        &format!("{group}:{artifact}:{version}"),
+    )
+    .await?;
+    Ok(Some(effective))
+}
+
+/// Import the BOMs (`<scope>import</scope>` entries of type `pom`) listed
+/// in the `<dependencyManagement>` of `pom`, the POM of `owner`, and
+/// return their coordinates.
+///
+/// As in Maven, each BOM contributes its effective dependency management:
+/// its parents are merged and its own imports follow it, depth first.
+/// Entries managed by `pom` itself win, then those of earlier imports. A
+/// BOM that cannot be fetched is skipped with a warning.
+async fn import_boms(
+    client: &Client,
+    repos: &[MavenRepository],
+    cache: &LocalCache,
+    activation: &ActivationContext,
+    pom: &mut Pom,
+    owner: &str,
+) -> miette::Result<Vec<(String, String, String)>> {
+    let coords = |pom: &Pom| -> Vec<(String, String, String)> {
+        pom.bom_imports()
+            .into_iter()
+            .filter_map(|d| {
+                let version = pom.interpolate(d.version.as_deref()?);
+                Some((
+                    pom.interpolate(&d.group_id),
+                    pom.interpolate(&d.artifact_id),
+                    version,
+                ))
+            })
+            .collect()
+    };
+    let mut pending: VecDeque<(String, String, String)> = coords(pom).into();
+    let mut seen: HashSet<String> = HashSet::from([owner.to_string()]);
+    let mut imported = Vec::new();
+
+    while let Some(coord) = pending.pop_front() {
+        let key = format!("{}:{}:{}", coord.0, coord.1, coord.2);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        if imported.len() >= MAX_BOM_IMPORTS {
+            tracing::warn!("{owner} imports more than {MAX_BOM_IMPORTS} BOMs; stopping at {key}");
+            break;
+        }
+        let Some(bom) = fetch_inherited_pom(
+            client,
+            repos,
+            cache,
+            activation,
+            (&coord.0, &coord.1, &coord.2),
+        )
+        .await?
+        else {
+            tracing::warn!("BOM {key} imported by {owner} not found");
+            continue;
+        };
+        let mut bom = bom.pom;
+        bom.resolve_properties();
+        pom.import_bom(&bom);
+        for nested in coords(&bom).into_iter().rev() {
+            pending.push_front(nested);
+        }
+        imported.push(coord);
+    }
+    Ok(imported)
+}
+
+/// Fetch a POM and merge its parent chain into it, nearest parent first.
+/// Every POM in the chain activates its own profiles (see
+/// [`Pom::apply_profiles`]) before it is merged.
+///
+/// Each parent is looked up on disk via `<relativePath>` next to the POM that
+/// references it (accepted only if its coordinates match), then in the cache
+/// and the repositories. A missing parent, a cycle, or a chain longer
+/// than [`MAX_PARENT_DEPTH`] stops the walk with a warning; the POM is still
+/// returned with whatever was inherited so far.
+async fn fetch_inherited_pom(
+    client: &Client,
+    repos: &[MavenRepository],
+    cache: &LocalCache,
+    activation: &ActivationContext,
+    (group, artifact, version): (&str, &str, &str),
+) -> miette::Result<Option<EffectivePom>> {
+    let Some(mut pom) =
+        fetch_pom_from_repos(client, repos, cache, group, artifact, version).await?
+    else {
+        return Ok(None);
+    };
+
+    let child_key = format!("{group}:{artifact}:{version}");
+    let mut profiles: Vec<(String, String)> = pom
+        .apply_profiles(activation)
+        .into_iter()
+        .map(|id| (child_key.clone(), id))
+        .collect();
+    let mut seen: HashSet<String> = HashSet::from([child_key.clone()]);
+    let mut dir = cache.artifact_dir(group, artifact, version);
+    let mut relative = pom.parent_relative_path().map(str::to_string);
+    let mut next = pom.parent.clone();
+    let mut parents = Vec::new();
+
+    while let Some(parent_ref) = next {
+        let coord = (
+            parent_ref.group_id.clone(),
+            parent_ref.artifact_id.clone(),
+            parent_ref.version.clone(),
+        );
+        let key = format!("{}:{}:{}", coord.0, coord.1, coord.2);
+        if !seen.insert(key.clone()) {
+            tracing::warn!("Parent POM cycle at {key} while resolving {child_key}");
+            break;
+        }
+        if parents.len() >= MAX_PARENT_DEPTH {
+            tracing::warn!(
+                "Parent POM chain of {child_key} exceeds {MAX_PARENT_DEPTH} levels; stopping at {key}"
+            );
+            break;
+        }
+
+        let local = relative
+            .as_deref()
+            .and_then(|rel| read_relative_parent(&dir, rel, &coord));
+        let mut parent = match local {
+            Some((parent, path)) => {
+                dir = path.parent().map(Path::to_path_buf).unwrap_or(dir);
+                parent
+            }
+            None => {
+                match fetch_pom_from_repos(client, repos, cache, &coord.0, &coord.1, &coord.2)
+                    .await?
+                {
+                    Some(parent) => {
+                        dir = cache.artifact_dir(&coord.0, &coord.1, &coord.2);
+                        parent
+                    }
+                    None => {
+                        tracing::warn!("Parent POM {key} of {child_key} not found");
+                        break;
+                    }
+                }
+            }
+        };
+
+        profiles.extend(
+            parent
+                .apply_profiles(activation)
+                .into_iter()
+                .map(|id| (key.clone(), id)),
+        );
+        pom.apply_parent(&parent);
+        relative = parent.parent_relative_path().map(str::to_string);
+        next = parent.parent.clone();
+        parents.push(coord);
+    }
+
+    Ok(Some(EffectivePom {
+        pom,
+        parents,
+        boms: Vec::new(),
+        profiles,
+    }))
+}
+
+/// Profile activation context for resolving `manifest`: the host OS and the
+/// project's JVM target (`java-target`, default 21) as the JDK, so resolution
+/// does not depend on which JDK happens to run Kargo.
+fn activation_context(manifest: &Manifest) -> ActivationContext {
+    let java_target = manifest
+        .targets
+        .values()
+        .find_map(|tc| tc.java_target.as_deref())
+        .and_then(|t| t.strip_prefix("1.").unwrap_or(t).parse().ok())
+        .unwrap_or(21);
+    ActivationContext::host(java_target)
+}
+
+/// Read a parent POM from `<relativePath>`, resolved against `dir`. Returns
+/// `None` unless it exists and declares the expected coordinates.
+fn read_relative_parent(
+    dir: &Path,
+    relative_path: &str,
+    (group, artifact, version): &(String, String, String),
+) -> Option<(Pom, PathBuf)> {
+    let mut path = dir.join(relative_path);
+    if path.is_dir() {
+        path = path.join("pom.xml");
+    }
+    let content = std::fs::read_to_string(&path).ok()?;
+    let pom = kargo_maven::pom::parse_pom(&content).ok()?;
+    let matches = pom.effective_group_id() == Some(group.as_str())
+        && pom.artifact_id.as_deref() == Some(artifact.as_str())
+        && pom.effective_version() == Some(version.as_str());
+    matches.then_some((pom, path))
+}
+
 /// Maven scope propagation rules.
-/// Processor scopes (`ksp`, `kapt`) propagate like `test`: all transitive
+/// `integration-test` and the processor scopes (`ksp`, `kapt`) propagate like `test`: all transitive
 /// deps inherit the processor scope so they stay out of the runtime classpath.
 fn propagate_scope(parent_scope: &str, dep_scope: &str) -> String {
     match (parent_scope, dep_scope) {
@@ -469,6 +1033,7 @@ fn propagate_scope(parent_scope: &str, dep_scope: &str) -> String {
         ("runtime", "compile") => "runtime",
         ("runtime", "runtime") => "runtime",
         ("test", _) => "test",
+        ("integration-test", _) => "integration-test",
         (_, "test") => "test",
         ("ksp", _) => "ksp",
         ("kapt", _) => "kapt",
@@ -485,7 +1050,10 @@ fn build_artifact_list(
 ) -> Vec<ResolvedArtifact> {
     let mut artifacts = Vec::new();
     for node in graph.all_nodes() {
-        let source = repos.first().map(|r| r.url.clone()).unwrap_or_default();
+        let source = kargo_maven::repository::for_group(repos, &node.group)
+            .next()
+            .map(|r| r.url.clone())
+            .unwrap_or_default();
 
         let node_idx = match graph.find(&node.key()) {
             Some(idx) => idx,
@@ -512,6 +1080,8 @@ fn build_artifact_list(
             source,
             checksum: None,
             dependencies: deps,
+            targets: Vec::new(),
+            relocated_from: None,
         });
     }
 
@@ -519,6 +1089,75 @@ fn build_artifact_list(
     artifacts
 }
 
+/// Propagate target restrictions from direct dependencies to their subtrees.
+///
+/// Anything reachable from an unrestricted direct dependency is needed on all
+/// targets and keeps an empty `targets` list. Everything else gets the union
+/// of the target sets of the restricted direct dependencies that reach it.
+fn assign_targets(
+    artifacts: &mut [ResolvedArtifact],
+    all_target_roots: &HashSet<String>,
+    restricted_roots: &HashMap<String, BTreeSet<String>>,
+) {
+    if restricted_roots.is_empty() {
+        return;
+    }
+    let children: HashMap<String, Vec<String>> = artifacts
+        .iter()
+        .map(|a| {
+            let deps = a
+                .dependencies
+                .iter()
+                .map(|d| format!("{}:{}", d.group, d.artifact))
+                .collect();
+            (format!("{}:{}", a.group, a.artifact), deps)
+        })
+        .collect();
+
+    let reachable_from = |roots: Vec<String>| -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<String> = roots.into_iter().collect();
+        while let Some(key) = queue.pop_front() {
+            if seen.insert(key.clone()) {
+                if let Some(deps) = children.get(&key) {
+                    queue.extend(deps.iter().cloned());
+                }
+            }
+        }
+        seen
+    };
+
+    let unrestricted = reachable_from(all_target_roots.iter().cloned().collect());
+    let mut restricted: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for (root, targets) in restricted_roots {
+        if unrestricted.contains(root) {
+            continue;
+        }
+        for key in reachable_from(vec![root.clone()]) {
+            if !unrestricted.contains(&key) {
+                restricted
+                    .entry(key)
+                    .or_default()
+                    .extend(targets.iter().cloned());
+            }
+        }
+    }
+
+    for artifact in artifacts {
+        let key = format!("{}:{}", artifact.group, artifact.artifact);
+        if let Some(targets) = restricted.remove(&key) {
+            artifact.targets = targets.into_iter().collect();
+        }
+    }
+}
+
+/// Apply the manifest's `[network]` limits to repository requests.
+pub fn configure_network(manifest: &Manifest) {
+    kargo_maven::throttle::configure(kargo_maven::throttle::NetworkLimits::from_config(
+        manifest.network.as_ref(),
+    ));
+}
+
 /// Build the list of repositories from a manifest, always including Maven Central.
 pub fn build_repos(manifest: &Manifest) -> Vec<MavenRepository> {
     let mut repos = Vec::new();
@@ -545,6 +1184,10 @@ mod tests {
         assert_eq!(propagate_scope("compile", "runtime"), "runtime");
         assert_eq!(propagate_scope("runtime", "compile"), "runtime");
         assert_eq!(propagate_scope("test", "compile"), "test");
+        assert_eq!(
+            propagate_scope("integration-test", "runtime"),
+            "integration-test"
+        );
         assert_eq!(propagate_scope("compile", "provided"), "provided");
     }
 
@@ -570,6 +1213,9 @@ kotlin = "2.3.0"
     #[test]
     fn lock_index_lookup() {
         let lockfile = Lockfile {
+            toolchain: None,
+            environment: None,
+            npm: vec![],
             package: vec![kargo_core::lockfile::LockedPackage {
                 name: "kotlinx-coroutines-core".to_string(),
                 group: "org.jetbrains.kotlinx".to_string(),
@@ -579,7 +1225,11 @@ kotlin = "2.3.0"
                 scope: None,
                 targets: vec![],
                 dependencies: vec![],
+                relocated_from: None,
+                members: Vec::new(),
             }],
+            file: Vec::new(),
+            members: Vec::new(),
         };
         let idx = build_lock_index(Some(&lockfile));
         assert_eq!(
@@ -603,4 +1253,471 @@ kotlin = "2.3.0"
         assert!(!repos.is_empty());
         assert!(repos.iter().any(|r| r.url.contains("maven.apache.org")));
     }
+
+    fn artifact(name: &str, deps: &[&str]) -> ResolvedArtifact {
+        ResolvedArtifact {
+            group: "g".to_string(),
+            artifact: name.to_string(),
+            version: "1.0".to_string(),
+            scope: "compile".to_string(),
+            source: String::new(),
+            checksum: None,
+            dependencies: deps
+                .iter()
+                .map(|d| ArtifactRef {
+                    group: "g".to_string(),
+                    artifact: d.to_string(),
+                    version: "1.0".to_string(),
+                })
+                .collect(),
+            targets: Vec::new(),
+            relocated_from: None,
+        }
+    }
+
+    #[test]
+    fn target_restrictions_propagate() {
+        // common -> shared; darwin -> shared, darwin-io; okhttp -> okio
+        let mut artifacts = vec![
+            artifact("common", &["shared"]),
+            artifact("darwin", &["shared", "darwin-io"]),
+            artifact("darwin-io", &[]),
+            artifact("okhttp", &["okio"]),
+            artifact("okio", &[]),
+            artifact("shared", &[]),
+        ];
+        let all_roots: HashSet<String> = ["g:common".to_string()].into();
+        let restricted: HashMap<String, BTreeSet<String>> = [
+            (
+                "g:darwin".to_string(),
+                ["ios-arm64".to_string(), "macos-arm64".to_string()].into(),
+            ),
+            ("g:okhttp".to_string(), ["jvm".to_string()].into()),
+        ]
+        .into();
+        assign_targets(&mut artifacts, &all_roots, &restricted);
+
+        let targets = |name: &str| {
+            artifacts
+                .iter()
+                .find(|a| a.artifact == name)
+                .unwrap()
+                .targets
+                .clone()
+        };
+        assert!(targets("common").is_empty());
+        assert!(targets("shared").is_empty());
+        assert_eq!(targets("darwin"), vec!["ios-arm64", "macos-arm64"]);
+        assert_eq!(targets("darwin-io"), vec!["ios-arm64", "macos-arm64"]);
+        assert_eq!(targets("okio"), vec!["jvm"]);
+    }
+
+    fn cache_pom(cache: &LocalCache, artifact: &str, version: &str, deps: &str) {
+        let dir = cache.artifact_dir("org.example", artifact, version);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{artifact}-{version}.pom")),
+            format!(
+                "<project><groupId>org.example</groupId><artifactId>{artifact}</artifactId>\
+                 <version>{version}</version>{deps}</project>"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn explanation_records_each_decision() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        cache_pom(
+            &cache,
+            "app-lib",
+            "1.0",
+            "<dependencyManagement><dependencies><dependency>\
+             <groupId>org.example</groupId><artifactId>managed</artifactId>\
+             <version>3.0</version></dependency></dependencies></dependencyManagement>\
+             <dependencies>\
+             <dependency><groupId>org.example</groupId><artifactId>managed</artifactId></dependency>\
+             <dependency><groupId>org.example</groupId><artifactId>pinned</artifactId>\
+             <version>2.1</version></dependency>\
+             <dependency><groupId>org.example</groupId><artifactId>shared</artifactId>\
+             <version>1.0</version></dependency>\
+             <dependency><groupId>org.example</groupId><artifactId>extra</artifactId>\
+             <version>1.0</version><optional>true</optional></dependency>\
+             </dependencies>",
+        );
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+app-lib = "org.example:app-lib:1.0"
+shared = "org.example:shared:2.0"
+"#,
+        )
+        .unwrap();
+        let locked = |name: &str, version: &str| kargo_core::lockfile::LockedPackage {
+            name: name.to_string(),
+            group: "org.example".to_string(),
+            version: version.to_string(),
+            checksum: None,
+            source: None,
+            scope: None,
+            targets: vec![],
+            dependencies: vec![],
+            relocated_from: None,
+            members: Vec::new(),
+        };
+        let lockfile = Lockfile {
+            package: vec![locked("app-lib", "1.0"), locked("pinned", "2.0")],
+            ..Default::default()
+        };
+
+        let result = resolve(&manifest, &[], &cache, Some(&lockfile), &Client::new())
+            .await
+            .unwrap();
+        let steps: Vec<String> = result
+            .explanation
+            .steps
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                "declared   org.example:app-lib:1.0 in [dependencies]",
+                "declared   org.example:shared:2.0 in [dependencies]",
+                "managed    org.example:managed -> 3.0 by <dependencyManagement> in \
+                 org.example:app-lib:1.0",
+                "locked     org.example:pinned 2.0 (Kargo.lock) instead of 2.1",
+                "skipped    org.example:extra from the POM of org.example:app-lib:1.0 (optional)",
+                "added      org.example:managed:3.0 (compile) from the POM of \
+                 org.example:app-lib:1.0",
+                "added      org.example:pinned:2.0 (compile) from the POM of \
+                 org.example:app-lib:1.0",
+                "conflict   org.example:shared kept 2.0 over 1.0 (nearest wins (depth 1 vs 2))",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn parent_chain_is_merged_before_interpolation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        // app-lib -> lib-parent (found via the default ../pom.xml)
+        //         -> root (cache/repository) -> app-lib (cycle, ignored)
+        cache_pom(
+            &cache,
+            "app-lib",
+            "1.0",
+            "<parent><groupId>org.example</groupId><artifactId>lib-parent</artifactId>\
+             <version>1.0</version></parent>\
+             <dependencies><dependency><groupId>org.example</groupId>\
+             <artifactId>core</artifactId></dependency></dependencies>",
+        );
+        std::fs::write(
+            cache
+                .artifact_dir("org.example", "app-lib", "1.0")
+                .join("../pom.xml"),
+            "<project><parent><groupId>org.example</groupId><artifactId>root</artifactId>\
+             <version>1.0</version><relativePath/></parent>\
+             <artifactId>lib-parent</artifactId>\
+             <properties><core.version>2.5</core.version></properties></project>",
+        )
+        .unwrap();
+        cache_pom(
+            &cache,
+            "root",
+            "1.0",
+            "<parent><groupId>org.example</groupId><artifactId>app-lib</artifactId>\
+             <version>1.0</version><relativePath/></parent>\
+             <properties><core.version>1.0</core.version></properties>\
+             <dependencyManagement><dependencies><dependency>\
+             <groupId>org.example</groupId><artifactId>core</artifactId>\
+             <version>${core.version}</version></dependency></dependencies>\
+             </dependencyManagement>",
+        );
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+app-lib = "org.example:app-lib:1.0"
+"#,
+        )
+        .unwrap();
+
+        let result = resolve(&manifest, &[], &cache, None, &Client::new())
+            .await
+            .unwrap();
+        let core = result
+            .artifacts
+            .iter()
+            .find(|a| a.artifact == "core")
+            .unwrap();
+        assert_eq!(core.version, "2.5");
+        assert_eq!(
+            result.parent_poms.into_iter().collect::<Vec<_>>(),
+            [
+                ("org.example".into(), "lib-parent".into(), "1.0".into()),
+                ("org.example".into(), "root".into(), "1.0".into()),
+            ]
+        );
+        assert!(result
+            .explanation
+            .to_string()
+            .contains("inherited  org.example:app-lib:1.0 from parent POM org.example:root:1.0"));
+    }
+
+    #[tokio::test]
+    async fn include_optional_follows_opted_in_optionals() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        cache_pom(
+            &cache,
+            "app-lib",
+            "1.0",
+            "<dependencies>\
+             <dependency><groupId>org.example</groupId><artifactId>poi</artifactId>\
+             <version>5.0</version><optional>true</optional></dependency>\
+             <dependency><groupId>org.example</groupId><artifactId>pdf</artifactId>\
+             <version>2.0</version><optional>true</optional></dependency>\
+             </dependencies>",
+        );
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+app-lib = { group = "org.example", artifact = "app-lib", version = "1.0", include-optional = ["org.example:poi"] }
+"#,
+        )
+        .unwrap();
+
+        let result = resolve(&manifest, &[], &cache, None, &Client::new())
+            .await
+            .unwrap();
+        let mut resolved: Vec<&str> = result
+            .artifacts
+            .iter()
+            .map(|a| a.artifact.as_str())
+            .collect();
+        resolved.sort();
+        assert_eq!(resolved, ["app-lib", "poi"]);
+
+        let optional: Vec<(&str, bool)> = result
+            .optional
+            .iter()
+            .map(|o| (o.artifact.as_str(), o.included))
+            .collect();
+        assert_eq!(optional, [("poi", true), ("pdf", false)]);
+        assert!(result
+            .optional
+            .iter()
+            .all(|o| o.declared_by == "org.example:app-lib:1.0"));
+    }
+
+    #[tokio::test]
+    async fn relocations_are_followed_and_recorded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        cache_pom(
+            &cache,
+            "old-lib",
+            "1.0",
+            "<distributionManagement><relocation>\
+             <artifactId>new-lib</artifactId><message>Renamed to new-lib</message>\
+             </relocation></distributionManagement>",
+        );
+        cache_pom(
+            &cache,
+            "new-lib",
+            "1.0",
+            "<dependencies><dependency><groupId>org.example</groupId>\
+             <artifactId>util</artifactId><version>2.0</version></dependency></dependencies>",
+        );
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+old-lib = "org.example:old-lib:1.0"
+"#,
+        )
+        .unwrap();
+
+        let result = resolve(&manifest, &[], &cache, None, &Client::new())
+            .await
+            .unwrap();
+        let mut resolved: Vec<(&str, Option<&str>)> = result
+            .artifacts
+            .iter()
+            .map(|a| (a.artifact.as_str(), a.relocated_from.as_deref()))
+            .collect();
+        resolved.sort();
+        assert_eq!(
+            resolved,
+            [("new-lib", Some("org.example:old-lib:1.0")), ("util", None)]
+        );
+        let relocation = &result.relocations[0];
+        assert!(relocation.direct);
+        assert_eq!(relocation.to, "org.example:new-lib:1.0");
+        assert_eq!(relocation.message.as_deref(), Some("Renamed to new-lib"));
+        assert!(result
+            .explanation
+            .to_string()
+            .contains("relocated  org.example:old-lib:1.0 -> org.example:new-lib:1.0"));
+    }
+
+    #[tokio::test]
+    async fn transitive_bom_imports_supply_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let managed = |artifact: &str, version: &str| {
+            format!(
+                "<dependency><groupId>org.example</groupId><artifactId>{artifact}</artifactId>\
+                 <version>{version}</version></dependency>"
+            )
+        };
+        let import = |artifact: &str, version: &str| {
+            format!(
+                "<dependency><groupId>org.example</groupId><artifactId>{artifact}</artifactId>\
+                 <version>{version}</version><type>pom</type><scope>import</scope></dependency>"
+            )
+        };
+        cache_pom(
+            &cache,
+            "app-lib",
+            "1.0",
+            &format!(
+                "<properties><platform.version>2.0</platform.version></properties>\
+                 <dependencyManagement><dependencies>{}</dependencies></dependencyManagement>\
+                 <dependencies>\
+                 <dependency><groupId>org.example</groupId><artifactId>util</artifactId></dependency>\
+                 <dependency><groupId>org.example</groupId><artifactId>extra</artifactId></dependency>\
+                 </dependencies>",
+                import("platform-bom", "${platform.version}")
+            ),
+        );
+        cache_pom(
+            &cache,
+            "platform-parent",
+            "2.0",
+            "<properties><util.version>4.2</util.version></properties>",
+        );
+        cache_pom(
+            &cache,
+            "platform-bom",
+            "2.0",
+            &format!(
+                "<parent><groupId>org.example</groupId><artifactId>platform-parent</artifactId>\
+                 <version>2.0</version></parent>\
+                 <dependencyManagement><dependencies>{}{}</dependencies></dependencyManagement>",
+                managed("util", "${util.version}"),
+                import("nested-bom", "1.0")
+            ),
+        );
+        cache_pom(
+            &cache,
+            "nested-bom",
+            "1.0",
+            &format!(
+                "<dependencyManagement><dependencies>{}{}</dependencies></dependencyManagement>",
+                managed("util", "9.9"),
+                managed("extra", "1.5")
+            ),
+        );
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+app-lib = "org.example:app-lib:1.0"
+"#,
+        )
+        .unwrap();
+
+        let result = resolve(&manifest, &[], &cache, None, &Client::new())
+            .await
+            .unwrap();
+        let mut resolved: Vec<(&str, &str)> = result
+            .artifacts
+            .iter()
+            .map(|a| (a.artifact.as_str(), a.version.as_str()))
+            .collect();
+        resolved.sort();
+        assert_eq!(
+            resolved,
+            [("app-lib", "1.0"), ("extra", "1.5"), ("util", "4.2")]
+        );
+        assert!(result.parent_poms.contains(&(
+            "org.example".into(),
+            "nested-bom".into(),
+            "1.0".into()
+        )));
+        assert!(result
+            .explanation
+            .to_string()
+            .contains("imported   BOM org.example:platform-bom:2.0 into org.example:app-lib:1.0"));
+    }
+
+    #[tokio::test]
+    async fn dependency_cycles_are_broken_and_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = LocalCache::new(tmp.path());
+        let dep = |artifact: &str, version: &str| {
+            format!(
+                "<dependencies><dependency><groupId>org.example</groupId>\
+                 <artifactId>{artifact}</artifactId><version>{version}</version>\
+                 </dependency></dependencies>"
+            )
+        };
+        cache_pom(&cache, "a", "1.0", &dep("b", "1.0"));
+        cache_pom(&cache, "b", "1.0", &dep("a", "0.9"));
+        let manifest = Manifest::parse_toml(
+            r#"
+[package]
+name = "app"
+version = "0.1.0"
+kotlin = "2.3.0"
+
+[dependencies]
+a = "org.example:a:1.0"
+"#,
+        )
+        .unwrap();
+
+        let result = resolve(&manifest, &[], &cache, None, &Client::new())
+            .await
+            .unwrap();
+        assert_eq!(result.artifacts.len(), 2);
+        let cycles: Vec<String> = result
+            .graph
+            .cycles()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            cycles,
+            ["org.example:a:1.0 -> org.example:b:1.0 -> org.example:a:1.0"]
+        );
+        assert!(result.explanation.to_string().contains(
+            "cycle      org.example:a:1.0 -> org.example:b:1.0 -> org.example:a:1.0 broken"
+        ));
+    }
 }
@@ -31,7 +31,7 @@ pub fn checksum_url(version: &KotlinVersion, mirror: Option<&str>) -> String {
 pub async fn download_file(url: &str, dest: &Path) -> miette::Result<PathBuf> {
     tracing::info!("Downloading {}", url);
 
-    let client = reqwest::Client::builder()
+    let client = kargo_util::http::client_builder()?
         .user_agent("kargo")
         .build()
         .map_err(|e| kargo_util::errors::KargoError::Network {
@@ -102,7 +102,7 @@ pub async fn fetch_checksum(
     mirror: Option<&str>,
 ) -> miette::Result<String> {
     let url = checksum_url(version, mirror);
-    let body = reqwest::Client::builder()
+    let body = kargo_util::http::client_builder()?
         .user_agent("kargo")
         .build()
         .map_err(|e| kargo_util::errors::KargoError::Network {
@@ -5,6 +5,7 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use kargo_util::errors::KargoError;
+use kargo_util::fs::long_path;
 
 use crate::download;
 use crate::version::KotlinVersion;
@@ -60,6 +61,7 @@ pub async fn install_kotlin(
             tracing::warn!("Checksum not available for Kotlin {version}, skipping verification");
         }
     }
+    kargo_util::trust::verify_file("kotlin", &version.to_string(), &url, &zip_path)?;
 
     // Extract
     print!("  Extracting... ");
@@ -126,7 +128,7 @@ pub fn list_installed() -> Vec<KotlinVersion> {
 
 /// Extract a zip archive to `dest`.
 fn extract_zip(zip_path: &Path, dest: &Path) -> miette::Result<()> {
-    let file = fs::File::open(zip_path).map_err(KargoError::Io)?;
+    let file = fs::File::open(long_path(zip_path)).map_err(KargoError::Io)?;
     let mut archive = zip::ZipArchive::new(file).map_err(|e| KargoError::Toolchain {
         message: format!("Failed to open zip: {e}"),
     })?;
@@ -136,7 +138,7 @@ fn extract_zip(zip_path: &Path, dest: &Path) -> miette::Result<()> {
             message: format!("Zip entry error: {e}"),
         })?;
 
-        let out_path = dest.join(entry.mangled_name());
+        let out_path = long_path(&dest.join(entry.mangled_name()));
 
         if entry.is_dir() {
             fs::create_dir_all(&out_path).map_err(KargoError::Io)?;
@@ -25,12 +25,19 @@ pub fn managed_android_sdk_dir() -> PathBuf {
 
 /// Discover an installed Android SDK and inventory its components.
 pub fn discover_android_sdk() -> Option<AndroidSdkInfo> {
+    discover_android_sdk_from(None)
+}
+
+/// Like [`discover_android_sdk`], but trying `local_sdk_dir` (the project's
+/// `local.properties` `sdk.dir`) first, as Android Studio and Gradle do.
+pub fn discover_android_sdk_from(local_sdk_dir: Option<&Path>) -> Option<AndroidSdkInfo> {
     let candidates: Vec<PathBuf> = [
+        local_sdk_dir.map(Path::to_path_buf),
         std::env::var("ANDROID_HOME").ok().map(PathBuf::from),
         std::env::var("ANDROID_SDK_ROOT").ok().map(PathBuf::from),
         Some(managed_android_sdk_dir()),
-        dirs_home().map(|h| h.join("Android/Sdk")),
-        dirs_home().map(|h| h.join("Library/Android/sdk")),
+        kargo_util::dirs::home_dir().map(|h| h.join("Android/Sdk")),
+        kargo_util::dirs::home_dir().map(|h| h.join("Library/Android/sdk")),
     ]
     .into_iter()
     .flatten()
@@ -194,6 +201,8 @@ pub async fn install_android_sdk(compile_sdk: u32) -> miette::Result<AndroidSdkI
     let tmp_dir = tempfile::tempdir().map_err(KargoError::Io)?;
     let zip_path = tmp_dir.path().join("cmdline-tools.zip");
     download::download_file(&cmdline_url, &zip_path).await?;
+    let name = cmdline_url.rsplit('/').next().unwrap_or(&cmdline_url);
+    kargo_util::trust::verify_file("android-cmdline-tools", name, &cmdline_url, &zip_path)?;
 
     let cmdline_dest = sdk_home.join("cmdline-tools").join("latest");
     kargo_util::fs::ensure_dir(&cmdline_dest).map_err(KargoError::Io)?;
@@ -320,10 +329,3 @@ fn print_android_sdk_instructions(compile_sdk: u32) {
     );
     println!();
 }
-
-fn dirs_home() -> Option<PathBuf> {
-    std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .ok()
-        .map(PathBuf::from)
-}
@@ -108,6 +108,14 @@ pub fn discover_jdk_for_target(config_jdk: Option<&str>, required_major: u32) ->
     None
 }
 
+impl JdkInfo {
+    /// Whether this JDK's `java` reads `@argfile` arguments, as every
+    /// `javac` does. The launcher gained them in JDK 9.
+    pub fn java_accepts_argfiles(&self) -> bool {
+        jdk_major(&self.version) >= 9
+    }
+}
+
 /// Parse the major version number from a JDK version string (e.g., "21" -> 21).
 pub fn jdk_major(version: &str) -> u32 {
     version.parse().unwrap_or(0)
@@ -127,7 +135,7 @@ fn discover_managed_jdk(min_major: Option<u32>) -> Option<JdkInfo> {
         })
         .filter(|(_, info)| min_major.map_or(true, |min| jdk_major(&info.version) >= min))
         .collect();
-    found.sort_by(|a, b| jdk_major(&b.1.version).cmp(&jdk_major(&a.1.version)));
+    found.sort_by_key(|(_, info)| std::cmp::Reverse(jdk_major(&info.version)));
     found.into_iter().next().map(|(_, info)| info)
 }
 
@@ -292,6 +300,12 @@ pub async fn install_jdk(
     let archive_path = tmp_dir.path().join(archive_name);
 
     download::download_file(&url, &archive_path).await?;
+    kargo_util::trust::verify_file(
+        "jdk",
+        &kargo_util::trust::platform_version(&dir_name),
+        &url,
+        &archive_path,
+    )?;
 
     kargo_util::fs::ensure_dir(&jdks_dir()).map_err(KargoError::Io)?;
 
@@ -17,6 +17,7 @@ use std::path::Path;
 use std::process::Command;
 
 use kargo_util::errors::KargoError;
+use kargo_util::fs::long_path;
 
 fn extract_zip_to(zip_path: &Path, dest: &Path) -> miette::Result<()> {
     let file = fs::File::open(zip_path).map_err(KargoError::Io)?;
@@ -28,7 +29,7 @@ fn extract_zip_to(zip_path: &Path, dest: &Path) -> miette::Result<()> {
         let mut entry = archive.by_index(i).map_err(|e| KargoError::Toolchain {
             message: format!("Zip entry error: {e}"),
         })?;
-        let out_path = dest.join(entry.mangled_name());
+        let out_path = long_path(&dest.join(entry.mangled_name()));
         if entry.is_dir() {
             fs::create_dir_all(&out_path).map_err(KargoError::Io)?;
         } else {
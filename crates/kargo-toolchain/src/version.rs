@@ -58,6 +58,12 @@ impl KotlinVersion {
     pub fn patch(&self) -> u64 {
         self.0.patch
     }
+
+    /// Returns `true` for releases without a pre-release suffix such as
+    /// `-Beta1` or `-RC`.
+    pub fn is_stable(&self) -> bool {
+        self.0.pre.is_empty()
+    }
 }
 
 impl fmt::Display for KotlinVersion {
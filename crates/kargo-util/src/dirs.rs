@@ -0,0 +1,274 @@
+//! Where Kargo keeps its per-user files: toolchains, JDKs, the global
+//! config and the build cache.
+//!
+//! `KARGO_HOME`, when set, holds all of them. Otherwise an existing
+//! `~/.kargo` keeps being used until [`migrate_legacy_home`] moves it, and
+//! new installs follow the platform's conventions:
+//!
+//! | Platform | Data and config                       | Cache                         |
+//! |----------|---------------------------------------|-------------------------------|
+//! | Linux    | `$XDG_DATA_HOME/kargo`                | `$XDG_CACHE_HOME/kargo`       |
+//! | macOS    | `~/Library/Application Support/kargo` | `~/Library/Caches/kargo`      |
+//! | Windows  | `%LOCALAPPDATA%\kargo`                | `%LOCALAPPDATA%\kargo\cache`  |
+//!
+//! `XDG_DATA_HOME` and `XDG_CACHE_HOME` default to `~/.local/share` and
+//! `~/.cache`. The `kargow` wrapper keeps its downloads in
+//! `~/.kargo/wrapper` (or `$KARGO_HOME/wrapper`) in every layout.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::fs::PathFlavor;
+
+/// Name of the legacy per-user directory in the home directory.
+pub const LEGACY_DIR_NAME: &str = ".kargo";
+
+/// Entries of `~/.kargo` that stay there after a migration, because the
+/// `kargow` scripts look for them in that place.
+const WRAPPER_DIR: &str = "wrapper";
+
+/// Entries of a legacy `~/.kargo` that belong in the cache directory.
+const CACHE_ENTRIES: &[&str] = &["build-cache", "cache"];
+
+/// Why the per-user directories are where they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `KARGO_HOME` is set.
+    KargoHome,
+    /// An existing `~/.kargo` from before platform directories were used.
+    Legacy,
+    /// XDG base directories, or the macOS and Windows equivalents.
+    Platform,
+}
+
+/// The per-user directories Kargo reads and writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KargoDirs {
+    /// Toolchains, JDKs, devices, plugins and `config.toml`.
+    pub data: PathBuf,
+    /// Files that can be deleted at any time, such as the build cache.
+    pub cache: PathBuf,
+    pub layout: Layout,
+}
+
+impl KargoDirs {
+    /// The directories for this process's environment.
+    pub fn from_env() -> Self {
+        Self::resolve(&|key| std::env::var_os(key), PathFlavor::host())
+    }
+
+    /// The directories for the environment variables `env` returns, on a
+    /// `flavor` platform. Only the legacy `~/.kargo` is looked up on disk.
+    pub fn resolve(env: &dyn Fn(&str) -> Option<OsString>, flavor: PathFlavor) -> Self {
+        if let Some(home) = env_path(env, "KARGO_HOME") {
+            return Self {
+                data: home.clone(),
+                cache: home,
+                layout: Layout::KargoHome,
+            };
+        }
+        let home = home_dir_from(env, flavor);
+        if let Some(legacy) = home.as_ref().map(|h| h.join(LEGACY_DIR_NAME)) {
+            if is_legacy_home(&legacy) {
+                return Self {
+                    data: legacy.clone(),
+                    cache: legacy,
+                    layout: Layout::Legacy,
+                };
+            }
+        }
+        Self::platform(env, flavor, home)
+    }
+
+    /// The platform directories, ignoring `KARGO_HOME` and `~/.kargo`.
+    fn platform(
+        env: &dyn Fn(&str) -> Option<OsString>,
+        flavor: PathFlavor,
+        home: Option<PathBuf>,
+    ) -> Self {
+        // Without a home directory, stay out of the working directory: it
+        // may be a project whose own `.kargo/` would collide.
+        let home = home.unwrap_or_else(|| std::env::temp_dir().join("kargo-home"));
+        let (data, cache) = match flavor {
+            PathFlavor::Unix => {
+                let data = xdg_path(env, "XDG_DATA_HOME")
+                    .unwrap_or_else(|| home.join(".local").join("share"));
+                let cache = xdg_path(env, "XDG_CACHE_HOME").unwrap_or_else(|| home.join(".cache"));
+                (data.join("kargo"), cache.join("kargo"))
+            }
+            PathFlavor::MacOs => {
+                let library = home.join("Library");
+                (
+                    library.join("Application Support").join("kargo"),
+                    library.join("Caches").join("kargo"),
+                )
+            }
+            PathFlavor::Windows => {
+                let data = env_path(env, "LOCALAPPDATA")
+                    .unwrap_or_else(|| home.join("AppData").join("Local"))
+                    .join("kargo");
+                let cache = data.join("cache");
+                (data, cache)
+            }
+        };
+        Self {
+            data,
+            cache,
+            layout: Layout::Platform,
+        }
+    }
+}
+
+/// The user's home directory, from `HOME` or `USERPROFILE`.
+pub fn home_dir() -> Option<PathBuf> {
+    home_dir_from(&|key| std::env::var_os(key), PathFlavor::host())
+}
+
+fn home_dir_from(env: &dyn Fn(&str) -> Option<OsString>, flavor: PathFlavor) -> Option<PathBuf> {
+    let keys: &[&str] = if flavor == PathFlavor::Windows {
+        &["USERPROFILE", "HOME"]
+    } else {
+        &["HOME", "USERPROFILE"]
+    };
+    keys.iter().find_map(|key| env_path(env, key))
+}
+
+/// `key` as a path, if it is set and not empty.
+fn env_path(env: &dyn Fn(&str) -> Option<OsString>, key: &str) -> Option<PathBuf> {
+    env(key).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// An XDG base directory variable. The spec says relative values are to be
+/// ignored like unset ones.
+fn xdg_path(env: &dyn Fn(&str) -> Option<OsString>, key: &str) -> Option<PathBuf> {
+    env_path(env, key).filter(|p| p.has_root())
+}
+
+/// Whether `dir` is a `~/.kargo` holding more than the wrapper's downloads.
+fn is_legacy_home(dir: &Path) -> bool {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name() != WRAPPER_DIR)
+        })
+        .unwrap_or(false)
+}
+
+/// What [`migrate_legacy_home`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub from: PathBuf,
+    pub to: KargoDirs,
+    /// The entries moved, as `(from, to)`.
+    pub moved: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Move `~/.kargo` into the platform directories: caches into the cache
+/// directory, everything else into the data directory. `None` when there
+/// is nothing to move or `KARGO_HOME` is set. Fails without moving
+/// anything if an entry already exists at its destination.
+pub fn migrate_legacy_home() -> io::Result<Option<Migration>> {
+    let env = |key: &str| std::env::var_os(key);
+    if env_path(&env, "KARGO_HOME").is_some() {
+        return Ok(None);
+    }
+    let Some(home) = home_dir() else {
+        return Ok(None);
+    };
+    let to = KargoDirs::platform(&env, PathFlavor::host(), Some(home.clone()));
+    migrate(&home.join(LEGACY_DIR_NAME), to)
+}
+
+/// Move the entries of the legacy directory `from` into `to`.
+pub fn migrate(from: &Path, to: KargoDirs) -> io::Result<Option<Migration>> {
+    if !is_legacy_home(from) {
+        return Ok(None);
+    }
+    let mut moves = Vec::new();
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == WRAPPER_DIR {
+            continue;
+        }
+        let dest_dir = if CACHE_ENTRIES.iter().any(|c| name == *c) {
+            &to.cache
+        } else {
+            &to.data
+        };
+        let dest = dest_dir.join(&name);
+        if dest.symlink_metadata().is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists; move or remove it before migrating {}",
+                    dest.display(),
+                    from.display()
+                ),
+            ));
+        }
+        moves.push((entry.path(), dest));
+    }
+    moves.sort();
+    for (src, dest) in &moves {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        move_entry(src, dest)?;
+    }
+    if std::fs::read_dir(from)?.next().is_none() {
+        std::fs::remove_dir(from)?;
+    }
+    Ok(Some(Migration {
+        from: from.to_path_buf(),
+        to,
+        moved: moves,
+    }))
+}
+
+/// Rename `src` to `dest`, copying and deleting when they are on different
+/// filesystems.
+fn move_entry(src: &Path, dest: &Path) -> io::Result<()> {
+    if std::fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    copy_entry(src, dest)?;
+    if src.is_dir() && !src.is_symlink() {
+        std::fs::remove_dir_all(src)
+    } else {
+        std::fs::remove_file(src)
+    }
+}
+
+fn copy_entry(src: &Path, dest: &Path) -> io::Result<()> {
+    let meta = src.symlink_metadata()?;
+    if meta.is_symlink() {
+        copy_symlink(src, dest)
+    } else if meta.is_dir() {
+        std::fs::create_dir_all(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(src, dest).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+}
+
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    let target = std::fs::read_link(src)?;
+    if src.is_dir() {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
 use miette::Diagnostic;
 use thiserror::Error;
 
@@ -29,10 +31,143 @@ pub enum KargoError {
     #[error("Toolchain error: {message}")]
     Toolchain { message: String },
 
+    /// Tests ran and at least one failed.
+    #[error("{message}")]
+    Test { message: String },
+
+    /// `kargo audit` found vulnerabilities at or above `--fail-on`.
+    #[error("{message}")]
+    Audit { message: String },
+
     /// Catch-all for miscellaneous errors.
     #[error("{message}")]
     Generic { message: String },
 }
 
+impl KargoError {
+    /// The process exit code for this error; see [`exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Io(_) | Self::Generic { .. } => exit_code::FAILURE,
+            Self::Manifest { .. } => exit_code::MANIFEST,
+            Self::Resolution { .. } => exit_code::RESOLUTION,
+            Self::Compilation { .. } => exit_code::COMPILE,
+            Self::Network { .. } => exit_code::NETWORK,
+            Self::Toolchain { .. } => exit_code::TOOLCHAIN,
+            Self::Test { .. } => exit_code::TEST,
+            Self::Audit { .. } => exit_code::AUDIT,
+        }
+    }
+
+    /// The category name `--error-format short` prints, e.g. `compile`.
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::Generic { .. } => "error",
+            Self::Manifest { .. } => "manifest",
+            Self::Resolution { .. } => "resolution",
+            Self::Compilation { .. } => "compile",
+            Self::Network { .. } => "network",
+            Self::Toolchain { .. } => "toolchain",
+            Self::Test { .. } => "test",
+            Self::Audit { .. } => "audit",
+        }
+    }
+}
+
+/// Process exit codes, one per failure category. Scripts and CI may rely
+/// on them: codes are never reused for another category.
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    /// A failure outside the categories below.
+    pub const FAILURE: i32 = 1;
+    /// Invalid command-line arguments (reported by the argument parser).
+    pub const USAGE: i32 = 2;
+    /// The sources do not compile or type-check.
+    pub const COMPILE: i32 = 3;
+    /// Tests ran and at least one failed.
+    pub const TEST: i32 = 4;
+    /// Dependencies could not be resolved, verified or kept locked.
+    pub const RESOLUTION: i32 = 5;
+    /// `kargo audit --fail-on` found vulnerabilities.
+    pub const AUDIT: i32 = 6;
+    /// `Kargo.toml` is invalid.
+    pub const MANIFEST: i32 = 7;
+    /// A download or other network request failed.
+    pub const NETWORK: i32 = 8;
+    /// No usable Kotlin compiler, JDK or SDK.
+    pub const TOOLCHAIN: i32 = 9;
+    /// `kargo check --all`: this plus one bit per failed category (33-63).
+    pub const CHECK_ALL: i32 = 32;
+    /// A bug in Kargo: it panicked.
+    pub const INTERNAL: i32 = 101;
+}
+
+/// The exit code for `report`: its [`KargoError`]'s, or
+/// [`exit_code::FAILURE`] for other errors.
+pub fn exit_code_of(report: &miette::Report) -> i32 {
+    report
+        .downcast_ref::<KargoError>()
+        .map_or(exit_code::FAILURE, KargoError::exit_code)
+}
+
+/// How errors and compiler diagnostics are printed on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Rendered reports with help text and source snippets.
+    #[default]
+    Human,
+    /// One line per error or diagnostic, for tools that parse stderr.
+    Short,
+}
+
+impl ErrorFormat {
+    /// Parse an `--error-format` value.
+    pub fn parse(s: &str) -> miette::Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "short" => Ok(Self::Short),
+            other => Err(KargoError::Generic {
+                message: format!("Unknown error format '{other}'. Available: human, short"),
+            }
+            .into()),
+        }
+    }
+}
+
+static ERROR_FORMAT: AtomicU8 = AtomicU8::new(ErrorFormat::Human as u8);
+
+/// Set the process-wide error format.
+pub fn set_error_format(format: ErrorFormat) {
+    ERROR_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// The process-wide error format.
+pub fn error_format() -> ErrorFormat {
+    match ERROR_FORMAT.load(Ordering::Relaxed) {
+        1 => ErrorFormat::Short,
+        _ => ErrorFormat::Human,
+    }
+}
+
+/// `report` as one `error[<category>]: <message>` line: the messages of
+/// the error and its causes joined by `: `, with whitespace collapsed.
+pub fn short_line(report: &miette::Report) -> String {
+    let category = report
+        .downcast_ref::<KargoError>()
+        .map_or("error", KargoError::category);
+    let message = report
+        .chain()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(": ");
+    format!("error[{category}]: {}", single_line(&message))
+}
+
+/// `text` with every run of whitespace, including newlines, as one space.
+pub fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Convenience alias for `miette::Result<T>`.
 pub type KargoResult<T> = miette::Result<T>;
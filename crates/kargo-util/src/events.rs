@@ -0,0 +1,140 @@
+//! Build lifecycle events for machine consumers.
+//!
+//! Events are JSON objects with an `"event"` field, written one per line.
+//! They go to stdout when a command runs with `--message-format
+//! json-diagnostics` (see [`set_stdout`]), and to an external observer when
+//! `KARGO_EVENT_SOCKET` names a unix socket, or on Windows a named pipe
+//! (`\\.\pipe\<name>`), that the observer is listening on.
+//!
+//! The observer is connected on the first event. If it cannot be reached,
+//! or stops reading for longer than [`WRITE_TIMEOUT`], Kargo logs a warning
+//! and carries on without it: observing a build never fails it. Lines sent
+//! to the observer also carry the `pid` of the Kargo process and a
+//! `timestamp` in milliseconds since the Unix epoch, since one observer may
+//! watch several builds at once.
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+/// Environment variable naming the observer's socket or pipe.
+pub const SOCKET_ENV: &str = "KARGO_EVENT_SOCKET";
+
+/// How long a write to the observer may block before it is dropped.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+static STDOUT: AtomicBool = AtomicBool::new(false);
+
+type Observer = Box<dyn Write + Send>;
+
+fn observer() -> &'static Mutex<Option<Observer>> {
+    static OBSERVER: OnceLock<Mutex<Option<Observer>>> = OnceLock::new();
+    OBSERVER.get_or_init(|| {
+        let observer = std::env::var_os(SOCKET_ENV)
+            .filter(|path| !path.is_empty())
+            .and_then(|path| match connect(Path::new(&path)) {
+                Ok(observer) => Some(observer),
+                Err(e) => {
+                    tracing::warn!(
+                        "Not sending build events to {}: {e}",
+                        Path::new(&path).display()
+                    );
+                    None
+                }
+            });
+        Mutex::new(observer)
+    })
+}
+
+/// Open the observer endpoint at `path`: a unix socket, or on Windows a
+/// named pipe.
+pub fn connect(path: &Path) -> std::io::Result<Box<dyn Write + Send>> {
+    #[cfg(unix)]
+    {
+        let stream = std::os::unix::net::UnixStream::connect(path)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        Ok(Box::new(stream))
+    }
+    #[cfg(not(unix))]
+    {
+        let pipe = std::fs::OpenOptions::new().write(true).open(path)?;
+        Ok(Box::new(pipe))
+    }
+}
+
+/// Also print events on stdout (`--message-format json-diagnostics`).
+pub fn set_stdout(enabled: bool) {
+    STDOUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether events are printed on stdout.
+pub fn stdout_enabled() -> bool {
+    STDOUT.load(Ordering::Relaxed)
+}
+
+/// Whether anyone receives events, so callers can skip building them.
+pub fn enabled() -> bool {
+    stdout_enabled() || observer().lock().map(|o| o.is_some()).unwrap_or(false)
+}
+
+/// Send `event` to stdout and the observer, whichever are enabled.
+pub fn emit(event: Value) {
+    if stdout_enabled() {
+        let mut out = std::io::stdout().lock();
+        let _ = writeln!(out, "{event}");
+        let _ = out.flush();
+    }
+    let Ok(mut observer) = observer().lock() else {
+        return;
+    };
+    let Some(sink) = observer.as_mut() else {
+        return;
+    };
+    let line = format!("{}\n", stamped(event));
+    if let Err(e) = sink.write_all(line.as_bytes()).and_then(|()| sink.flush()) {
+        tracing::warn!("Build event observer went away: {e}");
+        *observer = None;
+    }
+}
+
+/// `event` with the `pid` and `timestamp` fields of observer lines.
+fn stamped(mut event: Value) -> Value {
+    if let Value::Object(fields) = &mut event {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        fields.insert("pid".into(), std::process::id().into());
+        fields.insert("timestamp".into(), millis.into());
+    }
+    event
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn observer_lines_are_stamped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("events.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+        let mut sink = connect(&path).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let event = stamped(serde_json::json!({ "event": "start" }));
+        writeln!(sink, "{event}").unwrap();
+        drop(sink);
+
+        let line = BufReader::new(stream).lines().next().unwrap().unwrap();
+        let received: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(received["event"], "start");
+        assert_eq!(received["pid"], std::process::id());
+        assert!(received["timestamp"].as_u64().unwrap() > 0);
+    }
+}
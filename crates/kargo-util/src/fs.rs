@@ -39,3 +39,138 @@ pub fn dir_size(path: &Path) -> u64 {
         })
         .unwrap_or(0)
 }
+
+/// `path` in the form Windows file APIs accept beyond `MAX_PATH` (260
+/// characters): absolute paths get the `\\?\` extended-length prefix, UNC
+/// paths `\\?\UNC\`. Other paths, and every path on other platforms, are
+/// returned unchanged. Use it for the file operations themselves, not for
+/// paths shown to users or passed to external tools.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(extended) = path.to_str().and_then(extended_length_path) {
+        return PathBuf::from(extended);
+    }
+    path.to_path_buf()
+}
+
+/// The extended-length form of the absolute Windows path `path`, or `None`
+/// if it is relative or already a `\\?\` or `\\.\` path. Slashes become
+/// backslashes and `.` and `..` are resolved, since Windows does not
+/// normalize extended-length paths.
+pub fn extended_length_path(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    let bytes = path.as_bytes();
+    let (prefix, rest, fixed) = if let Some(unc) = path.strip_prefix(r"\\") {
+        // `..` never climbs above `\\server\share`.
+        (r"\\?\UNC\".to_string(), unc, 2)
+    } else if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":\\" {
+        (format!(r"\\?\{}", &path[..3]), &path[3..], 0)
+    } else {
+        return None;
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in rest.split('\\') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if parts.len() > fixed {
+                    parts.pop();
+                }
+            }
+            other => parts.push(other),
+        }
+    }
+    Some(format!("{prefix}{}", parts.join("\\")))
+}
+
+/// Path conventions of a host platform's filesystem, for
+/// [`normalize_path_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathFlavor {
+    /// `/` separators, case-sensitive names (Linux and other Unixes).
+    Unix,
+    /// `/` separators, case-insensitive names (APFS and HFS+ defaults).
+    MacOs,
+    /// `\` or `/` separators, case-insensitive names, drive letters, UNC
+    /// shares and `\\?\` prefixes.
+    Windows,
+}
+
+impl PathFlavor {
+    /// The flavor of the platform Kargo runs on.
+    pub fn host() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Unix
+        }
+    }
+
+    /// Whether names that differ only in case refer to the same file.
+    pub fn case_insensitive(self) -> bool {
+        self != Self::Unix
+    }
+}
+
+/// A canonical spelling of `path` on this platform, equal for every
+/// spelling of the same file: see [`normalize_path_for`]. Use it to compare
+/// paths and to key fingerprints and caches, not to open files.
+pub fn normalize_path(path: &Path) -> String {
+    normalize_path_for(&path.to_string_lossy(), PathFlavor::host())
+}
+
+/// `path` with `/` separators, without `.` and `..` segments (resolved
+/// lexically, symlinks are not followed) and without a `\\?\` prefix, and
+/// lowercased where names are case-insensitive. So `Src/Main/../main/A.kt`
+/// and `src/main/A.kt` compare equal on macOS and Windows.
+pub fn normalize_path_for(path: &str, flavor: PathFlavor) -> String {
+    let mut path = path.to_string();
+    if flavor == PathFlavor::Windows {
+        if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+            path = format!(r"\\{unc}");
+        } else if let Some(local) = path.strip_prefix(r"\\?\") {
+            path = local.to_string();
+        }
+        path = path.replace('\\', "/");
+    }
+    let root = if flavor == PathFlavor::Windows && path.starts_with("//") {
+        "//"
+    } else if path.starts_with('/') {
+        "/"
+    } else {
+        ""
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in path[root.len()..].split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => match parts.last() {
+                // `..` never climbs above a drive or the root.
+                Some(&drive)
+                    if flavor == PathFlavor::Windows
+                        && parts.len() == 1
+                        && drive.ends_with(':') => {}
+                None if !root.is_empty() => {}
+                Some(&last) if last != ".." => {
+                    parts.pop();
+                }
+                _ => parts.push(".."),
+            },
+            other => parts.push(other),
+        }
+    }
+    let normalized = match (root, parts.is_empty()) {
+        ("", true) => ".".to_string(),
+        _ => format!("{root}{}", parts.join("/")),
+    };
+    if flavor.case_insensitive() {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
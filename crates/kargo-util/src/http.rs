@@ -0,0 +1,182 @@
+//! HTTP client settings shared by every crate that talks to the network.
+//!
+//! Corporate networks often intercept TLS with their own root CA, require a
+//! client certificate, or only route one IP family. [`configure`] records the
+//! settings from `[network]` once at startup, and every HTTP client Kargo
+//! creates starts from [`client_builder`], so repository downloads, toolchain
+//! downloads and API calls all honour them.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::KargoError;
+
+/// TLS implementation used for HTTPS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TlsBackend {
+    /// rustls with the bundled Mozilla root certificates.
+    #[default]
+    Rustls,
+    /// The platform's TLS library (SChannel, Secure Transport, OpenSSL),
+    /// which also trusts the system certificate store.
+    Native,
+}
+
+/// Lowest TLS version accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// IP family used for outgoing connections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IpVersion {
+    #[default]
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+/// Settings applied to every HTTP client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpSettings {
+    pub tls_backend: TlsBackend,
+    pub min_tls_version: Option<TlsVersion>,
+    /// PEM file with extra root certificates to trust.
+    pub ca_bundle: Option<PathBuf>,
+    /// Also trust the backend's built-in roots (default true).
+    pub tls_built_in_roots: bool,
+    /// PEM client certificate for mutual TLS, with its key in `client_key`
+    /// or in the same file.
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    pub ip_version: IpVersion,
+}
+
+impl HttpSettings {
+    /// Defaults: rustls, built-in roots, any IP family.
+    pub fn new() -> Self {
+        Self {
+            tls_backend: TlsBackend::default(),
+            min_tls_version: None,
+            ca_bundle: None,
+            tls_built_in_roots: true,
+            client_cert: None,
+            client_key: None,
+            ip_version: IpVersion::default(),
+        }
+    }
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static SETTINGS: RwLock<Option<HttpSettings>> = RwLock::new(None);
+
+/// Use `settings` for every client created from now on.
+pub fn configure(settings: HttpSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = Some(settings);
+}
+
+/// The settings in effect.
+pub fn settings() -> HttpSettings {
+    SETTINGS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// A client builder with the configured TLS, certificate and IP settings.
+/// Fails if a configured certificate file cannot be read or parsed.
+pub fn client_builder() -> miette::Result<reqwest::ClientBuilder> {
+    apply(reqwest::Client::builder(), &settings())
+}
+
+/// Apply `settings` to `builder`.
+pub fn apply(
+    builder: reqwest::ClientBuilder,
+    settings: &HttpSettings,
+) -> miette::Result<reqwest::ClientBuilder> {
+    let mut builder = match settings.tls_backend {
+        TlsBackend::Rustls => builder.use_rustls_tls(),
+        TlsBackend::Native => builder.use_native_tls(),
+    };
+    builder = builder.tls_built_in_root_certs(settings.tls_built_in_roots);
+
+    if let Some(version) = settings.min_tls_version {
+        builder = builder.min_tls_version(match version {
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        });
+    }
+
+    if let Some(path) = &settings.ca_bundle {
+        let pem = read(path, "CA bundle")?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| invalid(path, "CA bundle", e))?;
+        if certs.is_empty() {
+            return Err(KargoError::Network {
+                message: format!("CA bundle {} contains no certificates", path.display()),
+            }
+            .into());
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(cert_path) = &settings.client_cert {
+        let cert = read(cert_path, "client certificate")?;
+        let key = match &settings.client_key {
+            Some(key_path) => read(key_path, "client key")?,
+            None => cert.clone(),
+        };
+        let identity = match settings.tls_backend {
+            TlsBackend::Rustls => {
+                let mut pem = cert;
+                pem.push(b'\n');
+                pem.extend_from_slice(&key);
+                reqwest::Identity::from_pem(&pem)
+            }
+            TlsBackend::Native => reqwest::Identity::from_pkcs8_pem(&cert, &key),
+        }
+        .map_err(|e| invalid(cert_path, "client certificate", e))?;
+        builder = builder.identity(identity);
+    }
+
+    builder = match settings.ip_version {
+        IpVersion::Any => builder,
+        IpVersion::Ipv4 => builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        IpVersion::Ipv6 => builder.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+    };
+
+    Ok(builder)
+}
+
+fn read(path: &std::path::Path, what: &str) -> miette::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        KargoError::Network {
+            message: format!("Failed to read {what} {}: {e}", path.display()),
+        }
+        .into()
+    })
+}
+
+fn invalid(path: &std::path::Path, what: &str, e: reqwest::Error) -> miette::Report {
+    KargoError::Network {
+        message: format!("Invalid {what} {}: {e}", path.display()),
+    }
+    .into()
+}
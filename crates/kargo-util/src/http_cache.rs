@@ -0,0 +1,116 @@
+//! Validators (`ETag`, `Last-Modified`) of HTTP responses that change in
+//! place, such as `maven-metadata.xml` and release listings.
+//!
+//! The last body of each URL is kept with its validators under
+//! `http/` in the Kargo cache directory. The next request for the URL is
+//! conditional (`If-None-Match`, `If-Modified-Since`), and a `304 Not
+//! Modified` answer is served from the stored body, so a server that has
+//! nothing new sends headers only.
+
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+/// A stored response: its validators and body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /// `request` made conditional on the body having changed.
+    pub fn conditional(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let mut request = request;
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(date) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, date);
+        }
+        request
+    }
+}
+
+/// Responses stored by URL.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    /// The store in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The store in the Kargo cache directory.
+    pub fn user() -> Self {
+        Self::new(crate::cache_path().join("http"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The stored response for `url`.
+    pub fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let (meta, body) = self.paths(url);
+        let meta = std::fs::read_to_string(meta).ok()?;
+        let mut cached: CachedResponse = serde_json::from_str(&meta).ok()?;
+        cached.body = std::fs::read(body).ok()?;
+        Some(cached)
+    }
+
+    /// Store the response to `url` if `headers` has a validator; without
+    /// one a later request could not be conditional. Failures only cost
+    /// the next request its shortcut, so they are logged, not returned.
+    pub fn store(&self, url: &str, headers: &HeaderMap, body: &[u8]) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let cached = CachedResponse {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            body: Vec::new(),
+        };
+        if cached.etag.is_none() && cached.last_modified.is_none() {
+            return;
+        }
+        let (meta, body_path) = self.paths(url);
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&body_path, body))
+            .and_then(|_| {
+                let json = serde_json::to_string(&cached).map_err(std::io::Error::other)?;
+                std::fs::write(&meta, json)
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache the response from {url}: {e}");
+        }
+    }
+
+    /// Forget the stored response for `url`.
+    pub fn remove(&self, url: &str) {
+        let (meta, body) = self.paths(url);
+        let _ = std::fs::remove_file(meta);
+        let _ = std::fs::remove_file(body);
+    }
+
+    /// The validators and body files of `url`, named by its hash so that
+    /// any URL maps to a plain file name.
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = crate::hash::sha256_bytes(url.as_bytes());
+        (
+            self.dir.join(format!("{key}.json")),
+            self.dir.join(format!("{key}.body")),
+        )
+    }
+}
@@ -1,25 +1,38 @@
 //! Shared utilities for the Kargo build tool.
 //!
 //! This crate provides cross-cutting concerns used by all other Kargo crates:
-//! error types, filesystem helpers, cryptographic hashing, process spawning,
-//! and terminal progress indicators.
+//! error types, build events, per-user directories, filesystem helpers,
+//! cryptographic hashing, HTTP client settings and response validators,
+//! process spawning, terminal progress indicators, machine resource
+//! detection, pinned digests of downloaded tools, and XML escaping.
 
 // thiserror's Display impl for `Manifest { message }` triggers a
 // false-positive `unused_assignments` lint in generated code.
 #![allow(unused_assignments)]
 
+pub mod dirs;
 pub mod errors;
+pub mod events;
 pub mod fs;
 pub mod hash;
+pub mod http;
+pub mod http_cache;
 pub mod process;
 pub mod progress;
+pub mod system;
+pub mod trust;
+pub mod xml;
 
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-/// Returns the path to the Kargo data directory (`~/.kargo/`).
+/// Returns the path to the Kargo data directory: `$KARGO_HOME`, an existing
+/// `~/.kargo/`, or the platform's data directory (see [`dirs`]).
 pub fn dirs_path() -> PathBuf {
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .unwrap_or_else(|_| ".".to_string());
-    Path::new(&home).join(".kargo")
+    dirs::KargoDirs::from_env().data
+}
+
+/// Returns the path to the Kargo cache directory, which is the data
+/// directory unless platform directories are in use (see [`dirs`]).
+pub fn cache_path() -> PathBuf {
+    dirs::KargoDirs::from_env().cache
 }
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::process::{Command, Output};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc;
+
+use tempfile::TempPath;
 
 use crate::errors::KargoError;
 
@@ -12,6 +16,7 @@ pub struct CommandBuilder {
     args: Vec<String>,
     env: HashMap<String, String>,
     cwd: Option<String>,
+    response_file: bool,
 }
 
 impl CommandBuilder {
@@ -22,6 +27,7 @@ impl CommandBuilder {
             args: Vec::new(),
             env: HashMap::new(),
             cwd: None,
+            response_file: false,
         }
     }
 
@@ -49,16 +55,164 @@ impl CommandBuilder {
         self
     }
 
+    /// Pass the arguments in an `@argfile` instead of on the command line,
+    /// as kotlinc, javac and (since JDK 9) java accept. Long classpaths and
+    /// source lists then stay under the 32K command-line limit of Windows.
+    pub fn response_file(mut self, enabled: bool) -> Self {
+        self.response_file = enabled;
+        self
+    }
+
     /// Execute the command and return its output.
     pub fn exec(&self) -> Result<Output, KargoError> {
+        let (mut cmd, _args) = self.command()?;
+        cmd.output().map_err(KargoError::from)
+    }
+
+    /// Run the command with inherited stdin, stdout, and stderr and wait
+    /// for it to exit.
+    pub fn status(&self) -> Result<ExitStatus, KargoError> {
+        let (mut cmd, _args) = self.command()?;
+        cmd.status().map_err(KargoError::from)
+    }
+
+    /// Replace the current process with the command. Only returns if the
+    /// command could not be executed.
+    #[cfg(unix)]
+    pub fn exec_replace(&self) -> KargoError {
+        use std::os::unix::process::CommandExt;
+        match self.command() {
+            // The response file has to outlive this process.
+            Ok((mut cmd, args)) => {
+                let _ = args.map(|a| a.keep());
+                KargoError::from(cmd.exec())
+            }
+            Err(e) => e,
+        }
+    }
+
+    /// Execute the command, invoking `on_line` for each line of stdout or
+    /// stderr as soon as it is written. The full output is still collected
+    /// and returned, exactly as with [`exec`](Self::exec).
+    pub fn exec_streaming(
+        &self,
+        mut on_line: impl FnMut(StreamKind, &str),
+    ) -> Result<Output, KargoError> {
+        let (mut cmd, _args) = self.command()?;
+        let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+        let (tx, rx) = mpsc::channel::<(StreamKind, String)>();
+        let mut readers = Vec::new();
+        if let Some(out) = child.stdout.take() {
+            readers.push(spawn_line_reader(out, StreamKind::Stdout, tx.clone()));
+        }
+        if let Some(err) = child.stderr.take() {
+            readers.push(spawn_line_reader(err, StreamKind::Stderr, tx.clone()));
+        }
+        drop(tx);
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        for (kind, line) in rx {
+            on_line(kind, &line);
+            let buf = match kind {
+                StreamKind::Stdout => &mut stdout,
+                StreamKind::Stderr => &mut stderr,
+            };
+            buf.extend_from_slice(line.as_bytes());
+            buf.push(b'\n');
+        }
+        for reader in readers {
+            let _ = reader.join();
+        }
+
+        let status = child.wait()?;
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// The command to spawn, and the response file holding its arguments,
+    /// which must be kept until the process exits.
+    fn command(&self) -> Result<(Command, Option<TempPath>), KargoError> {
         let mut cmd = Command::new(&self.program);
-        cmd.args(&self.args);
+        let mut response_file = None;
+        if self.response_file && !self.args.is_empty() {
+            let mut file = tempfile::Builder::new()
+                .prefix("kargo-args-")
+                .suffix(".txt")
+                .tempfile()?;
+            file.write_all(response_file_contents(&self.args).as_bytes())?;
+            let path = file.into_temp_path();
+            cmd.arg(format!("@{}", path.display()));
+            response_file = Some(path);
+        } else {
+            cmd.args(&self.args);
+        }
         for (k, v) in &self.env {
             cmd.env(k, v);
         }
         if let Some(ref dir) = self.cwd {
             cmd.current_dir(Path::new(dir));
         }
-        cmd.output().map_err(KargoError::from)
+        Ok((cmd, response_file))
+    }
+}
+
+/// `args` as an `@argfile`: one double-quoted argument per line, with
+/// backslashes and quotes escaped, which kotlinc, javac and java all read
+/// back verbatim.
+pub fn response_file_contents(args: &[String]) -> String {
+    let mut contents = String::new();
+    for arg in args {
+        contents.push('"');
+        for c in arg.chars() {
+            if c == '\\' || c == '"' {
+                contents.push('\\');
+            }
+            contents.push(c);
+        }
+        contents.push_str("\"\n");
+    }
+    contents
+}
+
+/// Exit code to report for a finished child process. A child killed by a
+/// signal maps to `128 + signal`, as shells do.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
     }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// Which output stream a line passed to [`CommandBuilder::exec_streaming`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Stdout,
+    Stderr,
+}
+
+fn spawn_line_reader(
+    pipe: impl std::io::Read + Send + 'static,
+    kind: StreamKind,
+    tx: mpsc::Sender<(StreamKind, String)>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else { break };
+            if tx.send((kind, line)).is_err() {
+                break;
+            }
+        }
+    })
 }
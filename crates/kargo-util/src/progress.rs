@@ -1,13 +1,88 @@
 use std::io::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 use console::Style;
 use indicatif::{ProgressBar, ProgressStyle};
 
+/// How much non-error output Kargo prints, set once from `-q`/`-v`/`-vv`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// `-q`: errors only. Status lines, warnings, spinners and progress
+    /// bars are suppressed.
+    Quiet,
+    #[default]
+    Normal,
+    /// `-v`: extra detail such as the commands Kargo runs.
+    Verbose,
+    /// `-vv`: everything `-v` prints plus debug logging.
+    VeryVerbose,
+}
+
+impl Verbosity {
+    /// Level for `-q` and the number of `-v` flags.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::VeryVerbose,
+        }
+    }
+
+    /// Default `tracing` filter for this level (`RUST_LOG` takes precedence).
+    pub fn tracing_filter(self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "error",
+            Verbosity::Normal => "warn",
+            Verbosity::Verbose => "info",
+            Verbosity::VeryVerbose => "debug",
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Set the process-wide output policy.
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// The process-wide output policy.
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::VeryVerbose,
+    }
+}
+
+/// Whether non-error output is suppressed (`-q`).
+pub fn is_quiet() -> bool {
+    verbosity() == Verbosity::Quiet
+}
+
+/// Whether `-v` or `-vv` was given.
+pub fn is_verbose() -> bool {
+    verbosity() >= Verbosity::Verbose
+}
+
+/// Print an informational line on stderr unless output is quiet.
+pub fn note(message: &str) {
+    if !is_quiet() {
+        let _ = writeln!(std::io::stderr(), "{message}");
+    }
+}
+
 /// Print a Cargo-style status line: `    Compiling my-app v0.1.0`
 ///
 /// The `label` is right-padded to 12 characters and printed in bold green,
-/// followed by the `message` in the default terminal colour.
+/// followed by the `message` in the default terminal colour. Status lines
+/// are suppressed when output is quiet.
 pub fn status(label: &str, message: &str) {
+    if is_quiet() {
+        return;
+    }
     let green_bold = Style::new().green().bold();
     let _ = writeln!(
         std::io::stderr(),
@@ -18,6 +93,9 @@ pub fn status(label: &str, message: &str) {
 
 /// Like [`status`] but uses bold cyan for informational (non-action) messages.
 pub fn status_info(label: &str, message: &str) {
+    if is_quiet() {
+        return;
+    }
     let cyan_bold = Style::new().cyan().bold();
     let _ = writeln!(
         std::io::stderr(),
@@ -28,6 +106,9 @@ pub fn status_info(label: &str, message: &str) {
 
 /// Print a warning-style status line (bold yellow label).
 pub fn status_warn(label: &str, message: &str) {
+    if is_quiet() {
+        return;
+    }
     let yellow_bold = Style::new().yellow().bold();
     let _ = writeln!(
         std::io::stderr(),
@@ -37,10 +118,14 @@ pub fn status_warn(label: &str, message: &str) {
 }
 
 /// Create an animated spinner with the given message for indeterminate progress.
+/// The spinner is hidden when output is quiet.
 ///
 /// The spinner ticks automatically and should be finished with
 /// [`ProgressBar::finish_with_message`] or [`ProgressBar::finish_and_clear`].
 pub fn spinner(message: &str) -> ProgressBar {
+    if is_quiet() {
+        return ProgressBar::hidden();
+    }
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -54,6 +139,9 @@ pub fn spinner(message: &str) -> ProgressBar {
 
 /// Create a progress bar with the given length and message for determinate progress.
 pub fn progress_bar(len: u64, message: &str) -> ProgressBar {
+    if is_quiet() {
+        return ProgressBar::hidden();
+    }
     let pb = ProgressBar::new(len);
     pb.set_style(
         ProgressStyle::default_bar()
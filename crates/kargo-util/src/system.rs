@@ -0,0 +1,101 @@
+//! Machine resources used to size parallel work.
+//!
+//! Each compiler or test JVM needs its own heap, so running one per core on
+//! a machine (or container) with little free memory gets compiles OOM-killed.
+//! [`fit_to_memory`] caps a desired worker count by the memory that is
+//! actually available.
+
+/// Memory a kotlinc or test JVM is assumed to need when `[build] job-memory`
+/// is not set.
+pub const DEFAULT_JOB_MEMORY: u64 = 1024 * 1024 * 1024;
+
+/// Number of CPU cores available to this process.
+pub fn cores() -> u32 {
+    std::thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(4)
+}
+
+/// Memory available for new work, in bytes, if the platform reports it.
+///
+/// On Linux this is `MemAvailable` from `/proc/meminfo`, lowered to the
+/// headroom left under a cgroup v2 memory limit when running in a container.
+/// On macOS it is the free, inactive and speculative pages from `vm_stat`.
+pub fn available_memory() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let available = parse_meminfo(&meminfo)?;
+        let cgroup = std::fs::read_to_string("/sys/fs/cgroup/memory.max")
+            .ok()
+            .and_then(|max| max.trim().parse::<u64>().ok())
+            .map(|max| {
+                let used = std::fs::read_to_string("/sys/fs/cgroup/memory.current")
+                    .ok()
+                    .and_then(|c| c.trim().parse::<u64>().ok())
+                    .unwrap_or(0);
+                max.saturating_sub(used)
+            });
+        Some(cgroup.map_or(available, |c| c.min(available)))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("vm_stat").output().ok()?;
+        parse_vm_stat(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// `wanted` workers, reduced so that each gets `per_worker` bytes of the
+/// `available` memory. Never less than one; unknown memory leaves `wanted`
+/// unchanged.
+pub fn fit_to_memory(wanted: u32, per_worker: u64, available: Option<u64>) -> u32 {
+    let wanted = wanted.max(1);
+    match available {
+        Some(bytes) if per_worker > 0 => {
+            let fit = (bytes / per_worker).min(u64::from(u32::MAX)) as u32;
+            wanted.min(fit).max(1)
+        }
+        _ => wanted,
+    }
+}
+
+/// Parallel compiler/test JVMs for this machine: one per core, as many as
+/// fit in available memory at `per_job` bytes each.
+pub fn auto_jobs(per_job: u64) -> u32 {
+    fit_to_memory(cores(), per_job, available_memory())
+}
+
+/// `MemAvailable` from `/proc/meminfo`, in bytes.
+pub fn parse_meminfo(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Free, inactive and speculative memory from `vm_stat` output, in bytes.
+pub fn parse_vm_stat(text: &str) -> Option<u64> {
+    let page_size: u64 = text
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let pages = |label: &str| -> u64 {
+        text.lines()
+            .find_map(|line| line.strip_prefix(label))
+            .and_then(|rest| rest.trim().trim_end_matches('.').parse().ok())
+            .unwrap_or(0)
+    };
+    Some(
+        (pages("Pages free:") + pages("Pages inactive:") + pages("Pages speculative:")) * page_size,
+    )
+}
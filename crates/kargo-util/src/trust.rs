@@ -0,0 +1,204 @@
+//! Provenance pins for auto-downloaded tools (`kargo.trust`).
+//!
+//! A project opts in by committing a `kargo.trust` file next to its
+//! `Kargo.toml` (or the workspace root's). It records the SHA-256 digest of
+//! every tool archive Kargo downloads — the Kotlin compiler, JDKs, KSP, the
+//! Android command-line tools and the Dokka JARs — keyed by tool and by a
+//! version that includes the platform where the archive differs per
+//! platform (for Dokka, the JAR's Maven coordinate):
+//!
+//! ```toml
+//! [kotlin."2.3.0"]
+//! url = "https://github.com/JetBrains/kotlin/releases/download/v2.3.0/kotlin-compiler-2.3.0.zip"
+//! sha256 = "..."
+//! ```
+//!
+//! A download with a pin must match it, whatever mirror it came from. A
+//! download without one is pinned on first use. `kargo trust update`
+//! refreshes pins after a legitimate re-release.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::KargoError;
+
+/// File name of the trust file.
+pub const TRUST_FILE: &str = "kargo.trust";
+
+const HEADER: &str = "\
+# Expected SHA-256 digests of the tools Kargo downloads for this project.
+# A download that does not match its pin fails. Review changes to this file
+# like code; refresh pins with `kargo trust update`.
+";
+
+/// The digest a download must have.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    /// Where the pinned archive was downloaded from.
+    pub url: String,
+    pub sha256: String,
+}
+
+/// A parsed `kargo.trust`: tool name -> version -> pin.
+#[derive(Debug, Clone, Default)]
+pub struct TrustFile {
+    pub path: PathBuf,
+    pub tools: BTreeMap<String, BTreeMap<String, Pin>>,
+}
+
+impl TrustFile {
+    /// The trust file at or above `start`, if there is one.
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(TRUST_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Read the trust file at `path`.
+    pub fn load(path: &Path) -> miette::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(KargoError::Io)?;
+        let tools = toml::from_str(&content).map_err(|e| KargoError::Generic {
+            message: format!("Failed to parse {}: {e}", path.display()),
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            tools,
+        })
+    }
+
+    /// Write the pins back to [`path`](Self::path).
+    pub fn save(&self) -> miette::Result<()> {
+        let body = toml::to_string_pretty(&self.tools).map_err(|e| KargoError::Generic {
+            message: format!("Failed to serialize {TRUST_FILE}: {e}"),
+        })?;
+        std::fs::write(&self.path, format!("{HEADER}\n{body}")).map_err(KargoError::Io)?;
+        Ok(())
+    }
+
+    pub fn get(&self, tool: &str, version: &str) -> Option<&Pin> {
+        self.tools.get(tool)?.get(version)
+    }
+
+    pub fn insert(&mut self, tool: &str, version: &str, pin: Pin) {
+        self.tools
+            .entry(tool.to_string())
+            .or_default()
+            .insert(version.to_string(), pin);
+    }
+
+    /// Compare a download of `tool` `version` from `url` with its pin,
+    /// pinning it if it has none.
+    pub fn check(
+        &mut self,
+        tool: &str,
+        version: &str,
+        url: &str,
+        sha256: &str,
+    ) -> miette::Result<()> {
+        match self.get(tool, version) {
+            Some(pin) if pin.sha256.eq_ignore_ascii_case(sha256) => Ok(()),
+            Some(pin) => Err(KargoError::Toolchain {
+                message: format!(
+                    "{tool} {version} downloaded from {url} has SHA-256 {sha256}, but {} pins {}. \
+                     The release channel may be compromised; if the new release is genuine, \
+                     run `kargo trust update`.",
+                    self.path.display(),
+                    pin.sha256
+                ),
+            }
+            .into()),
+            None => {
+                self.insert(
+                    tool,
+                    version,
+                    Pin {
+                        url: url.to_string(),
+                        sha256: sha256.to_string(),
+                    },
+                );
+                self.save()?;
+                crate::progress::status_info(
+                    "Pinned",
+                    &format!("{tool} {version} in {}", self.path.display()),
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check the downloaded `file` against the trust file of the current
+/// project, if it has one.
+pub fn verify_file(tool: &str, version: &str, url: &str, file: &Path) -> miette::Result<()> {
+    let Some(mut trust) = current()? else {
+        return Ok(());
+    };
+    let sha256 = crate::hash::sha256_file(file)?;
+    trust.check(tool, version, url, &sha256)
+}
+
+/// [`verify_file`] for a download held in memory.
+pub fn verify_bytes(tool: &str, version: &str, url: &str, data: &[u8]) -> miette::Result<()> {
+    let Some(mut trust) = current()? else {
+        return Ok(());
+    };
+    trust.check(tool, version, url, &crate::hash::sha256_bytes(data))
+}
+
+/// The trust file at or above the working directory.
+fn current() -> miette::Result<Option<TrustFile>> {
+    let Ok(cwd) = std::env::current_dir() else {
+        return Ok(None);
+    };
+    TrustFile::find(&cwd)
+        .map(|p| TrustFile::load(&p))
+        .transpose()
+}
+
+/// `version` qualified with the OS and architecture, for archives that
+/// differ per platform.
+pub fn platform_version(version: &str) -> String {
+    format!(
+        "{version}-{}-{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downloads_are_pinned_then_must_match() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(TRUST_FILE);
+        std::fs::write(&path, HEADER).unwrap();
+
+        let mut trust = TrustFile::load(&path).unwrap();
+        trust
+            .check("kotlin", "2.3.0", "https://example.com/k.zip", "aa11")
+            .unwrap();
+
+        let mut reloaded = TrustFile::load(&path).unwrap();
+        assert_eq!(reloaded.get("kotlin", "2.3.0").unwrap().sha256, "aa11");
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("# Expected SHA-256"));
+        reloaded
+            .check(
+                "kotlin",
+                "2.3.0",
+                "https://mirror.example.com/k.zip",
+                "AA11",
+            )
+            .unwrap();
+        let err = reloaded
+            .check("kotlin", "2.3.0", "https://example.com/k.zip", "bb22")
+            .unwrap_err();
+        assert!(err.to_string().contains("kargo trust update"));
+    }
+}
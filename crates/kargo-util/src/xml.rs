@@ -0,0 +1,9 @@
+//! Helpers for writing XML by hand.
+
+/// Escape `s` for use in XML text or a double-quoted attribute value.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
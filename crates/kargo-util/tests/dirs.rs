@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use kargo_util::dirs::{migrate, KargoDirs, Layout};
+use kargo_util::fs::PathFlavor;
+use tempfile::TempDir;
+
+fn resolve(vars: &[(&str, &str)], flavor: PathFlavor) -> KargoDirs {
+    let vars: HashMap<String, OsString> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), OsString::from(v)))
+        .collect();
+    KargoDirs::resolve(&|key| vars.get(key).cloned(), flavor)
+}
+
+#[test]
+fn test_kargo_home_holds_everything() {
+    let dirs = resolve(
+        &[("KARGO_HOME", "/opt/kargo"), ("HOME", "/home/me")],
+        PathFlavor::Unix,
+    );
+    assert_eq!(dirs.layout, Layout::KargoHome);
+    assert_eq!(dirs.data, Path::new("/opt/kargo"));
+    assert_eq!(dirs.cache, Path::new("/opt/kargo"));
+}
+
+#[test]
+fn test_xdg_directories_on_linux() {
+    let dirs = resolve(
+        &[
+            ("HOME", "/home/me"),
+            ("XDG_DATA_HOME", "/data"),
+            ("XDG_CACHE_HOME", "/var/cache/me"),
+        ],
+        PathFlavor::Unix,
+    );
+    assert_eq!(dirs.layout, Layout::Platform);
+    assert_eq!(dirs.data, Path::new("/data/kargo"));
+    assert_eq!(dirs.cache, Path::new("/var/cache/me/kargo"));
+}
+
+#[test]
+fn test_xdg_defaults_ignore_empty_and_relative_values() {
+    let dirs = resolve(
+        &[
+            ("HOME", "/home/me"),
+            ("KARGO_HOME", ""),
+            ("XDG_DATA_HOME", "relative"),
+            ("XDG_CACHE_HOME", ""),
+        ],
+        PathFlavor::Unix,
+    );
+    assert_eq!(dirs.data, Path::new("/home/me/.local/share/kargo"));
+    assert_eq!(dirs.cache, Path::new("/home/me/.cache/kargo"));
+}
+
+#[test]
+fn test_macos_directories() {
+    let dirs = resolve(&[("HOME", "/Users/me")], PathFlavor::MacOs);
+    assert_eq!(
+        dirs.data,
+        Path::new("/Users/me/Library/Application Support/kargo")
+    );
+    assert_eq!(dirs.cache, Path::new("/Users/me/Library/Caches/kargo"));
+}
+
+#[test]
+fn test_windows_directories() {
+    let local = r"C:\Users\me\AppData\Local";
+    let dirs = resolve(
+        &[("USERPROFILE", r"C:\Users\me"), ("LOCALAPPDATA", local)],
+        PathFlavor::Windows,
+    );
+    assert_eq!(dirs.data, PathBuf::from(local).join("kargo"));
+    assert_eq!(dirs.cache, PathBuf::from(local).join("kargo").join("cache"));
+}
+
+#[test]
+fn test_no_home_stays_out_of_the_working_directory() {
+    let dirs = resolve(&[], PathFlavor::Unix);
+    assert!(dirs.data.is_absolute());
+    assert!(dirs.data.starts_with(std::env::temp_dir()));
+}
+
+#[test]
+fn test_existing_legacy_home_is_kept() {
+    let home = TempDir::new().unwrap();
+    let legacy = home.path().join(".kargo");
+    let home_str = home.path().to_str().unwrap();
+
+    // A ~/.kargo holding only the wrapper's downloads is not a data dir.
+    std::fs::create_dir_all(legacy.join("wrapper")).unwrap();
+    let dirs = resolve(&[("HOME", home_str)], PathFlavor::Unix);
+    assert_eq!(dirs.layout, Layout::Platform);
+
+    std::fs::create_dir_all(legacy.join("toolchains")).unwrap();
+    let dirs = resolve(&[("HOME", home_str)], PathFlavor::Unix);
+    assert_eq!(dirs.layout, Layout::Legacy);
+    assert_eq!(dirs.data, legacy);
+    assert_eq!(dirs.cache, legacy);
+}
+
+#[test]
+fn test_migrate_moves_data_and_caches_and_keeps_the_wrapper() {
+    let tmp = TempDir::new().unwrap();
+    let legacy = tmp.path().join(".kargo");
+    std::fs::create_dir_all(legacy.join("toolchains").join("2.3.0")).unwrap();
+    std::fs::create_dir_all(legacy.join("build-cache").join("ab")).unwrap();
+    std::fs::create_dir_all(legacy.join("wrapper").join("0.9.0")).unwrap();
+    std::fs::write(legacy.join("config.toml"), "[build]\njobs = 2\n").unwrap();
+    let to = KargoDirs {
+        data: tmp.path().join("data").join("kargo"),
+        cache: tmp.path().join("cache").join("kargo"),
+        layout: Layout::Platform,
+    };
+
+    let migration = migrate(&legacy, to.clone()).unwrap().unwrap();
+    assert_eq!(migration.moved.len(), 3);
+    assert!(to.data.join("toolchains").join("2.3.0").is_dir());
+    assert!(to.cache.join("build-cache").join("ab").is_dir());
+    assert_eq!(
+        std::fs::read_to_string(to.data.join("config.toml")).unwrap(),
+        "[build]\njobs = 2\n"
+    );
+    assert!(legacy.join("wrapper").join("0.9.0").is_dir());
+    assert!(!legacy.join("toolchains").exists());
+
+    // Only the wrapper is left, so there is nothing more to migrate.
+    assert_eq!(migrate(&legacy, to).unwrap(), None);
+}
+
+#[test]
+fn test_migrate_refuses_to_overwrite() {
+    let tmp = TempDir::new().unwrap();
+    let legacy = tmp.path().join(".kargo");
+    std::fs::create_dir_all(legacy.join("jdks")).unwrap();
+    std::fs::create_dir_all(legacy.join("toolchains")).unwrap();
+    let to = KargoDirs {
+        data: tmp.path().join("data"),
+        cache: tmp.path().join("cache"),
+        layout: Layout::Platform,
+    };
+    std::fs::create_dir_all(to.data.join("toolchains")).unwrap();
+
+    let err = migrate(&legacy, to.clone()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert!(legacy.join("jdks").is_dir());
+    assert!(!to.data.join("jdks").exists());
+}
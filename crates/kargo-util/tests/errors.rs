@@ -61,3 +61,76 @@ fn test_io_error_from_conversion() {
     let kargo_err: KargoError = io_err.into();
     matches!(kargo_err, KargoError::Io(_));
 }
+
+#[test]
+fn test_exit_codes_are_distinct_per_category() {
+    use kargo_util::errors::exit_code;
+    let codes = [
+        KargoError::Generic {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Manifest {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Resolution {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Compilation {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Network {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Toolchain {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Test {
+            message: String::new(),
+        }
+        .exit_code(),
+        KargoError::Audit {
+            message: String::new(),
+        }
+        .exit_code(),
+    ];
+    let unique: std::collections::HashSet<i32> = codes.iter().copied().collect();
+    assert_eq!(unique.len(), codes.len());
+    assert!(!codes.contains(&exit_code::USAGE));
+    assert_eq!(
+        KargoError::Compilation {
+            message: String::new()
+        }
+        .exit_code(),
+        exit_code::COMPILE
+    );
+}
+
+#[test]
+fn test_exit_code_of_report() {
+    use kargo_util::errors::{exit_code, exit_code_of};
+    let report: miette::Report = KargoError::Test {
+        message: "Tests failed".into(),
+    }
+    .into();
+    assert_eq!(exit_code_of(&report), exit_code::TEST);
+    assert_eq!(exit_code_of(&miette::miette!("other")), exit_code::FAILURE);
+}
+
+#[test]
+fn test_short_line_is_one_line_with_category() {
+    let report: miette::Report = KargoError::Resolution {
+        message: "Checksum mismatch for a:b:1\n  expected: x\n  actual:   y".into(),
+    }
+    .into();
+    assert_eq!(
+        kargo_util::errors::short_line(&report),
+        "error[resolution]: Dependency resolution failed: Checksum mismatch for a:b:1 \
+         expected: x actual: y"
+    );
+}
@@ -1,4 +1,6 @@
-use kargo_util::fs::{ensure_dir, find_ancestor_with};
+use kargo_util::fs::{
+    ensure_dir, extended_length_path, find_ancestor_with, long_path, normalize_path_for, PathFlavor,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -43,3 +45,87 @@ fn test_ensure_dir_idempotent() {
     ensure_dir(&dir).unwrap();
     assert!(dir.is_dir());
 }
+
+#[test]
+fn test_extended_length_path_drive_and_unc() {
+    assert_eq!(
+        extended_length_path(r"C:\Users\dev\.kargo\cache\a.jar").as_deref(),
+        Some(r"\\?\C:\Users\dev\.kargo\cache\a.jar")
+    );
+    assert_eq!(
+        extended_length_path("c:/work/app/./build/../src").as_deref(),
+        Some(r"\\?\c:\work\app\src")
+    );
+    assert_eq!(
+        extended_length_path(r"\\server\share\..\..\deps\lib.jar").as_deref(),
+        Some(r"\\?\UNC\server\share\deps\lib.jar")
+    );
+}
+
+#[test]
+fn test_extended_length_path_leaves_other_paths() {
+    assert_eq!(extended_length_path(r"\\?\C:\already"), None);
+    assert_eq!(extended_length_path(r"\\.\pipe\kargo"), None);
+    assert_eq!(extended_length_path(r"relative\path"), None);
+    assert_eq!(extended_length_path("/usr/lib/jvm"), None);
+}
+
+#[test]
+fn test_long_path_handles_deep_directories() {
+    let tmp = TempDir::new().unwrap();
+    let segments: Vec<String> = (0..30).map(|i| format!("directory-{i:02}")).collect();
+    let deep = tmp.path().join(segments.join("/"));
+    assert!(deep.as_os_str().len() > 260);
+    ensure_dir(&long_path(&deep)).unwrap();
+    std::fs::write(long_path(&deep.join("a.txt")), "x").unwrap();
+    assert!(long_path(&deep.join("a.txt")).is_file());
+}
+
+#[test]
+fn test_normalize_path_unix_is_case_sensitive() {
+    assert_eq!(
+        normalize_path_for("/home/dev/app/src/main/../main/./kotlin/", PathFlavor::Unix),
+        "/home/dev/app/src/main/kotlin"
+    );
+    assert_eq!(
+        normalize_path_for("Src/Main/A.kt", PathFlavor::Unix),
+        "Src/Main/A.kt"
+    );
+    assert_eq!(
+        normalize_path_for("../lib/./x", PathFlavor::Unix),
+        "../lib/x"
+    );
+    assert_eq!(normalize_path_for("/../x", PathFlavor::Unix), "/x");
+    assert_eq!(normalize_path_for("a/..", PathFlavor::Unix), ".");
+    // A backslash is part of a Unix file name.
+    assert_eq!(normalize_path_for(r"a\b", PathFlavor::Unix), r"a\b");
+}
+
+#[test]
+fn test_normalize_path_macos_ignores_case() {
+    assert_eq!(
+        normalize_path_for("/Users/Dev/App/Src/Main/Kotlin/Main.kt", PathFlavor::MacOs),
+        normalize_path_for("/users/dev/app/src/main/kotlin/Main.kt", PathFlavor::MacOs)
+    );
+}
+
+#[test]
+fn test_normalize_path_windows_forms() {
+    let expected = "c:/users/dev/app/src/main/kotlin";
+    for spelling in [
+        r"C:\Users\Dev\App\Src\Main\Kotlin",
+        "c:/users/dev/app/src/main/kotlin/",
+        r"\\?\C:\Users\Dev\App\src\test\..\main\kotlin",
+    ] {
+        assert_eq!(normalize_path_for(spelling, PathFlavor::Windows), expected);
+    }
+    assert_eq!(
+        normalize_path_for(r"\\?\UNC\Server\Share\deps\..\lib.jar", PathFlavor::Windows),
+        "//server/share/lib.jar"
+    );
+    assert_eq!(
+        normalize_path_for(r"\\server\share\lib.jar", PathFlavor::Windows),
+        "//server/share/lib.jar"
+    );
+    assert_eq!(normalize_path_for(r"C:\..\x", PathFlavor::Windows), "c:/x");
+}
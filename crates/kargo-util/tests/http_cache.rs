@@ -0,0 +1,52 @@
+use kargo_util::http_cache::HttpCache;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use tempfile::TempDir;
+
+const URL: &str = "https://repo.example.com/org/example/lib/maven-metadata.xml";
+
+fn headers(pairs: &[(reqwest::header::HeaderName, &str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in pairs {
+        map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+    }
+    map
+}
+
+#[test]
+fn test_store_and_lookup_round_trip() {
+    let tmp = TempDir::new().unwrap();
+    let cache = HttpCache::new(tmp.path().to_path_buf());
+    assert!(cache.lookup(URL).is_none());
+
+    let date = "Wed, 21 Oct 2026 07:28:00 GMT";
+    cache.store(
+        URL,
+        &headers(&[(ETAG, "\"abc\""), (LAST_MODIFIED, date)]),
+        b"<metadata/>",
+    );
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+    assert_eq!(cached.last_modified.as_deref(), Some(date));
+    assert_eq!(cached.body, b"<metadata/>");
+
+    let request = cached
+        .conditional(reqwest::Client::new().get(URL))
+        .build()
+        .unwrap();
+    assert_eq!(request.headers()[IF_NONE_MATCH], "\"abc\"");
+    assert_eq!(request.headers()[IF_MODIFIED_SINCE], date);
+
+    cache.remove(URL);
+    assert!(cache.lookup(URL).is_none());
+}
+
+#[test]
+fn test_responses_without_validators_are_not_stored() {
+    let tmp = TempDir::new().unwrap();
+    let cache = HttpCache::new(tmp.path().to_path_buf());
+    cache.store(URL, &HeaderMap::new(), b"<metadata/>");
+    assert!(cache.lookup(URL).is_none());
+    assert!(!tmp.path().read_dir().unwrap().any(|_| true));
+}
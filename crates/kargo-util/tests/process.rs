@@ -77,3 +77,51 @@ fn test_builder_chaining() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert_eq!(stdout.trim(), "a b");
 }
+
+#[cfg(unix)]
+#[test]
+fn test_builder_exec_streaming_reports_lines() {
+    use kargo_util::process::StreamKind;
+
+    let mut seen = Vec::new();
+    let output = CommandBuilder::new("sh")
+        .arg("-c")
+        .arg("echo out; echo err >&2; exit 3")
+        .exec_streaming(|kind, line| seen.push((kind, line.to_string())))
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "err\n");
+    assert!(seen.contains(&(StreamKind::Stdout, "out".to_string())));
+    assert!(seen.contains(&(StreamKind::Stderr, "err".to_string())));
+}
+
+#[test]
+fn test_response_file_quotes_each_argument() {
+    let args = vec![
+        "-cp".to_string(),
+        r"C:\Program Files\lib\a.jar;b.jar".to_string(),
+        "say \"hi\"".to_string(),
+        String::new(),
+    ];
+    assert_eq!(
+        kargo_util::process::response_file_contents(&args),
+        "\"-cp\"\n\"C:\\\\Program Files\\\\lib\\\\a.jar;b.jar\"\n\"say \\\"hi\\\"\"\n\"\"\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_response_file_replaces_arguments() {
+    let output = CommandBuilder::new("echo")
+        .args(["one", "two"])
+        .response_file(true)
+        .exec()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = stdout.trim().strip_prefix('@').unwrap();
+    assert!(path.contains("kargo-args-"));
+    // Removed once the command has run.
+    assert!(!std::path::Path::new(path).exists());
+}
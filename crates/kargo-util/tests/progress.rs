@@ -0,0 +1,30 @@
+use kargo_util::progress::{set_verbosity, verbosity, Verbosity};
+
+#[test]
+fn test_verbosity_from_flags() {
+    assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+    assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+    assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+    assert_eq!(Verbosity::from_flags(false, 3), Verbosity::VeryVerbose);
+}
+
+#[test]
+fn test_verbosity_tracing_filter() {
+    assert_eq!(Verbosity::Quiet.tracing_filter(), "error");
+    assert_eq!(Verbosity::Normal.tracing_filter(), "warn");
+    assert_eq!(Verbosity::Verbose.tracing_filter(), "info");
+    assert_eq!(Verbosity::VeryVerbose.tracing_filter(), "debug");
+}
+
+#[test]
+fn test_set_verbosity_round_trips() {
+    for level in [
+        Verbosity::Quiet,
+        Verbosity::Verbose,
+        Verbosity::VeryVerbose,
+        Verbosity::Normal,
+    ] {
+        set_verbosity(level);
+        assert_eq!(verbosity(), level);
+    }
+}
@@ -0,0 +1,30 @@
+use kargo_util::system::{fit_to_memory, parse_meminfo, parse_vm_stat};
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+#[test]
+fn test_fit_to_memory_caps_by_available_memory() {
+    assert_eq!(fit_to_memory(8, GB, Some(32 * GB)), 8);
+    assert_eq!(fit_to_memory(8, GB, Some(3 * GB + 1)), 3);
+    assert_eq!(fit_to_memory(8, GB, Some(GB / 2)), 1);
+    assert_eq!(fit_to_memory(8, GB, None), 8);
+    assert_eq!(fit_to_memory(0, GB, Some(32 * GB)), 1);
+}
+
+#[test]
+fn test_parse_meminfo() {
+    let text =
+        "MemTotal:       16318596 kB\nMemFree:         1203280 kB\nMemAvailable:    8159304 kB\n";
+    assert_eq!(parse_meminfo(text), Some(8159304 * 1024));
+    assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+}
+
+#[test]
+fn test_parse_vm_stat() {
+    let text = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+                Pages free:                               10000.\n\
+                Pages active:                            200000.\n\
+                Pages inactive:                           20000.\n\
+                Pages speculative:                         1000.\n";
+    assert_eq!(parse_vm_stat(text), Some(31000 * 16384));
+}
@@ -0,0 +1,10 @@
+use kargo_util::xml;
+
+#[test]
+fn test_escape_text_and_attributes() {
+    assert_eq!(
+        xml::escape(r#"a < b && "c" > d"#),
+        "a &lt; b &amp;&amp; &quot;c&quot; &gt; d"
+    );
+    assert_eq!(xml::escape("plain"), "plain");
+}